@@ -14,6 +14,14 @@ if %~dp0 == C:%homepath%\GitHub\erg\ (
     echo publish erg_compiler ...
     cargo publish
     rem timeout 12
+    cd ../erg_linter
+    echo publish erg_linter ...
+    cargo publish
+    rem timeout 12
+    cd ../erg_driver
+    echo publish erg_driver ...
+    cargo publish
+    rem timeout 12
     cd ../els
     echo publish els ...
     cargo publish
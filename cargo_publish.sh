@@ -4,6 +4,10 @@ cd ../erg_parser
 cargo publish
 cd ../erg_compiler
 cargo publish
+cd ../erg_linter
+cargo publish
+cd ../erg_driver
+cargo publish
 cd ../els
 cargo publish
 cd ../../
@@ -0,0 +1,275 @@
+use erg_common::traits::{DequeStream, Locational};
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::erg_parser::token::TokenKind::*;
+use erg_compiler::hir::{Def, Expr};
+use erg_compiler::stable_id::StableDefId;
+use erg_compiler::varinfo::{AbsLocation, VarInfo};
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    Location, Position, Range, SymbolKind, Url,
+};
+use serde_json::Value;
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+/// Returns the innermost definition that satisfies `pred`.
+fn find_def<'e, I: IntoIterator<Item = &'e Expr>>(
+    exprs: I,
+    pred: &impl Fn(&Def) -> bool,
+) -> Option<&'e Def> {
+    for expr in exprs {
+        let found = match expr {
+            Expr::Def(def) => {
+                find_def(def.body.block.iter(), pred).or_else(|| pred(def).then_some(def))
+            }
+            Expr::ClassDef(class_def) => find_def(class_def.methods.iter(), pred),
+            Expr::PatchDef(patch_def) => find_def(patch_def.methods.iter(), pred),
+            _ => None,
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+fn is_same_item(lhs: &CallHierarchyItem, rhs: &CallHierarchyItem) -> bool {
+    lhs.uri == rhs.uri && lhs.selection_range == rhs.selection_range
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_call_hierarchy_prepare(
+        &mut self,
+        params: CallHierarchyPrepareParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyItem>>> {
+        send_log(format!("call hierarchy prepare requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(&uri, &token)? else {
+            return Ok(None);
+        };
+        if !vi.t.is_subr() {
+            return Ok(None);
+        }
+        Ok(self
+            .call_hierarchy_item(token.inspect(), &vi)
+            .map(|item| vec![item]))
+    }
+
+    /// Calls through trait methods are also included: if the item implements a trait method,
+    /// calls of the trait method are regarded as calls of the item.
+    pub(crate) fn handle_call_hierarchy_incoming(
+        &mut self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyIncomingCall>>> {
+        send_log(format!(
+            "call hierarchy incoming calls requested: {params:?}"
+        ))?;
+        let Some(def_loc) = self.item_to_abs_loc(&params.item) else {
+            return Ok(None);
+        };
+        let mut referrers = self.get_refs_from_abs_loc(&def_loc);
+        referrers.extend(self.get_trait_method_calls(&def_loc, &params.item.name));
+        let mut calls = Vec::<CallHierarchyIncomingCall>::new();
+        for referrer in referrers {
+            let Some(caller) = self.caller_item(&referrer.uri, referrer.range.start) else {
+                continue;
+            };
+            if let Some(call) = calls
+                .iter_mut()
+                .find(|call| is_same_item(&call.from, &caller))
+            {
+                call.from_ranges.push(referrer.range);
+            } else {
+                calls.push(CallHierarchyIncomingCall {
+                    from: caller,
+                    from_ranges: vec![referrer.range],
+                });
+            }
+        }
+        Ok(Some(calls))
+    }
+
+    pub(crate) fn handle_call_hierarchy_outgoing(
+        &mut self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> ELSResult<Option<Vec<CallHierarchyOutgoingCall>>> {
+        send_log(format!(
+            "call hierarchy outgoing calls requested: {params:?}"
+        ))?;
+        let Some(index) = self.get_index() else {
+            return Ok(None);
+        };
+        let Ok(path) = params.item.uri.to_file_path() else {
+            return Ok(None);
+        };
+        let mut calls = vec![];
+        for (_, value) in index.members().iter() {
+            if !value.vi.t.is_subr() {
+                continue;
+            }
+            let from_ranges = value
+                .referrers
+                .iter()
+                .filter(|referrer| referrer.module.as_ref() == Some(&path))
+                .filter_map(|referrer| {
+                    let range = util::loc_to_range(referrer.loc)?;
+                    let uri = &params.item.uri;
+                    let caller = self.caller_item(uri, range.start)?;
+                    is_same_item(&caller, &params.item).then_some(range)
+                })
+                .collect::<Vec<_>>();
+            if from_ranges.is_empty() {
+                continue;
+            }
+            if let Some(callee) = self.call_hierarchy_item(&value.name, &value.vi) {
+                calls.push(CallHierarchyOutgoingCall {
+                    to: callee,
+                    from_ranges,
+                });
+            }
+        }
+        Ok(Some(calls))
+    }
+
+    fn call_hierarchy_item(&self, name: &str, vi: &VarInfo) -> Option<CallHierarchyItem> {
+        let lsp_loc = self.abs_loc_to_lsp_loc(&vi.def_loc)?;
+        let uri = NormalizedUrl::new(lsp_loc.uri.clone());
+        let range = self
+            .analysis_result
+            .get_hir(&uri)
+            .and_then(|hir| {
+                find_def(hir.module.iter(), &|def| {
+                    def.sig.ident().vi.def_loc == vi.def_loc
+                })
+                .and_then(|def| util::loc_to_range(def.loc()))
+            })
+            .unwrap_or(lsp_loc.range);
+        let kind = if vi.t.is_method() {
+            SymbolKind::METHOD
+        } else {
+            SymbolKind::FUNCTION
+        };
+        Some(CallHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: Some(vi.t.to_string()),
+            uri: lsp_loc.uri,
+            range,
+            selection_range: lsp_loc.range,
+            data: Some(Value::String(vi.stable_id(name).to_string())),
+        })
+    }
+
+    /// The innermost subroutine containing `pos`.
+    /// If `pos` is at the top level, the module itself is the caller.
+    fn caller_item(&self, uri: &Url, pos: Position) -> Option<CallHierarchyItem> {
+        let normalized = NormalizedUrl::new(uri.clone());
+        let hir = self.analysis_result.get_hir(&normalized)?;
+        let caller = find_def(hir.module.iter(), &|def| {
+            def.sig.is_subr() && util::pos_in_loc(def, pos)
+        });
+        if let Some(def) = caller {
+            let ident = def.sig.ident();
+            return self.call_hierarchy_item(ident.inspect(), &ident.vi);
+        }
+        let path = util::uri_to_path(&normalized);
+        let name = path.file_stem()?.to_string_lossy().to_string();
+        let end = Position::new(
+            self.file_cache
+                .get_entire_code(&normalized)
+                .ok()?
+                .lines()
+                .count() as u32,
+            0,
+        );
+        Some(CallHierarchyItem {
+            name,
+            kind: SymbolKind::FILE,
+            tags: None,
+            detail: None,
+            uri: uri.clone(),
+            range: Range::new(Position::new(0, 0), end),
+            selection_range: Range::default(),
+            data: None,
+        })
+    }
+
+    fn item_to_abs_loc(&self, item: &CallHierarchyItem) -> Option<AbsLocation> {
+        let path = item.uri.to_file_path().ok()?;
+        let index = self.get_index()?;
+        // the item may be created before the module is edited
+        if let Some(def_loc) = item
+            .data
+            .as_ref()
+            .and_then(|data| data.as_str()?.parse::<StableDefId>().ok())
+            .and_then(|id| index.get_def_loc(&id))
+        {
+            return Some(def_loc);
+        }
+        let members = index.members();
+        members
+            .keys()
+            .find(|def_loc| {
+                def_loc.module.as_ref() == Some(&path)
+                    && util::loc_to_range(def_loc.loc) == Some(item.selection_range)
+            })
+            .cloned()
+    }
+
+    /// `T = Trait { .f = ... }; C|C <: T|.f = ...; h x: T = x.f()` => `.f` in `h` (if `def_loc` is of `C.f`)
+    ///
+    /// Trait method declarations are not registered in the index, so method call tokens are checked one by one.
+    fn get_trait_method_calls(&self, def_loc: &AbsLocation, name: &str) -> Vec<Location> {
+        let Some(impl_of) = self
+            .get_index()
+            .and_then(|index| index.get_refs(def_loc))
+            .and_then(|value| value.vi.impl_of.clone())
+        else {
+            return vec![];
+        };
+        let trait_name = impl_of.local_name();
+        let mut calls = vec![];
+        for uri in self.analysis_result.entries() {
+            let (Some(tokens), Some(visitor)) = (
+                self.file_cache.get_token_stream(&uri),
+                self.get_visitor(&uri),
+            ) else {
+                continue;
+            };
+            let tokens = tokens.iter().collect::<Vec<_>>();
+            for window in tokens.windows(3) {
+                let [receiver, dot, token] = window else {
+                    continue;
+                };
+                // `{ .f = ... }` is a declaration
+                let is_attr = !matches!(
+                    receiver.kind,
+                    LBrace | Comma | Semi | Newline | Indent | Dedent
+                );
+                if !is_attr || !dot.is(Dot) || &token.inspect()[..] != name {
+                    continue;
+                }
+                let Some(vi) = visitor.get_info(token) else {
+                    continue;
+                };
+                let is_trait_method =
+                    vi.t.self_t()
+                        .is_some_and(|self_t| self_t.local_name() == trait_name);
+                if let (true, Some(range)) = (is_trait_method, util::loc_to_range(token.loc())) {
+                    calls.push(Location::new(uri.clone().raw(), range));
+                }
+            }
+        }
+        calls
+    }
+}
@@ -4,33 +4,60 @@ use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::parse::Parsable;
 
 use lsp_types::request::{
-    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion, ExecuteCommand,
-    GotoDefinition, HoverRequest, InlayHintRequest, References, ResolveCompletionItem,
-    SemanticTokensFullRequest, SignatureHelpRequest, WillRenameFiles,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, CodeLensResolve, Completion,
+    DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest,
+    Formatting, GotoDefinition, GotoImplementation, GotoImplementationParams, HoverRequest,
+    InlayHintRequest, RangeFormatting, References, ResolveCompletionItem, SelectionRangeRequest,
+    SemanticTokensFullRequest, SignatureHelpRequest, WillRenameFiles, WorkspaceSymbol,
 };
 use lsp_types::{
-    CodeAction, CodeActionParams, CodeLensParams, CompletionItem, CompletionParams,
-    ExecuteCommandParams, GotoDefinitionParams, HoverParams, InlayHintParams, ReferenceParams,
-    RenameFilesParams, SemanticTokensParams, SignatureHelpParams,
+    CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CodeAction, CodeActionParams, CodeLens, CodeLensParams, CompletionItem, CompletionParams,
+    DocumentFormattingParams, DocumentHighlightParams, DocumentRangeFormattingParams,
+    DocumentSymbolParams, ExecuteCommandParams, FoldingRangeParams, GotoDefinitionParams,
+    HoverParams, InlayHintParams, ReferenceParams, RenameFilesParams, SelectionRangeParams,
+    SemanticTokensParams, SignatureHelpParams, WorkspaceSymbolParams,
 };
 
+use crate::pull_diagnostics::{DocumentDiagnosticParams, DocumentDiagnosticRequest};
 use crate::server::Server;
+use crate::type_hierarchy::{
+    TypeHierarchyPrepare, TypeHierarchyPrepareParams, TypeHierarchySubtypes,
+    TypeHierarchySubtypesParams, TypeHierarchySupertypes, TypeHierarchySupertypesParams,
+};
 
 #[derive(Debug, Clone)]
 pub struct SendChannels {
     completion: mpsc::Sender<(i64, CompletionParams)>,
     resolve_completion: mpsc::Sender<(i64, CompletionItem)>,
     goto_definition: mpsc::Sender<(i64, GotoDefinitionParams)>,
+    goto_implementation: mpsc::Sender<(i64, GotoImplementationParams)>,
     semantic_tokens_full: mpsc::Sender<(i64, SemanticTokensParams)>,
     inlay_hint: mpsc::Sender<(i64, InlayHintParams)>,
     hover: mpsc::Sender<(i64, HoverParams)>,
     references: mpsc::Sender<(i64, ReferenceParams)>,
+    document_highlight: mpsc::Sender<(i64, DocumentHighlightParams)>,
     code_lens: mpsc::Sender<(i64, CodeLensParams)>,
+    code_lens_resolve: mpsc::Sender<(i64, CodeLens)>,
     code_action: mpsc::Sender<(i64, CodeActionParams)>,
     code_action_resolve: mpsc::Sender<(i64, CodeAction)>,
     signature_help: mpsc::Sender<(i64, SignatureHelpParams)>,
     will_rename_files: mpsc::Sender<(i64, RenameFilesParams)>,
     execute_command: mpsc::Sender<(i64, ExecuteCommandParams)>,
+    document_diagnostic: mpsc::Sender<(i64, DocumentDiagnosticParams)>,
+    document_symbol: mpsc::Sender<(i64, DocumentSymbolParams)>,
+    workspace_symbol: mpsc::Sender<(i64, WorkspaceSymbolParams)>,
+    formatting: mpsc::Sender<(i64, DocumentFormattingParams)>,
+    range_formatting: mpsc::Sender<(i64, DocumentRangeFormattingParams)>,
+    folding_range: mpsc::Sender<(i64, FoldingRangeParams)>,
+    call_hierarchy_prepare: mpsc::Sender<(i64, CallHierarchyPrepareParams)>,
+    call_hierarchy_incoming: mpsc::Sender<(i64, CallHierarchyIncomingCallsParams)>,
+    call_hierarchy_outgoing: mpsc::Sender<(i64, CallHierarchyOutgoingCallsParams)>,
+    type_hierarchy_prepare: mpsc::Sender<(i64, TypeHierarchyPrepareParams)>,
+    type_hierarchy_supertypes: mpsc::Sender<(i64, TypeHierarchySupertypesParams)>,
+    type_hierarchy_subtypes: mpsc::Sender<(i64, TypeHierarchySubtypesParams)>,
+    selection_range: mpsc::Sender<(i64, SelectionRangeParams)>,
 }
 
 impl SendChannels {
@@ -38,46 +65,94 @@ impl SendChannels {
         let (tx_completion, rx_completion) = mpsc::channel();
         let (tx_resolve_completion, rx_resolve_completion) = mpsc::channel();
         let (tx_goto_definition, rx_goto_definition) = mpsc::channel();
+        let (tx_goto_implementation, rx_goto_implementation) = mpsc::channel();
         let (tx_semantic_tokens_full, rx_semantic_tokens_full) = mpsc::channel();
         let (tx_inlay_hint, rx_inlay_hint) = mpsc::channel();
         let (tx_hover, rx_hover) = mpsc::channel();
         let (tx_references, rx_references) = mpsc::channel();
+        let (tx_document_highlight, rx_document_highlight) = mpsc::channel();
         let (tx_code_lens, rx_code_lens) = mpsc::channel();
+        let (tx_code_lens_resolve, rx_code_lens_resolve) = mpsc::channel();
         let (tx_code_action, rx_code_action) = mpsc::channel();
         let (tx_code_action_resolve, rx_code_action_resolve) = mpsc::channel();
         let (tx_sig_help, rx_sig_help) = mpsc::channel();
         let (tx_will_rename_files, rx_will_rename_files) = mpsc::channel();
         let (tx_execute_command, rx_execute_command) = mpsc::channel();
+        let (tx_document_diagnostic, rx_document_diagnostic) = mpsc::channel();
+        let (tx_document_symbol, rx_document_symbol) = mpsc::channel();
+        let (tx_workspace_symbol, rx_workspace_symbol) = mpsc::channel();
+        let (tx_formatting, rx_formatting) = mpsc::channel();
+        let (tx_range_formatting, rx_range_formatting) = mpsc::channel();
+        let (tx_folding_range, rx_folding_range) = mpsc::channel();
+        let (tx_call_hierarchy_prepare, rx_call_hierarchy_prepare) = mpsc::channel();
+        let (tx_call_hierarchy_incoming, rx_call_hierarchy_incoming) = mpsc::channel();
+        let (tx_call_hierarchy_outgoing, rx_call_hierarchy_outgoing) = mpsc::channel();
+        let (tx_type_hierarchy_prepare, rx_type_hierarchy_prepare) = mpsc::channel();
+        let (tx_type_hierarchy_supertypes, rx_type_hierarchy_supertypes) = mpsc::channel();
+        let (tx_type_hierarchy_subtypes, rx_type_hierarchy_subtypes) = mpsc::channel();
+        let (tx_selection_range, rx_selection_range) = mpsc::channel();
         (
             Self {
                 completion: tx_completion,
                 resolve_completion: tx_resolve_completion,
                 goto_definition: tx_goto_definition,
+                goto_implementation: tx_goto_implementation,
                 semantic_tokens_full: tx_semantic_tokens_full,
                 inlay_hint: tx_inlay_hint,
                 hover: tx_hover,
                 references: tx_references,
+                document_highlight: tx_document_highlight,
                 code_lens: tx_code_lens,
+                code_lens_resolve: tx_code_lens_resolve,
                 code_action: tx_code_action,
                 code_action_resolve: tx_code_action_resolve,
                 signature_help: tx_sig_help,
                 will_rename_files: tx_will_rename_files,
                 execute_command: tx_execute_command,
+                document_diagnostic: tx_document_diagnostic,
+                document_symbol: tx_document_symbol,
+                workspace_symbol: tx_workspace_symbol,
+                formatting: tx_formatting,
+                range_formatting: tx_range_formatting,
+                folding_range: tx_folding_range,
+                call_hierarchy_prepare: tx_call_hierarchy_prepare,
+                call_hierarchy_incoming: tx_call_hierarchy_incoming,
+                call_hierarchy_outgoing: tx_call_hierarchy_outgoing,
+                type_hierarchy_prepare: tx_type_hierarchy_prepare,
+                type_hierarchy_supertypes: tx_type_hierarchy_supertypes,
+                type_hierarchy_subtypes: tx_type_hierarchy_subtypes,
+                selection_range: tx_selection_range,
             },
             ReceiveChannels {
                 completion: rx_completion,
                 resolve_completion: rx_resolve_completion,
                 goto_definition: rx_goto_definition,
+                goto_implementation: rx_goto_implementation,
                 semantic_tokens_full: rx_semantic_tokens_full,
                 inlay_hint: rx_inlay_hint,
                 hover: rx_hover,
                 references: rx_references,
+                document_highlight: rx_document_highlight,
                 code_lens: rx_code_lens,
+                code_lens_resolve: rx_code_lens_resolve,
                 code_action: rx_code_action,
                 code_action_resolve: rx_code_action_resolve,
                 signature_help: rx_sig_help,
                 will_rename_files: rx_will_rename_files,
                 execute_command: rx_execute_command,
+                document_diagnostic: rx_document_diagnostic,
+                document_symbol: rx_document_symbol,
+                workspace_symbol: rx_workspace_symbol,
+                formatting: rx_formatting,
+                range_formatting: rx_range_formatting,
+                folding_range: rx_folding_range,
+                call_hierarchy_prepare: rx_call_hierarchy_prepare,
+                call_hierarchy_incoming: rx_call_hierarchy_incoming,
+                call_hierarchy_outgoing: rx_call_hierarchy_outgoing,
+                type_hierarchy_prepare: rx_type_hierarchy_prepare,
+                type_hierarchy_supertypes: rx_type_hierarchy_supertypes,
+                type_hierarchy_subtypes: rx_type_hierarchy_subtypes,
+                selection_range: rx_selection_range,
             },
         )
     }
@@ -88,16 +163,32 @@ pub struct ReceiveChannels {
     pub(crate) completion: mpsc::Receiver<(i64, CompletionParams)>,
     pub(crate) resolve_completion: mpsc::Receiver<(i64, CompletionItem)>,
     pub(crate) goto_definition: mpsc::Receiver<(i64, GotoDefinitionParams)>,
+    pub(crate) goto_implementation: mpsc::Receiver<(i64, GotoImplementationParams)>,
     pub(crate) semantic_tokens_full: mpsc::Receiver<(i64, SemanticTokensParams)>,
     pub(crate) inlay_hint: mpsc::Receiver<(i64, InlayHintParams)>,
     pub(crate) hover: mpsc::Receiver<(i64, HoverParams)>,
     pub(crate) references: mpsc::Receiver<(i64, ReferenceParams)>,
+    pub(crate) document_highlight: mpsc::Receiver<(i64, DocumentHighlightParams)>,
     pub(crate) code_lens: mpsc::Receiver<(i64, CodeLensParams)>,
+    pub(crate) code_lens_resolve: mpsc::Receiver<(i64, CodeLens)>,
     pub(crate) code_action: mpsc::Receiver<(i64, CodeActionParams)>,
     pub(crate) code_action_resolve: mpsc::Receiver<(i64, CodeAction)>,
     pub(crate) signature_help: mpsc::Receiver<(i64, SignatureHelpParams)>,
     pub(crate) will_rename_files: mpsc::Receiver<(i64, RenameFilesParams)>,
     pub(crate) execute_command: mpsc::Receiver<(i64, ExecuteCommandParams)>,
+    pub(crate) document_diagnostic: mpsc::Receiver<(i64, DocumentDiagnosticParams)>,
+    pub(crate) document_symbol: mpsc::Receiver<(i64, DocumentSymbolParams)>,
+    pub(crate) workspace_symbol: mpsc::Receiver<(i64, WorkspaceSymbolParams)>,
+    pub(crate) formatting: mpsc::Receiver<(i64, DocumentFormattingParams)>,
+    pub(crate) range_formatting: mpsc::Receiver<(i64, DocumentRangeFormattingParams)>,
+    pub(crate) folding_range: mpsc::Receiver<(i64, FoldingRangeParams)>,
+    pub(crate) call_hierarchy_prepare: mpsc::Receiver<(i64, CallHierarchyPrepareParams)>,
+    pub(crate) call_hierarchy_incoming: mpsc::Receiver<(i64, CallHierarchyIncomingCallsParams)>,
+    pub(crate) call_hierarchy_outgoing: mpsc::Receiver<(i64, CallHierarchyOutgoingCallsParams)>,
+    pub(crate) type_hierarchy_prepare: mpsc::Receiver<(i64, TypeHierarchyPrepareParams)>,
+    pub(crate) type_hierarchy_supertypes: mpsc::Receiver<(i64, TypeHierarchySupertypesParams)>,
+    pub(crate) type_hierarchy_subtypes: mpsc::Receiver<(i64, TypeHierarchySubtypesParams)>,
+    pub(crate) selection_range: mpsc::Receiver<(i64, SelectionRangeParams)>,
 }
 
 pub trait Sendable<R: lsp_types::request::Request + 'static> {
@@ -124,6 +215,11 @@ macro_rules! impl_sendable {
 impl_sendable!(Completion, CompletionParams, completion);
 impl_sendable!(ResolveCompletionItem, CompletionItem, resolve_completion);
 impl_sendable!(GotoDefinition, GotoDefinitionParams, goto_definition);
+impl_sendable!(
+    GotoImplementation,
+    GotoImplementationParams,
+    goto_implementation
+);
 impl_sendable!(
     SemanticTokensFullRequest,
     SemanticTokensParams,
@@ -132,9 +228,60 @@ impl_sendable!(
 impl_sendable!(InlayHintRequest, InlayHintParams, inlay_hint);
 impl_sendable!(HoverRequest, HoverParams, hover);
 impl_sendable!(References, ReferenceParams, references);
+impl_sendable!(
+    DocumentHighlightRequest,
+    DocumentHighlightParams,
+    document_highlight
+);
 impl_sendable!(CodeLensRequest, CodeLensParams, code_lens);
 impl_sendable!(CodeActionRequest, CodeActionParams, code_action);
 impl_sendable!(CodeActionResolveRequest, CodeAction, code_action_resolve);
 impl_sendable!(SignatureHelpRequest, SignatureHelpParams, signature_help);
 impl_sendable!(WillRenameFiles, RenameFilesParams, will_rename_files);
 impl_sendable!(ExecuteCommand, ExecuteCommandParams, execute_command);
+impl_sendable!(DocumentSymbolRequest, DocumentSymbolParams, document_symbol);
+impl_sendable!(WorkspaceSymbol, WorkspaceSymbolParams, workspace_symbol);
+impl_sendable!(Formatting, DocumentFormattingParams, formatting);
+impl_sendable!(
+    RangeFormatting,
+    DocumentRangeFormattingParams,
+    range_formatting
+);
+impl_sendable!(FoldingRangeRequest, FoldingRangeParams, folding_range);
+impl_sendable!(
+    CallHierarchyPrepare,
+    CallHierarchyPrepareParams,
+    call_hierarchy_prepare
+);
+impl_sendable!(
+    CallHierarchyIncomingCalls,
+    CallHierarchyIncomingCallsParams,
+    call_hierarchy_incoming
+);
+impl_sendable!(
+    CallHierarchyOutgoingCalls,
+    CallHierarchyOutgoingCallsParams,
+    call_hierarchy_outgoing
+);
+impl_sendable!(
+    TypeHierarchyPrepare,
+    TypeHierarchyPrepareParams,
+    type_hierarchy_prepare
+);
+impl_sendable!(
+    TypeHierarchySupertypes,
+    TypeHierarchySupertypesParams,
+    type_hierarchy_supertypes
+);
+impl_sendable!(
+    TypeHierarchySubtypes,
+    TypeHierarchySubtypesParams,
+    type_hierarchy_subtypes
+);
+impl_sendable!(SelectionRangeRequest, SelectionRangeParams, selection_range);
+impl_sendable!(CodeLensResolve, CodeLens, code_lens_resolve);
+impl_sendable!(
+    DocumentDiagnosticRequest,
+    DocumentDiagnosticParams,
+    document_diagnostic
+);
@@ -2,21 +2,111 @@ use std::collections::HashMap;
 
 use erg_common::consts::{ERG_MODE, PYTHON_MODE};
 use erg_common::deepen_indent;
-use erg_common::traits::{Locational, Stream};
+use erg_common::traits::{DequeStream, Locational, Stream};
 use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::context::Context;
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::erg_parser::token::{Token, TokenKind};
-use erg_compiler::hir::Expr;
-use erg_compiler::ty::HasType;
+use erg_compiler::hir::{Def, Expr, NonDefaultParamSignature, Signature};
+use erg_compiler::ty::{HasType, Type};
 
 use lsp_types::{
-    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
-    Position, Range, TextEdit, Url, WorkspaceEdit,
+    CodeAction, CodeActionDisabled, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionResponse, Diagnostic, Position, Range, TextEdit, Url, WorkspaceEdit,
 };
 
 use crate::server::{send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
+/// Returns `None` if the type cannot be written in the source (e.g. it has type variables).
+fn type_to_annotation(ctx: &Context, t: &Type) -> Option<String> {
+    if t.has_qvar() || t.has_unbound_var() {
+        return None;
+    }
+    // `{1}` -> `Nat`
+    let t = ctx.readable_type(t.derefine()).to_string();
+    Some(t.replace("<module>::", ""))
+}
+
+/// Collects the outermost expressions contained in `range`.
+fn exprs_in_range<'e>(expr: &'e Expr, range: Range, found: &mut Vec<&'e Expr>) {
+    let Some(expr_range) = util::loc_to_range(expr.loc()) else {
+        return;
+    };
+    if range.start <= expr_range.start && expr_range.end <= range.end {
+        found.push(expr);
+        return;
+    }
+    if expr_range.end <= range.start || range.end <= expr_range.start {
+        return;
+    }
+    let children: Vec<&Expr> = match expr {
+        Expr::Def(def) => def.body.block.iter().collect(),
+        Expr::ClassDef(class_def) => class_def.methods.iter().collect(),
+        Expr::PatchDef(patch_def) => patch_def.methods.iter().collect(),
+        Expr::Lambda(lambda) => lambda.body.iter().collect(),
+        Expr::Call(call) => {
+            let args = call.args.pos_args.iter().map(|arg| &arg.expr);
+            let kw_args = call.args.kw_args.iter().map(|arg| &arg.expr);
+            [call.obj.as_ref()]
+                .into_iter()
+                .chain(args)
+                .chain(kw_args)
+                .collect()
+        }
+        Expr::BinOp(bin) => vec![&bin.lhs, &bin.rhs],
+        Expr::UnaryOp(unary) => vec![&unary.expr],
+        Expr::TypeAsc(tasc) => vec![&tasc.expr],
+        Expr::Compound(block) | Expr::Code(block) => block.iter().collect(),
+        Expr::Dummy(dummy) => dummy.iter().collect(),
+        _ => vec![],
+    };
+    for child in children {
+        exprs_in_range(child, range, found);
+    }
+}
+
+/// The interface of the code to be extracted into a function.
+#[derive(Default)]
+struct Extraction {
+    /// local variables defined outside the selection: `(name, type)`
+    params: Vec<(String, Option<String>)>,
+    /// variables defined in the selection and used after it
+    outputs: Vec<(String, Option<String>)>,
+    /// the type of the last expression of the selection
+    last_t: Option<String>,
+    is_procedural: bool,
+    /// the top-level chunk containing the selection (the function is inserted before it)
+    chunk: Option<Range>,
+}
+
+impl Extraction {
+    fn return_t(&self) -> Option<String> {
+        match &self.outputs[..] {
+            [] => self.last_t.clone(),
+            [(_, t)] => t.clone(),
+            outputs => {
+                let ts = outputs
+                    .iter()
+                    .map(|(_, t)| t.clone())
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", ts.join(", ")))
+            }
+        }
+    }
+
+    fn outputs(&self) -> Option<String> {
+        match &self.outputs[..] {
+            [] => None,
+            [(name, _)] => Some(name.clone()),
+            outputs => {
+                let names = outputs.iter().map(|(name, _)| &name[..]);
+                Some(format!("({})", names.collect::<Vec<_>>().join(", ")))
+            }
+        }
+    }
+}
+
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     fn gen_eliminate_unused_vars_action(
         &self,
@@ -138,6 +228,152 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Some(action)
     }
 
+    /// `f x = x + 1` -> `f(x: Int): Int = x + 1`
+    fn gen_add_type_annotation_action(
+        &self,
+        uri: &NormalizedUrl,
+        pos: Position,
+        kind: CodeActionKind,
+        diags: Vec<Diagnostic>,
+    ) -> Option<CodeAction> {
+        let visitor = self.get_visitor(uri)?;
+        let token = self.file_cache.get_token(uri, pos)?;
+        let Expr::Def(def) = visitor.get_min_expr(&token)? else {
+            return None;
+        };
+        if def.sig.ident().inspect() != &token.content || def.sig.inspect().starts_with('%') {
+            return None;
+        }
+        let edits = self.type_annotation_edits(uri, def)?;
+        if edits.is_empty() {
+            return None;
+        }
+        let mut changes = HashMap::new();
+        changes.insert(uri.clone().raw(), edits);
+        let action = CodeAction {
+            title: "Add type annotation".to_string(),
+            kind: Some(kind),
+            diagnostics: Some(diags),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..Default::default()
+        };
+        Some(action)
+    }
+
+    /// Returns `None` if the inferred types cannot be written in the source (e.g. generic subroutines).
+    fn type_annotation_edits(&self, uri: &NormalizedUrl, def: &Def) -> Option<Vec<TextEdit>> {
+        let pos = util::loc_to_pos(def.loc())?;
+        let ctx = self.get_local_ctx(uri, pos).into_iter().next()?;
+        let show = |t: &Type| type_to_annotation(ctx, t);
+        let insert = |loc: &dyn Locational, text: String| {
+            let pos = util::loc_to_range(loc.loc())?.end;
+            Some(TextEdit::new(Range::new(pos, pos), text))
+        };
+        let mut edits = vec![];
+        match &def.sig {
+            Signature::Var(var) => {
+                if var.t_spec.is_none() {
+                    let t = show(var.ident.ref_t())?;
+                    edits.push(insert(&var.ident, format!(": {t}"))?);
+                }
+            }
+            Signature::Subr(subr) => {
+                if subr.ref_t().is_quantified_subr() {
+                    return None;
+                }
+                let params = &subr.params;
+                let mut param_edit = |param: &NonDefaultParamSignature| {
+                    if param.raw.t_spec.is_some() || param.inspect().map(|n| &n[..]) == Some("self")
+                    {
+                        return Some(());
+                    }
+                    let t = show(&param.vi.t)?;
+                    edits.push(insert(param, format!(": {t}"))?);
+                    Some(())
+                };
+                for nd_param in params.non_defaults.iter() {
+                    param_edit(nd_param)?;
+                }
+                if let Some(var_params) = &params.var_params {
+                    param_edit(var_params)?;
+                }
+                for d_param in params.defaults.iter() {
+                    param_edit(&d_param.sig)?;
+                }
+                if params.parens.is_none() {
+                    let start = util::loc_to_range(params.loc())?.start;
+                    edits.push(TextEdit::new(Range::new(start, start), "(".to_string()));
+                }
+                let close = if params.parens.is_none() { ")" } else { "" };
+                if subr.return_t_spec.is_none() {
+                    let return_t = show(subr.ref_t().return_t().as_deref()?)?;
+                    edits.push(insert(params, format!("{close}: {return_t}"))?);
+                } else if !close.is_empty() {
+                    edits.push(insert(params, close.to_string())?);
+                }
+            }
+        }
+        Some(edits)
+    }
+
+    /// `foo` is not defined -> `{foo;} = import "bar"`
+    fn gen_import_actions(&self, uri: &NormalizedUrl, diag: &Diagnostic) -> Vec<CodeAction> {
+        let Ok(Some(name)) = self.file_cache.get_ranged(uri, diag.range) else {
+            return vec![];
+        };
+        let importer = util::uri_to_path(uri);
+        let mut imports = vec![];
+        for module in self.modules.values() {
+            for (var, vi) in module.context.local_dir() {
+                if var.inspect() != &name[..] || vi.vis.is_private() {
+                    continue;
+                }
+                let Some(path) = vi.def_loc.module.as_ref().filter(|path| **path != importer)
+                else {
+                    continue;
+                };
+                let Some((path, is_decl)) = util::import_path(&importer, path) else {
+                    continue;
+                };
+                let import = if PYTHON_MODE {
+                    format!("from {} import {name}\n", path.replace('/', "."))
+                } else if is_decl {
+                    format!("{{{name};}} = pyimport \"{path}\"\n")
+                } else {
+                    format!("{{{name};}} = import \"{path}\"\n")
+                };
+                imports.push(import);
+            }
+        }
+        if let Some(comps) = self.comp_cache.get("<module>") {
+            for comp in comps.iter() {
+                if comp.insert_text.as_ref() != Some(&name) {
+                    continue;
+                }
+                let edits = comp.additional_text_edits.iter().flatten();
+                imports.extend(edits.map(|edit| edit.new_text.clone()));
+            }
+        }
+        imports.sort();
+        imports.dedup();
+        imports
+            .into_iter()
+            .map(|import| {
+                let start = Position::new(0, 0);
+                let edit = TextEdit::new(Range::new(start, start), import.clone());
+                let mut changes = HashMap::new();
+                changes.insert(uri.clone().raw(), vec![edit]);
+                CodeAction {
+                    title: format!("Add `{}`", import.trim_end()),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diag.clone()]),
+                    edit: Some(WorkspaceEdit::new(changes)),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
     fn gen_extract_action(&self, params: &CodeActionParams) -> Vec<CodeAction> {
         let mut actions = vec![];
         if params.range.start.line == params.range.end.line {
@@ -164,37 +400,35 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let uri = NormalizedUrl::new(params.text_document.uri.clone());
         let visitor = self.get_visitor(&uri)?;
         let token = self.file_cache.get_token(&uri, params.range.start)?;
-        match visitor.get_min_expr(&token)? {
-            Expr::Def(def) => {
-                let title = if def.sig.is_subr() {
-                    "Inline function"
-                } else {
-                    "Inline variable"
-                };
-                let action = CodeAction {
-                    title: title.to_string(),
-                    kind: Some(CodeActionKind::REFACTOR_INLINE),
-                    data: Some(serde_json::to_value(params.clone()).unwrap()),
-                    ..Default::default()
-                };
-                Some(action)
-            }
-            Expr::Accessor(acc) => {
-                let title = if acc.ref_t().is_subr() {
-                    "Inline function"
-                } else {
-                    "Inline variable"
-                };
-                let action = CodeAction {
-                    title: title.to_string(),
-                    kind: Some(CodeActionKind::REFACTOR_INLINE),
-                    data: Some(serde_json::to_value(params.clone()).unwrap()),
-                    ..Default::default()
-                };
-                Some(action)
-            }
-            _ => None,
-        }
+        let is_subr = match visitor.get_min_expr(&token)? {
+            Expr::Def(def) => def.sig.is_subr(),
+            Expr::Accessor(acc) => acc.ref_t().is_subr(),
+            _ => return None,
+        };
+        let title = if is_subr {
+            "Inline function"
+        } else {
+            "Inline variable"
+        };
+        let disabled = if is_subr {
+            None
+        } else {
+            let (def_uri, def_token) = self.get_inline_target(&uri, params.range.start)?;
+            let visitor = self.get_visitor(&def_uri)?;
+            let Some(Expr::Def(def)) = visitor.get_min_expr(&def_token) else {
+                return None;
+            };
+            self.check_inlinable(def)
+                .err()
+                .map(|reason| CodeActionDisabled { reason })
+        };
+        Some(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::REFACTOR_INLINE),
+            disabled,
+            data: Some(serde_json::to_value(params.clone()).unwrap()),
+            ..Default::default()
+        })
     }
 
     fn send_normal_action(&self, params: &CodeActionParams) -> ELSResult<Vec<CodeAction>> {
@@ -206,7 +440,19 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 actions.extend(action);
             }
         }
-        actions.extend(self.send_quick_fix(params)?);
+        let quick_fixes = self.send_quick_fix(params)?;
+        if !quick_fixes
+            .iter()
+            .any(|action| action.title == "Add type annotation")
+        {
+            actions.extend(self.gen_add_type_annotation_action(
+                &uri,
+                params.range.start,
+                CodeActionKind::REFACTOR_REWRITE,
+                vec![],
+            ));
+        }
+        actions.extend(quick_fixes);
         actions.extend(self.gen_extract_action(params));
         actions.extend(self.gen_inline_action(params));
         Ok(actions)
@@ -225,6 +471,27 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             let actions = self.gen_eliminate_unused_vars_action(params)?;
             result.extend(actions);
         }
+        let uri = NormalizedUrl::new(params.text_document.uri.clone());
+        for diag in diags.iter().filter(|diag| {
+            diag.message
+                .lines()
+                .next()
+                .is_some_and(|msg| msg.ends_with("is not defined"))
+        }) {
+            result.extend(self.gen_import_actions(&uri, diag));
+        }
+        for diag in diags
+            .iter()
+            .filter(|diag| diag.message.contains("is not single"))
+        {
+            let action = self.gen_add_type_annotation_action(
+                &uri,
+                diag.range.start,
+                CodeActionKind::QUICKFIX,
+                vec![diag.clone()],
+            );
+            result.extend(action);
+        }
         Ok(result)
     }
 
@@ -284,7 +551,6 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let code = self.file_cache.get_ranged(&uri, range)?.unwrap_or_default();
         // `    |foo|` (|...| is the selected range) -> `|    foo|`
         let diff = indented_code.trim_end_matches(&code);
-        let diff_indent_len = diff.chars().take_while(|c| *c == ' ').count();
         let diff_is_indent = diff.trim().is_empty();
         let code = if diff_is_indent {
             range.start.character = 0;
@@ -292,51 +558,217 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         } else {
             code
         };
-        let body = if extract_function {
-            let mut code = deepen_indent(code);
-            let should_insert_indent = code.lines().count() == 1 && !diff_is_indent;
-            if should_insert_indent {
-                code = format!("{}{code}", " ".repeat(diff_indent_len));
-            }
-            // add `return` to the last line
-            if PYTHON_MODE {
-                let mut lines = code.lines().collect::<Vec<_>>();
-                let mut last_line = lines.last().unwrap().to_string();
-                let code_start = last_line.chars().position(|c| c != ' ').unwrap();
-                last_line.insert_str(code_start, "return ");
-                lines.pop();
-                lines.push(&last_line);
-                lines.join("\n")
+        if extract_function {
+            let extraction = self.analyze_extraction(&uri, params.range);
+            return Ok(self.extract_function(action, uri, range, code, indent_len, extraction));
+        }
+        let body = code.trim_start().to_string();
+        let expanded = if range.start.character == 0 {
+            format!("{}new_var", " ".repeat(indent_len))
+        } else {
+            "new_var".to_string()
+        };
+        let extracted = format!("{}new_var = {body}\n\n", " ".repeat(indent_len));
+        let edit1 = TextEdit::new(Range::new(start, start), extracted);
+        let edit2 = TextEdit::new(Range::new(range.start, range.end), expanded);
+        let mut changes = HashMap::new();
+        changes.insert(uri.raw(), vec![edit1, edit2]);
+        action.edit = Some(WorkspaceEdit::new(changes));
+        Ok(action)
+    }
+
+    /// ```erg
+    /// f x =
+    ///     y = x + 1
+    ///     z = y * 2
+    ///     z + y
+    /// ```
+    /// `z = y * 2` ->
+    /// ```erg
+    /// new_func(y: Int): Int =
+    ///     z = y * 2
+    ///     z
+    ///
+    /// f x =
+    ///     y = x + 1
+    ///     z = new_func(y)
+    ///     z + y
+    /// ```
+    fn extract_function(
+        &self,
+        mut action: CodeAction,
+        uri: NormalizedUrl,
+        range: Range,
+        code: String,
+        indent_len: usize,
+        extraction: Extraction,
+    ) -> CodeAction {
+        // if the selection is in a subroutine, the function is inserted at the top level
+        let (insert_line, base_indent) = match extraction.chunk {
+            Some(chunk) if chunk.start.line < range.start.line => (chunk.start.line, 0),
+            _ => (range.start.line, indent_len),
+        };
+        let min_indent = code
+            .lines()
+            .skip(if range.start.character == 0 { 0 } else { 1 })
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.chars().take_while(|c| *c == ' ').count())
+            .min()
+            .unwrap_or(0);
+        let mut lines = code
+            .lines()
+            .map(|line| {
+                line.get(min_indent..)
+                    .unwrap_or(line.trim_start())
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        if let Some(outputs) = extraction.outputs() {
+            lines.push(if PYTHON_MODE {
+                format!("return {outputs}")
             } else {
-                code
+                outputs
+            });
+        } else if PYTHON_MODE {
+            // add `return` to the last line
+            if let Some(last_line) = lines.last_mut() {
+                last_line.insert_str(0, "return ");
             }
+        }
+        let indent = " ".repeat(base_indent);
+        let body = deepen_indent(lines.join("\n"))
+            .lines()
+            .map(|line| format!("{indent}{line}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let name = if extraction.is_procedural && ERG_MODE {
+            "new_proc!"
         } else {
-            code.trim_start().to_string()
-        };
-        let sig = match (ERG_MODE, extract_function) {
-            (true, true) => "new_func() =\n",
-            (true, false) => "new_var = ",
-            (false, true) => "def new_func():\n",
-            (false, false) => "new_var = ",
+            "new_func"
         };
-        let expanded = if extract_function {
-            "new_func()"
+        let args = extraction
+            .params
+            .iter()
+            .map(|(name, _)| &name[..])
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sig = if ERG_MODE {
+            let params = extraction
+                .params
+                .iter()
+                .map(|(name, t)| match t {
+                    Some(t) => format!("{name}: {t}"),
+                    None => name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let return_t = extraction
+                .return_t()
+                .map(|t| format!(": {t}"))
+                .unwrap_or_default();
+            format!("{name}({params}){return_t} =")
         } else {
-            "new_var"
+            format!("def {name}({args}):")
         };
-        let expanded = if range.start.character == 0 {
-            format!("{}{expanded}", " ".repeat(indent_len))
+        let call = match extraction.outputs() {
+            Some(outputs) => format!("{outputs} = {name}({args})"),
+            None => format!("{name}({args})"),
+        };
+        let call = if range.start.character == 0 {
+            format!("{}{call}", " ".repeat(indent_len))
         } else {
-            expanded.to_string()
+            call
         };
-        let extracted = format!("{}{sig}{body}\n\n", " ".repeat(indent_len));
-        let start = Position::new(range.start.line, 0);
+        let extracted = format!("{indent}{sig}\n{body}\n\n");
+        let start = Position::new(insert_line, 0);
         let edit1 = TextEdit::new(Range::new(start, start), extracted);
-        let edit2 = TextEdit::new(Range::new(range.start, range.end), expanded);
+        let edit2 = TextEdit::new(range, call);
         let mut changes = HashMap::new();
         changes.insert(uri.raw(), vec![edit1, edit2]);
         action.edit = Some(WorkspaceEdit::new(changes));
-        Ok(action)
+        action
+    }
+
+    /// Computes the parameters and the return value of the selected code from the variables it uses:
+    /// local variables defined outside the selection are passed as arguments,
+    /// and variables defined in the selection and used after it are returned.
+    fn analyze_extraction(&self, uri: &NormalizedUrl, range: Range) -> Extraction {
+        let mut extraction = Extraction::default();
+        let (Some(hir), Some(visitor), Some(tokens)) = (
+            self.analysis_result.get_hir(uri),
+            self.get_visitor(uri),
+            self.file_cache.get_token_stream(uri),
+        ) else {
+            return extraction;
+        };
+        let contains = |range: Range, pos: Position| range.start <= pos && pos < range.end;
+        extraction.chunk = hir
+            .module
+            .iter()
+            .filter_map(|chunk| util::loc_to_range(chunk.loc()))
+            .find(|chunk| contains(*chunk, range.start));
+        let ctx = self.get_local_ctx(uri, range.start).into_iter().next();
+        let show = |t: &Type| ctx.and_then(|ctx| type_to_annotation(ctx, t));
+        let path = util::uri_to_path(uri);
+        let raw_uri = uri.clone().raw();
+        let mut prev_is_dot = false;
+        for token in tokens.iter() {
+            // `x.y`: `y` is an attribute
+            let is_attr = prev_is_dot;
+            prev_is_dot = token.is(TokenKind::Dot);
+            let Some(token_range) = util::loc_to_range(token.loc()) else {
+                continue;
+            };
+            if !token.is(TokenKind::Symbol)
+                || is_attr
+                || token_range.start < range.start
+                || range.end < token_range.end
+            {
+                continue;
+            }
+            if token.inspect().ends_with('!') {
+                extraction.is_procedural = true;
+            }
+            let Some(vi) = visitor.get_info(token) else {
+                continue;
+            };
+            let Some(def_range) = util::loc_to_range(vi.def_loc.loc) else {
+                continue;
+            };
+            if vi.def_loc.module.as_ref() != Some(&path) {
+                continue;
+            }
+            let name = token.inspect().to_string();
+            let is_known = |vars: &[(String, Option<String>)]| vars.iter().any(|(n, _)| n == &name);
+            if contains(range, def_range.start) {
+                if is_known(&extraction.outputs) {
+                    continue;
+                }
+                let used_later = self
+                    .get_refs_from_abs_loc(&vi.def_loc)
+                    .iter()
+                    .any(|r| r.uri == raw_uri && range.end <= r.range.start);
+                if used_later {
+                    extraction.outputs.push((name, show(&vi.t)));
+                }
+            } else if extraction
+                .chunk
+                .is_some_and(|chunk| contains(chunk, def_range.start))
+                && !is_known(&extraction.params)
+            {
+                extraction.params.push((name, show(&vi.t)));
+            }
+        }
+        let mut exprs = vec![];
+        for chunk in hir.module.iter() {
+            exprs_in_range(chunk, range, &mut exprs);
+        }
+        extraction.last_t = exprs.last().and_then(|expr| match expr {
+            Expr::Def(_) | Expr::ClassDef(_) | Expr::PatchDef(_) | Expr::ReDef(_) => None,
+            _ if expr.ref_t().is_nonelike() => None,
+            _ => show(expr.ref_t()),
+        });
+        extraction
     }
 
     fn resolve_inline_variable_action(&self, mut action: CodeAction) -> ELSResult<CodeAction> {
@@ -346,28 +778,107 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .and_then(|v| serde_json::from_value::<CodeActionParams>(v).ok())
             .ok_or("invalid params")?;
         let uri = NormalizedUrl::new(params.text_document.uri.clone());
-        let visitor = self.get_visitor(&uri).unwrap();
-        let token = self.file_cache.get_token(&uri, params.range.start).unwrap();
-        match visitor.get_min_expr(&token).unwrap() {
-            Expr::Def(def) => {
-                action.edit = Some(WorkspaceEdit::new(self.inline_var_def(def)));
+        let Some((def_uri, def_token)) = self.get_inline_target(&uri, params.range.start) else {
+            return Ok(action);
+        };
+        let visitor = self.get_visitor(&def_uri).ok_or("no HIR")?;
+        if let Some(Expr::Def(def)) = visitor.get_min_expr(&def_token) {
+            if let Err(reason) = self.check_inlinable(def) {
+                send_log(format!("cannot inline: {reason}"))?;
+                return Ok(action);
             }
+            action.edit = Some(WorkspaceEdit::new(self.inline_var_def(def)));
+        }
+        Ok(action)
+    }
+
+    /// Returns the file and the name token of the definition to be inlined.
+    /// The cursor may be on the definition or on a use of the variable.
+    fn get_inline_target(
+        &self,
+        uri: &NormalizedUrl,
+        pos: Position,
+    ) -> Option<(NormalizedUrl, Token)> {
+        let visitor = self.get_visitor(uri)?;
+        let token = self.file_cache.get_token(uri, pos)?;
+        match visitor.get_min_expr(&token)? {
+            Expr::Def(_) => Some((uri.clone(), token)),
             Expr::Accessor(acc) => {
-                let uri = NormalizedUrl::new(
-                    Url::from_file_path(acc.var_info().def_loc.module.as_ref().unwrap()).unwrap(),
-                );
-                let range = util::loc_to_range(acc.var_info().def_loc.loc).unwrap();
-                let token = self.file_cache.get_token(&uri, range.start).unwrap();
-                if let Some(Expr::Def(def)) = visitor.get_min_expr(&token) {
-                    action.edit = Some(WorkspaceEdit::new(self.inline_var_def(def)));
+                let def_loc = &acc.var_info().def_loc;
+                let uri = NormalizedUrl::new(Url::from_file_path(def_loc.module.as_ref()?).ok()?);
+                let range = util::loc_to_range(def_loc.loc)?;
+                let token = self.file_cache.get_token(&uri, range.start)?;
+                Some((uri, token))
+            }
+            _ => None,
+        }
+    }
+
+    /// A variable can be inlined if:
+    /// * it is assigned only once (not a mutable variable)
+    /// * its defining expression has no side effects (no procedure calls or mutable variables)
+    /// * the names in the defining expression refer to the same variables at every use site
+    fn check_inlinable(&self, def: &Def) -> Result<(), String> {
+        let ident = def.sig.ident();
+        let name = ident.inspect();
+        let Signature::Var(_) = &def.sig else {
+            return Err(format!("`{name}` is not a variable"));
+        };
+        if name.ends_with('!') {
+            return Err(format!("`{name}` is mutable"));
+        }
+        let path = ident.vi.def_loc.module.as_ref().ok_or("unknown module")?;
+        let uri = NormalizedUrl::new(Url::from_file_path(path).map_err(|_| "invalid path")?);
+        let body_range = util::loc_to_range(def.body.block.loc()).ok_or("unknown location")?;
+        let (Some(tokens), Some(visitor)) = (
+            self.file_cache.get_token_stream(&uri),
+            self.get_visitor(&uri),
+        ) else {
+            return Err("the file has not been analyzed".into());
+        };
+        let referrers = self.get_refs_from_abs_loc(&ident.vi.def_loc);
+        let mut prev_is_dot = false;
+        for token in tokens.iter() {
+            let is_attr = prev_is_dot;
+            prev_is_dot = token.is(TokenKind::Dot);
+            let Some(range) = util::loc_to_range(token.loc()) else {
+                continue;
+            };
+            if !token.is(TokenKind::Symbol)
+                || range.start < body_range.start
+                || body_range.end < range.end
+            {
+                continue;
+            }
+            if token.inspect().ends_with('!') {
+                return Err(format!("`{}` may have side effects", token.inspect()));
+            }
+            if is_attr {
+                continue;
+            }
+            let Some(vi) = visitor.get_info(token) else {
+                continue;
+            };
+            for referrer in referrers.iter() {
+                let ref_uri = NormalizedUrl::new(referrer.uri.clone());
+                let shadowed = self
+                    .get_local_ctx(&ref_uri, referrer.range.start)
+                    .into_iter()
+                    .find_map(|ctx| ctx.get_var_info(token.inspect()))
+                    .is_none_or(|(_, ref_vi)| ref_vi.def_loc != vi.def_loc);
+                if shadowed {
+                    return Err(format!(
+                        "`{}` refers to another variable at line {}",
+                        token.inspect(),
+                        referrer.range.start.line + 1
+                    ));
                 }
             }
-            _ => {}
         }
-        Ok(action)
+        Ok(())
     }
 
-    fn inline_var_def(&self, def: &erg_compiler::hir::Def) -> HashMap<Url, Vec<TextEdit>> {
+    fn inline_var_def(&self, def: &Def) -> HashMap<Url, Vec<TextEdit>> {
         let mut changes = HashMap::new();
         let mut range = util::loc_to_range(def.loc()).unwrap();
         range.end.character = u32::MAX;
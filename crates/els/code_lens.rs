@@ -1,12 +1,49 @@
+use erg_common::pathutil::is_test_file;
+use erg_common::python_util::opt_which_python;
+use erg_common::traits::{Locational, Stream};
+
 use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::ast::{self, Accessor};
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::hir::Expr;
 
-use lsp_types::{CodeLens, CodeLensParams};
+use lsp_types::{CodeLens, CodeLensParams, Command, Url};
+use serde::{Deserialize, Serialize};
 
 use crate::server::{send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RunKind {
+    Run,
+    Debug,
+    Test,
+}
+
+/// Stored in `CodeLens::data` and resolved to a command in `codeLens/resolve`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunLensData {
+    kind: RunKind,
+    uri: Url,
+    name: String,
+}
+
+/// `@Test` subroutines, and `test_*` subroutines in `*.test.er` or `tests/*.er`
+fn is_test(def: &ast::Def, uri: &NormalizedUrl) -> bool {
+    let by_decorator = def.sig.decorators().is_some_and(|decos| {
+        decos.iter().any(|deco| {
+            matches!(deco.expr(), ast::Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == "Test")
+        })
+    });
+    let in_test_file = is_test_file(util::uri_to_path(uri));
+    let by_name = def
+        .sig
+        .ident()
+        .is_some_and(|ident| ident.inspect().starts_with("test_"));
+    by_decorator || (in_test_file && by_name)
+}
+
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     pub(crate) fn handle_code_lens(
         &mut self,
@@ -18,6 +55,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let result = [
             self.send_trait_impls_lens(&uri)?,
             self.send_class_inherits_lens(&uri)?,
+            self.send_run_lens(&uri)?,
         ]
         .concat();
         Ok(Some(result))
@@ -51,4 +89,98 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     fn send_class_inherits_lens(&mut self, _uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
         Ok(vec![])
     }
+
+    /// "Run" and "Debug" for `main!` procedures, "Run test" for tests.
+    /// The commands are computed lazily in `codeLens/resolve`.
+    fn send_run_lens(&mut self, uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
+        let mut result = vec![];
+        let Some(module) = self.analysis_result.get_ast(uri) else {
+            return Ok(result);
+        };
+        for chunk in module.iter() {
+            let ast::Expr::Def(def) = chunk else {
+                continue;
+            };
+            let (Some(ident), true) = (def.sig.ident(), def.is_subr()) else {
+                continue;
+            };
+            let Some(range) = util::loc_to_range(ident.loc()) else {
+                continue;
+            };
+            let kinds = if is_test(def, uri) {
+                vec![RunKind::Test]
+            } else if &ident.inspect()[..] == "main!" {
+                vec![RunKind::Run, RunKind::Debug]
+            } else {
+                continue;
+            };
+            for kind in kinds {
+                let data = RunLensData {
+                    kind,
+                    uri: uri.clone().raw(),
+                    name: ident.inspect().to_string(),
+                };
+                result.push(CodeLens {
+                    range,
+                    command: None,
+                    data: Some(serde_json::to_value(data)?),
+                });
+            }
+        }
+        Ok(result)
+    }
+
+    /// The client executes the command lines in `arguments` in order.
+    /// * Run: `erg run <file>`
+    /// * Debug: `erg compile <file>` and `python -m pdb <file>.pyc`
+    /// * Run test: `erg test <file> --filter <name>`
+    pub(crate) fn handle_code_lens_resolve(&mut self, mut lens: CodeLens) -> ELSResult<CodeLens> {
+        send_log(format!("code lens resolve requested: {lens:?}"))?;
+        let Some(data) = lens.data.take() else {
+            return Ok(lens);
+        };
+        let data = RunLensData::deserialize(data)?;
+        let path = data
+            .uri
+            .to_file_path()
+            .map_err(|_| format!("invalid file path: {}", data.uri))?;
+        let file = path.to_string_lossy().to_string();
+        let (title, command, lines): (_, _, Vec<Vec<String>>) = match data.kind {
+            RunKind::Run => (
+                "▶ Run",
+                "erg.run",
+                vec![vec!["erg".into(), "run".into(), file]],
+            ),
+            RunKind::Debug => {
+                let pyc = path.with_extension("pyc").to_string_lossy().to_string();
+                let python = self
+                    .cfg
+                    .py_command
+                    .map(ToString::to_string)
+                    .or_else(|| opt_which_python().ok())
+                    .unwrap_or_else(|| "python3".into());
+                let lines = vec![
+                    vec!["erg".into(), "compile".into(), file],
+                    vec![python, "-m".into(), "pdb".into(), pyc],
+                ];
+                ("Debug", "erg.debug", lines)
+            }
+            RunKind::Test => {
+                let line = vec![
+                    "erg".into(),
+                    "test".into(),
+                    file,
+                    "--filter".into(),
+                    data.name,
+                ];
+                ("▶ Run test", "erg.test", vec![line])
+            }
+        };
+        lens.command = Some(Command {
+            title: title.to_string(),
+            command: command.to_string(),
+            arguments: Some(vec![serde_json::to_value(lines)?]),
+        });
+        Ok(lens)
+    }
 }
@@ -3,13 +3,12 @@ use erg_compiler::varinfo::AbsLocation;
 use serde_json::Value;
 
 use erg_compiler::artifact::BuildRunnable;
-use erg_compiler::hir::Expr;
 
-use lsp_types::{Command, ExecuteCommandParams, Location, Url};
+use lsp_types::{Command, ExecuteCommandParams, Url};
 
 use crate::_log;
 use crate::server::{ELSResult, Server};
-use crate::util::{self, NormalizedUrl};
+use crate::util;
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     pub(crate) fn handle_execute_command(
@@ -30,17 +29,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         &self,
         trait_loc: AbsLocation,
     ) -> ELSResult<Option<Command>> {
-        let refs = self.get_refs_from_abs_loc(&trait_loc);
-        let filter = |loc: Location| {
-            let uri = NormalizedUrl::new(loc.uri.clone());
-            let token = self.file_cache.get_token(&uri, loc.range.start)?;
-            let opt_visitor = self.get_visitor(&uri);
-            let min_expr = opt_visitor
-                .as_ref()
-                .and_then(|visitor| visitor.get_min_expr(&token))?;
-            matches!(min_expr, Expr::ClassDef(_)).then_some(loc)
-        };
-        let impls = refs.into_iter().filter_map(filter).collect::<Vec<_>>();
+        let impls = self.get_type_impls(&trait_loc);
         let impl_len = impls.len();
         let locations = serde_json::to_value(impls)?;
         let Ok(uri) = trait_loc.module.ok_or(()).and_then(Url::from_file_path) else {
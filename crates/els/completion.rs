@@ -4,7 +4,6 @@ use erg_common::consts::PYTHON_MODE;
 use erg_common::spawn::spawn_new_thread;
 use erg_compiler::erg_parser::parse::Parsable;
 use lsp_types::CompletionResponse;
-use serde_json::Value;
 
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
@@ -21,14 +20,15 @@ use erg_compiler::context::Context;
 use erg_compiler::erg_parser::token::TokenKind;
 use erg_compiler::hir::Expr;
 use erg_compiler::module::SharedCompilerResource;
+use erg_compiler::stable_id::{module_key, StableDefId};
 use erg_compiler::ty::{HasType, ParamTy, Type};
 use erg_compiler::varinfo::{AbsLocation, VarInfo};
 use erg_compiler::HIRBuilder;
 use TokenKind::*;
 
 use lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionParams, Documentation, MarkedString,
-    MarkupContent, MarkupKind, Position, Range, TextEdit,
+    CompletionItem, CompletionItemKind, CompletionParams, Documentation, InsertTextFormat,
+    MarkedString, MarkupContent, MarkupKind, Position, Range, TextEdit,
 };
 
 use crate::server::{send_log, ELSResult, Server};
@@ -49,6 +49,29 @@ fn comp_item_kind(vi: &VarInfo) -> CompletionItemKind {
     }
 }
 
+/// `f: (x: Int, y: Str) -> Int` => `f(${1:x}, ${2:y})`
+///
+/// Only required (non-default) parameters get tab stops, `self` is omitted.
+fn call_snippet(name: &str, t: &Type) -> Option<String> {
+    // type constructors (e.g. `Array(T)`) are usually not called in expressions
+    let return_t = t.return_t()?;
+    if !t.is_subr() || return_t.is_class_type() || return_t.is_trait_type() {
+        return None;
+    }
+    let skip = if t.self_t().is_some() { 1 } else { 0 };
+    let params = t
+        .non_default_params()?
+        .iter()
+        .skip(skip)
+        .enumerate()
+        .map(|(i, pt)| {
+            let name = pt.name().map_or("_", |name| &name[..]);
+            format!("${{{}:{}}}", i + 1, name.replace(['$', '}', '\\'], ""))
+        })
+        .collect::<Vec<_>>();
+    Some(format!("{name}({})$0", params.join(", ")))
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum CompletionKind {
     Local,
@@ -158,7 +181,7 @@ impl<'b> CompletionOrderSetter<'b> {
             if return_t.has_qvar() {
                 return false;
             }
-            self.mod_ctx.subtype_of(return_t, pt.typ())
+            self.mod_ctx.subtype_of(&return_t, pt.typ())
         }) {
             orders.push(CompletionOrder::ReturnTypeMatched);
         }
@@ -439,6 +462,16 @@ impl CompletionCache {
 }
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    fn snippet_supported(&self) -> bool {
+        self.client_capas
+            .text_document
+            .as_ref()
+            .and_then(|doc| doc.completion.as_ref())
+            .and_then(|comp| comp.completion_item.as_ref())
+            .and_then(|item| item.snippet_support)
+            .unwrap_or(false)
+    }
+
     /// Returns completion candidates from modules in the same directory
     fn neighbor_completion(
         &self,
@@ -451,10 +484,14 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 if vi.vis.is_private() {
                     continue;
                 }
-                let Some(path) = vi.def_loc.module.as_ref() else {
+                let Some((path, is_decl)) = vi
+                    .def_loc
+                    .module
+                    .as_ref()
+                    .and_then(|path| util::import_path(&util::uri_to_path(uri), path))
+                else {
                     continue;
                 };
-                let path = path.file_stem().unwrap().to_string_lossy();
                 let mut item = CompletionItem::new_simple(
                     format!("{name} (import from {path})"),
                     vi.t.to_string(),
@@ -465,6 +502,8 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 item.kind = Some(comp_item_kind(vi));
                 let import = if PYTHON_MODE {
                     format!("from {path} import {name}\n")
+                } else if is_decl {
+                    format!("{{{name};}} = pyimport \"{path}\"\n")
                 } else {
                     format!("{{{name};}} = import \"{path}\"\n")
                 };
@@ -535,11 +574,13 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .get_min_expr(&uri, pos, offset)
             .and_then(|(token, expr)| match expr {
                 Expr::Call(call) => {
-                    let sig_t = call.obj.t();
+                    let sig_t = call.signature_t()?;
                     let nth = self.nth(&uri, call.args.loc(), &token);
                     let additional = if matches!(token.kind, Comma) { 1 } else { 0 };
-                    let nth = nth + additional;
-                    sig_t.non_default_params()?.get(nth).cloned()
+                    // `x.f(...)`: the first parameter is `self`
+                    let self_param = if call.is_method_call() { 1 } else { 0 };
+                    let nth = nth + additional + self_param;
+                    sig_t.non_var_params()?.get(nth).cloned()
                 }
                 other if comp_kind == CompletionKind::Space => {
                     match other.show_acc().as_deref() {
@@ -577,6 +618,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let Some(mod_ctx) = self.modules.get(&uri).map(|m| &m.context) else {
             return Ok(None);
         };
+        let mut module_keys = Dict::new();
         for (name, vi) in contexts.into_iter().flat_map(|ctx| ctx.local_dir()) {
             if comp_kind.should_be_method() && vi.vis.is_private() {
                 continue;
@@ -584,7 +626,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             // only show static methods, if the receiver is a type
             if vi.t.is_method()
                 && receiver_t.as_ref().map_or(true, |t| {
-                    !mod_ctx.subtype_of(t, vi.t.self_t().unwrap_or(Type::OBJ))
+                    !mod_ctx.subtype_of(t, vi.t.self_t().as_deref().unwrap_or(Type::OBJ))
                 })
             {
                 continue;
@@ -609,7 +651,26 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             CompletionOrderSetter::new(vi, arg_pt.as_ref(), mod_ctx, item.label.clone())
                 .set(&mut item);
             item.kind = Some(comp_item_kind(vi));
-            item.data = Some(Value::String(vi.def_loc.to_string()));
+            let module = vi.def_loc.module.as_ref().map_or(String::new(), |path| {
+                module_keys
+                    .entry(path.clone())
+                    .or_insert_with(|| module_key(path))
+                    .clone()
+            });
+            let def_id = StableDefId::new(&module, &vi.vis.def_namespace, name.inspect());
+            // `def_loc` may be outdated when resolved, so the definition is searched by `def_id` first
+            item.data = Some(serde_json::json!({
+                "def_id": def_id.to_string(),
+                "def_loc": vi.def_loc.to_string(),
+            }));
+            // passing the subroutine itself (e.g. `map(f, xs)`) doesn't need a call snippet
+            let expects_subr = arg_pt.as_ref().is_some_and(|pt| pt.typ().is_subr());
+            if self.snippet_supported() && !expects_subr {
+                if let Some(snippet) = call_snippet(&item.label, &vi.t) {
+                    item.insert_text = Some(snippet);
+                    item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+                }
+            }
             already_appeared.insert(item.label.clone());
             result.push(item);
         }
@@ -634,8 +695,22 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         send_log(format!("completion resolve requested: {item:?}"))?;
         if let Some(data) = &item.data {
             let mut contents = vec![];
-            let Ok(def_loc) = data.as_str().unwrap_or_default().parse::<AbsLocation>() else {
-                return Ok(item);
+            let indexed = data["def_id"]
+                .as_str()
+                .and_then(|id| id.parse::<StableDefId>().ok())
+                .and_then(|id| self.get_index()?.get_def_loc(&id));
+            let def_loc = match indexed {
+                Some(def_loc) => def_loc,
+                None => {
+                    let Ok(def_loc) = data["def_loc"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .parse::<AbsLocation>()
+                    else {
+                        return Ok(item);
+                    };
+                    def_loc
+                }
             };
             self.show_doc_comment(None, &mut contents, &def_loc)?;
             let mut contents = contents.into_iter().map(mark_to_string).collect::<Vec<_>>();
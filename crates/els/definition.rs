@@ -71,17 +71,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                                     .and_then(|ctx| ctx.get_mod_with_t(mod_t))
                                     .and_then(|mod_ctx| mod_ctx.get_var_info(token.inspect()))
                                 {
-                                    let Some(path) = vi.def_loc.module.as_ref() else {
-                                        return Ok(GotoDefinitionResponse::Array(vec![]));
-                                    };
-                                    let def_uri = Url::from_file_path(path).unwrap();
-                                    let resp = GotoDefinitionResponse::Array(vec![
-                                        lsp_types::Location::new(
-                                            def_uri,
-                                            util::loc_to_range(vi.def_loc.loc).unwrap(),
-                                        ),
-                                    ]);
-                                    return Ok(resp);
+                                    let locs = self.abs_loc_to_lsp_loc(&vi.def_loc);
+                                    return Ok(GotoDefinitionResponse::Array(
+                                        locs.into_iter().collect(),
+                                    ));
                                 }
                             }
                         } else if let Expr::Accessor(acc) = def.body.block.last().unwrap() {
@@ -116,14 +109,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                         }
                     }
                 }
-                match (vi.def_loc.module, util::loc_to_range(vi.def_loc.loc)) {
-                    (Some(path), Some(range)) => {
-                        let def_uri = Url::from_file_path(path).unwrap();
-                        Ok(GotoDefinitionResponse::Array(vec![
-                            lsp_types::Location::new(def_uri, range),
-                        ]))
-                    }
-                    _ => {
+                match self.abs_loc_to_lsp_loc(&vi.def_loc) {
+                    Some(loc) => Ok(GotoDefinitionResponse::Array(vec![loc])),
+                    None => {
                         send_log("not found (maybe builtin)")?;
                         Ok(GotoDefinitionResponse::Array(vec![]))
                     }
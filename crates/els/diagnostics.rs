@@ -1,23 +1,26 @@
 use std::thread::sleep;
 use std::time::Duration;
 
+use erg_common::cancel::CancellationToken;
 use erg_common::consts::PYTHON_MODE;
 use erg_common::dict::Dict;
+use erg_common::error::ErrorKind;
 use erg_common::fn_name;
 use erg_common::spawn::spawn_new_thread;
 use erg_common::style::*;
-use erg_common::traits::Stream;
+use erg_common::traits::{Locational, Stream};
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::ast::Module;
 use erg_compiler::erg_parser::parse::Parsable;
-use erg_compiler::error::CompileErrors;
+use erg_compiler::error::{CompileError, CompileErrors};
 
 use lsp_types::{
-    Diagnostic, DiagnosticSeverity, NumberOrString, Position, PublishDiagnosticsParams, Range, Url,
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+    Position, PublishDiagnosticsParams, Range, Url,
 };
 use serde_json::json;
 
-use crate::diff::{ASTDiff, HIRDiff};
+use crate::diff::{def_name, def_sig, method_types, ASTDiff, HIRDiff};
 use crate::server::{send, send_log, AnalysisResult, DefaultFeatures, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
@@ -39,8 +42,44 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         } else {
             "exec"
         };
-        let mut checker = self.get_checker(path.clone());
-        let artifact = match checker.build(code.into(), mode) {
+        // the first analysis of a module also analyzes its dependencies, which may take a while
+        let progress = if self.analysis_result.get(&uri).is_none() {
+            let title = if self.analysis_result.is_empty() {
+                "Indexing workspace"
+            } else {
+                "Checking"
+            };
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+            Some(self.start_progress(title, file_name)?)
+        } else {
+            None
+        };
+        let cancel = CancellationToken::new();
+        // a newer check supersedes the older one
+        if let Some(prev) = self
+            .pending_checks
+            .borrow_mut()
+            .insert(uri.clone(), cancel.clone())
+        {
+            prev.cancel();
+        }
+        let mut checker = self.get_checker(path.clone(), cancel.clone());
+        let result = checker.build(code.into(), mode);
+        let cancelled = {
+            let mut pending = self.pending_checks.borrow_mut();
+            // if not cancelled, no newer check has been registered
+            if !cancel.is_cancelled() {
+                pending.remove(&uri);
+            }
+            cancel.is_cancelled()
+        };
+        if cancelled {
+            send_log(format!("checking {uri} cancelled"))?;
+            return Ok(());
+        }
+        let artifact = match result {
             Ok(artifact) => {
                 send_log(format!(
                     "checking {uri} passed, found warns: {}",
@@ -98,9 +137,21 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             send_log(format!("{uri}: {}", module.context.name))?;
             self.modules.insert(uri.clone(), module);
         }
+        drop(progress);
+        self.send_diagnostic_refresh()?;
         let dependents = self.dependents_of(&uri);
-        for dep in dependents {
+        if dependents.is_empty() {
+            return Ok(());
+        }
+        let progress = self.start_progress("Rechecking dependents", None)?;
+        let total = dependents.len();
+        for (i, dep) in dependents.into_iter().enumerate() {
             // _log!("dep: {dep}");
+            let file_name = util::uri_to_path(&dep)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            progress.report(file_name, (i * 100 / total) as u32)?;
             let code = self.file_cache.get_entire_code(&dep)?.to_string();
             self.check_file(dep, code)?;
         }
@@ -130,7 +181,131 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Ok(())
     }
 
-    fn make_uri_and_diags(&mut self, errors: CompileErrors) -> Vec<(Url, Vec<Diagnostic>)> {
+    /// Re-lowers only the top-level definition that has been edited and publishes the updated diagnostics.
+    /// Returns `false` if the edit is structural (definitions are added/removed/renamed or lines are shifted)
+    /// or changes the interface of the definition (the signature, the type of the definition or its methods),
+    /// in which case the whole module (including the dependents of the definition) should be checked.
+    pub(crate) fn recheck_edited_def(&mut self, uri: &NormalizedUrl) -> ELSResult<bool> {
+        let Some(new) = self.get_ast(uri) else {
+            return Ok(false);
+        };
+        let Some(old) = self.analysis_result.get_ast(uri) else {
+            return Ok(false);
+        };
+        let ASTDiff::Modification(idx, new_chunk) = ASTDiff::diff(&*old, &new) else {
+            return Ok(false);
+        };
+        let Some(old_chunk) = old.get(idx) else {
+            return Ok(false);
+        };
+        let Some(name) = def_name(&new_chunk).map(|name| name.to_string()) else {
+            return Ok(false);
+        };
+        if def_name(old_chunk).map(|n| &n[..]) != Some(&name[..])
+            || def_sig(old_chunk) != def_sig(&new_chunk)
+            || old_chunk.ln_begin() != new_chunk.ln_begin()
+            || old_chunk.ln_end() != new_chunk.ln_end()
+        {
+            return Ok(false);
+        }
+
+        let (Some(ln_begin), Some(ln_end)) = (new_chunk.ln_begin(), new_chunk.ln_end()) else {
+            return Ok(false);
+        };
+        let col_begin = new_chunk.col_begin();
+        drop(old);
+        let Some(old_methods) = self
+            .analysis_result
+            .get_hir(uri)
+            .and_then(|hir| hir.module.get(idx).map(method_types))
+        else {
+            return Ok(false);
+        };
+        // the uses in the edited definition are registered again by the lowerer
+        if let Some(shared) = self.get_shared() {
+            shared
+                .index
+                .remove_refs_in(&util::uri_to_path(uri), ln_begin..=ln_end);
+        }
+        let Some(mut lowerer) = self.steal_lowerer(uri) else {
+            return Ok(false);
+        };
+        let old_t = lowerer.unregister(&name).map(|vi| vi.t);
+        let res = lowerer.lower_chunk(new_chunk);
+        let new_t = lowerer.get_var_info(&name).map(|(_, vi)| vi.t.clone());
+        let (mut errors, warns) = lowerer.take_diagnostics();
+        self.restore_lowerer(uri.clone(), lowerer);
+        // the dependents of the definition should be checked again
+        let interface_changed = old_t != new_t
+            || res.as_ref().map_or(!old_methods.is_empty(), |chunk| {
+                method_types(chunk) != old_methods
+            });
+        if interface_changed {
+            return Ok(false);
+        }
+        let Some(mut result) = self.analysis_result.get_mut(uri) else {
+            return Ok(false);
+        };
+        match res {
+            Ok(hir_chunk) => {
+                if let Some(chunk) = result
+                    .artifact
+                    .object
+                    .as_mut()
+                    .and_then(|hir| hir.module.get_mut(idx))
+                {
+                    *chunk = hir_chunk;
+                }
+            }
+            Err(errs) => errors.extend(errs),
+        }
+        // diagnostics of the other definitions are still valid
+        let in_edited_def = |err: &CompileError| {
+            // `f is not used` is only reported by the whole-module check
+            let unused_def = err.core.kind == ErrorKind::UnusedWarning
+                && err.core.loc.ln_begin() == Some(ln_begin)
+                && err.core.loc.col_begin() == col_begin;
+            !unused_def
+                && err.input.path() == util::uri_to_path(uri).as_path()
+                && err
+                    .core
+                    .loc
+                    .ln_begin()
+                    .is_some_and(|ln| (ln_begin..=ln_end).contains(&ln))
+        };
+        let artifact = &mut result.artifact;
+        artifact
+            .errors
+            .ref_mut_payload()
+            .retain(|err| !in_edited_def(err));
+        artifact
+            .warns
+            .ref_mut_payload()
+            .retain(|err| !in_edited_def(err));
+        artifact.errors.extend(errors);
+        artifact.warns.extend(warns);
+        let diags = artifact
+            .errors
+            .clone()
+            .into_iter()
+            .chain(artifact.warns.clone())
+            .collect();
+        result.ast = new;
+        drop(result);
+        let uri_and_diags = self.make_uri_and_diags(diags);
+        if uri_and_diags
+            .iter()
+            .all(|(diag_uri, _)| diag_uri != uri.as_ref())
+        {
+            self.send_diagnostics(uri.clone().raw(), vec![])?;
+        }
+        for (uri, diags) in uri_and_diags.into_iter() {
+            self.send_diagnostics(uri, diags)?;
+        }
+        Ok(true)
+    }
+
+    pub(crate) fn make_uri_and_diags(&mut self, errors: CompileErrors) -> Vec<(Url, Vec<Diagnostic>)> {
         let mut uri_and_diags: Vec<(Url, Vec<Diagnostic>)> = vec![];
         for err in errors.into_iter() {
             let loc = err.core.get_loc_with_fallback();
@@ -144,7 +319,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 crate::_log!("failed to get uri: {}", err.input.path().display());
                 continue;
             };
+            let norm_uri = NormalizedUrl::new(err_uri.clone());
             let mut message = remove_style(&err.core.main_message);
+            // secondary spans (e.g. where a variable was moved)
+            let mut related_info = vec![];
             for sub in err.core.sub_messages {
                 for msg in sub.get_msg() {
                     message.push('\n');
@@ -155,25 +333,43 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     message.push_str("hint: ");
                     message.push_str(&remove_style(hint));
                 }
+                if sub.loc == loc || sub.get_msg().is_empty() {
+                    continue;
+                }
+                if let Some(range) = self.file_cache.loc_to_range(&norm_uri, sub.loc) {
+                    related_info.push(DiagnosticRelatedInformation {
+                        location: Location::new(err_uri.clone(), range),
+                        message: remove_style(&sub.get_msg().join("\n")),
+                    });
+                }
             }
-            let start = Position::new(
-                loc.ln_begin().unwrap_or(1) - 1,
-                loc.col_begin().unwrap_or(0),
-            );
-            let end = Position::new(loc.ln_end().unwrap_or(1) - 1, loc.col_end().unwrap_or(0));
+            let range = self
+                .file_cache
+                .loc_to_range(&norm_uri, loc)
+                .unwrap_or_else(|| {
+                    let start = Position::new(
+                        loc.ln_begin().unwrap_or(1) - 1,
+                        loc.col_begin().unwrap_or(0),
+                    );
+                    let end = Position::new(loc.ln_end().unwrap_or(1) - 1, 0);
+                    Range::new(start, end)
+                });
             let severity = if err.core.kind.is_warning() {
-                DiagnosticSeverity::WARNING
+                let Some(severity) = self.settings.borrow().warnings.severity() else {
+                    continue;
+                };
+                severity
             } else {
                 DiagnosticSeverity::ERROR
             };
             let source = if PYTHON_MODE { "pylyzer" } else { "els" };
             let diag = Diagnostic::new(
-                Range::new(start, end),
+                range,
                 Some(severity),
                 Some(NumberOrString::String(format!("E{}", err.core.errno))),
                 Some(source.to_string()),
                 message,
-                None,
+                (!related_info.is_empty()).then_some(related_info),
                 None,
             );
             if let Some((_, diags)) = uri_and_diags.iter_mut().find(|x| x.0 == err_uri) {
@@ -185,13 +381,24 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         uri_and_diags
     }
 
-    fn send_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> ELSResult<()> {
+    pub(crate) fn send_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> ELSResult<()> {
         if self
             .disabled_features
             .contains(&DefaultFeatures::Diagnostics)
         {
             return Ok(());
         }
+        // the diagnostics of a notebook (virtual module) are published to its cells
+        if let Some(notebook) = self.notebooks.get(&NormalizedUrl::new(uri.clone())) {
+            for (cell, diags) in notebook.split_diagnostics(diagnostics) {
+                self.send_diagnostics(cell, diags)?;
+            }
+            return Ok(());
+        }
+        // the client pulls the diagnostics by itself (`textDocument/diagnostic`)
+        if self.diag_client_capas.pull {
+            return Ok(());
+        }
         let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
         if self
             .client_capas
@@ -228,7 +435,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                             continue;
                         };
                         if latest_ver != ver {
-                            if let Ok(code) = _self.file_cache.get_entire_code(&uri) {
+                            // edits inside a definition body don't need whole-module checking
+                            if _self.recheck_edited_def(&uri).unwrap_or(false) {
+                                file_vers.insert(uri, latest_ver);
+                            } else if let Ok(code) = _self.file_cache.get_entire_code(&uri) {
                                 let _ = _self.check_file(uri.clone(), code);
                                 file_vers.insert(uri, latest_ver);
                             }
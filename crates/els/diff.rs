@@ -3,11 +3,52 @@ use std::fmt;
 use std::ops::{Deref, DerefMut};
 
 use erg_common::traits::Stream;
+use erg_common::Str;
 use erg_compiler::erg_parser::ast;
 use erg_compiler::erg_parser::ast::Module;
 use erg_compiler::hir;
 use erg_compiler::hir::HIR;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::ty::Type;
+
+/// The name defined by a top-level chunk (`x = ...`, `C = Class ...`, `P = Patch ...`)
+pub(crate) fn def_name(chunk: &ast::Expr) -> Option<&Str> {
+    match chunk {
+        ast::Expr::Def(def)
+        | ast::Expr::ClassDef(ast::ClassDef { def, .. })
+        | ast::Expr::PatchDef(ast::PatchDef { def, .. }) => def.sig.name_as_str(),
+        _ => None,
+    }
+}
+
+/// The signature of a top-level definition (`f(x: Int): Int`, `C = Class ...`)
+pub(crate) fn def_sig(chunk: &ast::Expr) -> Option<&ast::Signature> {
+    match chunk {
+        ast::Expr::Def(def)
+        | ast::Expr::ClassDef(ast::ClassDef { def, .. })
+        | ast::Expr::PatchDef(ast::PatchDef { def, .. }) => Some(&def.sig),
+        _ => None,
+    }
+}
+
+/// The names and the types of the methods defined by a top-level class (patch) definition
+pub(crate) fn method_types(chunk: &hir::Expr) -> Vec<(Str, Type)> {
+    let methods = match chunk {
+        hir::Expr::ClassDef(class_def) => &class_def.methods,
+        hir::Expr::PatchDef(patch_def) => &patch_def.methods,
+        _ => return vec![],
+    };
+    methods
+        .iter()
+        .filter_map(|method| match method {
+            hir::Expr::Def(def) => Some((
+                def.sig.ident().inspect().clone(),
+                def.sig.ident().vi.t.clone(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ASTDiff {
@@ -98,13 +139,8 @@ impl HIRDiff {
                 Some(Self::Addition(idx, expr))
             }
             ASTDiff::Modification(idx, expr) => {
-                if let ast::Expr::Def(def)
-                | ast::Expr::ClassDef(ast::ClassDef { def, .. })
-                | ast::Expr::PatchDef(ast::PatchDef { def, .. }) = &expr
-                {
-                    if let Some(name) = def.sig.name_as_str() {
-                        lowerer.unregister(name);
-                    }
+                if let Some(name) = def_name(&expr) {
+                    lowerer.unregister(name);
                 }
                 let expr = lowerer
                     .lower_chunk(expr)
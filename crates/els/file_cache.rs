@@ -129,7 +129,9 @@ impl FileCache {
     pub fn get_token(&self, uri: &NormalizedUrl, pos: Position) -> Option<Token> {
         let _ = self.load_once(uri);
         let ent = self.files.borrow_mut();
-        let tokens = ent.get(uri)?.token_stream.as_ref()?;
+        let entry = ent.get(uri)?;
+        let tokens = entry.token_stream.as_ref()?;
+        let pos = util::pos_to_col_pos(&entry.code, pos);
         for tok in tokens.iter() {
             if util::pos_in_loc(tok, pos) {
                 return Some(tok.clone());
@@ -146,7 +148,9 @@ impl FileCache {
     ) -> Option<Token> {
         let _ = self.load_once(uri);
         let ent = self.files.borrow_mut();
-        let tokens = ent.get(uri)?.token_stream.as_ref()?;
+        let entry = ent.get(uri)?;
+        let tokens = entry.token_stream.as_ref()?;
+        let pos = util::pos_to_col_pos(&entry.code, pos);
         let index = (|| {
             for (i, tok) in tokens.iter().enumerate() {
                 if util::pos_in_loc(tok, pos) {
@@ -163,6 +167,20 @@ impl FileCache {
         }
     }
 
+    /// Converts `loc` into an LSP range using the contents of `uri`.
+    /// If the file cannot be loaded, the columns are used as they are.
+    pub(crate) fn loc_to_range(
+        &self,
+        uri: &NormalizedUrl,
+        loc: erg_common::error::Location,
+    ) -> Option<Range> {
+        let _ = self.load_once(uri);
+        match self.files.borrow_mut().get(uri) {
+            Some(entry) => util::loc_to_range_in(&entry.code, loc),
+            None => util::loc_to_range(loc),
+        }
+    }
+
     /// 0-based
     pub(crate) fn get_line(&self, uri: &NormalizedUrl, line0: u32) -> Option<String> {
         let _ = self.load_once(uri);
@@ -181,20 +199,21 @@ impl FileCache {
         for (i, line) in file.code.lines().enumerate() {
             if i >= range.start.line as usize && i <= range.end.line as usize {
                 if i == range.start.line as usize && i == range.end.line as usize {
-                    if line.len() < range.end.character as usize {
+                    let Some(end) = util::utf16_to_byte(line, range.end.character) else {
                         return Ok(None);
-                    }
-                    code.push_str(
-                        &line[range.start.character as usize..range.end.character as usize],
-                    );
+                    };
+                    let start = util::utf16_to_byte(line, range.start.character).unwrap_or(end);
+                    code.push_str(&line[start.min(end)..end]);
                 } else if i == range.start.line as usize {
-                    code.push_str(&line[range.start.character as usize..]);
+                    let start =
+                        util::utf16_to_byte(line, range.start.character).unwrap_or(line.len());
+                    code.push_str(&line[start..]);
                     code.push('\n');
                 } else if i == range.end.line as usize {
-                    if line.len() < range.end.character as usize {
+                    let Some(end) = util::utf16_to_byte(line, range.end.character) else {
                         return Ok(None);
-                    }
-                    code.push_str(&line[..range.end.character as usize]);
+                    };
+                    code.push_str(&line[..end]);
                 } else {
                     code.push_str(line);
                     code.push('\n');
@@ -0,0 +1,115 @@
+use erg_common::traits::DequeStream;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::erg_parser::token::TokenKind::*;
+
+use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+fn new_folding_range(start: u32, end: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+    FoldingRange {
+        start_line: start,
+        start_character: None,
+        end_line: end,
+        end_character: None,
+        kind,
+    }
+}
+
+/// Consecutive line comments and multi-line comments (`#[ ... ]#`).
+fn comment_ranges(code: &str) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+    let mut run_start = None;
+    let mut in_multi_line = false;
+    let mut prev = 0;
+    for (i, line) in code.lines().enumerate() {
+        let i = i as u32;
+        let line = line.trim_start();
+        let is_comment = if in_multi_line {
+            in_multi_line = !line.contains("]#");
+            true
+        } else if let Some(rest) = line.strip_prefix("#[") {
+            in_multi_line = !rest.contains("]#");
+            true
+        } else {
+            line.starts_with('#')
+        };
+        match (is_comment, run_start) {
+            (true, None) => {
+                run_start = Some(i);
+            }
+            (false, Some(start)) => {
+                if prev > start {
+                    ranges.push(new_folding_range(
+                        start,
+                        prev,
+                        Some(FoldingRangeKind::Comment),
+                    ));
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+        prev = i;
+    }
+    if let Some(start) = run_start {
+        if prev > start {
+            ranges.push(new_folding_range(
+                start,
+                prev,
+                Some(FoldingRangeKind::Comment),
+            ));
+        }
+    }
+    ranges
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_folding_range(
+        &mut self,
+        params: FoldingRangeParams,
+    ) -> ELSResult<Option<Vec<FoldingRange>>> {
+        send_log(format!("folding range requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let code = self.file_cache.get_entire_code(&uri)?;
+        let mut ranges = self.block_ranges(&uri);
+        ranges.extend(comment_ranges(&code));
+        ranges.sort_by_key(|range| range.start_line);
+        Ok(Some(ranges))
+    }
+
+    /// Indented blocks (subroutine bodies, class bodies, methods blocks, `match` arms, etc.).
+    /// Since this is computed from the tokens, not the AST, blocks can be folded even if the file has syntax errors.
+    fn block_ranges(&self, uri: &NormalizedUrl) -> Vec<FoldingRange> {
+        let mut ranges = vec![];
+        let Some(tokens) = self.file_cache.get_token_stream(uri) else {
+            return ranges;
+        };
+        // 0-origin line of the last significant token
+        let mut last_line = 0;
+        let mut starts = vec![];
+        for token in tokens.iter() {
+            match token.kind {
+                Indent => {
+                    starts.push(last_line);
+                }
+                Dedent => {
+                    if let Some(start) = starts.pop() {
+                        if last_line > start {
+                            ranges.push(new_folding_range(start, last_line, None));
+                        }
+                    }
+                }
+                Newline | EOF => {}
+                _ => {
+                    let lines = token.content.matches('\n').count() as u32;
+                    last_line = token.lineno.saturating_sub(1) + lines;
+                }
+            }
+        }
+        ranges
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use erg_common::config::FormatStyle;
+use erg_common::diff::hunks;
+use erg_common::traits::DequeStream;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::format::Formatter;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::erg_parser::token::TokenKind::*;
+
+use lsp_types::{
+    DocumentFormattingParams, DocumentRangeFormattingParams, FormattingOptions, Position, Range,
+    TextEdit,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+/// Formats a single line.
+/// Only whitespace is changed: leading tabs are expanded (or spaces are converted to tabs) and trailing whitespace is removed.
+fn format_line(line: &str, options: &FormattingOptions) -> String {
+    let body = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - body.len()];
+    let tab_size = options.tab_size.max(1) as usize;
+    let width = indent
+        .chars()
+        .fold(0, |w, c| if c == '\t' { w + tab_size } else { w + 1 });
+    let mut formatted = if options.insert_spaces {
+        " ".repeat(width)
+    } else {
+        "\t".repeat(width / tab_size) + &" ".repeat(width % tab_size)
+    };
+    if options.trim_trailing_whitespace.unwrap_or(true) {
+        formatted.push_str(body.trim_end_matches([' ', '\t']));
+    } else {
+        formatted.push_str(body);
+    }
+    formatted
+}
+
+/// `verbatim`: 0-origin line numbers that must not be changed (e.g. inside multi-line strings)
+fn format_lines<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+    verbatim: &HashSet<usize>,
+    options: &FormattingOptions,
+) -> Vec<String> {
+    lines
+        .map(|(i, line)| {
+            if verbatim.contains(&i) {
+                line.to_string()
+            } else {
+                format_line(line, options)
+            }
+        })
+        .collect()
+}
+
+/// Formats an entire document.
+pub fn format_code(code: &str, verbatim: &HashSet<usize>, options: &FormattingOptions) -> String {
+    let mut lines = format_lines(code.lines().enumerate(), verbatim, options);
+    if options.trim_final_newlines.unwrap_or(true) {
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+    }
+    let mut formatted = lines.join("\n");
+    let has_final_newline = code.ends_with('\n');
+    if !lines.is_empty() && (has_final_newline || options.insert_final_newline.unwrap_or(true)) {
+        formatted.push('\n');
+    }
+    formatted
+}
+
+/// Formats the document with the canonical formatter (`erg fmt`).
+/// If the document cannot be parsed, only whitespace is formatted.
+fn format_document(code: &str, verbatim: &HashSet<usize>, options: &FormattingOptions) -> String {
+    let style = FormatStyle {
+        indent_width: options.tab_size.max(1) as usize,
+        ..FormatStyle::default()
+    };
+    Formatter::new(style)
+        .format(code)
+        .unwrap_or_else(|_| format_code(code, verbatim, options))
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_formatting(
+        &mut self,
+        params: DocumentFormattingParams,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        send_log(format!("formatting requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let code = self.file_cache.get_entire_code(&uri)?;
+        let verbatim = self.verbatim_lines(&uri);
+        let formatted = format_document(&code, &verbatim, &params.options);
+        if formatted == code {
+            return Ok(Some(vec![]));
+        }
+        let line_count = code.lines().count() as u32;
+        let range = Range::new(Position::new(0, 0), Position::new(line_count + 1, 0));
+        Ok(Some(vec![TextEdit::new(range, formatted)]))
+    }
+
+    /// The entire document is formatted, and the changes overlapping with the range are applied.
+    /// Only whole lines are formatted, so the range is extended to the start/end of the lines.
+    pub(crate) fn handle_range_formatting(
+        &mut self,
+        params: DocumentRangeFormattingParams,
+    ) -> ELSResult<Option<Vec<TextEdit>>> {
+        send_log(format!("range formatting requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let code = self.file_cache.get_entire_code(&uri)?;
+        let verbatim = self.verbatim_lines(&uri);
+        let (start, end) = (
+            params.range.start.line as usize,
+            params.range.end.line as usize,
+        );
+        let formatted = format_document(&code, &verbatim, &params.options);
+        let new_lines = formatted.lines().collect::<Vec<_>>();
+        let mut edits = vec![];
+        for hunk in hunks(&code, &formatted) {
+            // a hunk with no old lines (insertion) overlaps if it is inserted in the range
+            if hunk.old.start > end || hunk.old.end.max(hunk.old.start + 1) <= start {
+                continue;
+            }
+            let range = Range::new(
+                Position::new(hunk.old.start as u32, 0),
+                Position::new(hunk.old.end as u32, 0),
+            );
+            let text = new_lines[hunk.new]
+                .iter()
+                .map(|line| format!("{line}\n"))
+                .collect::<String>();
+            edits.push(TextEdit::new(range, text));
+        }
+        Ok(Some(edits))
+    }
+
+    /// Lines spanned by multi-line tokens (strings, doc comments), except for their first line.
+    fn verbatim_lines(&self, uri: &NormalizedUrl) -> HashSet<usize> {
+        let mut lines = HashSet::new();
+        let Some(tokens) = self.file_cache.get_token_stream(uri) else {
+            return lines;
+        };
+        for token in tokens.iter() {
+            if !matches!(
+                token.kind,
+                StrLit | StrInterpLeft | StrInterpMid | StrInterpRight | DocComment
+            ) {
+                continue;
+            }
+            // `Token::loc` does not span multiple lines, so count the newlines in the content
+            let begin = token.lineno as usize;
+            lines.extend(begin..begin + token.content.matches('\n').count());
+        }
+        lines
+    }
+}
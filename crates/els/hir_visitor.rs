@@ -280,6 +280,11 @@ impl<'a> HIRVisitor<'a> {
                 return Some(expr);
             }
         }
+        if let Some(kw_var) = &args.kw_var {
+            if let Some(expr) = self.get_expr(&kw_var.expr, token) {
+                return Some(expr);
+            }
+        }
         None
     }
 
@@ -357,7 +362,11 @@ impl<'a> HIRVisitor<'a> {
         if util::pos_in_loc(&lambda.params, util::loc_to_pos(token.loc())?) {
             return Some(expr);
         }
-        self.get_expr_from_block(&lambda.body, token)
+        lambda
+            .guard
+            .as_ref()
+            .and_then(|guard| self.get_expr_from_block(guard, token))
+            .or_else(|| self.get_expr_from_block(&lambda.body, token))
     }
 
     fn get_expr_from_array<'e>(
@@ -551,6 +560,11 @@ impl<'a> HIRVisitor<'a> {
                 return Some(expr);
             }
         }
+        if let Some(kw_var) = &args.kw_var {
+            if let Some(expr) = self.get_expr_info(&kw_var.expr, token) {
+                return Some(expr);
+            }
+        }
         None
     }
 
@@ -611,6 +625,17 @@ impl<'a> HIRVisitor<'a> {
                 return Some(param.sig.vi.clone());
             }
         }
+        if let Some(kw_var) = &params.kw_var_params {
+            if let Some(vi) = kw_var
+                .t_spec_as_expr
+                .as_ref()
+                .and_then(|t_spec| self.get_expr_info(t_spec, token))
+            {
+                return Some(vi);
+            } else if kw_var.raw.pat.loc() == token.loc() {
+                return Some(kw_var.vi.clone());
+            }
+        }
         None
     }
 
@@ -659,6 +684,12 @@ impl<'a> HIRVisitor<'a> {
 
     fn get_lambda_info(&self, lambda: &Lambda, token: &Token) -> Option<VarInfo> {
         self.get_params_info(&lambda.params, token)
+            .or_else(|| {
+                lambda
+                    .guard
+                    .as_ref()
+                    .and_then(|guard| self.get_block_info(guard, token))
+            })
             .or_else(|| self.get_block_info(&lambda.body, token))
     }
 
@@ -2,6 +2,7 @@ use erg_common::consts::PYTHON_MODE;
 use erg_common::lang::LanguageCode;
 use erg_common::trim_eliminate_top_indent;
 use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::ast::DocComment;
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::erg_parser::token::{Token, TokenCategory, TokenKind};
 use erg_compiler::ty::HasType;
@@ -53,6 +54,21 @@ fn sort_hovers(mut contents: Vec<MarkedString>) -> Vec<MarkedString> {
     contents
 }
 
+/// e.g. `<module>::C` & `f` in `foo.er` => `foo.C.f`
+fn qualified_name(vi: &VarInfo, name: &str) -> Option<String> {
+    let module = vi.def_loc.module.as_ref()?.file_stem()?.to_string_lossy();
+    let module = module.trim_end_matches(".d");
+    let namespace = vi.vis.def_namespace.replace("::", ".");
+    let namespace = namespace
+        .trim_start_matches("<module>")
+        .trim_start_matches('.');
+    if namespace.is_empty() {
+        Some(format!("{module}.{name}"))
+    } else {
+        Some(format!("{module}.{namespace}.{name}"))
+    }
+}
+
 macro_rules! next {
     ($def_pos: ident, $default_code_block: ident, $contents: ident, $prev_token: ident, $token: ident) => {
         if $def_pos.line == 0 {
@@ -161,6 +177,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     );
                     contents.push(typ);
                     self.show_type_defs(&vi, &mut contents)?;
+                    self.show_type_info(&vi, &token, &mut contents)?;
                     self.show_doc_comment(Some(token), &mut contents, &vi.def_loc)?;
                 }
                 // not found or not symbol, etc.
@@ -219,6 +236,58 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         Ok(())
     }
 
+    /// Shows the fully qualified name, implemented traits, side effects and unresolved type variables.
+    fn show_type_info(
+        &mut self,
+        vi: &VarInfo,
+        token: &Token,
+        contents: &mut Vec<MarkedString>,
+    ) -> ELSResult<()> {
+        let mut info = vec![];
+        if let Some(qual_name) = qualified_name(vi, &token.content) {
+            info.push(format!("`{qual_name}`"));
+        }
+        if vi.t.is_class_type() {
+            if let Some(def_uri) = vi
+                .def_loc
+                .module
+                .as_ref()
+                .and_then(|path| NormalizedUrl::try_from(path.as_path()).ok())
+            {
+                self.quick_check_file(def_uri.clone())?;
+                if let Some(module) = self.modules.get(&def_uri) {
+                    let traits = module.context.get_impl_traits_by_name(&token.content);
+                    if !traits.is_empty() {
+                        let traits = traits
+                            .iter()
+                            .map(|t| format!("`{}`", t.local_name()))
+                            .collect::<Vec<_>>();
+                        info.push(format!("implements: {}", traits.join(", ")));
+                    }
+                }
+            }
+        }
+        if vi.t.is_procedure() {
+            info.push("effects: may perform side effects (procedure)".into());
+        } else if vi.t.is_subr() {
+            info.push("effects: none (function)".into());
+        }
+        if vi.t.has_unbound_var() && !vi.t.is_quantified_subr() {
+            let unresolved =
+                vi.t.qvars()
+                    .into_iter()
+                    .map(|(name, constraint)| format!("`?{name}{constraint}`"))
+                    .collect::<Vec<_>>();
+            if !unresolved.is_empty() {
+                info.push(format!("not yet inferred: {}", unresolved.join(", ")));
+            }
+        }
+        if !info.is_empty() {
+            contents.push(MarkedString::from_markdown(info.join("  \n")));
+        }
+        Ok(())
+    }
+
     pub(crate) fn show_doc_comment(
         &self,
         var_token: Option<Token>,
@@ -249,11 +318,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     next!(def_pos, default_code_block, contents, prev_token, token);
                 }
                 if token.is(TokenKind::DocComment) {
-                    let code_block = token
-                        .content
-                        .trim_start_matches("'''")
-                        .trim_end_matches("'''")
-                        .to_string();
+                    let code_block = DocComment::content_of(&token);
                     let lang = lang_code(&code_block);
                     if lang.matches_feature() {
                         let code_block = trim_eliminate_top_indent(
@@ -0,0 +1,154 @@
+use erg_common::Str;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::{ClassDef, Expr};
+use erg_compiler::varinfo::AbsLocation;
+
+use lsp_types::request::{GotoImplementationParams, GotoImplementationResponse};
+use lsp_types::Location;
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+/// The type that owns the member under the cursor
+enum Owner {
+    /// the fully qualified name of the trait
+    Trait(Str),
+    Class(AbsLocation),
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_goto_implementation(
+        &mut self,
+        params: GotoImplementationParams,
+    ) -> ELSResult<Option<GotoImplementationResponse>> {
+        send_log(format!("implementation requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(&uri, &token)? else {
+            return Ok(None);
+        };
+        // `T = Trait { ... }` or `C = Class { ... }` => all classes implementing/inheriting it
+        if vi.t.is_class_type() || vi.t.is_trait_type() {
+            let impls = self.get_type_impls(&vi.def_loc);
+            return Ok(Some(GotoImplementationResponse::Array(impls)));
+        }
+        // `.f = ...` in a trait/class => all methods implementing/overriding it
+        let Some(owner) = self.get_owner_type(&vi.def_loc) else {
+            return Ok(None);
+        };
+        let mut impls = vec![];
+        let mut classes = vec![];
+        let mut ancestors = vec![owner];
+        let class_defs = self.collect_class_defs();
+        while let Some(owner) = ancestors.pop() {
+            for class_def in class_defs.iter() {
+                let impl_of_owner = match &owner {
+                    Owner::Trait(name) => Self::defines_impl_of(class_def, token.inspect(), name),
+                    Owner::Class(loc) => class_def
+                        .require_or_sup
+                        .as_ref()
+                        .and_then(|sup| sup.var_info())
+                        .is_some_and(|vi| &vi.def_loc == loc),
+                };
+                if !impl_of_owner {
+                    continue;
+                }
+                let class_loc = class_def.sig.ident().vi.def_loc.clone();
+                if classes.contains(&class_loc) {
+                    continue;
+                }
+                for method in class_def.methods.iter() {
+                    let Expr::Def(def) = method else {
+                        continue;
+                    };
+                    if def.sig.ident().inspect() == token.inspect() {
+                        impls.extend(self.abs_loc_to_lsp_loc(&def.sig.ident().vi.def_loc));
+                    }
+                }
+                classes.push(class_loc.clone());
+                // methods of subclasses may override the implementation
+                ancestors.push(Owner::Class(class_loc));
+            }
+        }
+        Ok(Some(GotoImplementationResponse::Array(impls)))
+    }
+
+    /// Classes that implement the trait or inherit the class defined at `type_loc`.
+    pub(crate) fn get_type_impls(&self, type_loc: &AbsLocation) -> Vec<Location> {
+        let refs = self.get_refs_from_abs_loc(type_loc);
+        let filter = |loc: Location| {
+            let uri = NormalizedUrl::new(loc.uri.clone());
+            let token = self.file_cache.get_token(&uri, loc.range.start)?;
+            let opt_visitor = self.get_visitor(&uri);
+            let min_expr = opt_visitor
+                .as_ref()
+                .and_then(|visitor| visitor.get_min_expr(&token))?;
+            match min_expr {
+                // exclude the methods blocks of the type itself
+                Expr::ClassDef(class_def) if &class_def.sig.ident().vi.def_loc != type_loc => {
+                    Some(loc)
+                }
+                _ => None,
+            }
+        };
+        refs.into_iter().filter_map(filter).collect()
+    }
+
+    fn get_owner_type(&self, def_loc: &AbsLocation) -> Option<Owner> {
+        let uri = NormalizedUrl::from_file_path(def_loc.module.as_ref()?).ok()?;
+        let pos = util::loc_to_pos(def_loc.loc)?;
+        let hir = self.analysis_result.get_hir(&uri)?;
+        for chunk in hir.module.iter() {
+            if !util::pos_in_loc(chunk, pos) {
+                continue;
+            }
+            match chunk {
+                Expr::Def(def) if def.def_kind().is_trait() => {
+                    let module = self.modules.get(&uri)?;
+                    let trait_ = module.context.get_type_by_name(def.sig.ident().inspect())?;
+                    return Some(Owner::Trait(trait_.qual_name()));
+                }
+                Expr::ClassDef(class_def) => {
+                    return Some(Owner::Class(class_def.sig.ident().vi.def_loc.clone()));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn defines_impl_of(class_def: &ClassDef, method_name: &str, trait_name: &str) -> bool {
+        class_def.methods.iter().any(|method| match method {
+            Expr::Def(def) => {
+                let ident = def.sig.ident();
+                &ident.inspect()[..] == method_name
+                    && ident
+                        .vi
+                        .impl_of
+                        .as_ref()
+                        .is_some_and(|t| &t.qual_name()[..] == trait_name)
+            }
+            _ => false,
+        })
+    }
+
+    fn collect_class_defs(&self) -> Vec<ClassDef> {
+        let mut class_defs = vec![];
+        for uri in self.analysis_result.entries() {
+            let Some(hir) = self.analysis_result.get_hir(&uri) else {
+                continue;
+            };
+            for chunk in hir.module.iter() {
+                if let Expr::ClassDef(class_def) = chunk {
+                    class_defs.push(class_def.clone());
+                }
+            }
+        }
+        class_defs
+    }
+}
@@ -8,10 +8,10 @@ use serde_json::Value;
 use erg_common::dict::Dict;
 use erg_common::error::Location;
 use erg_common::traits::NoTypeDisplay;
-use erg_common::traits::{Locational, Runnable, Stream};
+use erg_common::traits::{LimitedDisplay, Locational, Runnable, Stream};
 use erg_compiler::artifact::{BuildRunnable, IncompleteArtifact};
 use erg_compiler::hir::{Block, Call, ClassDef, Def, Expr, Lambda, Params, PatchDef, Signature};
-use erg_compiler::ty::HasType;
+use erg_compiler::ty::{HasType, Type};
 use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Position};
 
 use crate::server::{send, send_log, ELSResult, Server};
@@ -33,6 +33,28 @@ fn anot(ln: u32, col: u32, cont: String) -> InlayHint {
     }
 }
 
+/// Server-side settings for inlay hints, passed as `initializationOptions.inlayHints`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InlayHintOptions {
+    /// Show parameter names at call sites (`f x:= 1`)
+    pub parameter_names: bool,
+    /// Show the type bounds of generic subroutines (`f|T <: Add|`)
+    pub type_bounds: bool,
+    /// Nesting depth at which type hints are abbreviated (`...`). Negative values mean no limit.
+    pub max_type_depth: isize,
+}
+
+impl Default for InlayHintOptions {
+    fn default() -> Self {
+        Self {
+            parameter_names: true,
+            type_bounds: true,
+            max_type_depth: 10,
+        }
+    }
+}
+
 fn type_anot<D: std::fmt::Display>(ln_end: u32, col_end: u32, ty: D, return_t: bool) -> InlayHint {
     let position = Position::new(ln_end - 1, col_end);
     let string = if return_t {
@@ -117,6 +139,12 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         }
     }
 
+    fn fmt_type(&self, t: &Type) -> String {
+        let mut s = String::new();
+        let _ = t.limited_fmt(&mut s, self.settings.borrow().inlay_hints.max_type_depth);
+        s
+    }
+
     fn get_param_hint(&self, params: &Params) -> Vec<InlayHint> {
         let mut result = vec![];
         for nd_param in params.non_defaults.iter() {
@@ -126,7 +154,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             let (Some(ln_end), Some(col_end)) = (nd_param.ln_end(), nd_param.col_end()) else {
                 continue;
             };
-            let hint = type_anot(ln_end, col_end, &nd_param.vi.t, false);
+            let hint = type_anot(ln_end, col_end, self.fmt_type(&nd_param.vi.t), false);
             result.push(hint);
         }
         if let Some(var_params) = &params.var_params {
@@ -134,7 +162,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 return result;
             }
             if let (Some(ln_end), Some(col_end)) = (var_params.ln_end(), var_params.col_end()) {
-                let hint = type_anot(ln_end, col_end, &var_params.vi.t, false);
+                let hint = type_anot(ln_end, col_end, self.fmt_type(&var_params.vi.t), false);
                 result.push(hint);
             }
         }
@@ -145,7 +173,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             let (Some(ln_end), Some(col_end)) = (d_param.sig.ln_end(), d_param.sig.col_end()) else {
                 continue;
             };
-            let hint = type_anot(ln_end, col_end, &d_param.sig.vi.t, false);
+            let hint = type_anot(ln_end, col_end, self.fmt_type(&d_param.sig.vi.t), false);
             result.push(hint);
         }
         result
@@ -155,7 +183,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let mut result = vec![];
         result.extend(self.get_block_hint(&def.body.block));
         let Signature::Subr(subr) = &def.sig else { unreachable!() };
-        if subr.ref_t().is_quantified_subr() && subr.bounds.is_empty() {
+        if self.settings.borrow().inlay_hints.type_bounds
+            && subr.ref_t().is_quantified_subr()
+            && subr.bounds.is_empty()
+        {
             let subr = subr.ref_t().to_string();
             let ty_bounds = format!("|{}|", subr.split('|').nth(1).unwrap_or(""));
             let ident = def.sig.ident();
@@ -170,6 +201,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 return result;
             };
             if let Some((ln, col)) = def.sig.ln_end().zip(def.sig.col_end()) {
+                let return_t = self.fmt_type(&return_t);
                 let hint = type_anot(ln, col, return_t, subr.params.parens.is_none());
                 result.push(hint);
             }
@@ -188,7 +220,8 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         // don't show hints for compiler internal variables
         if def.sig.t_spec().is_none() && !def.sig.ident().inspect().starts_with(['%']) {
             if let Some((ln, col)) = def.sig.ln_begin().zip(def.sig.col_end()) {
-                let hint = type_anot(ln, col, def.sig.ident().ref_t(), false);
+                let t = self.fmt_type(def.sig.ident().ref_t());
+                let hint = type_anot(ln, col, t, false);
                 result.push(hint);
             }
         }
@@ -211,6 +244,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .zip(lambda.params.col_end())
             .zip(lambda.ref_t().return_t())
         {
+            let return_t = self.fmt_type(&return_t);
             let hint = type_anot(ln, col, return_t, lambda.params.parens.is_none());
             result.push(hint);
         }
@@ -245,8 +279,11 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         for (i, pos_arg) in call.args.pos_args.iter().enumerate() {
             let arg_is_lambda = matches!(&pos_arg.expr, Expr::Lambda(_));
             result.extend(self.get_expr_hint(&pos_arg.expr));
+            if !self.settings.borrow().inlay_hints.parameter_names {
+                continue;
+            }
             let index = if is_method { i + 1 } else { i };
-            if let Some(name) = param_ts.clone().nth(index).and_then(|pt| pt.name()) {
+            if let Some(name) = param_ts.get(index).and_then(|pt| pt.name()) {
                 if name.starts_with(['%']) {
                     continue;
                 }
@@ -1,3 +1,4 @@
+mod call_hierarchy;
 mod channels;
 mod code_action;
 mod code_lens;
@@ -7,14 +8,24 @@ mod definition;
 mod diagnostics;
 mod diff;
 mod file_cache;
+mod folding_range;
+mod formatting;
 mod hir_visitor;
 mod hover;
+mod implementation;
 mod inlay_hint;
 mod message;
+mod notebook;
+mod progress;
+mod pull_diagnostics;
 mod references;
 mod rename;
+mod selection_range;
 mod semantic;
 mod server;
+mod settings;
 mod sig_help;
+mod symbol;
+mod type_hierarchy;
 mod util;
 pub use server::*;
@@ -0,0 +1,342 @@
+use serde::Deserialize;
+
+use erg_common::dict::Dict;
+use erg_common::shared::Shared;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{
+    Diagnostic, Position, Range, TextDocumentContentChangeEvent, TextDocumentItem, Url,
+    VersionedTextDocumentIdentifier,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+// The notebook document synchronization was added in LSP 3.17 and is not defined in `lsp_types` 0.93.
+
+/// `1`: Markup, `2`: Code
+pub const CODE_CELL: u8 = 2;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCell {
+    pub kind: u8,
+    pub document: Url,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookDocument {
+    pub uri: Url,
+    pub version: i32,
+    pub cells: Vec<NotebookCell>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidOpenNotebookDocumentParams {
+    pub notebook_document: NotebookDocument,
+    pub cell_text_documents: Vec<TextDocumentItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedNotebookDocumentIdentifier {
+    pub version: i32,
+    pub uri: Url,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookDocumentIdentifier {
+    pub uri: Url,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCellArrayChange {
+    pub start: u32,
+    pub delete_count: u32,
+    pub cells: Option<Vec<NotebookCell>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCellStructureChange {
+    pub array: NotebookCellArrayChange,
+    pub did_open: Option<Vec<TextDocumentItem>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCellTextChange {
+    pub document: VersionedTextDocumentIdentifier,
+    pub changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookCellChanges {
+    pub structure: Option<NotebookCellStructureChange>,
+    pub data: Option<Vec<NotebookCell>>,
+    pub text_content: Option<Vec<NotebookCellTextChange>>,
+}
+
+/// The changes of the notebook metadata are ignored
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotebookDocumentChangeEvent {
+    pub cells: Option<NotebookCellChanges>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeNotebookDocumentParams {
+    pub notebook_document: VersionedNotebookDocumentIdentifier,
+    pub change: NotebookDocumentChangeEvent,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidSaveNotebookDocumentParams {
+    pub notebook_document: NotebookDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidCloseNotebookDocumentParams {
+    pub notebook_document: NotebookDocumentIdentifier,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub uri: Url,
+    pub kind: u8,
+    pub language_id: String,
+    pub code: String,
+}
+
+impl Cell {
+    fn new(cell: NotebookCell, opened: &[TextDocumentItem]) -> Self {
+        let item = opened.iter().find(|item| item.uri == cell.document);
+        Self {
+            kind: cell.kind,
+            language_id: item.map_or("erg".into(), |item| item.language_id.clone()),
+            code: item.map_or(String::new(), |item| item.text.clone()),
+            uri: cell.document,
+        }
+    }
+
+    pub fn is_erg_code(&self) -> bool {
+        self.kind == CODE_CELL && self.language_id == "erg"
+    }
+
+    fn apply_changes(&mut self, changes: Vec<TextDocumentContentChangeEvent>) {
+        for change in changes {
+            if let Some(range) = change.range {
+                let start = util::pos_to_byte_index(&self.code, range.start);
+                let end = util::pos_to_byte_index(&self.code, range.end);
+                self.code.replace_range(start..end, &change.text);
+            } else {
+                self.code = change.text;
+            }
+        }
+    }
+}
+
+/// An opened notebook. The Erg cells are concatenated into a virtual module (in order),
+/// so that a cell can refer to the variables defined in the preceding cells.
+#[derive(Debug, Clone)]
+pub struct Notebook {
+    pub version: i32,
+    pub cells: Vec<Cell>,
+}
+
+impl Notebook {
+    pub fn erg_cells(&self) -> impl Iterator<Item = &Cell> {
+        self.cells.iter().filter(|cell| cell.is_erg_code())
+    }
+
+    /// Returns the cells and the lines of the virtual module at which they start.
+    fn cell_lines(&self) -> Vec<(&Cell, u32)> {
+        let mut line = 0;
+        let mut result = vec![];
+        for cell in self.erg_cells() {
+            result.push((cell, line));
+            line += cell.code.lines().count().max(1) as u32;
+        }
+        result
+    }
+
+    pub fn virtual_module(&self) -> String {
+        let mut code = String::new();
+        for cell in self.erg_cells() {
+            let lines = cell.code.lines().collect::<Vec<_>>();
+            if lines.is_empty() {
+                code.push('\n');
+            }
+            for line in lines {
+                code.push_str(line);
+                code.push('\n');
+            }
+        }
+        code
+    }
+
+    /// Maps the diagnostics of the virtual module back to the cells.
+    /// Cells with no diagnostics are also included (so that the old diagnostics are cleared).
+    pub fn split_diagnostics(&self, diags: Vec<Diagnostic>) -> Vec<(Url, Vec<Diagnostic>)> {
+        let cell_lines = self.cell_lines();
+        let mut result = cell_lines
+            .iter()
+            .map(|(cell, _)| (cell.uri.clone(), vec![]))
+            .collect::<Vec<_>>();
+        for mut diag in diags {
+            let Some(idx) = cell_lines
+                .iter()
+                .rposition(|(_, start)| *start <= diag.range.start.line)
+            else {
+                continue;
+            };
+            let start = cell_lines[idx].1;
+            diag.range = Range::new(
+                Position::new(diag.range.start.line - start, diag.range.start.character),
+                Position::new(
+                    diag.range.end.line.saturating_sub(start),
+                    diag.range.end.character,
+                ),
+            );
+            result[idx].1.push(diag);
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NotebookCache(Shared<Dict<NormalizedUrl, Notebook>>);
+
+impl NotebookCache {
+    pub fn new() -> Self {
+        Self(Shared::new(Dict::new()))
+    }
+
+    pub fn get(&self, uri: &NormalizedUrl) -> Option<Notebook> {
+        self.0.borrow().get(uri).cloned()
+    }
+
+    pub fn insert(&self, uri: NormalizedUrl, notebook: Notebook) {
+        self.0.borrow_mut().insert(uri, notebook);
+    }
+
+    pub fn remove(&self, uri: &NormalizedUrl) -> Option<Notebook> {
+        self.0.borrow_mut().remove(uri)
+    }
+
+    /// Returns the notebook containing the cell.
+    pub fn owner_of(&self, cell: &Url) -> Option<(NormalizedUrl, Notebook)> {
+        self.0
+            .borrow()
+            .iter()
+            .find(|(_, notebook)| notebook.cells.iter().any(|c| &c.uri == cell))
+            .map(|(uri, notebook)| (uri.clone(), notebook.clone()))
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_did_open_notebook(
+        &mut self,
+        params: DidOpenNotebookDocumentParams,
+    ) -> ELSResult<()> {
+        let uri = NormalizedUrl::new(params.notebook_document.uri);
+        send_log(format!("notebook opened: {uri}"))?;
+        let cells = params
+            .notebook_document
+            .cells
+            .into_iter()
+            .map(|cell| Cell::new(cell, &params.cell_text_documents))
+            .collect();
+        let notebook = Notebook {
+            version: params.notebook_document.version,
+            cells,
+        };
+        let code = notebook.virtual_module();
+        self.notebooks.insert(uri.clone(), notebook);
+        self.file_cache
+            .update(&uri, code.clone(), Some(params.notebook_document.version));
+        self.check_file(uri, code)
+    }
+
+    /// The virtual module is rechecked by the auto diagnostics.
+    pub(crate) fn handle_did_change_notebook(
+        &mut self,
+        params: DidChangeNotebookDocumentParams,
+    ) -> ELSResult<()> {
+        let uri = NormalizedUrl::new(params.notebook_document.uri);
+        let Some(mut notebook) = self.notebooks.get(&uri) else {
+            return send_log(format!("notebook not opened: {uri}"));
+        };
+        if let Some(changes) = params.change.cells {
+            if let Some(structure) = changes.structure {
+                let opened = structure.did_open.unwrap_or_default();
+                let new_cells = structure
+                    .array
+                    .cells
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|cell| Cell::new(cell, &opened));
+                let start = (structure.array.start as usize).min(notebook.cells.len());
+                let end = (start + structure.array.delete_count as usize).min(notebook.cells.len());
+                notebook.cells.splice(start..end, new_cells);
+            }
+            for data in changes.data.unwrap_or_default() {
+                if let Some(cell) = notebook.cells.iter_mut().find(|c| c.uri == data.document) {
+                    cell.kind = data.kind;
+                }
+            }
+            for text in changes.text_content.unwrap_or_default() {
+                if let Some(cell) = notebook
+                    .cells
+                    .iter_mut()
+                    .find(|c| c.uri == text.document.uri)
+                {
+                    cell.apply_changes(text.changes);
+                }
+            }
+        }
+        notebook.version = params.notebook_document.version;
+        let code = notebook.virtual_module();
+        self.notebooks.insert(uri.clone(), notebook);
+        self.file_cache
+            .update(&uri, code, Some(params.notebook_document.version));
+        Ok(())
+    }
+
+    pub(crate) fn handle_did_save_notebook(
+        &mut self,
+        params: DidSaveNotebookDocumentParams,
+    ) -> ELSResult<()> {
+        let uri = NormalizedUrl::new(params.notebook_document.uri);
+        send_log(format!("notebook saved: {uri}"))?;
+        let code = self.file_cache.get_entire_code(&uri)?;
+        self.clear_cache(&uri);
+        self.check_file(uri, code)
+    }
+
+    pub(crate) fn handle_did_close_notebook(
+        &mut self,
+        params: DidCloseNotebookDocumentParams,
+    ) -> ELSResult<()> {
+        let uri = NormalizedUrl::new(params.notebook_document.uri);
+        send_log(format!("notebook closed: {uri}"))?;
+        if let Some(notebook) = self.notebooks.get(&uri) {
+            // clear the diagnostics of the cells
+            for (cell, _) in notebook.split_diagnostics(vec![]) {
+                self.send_diagnostics(cell, vec![])?;
+            }
+        }
+        self.notebooks.remove(&uri);
+        self.file_cache.remove(&uri);
+        Ok(())
+    }
+}
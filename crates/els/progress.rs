@@ -0,0 +1,87 @@
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::notification::{Notification, Progress};
+use lsp_types::request::{Request, WorkDoneProgressCreate};
+use lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, ProgressToken, WorkDoneProgress,
+    WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+    WorkDoneProgressReport,
+};
+use serde_json::json;
+
+use crate::server::{next_server_request_id, send, ELSResult, Server};
+
+/// A progress bar shown in the client while a long operation is running.
+/// The progress ends when this is dropped.
+#[derive(Debug)]
+pub(crate) struct ProgressReporter {
+    /// `None` if the client does not support work done progress
+    token: Option<ProgressToken>,
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        let _ = self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }));
+    }
+}
+
+impl ProgressReporter {
+    fn send(&self, value: WorkDoneProgress) -> ELSResult<()> {
+        let Some(token) = self.token.clone() else {
+            return Ok(());
+        };
+        let params = ProgressParams {
+            token,
+            value: ProgressParamsValue::WorkDone(value),
+        };
+        send(&json!({
+            "jsonrpc": "2.0",
+            "method": Progress::METHOD,
+            "params": params,
+        }))
+    }
+
+    pub(crate) fn report<S: Into<String>>(&self, message: S, percentage: u32) -> ELSResult<()> {
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(message.into()),
+            percentage: Some(percentage.min(100)),
+        }))
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// Creates a work done progress (`window/workDoneProgress/create`) and begins it.
+    pub(crate) fn start_progress<S: Into<String>>(
+        &self,
+        title: S,
+        message: Option<String>,
+    ) -> ELSResult<ProgressReporter> {
+        let supported = self
+            .client_capas
+            .window
+            .as_ref()
+            .and_then(|window| window.work_done_progress)
+            .unwrap_or(false);
+        if !supported {
+            return Ok(ProgressReporter { token: None });
+        }
+        let id = next_server_request_id();
+        let token = NumberOrString::String(format!("erg/progress/{id}"));
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": WorkDoneProgressCreate::METHOD,
+            "params": WorkDoneProgressCreateParams { token: token.clone() },
+        }))?;
+        let reporter = ProgressReporter { token: Some(token) };
+        reporter.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.into(),
+            cancellable: Some(false),
+            message,
+            percentage: Some(0),
+        }))?;
+        Ok(reporter)
+    }
+}
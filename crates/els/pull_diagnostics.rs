@@ -0,0 +1,204 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::request::Request;
+use lsp_types::{
+    Diagnostic, PartialResultParams, TextDocumentIdentifier, Url, WorkDoneProgressParams,
+};
+
+use crate::notebook::Notebook;
+use crate::server::{next_server_request_id, send, send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+// The pull diagnostics requests were added in LSP 3.17 and are not defined in `lsp_types` 0.93.
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    pub text_document: TextDocumentIdentifier,
+    pub identifier: Option<String>,
+    pub previous_result_id: Option<String>,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullDocumentDiagnosticReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub items: Vec<Diagnostic>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnchangedDocumentDiagnosticReport {
+    pub result_id: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DocumentDiagnosticReportKind {
+    Full(FullDocumentDiagnosticReport),
+    Unchanged(UnchangedDocumentDiagnosticReport),
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticReport {
+    #[serde(flatten)]
+    pub report: DocumentDiagnosticReportKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_documents: Option<HashMap<Url, DocumentDiagnosticReportKind>>,
+}
+
+#[derive(Debug)]
+pub enum DocumentDiagnosticRequest {}
+
+impl Request for DocumentDiagnosticRequest {
+    type Params = DocumentDiagnosticParams;
+    type Result = DocumentDiagnosticReport;
+    const METHOD: &'static str = "textDocument/diagnostic";
+}
+
+/// `textDocument.diagnostic` and `workspace.diagnostics` of the client capabilities
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiagnosticClientCapabilities {
+    /// If `true`, the diagnostics are not pushed (`textDocument/publishDiagnostics`)
+    pub pull: bool,
+    pub refresh_support: bool,
+}
+
+impl From<&Value> for DiagnosticClientCapabilities {
+    fn from(capabilities: &Value) -> Self {
+        Self {
+            pull: capabilities["textDocument"].get("diagnostic").is_some(),
+            refresh_support: capabilities["workspace"]["diagnostics"]["refreshSupport"]
+                .as_bool()
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// The same diagnostics have the same result ID.
+fn result_id(diags: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diags)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+fn full_report(items: Vec<Diagnostic>) -> DocumentDiagnosticReportKind {
+    DocumentDiagnosticReportKind::Full(FullDocumentDiagnosticReport {
+        result_id: Some(result_id(&items)),
+        items,
+    })
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// The diagnostics of the modules importing the document are also reported (`relatedDocuments`),
+    /// so that fixing a module (e.g. `foo.d.er`) clears stale errors in its importers.
+    pub(crate) fn handle_document_diagnostic(
+        &mut self,
+        params: DocumentDiagnosticParams,
+    ) -> ELSResult<DocumentDiagnosticReport> {
+        send_log(format!("document diagnostic requested: {params:?}"))?;
+        if let Some((uri, notebook)) = self.notebooks.owner_of(&params.text_document.uri) {
+            return Ok(self.get_cell_diagnostics(&uri, &notebook, &params));
+        }
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        if self.analysis_result.get(&uri).is_none() {
+            let code = self.file_cache.get_entire_code(&uri)?;
+            self.check_file(uri.clone(), code)?;
+        }
+        let items = self.get_diagnostics(&uri);
+        let related_documents = self
+            .dependents_of(&uri)
+            .into_iter()
+            .map(|dep| {
+                let items = self.get_diagnostics(&dep);
+                (dep.raw(), full_report(items))
+            })
+            .collect::<HashMap<_, _>>();
+        let related_documents = (!related_documents.is_empty()).then_some(related_documents);
+        let id = result_id(&items);
+        let report = if params.previous_result_id.as_ref() == Some(&id) {
+            DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport {
+                result_id: id,
+            })
+        } else {
+            full_report(items)
+        };
+        Ok(DocumentDiagnosticReport {
+            report,
+            related_documents,
+        })
+    }
+
+    fn get_cell_diagnostics(
+        &mut self,
+        uri: &NormalizedUrl,
+        notebook: &Notebook,
+        params: &DocumentDiagnosticParams,
+    ) -> DocumentDiagnosticReport {
+        let items = notebook
+            .split_diagnostics(self.get_diagnostics(uri))
+            .into_iter()
+            .find(|(cell, _)| cell == &params.text_document.uri)
+            .map(|(_, diags)| diags)
+            .unwrap_or_default();
+        let id = result_id(&items);
+        let report = if params.previous_result_id.as_ref() == Some(&id) {
+            DocumentDiagnosticReportKind::Unchanged(UnchangedDocumentDiagnosticReport {
+                result_id: id,
+            })
+        } else {
+            full_report(items)
+        };
+        DocumentDiagnosticReport {
+            report,
+            related_documents: None,
+        }
+    }
+
+    /// The last analysis results (errors and warnings) of the document.
+    fn get_diagnostics(&mut self, uri: &NormalizedUrl) -> Vec<Diagnostic> {
+        let Some(errors) = self.analysis_result.get_artifact(uri).map(|artifact| {
+            artifact
+                .errors
+                .clone()
+                .into_iter()
+                .chain(artifact.warns.clone())
+                .collect()
+        }) else {
+            return vec![];
+        };
+        self.make_uri_and_diags(errors)
+            .into_iter()
+            .filter(|(res_uri, _)| NormalizedUrl::new(res_uri.clone()) == *uri)
+            .flat_map(|(_, diags)| diags)
+            .collect()
+    }
+
+    /// Asks the client to pull the diagnostics again (e.g. after the dependents have been rechecked).
+    pub(crate) fn send_diagnostic_refresh(&self) -> ELSResult<()> {
+        if !self.diag_client_capas.pull || !self.diag_client_capas.refresh_support {
+            return Ok(());
+        }
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": next_server_request_id(),
+            "method": "workspace/diagnostic/refresh",
+        }))
+    }
+}
@@ -1,11 +1,15 @@
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::module::RefKind;
 use erg_compiler::varinfo::AbsLocation;
 
-use lsp_types::{Location, Position, ReferenceParams, Url};
+use lsp_types::{
+    DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams, Location, Position,
+    ReferenceParams, Url,
+};
 
 use crate::server::{ELSResult, Server};
-use crate::util::{self, NormalizedUrl};
+use crate::util::NormalizedUrl;
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     pub(crate) fn handle_references(
@@ -14,16 +18,26 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
     ) -> ELSResult<Option<Vec<Location>>> {
         let uri = NormalizedUrl::new(params.text_document_position.text_document.uri);
         let pos = params.text_document_position.position;
-        let result = self.show_refs_inner(&uri, pos);
+        let result = self.show_refs_inner(&uri, pos, params.context.include_declaration);
         Ok(Some(result))
     }
 
-    fn show_refs_inner(&self, uri: &NormalizedUrl, pos: Position) -> Vec<lsp_types::Location> {
+    fn show_refs_inner(
+        &self,
+        uri: &NormalizedUrl,
+        pos: Position,
+        include_declaration: bool,
+    ) -> Vec<lsp_types::Location> {
         if let Some(tok) = self.file_cache.get_token(uri, pos) {
             // send_log(format!("token: {tok}"))?;
             if let Some(visitor) = self.get_visitor(uri) {
                 if let Some(vi) = visitor.get_info(&tok) {
-                    return self.get_refs_from_abs_loc(&vi.def_loc);
+                    let mut refs = vec![];
+                    if include_declaration {
+                        refs.extend(self.abs_loc_to_lsp_loc(&vi.def_loc));
+                    }
+                    refs.extend(self.get_refs_from_abs_loc(&vi.def_loc));
+                    return refs;
                 }
             }
         }
@@ -37,16 +51,69 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 return vec![];
             }
             for referrer in value.referrers.iter() {
-                if let (Some(path), Some(range)) =
-                    (&referrer.module, util::loc_to_range(referrer.loc))
-                {
-                    let Ok(ref_uri) = Url::from_file_path(path) else {
-                        continue;
-                    };
-                    refs.push(lsp_types::Location::new(ref_uri, range));
-                }
+                refs.extend(self.abs_loc_to_lsp_loc(referrer));
             }
         }
         refs
     }
+
+    pub(crate) fn abs_loc_to_lsp_loc(&self, loc: &AbsLocation) -> Option<lsp_types::Location> {
+        let uri = Url::from_file_path(loc.module.as_ref()?).ok()?;
+        let range = self
+            .file_cache
+            .loc_to_range(&NormalizedUrl::new(uri.clone()), loc.loc)?;
+        Some(lsp_types::Location::new(uri, range))
+    }
+
+    /// Highlight the occurrences of the symbol under the cursor in the current document.
+    /// The kind of each occurrence is taken from the usage index built by the lowerer.
+    pub(crate) fn handle_document_highlight(
+        &mut self,
+        params: DocumentHighlightParams,
+    ) -> ELSResult<Option<Vec<DocumentHighlight>>> {
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(tok) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(visitor) = self.get_visitor(&uri) else {
+            return Ok(None);
+        };
+        let Some(vi) = visitor.get_info(&tok) else {
+            return Ok(None);
+        };
+        let mut result = vec![];
+        if let Some(def) = self.abs_loc_to_lsp_loc(&vi.def_loc) {
+            if NormalizedUrl::new(def.uri) == uri {
+                result.push(DocumentHighlight {
+                    range: def.range,
+                    kind: Some(DocumentHighlightKind::WRITE),
+                });
+            }
+        }
+        let Some(value) = self.get_index().and_then(|ind| ind.get_refs(&vi.def_loc)) else {
+            return Ok(Some(result));
+        };
+        if value.vi.def_loc == AbsLocation::unknown() {
+            return Ok(Some(result));
+        }
+        for referrer in value.referrers.iter() {
+            let Some(loc) = self.abs_loc_to_lsp_loc(referrer) else {
+                continue;
+            };
+            if NormalizedUrl::new(loc.uri) != uri {
+                continue;
+            }
+            let kind = match value.ref_kind(referrer) {
+                RefKind::Read => DocumentHighlightKind::READ,
+                RefKind::Write => DocumentHighlightKind::WRITE,
+                RefKind::Import => DocumentHighlightKind::TEXT,
+            };
+            result.push(DocumentHighlight {
+                range: loc.range,
+                kind: Some(kind),
+            });
+        }
+        Ok(Some(result))
+    }
 }
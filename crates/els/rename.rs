@@ -14,15 +14,15 @@ use erg_common::dict::Dict;
 
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::hir::{Expr, Literal};
-use erg_compiler::varinfo::{AbsLocation, VarKind};
+use erg_compiler::varinfo::{AbsLocation, VarInfo, VarKind};
 
 use lsp_types::{
     DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
-    RenameFile, RenameFilesParams, RenameParams, ResourceOp, TextDocumentEdit, TextEdit, Url,
-    WorkspaceEdit,
+    PrepareRenameResponse, RenameFile, RenameFilesParams, RenameParams, ResourceOp,
+    TextDocumentEdit, TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit,
 };
 
-use crate::server::{send, send_error_info, send_log, ELSResult, Server};
+use crate::server::{send, send_error, send_error_info, send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
@@ -38,43 +38,18 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                 .and_then(|visitor| visitor.get_info(&tok))
             {
                 let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
-                let is_std = vi
-                    .def_loc
-                    .module
-                    .as_ref()
-                    .map(|path| path.starts_with(&self.erg_path))
-                    .unwrap_or(false);
-                let kind = if vi.t.is_method() {
-                    "method"
-                } else if vi.t.is_subr() {
-                    "subroutine"
-                } else {
-                    "variable"
-                };
-                if vi.def_loc.loc.is_unknown() || is_std {
-                    let error_reason = match vi.kind {
-                        VarKind::Builtin => {
-                            format!("this is a builtin {kind} and cannot be renamed")
-                        }
-                        VarKind::FixedAuto => {
-                            format!("this is a fixed auto {kind} and cannot be renamed")
-                        }
-                        _ if is_std => {
-                            "this is a standard library API and cannot be renamed".to_string()
-                        }
-                        _ => format!("this {kind} cannot be renamed"),
-                    };
+                if let Err(error_reason) = self.check_renamable(&vi) {
                     let edit = WorkspaceEdit::new(changes);
                     send(
                         &json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": edit }),
                     )?;
                     return send_error_info(error_reason);
                 }
-                Self::commit_change(&mut changes, &vi.def_loc, params.new_name.clone());
+                self.commit_change(&mut changes, &vi.def_loc, params.new_name.clone());
                 if let Some(value) = self.get_index().and_then(|ind| ind.get_refs(&vi.def_loc)) {
                     // send_log(format!("referrers: {referrers:?}"))?;
                     for referrer in value.referrers.iter() {
-                        Self::commit_change(&mut changes, referrer, params.new_name.clone());
+                        self.commit_change(&mut changes, referrer, params.new_name.clone());
                     }
                 }
                 let dependencies = self.dependencies_of(&uri);
@@ -105,7 +80,65 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": Value::Null }))
     }
 
+    /// Validate the rename before the client asks for the new name.
+    /// Builtins, fixed auto variables and standard library APIs are rejected with an error response.
+    pub(crate) fn prepare_rename(&mut self, msg: &Value) -> ELSResult<()> {
+        let id = msg["id"].as_i64().unwrap();
+        let params = TextDocumentPositionParams::deserialize(&msg["params"])?;
+        send_log(format!("prepare rename request: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        if let Some(tok) = self.file_cache.get_token(&uri, params.position) {
+            if let Some(vi) = self
+                .get_visitor(&uri)
+                .and_then(|visitor| visitor.get_info(&tok))
+            {
+                if let Err(error_reason) = self.check_renamable(&vi) {
+                    return send_error(Some(id), -32803, error_reason);
+                }
+                if let Some(range) = self.file_cache.loc_to_range(&uri, tok.loc()) {
+                    let result = PrepareRenameResponse::RangeWithPlaceholder {
+                        range,
+                        placeholder: tok.content.to_string(),
+                    };
+                    return send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+                }
+            }
+        }
+        send(&json!({ "jsonrpc": "2.0", "id": id, "result": Value::Null }))
+    }
+
+    fn check_renamable(&self, vi: &VarInfo) -> Result<(), String> {
+        let is_std = vi
+            .def_loc
+            .module
+            .as_ref()
+            .map(|path| path.starts_with(&self.erg_path))
+            .unwrap_or(false);
+        if !vi.def_loc.loc.is_unknown() && !is_std {
+            return Ok(());
+        }
+        let kind = if vi.t.is_method() {
+            "method"
+        } else if vi.t.is_subr() {
+            "subroutine"
+        } else {
+            "variable"
+        };
+        let error_reason = match vi.kind {
+            VarKind::Builtin => {
+                format!("this is a builtin {kind} and cannot be renamed")
+            }
+            VarKind::FixedAuto => {
+                format!("this is a fixed auto {kind} and cannot be renamed")
+            }
+            _ if is_std => "this is a standard library API and cannot be renamed".to_string(),
+            _ => format!("this {kind} cannot be renamed"),
+        };
+        Err(error_reason)
+    }
+
     fn commit_change(
+        &self,
         changes: &mut HashMap<Url, Vec<TextEdit>>,
         abs_loc: &AbsLocation,
         new_name: String,
@@ -114,7 +147,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             let Ok(def_uri) = Url::from_file_path(path) else {
                 return;
             };
-            let Some(range) = util::loc_to_range(abs_loc.loc) else {
+            let Some(range) = self
+                .file_cache
+                .loc_to_range(&NormalizedUrl::new(def_uri.clone()), abs_loc.loc)
+            else {
                 return;
             };
             let edit = TextEdit::new(range, new_name);
@@ -192,13 +228,16 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let mut changes = HashMap::new();
         for dep in self.dependents_of(old_uri) {
             let imports = self.search_imports(&dep, old_path);
-            let edits = imports.iter().map(|lit| {
-                TextEdit::new(
-                    util::loc_to_range(lit.loc()).unwrap(),
-                    lit.token.content.replace(old_path, new_path),
-                )
-            });
-            changes.insert(dep.raw(), edits.collect());
+            let edits = imports
+                .iter()
+                .filter_map(|lit| {
+                    Some(TextEdit::new(
+                        self.file_cache.loc_to_range(&dep, lit.loc())?,
+                        lit.token.content.replace(old_path, new_path),
+                    ))
+                })
+                .collect();
+            changes.insert(dep.raw(), edits);
         }
         changes
     }
@@ -269,7 +308,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         } else if old_uri.as_str().ends_with(".py") {
             let d_er_file = PathBuf::from(old_uri.as_str().replace(".py", ".d.er"));
             if d_er_file.exists() {
-                let Ok(old_uri) =  Url::from_file_path(&d_er_file) else {
+                let Ok(old_uri) = Url::from_file_path(&d_er_file) else {
                     return;
                 };
                 let Ok(new_uri) = Url::parse(&new_uri.as_str().replace(".py", ".d.er")) else {
@@ -0,0 +1,266 @@
+use erg_common::error::Location;
+use erg_common::traits::{Locational, Runnable, Stream};
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::ast::{
+    Accessor, Args, Array, Block, ClassAttr, Def, Dict, Expr, Methods, Record, Set, Tuple,
+};
+use erg_compiler::erg_parser::build_ast::ASTBuilder;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+fn expr_loc(expr: &Expr) -> Location {
+    match expr {
+        // `BinOp::loc` does not include the left-hand side
+        Expr::BinOp(bin) => Location::concat(&expr_loc(&bin.args[0]), &expr_loc(&bin.args[1])),
+        _ => expr.loc(),
+    }
+}
+
+/// Collects the ranges of the syntax nodes containing `pos`, from the outermost to the innermost.
+struct SelectionChain {
+    pos: Position,
+    ranges: Vec<Range>,
+}
+
+impl SelectionChain {
+    fn new(pos: Position) -> Self {
+        Self {
+            pos,
+            ranges: vec![],
+        }
+    }
+
+    fn push<L: Locational>(&mut self, loc: &L) -> bool {
+        if !util::pos_in_loc(loc, self.pos) {
+            return false;
+        }
+        if let Some(range) = util::loc_to_range(loc.loc()) {
+            if self.ranges.last() != Some(&range) {
+                self.ranges.push(range);
+            }
+        }
+        true
+    }
+
+    fn block(&mut self, block: &Block) -> bool {
+        let (Some(first), Some(last)) = (block.first(), block.last()) else {
+            return false;
+        };
+        if !self.push(&Location::concat(&expr_loc(first), &expr_loc(last))) {
+            return false;
+        }
+        self.exprs(block.iter());
+        true
+    }
+
+    fn exprs<'e>(&mut self, exprs: impl IntoIterator<Item = &'e Expr>) {
+        for expr in exprs {
+            if self.expr(expr) {
+                break;
+            }
+        }
+    }
+
+    fn def(&mut self, def: &Def) -> bool {
+        if !self.push(def) {
+            return false;
+        }
+        let _ = self.push(&def.sig) || self.block(&def.body.block);
+        true
+    }
+
+    fn methods(&mut self, methods: &Methods) -> bool {
+        if !self.push(methods) {
+            return false;
+        }
+        for attr in methods.attrs.iter() {
+            let found = match attr {
+                ClassAttr::Def(def) => self.def(def),
+                ClassAttr::Decl(decl) => self.push(decl),
+                ClassAttr::Doc(doc) => self.push(doc),
+            };
+            if found {
+                break;
+            }
+        }
+        true
+    }
+
+    fn expr(&mut self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Def(def) => return self.def(def),
+            Expr::Methods(methods) => return self.methods(methods),
+            Expr::ClassDef(class_def) => {
+                if !self.push(class_def) {
+                    return false;
+                }
+                if !self.def(&class_def.def) {
+                    for methods in class_def.methods_list.iter() {
+                        if self.methods(methods) {
+                            break;
+                        }
+                    }
+                }
+                return true;
+            }
+            Expr::PatchDef(patch_def) => {
+                if !self.push(patch_def) {
+                    return false;
+                }
+                if !self.def(&patch_def.def) {
+                    for methods in patch_def.methods_list.iter() {
+                        if self.methods(methods) {
+                            break;
+                        }
+                    }
+                }
+                return true;
+            }
+            _ => {}
+        }
+        if !self.push(&expr_loc(expr)) {
+            return false;
+        }
+        match expr {
+            Expr::Accessor(Accessor::Attr(attr)) => {
+                self.expr(&attr.obj);
+            }
+            Expr::Accessor(Accessor::TupleAttr(attr)) => {
+                self.expr(&attr.obj);
+            }
+            Expr::Accessor(Accessor::Subscr(subscr)) => {
+                let _ = self.expr(&subscr.obj) || self.expr(&subscr.index);
+            }
+            Expr::Call(call) => {
+                let _ = self.expr(&call.obj) || self.args(&call.args);
+            }
+            Expr::BinOp(bin) => {
+                self.exprs(bin.args.iter().map(|arg| arg.as_ref()));
+            }
+            Expr::UnaryOp(unary) => {
+                self.exprs(unary.args.iter().map(|arg| arg.as_ref()));
+            }
+            Expr::Lambda(lambda) => {
+                let _ = self.push(&lambda.sig) || self.block(&lambda.body);
+            }
+            Expr::TypeAscription(tasc) => {
+                self.expr(&tasc.expr);
+            }
+            Expr::Array(Array::Normal(arr)) => {
+                self.args(&arr.elems);
+            }
+            Expr::Tuple(Tuple::Normal(tup)) => {
+                self.args(&tup.elems);
+            }
+            Expr::Set(Set::Normal(set)) => {
+                self.args(&set.elems);
+            }
+            Expr::Dict(Dict::Normal(dict)) => {
+                for kv in dict.kvs.iter() {
+                    if self.push(&Location::concat(&kv.key, &kv.value)) {
+                        let _ = self.expr(&kv.key) || self.expr(&kv.value);
+                        break;
+                    }
+                }
+            }
+            Expr::Record(rec) => {
+                self.record(rec);
+            }
+            Expr::DataPack(pack) => {
+                let _ = self.expr(&pack.class) || self.record(&pack.args);
+            }
+            Expr::ReDef(redef) => {
+                self.expr(&redef.expr);
+            }
+            Expr::Dummy(dummy) => {
+                self.exprs(dummy.exprs.iter());
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn record(&mut self, record: &Record) -> bool {
+        if !self.push(record) {
+            return false;
+        }
+        if let Record::Normal(rec) = record {
+            for attr in rec.attrs.iter() {
+                if self.def(attr) {
+                    break;
+                }
+            }
+        }
+        true
+    }
+
+    fn args(&mut self, args: &Args) -> bool {
+        for arg in args.pos_args() {
+            if self.expr(&arg.expr) {
+                return true;
+            }
+        }
+        for arg in args.kw_args() {
+            if self.push(arg) {
+                self.expr(&arg.expr);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    /// token -> expression -> statement -> block -> definition -> ...
+    pub(crate) fn handle_selection_range(
+        &mut self,
+        params: SelectionRangeParams,
+    ) -> ELSResult<Option<Vec<SelectionRange>>> {
+        send_log(format!("selection range requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let path = util::uri_to_path(&uri);
+        let code = self.file_cache.get_entire_code(&uri)?;
+        let mut builder = ASTBuilder::new(self.cfg.inherit(path));
+        let ast = match builder.build_without_desugaring(code.clone()) {
+            Ok(artifact) => Some(artifact.ast),
+            Err(artifact) => artifact.ast,
+        };
+        let mut result = vec![];
+        for pos in params.positions {
+            let mut chain = SelectionChain::new(pos);
+            if let Some(ast) = ast.as_ref() {
+                chain.exprs(ast.module.iter());
+            }
+            // the file cannot be parsed, so the whole line is selected
+            if let (true, Some(line)) =
+                (chain.ranges.is_empty(), code.lines().nth(pos.line as usize))
+            {
+                let end = Position::new(pos.line, line.encode_utf16().count() as u32);
+                chain
+                    .ranges
+                    .push(Range::new(Position::new(pos.line, 0), end));
+            }
+            if let Some(token) = self.file_cache.get_token(&uri, pos) {
+                chain.push(&token);
+            }
+            let mut selection = None;
+            for range in chain.ranges {
+                selection = Some(SelectionRange {
+                    range,
+                    parent: selection.map(Box::new),
+                });
+            }
+            let empty = SelectionRange {
+                range: Range::new(pos, pos),
+                parent: None,
+            };
+            result.push(selection.unwrap_or(empty));
+        }
+        Ok(Some(result))
+    }
+}
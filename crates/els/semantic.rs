@@ -1,38 +1,90 @@
 use erg_common::dict::Dict;
 use erg_common::error::Location;
+use erg_common::grapheme::col_to_utf16;
 use erg_common::traits::{Locational, Runnable};
 
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::ast::{
     Accessor, Args, BinOp, Block, Call, ClassAttr, Def, DefKind, Expr, Identifier, Methods, Params,
-    PolyTypeSpec, PreDeclTypeSpec, TypeSpec, UnaryOp, AST,
+    PolyTypeSpec, PreDeclTypeSpec, StrInterp, TypeSpec, UnaryOp, AST,
 };
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::erg_parser::token::TokenKind;
 use erg_compiler::ASTBuilder;
 
+use erg_compiler::erg_parser::token::Token;
+use erg_compiler::ty::Type;
+use erg_compiler::varinfo::VarInfo;
+
 use lsp_types::{
-    SemanticToken, SemanticTokenType, SemanticTokens, SemanticTokensParams, SemanticTokensResult,
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensResult,
 };
 
+use crate::hir_visitor::HIRVisitor;
 use crate::server::{send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
-#[derive(Debug)]
-struct ASTSemanticState {
+pub(crate) const TOKEN_TYPES: [SemanticTokenType; 13] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::OPERATOR,
+];
+
+/// `x!`, `C!` (mutable variables/types)
+const MUTABLE: SemanticTokenModifier = SemanticTokenModifier::new("mutable");
+/// `f!` (procedures, procedural methods)
+const PROCEDURE: SemanticTokenModifier = SemanticTokenModifier::new("procedure");
+
+pub(crate) const TOKEN_MODIFIERS: [SemanticTokenModifier; 3] =
+    [MUTABLE, PROCEDURE, SemanticTokenModifier::DEFAULT_LIBRARY];
+
+pub(crate) fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+struct ASTSemanticState<'a> {
     prev_line: u32,
     prev_col: u32,
     namespaces: Vec<Dict<String, SemanticTokenType>>,
     tokens: Vec<SemanticToken>,
+    /// Used to classify identifiers by the resolved `VarInfo`.
+    /// If the file has not been analyzed yet, the classification is based on the syntax only.
+    visitor: Option<HIRVisitor<'a>>,
+    /// to convert the columns into UTF-16 offsets
+    lines: Vec<String>,
 }
 
-impl ASTSemanticState {
-    fn new() -> Self {
+impl<'a> ASTSemanticState<'a> {
+    fn new(visitor: Option<HIRVisitor<'a>>, code: &str) -> Self {
         Self {
             prev_line: 1,
             prev_col: 0,
             namespaces: vec![Dict::new()],
             tokens: Vec::new(),
+            visitor,
+            lines: code.lines().map(str::to_string).collect(),
+        }
+    }
+
+    /// The UTF-16 offset of the column `col` in the line `ln` (1-origin).
+    fn character(&self, ln: u32, col: u32) -> u32 {
+        match (ln as usize).checked_sub(1).and_then(|i| self.lines.get(i)) {
+            Some(line) => col_to_utf16(line, col as usize) as u32,
+            None => col,
         }
     }
 
@@ -66,49 +118,92 @@ impl ASTSemanticState {
     }
 
     fn token_type_as_u32(token_type: SemanticTokenType) -> u32 {
-        match token_type.as_str() {
-            "namespace" => 0,
-            "type" => 1,
-            "class" => 2,
-            "enum" => 3,
-            "interface" => 4,
-            "struct" => 5,
-            "typeParameter" => 6,
-            "parameter" => 7,
-            "variable" => 8,
-            "property" => 9,
-            "enumMember" => 10,
-            "event" => 11,
-            "function" => 12,
-            "method" => 13,
-            "macro" => 14,
-            "keyword" => 15,
-            "modifier" => 16,
-            "comment" => 17,
-            "string" => 18,
-            "number" => 19,
-            "regexp" => 20,
-            "operator" => 21,
-            _ => 8,
+        TOKEN_TYPES
+            .iter()
+            .position(|typ| typ == &token_type)
+            .unwrap_or(6) as u32
+    }
+
+    fn modifier_as_bit(modifier: &SemanticTokenModifier) -> u32 {
+        TOKEN_MODIFIERS
+            .iter()
+            .position(|m| m == modifier)
+            .map_or(0, |i| 1 << i)
+    }
+
+    /// Classify the identifier by its `VarInfo`.
+    fn classify(vi: &VarInfo, name: &str) -> (SemanticTokenType, u32) {
+        let is_type = vi.t.is_class_type() || vi.t.is_trait_type() || vi.t == Type::Type;
+        let typ = if vi.kind.is_parameter() && is_type {
+            SemanticTokenType::TYPE_PARAMETER
+        } else if vi.t.is_class_type() {
+            SemanticTokenType::CLASS
+        } else if vi.t.is_trait_type() {
+            SemanticTokenType::INTERFACE
+        } else if vi.kind.is_parameter() {
+            SemanticTokenType::PARAMETER
+        } else if vi.t.is_method() {
+            SemanticTokenType::METHOD
+        } else if vi.t.is_subr() {
+            SemanticTokenType::FUNCTION
+        } else if vi.t.is_module() {
+            SemanticTokenType::NAMESPACE
+        } else if vi.kind.is_instance_attr() {
+            SemanticTokenType::PROPERTY
+        } else {
+            SemanticTokenType::VARIABLE
+        };
+        let mut modifiers = 0;
+        if vi.t.is_procedure() {
+            modifiers |= Self::modifier_as_bit(&PROCEDURE);
+        } else if name.ends_with('!') || vi.t.is_mut_type() {
+            modifiers |= Self::modifier_as_bit(&MUTABLE);
         }
+        if vi.kind.is_builtin() {
+            modifiers |= Self::modifier_as_bit(&SemanticTokenModifier::DEFAULT_LIBRARY);
+        }
+        (typ, modifiers)
+    }
+
+    fn get_info(&self, token: &Token) -> Option<VarInfo> {
+        self.visitor.as_ref()?.get_info(token)
     }
 
     fn gen_token(&mut self, loc: Location, token_type: SemanticTokenType) -> SemanticToken {
-        let delta_line = loc.ln_begin().unwrap_or(1).saturating_sub(self.prev_line);
+        self.gen_token_with_modifiers(loc, token_type, 0)
+    }
+
+    fn gen_token_with_modifiers(
+        &mut self,
+        loc: Location,
+        token_type: SemanticTokenType,
+        modifiers: u32,
+    ) -> SemanticToken {
+        let ln_begin = loc.ln_begin().unwrap_or(1);
+        let delta_line = ln_begin.saturating_sub(self.prev_line);
+        let start = self.character(ln_begin, loc.col_begin().unwrap_or(0));
         let delta_start = if delta_line == 0 {
-            loc.col_begin().unwrap_or(0).saturating_sub(self.prev_col)
+            start.saturating_sub(self.prev_col)
         } else {
-            loc.col_begin().unwrap_or(0)
+            start
+        };
+        let length = match (loc.col_begin(), loc.col_end()) {
+            (Some(_), Some(col_end)) if loc.ln_end() == Some(ln_begin) => {
+                self.character(ln_begin, col_end).saturating_sub(start)
+            }
+            _ => loc.length().unwrap_or(1),
         };
         let token = SemanticToken {
             delta_line,
             delta_start,
-            length: loc.length().unwrap_or(1),
+            length,
             token_type: Self::token_type_as_u32(token_type),
-            token_modifiers_bitset: 0,
+            token_modifiers_bitset: modifiers,
         };
         self.prev_line = loc.ln_begin().unwrap_or(self.prev_line);
-        self.prev_col = loc.col_begin().unwrap_or(self.prev_col);
+        if loc.col_begin().is_some() {
+            self.prev_col = start;
+        }
         token
     }
 
@@ -141,14 +236,19 @@ impl ASTSemanticState {
             Expr::Literal(lit) => {
                 let typ = match lit.token.kind {
                     TokenKind::StrLit => SemanticTokenType::STRING,
-                    TokenKind::NatLit | TokenKind::IntLit | TokenKind::RatioLit => {
-                        SemanticTokenType::NUMBER
-                    }
+                    TokenKind::NatLit
+                    | TokenKind::BinLit
+                    | TokenKind::OctLit
+                    | TokenKind::HexLit
+                    | TokenKind::IntLit
+                    | TokenKind::RatioLit
+                    | TokenKind::FloatLit => SemanticTokenType::NUMBER,
                     _ => SemanticTokenType::VARIABLE,
                 };
                 let token = self.gen_token(lit.loc(), typ);
                 vec![token]
             }
+            Expr::StrInterp(interp) => self.gen_from_str_interp(interp),
             Expr::Def(def) => self.gen_from_def(def),
             Expr::Lambda(lambda) => self.gen_from_block(Some(lambda.sig.params), lambda.body),
             Expr::Methods(methods) => self.gen_from_methods(methods),
@@ -205,6 +305,10 @@ impl ASTSemanticState {
     }
 
     fn gen_from_ident(&mut self, ident: Identifier) -> Vec<SemanticToken> {
+        if let Some(vi) = self.get_info(ident.name.token()) {
+            let (typ, modifiers) = Self::classify(&vi, ident.inspect());
+            return vec![self.gen_token_with_modifiers(ident.name.loc(), typ, modifiers)];
+        }
         let typ = self.get_variable_type(ident.inspect());
         vec![self.gen_token(ident.name.loc(), typ)]
     }
@@ -214,7 +318,11 @@ impl ASTSemanticState {
             Accessor::Ident(ident) => self.gen_from_ident(ident),
             Accessor::Attr(attr) => {
                 let mut tokens = self.gen_from_expr(*attr.obj);
-                tokens.push(self.gen_token(attr.ident.name.loc(), SemanticTokenType::PROPERTY));
+                let (typ, modifiers) = self
+                    .get_info(attr.ident.name.token())
+                    .map(|vi| Self::classify(&vi, attr.ident.inspect()))
+                    .unwrap_or((SemanticTokenType::PROPERTY, 0));
+                tokens.push(self.gen_token_with_modifiers(attr.ident.name.loc(), typ, modifiers));
                 tokens
             }
             _ => vec![],
@@ -229,7 +337,7 @@ impl ASTSemanticState {
 
     fn gen_from_args(&mut self, args: Args) -> Vec<SemanticToken> {
         let mut tokens = vec![];
-        let (pos_args, var_args, kw_args, ..) = args.deconstruct();
+        let (pos_args, var_args, kw_args, kw_var_args, ..) = args.deconstruct();
         for arg in pos_args {
             tokens.extend(self.gen_from_expr(arg.expr));
         }
@@ -239,6 +347,22 @@ impl ASTSemanticState {
         for arg in kw_args {
             tokens.extend(self.gen_from_expr(arg.expr));
         }
+        if let Some(kw_var_args) = kw_var_args {
+            tokens.extend(self.gen_from_expr(kw_var_args.expr));
+        }
+        tokens
+    }
+
+    fn gen_from_str_interp(&mut self, interp: StrInterp) -> Vec<SemanticToken> {
+        let mut tokens = vec![];
+        let mut lits = interp.lits.into_iter();
+        for expr in interp.exprs.into_iter() {
+            let lit = lits.next().unwrap();
+            tokens.push(self.gen_token(lit.loc(), SemanticTokenType::STRING));
+            tokens.extend(self.gen_from_expr(expr));
+        }
+        let lit = lits.next().unwrap();
+        tokens.push(self.gen_token(lit.loc(), SemanticTokenType::STRING));
         tokens
     }
 
@@ -261,7 +385,7 @@ impl ASTSemanticState {
         self.namespaces.push(Dict::new());
         let mut tokens = vec![];
         if let Some(params) = params {
-            let (nd_params, var_params, d_params, ..) = params.deconstruct();
+            let (nd_params, var_params, d_params, kw_var_params, ..) = params.deconstruct();
             for param in nd_params.into_iter() {
                 let typ = SemanticTokenType::PARAMETER;
                 tokens.push(self.gen_token(param.loc(), typ));
@@ -274,6 +398,10 @@ impl ASTSemanticState {
                 let typ = SemanticTokenType::PARAMETER;
                 tokens.push(self.gen_token(param.loc(), typ));
             }
+            if let Some(kw_var_param) = kw_var_params {
+                let typ = SemanticTokenType::PARAMETER;
+                tokens.push(self.gen_token(kw_var_param.loc(), typ));
+            }
         }
         for expr in block.into_iter() {
             tokens.extend(self.gen_from_expr(expr));
@@ -293,9 +421,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let path = util::uri_to_path(&uri);
         let src = self.file_cache.get_entire_code(&uri)?;
         let mut builder = ASTBuilder::new(self.cfg.inherit(path));
+        let mut state = ASTSemanticState::new(self.get_visitor(&uri), &src);
         let result = match builder.build_without_desugaring(src) {
             Ok(artifact) => {
-                let mut state = ASTSemanticState::new();
                 let tokens = state.enumerate_tokens(artifact.ast);
                 Some(SemanticTokensResult::Tokens(tokens))
             }
@@ -3,12 +3,15 @@ use std::io::{stdin, stdout, BufRead, Read, Write};
 use std::ops::Not;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::sync::mpsc;
 
+use erg_common::cancel::CancellationToken;
 use erg_common::config::ErgConfig;
 use erg_common::consts::PYTHON_MODE;
 use erg_common::dict::Dict;
 use erg_common::env::erg_path;
+use erg_common::lang::LanguageCode;
 use erg_common::shared::{
     MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLockReadGuard, RwLockWriteGuard, Shared,
 };
@@ -26,17 +29,22 @@ use erg_compiler::module::{SharedCompilerResource, SharedModuleGraph, SharedModu
 use erg_compiler::ty::HasType;
 
 use lsp_types::request::{
-    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, Completion, ExecuteCommand,
-    GotoDefinition, HoverRequest, InlayHintRequest, References, Rename, Request,
-    ResolveCompletionItem, SemanticTokensFullRequest, SignatureHelpRequest, WillRenameFiles,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, CodeActionResolveRequest, CodeLensRequest, CodeLensResolve, Completion,
+    DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest,
+    Formatting, GotoDefinition, GotoImplementation, HoverRequest, InlayHintRequest,
+    PrepareRenameRequest, RangeFormatting, References, Rename, Request, ResolveCompletionItem,
+    SelectionRangeRequest, SemanticTokensFullRequest, SignatureHelpRequest, WillRenameFiles,
+    WorkspaceSymbol,
 };
 use lsp_types::{
-    ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionProviderCapability,
-    CodeLensOptions, CompletionOptions, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-    ExecuteCommandOptions, HoverProviderCapability, InitializeResult, OneOf, Position,
-    SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
-    SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions,
-    WorkDoneProgressOptions,
+    CallHierarchyServerCapability, ClientCapabilities, CodeActionKind, CodeActionOptions,
+    CodeActionProviderCapability, CodeLensOptions, CompletionOptions, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, ImplementationProviderCapability,
+    InitializeResult, OneOf, Position, RenameOptions, SelectionRangeProviderCapability,
+    SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensServerCapabilities,
+    ServerCapabilities, SignatureHelpOptions, WorkDoneProgressOptions,
 };
 
 use serde::{Deserialize, Serialize};
@@ -48,6 +56,14 @@ use crate::completion::CompletionCache;
 use crate::file_cache::FileCache;
 use crate::hir_visitor::HIRVisitor;
 use crate::message::{ErrorMessage, LSPResult, LogMessage, ShowMessage};
+use crate::notebook::{
+    DidChangeNotebookDocumentParams, DidCloseNotebookDocumentParams, DidOpenNotebookDocumentParams,
+    DidSaveNotebookDocumentParams, NotebookCache,
+};
+use crate::pull_diagnostics::{DiagnosticClientCapabilities, DocumentDiagnosticRequest};
+use crate::semantic::semantic_tokens_legend;
+use crate::settings::ServerSettings;
+use crate::type_hierarchy::{TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes};
 use crate::util::{self, NormalizedUrl};
 
 pub type ELSResult<T> = Result<T, Box<dyn std::error::Error>>;
@@ -65,11 +81,20 @@ pub enum DefaultFeatures {
     Diagnostics,
     FindReferences,
     GotoDefinition,
+    GotoImplementation,
     Hover,
     InlayHint,
     Rename,
     SemanticTokens,
     SignatureHelp,
+    DocumentSymbol,
+    WorkspaceSymbol,
+    Formatting,
+    FoldingRange,
+    CallHierarchy,
+    TypeHierarchy,
+    SelectionRange,
+    NotebookSync,
     /* ELS specific features */
     SmartCompletion,
     DeepCompletion,
@@ -94,7 +119,26 @@ impl From<&str> for DefaultFeatures {
             "gotodefinition" | "gotoDefinition" | "goto-completion" => {
                 DefaultFeatures::GotoDefinition
             }
+            "gotoimplementation" | "gotoImplementation" | "goto-implementation" => {
+                DefaultFeatures::GotoImplementation
+            }
             "signaturehelp" | "signatureHelp" | "signature-help" => DefaultFeatures::SignatureHelp,
+            "documentsymbol" | "documentSymbol" | "document-symbol" => {
+                DefaultFeatures::DocumentSymbol
+            }
+            "workspacesymbol" | "workspaceSymbol" | "workspace-symbol" => {
+                DefaultFeatures::WorkspaceSymbol
+            }
+            "formatting" | "format" => DefaultFeatures::Formatting,
+            "foldingrange" | "foldingRange" | "folding-range" => DefaultFeatures::FoldingRange,
+            "callhierarchy" | "callHierarchy" | "call-hierarchy" => DefaultFeatures::CallHierarchy,
+            "typehierarchy" | "typeHierarchy" | "type-hierarchy" => DefaultFeatures::TypeHierarchy,
+            "selectionrange" | "selectionRange" | "selection-range" => {
+                DefaultFeatures::SelectionRange
+            }
+            "notebook" | "notebooks" | "notebookSync" | "notebook-sync" => {
+                DefaultFeatures::NotebookSync
+            }
             "smartcompletion" | "smartCompletion" | "smart-completion" => {
                 DefaultFeatures::SmartCompletion
             }
@@ -152,6 +196,14 @@ pub(crate) fn send<T: ?Sized + Serialize>(message: &T) -> ELSResult<()> {
     send_stdout(message)
 }
 
+/// IDs of the requests sent from the server to the client.
+/// The client's responses are ignored (see `Server::dispatch`).
+static SERVER_REQUEST_ID: AtomicI64 = AtomicI64::new(0);
+
+pub(crate) fn next_server_request_id() -> i64 {
+    SERVER_REQUEST_ID.fetch_add(1, Ordering::SeqCst)
+}
+
 pub(crate) fn send_log<S: Into<String>>(msg: S) -> ELSResult<()> {
     if cfg!(debug_assertions) || cfg!(feature = "debug") {
         send(&LogMessage::new(msg))
@@ -266,6 +318,14 @@ impl AnalysisResultCache {
     pub fn remove(&self, uri: &NormalizedUrl) -> Option<AnalysisResult> {
         self.0.borrow_mut().remove(uri)
     }
+
+    pub fn entries(&self) -> Vec<NormalizedUrl> {
+        self.0.borrow().keys().cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -310,14 +370,19 @@ pub struct Server<Checker: BuildRunnable = HIRBuilder, Parser: Parsable = Simple
     pub(crate) home: PathBuf,
     pub(crate) erg_path: PathBuf,
     pub(crate) client_capas: ClientCapabilities,
+    pub(crate) diag_client_capas: DiagnosticClientCapabilities,
     pub(crate) disabled_features: Vec<DefaultFeatures>,
     pub(crate) opt_features: Vec<OptionalFeatures>,
+    pub(crate) settings: Shared<ServerSettings>,
     pub(crate) file_cache: FileCache,
+    pub(crate) notebooks: NotebookCache,
     pub(crate) comp_cache: CompletionCache,
     // TODO: remove modules, analysis_result, and add `shared: SharedCompilerResource`
     pub(crate) modules: ModuleCache,
     pub(crate) analysis_result: AnalysisResultCache,
     pub(crate) current_sig: Option<Expr>,
+    /// The checks in progress, which are cancelled when a newer edit arrives
+    pub(crate) pending_checks: Shared<Dict<NormalizedUrl, CancellationToken>>,
     pub(crate) channels: Option<SendChannels>,
     pub(crate) _parser: std::marker::PhantomData<fn() -> Parser>,
     pub(crate) _checker: std::marker::PhantomData<fn() -> Checker>,
@@ -330,13 +395,17 @@ impl<C: BuildRunnable, P: Parsable> Clone for Server<C, P> {
             home: self.home.clone(),
             erg_path: self.erg_path.clone(),
             client_capas: self.client_capas.clone(),
+            diag_client_capas: self.diag_client_capas,
             disabled_features: self.disabled_features.clone(),
             opt_features: self.opt_features.clone(),
+            settings: self.settings.clone(),
             file_cache: self.file_cache.clone(),
+            notebooks: self.notebooks.clone(),
             comp_cache: self.comp_cache.clone(),
             modules: self.modules.clone(),
             analysis_result: self.analysis_result.clone(),
             current_sig: self.current_sig.clone(),
+            pending_checks: self.pending_checks.clone(),
             channels: self.channels.clone(),
             _parser: std::marker::PhantomData,
             _checker: std::marker::PhantomData,
@@ -352,12 +421,16 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             home: normalize_path(std::env::current_dir().unwrap_or_default()),
             erg_path: erg_path().clone(), // already normalized
             client_capas: ClientCapabilities::default(),
+            diag_client_capas: DiagnosticClientCapabilities::default(),
             disabled_features: vec![],
             opt_features: vec![],
+            settings: Shared::new(ServerSettings::default()),
             file_cache: FileCache::new(),
+            notebooks: NotebookCache::new(),
             modules: ModuleCache::new(),
             analysis_result: AnalysisResultCache::new(),
             current_sig: None,
+            pending_checks: Shared::new(Dict::new()),
             channels: None,
             _parser: std::marker::PhantomData,
             _checker: std::marker::PhantomData,
@@ -388,8 +461,18 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         // #[allow(clippy::collapsible_if)]
         if msg.get("params").is_some() && msg["params"].get("capabilities").is_some() {
             self.client_capas = ClientCapabilities::deserialize(&msg["params"]["capabilities"])?;
+            self.diag_client_capas =
+                DiagnosticClientCapabilities::from(&msg["params"]["capabilities"]);
             // send_log(format!("set client capabilities: {:?}", self.client_capas))?;
         }
+        if let Some(options) = msg
+            .get("params")
+            .and_then(|params| params.get("initializationOptions"))
+        {
+            let settings = ServerSettings::deserialize(options)?;
+            LanguageCode::set_message_lang(settings.language());
+            *self.settings.borrow_mut() = settings;
+        }
         let mut args = self.cfg.runtime_args.iter();
         while let Some(&arg) = args.next() {
             if arg == "--disable" {
@@ -409,9 +492,22 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         comp_options.trigger_characters = Some(TRIGGER_CHARS.map(String::from).to_vec());
         comp_options.resolve_provider = Some(true);
         result.capabilities.completion_provider = Some(comp_options);
-        result.capabilities.rename_provider = Some(OneOf::Left(true));
+        result.capabilities.rename_provider = Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        }));
         result.capabilities.references_provider = Some(OneOf::Left(true));
+        result.capabilities.document_highlight_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::FindReferences)
+            .not()
+            .then_some(OneOf::Left(true));
         result.capabilities.definition_provider = Some(OneOf::Left(true));
+        result.capabilities.implementation_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::GotoImplementation)
+            .not()
+            .then_some(ImplementationProviderCapability::Simple(true));
         result.capabilities.hover_provider = self
             .disabled_features
             .contains(&DefaultFeatures::Hover)
@@ -425,24 +521,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         let mut sema_options = SemanticTokensOptions::default();
         sema_options.range = Some(false);
         sema_options.full = Some(SemanticTokensFullOptions::Bool(true));
-        sema_options.legend = SemanticTokensLegend {
-            token_types: vec![
-                SemanticTokenType::NAMESPACE,
-                SemanticTokenType::TYPE,
-                SemanticTokenType::CLASS,
-                SemanticTokenType::INTERFACE,
-                SemanticTokenType::TYPE_PARAMETER,
-                SemanticTokenType::PARAMETER,
-                SemanticTokenType::VARIABLE,
-                SemanticTokenType::PROPERTY,
-                SemanticTokenType::FUNCTION,
-                SemanticTokenType::METHOD,
-                SemanticTokenType::STRING,
-                SemanticTokenType::NUMBER,
-                SemanticTokenType::OPERATOR,
-            ],
-            token_modifiers: vec![],
-        };
+        sema_options.legend = semantic_tokens_legend();
         result.capabilities.semantic_tokens_provider = self
             .disabled_features
             .contains(&DefaultFeatures::SemanticTokens)
@@ -478,9 +557,72 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     work_done_progress: None,
                 },
             });
+        result.capabilities.document_symbol_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::DocumentSymbol)
+            .not()
+            .then_some(OneOf::Left(true));
+        result.capabilities.workspace_symbol_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::WorkspaceSymbol)
+            .not()
+            .then_some(OneOf::Left(true));
+        let formatting = self
+            .disabled_features
+            .contains(&DefaultFeatures::Formatting)
+            .not();
+        result.capabilities.document_formatting_provider = formatting.then_some(OneOf::Left(true));
+        result.capabilities.document_range_formatting_provider =
+            formatting.then_some(OneOf::Left(true));
+        result.capabilities.folding_range_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::FoldingRange)
+            .not()
+            .then_some(FoldingRangeProviderCapability::Simple(true));
+        result.capabilities.call_hierarchy_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::CallHierarchy)
+            .not()
+            .then_some(CallHierarchyServerCapability::Simple(true));
+        result.capabilities.selection_range_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::SelectionRange)
+            .not()
+            .then_some(SelectionRangeProviderCapability::Simple(true));
         result.capabilities.code_lens_provider = Some(CodeLensOptions {
-            resolve_provider: Some(false),
+            resolve_provider: Some(true),
         });
+        let mut result = serde_json::to_value(result)?;
+        // `lsp_types` 0.93 does not support the type hierarchy (LSP 3.17)
+        if !self
+            .disabled_features
+            .contains(&DefaultFeatures::TypeHierarchy)
+        {
+            result["capabilities"]["typeHierarchyProvider"] = json!(true);
+        }
+        // the pull diagnostics are also LSP 3.17 features
+        if !self
+            .disabled_features
+            .contains(&DefaultFeatures::Diagnostics)
+        {
+            result["capabilities"]["diagnosticProvider"] = json!({
+                "identifier": "erg",
+                "interFileDependencies": true,
+                "workspaceDiagnostics": false,
+            });
+        }
+        // as well as the notebook document synchronization
+        if !self
+            .disabled_features
+            .contains(&DefaultFeatures::NotebookSync)
+        {
+            result["capabilities"]["notebookDocumentSync"] = json!({
+                "notebookSelector": [{
+                    "notebook": "*",
+                    "cells": [{ "language": "erg" }],
+                }],
+            });
+        }
         self.init_services();
         send(&json!({
             "jsonrpc": "2.0",
@@ -501,6 +643,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             receivers.goto_definition,
             Self::handle_goto_definition,
         );
+        self.start_service::<GotoImplementation>(
+            receivers.goto_implementation,
+            Self::handle_goto_implementation,
+        );
         self.start_service::<SemanticTokensFullRequest>(
             receivers.semantic_tokens_full,
             Self::handle_semantic_tokens_full,
@@ -508,6 +654,10 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         self.start_service::<InlayHintRequest>(receivers.inlay_hint, Self::handle_inlay_hint);
         self.start_service::<HoverRequest>(receivers.hover, Self::handle_hover);
         self.start_service::<References>(receivers.references, Self::handle_references);
+        self.start_service::<DocumentHighlightRequest>(
+            receivers.document_highlight,
+            Self::handle_document_highlight,
+        );
         self.start_service::<CodeLensRequest>(receivers.code_lens, Self::handle_code_lens);
         self.start_service::<CodeActionRequest>(receivers.code_action, Self::handle_code_action);
         self.start_service::<CodeActionResolveRequest>(
@@ -526,6 +676,59 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             receivers.execute_command,
             Self::handle_execute_command,
         );
+        self.start_service::<DocumentSymbolRequest>(
+            receivers.document_symbol,
+            Self::handle_document_symbol,
+        );
+        self.start_service::<WorkspaceSymbol>(
+            receivers.workspace_symbol,
+            Self::handle_workspace_symbol,
+        );
+        self.start_service::<Formatting>(receivers.formatting, Self::handle_formatting);
+        self.start_service::<RangeFormatting>(
+            receivers.range_formatting,
+            Self::handle_range_formatting,
+        );
+        self.start_service::<FoldingRangeRequest>(
+            receivers.folding_range,
+            Self::handle_folding_range,
+        );
+        self.start_service::<CallHierarchyPrepare>(
+            receivers.call_hierarchy_prepare,
+            Self::handle_call_hierarchy_prepare,
+        );
+        self.start_service::<CallHierarchyIncomingCalls>(
+            receivers.call_hierarchy_incoming,
+            Self::handle_call_hierarchy_incoming,
+        );
+        self.start_service::<CallHierarchyOutgoingCalls>(
+            receivers.call_hierarchy_outgoing,
+            Self::handle_call_hierarchy_outgoing,
+        );
+        self.start_service::<TypeHierarchyPrepare>(
+            receivers.type_hierarchy_prepare,
+            Self::handle_type_hierarchy_prepare,
+        );
+        self.start_service::<TypeHierarchySupertypes>(
+            receivers.type_hierarchy_supertypes,
+            Self::handle_type_hierarchy_supertypes,
+        );
+        self.start_service::<TypeHierarchySubtypes>(
+            receivers.type_hierarchy_subtypes,
+            Self::handle_type_hierarchy_subtypes,
+        );
+        self.start_service::<SelectionRangeRequest>(
+            receivers.selection_range,
+            Self::handle_selection_range,
+        );
+        self.start_service::<CodeLensResolve>(
+            receivers.code_lens_resolve,
+            Self::handle_code_lens_resolve,
+        );
+        self.start_service::<DocumentDiagnosticRequest>(
+            receivers.document_diagnostic,
+            Self::handle_document_diagnostic,
+        );
         self.start_auto_diagnostics();
     }
 
@@ -660,11 +863,16 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             "initialize" => self.init(msg, id),
             "shutdown" => self.shutdown(id),
             Rename::METHOD => self.rename(msg),
+            PrepareRenameRequest::METHOD => self.prepare_rename(msg),
             Completion::METHOD => self.parse_send::<Completion>(id, msg),
             ResolveCompletionItem::METHOD => self.parse_send::<ResolveCompletionItem>(id, msg),
             GotoDefinition::METHOD => self.parse_send::<GotoDefinition>(id, msg),
+            GotoImplementation::METHOD => self.parse_send::<GotoImplementation>(id, msg),
             HoverRequest::METHOD => self.parse_send::<HoverRequest>(id, msg),
             References::METHOD => self.parse_send::<References>(id, msg),
+            DocumentHighlightRequest::METHOD => {
+                self.parse_send::<DocumentHighlightRequest>(id, msg)
+            }
             SemanticTokensFullRequest::METHOD => {
                 self.parse_send::<SemanticTokensFullRequest>(id, msg)
             }
@@ -677,6 +885,26 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             CodeLensRequest::METHOD => self.parse_send::<CodeLensRequest>(id, msg),
             WillRenameFiles::METHOD => self.parse_send::<WillRenameFiles>(id, msg),
             ExecuteCommand::METHOD => self.parse_send::<ExecuteCommand>(id, msg),
+            DocumentSymbolRequest::METHOD => self.parse_send::<DocumentSymbolRequest>(id, msg),
+            WorkspaceSymbol::METHOD => self.parse_send::<WorkspaceSymbol>(id, msg),
+            Formatting::METHOD => self.parse_send::<Formatting>(id, msg),
+            RangeFormatting::METHOD => self.parse_send::<RangeFormatting>(id, msg),
+            FoldingRangeRequest::METHOD => self.parse_send::<FoldingRangeRequest>(id, msg),
+            CallHierarchyPrepare::METHOD => self.parse_send::<CallHierarchyPrepare>(id, msg),
+            CallHierarchyIncomingCalls::METHOD => {
+                self.parse_send::<CallHierarchyIncomingCalls>(id, msg)
+            }
+            CallHierarchyOutgoingCalls::METHOD => {
+                self.parse_send::<CallHierarchyOutgoingCalls>(id, msg)
+            }
+            TypeHierarchyPrepare::METHOD => self.parse_send::<TypeHierarchyPrepare>(id, msg),
+            TypeHierarchySupertypes::METHOD => self.parse_send::<TypeHierarchySupertypes>(id, msg),
+            TypeHierarchySubtypes::METHOD => self.parse_send::<TypeHierarchySubtypes>(id, msg),
+            SelectionRangeRequest::METHOD => self.parse_send::<SelectionRangeRequest>(id, msg),
+            CodeLensResolve::METHOD => self.parse_send::<CodeLensResolve>(id, msg),
+            DocumentDiagnosticRequest::METHOD => {
+                self.parse_send::<DocumentDiagnosticRequest>(id, msg)
+            }
             other => send_error(Some(id), -32600, format!("{other} is not supported")),
         }
     }
@@ -715,20 +943,50 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     // TODO: reset mutable dependent types
                     self.quick_check_file(uri)?;
                 }
+                let uri = NormalizedUrl::new(params.text_document.uri.clone());
+                // the result of the check in progress is already outdated
+                if let Some(cancel) = self.pending_checks.borrow_mut().remove(&uri) {
+                    cancel.cancel();
+                }
                 self.file_cache.incremental_update(params);
                 Ok(())
             }
+            "notebookDocument/didOpen" => {
+                let params = DidOpenNotebookDocumentParams::deserialize(msg["params"].clone())?;
+                self.handle_did_open_notebook(params)
+            }
+            "notebookDocument/didChange" => {
+                let params = DidChangeNotebookDocumentParams::deserialize(msg["params"].clone())?;
+                self.handle_did_change_notebook(params)
+            }
+            "notebookDocument/didSave" => {
+                let params = DidSaveNotebookDocumentParams::deserialize(msg["params"].clone())?;
+                self.handle_did_save_notebook(params)
+            }
+            "notebookDocument/didClose" => {
+                let params = DidCloseNotebookDocumentParams::deserialize(msg["params"].clone())?;
+                self.handle_did_close_notebook(params)
+            }
+            "workspace/didChangeConfiguration" => {
+                let params = DidChangeConfigurationParams::deserialize(msg["params"].clone())?;
+                self.handle_did_change_configuration(params)
+            }
             _ => send_log(format!("received notification: {method}")),
         }
     }
 
-    pub(crate) fn get_checker(&self, path: PathBuf) -> Checker {
+    pub(crate) fn get_checker(&self, path: PathBuf, cancel: CancellationToken) -> Checker {
+        let mut cfg = self.cfg.inherit(path.clone());
+        cfg.cancel = cancel;
+        if let Some(version) = self.settings.borrow().python_version() {
+            cfg.target_version = Some(version);
+        }
         if let Some(shared) = self.get_shared() {
             let shared = shared.clone();
             shared.clear(&path);
-            Checker::inherit(self.cfg.inherit(path), shared)
+            Checker::inherit(cfg, shared)
         } else {
-            Checker::new(self.cfg.inherit(path))
+            Checker::new(cfg)
         }
     }
 
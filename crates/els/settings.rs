@@ -0,0 +1,131 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use erg_common::lang::LanguageCode;
+use erg_common::python_util::PythonVersion;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+
+use lsp_types::request::{InlayHintRefreshRequest, Request};
+use lsp_types::{DiagnosticSeverity, DidChangeConfigurationParams};
+
+use crate::inlay_hint::InlayHintOptions;
+use crate::server::{next_server_request_id, send, send_error_info, send_log, ELSResult, Server};
+
+/// The severity with which warnings are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WarningLevel {
+    /// warnings are not reported
+    Off,
+    Hint,
+    Information,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl WarningLevel {
+    pub const fn severity(&self) -> Option<DiagnosticSeverity> {
+        match self {
+            Self::Off => None,
+            Self::Hint => Some(DiagnosticSeverity::HINT),
+            Self::Information => Some(DiagnosticSeverity::INFORMATION),
+            Self::Warning => Some(DiagnosticSeverity::WARNING),
+            Self::Error => Some(DiagnosticSeverity::ERROR),
+        }
+    }
+}
+
+/// Server settings, passed as `initializationOptions` and changed at runtime by `workspace/didChangeConfiguration`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ServerSettings {
+    pub inlay_hints: InlayHintOptions,
+    pub warnings: WarningLevel,
+    /// The Python version to be targeted (e.g. `"3.11"`)
+    pub python_version: Option<String>,
+    /// The language of messages (e.g. `"japanese"`, `"ja"`)
+    pub language: Option<String>,
+}
+
+impl ServerSettings {
+    pub fn python_version(&self) -> Option<PythonVersion> {
+        self.python_version.as_ref()?.parse().ok()
+    }
+
+    pub fn language(&self) -> Option<LanguageCode> {
+        self.language.as_ref()?.parse().ok()
+    }
+
+    /// Whether the documents should be rechecked when the settings are changed to `other`.
+    fn affects_checking(&self, other: &Self) -> bool {
+        self.warnings != other.warnings
+            || self.python_version != other.python_version
+            || self.language != other.language
+    }
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_did_change_configuration(
+        &mut self,
+        params: DidChangeConfigurationParams,
+    ) -> ELSResult<()> {
+        send_log(format!("configuration changed: {params:?}"))?;
+        // `{ "erg": { ... } }` or `{ ... }`
+        let settings = params.settings.get("erg").unwrap_or(&params.settings);
+        if settings.is_null() {
+            return Ok(());
+        }
+        let settings = ServerSettings::deserialize(settings)?;
+        self.apply_settings(settings)
+    }
+
+    /// Open documents are rechecked if needed.
+    pub(crate) fn apply_settings(&mut self, settings: ServerSettings) -> ELSResult<()> {
+        if settings.language.is_some() && settings.language().is_none() {
+            send_error_info(format!("unknown language: {:?}", settings.language))?;
+        }
+        if settings.python_version.is_some() && settings.python_version().is_none() {
+            send_error_info(format!(
+                "invalid Python version: {:?}",
+                settings.python_version
+            ))?;
+        }
+        LanguageCode::set_message_lang(settings.language());
+        let old = std::mem::replace(&mut *self.settings.borrow_mut(), settings.clone());
+        if old.inlay_hints != settings.inlay_hints {
+            self.send_inlay_hint_refresh()?;
+        }
+        if old.affects_checking(&settings) {
+            // the cached modules (and the interned names) were built with the old settings
+            if let Some(shared) = self.get_shared() {
+                shared.clear_all();
+            }
+            for uri in self.analysis_result.entries() {
+                let code = self.file_cache.get_entire_code(&uri)?;
+                self.check_file(uri, code)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send_inlay_hint_refresh(&self) -> ELSResult<()> {
+        let supported = self
+            .client_capas
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.inlay_hint.as_ref())
+            .and_then(|inlay_hint| inlay_hint.refresh_support)
+            .unwrap_or(false);
+        if !supported {
+            return Ok(());
+        }
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": next_server_request_id(),
+            "method": InlayHintRefreshRequest::METHOD,
+            "params": Value::Null,
+        }))
+    }
+}
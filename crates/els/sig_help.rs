@@ -1,9 +1,12 @@
+use erg_common::dict::Dict;
 use erg_common::traits::{DequeStream, LimitedDisplay, Locational, NoTypeDisplay};
+use erg_common::Str;
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::parse::Parsable;
 use erg_compiler::erg_parser::token::{Token, TokenKind};
-use erg_compiler::hir::Expr;
-use erg_compiler::ty::{HasType, ParamTy};
+use erg_compiler::hir::{Accessor, Call, Expr, Signature};
+use erg_compiler::ty::{HasType, ParamTy, Type};
+use erg_compiler::varinfo::AbsLocation;
 
 use lsp_types::{
     ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureHelpContext,
@@ -11,7 +14,7 @@ use lsp_types::{
 };
 
 use crate::server::{send_log, ELSResult, Server};
-use crate::util::NormalizedUrl;
+use crate::util::{self, NormalizedUrl};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Trigger {
@@ -31,19 +34,124 @@ impl From<String> for Trigger {
     }
 }
 
-pub enum ParamKind {
-    NonDefault,
-    VarArgs,
-    Default,
+/// The argument the user is currently typing
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ActiveArg {
+    Pos(usize),
+    Kw(Str),
 }
 
-fn get_end(start: usize, pt: &ParamTy, kind: ParamKind) -> usize {
-    let pad = match kind {
-        ParamKind::NonDefault => 2, // 2: `: `
-        ParamKind::VarArgs => 3,    // 3: `*{name}: `
-        ParamKind::Default => 4,    // 4: ` := `
-    };
-    start + pt.name().map(|n| n.len() + pad).unwrap_or(0) + pt.typ().to_string_unabbreviated().len()
+/// The subroutine being called
+struct Callee {
+    name: String,
+    t: Type,
+    def_loc: Option<AbsLocation>,
+    /// `x.f(...)`: `self` is passed implicitly
+    is_method: bool,
+}
+
+impl Callee {
+    fn from_acc(acc: &Accessor) -> Self {
+        let is_method = matches!(acc, Accessor::Attr(_)) && acc.ref_t().self_t().is_some();
+        Self {
+            name: acc.show(),
+            t: acc.ref_t().clone(),
+            def_loc: Some(acc.var_info().def_loc.clone()),
+            is_method,
+        }
+    }
+
+    fn from_call(call: &Call) -> Self {
+        match (&call.attr_name, call.obj.as_ref()) {
+            (Some(attr), obj) => Self {
+                name: format!(
+                    "{}.{}",
+                    obj.show_acc().unwrap_or_else(|| obj.to_string_notype()),
+                    attr.inspect()
+                ),
+                t: attr.ref_t().clone(),
+                def_loc: Some(attr.vi.def_loc.clone()),
+                is_method: attr.ref_t().self_t().is_some(),
+            },
+            (None, Expr::Accessor(acc)) => Self::from_acc(acc),
+            (None, obj) => Self {
+                name: obj.to_string_notype(),
+                t: obj.ref_t().clone(),
+                def_loc: None,
+                is_method: false,
+            },
+        }
+    }
+}
+
+/// Builds a signature label (e.g. `f(x: Int, y := Str): NoneType`)
+/// while recording the offsets (in UTF-16 code units) of each parameter.
+struct SignatureLabel {
+    label: String,
+    params: Vec<ParameterInformation>,
+}
+
+impl SignatureLabel {
+    fn new(name: &str) -> Self {
+        Self {
+            label: format!("{name}("),
+            params: vec![],
+        }
+    }
+
+    fn push_param(&mut self, text: String) {
+        if !self.params.is_empty() {
+            self.label.push_str(", ");
+        }
+        let start = self.label.encode_utf16().count() as u32;
+        self.label.push_str(&text);
+        let end = self.label.encode_utf16().count() as u32;
+        self.params.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        });
+    }
+
+    fn finish(mut self, return_t: Option<&Type>) -> (String, Vec<ParameterInformation>) {
+        self.label.push(')');
+        if let Some(return_t) = return_t {
+            self.label.push_str(": ");
+            self.label.push_str(&return_t.to_string_unabbreviated());
+        }
+        (self.label, self.params)
+    }
+}
+
+fn param_text(pt: &ParamTy, prefix: &str) -> String {
+    match pt.name() {
+        Some(name) => format!("{prefix}{name}: {}", pt.typ().to_string_unabbreviated()),
+        None => format!("{prefix}{}", pt.typ().to_string_unabbreviated()),
+    }
+}
+
+/// Returns the index of the active parameter if `subr` can accept `active`.
+fn active_param_index(subr: &Type, skip: usize, active: &ActiveArg) -> Option<u32> {
+    let non_default_params = subr.non_default_params()?;
+    let non_defaults = non_default_params.len().saturating_sub(skip);
+    let var_params = subr.var_params();
+    let defaults = subr.default_params()?;
+    match active {
+        ActiveArg::Pos(n) if *n < non_defaults => Some(*n as u32),
+        ActiveArg::Pos(_) if var_params.is_some() => Some(non_defaults as u32),
+        ActiveArg::Pos(n) if *n < non_defaults + defaults.len() => Some(*n as u32),
+        ActiveArg::Pos(_) => None,
+        ActiveArg::Kw(kw) => {
+            let names = non_default_params[skip..]
+                .iter()
+                .chain(var_params.as_deref())
+                .chain(defaults.iter())
+                .map(|pt| pt.name());
+            names
+                .enumerate()
+                .find(|(_, name)| *name == Some(kw))
+                .map(|(i, _)| i as u32)
+        }
+    }
 }
 
 impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
@@ -115,6 +223,19 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
             .unwrap_or(argc) // `commas.col_end() < token.col_end()` means the token is the last argument
     }
 
+    /// If the cursor is on a keyword argument (`f(x := 1)`), it is matched with the parameter by name.
+    fn active_arg(&self, call: &Call, pos: Position, positional: usize) -> ActiveArg {
+        for kw_arg in call.args.kw_args.iter() {
+            let Some(range) = util::loc_to_range(kw_arg.loc()) else {
+                continue;
+            };
+            if range.start <= pos && pos <= range.end {
+                return ActiveArg::Kw(kw_arg.keyword.content.clone());
+            }
+        }
+        ActiveArg::Pos(positional)
+    }
+
     fn resend_help(
         &mut self,
         uri: &NormalizedUrl,
@@ -128,8 +249,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
                     self.current_sig = None;
                     return None;
                 }
-                let nth = self.nth(uri, call.args.loc(), &token) as u32;
-                return self.make_sig_help(call.obj.as_ref(), nth);
+                let nth = self.nth(uri, call.args.loc(), &token);
+                let active = self.active_arg(call, pos, nth);
+                return self.make_sig_help(&Callee::from_call(call), &active);
             }
         } else {
             crate::_log!("failed to get the token");
@@ -139,7 +261,7 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
 
     fn get_first_help(&mut self, uri: &NormalizedUrl, pos: Position) -> Option<SignatureHelp> {
         if let Some((_token, Expr::Accessor(acc))) = self.get_min_expr(uri, pos, -2) {
-            return self.make_sig_help(&acc, 0);
+            return self.make_sig_help(&Callee::from_acc(&acc), &ActiveArg::Pos(0));
         } else {
             crate::_log!("lex error occurred");
         }
@@ -148,8 +270,9 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
 
     fn get_continuous_help(&mut self, uri: &NormalizedUrl, pos: Position) -> Option<SignatureHelp> {
         if let Some((comma, Expr::Call(call))) = self.get_min_expr(uri, pos, -1) {
-            let nth = self.nth(uri, call.args.loc(), &comma) as u32 + 1;
-            let help = self.make_sig_help(call.obj.as_ref(), nth);
+            let nth = self.nth(uri, call.args.loc(), &comma) + 1;
+            let active = self.active_arg(&call, pos, nth);
+            let help = self.make_sig_help(&Callee::from_call(&call), &active);
             self.current_sig = Some(Expr::Call(call));
             return help;
         } else {
@@ -158,61 +281,105 @@ impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
         None
     }
 
-    fn make_sig_help<S: HasType + NoTypeDisplay>(
-        &self,
-        sig: &S,
-        nth: u32,
-    ) -> Option<SignatureHelp> {
-        let sig_t = sig.ref_t();
-        let mut parameters = vec![];
-        let sig = sig.to_string_notype();
-        let label = format!("{sig}: {}", sig_t.to_string_unabbreviated());
-        let mut end = sig.len() + 1; // +1: (
-        for nd_param in sig_t.non_default_params()? {
-            let start = end + 2;
-            end = get_end(start, nd_param, ParamKind::NonDefault);
-            let param_info = ParameterInformation {
-                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
-                documentation: None, //Some(Documentation::String(nd_param.typ().to_string())),
-            };
-            parameters.push(param_info);
-        }
-        if let Some(var_params) = sig_t.var_params() {
-            let start = end + 2;
-            end = get_end(start, var_params, ParamKind::VarArgs);
-            let param_info = ParameterInformation {
-                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
-                documentation: None, //Some(Documentation::String(var_params.typ().to_string())),
+    /// Source code of the default values of the subroutine defined at `def_loc`
+    fn default_values(&self, def_loc: &AbsLocation) -> Dict<Str, String> {
+        let mut values = Dict::new();
+        let Some(uri) = def_loc
+            .module
+            .as_ref()
+            .and_then(|path| NormalizedUrl::from_file_path(path).ok())
+        else {
+            return values;
+        };
+        let Some(range) = util::loc_to_range(def_loc.loc) else {
+            return values;
+        };
+        let Some((_, Expr::Def(def))) = self.get_min_expr(&uri, range.start, 0) else {
+            return values;
+        };
+        let Signature::Subr(subr) = &def.sig else {
+            return values;
+        };
+        for d_param in subr.params.defaults.iter() {
+            let (Some(name), Some(range)) = (
+                d_param.sig.inspect(),
+                util::loc_to_range(d_param.default_val.loc()),
+            ) else {
+                continue;
             };
-            parameters.push(param_info);
+            if let Ok(Some(code)) = self.file_cache.get_ranged(&uri, range) {
+                values.insert(name.clone(), code);
+            }
         }
-        let var_args_nth = if sig_t.var_params().is_some() {
-            sig_t.non_default_params()?.len() as u32
-        } else {
-            u32::MAX
-        };
-        for d_params in sig_t.default_params()? {
-            let start = end + 2;
-            end = get_end(start, d_params, ParamKind::Default);
-            let param_info = ParameterInformation {
-                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
-                documentation: None, //Some(Documentation::String(d_params.typ().to_string())),
+        values
+    }
+
+    fn make_signature(
+        &self,
+        callee: &Callee,
+        subr: &Type,
+        default_values: &Dict<Str, String>,
+    ) -> Option<SignatureInformation> {
+        let skip = if callee.is_method { 1 } else { 0 };
+        let mut label = SignatureLabel::new(&callee.name);
+        for nd_param in subr.non_default_params()?.iter().skip(skip) {
+            label.push_param(param_text(nd_param, ""));
+        }
+        if let Some(var_params) = subr.var_params() {
+            label.push_param(param_text(&var_params, "*"));
+        }
+        for d_param in subr.default_params()?.iter() {
+            let default = d_param.name().and_then(|name| default_values.get(name));
+            let text = match (d_param.name(), default) {
+                (Some(_), Some(default)) => format!("{} := {default}", param_text(d_param, "")),
+                (Some(name), None) => {
+                    format!("{name} := {}", d_param.typ().to_string_unabbreviated())
+                }
+                (None, _) => param_text(d_param, ""),
             };
-            parameters.push(param_info);
+            label.push_param(text);
+        }
+        if let Some(kw_var_params) = subr.kw_var_params() {
+            label.push_param(param_text(&kw_var_params, "**"));
         }
-        let nth = (parameters.len().saturating_sub(1) as u32)
-            .min(nth)
-            .min(var_args_nth);
+        let (label, parameters) = label.finish(subr.return_t().as_deref());
         let info = SignatureInformation {
             label,
             documentation: None,
             parameters: Some(parameters),
-            active_parameter: Some(nth),
+            active_parameter: None,
         };
+        Some(info)
+    }
+
+    /// Each overload (`f: (Int -> Int) and (Str -> Str)`) is shown as a separate signature.
+    /// The first overload that can accept the active argument is selected.
+    fn make_sig_help(&self, callee: &Callee, active: &ActiveArg) -> Option<SignatureHelp> {
+        let default_values = callee
+            .def_loc
+            .as_ref()
+            .map(|def_loc| self.default_values(def_loc))
+            .unwrap_or_default();
+        let skip = if callee.is_method { 1 } else { 0 };
+        let mut signatures = vec![];
+        let mut active_signature = None;
+        for subr in callee.t.intersection_types() {
+            let Some(mut info) = self.make_signature(callee, &subr, &default_values) else {
+                continue;
+            };
+            info.active_parameter = active_param_index(&subr, skip, active);
+            if active_signature.is_none() && info.active_parameter.is_some() {
+                active_signature = Some(signatures.len() as u32);
+            }
+            signatures.push(info);
+        }
+        if signatures.is_empty() {
+            return None;
+        }
         Some(SignatureHelp {
-            signatures: vec![info],
+            signatures,
+            active_signature: Some(active_signature.unwrap_or(0)),
             active_parameter: None,
-            active_signature: None,
         })
     }
 }
@@ -0,0 +1,242 @@
+use erg_common::traits::Locational;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::hir::{Block, Def, Expr};
+use erg_compiler::ty::HasType;
+
+use erg_compiler::varinfo::VarInfo;
+
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, SymbolInformation, SymbolKind,
+    Url, WorkspaceSymbolParams,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+fn def_symbol_kind(def: &Def, in_class: bool) -> SymbolKind {
+    let kind = def.def_kind();
+    if kind.is_class() {
+        SymbolKind::CLASS
+    } else if kind.is_trait() {
+        SymbolKind::INTERFACE
+    } else if def.sig.is_subr() || def.sig.ident().ref_t().is_subr() {
+        if in_class {
+            SymbolKind::METHOD
+        } else {
+            SymbolKind::FUNCTION
+        }
+    } else if in_class {
+        SymbolKind::FIELD
+    } else if def.sig.is_const() {
+        SymbolKind::CONSTANT
+    } else {
+        SymbolKind::VARIABLE
+    }
+}
+
+const WORKSPACE_SYMBOL_LIMIT: usize = 256;
+
+fn var_symbol_kind(vi: &VarInfo) -> SymbolKind {
+    if vi.t.is_class_type() {
+        SymbolKind::CLASS
+    } else if vi.t.is_trait_type() {
+        SymbolKind::INTERFACE
+    } else if vi.t.is_method() {
+        SymbolKind::METHOD
+    } else if vi.t.is_subr() {
+        SymbolKind::FUNCTION
+    } else if vi.kind.is_instance_attr() {
+        SymbolKind::FIELD
+    } else {
+        SymbolKind::VARIABLE
+    }
+}
+
+/// Case-insensitive subsequence matching.
+/// Returns `None` if `name` does not contain all characters of `query` in order.
+/// The higher the score, the better the match (consecutive matches and matches at the beginning are preferred).
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut chars = name.chars().enumerate();
+    let mut prev = None;
+    for q in query.chars() {
+        let (i, _) = chars.find(|(_, c)| c.eq_ignore_ascii_case(&q))?;
+        if i == 0 {
+            score += 3;
+        }
+        if prev.map_or(i == 0, |prev| prev + 1 == i) {
+            score += 2;
+        }
+        score += 1;
+        prev = Some(i);
+    }
+    Some(score)
+}
+
+#[allow(deprecated)]
+fn new_symbol<L: Locational, M: Locational>(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    whole: &L,
+    selection: &M,
+    children: Option<Vec<DocumentSymbol>>,
+) -> Option<DocumentSymbol> {
+    let range = util::loc_to_range(whole.loc())?;
+    let selection_range = util::loc_to_range(selection.loc()).unwrap_or(range);
+    Some(DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children,
+    })
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_document_symbol(
+        &mut self,
+        params: DocumentSymbolParams,
+    ) -> ELSResult<Option<DocumentSymbolResponse>> {
+        send_log(format!("document symbol requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let Some(hir) = self.analysis_result.get_hir(&uri) else {
+            return Ok(None);
+        };
+        let symbols = Self::block_to_symbols(hir.module.iter(), false);
+        Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+    }
+
+    fn block_to_symbols<'e, I: IntoIterator<Item = &'e Expr>>(
+        exprs: I,
+        in_class: bool,
+    ) -> Vec<DocumentSymbol> {
+        exprs
+            .into_iter()
+            .filter_map(|expr| Self::expr_to_symbol(expr, in_class))
+            .collect()
+    }
+
+    fn expr_to_symbol(expr: &Expr, in_class: bool) -> Option<DocumentSymbol> {
+        match expr {
+            Expr::Def(def) => {
+                let ident = def.sig.ident();
+                let children = Self::def_children(&def.body.block);
+                let kind = def_symbol_kind(def, in_class);
+                let detail = (kind != SymbolKind::CLASS && kind != SymbolKind::INTERFACE)
+                    .then(|| ident.ref_t().to_string());
+                new_symbol(
+                    ident.inspect().to_string(),
+                    detail,
+                    kind,
+                    def,
+                    ident,
+                    children,
+                )
+            }
+            Expr::ClassDef(class_def) => {
+                let ident = class_def.sig.ident();
+                let methods = Self::block_to_symbols(class_def.methods.iter(), true);
+                new_symbol(
+                    ident.inspect().to_string(),
+                    None,
+                    SymbolKind::CLASS,
+                    class_def,
+                    ident,
+                    Some(methods),
+                )
+            }
+            Expr::PatchDef(patch_def) => {
+                let ident = patch_def.sig.ident();
+                let methods = Self::block_to_symbols(patch_def.methods.iter(), true);
+                new_symbol(
+                    ident.inspect().to_string(),
+                    None,
+                    SymbolKind::NAMESPACE,
+                    patch_def,
+                    ident,
+                    Some(methods),
+                )
+            }
+            Expr::TypeAsc(tasc) if in_class => {
+                let Expr::Accessor(acc) = tasc.expr.as_ref() else {
+                    return None;
+                };
+                new_symbol(
+                    acc.show(),
+                    Some(tasc.spec.spec_t.to_string()),
+                    SymbolKind::FIELD,
+                    tasc,
+                    acc,
+                    None,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Local definitions inside a subroutine body (e.g. inner functions) are shown as children.
+    fn def_children(block: &Block) -> Option<Vec<DocumentSymbol>> {
+        let children = Self::block_to_symbols(block.iter(), false)
+            .into_iter()
+            .filter(|sym| {
+                sym.kind == SymbolKind::FUNCTION
+                    || sym.kind == SymbolKind::CLASS
+                    || sym.kind == SymbolKind::INTERFACE
+            })
+            .collect::<Vec<_>>();
+        (!children.is_empty()).then_some(children)
+    }
+
+    pub(crate) fn handle_workspace_symbol(
+        &mut self,
+        params: WorkspaceSymbolParams,
+    ) -> ELSResult<Option<Vec<SymbolInformation>>> {
+        send_log(format!("workspace symbol requested: {params:?}"))?;
+        let Some(index) = self.get_index() else {
+            return Ok(None);
+        };
+        let mut candidates = vec![];
+        for (def_loc, value) in index.members().iter() {
+            let (Some(path), Some(range)) = (&def_loc.module, util::loc_to_range(def_loc.loc))
+            else {
+                continue;
+            };
+            // parameters are not worth jumping to
+            if value.vi.kind.is_parameter() {
+                continue;
+            }
+            let Some(score) = fuzzy_score(&params.query, &value.name) else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(path) else {
+                continue;
+            };
+            let container_name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().trim_end_matches(".d").to_string());
+            #[allow(deprecated)]
+            let symbol = SymbolInformation {
+                name: value.name.to_string(),
+                kind: var_symbol_kind(&value.vi),
+                tags: None,
+                deprecated: None,
+                location: lsp_types::Location::new(uri, range),
+                container_name,
+            };
+            candidates.push((score, symbol));
+        }
+        candidates.sort_by(|(l, l_sym), (r, r_sym)| r.cmp(l).then(l_sym.name.cmp(&r_sym.name)));
+        let symbols = candidates
+            .into_iter()
+            .take(WORKSPACE_SYMBOL_LIMIT)
+            .map(|(_, symbol)| symbol)
+            .collect();
+        Ok(Some(symbols))
+    }
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::context::Context;
+use erg_compiler::erg_parser::parse::Parsable;
+use erg_compiler::ty::Type;
+use erg_compiler::varinfo::VarInfo;
+
+use lsp_types::request::Request;
+use lsp_types::{
+    PartialResultParams, Range, SymbolKind, SymbolTag, TextDocumentPositionParams, Url,
+    WorkDoneProgressParams,
+};
+
+use crate::server::{send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+// The type hierarchy requests were added in LSP 3.17 and are not defined in `lsp_types` 0.93.
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<SymbolTag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub uri: Url,
+    pub range: Range,
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyPrepareParams {
+    #[serde(flatten)]
+    pub text_document_position_params: TextDocumentPositionParams,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySupertypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchySubtypesParams {
+    pub item: TypeHierarchyItem,
+    #[serde(flatten)]
+    pub work_done_progress_params: WorkDoneProgressParams,
+    #[serde(flatten)]
+    pub partial_result_params: PartialResultParams,
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchyPrepare {}
+
+impl Request for TypeHierarchyPrepare {
+    type Params = TypeHierarchyPrepareParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "textDocument/prepareTypeHierarchy";
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySupertypes {}
+
+impl Request for TypeHierarchySupertypes {
+    type Params = TypeHierarchySupertypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/supertypes";
+}
+
+#[derive(Debug)]
+pub enum TypeHierarchySubtypes {}
+
+impl Request for TypeHierarchySubtypes {
+    type Params = TypeHierarchySubtypesParams;
+    type Result = Option<Vec<TypeHierarchyItem>>;
+    const METHOD: &'static str = "typeHierarchy/subtypes";
+}
+
+fn is_type_def(vi: &VarInfo) -> bool {
+    vi.t.is_class_type() || vi.t.is_trait_type()
+}
+
+impl<Checker: BuildRunnable, Parser: Parsable> Server<Checker, Parser> {
+    pub(crate) fn handle_type_hierarchy_prepare(
+        &mut self,
+        params: TypeHierarchyPrepareParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("type hierarchy prepare requested: {params:?}"))?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let Some(token) = self.file_cache.get_token(&uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(&uri, &token)? else {
+            return Ok(None);
+        };
+        if !is_type_def(&vi) {
+            return Ok(None);
+        }
+        Ok(self
+            .type_hierarchy_item(token.inspect(), &vi)
+            .map(|item| vec![item]))
+    }
+
+    /// Super classes, (nominally) implemented traits and structural types the class satisfies.
+    pub(crate) fn handle_type_hierarchy_supertypes(
+        &mut self,
+        params: TypeHierarchySupertypesParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("type hierarchy supertypes requested: {params:?}"))?;
+        let Some((ctx, typ)) = self.get_item_type(&params.item) else {
+            return Ok(None);
+        };
+        let mut items = vec![];
+        for sup in ctx.get_super_types(&typ) {
+            // types defined in the current module are not found by `get_type_info` (`<module>::C`)
+            let info = ctx
+                .get_type_info(&sup)
+                .or_else(|| ctx.get_var_info(&sup.local_name()));
+            if let Some((name, vi)) = info {
+                items.extend(self.type_hierarchy_item(name.inspect(), vi));
+            }
+        }
+        for (name, vi, sup) in self.get_workspace_types() {
+            if sup.is_structural() && sup != typ && ctx.subtype_of(&typ, &sup) {
+                items.extend(self.type_hierarchy_item(&name, &vi));
+            }
+        }
+        Ok(Some(items))
+    }
+
+    /// Classes/traits that inherit or implement the type.
+    /// If the type is structural, all types that satisfy it are included.
+    pub(crate) fn handle_type_hierarchy_subtypes(
+        &mut self,
+        params: TypeHierarchySubtypesParams,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        send_log(format!("type hierarchy subtypes requested: {params:?}"))?;
+        let Some((ctx, typ)) = self.get_item_type(&params.item) else {
+            return Ok(None);
+        };
+        let mut items = vec![];
+        for (name, vi, sub) in self.get_workspace_types() {
+            if sub == typ {
+                continue;
+            }
+            let is_sub = if typ.is_structural() {
+                ctx.subtype_of(&sub, &typ)
+            } else {
+                ctx.get_super_types(&sub).contains(&typ)
+            };
+            if is_sub {
+                items.extend(self.type_hierarchy_item(&name, &vi));
+            }
+        }
+        Ok(Some(items))
+    }
+
+    fn type_hierarchy_item(&self, name: &str, vi: &VarInfo) -> Option<TypeHierarchyItem> {
+        let lsp_loc = self.abs_loc_to_lsp_loc(&vi.def_loc)?;
+        let kind = if vi.t.is_trait_type() {
+            SymbolKind::INTERFACE
+        } else {
+            SymbolKind::CLASS
+        };
+        Some(TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp_loc.uri,
+            range: lsp_loc.range,
+            selection_range: lsp_loc.range,
+            data: None,
+        })
+    }
+
+    fn get_item_type(&self, item: &TypeHierarchyItem) -> Option<(&Context, Type)> {
+        let uri = NormalizedUrl::new(item.uri.clone());
+        let ctx = &self.modules.get(&uri)?.context;
+        let typ = ctx.get_type_by_name(&item.name)?;
+        Some((ctx, typ))
+    }
+
+    /// All classes and traits defined in the analyzed modules.
+    fn get_workspace_types(&self) -> Vec<(String, VarInfo, Type)> {
+        let mut types = vec![];
+        let Some(index) = self.get_index() else {
+            return types;
+        };
+        for (def_loc, value) in index.members().iter() {
+            if !is_type_def(&value.vi) {
+                continue;
+            }
+            let Some(path) = def_loc.module.as_ref() else {
+                continue;
+            };
+            let Ok(uri) = NormalizedUrl::from_file_path(path) else {
+                continue;
+            };
+            let typ = self
+                .modules
+                .get(&uri)
+                .and_then(|module| module.context.get_type_by_name(&value.name));
+            if let (Some(typ), Some(_)) = (typ, util::loc_to_range(def_loc.loc)) {
+                types.push((value.name.to_string(), value.vi.clone(), typ));
+            }
+        }
+        types
+    }
+}
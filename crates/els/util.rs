@@ -3,6 +3,7 @@ use std::fs::{metadata, Metadata};
 use std::path::{Path, PathBuf};
 
 use erg_common::consts::CASE_SENSITIVE;
+use erg_common::grapheme::{col_to_utf16, utf16_to_col};
 use erg_common::normalize_path;
 use erg_common::traits::{DequeStream, Locational};
 
@@ -80,6 +81,29 @@ pub(crate) fn loc_to_range(loc: erg_common::error::Location) -> Option<Range> {
     Some(Range::new(start, end))
 }
 
+/// Like `loc_to_range`, but the columns (grapheme clusters) are converted into the LSP characters (UTF-16) using `code`.
+pub(crate) fn loc_to_range_in(code: &str, loc: erg_common::error::Location) -> Option<Range> {
+    let mut range = loc_to_range(loc)?;
+    let mut lines = code.lines().skip(range.start.line as usize);
+    let start_line = lines.next().unwrap_or("");
+    range.start.character = col_to_utf16(start_line, range.start.character as usize) as u32;
+    let end_line = if range.end.line == range.start.line {
+        start_line
+    } else {
+        lines
+            .nth((range.end.line - range.start.line - 1) as usize)
+            .unwrap_or("")
+    };
+    range.end.character = col_to_utf16(end_line, range.end.character as usize) as u32;
+    Some(range)
+}
+
+/// Converts the LSP character (UTF-16) of `pos` into the column (grapheme clusters) of `Location`.
+pub(crate) fn pos_to_col_pos(code: &str, pos: Position) -> Position {
+    let line = code.lines().nth(pos.line as usize).unwrap_or("");
+    Position::new(pos.line, utf16_to_col(line, pos.character as usize) as u32)
+}
+
 pub(crate) fn loc_to_pos(loc: erg_common::error::Location) -> Option<Position> {
     // FIXME: should `Position::new(loc.ln_begin()? - 1, loc.col_begin()?)`
     // but completion doesn't work (because the newline will be included)
@@ -108,6 +132,19 @@ pub(crate) fn pos_in_loc<L: Locational>(loc: &L, pos: Position) -> bool {
     }
 }
 
+/// Converts the LSP character (UTF-16) into the byte offset in `line`.
+/// Returns `None` if `character` is beyond the end of the line.
+pub(crate) fn utf16_to_byte(line: &str, character: u32) -> Option<usize> {
+    let mut utf16 = 0;
+    for (i, c) in line.char_indices() {
+        if utf16 >= character {
+            return Some(i);
+        }
+        utf16 += c.len_utf16() as u32;
+    }
+    (utf16 >= character).then_some(line.len())
+}
+
 pub(crate) fn pos_to_byte_index(src: &str, pos: Position) -> usize {
     if src.is_empty() {
         return 0;
@@ -122,7 +159,8 @@ pub(crate) fn pos_to_byte_index(src: &str, pos: Position) -> usize {
             line += 1;
             col = 0;
         } else {
-            col += 1;
+            // LSP characters are UTF-16 code units
+            col += c.len_utf16() as u32;
         }
     }
     // EOF
@@ -153,6 +191,28 @@ pub(crate) fn uri_to_path(uri: &NormalizedUrl) -> PathBuf {
     )
 }
 
+/// The path of `module` that can be imported from `importer` (e.g. `foo/bar` for `foo/bar.er`),
+/// and whether it is a declaration file of a Python module (`.d.er`).
+/// Returns `None` if `module` is not under the directory of `importer`.
+pub(crate) fn import_path(importer: &Path, module: &Path) -> Option<(String, bool)> {
+    let rel = module.strip_prefix(importer.parent()?).ok()?;
+    let rel = rel
+        .components()
+        .map(|comp| comp.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let is_decl = rel.ends_with(".d.er");
+    let path = rel
+        .trim_end_matches(".d.er")
+        .trim_end_matches(".er")
+        .trim_end_matches("__init__")
+        .trim_end_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+    Some((path.to_string(), is_decl))
+}
+
 pub(crate) fn denormalize(uri: Url) -> Url {
     Url::parse(&uri.as_str().replace("c:", "file:///c%3A")).unwrap()
 }
@@ -23,6 +23,21 @@ impl<T: ?Sized> CacheSet<T> {
     }
 }
 
+impl<T: Hash + ?Sized> CacheSet<T> {
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// The cached values are still valid, but are no longer shared with the newly cached ones.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
 impl Clone for CacheSet<str> {
     fn clone(&self) -> Self {
         Self(self.0.clone())
@@ -37,22 +52,26 @@ impl<T: Hash + Eq> Clone for CacheSet<T> {
 
 impl CacheSet<str> {
     pub fn get(&self, s: &str) -> Str {
-        if let Some(cached) = self.0.borrow().get(s) {
+        // lookup and insertion are done under the same lock,
+        // so all threads get the same (stored) `Arc`
+        let mut cache = self.0.borrow_mut();
+        if let Some(cached) = cache.get(s) {
             return cached.clone().into();
-        } // &self.0 is dropped
+        }
         let s = Str::rc(s);
-        self.0.borrow_mut().insert(s.clone().into_rc());
+        cache.insert(s.clone().into_rc());
         s
     }
 }
 
 impl<T: Hash + Eq + Clone> CacheSet<[T]> {
     pub fn get(&self, q: &[T]) -> Arc<[T]> {
-        if let Some(cached) = self.0.borrow().get(q) {
+        let mut cache = self.0.borrow_mut();
+        if let Some(cached) = cache.get(q) {
             return cached.clone();
-        } // &self.0 is dropped
+        }
         let s = ArcArray::from(q);
-        self.0.borrow_mut().insert(s.clone());
+        cache.insert(s.clone());
         s
     }
 }
@@ -63,11 +82,12 @@ impl<T: Hash + Eq> CacheSet<T> {
         Arc<T>: Borrow<Q>,
         Q: ToOwned<Owned = T>,
     {
-        if let Some(cached) = self.0.borrow().get(q) {
+        let mut cache = self.0.borrow_mut();
+        if let Some(cached) = cache.get(q) {
             return cached.clone();
-        } // &self.0 is dropped
+        }
         let s = Arc::from(q.to_owned());
-        self.0.borrow_mut().insert(s.clone());
+        cache.insert(s.clone());
         s
     }
 }
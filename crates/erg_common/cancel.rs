@@ -0,0 +1,132 @@
+//! Cancellation of an in-flight compilation.
+//!
+//! A `CancellationToken` is carried by `ErgConfig` (so it is shared with the builders of the imported modules),
+//! and the compiler checks it between the phases and the top-level chunks.
+//! A cancelled compilation fails with a single `InterruptedError` ("compilation cancelled") and emits no artifacts.
+//!
+//! The token is cancelled by Ctrl-C (see `cancel_on_ctrl_c`), by the language server when a newer edit arrives, etc.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+static CTRL_C_TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+
+/// Called from the signal handler, so this must be async-signal-safe (no allocation, no locking).
+fn on_ctrl_c() {
+    match CTRL_C_TOKEN.get() {
+        // the second Ctrl-C terminates the process immediately
+        Some(token) if !token.is_cancelled() => token.cancel(),
+        _ => sys::exit_immediately(crate::exit_code::CANCELLED),
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::c_int;
+
+    const SIGINT: c_int = 2;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    extern "C" fn handler(_signum: c_int) {
+        super::on_ctrl_c();
+    }
+
+    pub(super) fn install() {
+        // SAFETY: `handler` only touches atomics or calls `_exit`
+        unsafe {
+            signal(SIGINT, handler);
+        }
+    }
+
+    pub(super) fn exit_immediately(code: i32) -> ! {
+        // SAFETY: `_exit` is async-signal-safe
+        unsafe { _exit(code) }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    const CTRL_C_EVENT: u32 = 0;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    unsafe extern "system" fn handler(ctrl_type: u32) -> i32 {
+        if ctrl_type == CTRL_C_EVENT {
+            super::on_ctrl_c();
+            1
+        } else {
+            0
+        }
+    }
+
+    pub(super) fn install() {
+        // SAFETY: `handler` only touches atomics or exits the process
+        unsafe {
+            SetConsoleCtrlHandler(Some(handler), 1);
+        }
+    }
+
+    pub(super) fn exit_immediately(code: i32) -> ! {
+        std::process::exit(code)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub(super) fn install() {}
+
+    pub(super) fn exit_immediately(code: i32) -> ! {
+        std::process::exit(code)
+    }
+}
+
+/// Makes Ctrl-C cancel `token` instead of killing the process.
+/// The second Ctrl-C terminates the process (with `exit_code::CANCELLED`).
+/// This can be called only once; the later calls are ignored.
+pub fn cancel_on_ctrl_c(token: &CancellationToken) {
+    if CTRL_C_TOKEN.set(token.clone()).is_ok() {
+        sys::install();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let shared = token.clone();
+        assert!(!shared.is_cancelled());
+        token.cancel();
+        assert!(shared.is_cancelled());
+        assert!(!CancellationToken::new().is_cancelled());
+    }
+}
@@ -0,0 +1,355 @@
+//! Generates the shell completion scripts of `erg` (`erg completions bash|zsh|fish|powershell`).
+//!
+//! The lint names and the target versions are not embedded in the scripts;
+//! the scripts call `erg completions lints` (`erg completions target-versions`) when completing them.
+use std::fmt;
+
+use crate::config::{EmitKind, ErgConfig, ErgMode};
+use crate::help_messages::{COMMANDS, OPTIONS};
+use crate::traits::ExitStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl TryFrom<&str> for Shell {
+    type Error = ();
+    fn try_from(s: &str) -> Result<Self, ()> {
+        match s {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            "powershell" | "pwsh" => Ok(Self::PowerShell),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<Shell> for &str {
+    fn from(shell: Shell) -> Self {
+        match shell {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::PowerShell => "powershell",
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", <&str>::from(*self))
+    }
+}
+
+impl Shell {
+    pub const ALL: [Shell; 4] = [Self::Bash, Self::Zsh, Self::Fish, Self::PowerShell];
+}
+
+/// What `erg completions` prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompletionTarget {
+    Script(Shell),
+    /// the names of the lints, one per line (called by the scripts)
+    Lints,
+    /// the Python versions that `--target-version` accepts, one per line (called by the scripts)
+    TargetVersions,
+}
+
+impl TryFrom<&str> for CompletionTarget {
+    type Error = ();
+    fn try_from(s: &str) -> Result<Self, ()> {
+        match s {
+            "lints" => Ok(Self::Lints),
+            "target-versions" => Ok(Self::TargetVersions),
+            _ => Shell::try_from(s).map(Self::Script),
+        }
+    }
+}
+
+/// The Python versions that the compiler can generate bytecode for
+pub const TARGET_VERSIONS: [&str; 6] = ["3.6", "3.7", "3.8", "3.9", "3.10", "3.11"];
+
+/// How the value of an option is completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ValueHint {
+    /// a value that cannot be completed (e.g. a number)
+    Any,
+    File,
+    Dir,
+    Values(Vec<&'static str>),
+    /// the values printed by `erg completions <target>`
+    Dynamic(&'static str),
+}
+
+/// `None` if the option is a flag
+fn value_hint(option: &str) -> Option<ValueHint> {
+    match option {
+        "--mode" => Some(ValueHint::Values(ErgMode::ALL.map(<&str>::from).to_vec())),
+        "--emit" => Some(ValueHint::Values(EmitKind::ALL.map(<&str>::from).to_vec())),
+        "--allow" => Some(ValueHint::Dynamic("lints")),
+        "--target-version" => Some(ValueHint::Dynamic("target-versions")),
+        "--output-dir" | "--dest" => Some(ValueHint::Dir),
+        "--path" => Some(ValueHint::File),
+        "-c"
+        | "--code"
+        | "--filter"
+        | "--indent-width"
+        | "--max-blank-lines"
+        | "-m"
+        | "--module"
+        | "--ps1"
+        | "--ps2"
+        | "-o"
+        | "--opt-level"
+        | "--optimization-level"
+        | "--py-command"
+        | "--python-command"
+        | "--hex-py-magic-num"
+        | "--hex-python-magic-number"
+        | "--py-magic-num"
+        | "--python-magic-number"
+        | "--py-server-timeout"
+        | "-p"
+        | "--python-version"
+        | "--verbose" => Some(ValueHint::Any),
+        _ => None,
+    }
+}
+
+/// `-?` is omitted because it is a glob pattern in most shells
+fn options() -> impl Iterator<Item = &'static str> {
+    OPTIONS.iter().copied().filter(|opt| *opt != "-?")
+}
+
+fn bash_script() -> String {
+    let mut cases = String::new();
+    for opt in options() {
+        let reply = match value_hint(opt) {
+            None => continue,
+            Some(ValueHint::Any) => "return".to_string(),
+            Some(ValueHint::File) => "COMPREPLY=($(compgen -f -- \"$cur\")); return".to_string(),
+            Some(ValueHint::Dir) => "COMPREPLY=($(compgen -d -- \"$cur\")); return".to_string(),
+            Some(ValueHint::Values(values)) => format!(
+                "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return",
+                values.join(" ")
+            ),
+            Some(ValueHint::Dynamic(target)) => format!(
+                "COMPREPLY=($(compgen -W \"$(erg completions {target} 2>/dev/null)\" -- \"$cur\")); return"
+            ),
+        };
+        cases.push_str(&format!("        {opt}) {reply} ;;\n"));
+    }
+    format!(
+        r#"_erg() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+{cases}        completions) COMPREPLY=($(compgen -W "{shells}" -- "$cur")); return ;;
+    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{options}" -- "$cur"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{commands}" -- "$cur") $(compgen -f -- "$cur"))
+    else
+        COMPREPLY=($(compgen -f -- "$cur"))
+    fi
+}}
+complete -o filenames -F _erg erg
+"#,
+        shells = Shell::ALL.map(<&str>::from).join(" "),
+        options = options().collect::<Vec<_>>().join(" "),
+        commands = COMMANDS.join(" "),
+    )
+}
+
+fn zsh_script() -> String {
+    let mut cases = String::new();
+    for opt in options() {
+        let reply = match value_hint(opt) {
+            None => continue,
+            Some(ValueHint::Any) => "return".to_string(),
+            Some(ValueHint::File) => "_files; return".to_string(),
+            Some(ValueHint::Dir) => "_files -/; return".to_string(),
+            Some(ValueHint::Values(values)) => {
+                format!("compadd -- {}; return", values.join(" "))
+            }
+            Some(ValueHint::Dynamic(target)) => {
+                format!("compadd -- ${{(f)\"$(erg completions {target} 2>/dev/null)\"}}; return")
+            }
+        };
+        cases.push_str(&format!("        {opt}) {reply} ;;\n"));
+    }
+    format!(
+        r#"#compdef erg
+
+_erg() {{
+    case "${{words[CURRENT-1]}}" in
+{cases}        completions) compadd -- {shells}; return ;;
+    esac
+    if [[ "$PREFIX" == -* ]]; then
+        compadd -- {options}
+        return
+    fi
+    if (( CURRENT == 2 )); then
+        compadd -- {commands}
+    fi
+    _files
+}}
+
+if [ "$funcstack[1]" = "_erg" ]; then
+    _erg "$@"
+else
+    compdef _erg erg
+fi
+"#,
+        shells = Shell::ALL.map(<&str>::from).join(" "),
+        options = options().collect::<Vec<_>>().join(" "),
+        commands = COMMANDS.join(" "),
+    )
+}
+
+fn fish_script() -> String {
+    let mut script = format!(
+        "complete -c erg -n __fish_use_subcommand -a '{}'\n",
+        COMMANDS.join(" ")
+    );
+    script.push_str(&format!(
+        "complete -c erg -n '__fish_seen_subcommand_from completions' -x -a '{}'\n",
+        Shell::ALL.map(<&str>::from).join(" ")
+    ));
+    for opt in options() {
+        let name = if let Some(long) = opt.strip_prefix("--") {
+            format!("-l {long}")
+        } else if opt.len() == 2 {
+            format!("-s {}", &opt[1..])
+        } else {
+            format!("-o {}", &opt[1..])
+        };
+        let value = match value_hint(opt) {
+            None => String::new(),
+            Some(ValueHint::Any) => " -x".to_string(),
+            Some(ValueHint::File) => " -r -F".to_string(),
+            Some(ValueHint::Dir) => " -x -a '(__fish_complete_directories)'".to_string(),
+            Some(ValueHint::Values(values)) => format!(" -x -a '{}'", values.join(" ")),
+            Some(ValueHint::Dynamic(target)) => {
+                format!(" -x -a '(erg completions {target} 2>/dev/null)'")
+            }
+        };
+        script.push_str(&format!("complete -c erg {name}{value}\n"));
+    }
+    script
+}
+
+fn powershell_script() -> String {
+    let quote = |values: &mut dyn Iterator<Item = &str>| {
+        values
+            .map(|value| format!("'{value}'"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut cases = String::new();
+    let mut no_completion = vec![];
+    for opt in options() {
+        let candidates = match value_hint(opt) {
+            None => continue,
+            Some(ValueHint::Any) => {
+                no_completion.push(opt);
+                continue;
+            }
+            Some(ValueHint::File) => "@(Get-ChildItem -Name)".to_string(),
+            Some(ValueHint::Dir) => "@(Get-ChildItem -Name -Directory)".to_string(),
+            Some(ValueHint::Values(values)) => {
+                format!("@({})", quote(&mut values.into_iter()))
+            }
+            Some(ValueHint::Dynamic(target)) => {
+                format!("@(erg completions {target} 2>$null)")
+            }
+        };
+        cases.push_str(&format!("        '{opt}' {{ {candidates} }}\n"));
+    }
+    format!(
+        r#"Register-ArgumentCompleter -Native -CommandName erg -ScriptBlock {{
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})
+    $prev = if ($wordToComplete) {{ $words[-2] }} else {{ $words[-1] }}
+    $count = if ($wordToComplete) {{ $words.Count - 1 }} else {{ $words.Count }}
+    $candidates = switch -CaseSensitive ($prev) {{
+{cases}        'completions' {{ @({shells}) }}
+        {{ $_ -cin @({no_completion}) }} {{ @() }}
+        default {{
+            if ($wordToComplete -like '-*') {{ @({options}) }}
+            elseif ($count -eq 1) {{ @({commands}) + @(Get-ChildItem -Name) }}
+            else {{ @(Get-ChildItem -Name) }}
+        }}
+    }}
+    $candidates | Where-Object {{ $_ -like "$wordToComplete*" }} | ForEach-Object {{
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }}
+}}
+"#,
+        shells = quote(&mut Shell::ALL.map(<&str>::from).into_iter()),
+        no_completion = quote(&mut no_completion.into_iter()),
+        options = quote(&mut options()),
+        commands = quote(&mut COMMANDS.iter().copied()),
+    )
+}
+
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_script(),
+        Shell::Zsh => zsh_script(),
+        Shell::Fish => fish_script(),
+        Shell::PowerShell => powershell_script(),
+    }
+}
+
+/// Prints the completion script (or the candidates of `target`).
+/// `lints` are the names of the lints (the compiler does not know them).
+pub fn run(cfg: &ErgConfig, lints: &[&str]) -> ExitStatus {
+    match cfg.completion {
+        Some(CompletionTarget::Script(shell)) => print!("{}", script(shell)),
+        Some(CompletionTarget::Lints) => {
+            for lint in lints {
+                println!("{lint}");
+            }
+        }
+        Some(CompletionTarget::TargetVersions) => {
+            for version in TARGET_VERSIONS {
+                println!("{version}");
+            }
+        }
+        None => {
+            eprintln!("the shell is not passed (`erg completions bash|zsh|fish|powershell`)");
+            return ExitStatus::new(crate::exit_code::USAGE_ERROR, 0, 0);
+        }
+    }
+    ExitStatus::OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_scripts() {
+        assert_eq!(
+            CompletionTarget::try_from("pwsh"),
+            Ok(CompletionTarget::Script(Shell::PowerShell))
+        );
+        assert!(CompletionTarget::try_from("cmd").is_err());
+        for shell in Shell::ALL {
+            let script = script(shell);
+            assert!(script.contains("transpile"), "{shell}");
+            assert!(script.contains("target-version"), "{shell}");
+            assert!(script.contains("erg completions lints"), "{shell}");
+            assert!(!script.contains("-?"), "{shell}");
+        }
+        assert!(bash_script().contains("--mode) COMPREPLY=($(compgen -W \"lex parse"));
+        assert!(fish_script().contains("complete -c erg -s q\n"));
+    }
+}
@@ -4,16 +4,37 @@
 use std::env;
 use std::fmt;
 use std::io::{stdin, IsTerminal, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::str::FromStr;
 
+use crate::cancel::CancellationToken;
+use crate::completion::{CompletionTarget, Shell};
+use crate::exit_code;
 use crate::help_messages::{command_message, mode_message, OPTIONS};
-use crate::io::{Input, Output};
+use crate::io::{Input, InputKind, Output};
+use crate::lang::LanguageCode;
 use crate::levenshtein::get_similar_name;
 use crate::normalize_path;
-use crate::python_util::{detect_magic_number, get_python_version, PythonVersion};
+use crate::pathutil::{find_upward, is_glob_pattern, package_root};
+use crate::python_util::{
+    detect_magic_number, env_magic_number, get_python_version, PythonVersion,
+};
 use crate::serialize::{get_magic_num_from_bytes, get_ver_from_magic_num};
+use crate::toml::{self, TomlError, TomlValue};
+
+/// The name of the project configuration file, which is searched upward from the working directory.
+pub const PROJECT_CONFIG_FILE: &str = "erg.toml";
+
+const PROJECT_CONFIG_KEYS: [&str; 7] = [
+    "target_version",
+    "opt_level",
+    "language",
+    "module_paths",
+    "deterministic",
+    "lint.allow",
+    "lint.<name>",
+];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ErgMode {
@@ -22,11 +43,22 @@ pub enum ErgMode {
     Desugar,
     TypeCheck,
     FullCheck,
+    Format,
+    Lint,
+    Fix,
+    Doc,
+    Test,
+    Bench,
+    Minimize,
+    Coverage,
     Compile,
     Transpile,
     Execute,
     LanguageServer,
     Read,
+    Completions,
+    New,
+    Init,
 }
 
 impl TryFrom<&str> for ErgMode {
@@ -38,11 +70,22 @@ impl TryFrom<&str> for ErgMode {
             "desugar" | "desugarer" => Ok(Self::Desugar),
             "typecheck" | "lower" | "tc" => Ok(Self::TypeCheck),
             "fullcheck" | "check" | "checker" => Ok(Self::FullCheck),
+            "fmt" | "format" | "formatter" => Ok(Self::Format),
+            "lint" | "linter" => Ok(Self::Lint),
+            "fix" | "fixer" => Ok(Self::Fix),
+            "doc" | "docs" | "document" => Ok(Self::Doc),
+            "test" | "tester" => Ok(Self::Test),
+            "bench" | "benchmark" => Ok(Self::Bench),
+            "minimize" | "minimizer" => Ok(Self::Minimize),
+            "coverage" => Ok(Self::Coverage),
             "compile" | "compiler" => Ok(Self::Compile),
             "transpile" | "transpiler" => Ok(Self::Transpile),
             "run" | "execute" => Ok(Self::Execute),
             "server" | "language-server" => Ok(Self::LanguageServer),
             "byteread" | "read" | "reader" => Ok(Self::Read),
+            "completions" | "completion" => Ok(Self::Completions),
+            "new" => Ok(Self::New),
+            "init" => Ok(Self::Init),
             _ => Err(()),
         }
     }
@@ -56,11 +99,22 @@ impl From<ErgMode> for &str {
             ErgMode::Desugar => "desugar",
             ErgMode::TypeCheck => "typecheck",
             ErgMode::FullCheck => "fullcheck",
+            ErgMode::Format => "fmt",
+            ErgMode::Lint => "lint",
+            ErgMode::Fix => "fix",
+            ErgMode::Doc => "doc",
+            ErgMode::Test => "test",
+            ErgMode::Bench => "bench",
+            ErgMode::Minimize => "minimize",
+            ErgMode::Coverage => "coverage",
             ErgMode::Compile => "compile",
             ErgMode::Transpile => "transpile",
             ErgMode::Execute => "execute",
             ErgMode::LanguageServer => "language-server",
             ErgMode::Read => "read",
+            ErgMode::Completions => "completions",
+            ErgMode::New => "new",
+            ErgMode::Init => "init",
         }
     }
 }
@@ -71,13 +125,215 @@ impl fmt::Display for ErgMode {
     }
 }
 
+impl ErgMode {
+    pub const ALL: [ErgMode; 21] = [
+        Self::Lex,
+        Self::Parse,
+        Self::Desugar,
+        Self::TypeCheck,
+        Self::FullCheck,
+        Self::Format,
+        Self::Lint,
+        Self::Fix,
+        Self::Doc,
+        Self::Test,
+        Self::Bench,
+        Self::Minimize,
+        Self::Coverage,
+        Self::Compile,
+        Self::Transpile,
+        Self::Execute,
+        Self::LanguageServer,
+        Self::Read,
+        Self::Completions,
+        Self::New,
+        Self::Init,
+    ];
+
+    /// The modes running the program exit with the exit code of the program.
+    pub const fn runs_program(&self) -> bool {
+        matches!(self, Self::Execute | Self::Test | Self::Bench)
+    }
+}
+
+/// The style options of `erg fmt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatStyle {
+    /// the number of spaces per indentation level
+    pub indent_width: usize,
+    /// consecutive blank lines are reduced to this number
+    pub max_blank_lines: usize,
+    /// put spaces around binary operators (e.g. `x+1` -> `x + 1`)
+    pub spaces_around_ops: bool,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            max_blank_lines: 2,
+            spaces_around_ops: true,
+        }
+    }
+}
+
+/// The kinds of the packages that `erg new` (`erg init`) creates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PackageTemplate {
+    /// an executable package (`src/main.er`)
+    #[default]
+    App,
+    /// a library package (`src/lib.er`)
+    Lib,
+    /// a library wrapping a Python module (`src/*.py` and its declaration file `src/*.d.er`)
+    Py,
+}
+
+impl From<PackageTemplate> for &str {
+    fn from(template: PackageTemplate) -> Self {
+        match template {
+            PackageTemplate::App => "app",
+            PackageTemplate::Lib => "lib",
+            PackageTemplate::Py => "py",
+        }
+    }
+}
+
+impl fmt::Display for PackageTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", <&str>::from(*self))
+    }
+}
+
+/// The intermediate representations (and the outputs) that `--emit` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmitKind {
+    /// the desugared AST
+    Ast,
+    /// the HIR without the types
+    Hir,
+    TypedHir,
+    /// the disassembly of the code object
+    Bytecode,
+    Pyc,
+    /// the transpiled Python script
+    Py,
+    /// the Python stub file of the public API
+    Pyi,
+}
+
+impl TryFrom<&str> for EmitKind {
+    type Error = ();
+    fn try_from(s: &str) -> Result<Self, ()> {
+        match s {
+            "ast" => Ok(Self::Ast),
+            "hir" => Ok(Self::Hir),
+            "typed-hir" => Ok(Self::TypedHir),
+            "bytecode" => Ok(Self::Bytecode),
+            "pyc" => Ok(Self::Pyc),
+            "py" => Ok(Self::Py),
+            "pyi" => Ok(Self::Pyi),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<EmitKind> for &str {
+    fn from(kind: EmitKind) -> Self {
+        match kind {
+            EmitKind::Ast => "ast",
+            EmitKind::Hir => "hir",
+            EmitKind::TypedHir => "typed-hir",
+            EmitKind::Bytecode => "bytecode",
+            EmitKind::Pyc => "pyc",
+            EmitKind::Py => "py",
+            EmitKind::Pyi => "pyi",
+        }
+    }
+}
+
+impl fmt::Display for EmitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", <&str>::from(*self))
+    }
+}
+
+impl EmitKind {
+    pub const ALL: [EmitKind; 7] = [
+        Self::Ast,
+        Self::Hir,
+        Self::TypedHir,
+        Self::Bytecode,
+        Self::Pyc,
+        Self::Py,
+        Self::Pyi,
+    ];
+
+    /// The extension of the file written by default (e.g. `foo.er` -> `foo.typed.hir`)
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Ast => "ast",
+            Self::Hir => "hir",
+            Self::TypedHir => "typed.hir",
+            Self::Bytecode => "bytecode",
+            Self::Pyc => "pyc",
+            Self::Py => "py",
+            Self::Pyi => "pyi",
+        }
+    }
+
+    pub const fn is_binary(&self) -> bool {
+        matches!(self, Self::Pyc)
+    }
+}
+
+/// An item of `--emit=kind[=path]`.
+/// `path` is `-` for stdout, and `None` for the default file (`<input>.<extension>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmitTarget {
+    pub kind: EmitKind,
+    pub path: Option<PathBuf>,
+}
+
+impl EmitTarget {
+    /// Parses the value of `--emit` (e.g. `ast,hir=-,pyc=out/foo.pyc`).
+    /// ```
+    /// # use erg_common::config::{EmitKind, EmitTarget};
+    /// let targets = EmitTarget::parse_list("ast,pyc=-").unwrap();
+    /// assert_eq!(targets[0].kind, EmitKind::Ast);
+    /// assert_eq!(targets[1].path, Some("-".into()));
+    /// assert!(EmitTarget::parse_list("asm").is_err());
+    /// ```
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, String> {
+        value
+            .split(',')
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let (kind, path) = match item.split_once('=') {
+                    Some((kind, path)) => (kind, Some(PathBuf::from(path))),
+                    None => (item, None),
+                };
+                EmitKind::try_from(kind)
+                    .map(|kind| Self { kind, path })
+                    .map_err(|_| kind.to_string())
+            })
+            .collect()
+    }
+
+    pub fn is_stdout(&self) -> bool {
+        self.path
+            .as_ref()
+            .is_some_and(|path| path.as_os_str() == "-")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ErgConfig {
     pub mode: ErgMode,
     /// optimization level.
     /// * 0: no optimization
     /// * 1 (default): e.g. constant folding, dead code elimination
-    /// * 2: e.g. static dispatching, inlining, peephole
+    /// * 2: e.g. static dispatching, inlining, peephole, memoization of pure functions
     /// * 3: e.g. JIT compiling
     pub opt_level: u8,
     pub no_std: bool,
@@ -86,6 +342,8 @@ pub struct ErgConfig {
     pub target_version: Option<PythonVersion>,
     pub py_server_timeout: u64,
     pub quiet_repl: bool,
+    /// suppress the output other than the diagnostics (summaries, diffs, progress, etc.)
+    pub quiet: bool,
     pub show_type: bool,
     pub input: Input,
     pub output: Output,
@@ -100,6 +358,45 @@ pub struct ErgConfig {
     pub ps1: &'static str,
     pub ps2: &'static str,
     pub runtime_args: Vec<&'static str>,
+    /// files, directories and glob patterns to be checked (e.g. `erg check foo.er src/`)
+    pub targets: Vec<PathBuf>,
+    /// rerun when the files are changed
+    pub watch: bool,
+    /// only report the files that are not formatted (`erg fmt --check`)
+    pub fmt_check: bool,
+    pub fmt_style: FormatStyle,
+    /// only run the tests (benchmarks) whose names contain this string (`erg test --filter <name>`)
+    pub test_filter: Option<&'static str>,
+    /// record the lines executed by the tests (`erg test --coverage`, see `erg_compiler::coverage`)
+    pub coverage: bool,
+    /// also write the Python script transpiled from the same HIR as the .pyc (`erg compile --with-py`)
+    pub with_py: bool,
+    /// check that the artifacts can be written without writing them (`--dry-run`, see `erg_compiler::writer`)
+    pub dry_run: bool,
+    /// apply the machine-applicable suggestions (`erg lint --fix`)
+    pub fix: bool,
+    /// the names of the lints not to be performed (`erg lint --allow`)
+    pub allowed_lints: Vec<&'static str>,
+    /// what `erg completions` prints
+    pub completion: Option<CompletionTarget>,
+    /// the kind of the package created by `erg new` (`erg init`)
+    pub package_template: PackageTemplate,
+    /// print the diagnostics as JSON lines instead of the human-readable format (`erg check --json`, `erg lint --json`)
+    pub json_output: bool,
+    /// measure the time and the memory of each compilation phase (`--timings`)
+    pub timings: bool,
+    /// also count the free type variables, the type clones and the HIR nodes created in each phase (`--memory-stats`)
+    pub memory_stats: bool,
+    /// write the self time of the hot paths of the compiler as collapsed stacks (`--profile-self`, see `erg_common::profile`)
+    pub profile_self: bool,
+    /// make the artifacts and the dumps reproducible (`--deterministic`, see `erg_common::deterministic`)
+    pub deterministic: bool,
+    /// shared with the configurations copied from this (e.g. for the imported modules)
+    pub cancel: CancellationToken,
+    /// the intermediate representations to be written instead of running the program (`--emit=ast,hir,...`)
+    pub emit: Vec<EmitTarget>,
+    /// additional directories to search for modules (`module_paths` in `erg.toml`)
+    pub module_paths: Vec<PathBuf>,
 }
 
 impl Default for ErgConfig {
@@ -114,6 +411,7 @@ impl Default for ErgConfig {
             target_version: None,
             py_server_timeout: 10,
             quiet_repl: false,
+            quiet: false,
             show_type: false,
             input: Input::repl(),
             output: Output::stdout(),
@@ -123,10 +421,38 @@ impl Default for ErgConfig {
             ps1: ">>> ",
             ps2: "... ",
             runtime_args: vec![],
+            targets: vec![],
+            watch: false,
+            fmt_check: false,
+            fmt_style: FormatStyle::default(),
+            test_filter: None,
+            coverage: false,
+            with_py: false,
+            dry_run: false,
+            fix: false,
+            allowed_lints: vec![],
+            completion: None,
+            package_template: PackageTemplate::default(),
+            json_output: false,
+            timings: false,
+            memory_stats: false,
+            profile_self: false,
+            deterministic: false,
+            cancel: CancellationToken::new(),
+            emit: vec![],
+            module_paths: vec![],
         }
     }
 }
 
+fn parse_emit(value: &str) -> Vec<EmitTarget> {
+    EmitTarget::parse_list(value).unwrap_or_else(|kind| {
+        let kinds = EmitKind::ALL.map(<&str>::from).join(", ");
+        eprintln!("invalid kind of `--emit`: {kind} (expected one of {kinds})");
+        process::exit(exit_code::USAGE_ERROR);
+    })
+}
+
 impl ErgConfig {
     pub fn with_main_path(path: PathBuf) -> Self {
         let path = normalize_path(path);
@@ -172,6 +498,22 @@ impl ErgConfig {
         dump_path
     }
 
+    /// The .pyc path that Python uses for the script next to it (e.g. `foo.er` -> `__pycache__/foo.cpython-311.pyc`).
+    pub fn cache_pyc_path(&self) -> PathBuf {
+        let dump_path = self.dump_path();
+        let magic_num = self.py_magic_num.unwrap_or_else(env_magic_number);
+        let minor = get_ver_from_magic_num(magic_num).minor.unwrap_or(0);
+        let stem = dump_path.file_stem().unwrap_or_default().to_string_lossy();
+        dump_path
+            .with_file_name("__pycache__")
+            .join(format!("{stem}.cpython-3{minor}.pyc"))
+    }
+
+    /// The artifacts of the source read from stdin are written to stdout (unless `--output-dir` is given).
+    pub fn dumps_to_stdout(&self) -> bool {
+        self.input.is_stdin() && self.dist_dir.is_none()
+    }
+
     pub fn dump_pyc_filename(&self) -> String {
         let dump_filename = self.dump_filename();
         if dump_filename.ends_with(".er") {
@@ -190,18 +532,187 @@ impl ErgConfig {
         }
     }
 
+    /// Searches `erg.toml` upward from `dir`.
+    pub fn find_project_config<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+        find_upward(dir, PROJECT_CONFIG_FILE)
+    }
+
+    /// Overrides the settings with the project configuration file (`erg.toml`) at `path`.
+    /// Relative paths in the file are resolved from the directory of the file.
+    pub fn load_project_config(&mut self, path: &Path) -> Result<(), TomlError> {
+        let src = std::fs::read_to_string(path).map_err(|err| TomlError {
+            line: 0,
+            msg: err.to_string(),
+        })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        self.apply_project_config(&src, base)
+    }
+
+    /// Overrides the settings with the contents of `erg.toml`.
+    /// See `doc/EN/command_options.md` for the available keys.
+    pub fn apply_project_config(&mut self, src: &str, base: &Path) -> Result<(), TomlError> {
+        for entry in toml::parse(src)? {
+            let err = |msg: String| TomlError {
+                line: entry.line,
+                msg,
+            };
+            let type_err = |expected: &str| {
+                err(format!(
+                    "`{}` must be {expected}, but found {} `{}`",
+                    entry.full_key(),
+                    entry.value.type_name(),
+                    entry.value
+                ))
+            };
+            match (&entry.section[..], &entry.key[..], &entry.value) {
+                ("", "target_version", TomlValue::Str(ver)) => {
+                    let ver = ver
+                        .parse::<PythonVersion>()
+                        .map_err(|_| err(format!("invalid Python version: `{ver}`")))?;
+                    self.target_version = Some(ver);
+                }
+                ("", "target_version", _) => return Err(type_err("a string")),
+                ("", "opt_level", TomlValue::Int(level)) => {
+                    self.opt_level = u8::try_from(*level)
+                        .ok()
+                        .filter(|level| *level <= 3)
+                        .ok_or_else(|| {
+                            err(format!("`opt_level` must be 0 to 3, but found {level}"))
+                        })?;
+                }
+                ("", "opt_level", _) => return Err(type_err("an integer")),
+                ("", "language", TomlValue::Str(lang)) => {
+                    let lang = lang
+                        .parse::<LanguageCode>()
+                        .ok()
+                        .filter(|lang| !lang.is_pl())
+                        .ok_or_else(|| err(format!("unknown language: `{lang}`")))?;
+                    LanguageCode::set_message_lang(Some(lang));
+                }
+                ("", "language", _) => return Err(type_err("a string")),
+                ("", "module_paths", TomlValue::Array(paths)) => {
+                    for path in paths {
+                        let TomlValue::Str(path) = path else {
+                            return Err(type_err("an array of strings"));
+                        };
+                        self.module_paths.push(normalize_path(base.join(path)));
+                    }
+                }
+                ("", "module_paths", _) => return Err(type_err("an array of strings")),
+                ("", "deterministic", TomlValue::Bool(deterministic)) => {
+                    self.deterministic = *deterministic;
+                }
+                ("", "deterministic", _) => return Err(type_err("a boolean")),
+                ("lint", "allow", TomlValue::Array(lints)) => {
+                    for lint in lints {
+                        let TomlValue::Str(lint) = lint else {
+                            return Err(type_err("an array of strings"));
+                        };
+                        self.allowed_lints
+                            .push(Box::leak(lint.clone().into_boxed_str()));
+                    }
+                }
+                ("lint", "allow", _) => return Err(type_err("an array of strings")),
+                // `<lint-name> = "allow" | "warn"`
+                ("lint", lint, TomlValue::Str(level)) => match &level[..] {
+                    "allow" => {
+                        self.allowed_lints
+                            .push(Box::leak(lint.to_string().into_boxed_str()));
+                    }
+                    "warn" => {
+                        self.allowed_lints.retain(|allowed| *allowed != lint);
+                    }
+                    _ => {
+                        return Err(err(format!(
+                            "the level of a lint must be `allow` or `warn`, but found `{level}`"
+                        )))
+                    }
+                },
+                ("lint", _, _) => return Err(type_err("`\"allow\"` or `\"warn\"`")),
+                _ => {
+                    let key = entry.full_key();
+                    let msg = if let Some(similar) =
+                        get_similar_name(PROJECT_CONFIG_KEYS.iter().copied(), &key)
+                    {
+                        format!("unknown key: `{key}` (did you mean `{similar}`?)")
+                    } else {
+                        format!("unknown key: `{key}`")
+                    };
+                    return Err(err(msg));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `Input::resolve_real_path`, but also searches `module_paths`.
+    pub fn resolve_real_path(&self, path: &Path) -> Option<PathBuf> {
+        self.input.resolve_real_path(path).or_else(|| {
+            self.module_paths
+                .iter()
+                .find_map(|dir| Input::resolve_real_path_in(dir, path))
+        })
+    }
+
+    /// Like `Input::resolve_decl_path`, but also searches `module_paths`.
+    pub fn resolve_decl_path(&self, path: &Path) -> Option<PathBuf> {
+        self.input.resolve_decl_path(path).or_else(|| {
+            self.module_paths
+                .iter()
+                .find_map(|dir| Input::resolve_decl_path_in(dir, path))
+        })
+    }
+
+    /// Like `Input::resolve_path`, but also searches `module_paths`.
+    pub fn resolve_path(&self, path: &Path) -> Option<PathBuf> {
+        self.resolve_real_path(path)
+            .or_else(|| self.resolve_decl_path(path))
+    }
+
+    /// precedence: command line options > `erg.toml` > defaults
     pub fn parse() -> Self {
         let mut args = env::args();
         args.next(); // "ergc"
         let mut cfg = Self::default();
+        if let Some(path) = Self::find_project_config(".") {
+            if let Err(err) = cfg.load_project_config(&path) {
+                eprintln!("invalid {PROJECT_CONFIG_FILE} ({}): {err}", path.display());
+                process::exit(exit_code::USAGE_ERROR);
+            }
+        }
+        // the path of the source read from stdin (`erg check - --path foo.er`)
+        let mut stdin_path = None;
         // not `for` because we need to consume the next argument
         while let Some(arg) = args.next() {
             match &arg[..] {
+                // the path of the package (`erg new test` creates a package named `test`)
+                _ if matches!(cfg.mode, ErgMode::New | ErgMode::Init) && !arg.starts_with('-') => {
+                    cfg.targets.push(PathBuf::from(arg));
+                }
                 /* Commands */
-                "lex" | "parse" | "desugar" | "typecheck" | "check" | "compile" | "transpile"
-                | "run" | "execute" | "server" | "tc" => {
+                "lex" | "parse" | "desugar" | "typecheck" | "check" | "fmt" | "lint" | "fix"
+                | "doc" | "test" | "bench" | "minimize" | "compile" | "transpile" | "run"
+                | "execute" | "server" | "tc" | "new" | "init" => {
                     cfg.mode = ErgMode::try_from(&arg[..]).unwrap();
                 }
+                "coverage" => {
+                    cfg.mode = ErgMode::Coverage;
+                    let action = args.next().unwrap_or_default();
+                    if action != "report" {
+                        eprintln!("invalid coverage command: `{action}` (expected `report`)");
+                        process::exit(exit_code::USAGE_ERROR);
+                    }
+                }
+                "completions" => {
+                    cfg.mode = ErgMode::Completions;
+                    let shell = args.next().unwrap_or_default();
+                    cfg.completion =
+                        Some(CompletionTarget::try_from(&shell[..]).unwrap_or_else(|_| {
+                            let shells = Shell::ALL.map(<&str>::from).join(", ");
+                            eprintln!("invalid shell: `{shell}` (expected one of {shells})");
+                            process::exit(exit_code::USAGE_ERROR);
+                        }));
+                }
                 /* Options */
                 "--" => {
                     for arg in args {
@@ -209,18 +720,109 @@ impl ErgConfig {
                     }
                     break;
                 }
+                "-" => {
+                    let mut buffer = String::new();
+                    stdin().read_to_string(&mut buffer).unwrap();
+                    cfg.input = Input::pipe(buffer);
+                }
                 "-c" | "--code" => {
                     cfg.input = Input::str(args.next().expect("the value of `-c` is not passed"));
                 }
+                "--check" if cfg.mode == ErgMode::Format => {
+                    cfg.fmt_check = true;
+                }
                 "--check" => {
                     cfg.mode = ErgMode::FullCheck;
                 }
                 "--compile" | "--dump-as-pyc" => {
                     cfg.mode = ErgMode::Compile;
                 }
+                "--coverage" => {
+                    cfg.coverage = true;
+                }
+                "--filter" => {
+                    let filter = args
+                        .next()
+                        .expect("the value of `--filter` is not passed")
+                        .into_boxed_str();
+                    cfg.test_filter = Some(Box::leak(filter));
+                }
+                "--app" => {
+                    cfg.package_template = PackageTemplate::App;
+                }
+                "--lib" => {
+                    cfg.package_template = PackageTemplate::Lib;
+                }
+                "--py" => {
+                    cfg.package_template = PackageTemplate::Py;
+                }
+                "--allow" => {
+                    let lint = args
+                        .next()
+                        .expect("the value of `--allow` is not passed")
+                        .into_boxed_str();
+                    cfg.allowed_lints.push(Box::leak(lint));
+                }
+                "--fix" => {
+                    cfg.fix = true;
+                }
+                "--with-py" => {
+                    cfg.with_py = true;
+                }
+                "--dry-run" => {
+                    cfg.dry_run = true;
+                }
+                "--indent-width" => {
+                    cfg.fmt_style.indent_width = args
+                        .next()
+                        .expect("the value of `--indent-width` is not passed")
+                        .parse::<usize>()
+                        .expect("the value of `--indent-width` is not a number");
+                }
+                "--json" => {
+                    cfg.json_output = true;
+                }
+                "--json-only" => {
+                    cfg.json_output = true;
+                    cfg.quiet = true;
+                }
+                "--timings" => {
+                    cfg.timings = true;
+                }
+                "--deterministic" => {
+                    cfg.deterministic = true;
+                }
+                "--memory-stats" => {
+                    cfg.timings = true;
+                    cfg.memory_stats = true;
+                }
+                "--profile-self" => {
+                    cfg.profile_self = true;
+                }
+                "--emit" => {
+                    let value = args.next().expect("the value of `--emit` is not passed");
+                    cfg.emit.extend(parse_emit(&value));
+                }
+                other if other.starts_with("--emit=") => {
+                    cfg.emit.extend(parse_emit(&other["--emit=".len()..]));
+                }
                 "--language-server" => {
                     cfg.mode = ErgMode::LanguageServer;
                 }
+                "--max-blank-lines" => {
+                    cfg.fmt_style.max_blank_lines = args
+                        .next()
+                        .expect("the value of `--max-blank-lines` is not passed")
+                        .parse::<usize>()
+                        .expect("the value of `--max-blank-lines` is not a number");
+                }
+                "--no-op-spaces" => {
+                    cfg.fmt_style.spaces_around_ops = false;
+                }
+                "--path" => {
+                    let path = args.next().expect("the value of `--path` is not passed");
+                    stdin_path = Some(normalize_path(PathBuf::from(path)));
+                }
                 "--no-std" => {
                     cfg.no_std = true;
                 }
@@ -317,7 +919,11 @@ impl ErgConfig {
                         .parse::<u64>()
                         .expect("the value of `--py-server-timeout` is not a number");
                 }
-                "-q" | "--quiet-startup" | "--quiet-repl" => {
+                "-q" | "--quiet" => {
+                    cfg.quiet = true;
+                    cfg.quiet_repl = true;
+                }
+                "--quiet-startup" | "--quiet-repl" => {
                     cfg.quiet_repl = true;
                 }
                 "-t" | "--show-type" => {
@@ -338,6 +944,9 @@ impl ErgConfig {
                         .parse::<u8>()
                         .expect("the value of `--verbose` is not a number");
                 }
+                "-w" | "--watch" => {
+                    cfg.watch = true;
+                }
                 "-V" | "--version" => {
                     println!("Erg {}", env!("CARGO_PKG_VERSION"));
                     process::exit(0);
@@ -377,23 +986,72 @@ USAGE:
 
     For more information try `erg --help`"
                     );
-                    process::exit(2);
+                    process::exit(exit_code::USAGE_ERROR);
+                }
+                _ if matches!(
+                    cfg.mode,
+                    ErgMode::FullCheck
+                        | ErgMode::Format
+                        | ErgMode::Lint
+                        | ErgMode::Fix
+                        | ErgMode::Doc
+                        | ErgMode::Test
+                        | ErgMode::Bench
+                        | ErgMode::Coverage
+                        | ErgMode::Compile
+                ) =>
+                {
+                    cfg.targets.push(PathBuf::from(arg));
                 }
                 _ => {
                     let path = PathBuf::from_str(&arg[..])
                         .unwrap_or_else(|_| panic!("invalid file path: {arg}"));
                     let path = normalize_path(path);
                     cfg.input = Input::file(path);
-                    if let Some("--") = args.next().as_ref().map(|s| &s[..]) {
-                        for arg in args {
-                            cfg.runtime_args.push(Box::leak(arg.into_boxed_str()));
-                        }
+                    // the rest are passed to the program (`erg script.er a b`, `erg run script.er -- a b`)
+                    let mut args = args.peekable();
+                    if args.peek().is_some_and(|arg| arg == "--") {
+                        args.next();
+                    }
+                    for arg in args {
+                        cfg.runtime_args.push(Box::leak(arg.into_boxed_str()));
                     }
                     break;
                 }
             }
         }
-        if cfg.input.is_repl() && cfg.mode != ErgMode::LanguageServer {
+        // `erg compile foo.er` compiles a file as usual, and the other targets (multiple files, directories and glob patterns) are compiled at once
+        if cfg.mode == ErgMode::Compile
+            && cfg.targets.len() == 1
+            && !cfg.targets[0].is_dir()
+            && !is_glob_pattern(&cfg.targets[0].to_string_lossy())
+        {
+            cfg.input = Input::file(normalize_path(cfg.targets.remove(0)));
+        }
+        // `erg check` (`erg fmt`, `erg lint`, `erg fix`, `erg doc`, `erg test`, `erg bench`) in a package checks (formats, lints, fixes, documents, tests, benchmarks) the whole package
+        let checks_package = matches!(
+            cfg.mode,
+            ErgMode::FullCheck
+                | ErgMode::Format
+                | ErgMode::Lint
+                | ErgMode::Fix
+                | ErgMode::Doc
+                | ErgMode::Test
+                | ErgMode::Bench
+        ) && cfg.targets.is_empty()
+            && package_root(".").is_some();
+        if cfg.input.is_repl()
+            && cfg.targets.is_empty()
+            && !checks_package
+            && !matches!(
+                cfg.mode,
+                ErgMode::LanguageServer
+                    | ErgMode::Completions
+                    | ErgMode::Coverage
+                    | ErgMode::New
+                    | ErgMode::Init
+            )
+        {
             let is_stdin_piped = !stdin().is_terminal();
             let input = if is_stdin_piped {
                 let mut buffer = String::new();
@@ -404,6 +1062,85 @@ USAGE:
             };
             cfg.input = input;
         }
+        if let Some(path) = stdin_path {
+            let InputKind::Pipe(src) = cfg.input.kind else {
+                eprintln!("`--path` can only be used with the source read from stdin (`-`)");
+                process::exit(exit_code::USAGE_ERROR);
+            };
+            cfg.input = Input::buffer(path, src);
+        }
+        // the modules in `module_paths` should also be found at runtime
+        if !cfg.module_paths.is_empty() {
+            let py_path = env::var_os("PYTHONPATH").unwrap_or_default();
+            let paths = cfg
+                .module_paths
+                .iter()
+                .cloned()
+                .chain(env::split_paths(&py_path));
+            if let Ok(py_path) = env::join_paths(paths) {
+                env::set_var("PYTHONPATH", py_path);
+            }
+        }
         cfg
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_project_config() {
+        let mut cfg = ErgConfig::default();
+        let src = r#"
+target_version = "3.10"
+opt_level = 2
+module_paths = ["lib", "vendor/erg"]
+deterministic = true
+
+[lint]
+allow = ["unused-variable"]
+too-many-params = "allow"
+"#;
+        cfg.apply_project_config(src, Path::new("/project"))
+            .unwrap();
+        assert_eq!(
+            cfg.target_version,
+            Some(PythonVersion::new(3, Some(10), None))
+        );
+        assert_eq!(cfg.opt_level, 2);
+        assert!(cfg.deterministic);
+        assert_eq!(
+            cfg.module_paths,
+            vec![
+                normalize_path(PathBuf::from("/project/lib")),
+                normalize_path(PathBuf::from("/project/vendor/erg"))
+            ]
+        );
+        assert_eq!(
+            cfg.allowed_lints,
+            vec!["unused-variable", "too-many-params"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_project_config() {
+        let mut cfg = ErgConfig::default();
+        let err = cfg
+            .apply_project_config("opt_level = \"2\"", Path::new("."))
+            .unwrap_err();
+        assert_eq!(err.line, 1);
+        let err = cfg
+            .apply_project_config("\nopt_level = 4", Path::new("."))
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+        let err = cfg
+            .apply_project_config("target_versoin = \"3.11\"", Path::new("."))
+            .unwrap_err();
+        assert!(err.msg.contains("did you mean `target_version`?"));
+        let err = cfg
+            .apply_project_config("[lint]\nunused-variable = \"deny\"", Path::new("."))
+            .unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
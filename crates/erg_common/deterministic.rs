@@ -0,0 +1,125 @@
+//! Deterministic compilation (`--deterministic`).
+//!
+//! In this mode, the emitted artifacts do not depend on the scheduling of threads, the time, or the memory layout:
+//!
+//! * the imported modules are analyzed one by one (so the ids of the type variables and the generated names are stable)
+//! * the timestamp of .pyc files is `SOURCE_DATE_EPOCH` (or 0)
+//! * the ids of the type variables (`?123`, `%123`) and the addresses (`0x7f...`) in the text dumps are renumbered
+//!   in the order of appearance (see `normalize`)
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// The timestamp (seconds since the UNIX epoch) to be recorded in the artifacts.
+/// `SOURCE_DATE_EPOCH` is respected (see <https://reproducible-builds.org/specs/source-date-epoch/>).
+pub fn timestamp() -> u32 {
+    if let Some(epoch) = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<u32>().ok())
+    {
+        return epoch;
+    }
+    if is_enabled() {
+        return 0;
+    }
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_secs() as u32)
+        .unwrap_or(0)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Renumbers the ids of the type variables (`?123`, `%123`) and the addresses (`0x7f...`) in `text`
+/// in the order of their first appearance.
+/// The same id (address) is always mapped to the same number.
+///
+/// ```
+/// # use erg_common::deterministic::normalize;
+/// assert_eq!(
+///     normalize("|%52: Type|(x: %52) -> ?48 at 0x7f42df6199d0"),
+///     "|%1: Type|(x: %1) -> ?2 at 0x1"
+/// );
+/// assert_eq!(normalize("%v_global_3 x1 ?T 0xff"), "%v_global_3 x1 ?T 0xff");
+/// ```
+pub fn normalize(text: &str) -> String {
+    /// shorter hex numbers are regarded as literals
+    const MIN_ADDR_DIGITS: usize = 8;
+    let mut ids = HashMap::<&str, usize>::new();
+    let mut addrs = HashMap::<&str, usize>::new();
+    let mut normalized = String::with_capacity(text.len());
+    let mut prev = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let after_ident = prev.is_some_and(is_ident_char);
+        if !after_ident && (c == '?' || c == '%') {
+            let digits = rest[1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len() - 1);
+            let followed_by_ident = rest[1 + digits..].starts_with(is_ident_char);
+            if digits > 0 && !followed_by_ident {
+                let next_id = ids.len() + 1;
+                let id = *ids.entry(&rest[1..1 + digits]).or_insert(next_id);
+                normalized.push(c);
+                normalized.push_str(&id.to_string());
+                prev = Some('0');
+                rest = &rest[1 + digits..];
+                continue;
+            }
+        }
+        if !after_ident && rest.starts_with("0x") {
+            let digits = rest[2..]
+                .find(|c: char| !c.is_ascii_hexdigit())
+                .unwrap_or(rest.len() - 2);
+            let followed_by_ident = rest[2 + digits..].starts_with(is_ident_char);
+            if digits >= MIN_ADDR_DIGITS && !followed_by_ident {
+                let next_addr = addrs.len() + 1;
+                let addr = *addrs.entry(&rest[2..2 + digits]).or_insert(next_addr);
+                normalized.push_str(&format!("0x{addr:x}"));
+                prev = Some('0');
+                rest = &rest[2 + digits..];
+                continue;
+            }
+        }
+        normalized.push(c);
+        prev = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let dump = "::id(x(: %52), , ) (: |%52: Type|(x: %52) -> %52)\n::f(: ?61 -> ?52)";
+        assert_eq!(
+            normalize(dump),
+            "::id(x(: %1), , ) (: |%1: Type|(x: %1) -> %1)\n::f(: ?2 -> ?1)"
+        );
+        // the result does not depend on the original ids
+        assert_eq!(normalize(dump), normalize(&dump.replace("52", "1052")));
+        assert_eq!(
+            normalize("<code object f at 0x7f42d82f7db0> <code object g at 0x7f42d82f7db0>"),
+            "<code object f at 0x1> <code object g at 0x1>"
+        );
+        assert_eq!(
+            normalize("a%2 x?3 \"100%\" ?1a 日本%3"),
+            "a%2 x?3 \"100%\" ?1a 日本%3"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! A line-based diff (e.g. for `erg fmt --check`).
+use std::ops::Range;
+
+use crate::style::{colors::GREEN, colors::RED, RESET};
+
+/// A changed region: `old` (0-origin line numbers) is replaced with `new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// If the region to be compared is larger than this (lines * lines), it is regarded as a single hunk.
+const MAX_TABLE_SIZE: usize = 4_000_000;
+
+/// Returns the changed regions of `old` and `new` (compared line by line).
+/// ```
+/// # use erg_common::diff::{hunks, Hunk};
+/// let hunks = hunks("a\nb\nc\n", "a\nB\nc\nd\n");
+/// assert_eq!(hunks, vec![Hunk { old: 1..2, new: 1..2 }, Hunk { old: 3..3, new: 3..4 }]);
+/// ```
+pub fn hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return vec![];
+    }
+    if old_mid.len() * new_mid.len() > MAX_TABLE_SIZE {
+        return vec![Hunk {
+            old: prefix..old.len() - suffix,
+            new: prefix..new.len() - suffix,
+        }];
+    }
+    // lcs[i][j]: the length of the LCS of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut hunks = vec![];
+    let mut current: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert(Hunk {
+            old: prefix + i..prefix + i,
+            new: prefix + j..prefix + j,
+        });
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+            hunk.new.end = prefix + j;
+        } else {
+            i += 1;
+            hunk.old.end = prefix + i;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+/// Returns the unified diff of `old` and `new` (colored).
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_name: &str,
+    new_name: &str,
+    context: usize,
+) -> String {
+    let hunks = hunks(old, new);
+    if hunks.is_empty() {
+        return if old == new {
+            String::new()
+        } else {
+            format!("--- {old_name}\n+++ {new_name}\n(only the line endings differ)\n")
+        };
+    }
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    let mut idx = 0;
+    while idx < hunks.len() {
+        // merge the hunks whose contexts overlap
+        let mut last = idx;
+        while last + 1 < hunks.len()
+            && hunks[last + 1].old.start <= hunks[last].old.end + context * 2
+        {
+            last += 1;
+        }
+        let old_start = hunks[idx].old.start.saturating_sub(context);
+        let old_end = (hunks[last].old.end + context).min(old_lines.len());
+        let new_start = hunks[idx].new.start.saturating_sub(context);
+        let new_end = (hunks[last].new.end + context).min(new_lines.len());
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_end - old_start,
+            new_start + 1,
+            new_end - new_start
+        ));
+        let mut line = old_start;
+        for hunk in hunks[idx..=last].iter() {
+            for same in old_lines[line..hunk.old.start].iter() {
+                diff.push_str(&format!(" {same}\n"));
+            }
+            for removed in old_lines[hunk.old.clone()].iter() {
+                diff.push_str(&format!("{RED}-{removed}{RESET}\n"));
+            }
+            for added in new_lines[hunk.new.clone()].iter() {
+                diff.push_str(&format!("{GREEN}+{added}{RESET}\n"));
+            }
+            line = hunk.old.end;
+        }
+        for same in old_lines[line..old_end].iter() {
+            diff.push_str(&format!(" {same}\n"));
+        }
+        idx = last + 1;
+    }
+    diff
+}
@@ -5,6 +5,7 @@ use std::cmp::{self, Ordering};
 use std::fmt;
 use std::io::{stderr, BufWriter, Write as _};
 
+use crate::grapheme;
 use crate::io::{Input, InputKind};
 use crate::style::Attribute;
 use crate::style::Characters;
@@ -130,6 +131,14 @@ impl ErrorKind {
     pub fn is_exception(&self) -> bool {
         (200..=255).contains(&(*self as u8))
     }
+
+    /// the code cannot be parsed
+    pub fn is_syntax_error(&self) -> bool {
+        matches!(
+            self,
+            Self::SyntaxError | Self::IndentationError | Self::TabError
+        )
+    }
 }
 
 impl From<&str> for ErrorKind {
@@ -226,7 +235,9 @@ impl From<&str> for ErrorKind {
 /// The beginning and end of each row and column where the error occurred.
 /// Basically, the beginning and end of each row and column where the error occurred is kept.
 ///
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// The columns count grapheme clusters (see `erg_common::grapheme`).
+/// `Range` also holds the byte offsets in the source, which are not considered in comparison.
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Location {
     ///
     /// Location used for basic errors
@@ -240,6 +251,8 @@ pub enum Location {
     ///    col_begin: 0,
     ///    ln_end: 2,
     ///    col_end: 1,
+    ///    byte_begin: 6,
+    ///    byte_end: 7,
     /// }
     /// ```
     ///
@@ -250,6 +263,9 @@ pub enum Location {
         col_begin: u32,
         ln_end: u32,
         col_end: u32,
+        /// 0-origin, `Location::UNKNOWN_BYTE` if unknown
+        byte_begin: u32,
+        byte_end: u32,
     },
     /// Used for loss of location information when desugared.
     /// If there are guaranteed to be multiple rows
@@ -262,6 +278,53 @@ pub enum Location {
     Unknown,
 }
 
+impl PartialEq for Location {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Range {
+                    ln_begin: lb1,
+                    col_begin: cb1,
+                    ln_end: le1,
+                    col_end: ce1,
+                    ..
+                },
+                Self::Range {
+                    ln_begin: lb2,
+                    col_begin: cb2,
+                    ln_end: le2,
+                    col_end: ce2,
+                    ..
+                },
+            ) => (lb1, cb1, le1, ce1) == (lb2, cb2, le2, ce2),
+            (Self::LineRange(lb1, le1), Self::LineRange(lb2, le2)) => (lb1, le1) == (lb2, le2),
+            (Self::Line(l1), Self::Line(l2)) => l1 == l2,
+            (Self::Unknown, Self::Unknown) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Location {}
+
+impl std::hash::Hash for Location {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                ..
+            } => (ln_begin, col_begin, ln_end, col_end).hash(state),
+            Self::LineRange(ln_begin, ln_end) => (ln_begin, ln_end).hash(state),
+            Self::Line(ln) => ln.hash(state),
+            Self::Unknown => {}
+        }
+    }
+}
+
 impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -270,6 +333,7 @@ impl fmt::Display for Location {
                 col_begin,
                 ln_end,
                 col_end,
+                ..
             } => write!(f, "{ln_begin}:{col_begin}-{ln_end}:{col_end}"),
             Self::LineRange(ln_begin, ln_end) => write!(f, "{ln_begin}:?-{ln_end}:?"),
             Self::Line(ln) => write!(f, "{ln}:?-{ln}:?"),
@@ -293,12 +357,7 @@ impl std::str::FromStr for Location {
         let ln_end = comp2.next().unwrap().parse::<u32>().map_err(|_| ())?;
         let col_end = comp2.next().unwrap().parse::<u32>();
         match (col_begin, col_end) {
-            (Ok(col_begin), Ok(col_end)) => Ok(Self::Range {
-                ln_begin,
-                col_begin,
-                ln_end,
-                col_end,
-            }),
+            (Ok(col_begin), Ok(col_end)) => Ok(Self::range(ln_begin, col_begin, ln_end, col_end)),
             _ if ln_begin == ln_end => Ok(Self::Line(ln_begin)),
             _ => Ok(Self::LineRange(ln_begin, ln_end)),
         }
@@ -353,7 +412,13 @@ impl Location {
             r_loc.ln_end(),
             r_loc.col_end(),
         ) {
-            (Some(lb), Some(cb), Some(le), Some(ce)) => Self::range(lb, cb, le, ce),
+            (Some(lb), Some(cb), Some(le), Some(ce)) => {
+                let loc = Self::range(lb, cb, le, ce);
+                match (l_loc.byte_begin(), r_loc.byte_end()) {
+                    (Some(begin), Some(end)) => loc.with_byte_range(begin, end),
+                    _ => loc,
+                }
+            }
             (Some(lb), _, Some(le), _) => Self::LineRange(lb, le),
             (Some(l), _, _, _) | (_, _, Some(l), _) => Self::Line(l),
             _ => Self::Unknown,
@@ -373,12 +438,37 @@ impl Location {
         Self::concat(first_known, last_known)
     }
 
+    pub const UNKNOWN_BYTE: u32 = u32::MAX;
+
     pub const fn range(ln_begin: u32, col_begin: u32, ln_end: u32, col_end: u32) -> Self {
         Self::Range {
             ln_begin,
             col_begin,
             ln_end,
             col_end,
+            byte_begin: Self::UNKNOWN_BYTE,
+            byte_end: Self::UNKNOWN_BYTE,
+        }
+    }
+
+    /// Sets the byte offsets (only for `Range`).
+    pub const fn with_byte_range(self, begin: u32, end: u32) -> Self {
+        match self {
+            Self::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                ..
+            } => Self::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                byte_begin: begin,
+                byte_end: end,
+            },
+            other => other,
         }
     }
 
@@ -428,6 +518,36 @@ impl Location {
         }
     }
 
+    /// 0-origin byte offset in the source
+    pub const fn byte_begin(&self) -> Option<u32> {
+        match self {
+            Self::Range { byte_begin, .. } if *byte_begin != Self::UNKNOWN_BYTE => {
+                Some(*byte_begin)
+            }
+            _ => None,
+        }
+    }
+
+    pub const fn byte_end(&self) -> Option<u32> {
+        match self {
+            Self::Range { byte_end, .. } if *byte_end != Self::UNKNOWN_BYTE => Some(*byte_end),
+            _ => None,
+        }
+    }
+
+    /// ```
+    /// # use erg_common::error::Location;
+    /// let src = "x = \"あいう\"";
+    /// let loc = Location::range(1, 4, 1, 9).with_byte_range(4, 15);
+    /// assert_eq!(&src[loc.byte_range().unwrap()], "\"あいう\"");
+    /// assert_eq!(loc, Location::range(1, 4, 1, 9));
+    /// ```
+    pub fn byte_range(&self) -> Option<std::ops::Range<usize>> {
+        let begin = self.byte_begin()? as usize;
+        let end = self.byte_end()? as usize;
+        (begin <= end).then_some(begin..end)
+    }
+
     pub const fn length(&self) -> Option<u32> {
         match self {
             Self::Range {
@@ -456,12 +576,14 @@ impl Location {
                     col_begin: cb1,
                     ln_end: le1,
                     col_end: ce1,
+                    ..
                 },
                 Self::Range {
                     ln_begin: lb2,
                     col_begin: cb2,
                     ln_end: le2,
                     col_end: ce2,
+                    ..
                 },
             ) => {
                 let same_start_line = lb1 == lb2;
@@ -504,6 +626,15 @@ fn format_context<E: ErrorDisplay + ?Sized>(
     let max_digit = ln_end.to_string().len();
     let (vbreak, vbar) = chars.gutters();
     let offset = format!("{} {} ", &" ".repeat(max_digit), vbreak);
+    // the columns (grapheme clusters) are converted into the display widths
+    let width_until = |i: usize, col: usize| {
+        codes
+            .get(i)
+            .map_or(col, |line| grapheme::col_to_width(line, col))
+    };
+    let line_width = |i: usize| codes.get(i).map_or(0, |line| grapheme::width(line));
+    let col_begin = width_until(0, col_begin);
+    let col_end = width_until(final_step, col_end);
     for (i, lineno) in (ln_begin..=ln_end).enumerate() {
         context.push_str_with_color(&format!("{lineno:<max_digit$} {vbar} "), gutter_color);
         context.push_str(codes.get(i).unwrap_or(&String::new()));
@@ -518,13 +649,13 @@ fn format_context<E: ErrorDisplay + ?Sized>(
         } else if i == 0 {
             context.push_str(&" ".repeat(col_begin));
             context.push_str_with_color(
-                &mark.repeat(cmp::max(1, codes[i].len().saturating_sub(col_begin))),
+                &mark.repeat(cmp::max(1, line_width(i).saturating_sub(col_begin))),
                 err_color,
             );
         } else if i == final_step {
             context.push_str_with_color(&mark.repeat(col_end), err_color);
         } else {
-            context.push_str_with_color(&mark.repeat(cmp::max(1, codes[i].len())), err_color);
+            context.push_str_with_color(&mark.repeat(cmp::max(1, line_width(i))), err_color);
         }
         context.push_str("\n");
     }
@@ -649,6 +780,7 @@ impl SubMessage {
                 col_begin,
                 ln_end,
                 col_end,
+                ..
             } => format_context(
                 e,
                 ln_begin as usize,
@@ -676,7 +808,10 @@ impl SubMessage {
                         gutter_color,
                     );
                     cxt.push_str(&" ".repeat(lineno.to_string().len()));
-                    cxt.push_str_with_color(&mark.repeat(cmp::max(1, codes[i].len())), err_color);
+                    cxt.push_str_with_color(
+                        &mark.repeat(cmp::max(1, grapheme::width(&codes[i]))),
+                        err_color,
+                    );
                     cxt.push_str("\n");
                 }
                 cxt.push_str("\n");
@@ -712,7 +847,22 @@ impl SubMessage {
                 cxt.to_string()
             }
             Location::Unknown => match &e.input().kind {
-                InputKind::File(_) => "\n".to_string(),
+                // not related to a position in the file (e.g. IO errors)
+                InputKind::File(_) if self.msg.is_empty() && self.hint.is_none() => {
+                    "\n".to_string()
+                }
+                InputKind::File(_) => {
+                    let mut cxt = StyledStrings::default();
+                    for msg in self.msg.iter() {
+                        cxt.push_str(msg);
+                        cxt.push_str("\n");
+                    }
+                    if let Some(hint) = self.hint.as_ref() {
+                        cxt.push_str(hint);
+                        cxt.push_str("\n");
+                    }
+                    cxt.to_string()
+                }
                 _other => {
                     let (_, vbar) = chars.gutters();
                     let mut cxt = StyledStrings::default();
@@ -735,6 +885,118 @@ impl SubMessage {
     }
 }
 
+/// How reliable a `Suggestion` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Applicability {
+    /// The suggestion is definitely correct, and can be applied automatically (e.g. `erg lint --fix`)
+    MachineApplicable,
+    /// The suggestion may be incorrect (e.g. the code may not compile), so the user should review it
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    pub const fn is_machine_applicable(&self) -> bool {
+        matches!(self, Self::MachineApplicable)
+    }
+}
+
+/// A suggested edit: replace the code at `loc` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Suggestion {
+    pub loc: Location,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new<S: Into<String>>(
+        loc: Location,
+        replacement: S,
+        applicability: Applicability,
+    ) -> Self {
+        Self {
+            loc,
+            replacement: replacement.into(),
+            applicability,
+        }
+    }
+
+    pub const fn is_machine_applicable(&self) -> bool {
+        self.applicability.is_machine_applicable()
+    }
+}
+
+/// Converts a (1-origin) line number and a (0-origin, in grapheme clusters) column into a byte offset of `src`.
+fn byte_offset(line_starts: &[usize], src: &str, ln: u32, col: u32) -> Option<usize> {
+    let start = *line_starts.get((ln as usize).checked_sub(1)?)?;
+    let line = src[start..].split(['\r', '\n']).next().unwrap_or("");
+    Some(start + grapheme::col_to_byte(line, col as usize))
+}
+
+/// Applies the suggestions to `src`, and returns the new code and the number of the applied suggestions.
+/// Suggestions without a range, or overlapping with another (earlier) suggestion, are not applied.
+/// ```
+/// # use erg_common::error::{apply_suggestions, Applicability, Location, Suggestion};
+/// let src = "x = y == True\nprint! 3.14\n";
+/// let sugs = [
+///     Suggestion::new(Location::range(1, 4, 1, 13), "y", Applicability::MachineApplicable),
+///     Suggestion::new(Location::range(2, 7, 2, 11), "math.pi", Applicability::MaybeIncorrect),
+///     Suggestion::new(Location::range(1, 9, 1, 13), "False", Applicability::MachineApplicable),
+/// ];
+/// let (fixed, applied) = apply_suggestions(src, sugs.iter());
+/// assert_eq!(fixed, "x = y\nprint! math.pi\n");
+/// assert_eq!(applied, 2);
+/// ```
+pub fn apply_suggestions<'s>(
+    src: &str,
+    suggestions: impl IntoIterator<Item = &'s Suggestion>,
+) -> (String, usize) {
+    let line_starts = std::iter::once(0)
+        .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+        .collect::<Vec<_>>();
+    let mut edits = suggestions
+        .into_iter()
+        .filter_map(|sug| match sug.loc {
+            // the byte offsets are used if they are valid for `src`
+            loc @ Location::Range { .. }
+                if loc.byte_range().is_some_and(|range| {
+                    src.is_char_boundary(range.start) && src.is_char_boundary(range.end)
+                }) =>
+            {
+                let range = loc.byte_range().unwrap();
+                Some((range.start, range.end, &sug.replacement))
+            }
+            Location::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                ..
+            } => {
+                let start = byte_offset(&line_starts, src, ln_begin, col_begin)?;
+                let end = byte_offset(&line_starts, src, ln_end, col_end)?;
+                (start <= end).then_some((start, end, &sug.replacement))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // stable sort: the earlier suggestion wins
+    edits.sort_by_key(|(start, end, _)| (*start, *end));
+    let mut fixed = String::with_capacity(src.len());
+    let (mut cursor, mut applied) = (0, 0);
+    for (start, end, replacement) in edits {
+        if start < cursor {
+            continue;
+        }
+        fixed.push_str(&src[cursor..start]);
+        fixed.push_str(replacement);
+        cursor = end;
+        applied += 1;
+    }
+    fixed.push_str(&src[cursor..]);
+    (fixed, applied)
+}
+
 /// In Erg, common parts used by error.
 /// Must be wrap when to use.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -744,6 +1006,8 @@ pub struct ErrorCore {
     pub errno: usize,
     pub kind: ErrorKind,
     pub loc: Location,
+    /// edits that fix the error (see `apply_suggestions`)
+    pub suggestions: Vec<Suggestion>,
     theme: Theme,
 }
 
@@ -769,10 +1033,16 @@ impl ErrorCore {
             errno,
             kind,
             loc,
+            suggestions: vec![],
             theme: THEME,
         }
     }
 
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
     pub fn dummy(errno: usize) -> Self {
         Self::new(
             vec![SubMessage::only_loc(Location::Unknown)],
@@ -783,6 +1053,43 @@ impl ErrorCore {
         )
     }
 
+    /// The compilation is cancelled (see `erg_common::cancel`).
+    pub fn cancelled(errno: usize) -> Self {
+        Self::new(
+            vec![],
+            switch_lang!(
+                "japanese" => "コンパイルが中断されました",
+                "simplified_chinese" => "编译已取消",
+                "traditional_chinese" => "編譯已取消",
+                "english" => "compilation cancelled",
+            ),
+            errno,
+            InterruptedError,
+            Location::Unknown,
+        )
+    }
+
+    /// The analysis stopped because the code is nested too deeply (see `spawn::is_stack_exhausted`).
+    pub fn too_deeply_nested(errno: usize, loc: Location) -> Self {
+        Self::new(
+            vec![SubMessage::only_loc(loc)],
+            switch_lang!(
+                "japanese" => "ネストが深すぎます",
+                "simplified_chinese" => "嵌套太深",
+                "traditional_chinese" => "嵌套太深",
+                "english" => "the code is nested too deeply",
+            ),
+            errno,
+            RecursionError,
+            loc,
+        )
+    }
+
+    /// Whether this is the error of a cancelled compilation.
+    pub fn is_cancellation(&self) -> bool {
+        self.kind == InterruptedError
+    }
+
     pub fn unreachable(fn_name: &str, line: u32) -> Self {
         Self::bug(line as usize, Location::Line(line), fn_name, line)
     }
@@ -0,0 +1,80 @@
+//! The exit codes of `erg` (see `doc/EN/tools/exit_code.md`).
+//!
+//! The commands running the program (`erg run`, `erg test`, `erg bench`) exit with the code of the program,
+//! but the other commands (`erg check`, `erg compile`, `erg lint`, ...) exit with one of these codes,
+//! so that CI scripts can tell how the command failed.
+use crate::config::ErgMode;
+use crate::error::{ErrorCore, ErrorKind};
+use crate::traits::ExitStatus;
+
+pub const OK: i32 = 0;
+/// type errors, name errors, ownership errors, etc. (the code can be parsed)
+pub const COMPILE_ERROR: i32 = 1;
+/// invalid command line arguments
+pub const USAGE_ERROR: i32 = 2;
+/// the code cannot be parsed
+pub const SYNTAX_ERROR: i32 = 3;
+/// no errors, but some warnings
+pub const WARNINGS: i32 = 4;
+/// a bug of the compiler (a panic or a `CompilerSystemError`)
+pub const INTERNAL_ERROR: i32 = 101;
+/// the compilation is cancelled by Ctrl-C (128 + SIGINT)
+pub const CANCELLED: i32 = 130;
+
+/// The exit code for the errors. The most severe kind wins (cancelled > internal > syntax > compile).
+pub fn of_errors<'a>(errors: impl IntoIterator<Item = &'a ErrorCore>) -> i32 {
+    let mut code = OK;
+    for core in errors {
+        let kind_code = match core.kind {
+            ErrorKind::InterruptedError => CANCELLED,
+            ErrorKind::CompilerSystemError => INTERNAL_ERROR,
+            kind if kind.is_syntax_error() => SYNTAX_ERROR,
+            kind if kind.is_warning() => OK,
+            _ => COMPILE_ERROR,
+        };
+        code = match (code, kind_code) {
+            (CANCELLED, _) | (_, CANCELLED) => CANCELLED,
+            (INTERNAL_ERROR, _) | (_, INTERNAL_ERROR) => INTERNAL_ERROR,
+            (SYNTAX_ERROR, _) | (_, SYNTAX_ERROR) => SYNTAX_ERROR,
+            (COMPILE_ERROR, _) | (_, COMPILE_ERROR) => COMPILE_ERROR,
+            _ => OK,
+        };
+    }
+    code
+}
+
+/// The exit code of the process.
+/// If only warnings are reported, `WARNINGS` is returned (except for the commands running the program).
+pub fn of_status(status: &ExitStatus, mode: ErgMode) -> i32 {
+    if status.code == OK && status.num_warns > 0 && !mode.runs_program() {
+        WARNINGS
+    } else {
+        status.code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Location;
+
+    fn core(kind: ErrorKind) -> ErrorCore {
+        ErrorCore::new(vec![], "", 0, kind, Location::Unknown)
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(of_errors([]), OK);
+        let type_err = core(ErrorKind::TypeError);
+        let syntax_err = core(ErrorKind::SyntaxError);
+        let warn = core(ErrorKind::UnusedWarning);
+        assert_eq!(of_errors([&warn]), OK);
+        assert_eq!(of_errors([&type_err, &warn]), COMPILE_ERROR);
+        assert_eq!(of_errors([&type_err, &syntax_err]), SYNTAX_ERROR);
+        let cancelled = ErrorCore::cancelled(0);
+        assert_eq!(of_errors([&syntax_err, &cancelled]), CANCELLED);
+        let status = ExitStatus::compile_passed(1);
+        assert_eq!(of_status(&status, ErgMode::FullCheck), WARNINGS);
+        assert_eq!(of_status(&status, ErgMode::Execute), OK);
+    }
+}
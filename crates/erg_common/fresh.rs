@@ -1,6 +1,5 @@
-use std::cell::RefCell;
-use std::rc::Rc;
 use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
 
 use crate::Str;
 
@@ -35,18 +34,18 @@ impl FreshNameGenerator {
 pub static FRESH_GEN: FreshNameGenerator = FreshNameGenerator::new("global");
 
 #[derive(Debug, Clone, Default)]
-pub struct SharedFreshNameGenerator(Rc<RefCell<FreshNameGenerator>>);
+pub struct SharedFreshNameGenerator(Arc<FreshNameGenerator>);
 
 impl SharedFreshNameGenerator {
     pub fn new(prefix: &'static str) -> Self {
-        Self(Rc::new(RefCell::new(FreshNameGenerator::new(prefix))))
+        Self(Arc::new(FreshNameGenerator::new(prefix)))
     }
 
     pub fn fresh_varname(&self) -> Str {
-        self.0.borrow_mut().fresh_varname()
+        self.0.fresh_varname()
     }
 
     pub fn fresh_param_name(&self) -> Str {
-        self.0.borrow_mut().fresh_param_name()
+        self.0.fresh_param_name()
     }
 }
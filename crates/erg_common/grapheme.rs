@@ -0,0 +1,270 @@
+//! Unicode-aware text positions.
+//!
+//! The columns of `Location` count grapheme clusters (user-perceived characters),
+//! so that a CJK character, an emoji (even a ZWJ sequence such as 👨‍👩‍👧) or a letter with combining marks is one column.
+//! This module converts them to byte offsets, UTF-16 offsets (used by LSP) and display widths (used for underlining errors).
+//!
+//! The segmentation is a lightweight approximation of [UAX #29](https://unicode.org/reports/tr29/):
+//! combining marks, variation selectors, emoji modifiers, ZWJ sequences, tag sequences and regional indicator pairs are handled.
+
+/// Whether `c` is attached to the preceding character.
+fn is_extend(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0300..=0x036F // Combining Diacritical Marks
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x0610..=0x061A
+            | 0x064B..=0x065F
+            | 0x0670
+            | 0x06D6..=0x06DC
+            | 0x06DF..=0x06E4
+            | 0x0900..=0x0903 // Devanagari signs
+            | 0x093A..=0x094F
+            | 0x0951..=0x0957
+            | 0x0E31
+            | 0x0E34..=0x0E3A // Thai vowels and tone marks
+            | 0x0E47..=0x0E4E
+            | 0x1160..=0x11FF // Hangul Jamo (medial vowels and final consonants)
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D // ZWNJ, ZWJ
+            | 0x20D0..=0x20FF
+            | 0x3099..=0x309A // combining (semi-)voiced sound marks
+            | 0xFE00..=0xFE0F // Variation Selectors
+            | 0xFE20..=0xFE2F
+            | 0x1F3FB..=0x1F3FF // Emoji Modifiers (skin tones)
+            | 0xE0020..=0xE007F // Tags
+            | 0xE0100..=0xE01EF
+    )
+}
+
+const fn is_regional_indicator(c: char) -> bool {
+    matches!(c as u32, 0x1F1E6..=0x1F1FF)
+}
+
+/// Whether a grapheme cluster boundary is between `prev` and `c`.
+/// `ri_count` is the number of the regional indicators immediately before `c`.
+fn is_boundary(prev: char, c: char, ri_count: usize) -> bool {
+    if prev == '\r' && c == '\n' {
+        return false;
+    }
+    if is_extend(c) || prev == '\u{200D}' {
+        return false;
+    }
+    // a flag consists of two regional indicators
+    !(is_regional_indicator(c) && ri_count % 2 == 1)
+}
+
+/// An iterator over the grapheme clusters of a string.
+#[derive(Debug, Clone)]
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut prev = first;
+        let mut ri_count = usize::from(is_regional_indicator(first));
+        let mut end = self.rest.len();
+        for (i, c) in chars {
+            if is_boundary(prev, c, ri_count) {
+                end = i;
+                break;
+            }
+            ri_count = if is_regional_indicator(c) {
+                ri_count + 1
+            } else {
+                0
+            };
+            prev = c;
+        }
+        let (grapheme, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(grapheme)
+    }
+}
+
+/// ```
+/// # use erg_common::grapheme::graphemes;
+/// assert_eq!(graphemes("a👨‍👩‍👧é🇯🇵").collect::<Vec<_>>(), vec!["a", "👨‍👩‍👧", "é", "🇯🇵"]);
+/// ```
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+/// The number of columns `s` occupies in `Location`.
+pub fn grapheme_count(s: &str) -> usize {
+    graphemes(s).count()
+}
+
+/// The display width of a character in a terminal (0, 1 or 2).
+pub fn char_width(c: char) -> usize {
+    if c.is_control() || is_extend(c) {
+        return 0;
+    }
+    let wide = matches!(
+        c as u32,
+        0x1100..=0x115F
+            | 0x231A..=0x231B
+            | 0x2329..=0x232A
+            | 0x23E9..=0x23EC
+            | 0x25FD..=0x25FE
+            | 0x2614..=0x2615
+            | 0x2648..=0x2653
+            | 0x26A1
+            | 0x26AA..=0x26AB
+            | 0x26BD..=0x26BE
+            | 0x26C4..=0x26C5
+            | 0x26D4
+            | 0x26EA
+            | 0x26F2..=0x26F5
+            | 0x26FA
+            | 0x26FD
+            | 0x2705
+            | 0x270A..=0x270B
+            | 0x2728
+            | 0x274C
+            | 0x2753..=0x2755
+            | 0x2757
+            | 0x2795..=0x2797
+            | 0x27B0
+            | 0x27BF
+            | 0x2B1B..=0x2B1C
+            | 0x2B50
+            | 0x2B55
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xA960..=0xA97F
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE10..=0xFE19
+            | 0xFE30..=0xFE6F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F004
+            | 0x1F0CF
+            | 0x1F18E
+            | 0x1F191..=0x1F19A
+            | 0x1F1E6..=0x1F1FF
+            | 0x1F200..=0x1F251
+            | 0x1F300..=0x1F64F
+            | 0x1F680..=0x1F6FF
+            | 0x1F7E0..=0x1F7EB
+            | 0x1F90C..=0x1F9FF
+            | 0x1FA70..=0x1FAFF
+            | 0x20000..=0x3FFFD
+    );
+    if wide {
+        2
+    } else {
+        1
+    }
+}
+
+/// The display width of a grapheme cluster.
+fn grapheme_width(grapheme: &str) -> usize {
+    // emoji presentation selector
+    if grapheme.contains('\u{FE0F}') {
+        return 2;
+    }
+    grapheme.chars().map(char_width).max().unwrap_or(0)
+}
+
+/// The display width of `s` in a terminal.
+///
+/// ```
+/// # use erg_common::grapheme::width;
+/// assert_eq!(width("abc"), 3);
+/// assert_eq!(width("あいう"), 6);
+/// assert_eq!(width("👨‍👩‍👧"), 2);
+/// ```
+pub fn width(s: &str) -> usize {
+    graphemes(s).map(grapheme_width).sum()
+}
+
+/// The byte offset of the column `col` (0-origin) in `line`.
+/// If `col` is out of the line, the length of the line is returned.
+pub fn col_to_byte(line: &str, col: usize) -> usize {
+    graphemes(line)
+        .take(col)
+        .map(str::len)
+        .sum::<usize>()
+        .min(line.len())
+}
+
+/// The column of the byte offset `byte` in `line`.
+/// An offset in the middle of a grapheme cluster is rounded down.
+pub fn byte_to_col(line: &str, byte: usize) -> usize {
+    let mut offset = 0;
+    graphemes(line)
+        .take_while(|grapheme| {
+            offset += grapheme.len();
+            offset <= byte
+        })
+        .count()
+}
+
+/// The UTF-16 offset (used by LSP) of the column `col` in `line`.
+pub fn col_to_utf16(line: &str, col: usize) -> usize {
+    graphemes(line)
+        .take(col)
+        .map(|grapheme| grapheme.encode_utf16().count())
+        .sum()
+}
+
+/// The column of the UTF-16 offset `utf16` in `line`.
+/// An offset in the middle of a grapheme cluster is rounded down.
+pub fn utf16_to_col(line: &str, utf16: usize) -> usize {
+    let mut offset = 0;
+    graphemes(line)
+        .take_while(|grapheme| {
+            offset += grapheme.encode_utf16().count();
+            offset <= utf16
+        })
+        .count()
+}
+
+/// The display width of the first `col` columns of `line`.
+pub fn col_to_width(line: &str, col: usize) -> usize {
+    graphemes(line).take(col).map(grapheme_width).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphemes() {
+        assert_eq!(grapheme_count(""), 0);
+        assert_eq!(grapheme_count("print! \"日本語\""), 12);
+        // e + COMBINING ACUTE ACCENT
+        assert_eq!(grapheme_count("e\u{301}"), 1);
+        // thumbs up + skin tone
+        assert_eq!(grapheme_count("👍🏽"), 1);
+        // 3 flags
+        assert_eq!(grapheme_count("🇯🇵🇺🇸🇫"), 3);
+        assert_eq!(grapheme_count("\r\n"), 1);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let line = "x = \"あ👨‍👩‍👧\" # e\u{301}";
+        // `#`
+        let col = 9;
+        assert_eq!(&line[col_to_byte(line, col)..], "# e\u{301}");
+        assert_eq!(byte_to_col(line, col_to_byte(line, col)), col);
+        assert_eq!(col_to_utf16(line, col), 16);
+        assert_eq!(utf16_to_col(line, 16), col);
+        assert_eq!(col_to_width(line, col), 11);
+        assert_eq!(col_to_byte(line, 100), line.len());
+        assert_eq!(utf16_to_col(line, 100), grapheme_count(line));
+    }
+}
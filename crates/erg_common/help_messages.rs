@@ -11,6 +11,7 @@ USAGE:
 ARGS:
     <script> スクリプトファイルからプログラムを読み込む
             <script>に渡す引数を入力する
+    -        標準入力からプログラムを読み込む(compile, transpileでは成果物を標準出力に書き出す)
 
 OPTIONS
     --help/-?/-h                         このhelpを表示
@@ -23,16 +24,47 @@ OPTIONS
     --mode (mode)                        指定モードで実行(詳細は--mode --helpを参照)
     --code/-c (string)                   文字列として渡したプログラムを実行
     --module/-m (string)                 モジュールを実行
+    --path (path)                        標準入力(-)から読み込んだソースのパスを指定(診断とimportの解決に使用)
+    --watch/-w                           ファイルの変更を監視して再実行(check, run)
+    --emit (kind[=path],...)             中間表現を出力(ast, hir, typed-hir, bytecode, pyc, py, pyi; pathが-なら標準出力)
+    --filter (name)                      名前にnameを含むテスト/ベンチマークのみ実行(test, bench)
+    --coverage                           実行された行をbuild/coverage.txtに記録(test)
+    --with-py                            同じHIRから生成した.pyファイルも出力し、.pycを__pycache__に書き出す(compile)
+    --dry-run                            成果物を書き出さず、書き込めるかのみ確認(compile, transpile, emit, doc)
+    --allow (lint)                       指定したリントを実行しない(lint, fix)
+    --fix                                自動で適用可能な修正を適用(lint)
+    --indent-width (uint)                インデント幅を指定(fmt)
+    --json                               診断をJSON Lines形式で出力(check, lint)
+    --json-only                          診断のみをJSON Lines形式で出力(--json --quiet)
+    --quiet/-q                           診断以外の出力(要約, 差分など)を抑制
+    --max-blank-lines (uint)             連続する空行の最大数を指定(fmt)
+    --no-op-spaces                       二項演算子の前後に空白を入れない(fmt)
+    --deterministic                      再現可能な成果物・ダンプを出力(モジュールを逐次解析し、型変数のIDを振り直す)
+    --timings                            フェーズ・モジュールごとの時間とメモリを表示(build/timings.jsonにトレースを出力)
+    --memory-stats                       --timingsに加え、フェーズごとに生成された自由型変数・型の複製・HIRノードの数を表示
+    --profile-self                       型検査器のホットパスの時間を折りたたみスタック形式でbuild/profile.foldedに出力(フレームグラフ用)
+    --output-dir (path)                  出力先のディレクトリを指定(compile, doc)
 
 COMMAND
     lex                                  字句解析
     parse                                構文解析
     typecheck|tc                         型検査
-    check                                全ての検査(所有権検査, 副作用検査などを含む)
-    compile                              コンパイル
+    check [files...]                     全ての検査(所有権検査, 副作用検査などを含む), 引数がなければパッケージ全体
+    fmt [files...] [--check]             ソースコードを整形(--checkでは整形されていないファイルを報告)
+    lint [files...] [--fix] [--json]     リントを実行(--fixでは自動修正、ビルドは行わない)
+    fix [files...]                       自動で適用可能な修正をファイルに適用し、変更内容を表示
+    doc [files...]                       APIドキュメント(HTML, JSON)を生成(出力先はbuild/doc, --output-dirで変更可)
+    test [files...] [--filter (name)]    @Testの付いた関数を実行(--filterで名前を絞り込み)
+    bench [files...] [--filter (name)]   @Benchの付いた関数の実行時間を計測(-oで最適化レベルを指定)
+    minimize (file)                      コンパイラをクラッシュさせるコードを最小化(file.min.erに出力)
+    coverage report [files...]           test --coverageで記録されたカバレッジを表示
+    compile [files...]                   コンパイル(複数のファイル, ディレクトリ, globパターンはまとめてコンパイル)
     transpile                            トランスパイル
     run|exec                             実行(デフォルト)
-    server                               言語サーバーを起動",
+    server                               言語サーバーを起動
+    completions (shell)                  シェル補完スクリプトを出力(bash, zsh, fish, powershell)
+    new (path) [--app|--lib|--py]        新しいパッケージを作成(--pyはPythonモジュールを利用するパッケージ)
+    init [path] [--app|--lib|--py]       既存のディレクトリをパッケージとして初期化",
 
     "simplified_chinese" =>
     "\
@@ -42,6 +74,7 @@ USAGE:
 ARGS:
     <script> 从脚本文件读取程序
             参数也可以指定要传递给 <script>
+    -        从标准输入读取程序(compile, transpile 时将产物写入标准输出)
 
 OPTIONS
     --help/-?/-h                         显示帮助
@@ -54,16 +87,47 @@ OPTIONS
     --mode (mode)                        执行模式 (更多信息见`--mode --help`)
     --code/-c (string)                   作为字符串传入程序
     --module/-m (string)                 要执行的模块
+    --path (path)                        指定从标准输入(-)读取的源代码的路径(用于诊断和 import 的解析)
+    --watch/-w                           监视文件变更并重新执行(check, run)
+    --emit (kind[=path],...)             输出中间表示(ast, hir, typed-hir, bytecode, pyc, py, pyi; path 为 - 时输出到标准输出)
+    --filter (name)                      只执行名称包含 name 的测试/基准测试(test, bench)
+    --coverage                           将执行过的行记录到 build/coverage.txt(test)
+    --with-py                            同时输出由同一 HIR 生成的 .py 文件, 并将 .pyc 写入 __pycache__(compile)
+    --dry-run                            不写入产物, 只检查是否可以写入(compile, transpile, emit, doc)
+    --allow (lint)                       不执行指定的 lint(lint, fix)
+    --fix                                应用可自动应用的修复(lint)
+    --indent-width (uint)                指定缩进宽度(fmt)
+    --json                               以 JSON Lines 格式输出诊断信息(check, lint)
+    --json-only                          仅以 JSON Lines 格式输出诊断信息(--json --quiet)
+    --quiet/-q                           不输出诊断以外的信息(摘要、差异等)
+    --max-blank-lines (uint)             指定连续空行的最大数量(fmt)
+    --no-op-spaces                       不在二元运算符两侧加空格(fmt)
+    --deterministic                      输出可复现的产物和转储(逐个分析模块并重新编号类型变量)
+    --timings                            显示各阶段、各模块的时间和内存(将跟踪输出到 build/timings.json)
+    --memory-stats                       在 --timings 的基础上显示各阶段创建的自由类型变量、类型复制和 HIR 节点的数量
+    --profile-self                       将类型检查器热点路径的时间以折叠栈格式输出到 build/profile.folded(用于火焰图)
+    --output-dir (path)                  指定输出目录(compile, doc)
 
 COMMAND
     lex                                  字词解析
     parse                                语法解析
     typecheck|tc                         类型检查
-    check                                全部检查(包括所有权检查, 副作用检查等)
-    compile                              编译
+    check [files...]                     全部检查(包括所有权检查, 副作用检查等), 无参数时检查整个包
+    fmt [files...] [--check]             格式化源代码(--check 时只报告未格式化的文件)
+    lint [files...] [--fix] [--json]     执行 lint(--fix 时自动修复，不进行构建)
+    fix [files...]                       将可自动应用的修复写入文件并显示更改
+    doc [files...]                       生成 API 文档(HTML, JSON), 输出到 build/doc(可用 --output-dir 更改)
+    test [files...] [--filter (name)]    执行带有 @Test 的函数(--filter 按名称筛选)
+    bench [files...] [--filter (name)]   测量带有 @Bench 的函数的执行时间(-o 指定优化级别)
+    minimize (file)                      最小化使编译器崩溃的代码(输出到 file.min.er)
+    coverage report [files...]           显示 test --coverage 记录的覆盖率
+    compile [files...]                   编译(多个文件, 目录, glob 模式一起编译)
     transpile                            转译
     run|exec                             执行(默认模式)
-    server                               执行语言服务器",
+    server                               执行语言服务器
+    completions (shell)                  输出 shell 补全脚本(bash, zsh, fish, powershell)
+    new (path) [--app|--lib|--py]        创建新的包(--py: 使用 Python 模块的包)
+    init [path] [--app|--lib|--py]       将已有的目录初始化为包",
 
     "traditional_chinese" =>
         "\
@@ -73,6 +137,7 @@ USAGE:
 ARGS:
     <script> 從腳本檔案讀取程式
             參數也可以指定要傳遞給 <script>
+    -        從標準輸入讀取程式(compile, transpile 時將產物寫入標準輸出)
 
 OPTIONS
     --help/-?/-h                         顯示幫助
@@ -85,16 +150,47 @@ OPTIONS
     --mode (mode)                        執行模式 (更多信息見`--mode --help`)
     --code/-c (string)                   作為字串傳入程式
     --module/-m (string)                 要執行的模塊
+    --path (path)                        指定從標準輸入(-)讀取的原始碼的路徑(用於診斷和 import 的解析)
+    --watch/-w                           監視檔案變更並重新執行(check, run)
+    --emit (kind[=path],...)             輸出中間表示(ast, hir, typed-hir, bytecode, pyc, py, pyi; path 為 - 時輸出到標準輸出)
+    --filter (name)                      只執行名稱包含 name 的測試/基準測試(test, bench)
+    --coverage                           將執行過的行記錄到 build/coverage.txt(test)
+    --with-py                            同時輸出由同一 HIR 生成的 .py 檔案, 並將 .pyc 寫入 __pycache__(compile)
+    --dry-run                            不寫入產物, 只檢查是否可以寫入(compile, transpile, emit, doc)
+    --allow (lint)                       不執行指定的 lint(lint, fix)
+    --fix                                套用可自動套用的修正(lint)
+    --indent-width (uint)                指定縮排寬度(fmt)
+    --json                               以 JSON Lines 格式輸出診斷資訊(check, lint)
+    --json-only                          僅以 JSON Lines 格式輸出診斷資訊(--json --quiet)
+    --quiet/-q                           不輸出診斷以外的資訊(摘要、差異等)
+    --max-blank-lines (uint)             指定連續空行的最大數量(fmt)
+    --no-op-spaces                       不在二元運算子兩側加空格(fmt)
+    --deterministic                      輸出可重現的產物和轉儲(逐個分析模組並重新編號型別變數)
+    --timings                            顯示各階段、各模組的時間和記憶體(將追蹤輸出到 build/timings.json)
+    --memory-stats                       在 --timings 的基礎上顯示各階段建立的自由型別變數、型別複製和 HIR 節點的數量
+    --profile-self                       將型別檢查器熱點路徑的時間以摺疊堆疊格式輸出到 build/profile.folded(用於火焰圖)
+    --output-dir (path)                  指定輸出目錄(compile, doc)
 
 COMMAND
     lex                                  字詞解析
     parse                                語法解析
     typecheck|tc                         型檢查
-    check                                全部檢查(包括所有權檢查, 副作用檢查等)
-    compile                              編譯
+    check [files...]                     全部檢查(包括所有權檢查, 副作用檢查等), 無參數時檢查整個包
+    fmt [files...] [--check]             格式化原始碼(--check 時只報告未格式化的檔案)
+    lint [files...] [--fix] [--json]     執行 lint(--fix 時自動修正，不進行建置)
+    fix [files...]                       將可自動套用的修正寫入檔案並顯示變更
+    doc [files...]                       產生 API 文件(HTML, JSON), 輸出到 build/doc(可用 --output-dir 更改)
+    test [files...] [--filter (name)]    執行帶有 @Test 的函式(--filter 依名稱篩選)
+    bench [files...] [--filter (name)]   測量帶有 @Bench 的函式的執行時間(-o 指定最佳化等級)
+    minimize (file)                      最小化使編譯器崩潰的程式碼(輸出到 file.min.er)
+    coverage report [files...]           顯示 test --coverage 記錄的覆蓋率
+    compile [files...]                   編譯(多個檔案, 目錄, glob 模式一起編譯)
     transpile                            轉譯
     run|exec                             執行(預設模式)
-    server                               執行語言伺服器",
+    server                               執行語言伺服器
+    completions (shell)                  輸出 shell 補全腳本(bash, zsh, fish, powershell)
+    new (path) [--app|--lib|--py]        建立新的套件(--py: 使用 Python 模組的套件)
+    init [path] [--app|--lib|--py]       將已有的目錄初始化為套件",
 
     "english" =>
         "\
@@ -104,6 +200,7 @@ USAGE:
 ARGS:
     <script> program read from script file
             Arguments can also be specified to be passed to the <script>
+    -        program read from stdin (compile and transpile write the artifacts to stdout)
 
 OPTIONS
     --help/-?/-h                         show this help
@@ -116,16 +213,47 @@ OPTIONS
     --mode (mode)                        execution mode (See `--mode --help` for details)
     --code/-c (string)                   program passed in as string
     --module/-m (string)                 module to be executed
+    --path (path)                        the path of the source read from stdin (-), used for the diagnostics and the imports
+    --watch/-w                           rerun when the files are changed (check, run)
+    --emit (kind[=path],...)             write the intermediate representations (ast, hir, typed-hir, bytecode, pyc, py, pyi; path - means stdout)
+    --filter (name)                      run only the tests (benchmarks) whose names contain name (test, bench)
+    --coverage                           record the executed lines into build/coverage.txt (test)
+    --with-py                            also write the .py transpiled from the same HIR, and write the .pyc into __pycache__ (compile)
+    --dry-run                            check that the artifacts can be written without writing them (compile, transpile, emit, doc)
+    --allow (lint)                       do not perform the lint (lint, fix)
+    --fix                                apply the machine-applicable fixes (lint)
+    --indent-width (uint)                indentation width (fmt)
+    --json                               print the diagnostics as JSON lines (check, lint)
+    --json-only                          print only the diagnostics as JSON lines (--json --quiet)
+    --quiet/-q                           suppress the output other than the diagnostics (summaries, diffs, etc.)
+    --max-blank-lines (uint)             maximum number of consecutive blank lines (fmt)
+    --no-op-spaces                       do not put spaces around binary operators (fmt)
+    --deterministic                      make the artifacts and the dumps reproducible (the modules are analyzed one by one and the type variables are renumbered)
+    --timings                            show the time and the memory per phase and module (the trace is written to build/timings.json)
+    --memory-stats                       --timings, plus the numbers of the free type variables, the type clones and the HIR nodes created per phase
+    --profile-self                       write the time of the hot paths of the type checker to build/profile.folded as collapsed stacks (for flame graphs)
+    --output-dir (path)                  output directory (compile, doc)
 
 COMMAND
     lex                                  lexical analysis
     parse                                syntax analysis
     typecheck|tc                         type check
-    check                                full check (including ownership check, effect check, etc.)
-    compile                              compile
+    check [files...]                     full check (including ownership check, effect check, etc.), the whole package if no files are given
+    fmt [files...] [--check]             format the source code (--check: report the unformatted files only)
+    lint [files...] [--fix] [--json]     run the lints without building (--fix: apply the machine-applicable fixes)
+    fix [files...]                       apply the machine-applicable fixes in place and show the changes
+    doc [files...]                       generate the API documentation (HTML and JSON) into build/doc (see --output-dir)
+    test [files...] [--filter (name)]    run the functions marked with @Test (--filter: run the tests whose names contain name)
+    bench [files...] [--filter (name)]   measure the functions marked with @Bench (see -o for the optimization level)
+    minimize (file)                      minimize a source that crashes the compiler (into file.min.er)
+    coverage report [files...]           show the coverage recorded by test --coverage
+    compile [files...]                   compile (multiple files, directories and glob patterns are compiled at once)
     transpile                            transpile
     run|exec                             execute (default mode)
-    server                               execute language server",
+    server                               execute language server
+    completions (shell)                  print the shell completion script (bash, zsh, fish, powershell)
+    new (path) [--app|--lib|--py]        create a new package (--py: a package using a Python module)
+    init [path] [--app|--lib|--py]       initialize an existing directory as a package",
     )
 }
 
@@ -275,29 +403,72 @@ read
     )
 }
 
+/// The subcommands of `erg` (`erg <command>`)
+pub const COMMANDS: &[&str] = &[
+    "lex",
+    "parse",
+    "desugar",
+    "typecheck",
+    "tc",
+    "check",
+    "fmt",
+    "lint",
+    "fix",
+    "doc",
+    "test",
+    "bench",
+    "minimize",
+    "coverage",
+    "compile",
+    "transpile",
+    "run",
+    "execute",
+    "server",
+    "completions",
+    "new",
+    "init",
+];
+
 pub const OPTIONS: &[&str] = &[
+    "--allow",
+    "--app",
     "--build-features",
     "-c",
     "--code",
     "--check",
     "--compile",
+    "--coverage",
     "--dest",
+    "--deterministic",
+    "--dry-run",
     "--dump-as-pyc",
+    "--emit",
     "--language-server",
     "--no-std",
     "--help",
     "-?",
     "-h",
     "--hex-py-magic-num",
+    "--filter",
+    "--fix",
     "--hex-python-magic-number",
+    "--indent-width",
+    "--json",
+    "--json-only",
+    "--lib",
     "--mode",
+    "--max-blank-lines",
+    "--memory-stats",
     "--module",
     "-m",
+    "--no-op-spaces",
     "--optimization-level",
     "--opt-level",
     "-o",
     "--output-dir",
+    "--path",
     "--ping",
+    "--profile-self",
     "--ps1",
     "--ps2",
     "--python-version",
@@ -305,14 +476,21 @@ pub const OPTIONS: &[&str] = &[
     "--py-server-timeout",
     "--py-command",
     "--python-command",
+    "--py",
     "--py-magic-num",
     "--python-magic-number",
+    "--quiet",
+    "-q",
     "--quiet-startup",
     "--quiet-repl",
     "--show-type",
     "-t",
     "--target-version",
+    "--timings",
     "--version",
     "-V",
     "--verbose",
+    "--watch",
+    "-w",
+    "--with-py",
 ];
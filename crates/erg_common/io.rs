@@ -63,6 +63,8 @@ pub enum InputKind {
     DummyREPL(DummyStdin),
     /// same content as cfg.command
     Pipe(String),
+    /// from stdin, but treated as the file of the path (`erg check - --path foo.er`)
+    Buffer(PathBuf, String),
     /// from command option | eval
     Str(String),
     Dummy,
@@ -75,7 +77,7 @@ impl InputKind {
 
     pub fn path(&self) -> &Path {
         match self {
-            Self::File(filename) => filename.as_path(),
+            Self::File(filename) | Self::Buffer(filename, _) => filename.as_path(),
             Self::REPL | Self::Pipe(_) => Path::new("<stdin>"),
             Self::DummyREPL(_stdin) => Path::new("<stdin>"),
             Self::Str(_) => Path::new("<string>"),
@@ -85,7 +87,7 @@ impl InputKind {
 
     pub fn as_str(&self) -> &str {
         match self {
-            Self::File(filename) => filename.to_str().unwrap_or("_"),
+            Self::File(filename) | Self::Buffer(filename, _) => filename.to_str().unwrap_or("_"),
             Self::REPL | Self::DummyREPL(_) | Self::Pipe(_) => "<stdin>",
             Self::Str(_) => "<string>",
             Self::Dummy => "<dummy>",
@@ -93,7 +95,7 @@ impl InputKind {
     }
 
     pub fn dir(&self) -> PathBuf {
-        if let Self::File(path) = self {
+        if let Self::File(path) | Self::Buffer(path, _) = self {
             let mut path = path.clone();
             path.pop();
             if path.parent().is_none() {
@@ -107,7 +109,7 @@ impl InputKind {
     }
 
     pub fn project_root(&self) -> Option<PathBuf> {
-        if let Self::File(path) = self {
+        if let Self::File(path) | Self::Buffer(path, _) = self {
             let mut parent = path.clone();
             while parent.pop() {
                 if parent.join("package.er").exists() {
@@ -155,6 +157,10 @@ impl Input {
         Self::new(InputKind::Pipe(src), random())
     }
 
+    pub fn buffer(path: PathBuf, src: String) -> Self {
+        Self::new(InputKind::Buffer(path, src), random())
+    }
+
     pub fn str(src: String) -> Self {
         Self::new(InputKind::Str(src), random())
     }
@@ -175,6 +181,15 @@ impl Input {
         self.kind.is_repl()
     }
 
+    pub const fn is_file(&self) -> bool {
+        matches!(self.kind, InputKind::File(_))
+    }
+
+    /// read from stdin (a pipe or `-`), not from the REPL
+    pub const fn is_stdin(&self) -> bool {
+        matches!(self.kind, InputKind::Pipe(_) | InputKind::Buffer(..))
+    }
+
     pub const fn id(&self) -> u64 {
         self.id
     }
@@ -211,9 +226,13 @@ impl Input {
         GLOBAL_STDIN.set_indent(indent);
     }
 
+    pub fn take_dedent(&self) -> usize {
+        GLOBAL_STDIN.take_dedent()
+    }
+
     pub fn file_stem(&self) -> String {
         match &self.kind {
-            InputKind::File(filename) => filename
+            InputKind::File(filename) | InputKind::Buffer(filename, _) => filename
                 .file_stem()
                 .and_then(|f| f.to_str())
                 .unwrap_or("_")
@@ -228,14 +247,14 @@ impl Input {
 
     pub fn full_path(&self) -> PathBuf {
         match &self.kind {
-            InputKind::File(filename) => filename.clone(),
+            InputKind::File(filename) | InputKind::Buffer(filename, _) => filename.clone(),
             _ => PathBuf::from(self.file_stem()),
         }
     }
 
     pub fn filename(&self) -> String {
         match &self.kind {
-            InputKind::File(filename) => filename
+            InputKind::File(filename) | InputKind::Buffer(filename, _) => filename
                 .file_name()
                 .and_then(|f| f.to_str())
                 .unwrap_or("_")
@@ -251,7 +270,7 @@ impl Input {
 
     pub fn module_name(&self) -> String {
         match &self.kind {
-            InputKind::File(filename) => {
+            InputKind::File(filename) | InputKind::Buffer(filename, _) => {
                 let file_stem = if filename.file_stem() == Some(OsStr::new("__init__"))
                     || filename.file_stem() == Some(OsStr::new("__init__.d"))
                 {
@@ -296,7 +315,7 @@ impl Input {
                     }
                 }
             }
-            InputKind::Pipe(s) | InputKind::Str(s) => s.clone(),
+            InputKind::Pipe(s) | InputKind::Str(s) | InputKind::Buffer(_, s) => s.clone(),
             InputKind::REPL => GLOBAL_STDIN.read(),
             InputKind::DummyREPL(dummy) => dummy.read_line(),
             InputKind::Dummy => panic!("cannot read from a dummy file"),
@@ -309,7 +328,7 @@ impl Input {
                 let file = File::open(filename)?;
                 read_file(file)
             }
-            InputKind::Pipe(s) | InputKind::Str(s) => Ok(s.clone()),
+            InputKind::Pipe(s) | InputKind::Str(s) | InputKind::Buffer(_, s) => Ok(s.clone()),
             InputKind::REPL => Ok(GLOBAL_STDIN.read()),
             InputKind::DummyREPL(dummy) => Ok(dummy.read_line()),
             InputKind::Dummy => panic!("cannot read from a dummy file"),
@@ -340,7 +359,7 @@ impl Input {
                     }
                 }
             }
-            InputKind::Pipe(s) | InputKind::Str(s) => s.clone(),
+            InputKind::Pipe(s) | InputKind::Str(s) | InputKind::Buffer(_, s) => s.clone(),
             InputKind::REPL => GLOBAL_STDIN.read(),
             InputKind::Dummy | InputKind::DummyREPL(_) => panic!("cannot read from a dummy file"),
         }
@@ -360,11 +379,12 @@ impl Input {
                 }
                 Err(_) => vec!["<file not found>".into()],
             },
-            InputKind::Pipe(s) | InputKind::Str(s) => s.split('\n').collect::<Vec<_>>()
-                [ln_begin - 1..=ln_end - 1]
-                .iter()
-                .map(|s| s.to_string())
-                .collect(),
+            InputKind::Pipe(s) | InputKind::Str(s) | InputKind::Buffer(_, s) => {
+                s.split('\n').collect::<Vec<_>>()[ln_begin - 1..=ln_end - 1]
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            }
             InputKind::REPL => {
                 if ln_begin == ln_end {
                     vec![GLOBAL_STDIN.reread()]
@@ -385,7 +405,7 @@ impl Input {
                 reader.read_to_string(&mut buf).unwrap();
                 buf
             }
-            InputKind::Pipe(s) | InputKind::Str(s) => s.clone(),
+            InputKind::Pipe(s) | InputKind::Str(s) | InputKind::Buffer(_, s) => s.clone(),
             InputKind::REPL => GLOBAL_STDIN.reread().trim_end().to_owned(),
             InputKind::DummyREPL(dummy) => dummy.reread().unwrap_or_default(),
             InputKind::Dummy => panic!("cannot read from a dummy file"),
@@ -400,7 +420,10 @@ impl Input {
     /// 1. `{path/to}.er`
     /// 2. `{path/to}/__init__.er`
     fn resolve_local(&self, path: &Path) -> Result<PathBuf, std::io::Error> {
-        let mut dir = self.dir();
+        Self::resolve_local_in(self.dir(), path)
+    }
+
+    fn resolve_local_in(mut dir: PathBuf, path: &Path) -> Result<PathBuf, std::io::Error> {
         dir.push(path);
         dir.set_extension("er"); // {path/to}.er
         let path = dir.canonicalize().or_else(|_| {
@@ -412,23 +435,29 @@ impl Input {
         Ok(normalize_path(path))
     }
 
-    fn resolve_local_decl(&self, dir: PathBuf, path: &Path) -> Result<PathBuf, std::io::Error> {
-        self._resolve_local_decl(dir.clone(), path).or_else(|_| {
+    fn resolve_local_decl(dir: PathBuf, path: &Path) -> Result<PathBuf, std::io::Error> {
+        Self::_resolve_local_decl(dir.clone(), path).or_else(|_| {
             let path = add_postfix_foreach(path, ".d");
-            self._resolve_local_decl(dir, &path)
+            Self::_resolve_local_decl(dir, &path)
         })
     }
 
+    /// Searches `{dir}/{path/to}.er` and `{dir}/{path/to}/__init__.er`.
+    pub fn resolve_real_path_in(dir: &Path, path: &Path) -> Option<PathBuf> {
+        Self::resolve_local_in(dir.to_path_buf(), path).ok()
+    }
+
+    /// Searches the declaration file of `path` in `dir` (in the same order as the local declaration files).
+    pub fn resolve_decl_path_in(dir: &Path, path: &Path) -> Option<PathBuf> {
+        Self::resolve_local_decl(dir.to_path_buf(), path).ok()
+    }
+
     /// resolution order:
     /// 1. `{path/to}.d.er`
     /// 2. `{path/to}/__init__.d.er`
     /// 3. `{path}/__pycache__/{to}.d.er`
     /// 4. `{path/to}/__pycache__/__init__.d.er`
-    fn _resolve_local_decl(
-        &self,
-        mut dir: PathBuf,
-        path: &Path,
-    ) -> Result<PathBuf, std::io::Error> {
+    fn _resolve_local_decl(mut dir: PathBuf, path: &Path) -> Result<PathBuf, std::io::Error> {
         let mut comps = path.components();
         let last = comps
             .next_back()
@@ -548,7 +577,7 @@ impl Input {
     /// 9.  `site-packages/{path}/__pycache__/{to}.d.er`
     /// 10. `site-packages/{path/to}/__pycache__/__init__.d.er`
     pub fn resolve_decl_path(&self, path: &Path) -> Option<PathBuf> {
-        if let Ok(path) = self.resolve_local_decl(self.dir(), path) {
+        if let Ok(path) = Self::resolve_local_decl(self.dir(), path) {
             return Some(path);
         }
         // e.g.
@@ -561,7 +590,7 @@ impl Input {
         if let Some((root, first)) = self.project_root().zip(path.components().next()) {
             if root.ends_with(first) || remove_postfix(root.clone(), ".d").ends_with(first) {
                 let path = path.iter().skip(1).collect::<PathBuf>();
-                if let Ok(path) = self.resolve_local_decl(root, &path) {
+                if let Ok(path) = Self::resolve_local_decl(root, &path) {
                     return Some(path);
                 }
             }
@@ -1,7 +1,12 @@
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 use crate::consts::{ERG_MODE, PYTHON_MODE};
 
+/// The language of messages selected at runtime (0: selected by the features).
+/// See `LanguageCode::set_message_lang`.
+static MESSAGE_LANG: AtomicU8 = AtomicU8::new(0);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LanguageCode {
     English,
@@ -117,4 +122,38 @@ impl LanguageCode {
     pub fn as_str(&self) -> &str {
         <&str>::from(*self)
     }
+
+    /// Overrides the language of messages (selected by the features by default) at runtime.
+    /// `None` resets it. Only natural languages are valid.
+    pub fn set_message_lang(lang: Option<Self>) {
+        let n = match lang {
+            Some(Self::English) => 1,
+            Some(Self::Japanese) => 2,
+            Some(Self::SimplifiedChinese) => 3,
+            Some(Self::TraditionalChinese) => 4,
+            _ => 0,
+        };
+        MESSAGE_LANG.store(n, Ordering::Relaxed);
+    }
+
+    pub fn message_lang() -> Option<Self> {
+        match MESSAGE_LANG.load(Ordering::Relaxed) {
+            1 => Some(Self::English),
+            2 => Some(Self::Japanese),
+            3 => Some(Self::SimplifiedChinese),
+            4 => Some(Self::TraditionalChinese),
+            _ => None,
+        }
+    }
+
+    /// Whether messages should be displayed in `lang_name` (e.g. `"japanese"`). Used in `switch_lang!`.
+    pub fn is_message_lang(lang_name: &str) -> bool {
+        let Ok(code) = lang_name.parse::<Self>() else {
+            return false;
+        };
+        match Self::message_lang() {
+            Some(lang) => lang == code,
+            None => code.matches_feature(),
+        }
+    }
 }
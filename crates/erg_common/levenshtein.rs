@@ -40,6 +40,110 @@ pub fn levenshtein(a: &str, b: &str, limit: usize) -> Option<usize> {
     (dcol[m] <= limit).then_some(dcol[m])
 }
 
+/// The cost of an insertion, a deletion, a substitution or a transposition of adjacent characters.
+const EDIT_COST: usize = 2;
+/// The cost of a substitution which only changes the case (e.g. `a` -> `A`).
+const CASE_COST: usize = 1;
+
+/// The maximum number of edits allowed for a name with `len` characters.
+/// Names shorter than 3 characters only match if they differ in case.
+const fn max_edits(len: usize) -> usize {
+    len / 3
+}
+
+/// Weighted Damerau-Levenshtein (optimal string alignment) distance.
+/// Case-only differences cost `CASE_COST`, and the other edits cost `EDIT_COST`.
+fn weighted_distance(a: &[char], b: &[char]) -> usize {
+    let m = b.len();
+    // rows i-2, i-1, i
+    let mut prev2 = vec![0; m + 1];
+    let mut prev: Vec<_> = (0..=m).map(|j| j * EDIT_COST).collect();
+    let mut cur = vec![0; m + 1];
+    for i in 1..=a.len() {
+        cur[0] = i * EDIT_COST;
+        for j in 1..=m {
+            let subst = if a[i - 1] == b[j - 1] {
+                0
+            } else if a[i - 1].to_lowercase().eq(b[j - 1].to_lowercase()) {
+                CASE_COST
+            } else {
+                EDIT_COST
+            };
+            let mut dist = (prev[j - 1] + subst)
+                .min(prev[j] + EDIT_COST)
+                .min(cur[j - 1] + EDIT_COST);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                dist = dist.min(prev2[j - 2] + EDIT_COST);
+            }
+            cur[j] = dist;
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[m]
+}
+
+/// Returns the cost of correcting `name` to `candidate` (lower is more similar),
+/// or `None` if `candidate` is too different to be suggested.
+///
+/// Case-insensitive matches are always accepted.
+/// Otherwise, the number of edits allowed depends on the length of `name`,
+/// so short names are not matched with unrelated names (e.g. `x` and `y`).
+///
+/// ```
+/// # use erg_common::levenshtein::similarity_cost;
+/// assert_eq!(similarity_cost("True", "true"), Some(1));
+/// assert_eq!(similarity_cost("print!", "pritn!"), Some(2));
+/// assert_eq!(similarity_cost("y", "x"), None);
+/// assert_eq!(similarity_cost("False", "f"), None);
+/// ```
+pub fn similarity_cost(candidate: &str, name: &str) -> Option<usize> {
+    if candidate == name {
+        return Some(0);
+    }
+    let a = candidate.chars().collect::<Vec<_>>();
+    let b = name.chars().collect::<Vec<_>>();
+    let max_edits = max_edits(b.len());
+    if a.len().abs_diff(b.len()) > max_edits {
+        return None;
+    }
+    let dist = weighted_distance(&a, &b);
+    let case_insensitive_eq = candidate.to_lowercase() == name.to_lowercase();
+    (case_insensitive_eq || dist <= max_edits * EDIT_COST).then_some(dist)
+}
+
+/// Returns the candidate most similar to `name`.
+/// Each candidate has its scope distance (0 for the innermost scope),
+/// and the nearer one is preferred if the similarities are the same.
+pub fn get_similar_name_in_scopes<'a, S, I: Iterator<Item = (usize, &'a S)>>(
+    candidates: I,
+    name: &str,
+) -> Option<&'a S>
+where
+    S: std::borrow::Borrow<str> + ?Sized,
+{
+    get_similar_name_and_some_in_scopes(candidates.map(|(depth, v)| (depth, &(), v)), name)
+        .map(|(_, v)| v)
+}
+
+/// Same as `get_similar_name_in_scopes`, but also returns the data attached to the candidate.
+pub fn get_similar_name_and_some_in_scopes<'a, S, T, I>(
+    candidates: I,
+    name: &str,
+) -> Option<(&'a T, &'a S)>
+where
+    S: std::borrow::Borrow<str> + ?Sized,
+    I: Iterator<Item = (usize, &'a T, &'a S)>,
+{
+    candidates
+        .filter_map(|(depth, some, v)| {
+            let cost = similarity_cost(v.borrow(), name)?;
+            Some(((cost, depth, v.borrow()), (some, v)))
+        })
+        .min_by_key(|(key, _)| *key)
+        .map(|(_, candidate)| candidate)
+}
+
 pub fn get_similar_name<'a, S: ?Sized, I: Iterator<Item = &'a S>>(
     candidates: I,
     name: &str,
@@ -47,15 +151,7 @@ pub fn get_similar_name<'a, S: ?Sized, I: Iterator<Item = &'a S>>(
 where
     S: std::borrow::Borrow<str>,
 {
-    let limit = (name.len() as f64).sqrt().round() as usize;
-    let most_similar_name =
-        candidates.min_by_key(|v| levenshtein(v.borrow(), name, limit).unwrap_or(usize::MAX))?;
-    let dist = levenshtein(most_similar_name.borrow(), name, limit);
-    if dist.is_none() || dist.unwrap() >= limit {
-        None
-    } else {
-        Some(most_similar_name)
-    }
+    get_similar_name_in_scopes(candidates.map(|v| (0, v)), name)
 }
 
 pub fn get_similar_name_and_some<'a, S: ?Sized, T, I: Iterator<Item = (&'a T, &'a S)>>(
@@ -65,20 +161,11 @@ pub fn get_similar_name_and_some<'a, S: ?Sized, T, I: Iterator<Item = (&'a T, &'
 where
     S: std::borrow::Borrow<str>,
 {
-    let limit = (name.len() as f64).sqrt().round() as usize;
-    let most_similar_name_and_some = candidates
-        .min_by_key(|(_, v)| levenshtein(v.borrow(), name, limit).unwrap_or(usize::MAX))?;
-    let dist = levenshtein(most_similar_name_and_some.1.borrow(), name, limit);
-    if dist.is_none() || dist.unwrap() >= limit {
-        None
-    } else {
-        Some(most_similar_name_and_some)
-    }
+    get_similar_name_and_some_in_scopes(candidates.map(|(some, v)| (0, some, v)), name)
 }
-
 #[cfg(test)]
 mod tests {
-    use crate::levenshtein::get_similar_name;
+    use crate::levenshtein::{get_similar_name, get_similar_name_in_scopes};
 
     #[test]
     fn test_get_similar_name() {
@@ -96,4 +183,50 @@ mod tests {
             Some("False")
         );
     }
+
+    #[test]
+    fn test_get_similar_short_name() {
+        // short names are not matched with unrelated names
+        assert_eq!(get_similar_name(["x", "ab", "id"].into_iter(), "y"), None);
+        assert_eq!(get_similar_name(["is", "int"].into_iter(), "in"), None);
+        assert_eq!(
+            get_similar_name(["Int", "abs"].into_iter(), "int"),
+            Some("Int")
+        );
+        assert_eq!(get_similar_name(["ord", "str"].into_iter(), "for"), None);
+        // too long
+        assert_eq!(get_similar_name(["print!"].into_iter(), "pr"), None);
+    }
+
+    #[test]
+    fn test_get_similar_name_weights() {
+        // transposition is a single edit
+        assert_eq!(
+            get_similar_name(["print!", "pint!"].into_iter(), "pritn!"),
+            Some("print!")
+        );
+        // case-insensitive match is preferred
+        assert_eq!(
+            get_similar_name(["counter", "Count"].into_iter(), "count"),
+            Some("Count")
+        );
+        assert_eq!(
+            get_similar_name(["HTTP_SERVER"].into_iter(), "http_server"),
+            Some("HTTP_SERVER")
+        );
+    }
+
+    #[test]
+    fn test_get_similar_name_in_scopes() {
+        let candidates = [(2, "values"), (0, "value2"), (1, "value1")];
+        assert_eq!(
+            get_similar_name_in_scopes(candidates.into_iter(), "value"),
+            Some("value2")
+        );
+        let candidates = [(2, "value"), (0, "valeu")];
+        assert_eq!(
+            get_similar_name_in_scopes(candidates.into_iter(), "Value"),
+            Some("value")
+        );
+    }
 }
@@ -3,19 +3,26 @@ use std::fmt;
 use std::path::PathBuf;
 
 pub mod cache;
+pub mod cancel;
+pub mod completion;
 pub mod config;
 pub mod consts;
 pub mod datetime;
+pub mod deterministic;
 pub mod dict;
+pub mod diff;
 pub mod env;
 pub mod erg_util;
 pub mod error;
+pub mod exit_code;
 pub mod fresh;
 pub mod fxhash;
+pub mod grapheme;
 pub mod help_messages;
 pub mod io;
 pub mod lang;
 pub mod levenshtein;
+pub mod logger;
 pub mod macros;
 pub mod opcode;
 pub mod opcode308;
@@ -23,6 +30,7 @@ pub mod opcode309;
 pub mod opcode310;
 pub mod opcode311;
 pub mod pathutil;
+pub mod profile;
 pub mod python_util;
 pub mod random;
 pub mod serialize;
@@ -32,9 +40,12 @@ pub mod spawn;
 pub mod stdin;
 pub mod str;
 pub mod style;
+pub mod timing;
+pub mod toml;
 pub mod traits;
 pub mod triple;
 pub mod tsort;
+pub mod watch;
 
 use consts::CASE_SENSITIVE;
 
@@ -118,6 +129,29 @@ pub fn normalize_newline(src: &str) -> String {
     src.replace("\r\n", "\n").replace('\r', "\n")
 }
 
+/// Quotes and escapes the string as a JSON string literal.
+/// ```
+/// # use erg_common::escape_json;
+/// assert_eq!(escape_json("a \"b\"\n"), r#""a \"b\"\n""#);
+/// ```
+pub fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
 /// cut \n
 #[inline]
 pub fn chomp(src: &str) -> String {
@@ -0,0 +1,295 @@
+//! The logger used by `log!`.
+//!
+//! The output is controlled at runtime by the environment variable `ERG_LOG`.
+//! It is a comma-separated list of directives, each of which is `level` or `target=level`.
+//!
+//! ```sh
+//! ERG_LOG=info                  # all targets, `info` and `err` records
+//! ERG_LOG=parser=debug,codegen  # all records of the parser and the code generator
+//! ERG_LOG=info,unifier=off      # all targets except the unifier
+//! ```
+//!
+//! Levels: `off`, `err` (`error`), `info`, `debug`.
+//! Targets: `parser`, `unifier`, `linker`, `codegen`, `compiler`, `server`, `common`.
+//!
+//! If `ERG_LOG_JSON` is set to a file path, the records are written to the file as JSON lines instead of the console.
+//! With the `debug` feature, all records are displayed unless `ERG_LOG` is specified.
+use std::fmt;
+use std::fs::File;
+use std::io::{stderr, stdout, Write};
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::style::{colors::DEBUG, colors::DEBUG_ERROR, colors::DEBUG_MAIN, RESET};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Error => write!(f, "err"),
+            Self::Info => write!(f, "info"),
+            Self::Debug => write!(f, "debug"),
+        }
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "err" | "error" => Ok(Self::Error),
+            "info" => Ok(Self::Info),
+            "debug" | "all" => Ok(Self::Debug),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The subsystem which emits a record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogTarget {
+    Parser,
+    Unifier,
+    Linker,
+    Codegen,
+    Compiler,
+    Server,
+    Common,
+}
+
+impl fmt::Display for LogTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parser => write!(f, "parser"),
+            Self::Unifier => write!(f, "unifier"),
+            Self::Linker => write!(f, "linker"),
+            Self::Codegen => write!(f, "codegen"),
+            Self::Compiler => write!(f, "compiler"),
+            Self::Server => write!(f, "server"),
+            Self::Common => write!(f, "common"),
+        }
+    }
+}
+
+impl FromStr for LogTarget {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parser" => Ok(Self::Parser),
+            "unifier" => Ok(Self::Unifier),
+            "linker" => Ok(Self::Linker),
+            "codegen" => Ok(Self::Codegen),
+            "compiler" => Ok(Self::Compiler),
+            "server" | "els" => Ok(Self::Server),
+            "common" => Ok(Self::Common),
+            _ => Err(()),
+        }
+    }
+}
+
+impl LogTarget {
+    /// Determines the target from the source file path of the record (`file!()`).
+    pub fn from_path(path: &str) -> Self {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or("");
+        if path.contains("erg_parser") {
+            Self::Parser
+        } else if file_name == "unify.rs" {
+            Self::Unifier
+        } else if file_name == "link_hir.rs" || file_name == "link_ast.rs" {
+            Self::Linker
+        } else if file_name == "codegen.rs" {
+            Self::Codegen
+        } else if path.contains("erg_compiler") {
+            Self::Compiler
+        } else if path.contains("els") {
+            Self::Server
+        } else {
+            Self::Common
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LoggerConfig {
+    default: LogLevel,
+    targets: Vec<(LogTarget, LogLevel)>,
+    /// the most verbose level of all targets (for quick rejection)
+    max: LogLevel,
+    json: Option<Mutex<File>>,
+}
+
+impl LoggerConfig {
+    fn parse(directives: &str, default: LogLevel) -> Self {
+        let mut config = Self {
+            default,
+            targets: vec![],
+            max: default,
+            json: None,
+        };
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            if let Some((target, level)) = directive.split_once('=') {
+                if let (Ok(target), Ok(level)) = (target.trim().parse(), level.trim().parse()) {
+                    config.targets.push((target, level));
+                }
+            } else if let Ok(level) = directive.parse() {
+                config.default = level;
+            } else if let Ok(target) = directive.parse() {
+                config.targets.push((target, LogLevel::Debug));
+            }
+        }
+        config.max = config
+            .targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(config.default, LogLevel::max);
+        config
+    }
+
+    fn from_env() -> Self {
+        let default = if cfg!(feature = "debug") {
+            LogLevel::Debug
+        } else {
+            LogLevel::Off
+        };
+        let mut config = match std::env::var("ERG_LOG") {
+            Ok(directives) => Self::parse(&directives, LogLevel::Off),
+            Err(_) => Self::parse("", default),
+        };
+        if let Ok(path) = std::env::var("ERG_LOG_JSON") {
+            config.json = File::create(path).ok().map(Mutex::new);
+        }
+        config
+    }
+
+    fn level_of(&self, target: LogTarget) -> LogLevel {
+        self.targets
+            .iter()
+            .rev()
+            .find(|(t, _)| *t == target)
+            .map_or(self.default, |(_, level)| *level)
+    }
+}
+
+static CONFIG: OnceLock<LoggerConfig> = OnceLock::new();
+
+fn config() -> &'static LoggerConfig {
+    CONFIG.get_or_init(LoggerConfig::from_env)
+}
+
+/// Returns `true` if a record of `level` emitted in the file `path` should be output.
+#[inline]
+pub fn enabled(level: LogLevel, path: &str) -> bool {
+    let config = config();
+    config.max >= level && config.level_of(LogTarget::from_path(path)) >= level
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    let mut in_ansi_escape = false;
+    for c in s.chars() {
+        // ANSI escape sequences (colors) are removed
+        if in_ansi_escape {
+            in_ansi_escape = !c.is_ascii_alphabetic();
+            continue;
+        }
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\x1b' => in_ansi_escape = true,
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes a record. Use `log!` instead of calling this directly.
+pub fn write_record(level: LogLevel, path: &str, line: u32, args: fmt::Arguments) {
+    let target = LogTarget::from_path(path);
+    if let Some(json) = &config().json {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |d| d.as_secs_f64());
+        let record = format!(
+            "{{\"time\":{time:.6},\"level\":\"{level}\",\"target\":\"{target}\",\"file\":\"{}\",\"line\":{line},\"message\":\"{}\"}}",
+            escape_json(path),
+            escape_json(args.to_string().trim_end()),
+        );
+        if let Ok(mut file) = json.lock() {
+            let _ = writeln!(file, "{record}");
+        }
+        return;
+    }
+    let color = match level {
+        LogLevel::Error => DEBUG_ERROR,
+        LogLevel::Info => DEBUG_MAIN,
+        _ => "",
+    };
+    let record = format!("[{DEBUG}{target}{RESET}] {path}:{line:04}: {color}{args}{RESET}\n");
+    // without the `debug` feature, stdout is reserved (e.g. the language server communicates through it)
+    if cfg!(feature = "debug") {
+        let _ = stdout().write_all(record.as_bytes());
+    } else {
+        let _ = stderr().write_all(record.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directives() {
+        let config = LoggerConfig::parse("info,parser=debug,codegen=off", LogLevel::Off);
+        assert_eq!(config.level_of(LogTarget::Compiler), LogLevel::Info);
+        assert_eq!(config.level_of(LogTarget::Parser), LogLevel::Debug);
+        assert_eq!(config.level_of(LogTarget::Codegen), LogLevel::Off);
+        assert_eq!(config.max, LogLevel::Debug);
+        let config = LoggerConfig::parse("unifier", LogLevel::Off);
+        assert_eq!(config.level_of(LogTarget::Unifier), LogLevel::Debug);
+        assert_eq!(config.level_of(LogTarget::Linker), LogLevel::Off);
+    }
+
+    #[test]
+    fn test_target_from_path() {
+        assert_eq!(
+            LogTarget::from_path("crates/erg_parser/lex.rs"),
+            LogTarget::Parser
+        );
+        assert_eq!(
+            LogTarget::from_path("crates/erg_compiler/context/unify.rs"),
+            LogTarget::Unifier
+        );
+        assert_eq!(
+            LogTarget::from_path("crates\\erg_compiler\\link_hir.rs"),
+            LogTarget::Linker
+        );
+        assert_eq!(
+            LogTarget::from_path("crates/erg_compiler/lower.rs"),
+            LogTarget::Compiler
+        );
+    }
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json("a\"b\n\x1b[0mc"), "a\\\"b\\nc");
+    }
+}
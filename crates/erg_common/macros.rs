@@ -147,7 +147,7 @@ macro_rules! switch_lang {
         $lang_name: literal => $msg: expr,
         $($rest_lang_name: literal => $rest_msg: expr,)+
     ) => {{
-        if cfg!(feature = $lang_name) {
+        if $crate::lang::LanguageCode::is_message_lang($lang_name) {
             $msg
         } else {
             switch_lang!($($rest_lang_name => $rest_msg,)+)
@@ -435,36 +435,35 @@ macro_rules! debug_info {
 }
 
 /// Debug log utility.
+/// The output is controlled by `ERG_LOG` at runtime (see `erg_common::logger`).
 /// directives:
-///     c: colored output
-///     f: file specified
-///     f+c: file specified and colored (e.g. colored output to stderr)
-///     info: info logging, (comprehensive shorthand for "c GREEN")
-///     info_f: file version of info
-///     err: error logging, (comprehensive shorthand for "c RED")
-///     err_f: file version of err
+///     info: `info` level record
+///     err: `err` level record
+///     (none), c: `debug` level record
+///     info_f/err_f: output specified (colored)
+///     f: output specified
 #[macro_export]
 macro_rules! log {
     (info $($arg: tt)*) => {{
-        $crate::log!(c DEBUG_MAIN, $($arg)*);
+        $crate::log!(lv Info, $($arg)*);
     }};
 
     (err $($arg: tt)*) => {{
-        $crate::log!(c DEBUG_ERROR, $($arg)*);
+        $crate::log!(lv Error, $($arg)*);
     }};
 
     (info_f $output:ident, $($arg: tt)*) => {{
-        $crate::log!(f+c $output, DEBUG_MAIN, $($arg)*);
+        $crate::log!(f+c $output, Info, DEBUG_MAIN, $($arg)*);
     }};
 
     (err_f $output:ident, $($arg: tt)*) => {{
-        $crate::log!(f+c $output, DEBUG_ERROR, $($arg)*);
+        $crate::log!(f+c $output, Error, DEBUG_ERROR, $($arg)*);
     }};
 
     (f $output: ident, $($arg: tt)*) => {{
-        if cfg!(feature = "debug") {
+        if $crate::logger::enabled($crate::logger::LogLevel::Debug, file!()) {
             #[allow(unused_imports)]
-            use $crate::color::{RESET, colors::DEBUG_MAIN, colors::DEBUG_ERROR};
+            use $crate::style::{RESET, colors::DEBUG_MAIN, colors::DEBUG_ERROR};
             $crate::debug_info!($output);
             write!($output, $($arg)*).unwrap();
             write!($output, "{}", RESET).unwrap(); // color color anyway
@@ -472,23 +471,31 @@ macro_rules! log {
         }
     }};
 
-    (c $color:ident, $($arg: tt)*) => {{
-        if cfg!(feature = "debug") {
+    (lv $level:ident, $($arg: tt)*) => {{
+        if $crate::logger::enabled($crate::logger::LogLevel::$level, file!()) {
+            // the arguments may refer to the colors
             #[allow(unused_imports)]
-            use $crate::style::{RESET, colors::DEBUG_MAIN, colors::DEBUG_ERROR};
-            $crate::debug_info!();
-            print!("{}", $color);
-            println!($($arg)*);
-            print!("{}", RESET); // reset color anyway
+            use $crate::style::*;
+            $crate::logger::write_record(
+                $crate::logger::LogLevel::$level,
+                file!(),
+                line!(),
+                format_args!($($arg)*),
+            );
         }
     }};
 
-    (f+c $output:ident, $color:ident, $($arg: tt)*) => {{
-        if cfg!(feature = "debug") {
+    // colored output is not supported by the logger (colors are decided by the level)
+    (c $color:ident, $($arg: tt)*) => {{
+        $crate::log!(lv Debug, $($arg)*);
+    }};
+
+    (f+c $output:ident, $level:ident, $color:ident, $($arg: tt)*) => {{
+        if $crate::logger::enabled($crate::logger::LogLevel::$level, file!()) {
             #[allow(unused_imports)]
-            use $crate::style::{RESET, colors::DEBUG_MAIN};
+            use $crate::style::{RESET, colors::DEBUG_MAIN, colors::DEBUG_ERROR};
             $crate::debug_info!($output);
-            write!($output, "{}{}{}", $color, $($arg)*, RESET).unwrap();
+            write!($output, "{}", $color).unwrap();
             write!($output, $($arg)*).unwrap();
             write!($output, "{}", RESET).unwrap(); // reset color anyway
             $output.flush().unwrap();
@@ -496,12 +503,7 @@ macro_rules! log {
     }};
 
     ($($arg: tt)*) => {{
-        if cfg!(feature = "debug") {
-            use $crate::style::*;
-            $crate::debug_info!();
-            println!($($arg)*);
-            print!("{}", RESET); // reset color anyway
-        }
+        $crate::log!(lv Debug, $($arg)*);
     }};
 }
 
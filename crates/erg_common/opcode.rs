@@ -25,6 +25,7 @@ pub enum CommonOpcode {
     UNARY_INVERT = 15,
     BINARY_MATRIX_MULTIPLY = 16,
     INPLACE_MATRIX_MULTIPLY = 17,
+    BINARY_SUBSCR = 25,
     STORE_SUBSCR = 60,
     GET_ITER = 68,
     GET_YIELD_FROM_ITER = 69,
@@ -65,8 +66,12 @@ pub enum CommonOpcode {
     DELETE_FAST = 126,
     RAISE_VARARGS = 130,
     MAKE_FUNCTION = 132,
+    BUILD_SLICE = 133,
     CALL_FUNCTION_EX = 142,
     EXTENDED_ARG = 144,
+    LIST_APPEND = 145,
+    SET_ADD = 146,
+    MAP_ADD = 147,
     LOAD_METHOD = 160,
     NOT_IMPLEMENTED = 255,
 }
@@ -90,6 +95,7 @@ impl TryFrom<u8> for CommonOpcode {
             11 => UNARY_NEGATIVE,
             12 => UNARY_NOT,
             15 => UNARY_INVERT,
+            25 => BINARY_SUBSCR,
             60 => STORE_SUBSCR,
             68 => GET_ITER,
             69 => GET_YIELD_FROM_ITER,
@@ -130,8 +136,12 @@ impl TryFrom<u8> for CommonOpcode {
             126 => DELETE_FAST,
             130 => RAISE_VARARGS,
             132 => MAKE_FUNCTION,
+            133 => BUILD_SLICE,
             142 => CALL_FUNCTION_EX,
             144 => EXTENDED_ARG,
+            145 => LIST_APPEND,
+            146 => SET_ADD,
+            147 => MAP_ADD,
             160 => LOAD_METHOD,
             255 => NOT_IMPLEMENTED,
             _other => return Err(()),
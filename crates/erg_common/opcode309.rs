@@ -101,6 +101,7 @@ impl_u8_enum! {Opcode309;
     RAISE_VARARGS = 130,
     CALL_FUNCTION = 131,
     MAKE_FUNCTION = 132,
+    BUILD_SLICE = 133,
     LOAD_CLOSURE = 135,
     LOAD_DEREF = 136,
     STORE_DEREF = 137,
@@ -108,6 +109,10 @@ impl_u8_enum! {Opcode309;
     CALL_FUNCTION_EX = 142,
     SETUP_WITH = 143,
     EXTENDED_ARG = 144,
+    LIST_APPEND = 145,
+    SET_ADD = 146,
+    MAP_ADD = 147,
+    BUILD_MAP_UNPACK_WITH_CALL = 151,
     BUILD_TUPLE_UNPACK_WITH_CALL = 158,
     LOAD_METHOD = 160,
     CALL_METHOD = 161,
@@ -99,6 +99,7 @@ impl_u8_enum! {Opcode310;
     RAISE_VARARGS = 130,
     CALL_FUNCTION = 131,
     MAKE_FUNCTION = 132,
+    BUILD_SLICE = 133,
     LOAD_CLOSURE = 135,
     LOAD_DEREF = 136,
     STORE_DEREF = 137,
@@ -106,9 +107,13 @@ impl_u8_enum! {Opcode310;
     CALL_FUNCTION_EX = 142,
     SETUP_WITH = 143,
     EXTENDED_ARG = 144,
+    LIST_APPEND = 145,
+    SET_ADD = 146,
+    MAP_ADD = 147,
     LOAD_METHOD = 160,
     CALL_METHOD = 161,
     LIST_EXTEND = 162,
+    DICT_MERGE = 164,
     // Erg-specific opcodes (must have a unary `ERG_`)
     // Define in descending order from 219, 255
     ERG_POP_NTH = 196,
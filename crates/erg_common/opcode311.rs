@@ -82,6 +82,7 @@ impl_u8_enum! {Opcode311;
     RAISE_VARARGS = 130,
     CALL_FUNCTION = 131,
     MAKE_FUNCTION = 132,
+    BUILD_SLICE = 133,
     MAKE_CELL = 135,
     LOAD_CLOSURE = 136,
     LOAD_DEREF = 137,
@@ -99,6 +100,7 @@ impl_u8_enum! {Opcode311;
     FORMAT_VALUE = 155,
     LOAD_METHOD = 160,
     LIST_EXTEND = 162,
+    DICT_MERGE = 164,
     PRECALL = 166,
     CALL = 171,
     KW_NAMES = 172,
@@ -212,3 +212,151 @@ pub fn squash(path: PathBuf) -> PathBuf {
 pub fn remove_verbatim(path: &Path) -> String {
     path.to_string_lossy().replace("\\\\?\\", "")
 }
+
+/// Matches `path` against a glob `pattern` (`*`, `?` and `**` are supported).
+/// ```
+/// # use erg_common::pathutil::glob_match;
+/// assert!(glob_match("src/*.er", "src/main.er"));
+/// assert!(!glob_match("src/*.er", "src/foo/bar.er"));
+/// assert!(glob_match("src/**/*.er", "src/foo/bar.er"));
+/// assert!(glob_match("src/**/*.er", "src/main.er"));
+/// assert!(glob_match("t?st.er", "test.er"));
+/// ```
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.split(['/', '\\']).collect::<Vec<_>>();
+    let path = path.split(['/', '\\']).collect::<Vec<_>>();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            match_components(&pattern[1..], path)
+                || (!path.is_empty() && match_components(pattern, &path[1..]))
+        }
+        (Some(pat), Some(comp)) => {
+            let pat = pat.chars().collect::<Vec<_>>();
+            let comp = comp.chars().collect::<Vec<_>>();
+            match_chars(&pat, &comp) && match_components(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn match_chars(pattern: &[char], s: &[char]) -> bool {
+    match (pattern.first(), s.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            match_chars(&pattern[1..], s) || (!s.is_empty() && match_chars(pattern, &s[1..]))
+        }
+        (Some('?'), Some(_)) => match_chars(&pattern[1..], &s[1..]),
+        (Some(p), Some(c)) if p == c => match_chars(&pattern[1..], &s[1..]),
+        _ => false,
+    }
+}
+
+pub fn is_glob_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Returns the Erg files (`*.er`) in `dir` recursively, sorted by path.
+/// Hidden directories and `__pycache__` are skipped.
+pub fn erg_files<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let Ok(entries) = dir.as_ref().read_dir() else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "__pycache__" {
+                files.extend(erg_files(&path));
+            }
+        } else if name.ends_with(".er") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Test files are `*.test.er` and the Erg files directly under a `tests` directory.
+pub fn is_test_file<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    path.to_string_lossy().ends_with(".test.er")
+        || (path.extension().is_some_and(|ext| ext == "er")
+            && path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .is_some_and(|dir| dir == "tests"))
+}
+
+/// Expands a glob pattern (e.g. `src/**/*.er`) into the matching Erg files.
+pub fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    // the longest directory prefix without wildcards
+    let base = pattern
+        .split(['/', '\\'])
+        .take_while(|comp| !is_glob_pattern(comp))
+        .collect::<Vec<_>>()
+        .join("/");
+    let base_dir = if base.is_empty() { "." } else { &base[..] };
+    erg_files(base_dir)
+        .into_iter()
+        .map(|path| {
+            if base.is_empty() {
+                path.strip_prefix(".").map_or(path.clone(), PathBuf::from)
+            } else {
+                path
+            }
+        })
+        .filter(|path| glob_match(pattern, &path.to_string_lossy()))
+        .collect()
+}
+
+/// Expands files, directories and glob patterns (e.g. the arguments of `erg check`) into Erg files.
+/// If no targets are given, the files of the package containing the current directory are returned.
+pub fn expand_targets(targets: &[PathBuf]) -> Vec<PathBuf> {
+    if targets.is_empty() {
+        return package_root(".").map_or(vec![], |root| {
+            let src = root.join("src");
+            erg_files(if src.is_dir() { src } else { root })
+        });
+    }
+    let mut files = vec![];
+    for target in targets.iter() {
+        let pattern = target.to_string_lossy();
+        if target.is_dir() {
+            files.extend(erg_files(target));
+        } else if is_glob_pattern(&pattern) {
+            files.extend(expand_glob(&pattern));
+        } else {
+            files.push(target.clone());
+        }
+    }
+    files.dedup();
+    files
+}
+
+/// Returns the nearest ancestor directory (including `dir` itself) that contains `package.er`.
+pub fn package_root<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+    find_upward(dir, "package.er").map(|mut path| {
+        path.pop();
+        path
+    })
+}
+
+/// Returns the path of `file_name` in the nearest ancestor directory (including `dir` itself) that contains it.
+pub fn find_upward<P: AsRef<Path>>(dir: P, file_name: &str) -> Option<PathBuf> {
+    let mut dir = dir.as_ref().canonicalize().ok()?;
+    loop {
+        let path = dir.join(file_name);
+        if path.exists() {
+            return Some(path);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! A lightweight self-profiler of the compiler (`--profile-self`).
+//!
+//! The hot paths (unification, instantiation, etc.) are instrumented with scoped timers (`PROFILER.scope(..)`).
+//! The self time of each stack of scopes is accumulated, and written in the collapsed stack format
+//! (`parse;lower;sub_unify 1234`, in microseconds), which can be converted into a flame graph
+//! with `flamegraph.pl` or `inferno-flamegraph`, or viewed with speedscope.
+//!
+//! The scopes are disabled unless `PROFILER.enable()` is called, and then cost only an atomic load.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::pathutil::package_root;
+
+type Stack = Vec<&'static str>;
+
+#[derive(Debug)]
+struct Frame {
+    name: &'static str,
+    start: Instant,
+    /// the total time of the callee scopes
+    children: Duration,
+}
+
+/// The scopes of the current thread. The samples are flushed to the profiler when the outermost scope exits.
+#[derive(Debug, Default)]
+struct ThreadProfile {
+    frames: Vec<Frame>,
+    samples: HashMap<Stack, Duration>,
+}
+
+thread_local! {
+    static THREAD_PROFILE: RefCell<ThreadProfile> = RefCell::new(ThreadProfile::default());
+}
+
+#[derive(Debug)]
+pub struct SelfProfiler {
+    enabled: AtomicBool,
+    samples: Mutex<Option<HashMap<Stack, Duration>>>,
+}
+
+pub static PROFILER: SelfProfiler = SelfProfiler::new();
+
+impl Default for SelfProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Records the time until dropped (see `SelfProfiler::scope`).
+#[must_use]
+#[derive(Debug)]
+pub struct ProfileScope<'p> {
+    profiler: Option<&'p SelfProfiler>,
+}
+
+impl Drop for ProfileScope<'_> {
+    fn drop(&mut self) {
+        if let Some(profiler) = self.profiler {
+            profiler.exit();
+        }
+    }
+}
+
+impl SelfProfiler {
+    pub const fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            samples: Mutex::new(None),
+        }
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Measures the time until the returned scope is dropped as `name`.
+    /// Direct recursion (`name` is the innermost scope) is merged into the outer scope.
+    #[inline]
+    pub fn scope(&self, name: &'static str) -> ProfileScope<'_> {
+        if !self.is_enabled() {
+            return ProfileScope { profiler: None };
+        }
+        let entered = THREAD_PROFILE.with(|profile| {
+            let mut profile = profile.borrow_mut();
+            if profile
+                .frames
+                .last()
+                .is_some_and(|frame| frame.name == name)
+            {
+                return false;
+            }
+            profile.frames.push(Frame {
+                name,
+                start: Instant::now(),
+                children: Duration::ZERO,
+            });
+            true
+        });
+        ProfileScope {
+            profiler: entered.then_some(self),
+        }
+    }
+
+    fn exit(&self) {
+        let flushed = THREAD_PROFILE.with(|profile| {
+            let mut profile = profile.borrow_mut();
+            let stack = profile
+                .frames
+                .iter()
+                .map(|frame| frame.name)
+                .collect::<Stack>();
+            let frame = profile.frames.pop()?;
+            let total = frame.start.elapsed();
+            if let Some(parent) = profile.frames.last_mut() {
+                parent.children += total;
+            }
+            *profile.samples.entry(stack).or_default() += total.saturating_sub(frame.children);
+            profile
+                .frames
+                .is_empty()
+                .then(|| std::mem::take(&mut profile.samples))
+        });
+        if let Some(flushed) = flushed {
+            if let Ok(mut samples) = self.samples.lock() {
+                let samples = samples.get_or_insert_with(HashMap::new);
+                for (stack, time) in flushed {
+                    *samples.entry(stack).or_default() += time;
+                }
+            }
+        }
+    }
+
+    /// The self time of each stack in the collapsed stack format (sorted by the stacks, zero samples are omitted).
+    pub fn collapsed(&self) -> String {
+        let samples = self
+            .samples
+            .lock()
+            .ok()
+            .and_then(|samples| samples.clone())
+            .unwrap_or_default();
+        let mut samples = samples
+            .into_iter()
+            .map(|(stack, time)| (stack.join(";"), time.as_micros()))
+            .filter(|(_, micros)| *micros > 0)
+            .collect::<Vec<_>>();
+        samples.sort();
+        let mut collapsed = String::new();
+        for (stack, micros) in samples {
+            let _ = writeln!(collapsed, "{stack} {micros}");
+        }
+        collapsed
+    }
+
+    /// Writes the collapsed stacks to `build/profile.folded` (of the package, if any).
+    pub fn report(&self) {
+        let out_dir = package_root(".")
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("build");
+        let path = out_dir.join("profile.folded");
+        match fs::create_dir_all(&out_dir).and_then(|_| fs::write(&path, self.collapsed())) {
+            Ok(()) => eprintln!("the profile is written to {}", path.display()),
+            Err(err) => eprintln!("cannot write {}: {err}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn busy(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {}
+    }
+
+    #[test]
+    fn test_self_profiler() {
+        let profiler = SelfProfiler::new();
+        {
+            let _scope = profiler.scope("lower");
+        }
+        assert!(profiler.collapsed().is_empty());
+        profiler.enable();
+        // the thread-local frames are not shared with the other tests
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let _lower = profiler.scope("lower");
+                busy(Duration::from_millis(2));
+                {
+                    let _unify = profiler.scope("sub_unify");
+                    let _recursive = profiler.scope("sub_unify");
+                    busy(Duration::from_millis(2));
+                }
+                drop(_lower);
+                let collapsed = profiler.collapsed();
+                let lines = collapsed.lines().collect::<Vec<_>>();
+                assert_eq!(lines.len(), 2, "{collapsed}");
+                assert!(lines[0].starts_with("lower "));
+                assert!(lines[1].starts_with("lower;sub_unify "));
+                let micros = lines[1].rsplit(' ').next().unwrap();
+                assert!(micros.parse::<u128>().unwrap() >= 2000);
+            });
+        });
+    }
+}
@@ -650,10 +650,15 @@ impl PythonVersion {
         }
     }
 
+    /// The omitted parts are regarded as 0 (`3.10` <= `3.10.0`).
     pub fn le(&self, other: &Self) -> bool {
-        self.major <= other.major
-            || (self.major == other.major && self.minor <= other.minor)
-            || (self.major == other.major && self.minor == other.minor && self.micro <= other.micro)
+        let lhs = (self.major, self.minor.unwrap_or(0), self.micro.unwrap_or(0));
+        let rhs = (
+            other.major,
+            other.minor.unwrap_or(0),
+            other.micro.unwrap_or(0),
+        );
+        lhs <= rhs
     }
 
     pub fn minor_is(&self, major: u8, minor: u8) -> bool {
@@ -670,6 +675,19 @@ impl PythonVersion {
     }
 }
 
+impl std::fmt::Display for PythonVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{minor}")?;
+            if let Some(micro) = self.micro {
+                write!(f, ".{micro}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl std::str::FromStr for PythonVersion {
     type Err = String;
 
@@ -853,7 +871,8 @@ pub fn spawn_py(py_command: Option<&str>, code: &str) {
     }
 }
 
-pub fn exec_py_code(code: &str, output: Output) -> std::io::Result<ExitStatus> {
+/// `args`: the command line arguments passed to the code (`sys.argv[1:]`)
+pub fn exec_py_code(code: &str, args: &[&str], output: Output) -> std::io::Result<ExitStatus> {
     let mut out = if cfg!(windows) {
         let fallback = |err: std::io::Error| {
             // if the filename or extension is too long
@@ -867,6 +886,7 @@ pub fn exec_py_code(code: &str, output: Output) -> std::io::Result<ExitStatus> {
                     .unwrap();
                 Command::new(which_python())
                     .arg(tmp_file)
+                    .args(args)
                     .stdout(output.clone())
                     .spawn()
             } else {
@@ -876,15 +896,19 @@ pub fn exec_py_code(code: &str, output: Output) -> std::io::Result<ExitStatus> {
         Command::new(which_python())
             .arg("-c")
             .arg(code)
+            .args(args)
             .stdout(output.clone())
             .spawn()
             .or_else(fallback)
             .expect("cannot execute python")
     } else {
-        let exec_command = format!("{} -c \"{code}\"", which_python());
+        // the arguments are passed as the positional parameters of the shell (not to be interpreted)
+        let exec_command = format!("{} -c \"{code}\" \"$@\"", which_python());
         Command::new("sh")
             .arg("-c")
             .arg(exec_command)
+            .arg("erg")
+            .args(args)
             .stdout(output)
             .spawn()
             .expect("cannot execute python")
@@ -1,6 +1,5 @@
 //! オブジェクトのシリアライズ(バイナリ列化)のためのユーティリティーを定義・実装する
-use std::time::{SystemTime, UNIX_EPOCH};
-
+use crate::deterministic;
 use crate::impl_display_from_debug;
 use crate::python_util::PythonVersion;
 use crate::Str;
@@ -38,11 +37,66 @@ pub const fn get_ver_from_magic_num(magic_num: u32) -> PythonVersion {
 }
 
 pub fn get_timestamp_bytes() -> [u8; 4] {
-    let secs = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|dur| dur.as_secs() as u32)
-        .unwrap_or(0);
-    secs.to_le_bytes()
+    deterministic::timestamp().to_le_bytes()
+}
+
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+/// SipHash-c-d with the key `(k0, 0)`
+fn siphash(c_rounds: usize, d_rounds: usize, k0: u64, data: &[u8]) -> u64 {
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        0x7465646279746573,
+    ];
+    let chunks = data.chunks_exact(8);
+    let rest = chunks.remainder();
+    let mut last = [0u8; 8];
+    last[..rest.len()].copy_from_slice(rest);
+    let last = u64::from_le_bytes(last) | ((data.len() as u64) << 56);
+    let words = chunks.map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()));
+    for m in words.chain(std::iter::once(last)) {
+        v[3] ^= m;
+        for _ in 0..c_rounds {
+            sip_round(&mut v);
+        }
+        v[0] ^= m;
+    }
+    v[2] ^= 0xff;
+    for _ in 0..d_rounds {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// The hash of the source file recorded in a hash-based .pyc (PEP 552),
+/// the same as `importlib.util.source_hash` of the Python of `py_magic_num`.
+/// ```
+/// # use erg_common::serialize::get_source_hash_bytes;
+/// let hash = get_source_hash_bytes(3495, b"print(1)\n");
+/// assert_eq!(hash, [0xda, 0x81, 0x2d, 0x7f, 0x40, 0x7a, 0x30, 0x59]);
+/// ```
+pub fn get_source_hash_bytes(py_magic_num: u32, source: &[u8]) -> [u8; 8] {
+    let key = u32::from_le_bytes(get_magic_num_bytes(py_magic_num)) as u64;
+    // Python 3.11 switched from SipHash-2-4 to SipHash-1-3
+    let hash = if get_ver_from_magic_num(py_magic_num).minor >= Some(11) {
+        siphash(1, 3, key, source)
+    } else {
+        siphash(2, 4, key, source)
+    };
+    hash.to_le_bytes()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -106,6 +106,11 @@ impl<T: Hash> Set<T> {
         self.elems.is_empty()
     }
 
+    #[inline]
+    pub fn clear(&mut self) {
+        self.elems.clear();
+    }
+
     #[inline]
     pub fn iter(&self) -> Iter<'_, T> {
         self.elems.iter()
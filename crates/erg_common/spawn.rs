@@ -1,5 +1,6 @@
 #[cfg(all(unix, any(feature = "debug", feature = "backtrace")))]
 pub use backtrace_on_stack_overflow;
+use std::cell::Cell;
 use std::thread::{self, JoinHandle};
 
 const STACK_SIZE: usize = if cfg!(feature = "large_thread") {
@@ -8,6 +9,38 @@ const STACK_SIZE: usize = if cfg!(feature = "large_thread") {
     4 * 1024 * 1024
 };
 
+/// The recursive analyses stop (with an error) when the remaining stack is less than this.
+/// This must be larger than the stack used between two checks (`is_stack_exhausted`), which is large in debug builds.
+const RED_ZONE: usize = 256 * 1024;
+
+thread_local! {
+    /// The lowest address of the stack that the analyses may use (0 if unknown).
+    static STACK_LIMIT: Cell<usize> = const { Cell::new(0) };
+}
+
+#[inline(never)]
+fn stack_pointer() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// Runs `run` recording the stack size of the current thread (the stack is assumed to grow downward).
+fn with_stack_limit<T>(stack_size: usize, run: impl FnOnce() -> T) -> T {
+    let red_zone = RED_ZONE.min(stack_size / 4);
+    let limit = stack_pointer().saturating_sub(stack_size - red_zone);
+    STACK_LIMIT.with(|l| l.set(limit));
+    run()
+}
+
+/// Whether the stack of the current thread is nearly exhausted.
+/// The recursive analyses (parsing, lowering, etc.) check this to report too deeply nested code as an error instead of overflowing the stack.
+/// This is always `false` in the threads not spawned by `exec_new_thread`/`spawn_new_thread`.
+#[inline]
+pub fn is_stack_exhausted() -> bool {
+    let limit = STACK_LIMIT.with(|l| l.get());
+    limit != 0 && stack_pointer() < limit
+}
+
 #[macro_export]
 macro_rules! enable_overflow_stacktrace {
     () => {
@@ -29,16 +62,25 @@ where
     let child = thread::Builder::new()
         .name(name.to_string())
         .stack_size(STACK_SIZE)
-        .spawn(run)
+        .spawn(|| with_stack_limit(STACK_SIZE, run))
         .unwrap();
     // Wait for thread to join
     child.join().unwrap_or_else(|err| {
         eprintln!("Thread panicked: {err:?}");
-        std::process::exit(1);
+        std::process::exit(crate::exit_code::INTERNAL_ERROR);
     })
 }
 
 pub fn spawn_new_thread<F, T>(run: F, name: &str) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_new_thread_with_stack(run, name, STACK_SIZE)
+}
+
+/// Same as `spawn_new_thread`, but the stack size is specified.
+pub fn spawn_new_thread_with_stack<F, T>(run: F, name: &str, stack_size: usize) -> JoinHandle<T>
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
@@ -46,7 +88,31 @@ where
     enable_overflow_stacktrace!();
     thread::Builder::new()
         .name(name.to_string())
-        .stack_size(STACK_SIZE)
-        .spawn(run)
+        .stack_size(stack_size)
+        .spawn(move || with_stack_limit(stack_size, run))
         .unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(n: usize) -> usize {
+        // a large frame
+        let buf = std::hint::black_box([0u8; 1024]);
+        if is_stack_exhausted() {
+            n
+        } else {
+            depth(n + 1) + buf[0] as usize
+        }
+    }
+
+    #[test]
+    fn test_stack_exhausted() {
+        assert!(!is_stack_exhausted());
+        let depth = spawn_new_thread(|| depth(0), "test_stack_exhausted")
+            .join()
+            .unwrap();
+        assert!(depth > 0);
+    }
+}
@@ -5,20 +5,128 @@ use std::io::{stdin, BufRead, BufReader};
 
 #[cfg(feature = "full-repl")]
 use crossterm::{
-    cursor::MoveToColumn,
+    cursor::{MoveToColumn, MoveUp},
     event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
     style::Print,
-    terminal::{disable_raw_mode, enable_raw_mode},
+    terminal::{disable_raw_mode, enable_raw_mode, size},
     terminal::{Clear, ClearType},
 };
 #[cfg(feature = "full-repl")]
+use std::fs::OpenOptions;
+#[cfg(feature = "full-repl")]
+use std::io::Write;
+#[cfg(feature = "full-repl")]
+use std::path::PathBuf;
+#[cfg(feature = "full-repl")]
 use std::process::Command;
 #[cfg(feature = "full-repl")]
 use std::process::Output;
 
+#[cfg(feature = "full-repl")]
+use crate::env::erg_path;
 use crate::shared::Shared;
 
+#[cfg(feature = "full-repl")]
+fn common_prefix(candidates: &[String]) -> &str {
+    let Some(first) = candidates.first() else {
+        return "";
+    };
+    let len = candidates
+        .iter()
+        .skip(1)
+        .fold(first.len(), |len, candidate| {
+            first[..len]
+                .char_indices()
+                .zip(candidate.chars())
+                .find(|((_, l), r)| l != r)
+                .map_or(len.min(candidate.len()), |((i, _), _)| i)
+        });
+    &first[..len]
+}
+
+/// The input history of the REPL, which is saved in `$ERG_PATH/.erg_history` and shared between sessions.
+#[cfg(feature = "full-repl")]
+#[derive(Debug, Default)]
+struct History {
+    entries: Vec<String>,
+    /// `entries.len()` means the line being edited (not a history entry)
+    position: usize,
+    /// The line being edited is saved while browsing the history
+    editing: String,
+    path: Option<PathBuf>,
+}
+
+#[cfg(feature = "full-repl")]
+impl History {
+    const MAX_ENTRIES: usize = 1000;
+
+    fn load() -> Self {
+        let path = erg_path().join(".erg_history");
+        let mut entries = std::fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if entries.len() > Self::MAX_ENTRIES {
+            entries.drain(..entries.len() - Self::MAX_ENTRIES);
+            let _ = std::fs::write(&path, entries.join("\n") + "\n");
+        }
+        Self {
+            position: entries.len(),
+            entries,
+            editing: String::new(),
+            path: Some(path),
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        self.position = self.entries.len();
+        if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        self.position = self.entries.len();
+        if let Some(path) = self.path.as_ref() {
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+
+    fn prev(&mut self, current: &str) -> Option<&str> {
+        if self.position == 0 {
+            return None;
+        }
+        if self.position == self.entries.len() {
+            self.editing = current.to_string();
+        }
+        self.position -= 1;
+        self.entries.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        if self.position >= self.entries.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(
+            self.entries
+                .get(self.position)
+                .unwrap_or(&self.editing)
+                .as_str(),
+        )
+    }
+
+    /// Returns the index of the latest entry containing `query` before `before`.
+    fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+}
+
 /// e.g.
 /// ```erg
 /// >>> print! 1
@@ -36,8 +144,10 @@ pub struct StdinReader {
     lineno: usize,
     buf: Vec<String>,
     #[cfg(feature = "full-repl")]
-    history_input_position: usize,
+    history: History,
     indent: u16,
+    /// The number of blocks closed by the user (by backspace at the beginning of the line)
+    dedent: usize,
 }
 
 impl StdinReader {
@@ -89,8 +199,9 @@ impl StdinReader {
         )
     }
 
+    /// `complete` is not used because the input is line-buffered.
     #[cfg(not(feature = "full-repl"))]
-    pub fn read(&mut self) -> String {
+    pub fn read_with(&mut self, _complete: &dyn Fn(&str) -> Vec<String>) -> String {
         let mut line = "".to_string();
         let stdin = stdin();
         let mut reader = BufReader::new(stdin.lock());
@@ -100,12 +211,13 @@ impl StdinReader {
         self.buf.last().cloned().unwrap_or_default()
     }
 
+    /// `complete` returns the names that complete the last word of the given code (used for Tab completion).
     #[cfg(feature = "full-repl")]
-    pub fn read(&mut self) -> String {
+    pub fn read_with(&mut self, complete: &dyn Fn(&str) -> Vec<String>) -> String {
         enable_raw_mode().unwrap();
         let mut output = std::io::stdout();
         let mut line = String::new();
-        self.input(&mut line).unwrap();
+        self.input(&mut line, complete).unwrap();
         disable_raw_mode().unwrap();
         execute!(output, MoveToColumn(0)).unwrap();
         self.lineno += 1;
@@ -114,15 +226,17 @@ impl StdinReader {
     }
 
     #[cfg(feature = "full-repl")]
-    fn input(&mut self, line: &mut String) -> std::io::Result<()> {
+    fn input(
+        &mut self,
+        line: &mut String,
+        complete: &dyn Fn(&str) -> Vec<String>,
+    ) -> std::io::Result<()> {
         let mut position = 0;
-        let mut consult_history = false;
         let mut stdout = std::io::stdout();
         while let Event::Key(KeyEvent {
             code, modifiers, ..
         }) = read()?
         {
-            consult_history = false;
             match (code, modifiers) {
                 (KeyCode::Char('z'), KeyModifiers::CONTROL)
                 | (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
@@ -148,17 +262,69 @@ impl StdinReader {
                     line.insert_str(position, &clipboard);
                     position += clipboard.len();
                 }
+                (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
+                    position = 0;
+                }
+                (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
+                    position = line.len();
+                }
+                (KeyCode::Char('k'), KeyModifiers::CONTROL) => {
+                    line.truncate(position);
+                }
+                (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                    line.drain(..position);
+                    position = 0;
+                }
+                (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+                    let word_start = line[..position]
+                        .trim_end()
+                        .rfind(' ')
+                        .map_or(0, |i| i + 1);
+                    line.drain(word_start..position);
+                    position = word_start;
+                }
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                    let (found, accepted) = self.reverse_search(&mut stdout)?;
+                    if let Some(found) = found {
+                        *line = found;
+                        position = line.len();
+                    }
+                    if accepted {
+                        println!();
+                        break;
+                    }
+                }
                 (_, KeyModifiers::CONTROL) => continue,
-                (KeyCode::Tab, _) => {
+                (KeyCode::Tab, _) if line[..position].trim().is_empty() => {
                     line.insert_str(position, "    ");
                     position += 4;
                 }
+                (KeyCode::Tab, _) => {
+                    let candidates = complete(&line[..position]);
+                    let word_start = line[..position]
+                        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '!'))
+                        .map_or(0, |i| i + 1);
+                    let common = common_prefix(&candidates);
+                    if common.len() > position - word_start {
+                        line.insert_str(position, &common[position - word_start..]);
+                        position += common.len() - (position - word_start);
+                    } else if candidates.len() > 1 {
+                        self.redraw(&mut stdout, line, position)?;
+                        self.show_candidates(&mut stdout, &candidates, position)?;
+                        continue;
+                    }
+                }
                 (KeyCode::Home, _) => {
                     position = 0;
                 }
                 (KeyCode::End, _) => {
                     position = line.len();
                 }
+                // close the current block
+                (KeyCode::Backspace, _) if position == 0 && self.indent > 1 => {
+                    self.indent -= 1;
+                    self.dedent += 1;
+                }
                 (KeyCode::Backspace, _) => {
                     if position == 0 {
                         continue;
@@ -173,42 +339,15 @@ impl StdinReader {
                     line.remove(position);
                 }
                 (KeyCode::Up, _) => {
-                    consult_history = true;
-                    if self.history_input_position == 0 {
-                        continue;
-                    }
-                    self.history_input_position -= 1;
-                    execute!(stdout, MoveToColumn(4), Clear(ClearType::UntilNewLine))?;
-                    if let Some(l) = self.buf.get(self.history_input_position) {
-                        position = l.len();
-                        line.clear();
-                        line.push_str(l);
+                    if let Some(prev) = self.history.prev(line) {
+                        *line = prev.to_string();
+                        position = line.len();
                     }
                 }
                 (KeyCode::Down, _) => {
-                    if self.history_input_position == self.buf.len() {
-                        continue;
-                    }
-                    if self.history_input_position == self.buf.len() - 1 {
-                        *line = "".to_string();
-                        position = 0;
-                        self.history_input_position += 1;
-                        execute!(
-                            stdout,
-                            MoveToColumn(4),
-                            Clear(ClearType::UntilNewLine),
-                            MoveToColumn(self.indent * 4),
-                            Print(line.to_owned()),
-                            MoveToColumn(self.indent * 4 + position as u16)
-                        )?;
-                        continue;
-                    }
-                    self.history_input_position += 1;
-                    execute!(stdout, MoveToColumn(4), Clear(ClearType::UntilNewLine))?;
-                    if let Some(l) = self.buf.get(self.history_input_position) {
-                        position = l.len();
-                        line.clear();
-                        line.push_str(l);
+                    if let Some(next) = self.history.next() {
+                        *line = next.to_string();
+                        position = line.len();
                     }
                 }
                 (KeyCode::Left, _) => {
@@ -224,6 +363,12 @@ impl StdinReader {
                     position += 1;
                 }
                 (KeyCode::Enter, _) => {
+                    // erase the completion candidates
+                    execute!(
+                        stdout,
+                        MoveToColumn(self.indent * 4 + line.len() as u16),
+                        Clear(ClearType::FromCursorDown)
+                    )?;
                     println!();
                     break;
                 }
@@ -234,19 +379,107 @@ impl StdinReader {
                 }
                 _ => {}
             }
+            self.redraw(&mut stdout, line, position)?;
+        }
+        self.history.push(line);
+        Ok(())
+    }
+
+    #[cfg(feature = "full-repl")]
+    fn redraw(
+        &self,
+        stdout: &mut std::io::Stdout,
+        line: &str,
+        position: usize,
+    ) -> std::io::Result<()> {
+        execute!(
+            stdout,
+            MoveToColumn(4),
+            Clear(ClearType::FromCursorDown),
+            MoveToColumn(self.indent * 4),
+            Print(line.to_owned()),
+            MoveToColumn(self.indent * 4 + position as u16)
+        )
+    }
+
+    /// Shows the completion candidates below the current line (until the next redraw).
+    #[cfg(feature = "full-repl")]
+    fn show_candidates(
+        &self,
+        stdout: &mut std::io::Stdout,
+        candidates: &[String],
+        position: usize,
+    ) -> std::io::Result<()> {
+        let (width, _) = size().unwrap_or((80, 24));
+        let mut list = candidates.join("  ");
+        if list.chars().count() >= width as usize {
+            list = list.chars().take(width as usize - 4).collect::<String>() + "...";
+        }
+        execute!(
+            stdout,
+            Print("\r\n"),
+            Print(list),
+            MoveUp(1),
+            MoveToColumn(self.indent * 4 + position as u16)
+        )
+    }
+
+    /// Incremental reverse search of the history (Ctrl-R).
+    /// Returns the found entry and whether it was accepted with Enter.
+    #[cfg(feature = "full-repl")]
+    fn reverse_search(
+        &mut self,
+        stdout: &mut std::io::Stdout,
+    ) -> std::io::Result<(Option<String>, bool)> {
+        let mut query = String::new();
+        let mut found = None;
+        loop {
+            let matched = found.map_or("", |i: usize| &self.history.entries[i]);
             execute!(
                 stdout,
                 MoveToColumn(4),
                 Clear(ClearType::UntilNewLine),
                 MoveToColumn(self.indent * 4),
-                Print(line.to_owned()),
-                MoveToColumn(self.indent * 4 + position as u16)
+                Print(format!("(reverse-i-search)`{query}': {matched}")),
             )?;
+            let Event::Key(KeyEvent {
+                code, modifiers, ..
+            }) = read()?
+            else {
+                continue;
+            };
+            let latest = self.history.entries.len();
+            match (code, modifiers) {
+                // search for an older entry
+                (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                    found = self
+                        .history
+                        .search(&query, found.unwrap_or(latest))
+                        .or(found);
+                }
+                (KeyCode::Char('g'), KeyModifiers::CONTROL) | (KeyCode::Esc, _) => {
+                    return Ok((None, false));
+                }
+                (KeyCode::Backspace, _) => {
+                    query.pop();
+                    found = self.history.search(&query, latest);
+                }
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                    query.push(c);
+                    found = self
+                        .history
+                        .search(&query, found.map_or(latest, |i| i + 1));
+                }
+                (KeyCode::Enter, _) => {
+                    let found = found.map(|i| self.history.entries[i].clone());
+                    return Ok((found, true));
+                }
+                _ => {
+                    let found = found.map(|i| self.history.entries[i].clone());
+                    return Ok((found, false));
+                }
+            }
         }
-        if !consult_history {
-            self.history_input_position = self.buf.len() + 1;
-        }
-        Ok(())
     }
 
     pub fn reread(&self) -> String {
@@ -275,14 +508,19 @@ impl GlobalStdin {
                 lineno: 1,
                 buf: vec![],
                 #[cfg(feature = "full-repl")]
-                history_input_position: 1,
+                history: History::load(),
                 indent: 1,
+                dedent: 0,
             })
         })
     }
 
     pub fn read(&'static self) -> String {
-        self.get().borrow_mut().read()
+        self.get().borrow_mut().read_with(&|_| vec![])
+    }
+
+    pub fn read_with(&'static self, complete: &dyn Fn(&str) -> Vec<String>) -> String {
+        self.get().borrow_mut().read_with(complete)
     }
 
     pub fn reread(&'static self) -> String {
@@ -309,6 +547,11 @@ impl GlobalStdin {
         self.get().borrow_mut().indent = n as u16;
     }
 
+    /// Returns the number of blocks closed by the user while reading the last line.
+    pub fn take_dedent(&'static self) -> usize {
+        std::mem::take(&mut self.get().borrow_mut().dedent)
+    }
+
     pub fn insert_whitespace(&'static self, whitespace: &str) {
         if let Some(line) = self.get().borrow_mut().last_line() {
             line.insert_str(0, whitespace);
@@ -2,9 +2,16 @@ use std::borrow::{Borrow, Cow};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ops::{Add, Deref};
+use std::sync::{Arc, OnceLock};
+
+use crate::cache::CacheSet;
 
 pub type ArcStr = std::sync::Arc<str>;
 
+/// The global string interner (used for the identifiers and type names lexed from the source).
+/// It grows until `Str::clear_interned` is called (e.g. when the compiler resources are reset).
+static INTERNER: OnceLock<CacheSet<str>> = OnceLock::new();
+
 /// Used to hold an immutable string.
 ///
 /// It can construct as a const (by Str::ever).
@@ -17,7 +24,7 @@ pub enum Str {
 impl PartialEq for Str {
     #[inline]
     fn eq(&self, other: &Str) -> bool {
-        self[..] == other[..]
+        self.ptr_eq(other) || self[..] == other[..]
     }
 }
 
@@ -166,6 +173,43 @@ impl Str {
         Str::Rc(s.into())
     }
 
+    /// Returns the interned string.
+    /// Interned strings with the same contents share a single allocation and can be compared by pointer.
+    /// ```
+    /// # use erg_common::str::Str;
+    /// let a = Str::intern("foo");
+    /// let b = Str::intern(&String::from("foo"));
+    /// assert!(a.ptr_eq(&b));
+    /// ```
+    pub fn intern(s: &str) -> Self {
+        INTERNER.get_or_init(CacheSet::new).get(s)
+    }
+
+    /// Clears the interned strings to release the memory.
+    /// The strings interned so far are still valid, and they are equal to (but not `ptr_eq` with) the newly interned ones.
+    pub fn clear_interned() {
+        if let Some(interner) = INTERNER.get() {
+            interner.clear();
+        }
+    }
+
+    /// Interns `self` (static strings are returned as they are).
+    pub fn into_interned(self) -> Self {
+        match self {
+            Str::Rc(s) => Self::intern(&s),
+            Str::Static(_) => self,
+        }
+    }
+
+    /// Returns `true` if `self` and `other` point to the same string.
+    pub fn ptr_eq(&self, other: &Str) -> bool {
+        match (self, other) {
+            (Str::Rc(l), Str::Rc(r)) => Arc::ptr_eq(l, r),
+            (Str::Static(l), Str::Static(r)) => std::ptr::eq(*l, *r),
+            _ => false,
+        }
+    }
+
     pub fn into_rc(self) -> ArcStr {
         match self {
             Str::Rc(s) => s,
@@ -283,4 +327,45 @@ mod tests {
             vec!["aa", "bb", "ff"]
         );
     }
+
+    #[test]
+    fn test_clear_interned() {
+        // the global interner is not cleared here (the other tests use it)
+        let interner = CacheSet::<str>::new();
+        let a = interner.get("ident");
+        assert!(a.ptr_eq(&interner.get("ident")));
+        interner.clear();
+        assert!(interner.is_empty());
+        let b = interner.get("ident");
+        assert!(!a.ptr_eq(&b));
+        assert_eq!(a, b);
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn test_intern() {
+        let a = Str::intern("ident");
+        let b = Str::rc("ident").into_interned();
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&Str::rc("ident")));
+        assert_eq!(a, Str::rc("ident"));
+        assert!(!a.ptr_eq(&Str::intern("ident2")));
+    }
+
+    #[test]
+    fn test_intern_concurrently() {
+        let interner = CacheSet::<str>::new();
+        let handles = (0..8)
+            .map(|_| {
+                let interner = interner.clone();
+                std::thread::spawn(move || interner.get("ident"))
+            })
+            .collect::<Vec<_>>();
+        let interned = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>();
+        assert!(interned.iter().all(|s| s.ptr_eq(&interned[0])));
+        assert_eq!(interner.len(), 1);
+    }
 }
@@ -0,0 +1,459 @@
+//! Measures the wall time and the memory usage of each compilation phase (`--timings`).
+//!
+//! The measurement is global (the imported modules are compiled in other threads),
+//! and is disabled unless `TIMINGS.enable()` is called.
+//! With `--memory-stats`, the numbers of the objects created by the compiler are also counted (see `Counter`).
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use crate::escape_json;
+use crate::pathutil::package_root;
+use crate::profile::PROFILER;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    /// the builtin types and modules are registered
+    Builtins,
+    Parse,
+    Desugar,
+    Lower,
+    /// type variables are dereferenced (unified) and the types are finalized
+    Resolve,
+    EffectCheck,
+    OwnershipCheck,
+    Link,
+    Optimize,
+    CodeGen,
+}
+
+impl Phase {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Builtins => "builtins",
+            Self::Parse => "parse",
+            Self::Desugar => "desugar",
+            Self::Lower => "lower",
+            Self::Resolve => "resolve",
+            Self::EffectCheck => "effect check",
+            Self::OwnershipCheck => "ownership check",
+            Self::Link => "link",
+            Self::Optimize => "optimize",
+            Self::CodeGen => "codegen",
+        }
+    }
+}
+
+/// The objects counted with `--memory-stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Counter {
+    /// free (unbound) type variables created
+    FreeVars,
+    /// `Type`s cloned (including the nested ones)
+    TypeClones,
+    /// HIR expressions created by the lowerer
+    HirNodes,
+}
+
+impl Counter {
+    pub const ALL: [Counter; 3] = [Self::FreeVars, Self::TypeClones, Self::HirNodes];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::FreeVars => "free vars",
+            Self::TypeClones => "type clones",
+            Self::HirNodes => "HIR nodes",
+        }
+    }
+
+    /// for the keys of the Chrome trace
+    const fn key(&self) -> &'static str {
+        match self {
+            Self::FreeVars => "free_vars",
+            Self::TypeClones => "type_clones",
+            Self::HirNodes => "hir_nodes",
+        }
+    }
+}
+
+/// A measured execution of a phase
+#[derive(Debug, Clone)]
+pub struct PhaseSpan {
+    pub phase: Phase,
+    pub module: String,
+    /// since the measurement is enabled
+    pub start: Duration,
+    pub duration: Duration,
+    /// the change of the resident set size (in bytes).
+    /// This is approximate because the other threads also allocate.
+    pub rss_delta: Option<i64>,
+    /// the increase of the counters (indexed by `Counter`), if `--memory-stats` is enabled.
+    /// This is approximate for the same reason as `rss_delta`.
+    pub counts: Option<[u64; Counter::ALL.len()]>,
+    pub thread: ThreadId,
+}
+
+/// The resident set size of the process (in bytes), if available (Linux only).
+pub fn current_rss() -> Option<i64> {
+    read_status_kb("VmRSS:")
+}
+
+/// The peak resident set size of the process (in bytes), if available (Linux only).
+pub fn peak_rss() -> Option<i64> {
+    read_status_kb("VmHWM:")
+}
+
+fn read_status_kb(key: &str) -> Option<i64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with(key))?;
+    let kb = line[key.len()..].trim().trim_end_matches("kB").trim();
+    kb.parse::<i64>().ok().map(|kb| kb * 1024)
+}
+
+fn format_bytes(bytes: i64) -> String {
+    let mib = bytes as f64 / (1024.0 * 1024.0);
+    format!("{mib:+.1} MiB")
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Paths are shown relative to the current directory if possible.
+fn module_label(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[derive(Debug)]
+pub struct Timings {
+    enabled: AtomicBool,
+    start: OnceLock<Instant>,
+    spans: Mutex<Vec<PhaseSpan>>,
+    memory_stats: AtomicBool,
+    counters: [AtomicU64; Counter::ALL.len()],
+}
+
+pub static TIMINGS: Timings = Timings::new();
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timings {
+    pub const fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            start: OnceLock::new(),
+            spans: Mutex::new(vec![]),
+            memory_stats: AtomicBool::new(false),
+            counters: [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)],
+        }
+    }
+
+    pub fn enable(&self) {
+        self.start.get_or_init(Instant::now);
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Enables the measurement and the counters (`--memory-stats`).
+    pub fn enable_memory_stats(&self) {
+        self.enable();
+        self.memory_stats.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_memory_stats_enabled(&self) -> bool {
+        self.memory_stats.load(Ordering::Relaxed)
+    }
+
+    /// Increments `counter` (if `--memory-stats` is enabled).
+    #[inline]
+    pub fn count(&self, counter: Counter) {
+        if self.is_memory_stats_enabled() {
+            self.counters[counter as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The total of `counter` since the counters are enabled.
+    pub fn counter(&self, counter: Counter) -> u64 {
+        self.counters[counter as usize].load(Ordering::Relaxed)
+    }
+
+    fn snapshot_counters(&self) -> Option<[u64; Counter::ALL.len()]> {
+        self.is_memory_stats_enabled()
+            .then(|| Counter::ALL.map(|counter| self.counter(counter)))
+    }
+
+    /// Runs `f` and records its wall time and memory usage as `phase` of `module`.
+    /// If the measurement is disabled, just runs `f`.
+    /// The phase is also the outermost scope of the self-profiler (`--profile-self`).
+    pub fn measure<T>(&self, phase: Phase, module: &Path, f: impl FnOnce() -> T) -> T {
+        let _scope = PROFILER.scope(phase.name());
+        if !self.is_enabled() {
+            return f();
+        }
+        let start = Instant::now();
+        let rss = current_rss();
+        let counts = self.snapshot_counters();
+        let res = f();
+        let duration = start.elapsed();
+        let rss_delta = current_rss().zip(rss).map(|(after, before)| after - before);
+        let counts = self
+            .snapshot_counters()
+            .zip(counts)
+            .map(|(after, before)| std::array::from_fn(|i| after[i].saturating_sub(before[i])));
+        let span = PhaseSpan {
+            phase,
+            module: module_label(module),
+            start: start.duration_since(*self.start.get_or_init(Instant::now)),
+            duration,
+            rss_delta,
+            counts,
+            thread: thread::current().id(),
+        };
+        if let Ok(mut spans) = self.spans.lock() {
+            spans.push(span);
+        }
+        res
+    }
+
+    pub fn spans(&self) -> Vec<PhaseSpan> {
+        self.spans
+            .lock()
+            .map(|spans| spans.clone())
+            .unwrap_or_default()
+    }
+
+    /// The totals per phase (in the order of the phases) and per module (the slowest first).
+    pub fn table(&self) -> String {
+        let spans = self.spans();
+        let mut phases = HashMap::<Phase, (usize, Duration, Option<i64>)>::new();
+        let mut modules = HashMap::<&str, Duration>::new();
+        for span in spans.iter() {
+            let (calls, time, mem) = phases.entry(span.phase).or_default();
+            *calls += 1;
+            *time += span.duration;
+            if let Some(delta) = span.rss_delta {
+                *mem = Some(mem.unwrap_or(0) + delta);
+            }
+            *modules.entry(&span.module).or_default() += span.duration;
+        }
+        let total = spans.iter().map(|span| span.duration).sum::<Duration>();
+        let share = |time: Duration| {
+            if total.is_zero() {
+                0.0
+            } else {
+                time.as_secs_f64() / total.as_secs_f64() * 100.0
+            }
+        };
+        let mut table = format!(
+            "{:<16} {:>6} {:>12} {:>7} {:>12}\n",
+            "phase", "calls", "time", "share", "memory"
+        );
+        let mut phases = phases.into_iter().collect::<Vec<_>>();
+        phases.sort_by_key(|(phase, _)| *phase);
+        for (phase, (calls, time, mem)) in phases {
+            let _ = writeln!(
+                table,
+                "{:<16} {calls:>6} {:>12} {:>6.1}% {:>12}",
+                phase.name(),
+                format_ms(time),
+                share(time),
+                mem.map_or("-".into(), format_bytes),
+            );
+        }
+        let mut modules = modules.into_iter().collect::<Vec<_>>();
+        modules.sort_by(|(l_mod, l), (r_mod, r)| r.cmp(l).then(l_mod.cmp(r_mod)));
+        let _ = writeln!(table, "\n{:<40} {:>12} {:>7}", "module", "time", "share");
+        const MAX_MODULES: usize = 20;
+        for (module, time) in modules.iter().take(MAX_MODULES) {
+            let _ = writeln!(
+                table,
+                "{module:<40} {:>12} {:>6.1}%",
+                format_ms(*time),
+                share(*time)
+            );
+        }
+        if modules.len() > MAX_MODULES {
+            let _ = writeln!(table, "... and {} more", modules.len() - MAX_MODULES);
+        }
+        if self.is_memory_stats_enabled() {
+            table.push_str(&self.counter_table(&spans));
+        }
+        let wall = self
+            .start
+            .get()
+            .map_or(Duration::ZERO, |start| start.elapsed());
+        let _ = write!(
+            table,
+            "\ntotal: {} in phases, {} wall time",
+            format_ms(total),
+            format_ms(wall)
+        );
+        if let Some(peak) = peak_rss() {
+            let _ = write!(
+                table,
+                ", peak RSS {:.1} MiB",
+                peak as f64 / (1024.0 * 1024.0)
+            );
+        }
+        table
+    }
+
+    /// The increase of the counters per phase (`--memory-stats`)
+    fn counter_table(&self, spans: &[PhaseSpan]) -> String {
+        let mut phases = HashMap::<Phase, [u64; Counter::ALL.len()]>::new();
+        for span in spans.iter() {
+            if let Some(counts) = span.counts {
+                let total = phases.entry(span.phase).or_default();
+                for (total, count) in total.iter_mut().zip(counts) {
+                    *total += count;
+                }
+            }
+        }
+        let mut table = format!("\n{:<16}", "phase");
+        for counter in Counter::ALL {
+            let _ = write!(table, " {:>12}", counter.name());
+        }
+        table.push('\n');
+        let mut phases = phases.into_iter().collect::<Vec<_>>();
+        phases.sort_by_key(|(phase, _)| *phase);
+        for (phase, counts) in phases {
+            let _ = write!(table, "{:<16}", phase.name());
+            for count in counts {
+                let _ = write!(table, " {count:>12}");
+            }
+            table.push('\n');
+        }
+        let _ = write!(table, "{:<16}", "total");
+        for counter in Counter::ALL {
+            let _ = write!(table, " {:>12}", self.counter(counter));
+        }
+        table.push('\n');
+        table
+    }
+
+    /// The spans in the Chrome trace event format (can be viewed with `chrome://tracing` or Perfetto).
+    pub fn chrome_trace(&self) -> String {
+        let spans = self.spans();
+        let mut threads = Vec::<ThreadId>::new();
+        let mut events = vec![];
+        for span in spans.iter() {
+            let tid = match threads.iter().position(|id| *id == span.thread) {
+                Some(idx) => idx + 1,
+                None => {
+                    threads.push(span.thread);
+                    threads.len()
+                }
+            };
+            let rss_delta = span
+                .rss_delta
+                .map_or("null".to_string(), |delta| delta.to_string());
+            let counts = span.counts.map_or(String::new(), |counts| {
+                Counter::ALL
+                    .iter()
+                    .zip(counts)
+                    .map(|(counter, count)| format!(",\"{}\":{count}", counter.key()))
+                    .collect()
+            });
+            events.push(format!(
+                "{{\"name\":{},\"cat\":\"erg\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":{tid},\"args\":{{\"module\":{},\"rss_delta\":{rss_delta}{counts}}}}}",
+                escape_json(span.phase.name()),
+                span.start.as_micros(),
+                span.duration.as_micros(),
+                escape_json(&span.module),
+            ));
+        }
+        format!(
+            "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n",
+            events.join(",\n")
+        )
+    }
+
+    /// Prints the table to stderr and writes the Chrome trace to `build/timings.json` (of the package, if any).
+    pub fn report(&self) {
+        eprintln!("{}", self.table());
+        let out_dir = package_root(".")
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("build");
+        let path = out_dir.join("timings.json");
+        match fs::create_dir_all(&out_dir).and_then(|_| fs::write(&path, self.chrome_trace())) {
+            Ok(()) => eprintln!("the trace is written to {}", path.display()),
+            Err(err) => eprintln!("cannot write {}: {err}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings() {
+        let timings = Timings::new();
+        assert_eq!(timings.measure(Phase::Parse, Path::new("a.er"), || 1), 1);
+        assert!(timings.spans().is_empty());
+        timings.enable();
+        timings.measure(Phase::Parse, Path::new("a.er"), || {});
+        timings.measure(Phase::Lower, Path::new("a.er"), || {});
+        timings.measure(Phase::Parse, Path::new("b.er"), || {});
+        let spans = timings.spans();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1].phase, Phase::Lower);
+        let table = timings.table();
+        assert!(table
+            .lines()
+            .any(|line| line.starts_with("parse") && line.contains(" 2 ")));
+        assert!(table.contains("b.er"));
+        let trace = timings.chrome_trace();
+        assert_eq!(trace.matches("\"ph\":\"X\"").count(), 3);
+        assert!(trace.contains("\"name\":\"lower\""));
+        assert!(!table.contains("free vars"));
+    }
+
+    #[test]
+    fn test_memory_stats() {
+        let timings = Timings::new();
+        timings.count(Counter::FreeVars);
+        assert_eq!(timings.counter(Counter::FreeVars), 0);
+        timings.enable_memory_stats();
+        assert!(timings.is_enabled());
+        timings.measure(Phase::Lower, Path::new("a.er"), || {
+            timings.count(Counter::FreeVars);
+            timings.count(Counter::HirNodes);
+            timings.count(Counter::HirNodes);
+        });
+        timings.count(Counter::TypeClones);
+        let spans = timings.spans();
+        assert_eq!(spans[0].counts, Some([1, 0, 2]));
+        let table = timings.table();
+        let lower = table
+            .lines()
+            .rev()
+            .find(|line| line.starts_with("lower"))
+            .unwrap();
+        assert_eq!(
+            lower.split_whitespace().collect::<Vec<_>>(),
+            ["lower", "1", "0", "2"]
+        );
+        assert!(table
+            .lines()
+            .any(|line| line.split_whitespace().eq(["total", "1", "1", "2"])));
+        assert!(timings.chrome_trace().contains("\"hir_nodes\":2"));
+    }
+}
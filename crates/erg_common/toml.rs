@@ -0,0 +1,351 @@
+//! A minimal TOML parser for the project configuration file (`erg.toml`).
+//!
+//! Only the subset used by `erg.toml` is supported:
+//! `[section]` headers, `key = value` pairs and `#` comments,
+//! where a value is a string (`"..."`, `'...'`), an integer, a boolean or an array of them (which may span multiple lines).
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<TomlValue>),
+}
+
+impl fmt::Display for TomlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Str(s) => write!(f, "{s:?}"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Array(arr) => {
+                write!(f, "[")?;
+                for (i, v) in arr.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+impl TomlValue {
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Str(_) => "string",
+            Self::Int(_) => "integer",
+            Self::Bool(_) => "boolean",
+            Self::Array(_) => "array",
+        }
+    }
+}
+
+/// `key = value` in `[section]` (`section` is empty for the top-level keys)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlEntry {
+    pub section: String,
+    pub key: String,
+    pub value: TomlValue,
+    /// 1-origin
+    pub line: usize,
+}
+
+impl TomlEntry {
+    /// `section.key` (or `key` for the top-level keys)
+    pub fn full_key(&self) -> String {
+        if self.section.is_empty() {
+            self.key.clone()
+        } else {
+            format!("{}.{}", self.section, self.key)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlError {
+    /// 1-origin
+    pub line: usize,
+    pub msg: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+impl TomlError {
+    fn new(line: usize, msg: impl Into<String>) -> Self {
+        Self {
+            line,
+            msg: msg.into(),
+        }
+    }
+}
+
+/// Removes the comment (outside of strings) from the line.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+fn is_bare_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Whether the brackets of the (comment-stripped) value are balanced.
+fn is_complete(value: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut escaped = false;
+    for c in value.chars() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '[' => depth += 1,
+            None if c == ']' => depth -= 1,
+            None => {}
+        }
+    }
+    depth <= 0
+}
+
+struct ValueParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl ValueParser<'_> {
+    fn skip_ws(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn error(&self, msg: impl Into<String>) -> TomlError {
+        TomlError::new(self.line, msg)
+    }
+
+    fn parse_value(&mut self) -> Result<TomlValue, TomlError> {
+        self.skip_ws();
+        match self.chars.peek().copied() {
+            Some('"') => {
+                self.chars.next();
+                self.parse_basic_str()
+            }
+            Some('\'') => {
+                self.chars.next();
+                let mut s = String::new();
+                for c in self.chars.by_ref() {
+                    if c == '\'' {
+                        return Ok(TomlValue::Str(s));
+                    }
+                    s.push(c);
+                }
+                Err(self.error("unterminated string"))
+            }
+            Some('[') => {
+                self.chars.next();
+                let mut arr = vec![];
+                loop {
+                    self.skip_ws();
+                    if self.chars.next_if_eq(&']').is_some() {
+                        return Ok(TomlValue::Array(arr));
+                    }
+                    arr.push(self.parse_value()?);
+                    self.skip_ws();
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(TomlValue::Array(arr)),
+                        _ => return Err(self.error("expected `,` or `]` in the array")),
+                    }
+                }
+            }
+            Some(_) => {
+                let mut token = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| !c.is_whitespace() && *c != ',' && *c != ']')
+                {
+                    token.push(c);
+                }
+                match &token[..] {
+                    "true" => Ok(TomlValue::Bool(true)),
+                    "false" => Ok(TomlValue::Bool(false)),
+                    _ => token
+                        .replace('_', "")
+                        .parse::<i64>()
+                        .map(TomlValue::Int)
+                        .map_err(|_| self.error(format!("invalid value: `{token}`"))),
+                }
+            }
+            None => Err(self.error("the value is missing")),
+        }
+    }
+
+    fn parse_basic_str(&mut self) -> Result<TomlValue, TomlError> {
+        let mut s = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(TomlValue::Str(s)),
+                '\\' => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some(other) => {
+                        return Err(self.error(format!("unknown escape sequence: `\\{other}`")))
+                    }
+                    None => break,
+                },
+                c => s.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+}
+
+/// Parses the TOML source and returns the entries in the order of appearance.
+///
+/// ```
+/// # use erg_common::toml::{parse, TomlValue};
+/// let entries = parse("opt_level = 2\n[lint]\nallow = [\"unused\"]").unwrap();
+/// assert_eq!(entries[0].full_key(), "opt_level");
+/// assert_eq!(entries[1].full_key(), "lint.allow");
+/// assert_eq!(entries[1].value, TomlValue::Array(vec![TomlValue::Str("unused".into())]));
+/// ```
+pub fn parse(src: &str) -> Result<Vec<TomlEntry>, TomlError> {
+    let mut entries: Vec<TomlEntry> = vec![];
+    let mut section = String::new();
+    let mut lines = src.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let lineno = i + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let Some(name) = header.strip_suffix(']') else {
+                return Err(TomlError::new(lineno, "expected `]`"));
+            };
+            let name = name.trim();
+            if !name.split('.').all(|key| is_bare_key(key.trim())) {
+                return Err(TomlError::new(
+                    lineno,
+                    format!("invalid section name: `{name}`"),
+                ));
+            }
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(TomlError::new(lineno, "expected `key = value`"));
+        };
+        let key = key.trim();
+        if !is_bare_key(key) {
+            return Err(TomlError::new(lineno, format!("invalid key: `{key}`")));
+        }
+        // arrays may span multiple lines
+        let mut value = value.trim().to_string();
+        while !is_complete(&value) {
+            let Some((_, next)) = lines.next() else {
+                return Err(TomlError::new(lineno, "unterminated array"));
+            };
+            value.push(' ');
+            value.push_str(strip_comment(next).trim());
+        }
+        let mut parser = ValueParser {
+            chars: value.chars().peekable(),
+            line: lineno,
+        };
+        let parsed = parser.parse_value()?;
+        parser.skip_ws();
+        if let Some(c) = parser.chars.next() {
+            return Err(TomlError::new(
+                lineno,
+                format!("unexpected character after the value: `{c}`"),
+            ));
+        }
+        if entries
+            .iter()
+            .any(|entry| entry.section == section && entry.key == key)
+        {
+            return Err(TomlError::new(lineno, format!("duplicate key: `{key}`")));
+        }
+        entries.push(TomlEntry {
+            section: section.clone(),
+            key: key.to_string(),
+            value: parsed,
+            line: lineno,
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml() {
+        let src = r#"
+# comment
+target_version = "3.11" # trailing comment
+opt_level = 2
+language = 'ja'
+
+[lint]
+allow = [
+    "unused-variable",  # comment in an array
+    "too-many-params",
+]
+"#;
+        let entries = parse(src).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].value, TomlValue::Str("3.11".into()));
+        assert_eq!(entries[1].value, TomlValue::Int(2));
+        assert_eq!(entries[2].value, TomlValue::Str("ja".into()));
+        assert_eq!(entries[3].full_key(), "lint.allow");
+        assert_eq!(entries[3].line, 8);
+        assert_eq!(
+            entries[3].value,
+            TomlValue::Array(vec![
+                TomlValue::Str("unused-variable".into()),
+                TomlValue::Str("too-many-params".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_toml_error() {
+        assert_eq!(parse("a = ").unwrap_err().line, 1);
+        assert_eq!(parse("\na = \"b").unwrap_err().line, 2);
+        assert_eq!(parse("a = 1\na = 2").unwrap_err().line, 2);
+        assert!(parse("[lint\n").is_err());
+        assert!(parse("a = [1, 2").is_err());
+        assert!(parse("a = 1 2").is_err());
+    }
+}
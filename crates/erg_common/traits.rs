@@ -4,15 +4,23 @@
 use std::collections::vec_deque;
 use std::collections::VecDeque;
 use std::env::consts::{ARCH, OS};
+use std::fs;
 use std::io::{stdout, BufWriter, Write};
 use std::mem;
+use std::path::PathBuf;
 use std::process;
 use std::slice::{Iter, IterMut};
+use std::time::SystemTime;
 
+use crate::cancel::CancellationToken;
 use crate::config::ErgConfig;
 use crate::consts::{BUILD_DATE, GIT_HASH_SHORT, SEMVER};
 use crate::error::{ErrorDisplay, ErrorKind, Location, MultiErrorDisplay};
+use crate::exit_code;
 use crate::io::{Input, InputKind};
+use crate::pathutil::erg_files;
+use crate::stdin::GLOBAL_STDIN;
+use crate::watch::{status_line, FileWatcher, CLEAR_SCREEN};
 use crate::{addr_eq, chomp, log, switch_unreachable};
 
 pub trait DequeStream<T>: Sized {
@@ -622,6 +630,73 @@ impl VirtualMachine {
     }
 }
 
+/// A file loaded into the REPL session by `:load`.
+#[derive(Debug, Clone)]
+pub struct LoadedFile {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    /// The names defined by the file (removed before reloading)
+    pub names: Vec<String>,
+}
+
+impl LoadedFile {
+    pub const fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            modified: None,
+            names: vec![],
+        }
+    }
+
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.modified.is_none() || self.last_modified() != self.modified
+    }
+}
+
+/// Evaluates `file` in the REPL session. The definitions of the previous load are removed.
+/// Returns the number of errors.
+fn load_file<R: Runnable>(
+    instance: &mut R,
+    file: &mut LoadedFile,
+    output: &mut impl Write,
+) -> usize {
+    let src = match fs::read_to_string(&file.path) {
+        Ok(src) => src,
+        Err(err) => {
+            writeln!(output, "cannot load {}: {err}", file.path.display()).unwrap();
+            return 1;
+        }
+    };
+    file.modified = file.last_modified();
+    instance.remove_defs(&file.names);
+    let before = instance.module_names();
+    let res = instance.eval(src);
+    file.names = instance
+        .module_names()
+        .into_iter()
+        .filter(|name| !before.contains(name))
+        .collect();
+    let num_errors = match res {
+        Ok(_) => {
+            writeln!(output, "loaded: {}", file.path.display()).unwrap();
+            0
+        }
+        Err(errs) => {
+            errs.write_all_stderr();
+            errs.len()
+        }
+    };
+    instance.input().set_block_begin();
+    instance.clear();
+    num_errors
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ExitStatus {
     pub code: i32,
@@ -665,6 +740,20 @@ pub trait Runnable: Sized + Default {
     fn clear(&mut self);
     fn eval(&mut self, src: String) -> Result<String, Self::Errs>;
     fn exec(&mut self) -> Result<ExitStatus, Self::Errs>;
+    /// Returns the names that complete the last word of `code` (used for Tab completion in the REPL).
+    fn complete(&self, _code: &str) -> Vec<String> {
+        vec![]
+    }
+    /// Executes a REPL command (e.g. `:type expr`). Returns `None` if the command is not supported.
+    fn exec_command(&mut self, _command: &str) -> Option<Result<String, Self::Errs>> {
+        None
+    }
+    /// Returns the names defined in the session (used by `:load` and `:reload`).
+    fn module_names(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Removes the definitions of `names` from the session so that they can be redefined.
+    fn remove_defs(&mut self, _names: &[String]) {}
     fn expect_block(&self, src: &str) -> BlockKind {
         let multi_line_str = "\"\"\"";
         if src.contains(multi_line_str) && src.rfind(multi_line_str) == src.find(multi_line_str) {
@@ -744,7 +833,9 @@ pub trait Runnable: Sized + Default {
         let mut num_errors = 0;
         let mut instance = Self::new(cfg);
         let res = match &instance.input().kind {
-            InputKind::File(_) | InputKind::Pipe(_) | InputKind::Str(_) => instance.exec(),
+            InputKind::File(_) | InputKind::Pipe(_) | InputKind::Str(_) | InputKind::Buffer(..) => {
+                instance.exec()
+            }
             InputKind::REPL | InputKind::DummyREPL(_) => {
                 let output = stdout();
                 let mut output = BufWriter::new(output.lock());
@@ -756,6 +847,7 @@ pub trait Runnable: Sized + Default {
                 }
                 output.flush().unwrap();
                 let mut vm = VirtualMachine::new();
+                let mut loaded = Vec::<LoadedFile>::new();
                 loop {
                     let indent = vm.indent();
                     if vm.now_block.len() > 1 {
@@ -767,8 +859,22 @@ pub trait Runnable: Sized + Default {
                         output.flush().unwrap();
                     }
                     instance.cfg().input.set_indent(vm.length);
-                    let line = chomp(&instance.cfg_mut().input.read());
+                    let line = match instance.input().kind {
+                        InputKind::REPL => GLOBAL_STDIN.read_with(&|code| instance.complete(code)),
+                        _ => instance.cfg_mut().input.read(),
+                    };
+                    let line = chomp(&line);
                     let line = line.trim_end();
+                    // the user closed some blocks (by backspace at the beginning of the line)
+                    let indent = match instance.input().take_dedent() {
+                        0 => indent,
+                        dedent => {
+                            for _ in 0..dedent.min(vm.now_block.len() - 1) {
+                                vm.remove_block_kind();
+                            }
+                            vm.indent()
+                        }
+                    };
                     match line {
                         ":quit" | ":exit" => {
                             instance.quit_successfully(output);
@@ -780,6 +886,57 @@ pub trait Runnable: Sized + Default {
                             instance.clear();
                             continue;
                         }
+                        command if command.starts_with(":load ") && vm.now_block.len() == 1 => {
+                            let path = PathBuf::from(command.trim_start_matches(":load ").trim());
+                            let mut file = match loaded.iter().position(|file| file.path == path) {
+                                Some(idx) => loaded.remove(idx),
+                                None => LoadedFile::new(path),
+                            };
+                            num_errors += load_file(&mut instance, &mut file, &mut output);
+                            output.flush().unwrap();
+                            // the file was read
+                            if file.modified.is_some() {
+                                loaded.push(file);
+                            }
+                            continue;
+                        }
+                        ":reload" if vm.now_block.len() == 1 => {
+                            let mut changed = false;
+                            for file in loaded.iter_mut().filter(|file| file.is_changed()) {
+                                changed = true;
+                                num_errors += load_file(&mut instance, file, &mut output);
+                            }
+                            if !changed {
+                                writeln!(output, "no loaded files have been changed").unwrap();
+                            }
+                            output.flush().unwrap();
+                            continue;
+                        }
+                        command if command.starts_with(':') && vm.now_block.len() == 1 => {
+                            match instance.exec_command(command) {
+                                Some(Ok(out)) => {
+                                    output.write_all((out + "\n").as_bytes()).unwrap();
+                                    output.flush().unwrap();
+                                }
+                                Some(Err(errs)) => {
+                                    num_errors += errs.len();
+                                    errs.write_all_stderr();
+                                }
+                                None => {
+                                    let commands = ":quit, :clear, :type <expr>, :info <name>, :load <file>, :reload, :browse <module>";
+                                    output
+                                        .write_all(
+                                            format!("unknown command: {command} (available: {commands})\n")
+                                                .as_bytes(),
+                                        )
+                                        .unwrap();
+                                    output.flush().unwrap();
+                                }
+                            }
+                            instance.input().set_block_begin();
+                            instance.clear();
+                            continue;
+                        }
                         "" => {
                             // eval after the end of the block
                             if vm.now_block.len() == 2 {
@@ -944,8 +1101,44 @@ pub trait Runnable: Sized + Default {
             Err(errs) => {
                 num_errors += errs.len();
                 errs.write_all_stderr();
-                ExitStatus::new(1, 0, num_errors)
+                let code = exit_code::of_errors(errs.iter().map(|err| err.core()));
+                ExitStatus::new(code, 0, num_errors)
+            }
+        }
+    }
+
+    /// Runs the input file, and reruns it whenever the Erg files in the package
+    /// (or the directory of the file) are changed. Never returns unless the input is not a file.
+    fn watch(cfg: ErgConfig) -> ExitStatus {
+        if !matches!(cfg.input.kind, InputKind::File(_)) {
+            return Self::run(cfg);
+        }
+        let root = cfg.input.project_root().unwrap_or_else(|| cfg.input.dir());
+        let mut watcher = FileWatcher::default();
+        watcher.set_paths(erg_files(&root));
+        loop {
+            print!("{CLEAR_SCREEN}");
+            stdout().flush().unwrap();
+            // an edit during the compilation cancels it (and the compilation is restarted)
+            let mut cfg = cfg.copy();
+            cfg.cancel = CancellationToken::new();
+            let cancel = cfg.cancel.clone();
+            let guard = watcher.cancel_on_change(&cancel);
+            let status = {
+                let mut instance = Self::new(cfg);
+                match instance.exec() {
+                    Ok(status) => status,
+                    Err(errs) => {
+                        errs.write_all_stderr();
+                        ExitStatus::new(1, 0, errs.len())
+                    }
+                }
+            };
+            drop(guard);
+            if !cancel.is_cancelled() {
+                println!("{}", status_line(1, status.num_errors, status.num_warns));
             }
+            watcher.wait(|| erg_files(&root));
         }
     }
 }
@@ -1038,17 +1231,7 @@ macro_rules! impl_locational {
             fn loc(&self) -> Location {
                 let begin_loc = self.$begin.loc();
                 let end_loc = self.$end.loc();
-                match (
-                    begin_loc.ln_begin(),
-                    begin_loc.col_begin(),
-                    end_loc.ln_end(),
-                    end_loc.col_end(),
-                ) {
-                    (Some(lb), Some(cb), Some(le), Some(ce)) => Location::range(lb, cb, le, ce),
-                    (Some(lb), _, Some(le), _) => Location::LineRange(lb, le),
-                    (Some(l), _, _, _) | (_, _, Some(l), _) => Location::Line(l),
-                    _ => Location::Unknown,
-                }
+                Location::concat(&begin_loc, &end_loc)
             }
             fn ln_begin(&self) -> Option<u32> {
                 self.$begin.ln_begin()
@@ -1072,17 +1255,7 @@ macro_rules! impl_locational {
                 if begin_loc.is_unknown() {
                     return end_loc;
                 }
-                match (
-                    begin_loc.ln_begin(),
-                    begin_loc.col_begin(),
-                    end_loc.ln_end(),
-                    end_loc.col_end(),
-                ) {
-                    (Some(lb), Some(cb), Some(le), Some(ce)) => Location::range(lb, cb, le, ce),
-                    (Some(lb), _, Some(le), _) => Location::LineRange(lb, le),
-                    (Some(l), _, _, _) | (_, _, Some(l), _) => Location::Line(l),
-                    _ => Location::Unknown,
-                }
+                Location::concat(&begin_loc, &end_loc)
             }
         }
     };
@@ -1094,17 +1267,7 @@ macro_rules! impl_locational {
                 if begin_loc.is_unknown() && end_loc.is_unknown() {
                     return self.$middle.loc();
                 }
-                match (
-                    begin_loc.ln_begin(),
-                    begin_loc.col_begin(),
-                    end_loc.ln_end(),
-                    end_loc.col_end(),
-                ) {
-                    (Some(lb), Some(cb), Some(le), Some(ce)) => Location::range(lb, cb, le, ce),
-                    (Some(lb), _, Some(le), _) => Location::LineRange(lb, le),
-                    (Some(l), _, _, _) | (_, _, Some(l), _) => Location::Line(l),
-                    _ => Location::Unknown,
-                }
+                Location::concat(&begin_loc, &end_loc)
             }
             fn ln_begin(&self) -> Option<u32> {
                 self.$begin.ln_begin()
@@ -0,0 +1,177 @@
+//! A polling file watcher used by `--watch`.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, sleep};
+use std::time::{Duration, SystemTime};
+
+use crate::cancel::CancellationToken;
+use crate::dict::Dict;
+use crate::style::{colors::GREEN, colors::RED, colors::YELLOW, RESET};
+
+/// Clears the screen and moves the cursor to the top left.
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[1;1H";
+
+/// Watches the modification times of files.
+/// Files can be added, changed or removed.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    /// `None` if the file does not exist
+    mtimes: Dict<PathBuf, Option<SystemTime>>,
+    interval: Duration,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(300))
+    }
+}
+
+impl FileWatcher {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            mtimes: Dict::new(),
+            interval,
+        }
+    }
+
+    fn modified(path: &PathBuf) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
+
+    /// Replaces the watched files. The modification times of the files already watched are kept.
+    pub fn set_paths(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        let mut mtimes = Dict::new();
+        for path in paths {
+            let mtime = self
+                .mtimes
+                .get(&path)
+                .cloned()
+                .unwrap_or_else(|| Self::modified(&path));
+            mtimes.insert(path, mtime);
+        }
+        self.mtimes = mtimes;
+    }
+
+    /// Returns the files changed since the last call (and updates the modification times).
+    pub fn changed_files(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, mtime) in self.mtimes.iter_mut() {
+            let new = Self::modified(path);
+            if new != *mtime {
+                *mtime = new;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Cancels `token` when some files are changed, until the returned guard is dropped.
+    /// `self` is not updated, so the next `wait` returns the changed files.
+    pub fn cancel_on_change(&self, token: &CancellationToken) -> CancelOnChange {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let mut watcher = self.clone();
+        let token = token.clone();
+        let _stopped = stopped.clone();
+        thread::spawn(move || {
+            while !_stopped.load(Ordering::Relaxed) && !token.is_cancelled() {
+                sleep(watcher.interval);
+                if !watcher.changed_files().is_empty() {
+                    token.cancel();
+                }
+            }
+        });
+        CancelOnChange { stopped }
+    }
+
+    /// Blocks until some files are changed.
+    /// `rescan` is called periodically to find the files to be watched (e.g. newly created files).
+    pub fn wait(&mut self, mut rescan: impl FnMut() -> Vec<PathBuf>) -> Vec<PathBuf> {
+        loop {
+            sleep(self.interval);
+            let known = self.mtimes.keys().cloned().collect::<Vec<_>>();
+            let new_files = rescan()
+                .into_iter()
+                .filter(|path| !known.contains(path))
+                .collect::<Vec<_>>();
+            let mut changed = self.changed_files();
+            for path in new_files {
+                self.mtimes.insert(path.clone(), Self::modified(&path));
+                changed.push(path);
+            }
+            if !changed.is_empty() {
+                return changed;
+            }
+        }
+    }
+}
+
+/// Stops watching for the cancellation when dropped (see `FileWatcher::cancel_on_change`).
+#[derive(Debug)]
+pub struct CancelOnChange {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for CancelOnChange {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// e.g. `[12:34:56] 1 error(s), 0 warning(s) in 3 file(s). Watching for changes...`
+pub fn status_line(num_files: usize, num_errors: usize, num_warns: usize) -> String {
+    let color = if num_errors > 0 {
+        RED
+    } else if num_warns > 0 {
+        YELLOW
+    } else {
+        GREEN
+    };
+    let time = crate::datetime::now();
+    let time = time.rsplit(' ').next().unwrap_or_default();
+    format!(
+        "[{time}] {color}{num_errors} error(s), {num_warns} warning(s){RESET} in {num_files} file(s). Watching for changes... (Ctrl+C to quit)"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_watcher() {
+        let path = std::env::temp_dir().join(format!("erg_watch_test_{}.er", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut watcher = FileWatcher::new(Duration::from_millis(10));
+        watcher.set_paths([path.clone()]);
+        assert!(watcher.changed_files().is_empty());
+        fs::write(&path, "x = 1").unwrap();
+        assert_eq!(watcher.changed_files(), vec![path.clone()]);
+        assert!(watcher.changed_files().is_empty());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(watcher.changed_files(), vec![path]);
+    }
+
+    #[test]
+    fn test_cancel_on_change() {
+        let path = std::env::temp_dir().join(format!("erg_cancel_test_{}.er", std::process::id()));
+        fs::write(&path, "x = 1").unwrap();
+        let mut watcher = FileWatcher::new(Duration::from_millis(10));
+        watcher.set_paths([path.clone()]);
+        let token = CancellationToken::new();
+        let guard = watcher.cancel_on_change(&token);
+        sleep(Duration::from_millis(50));
+        assert!(!token.is_cancelled());
+        fs::remove_file(&path).unwrap();
+        let mut waited = 0;
+        while !token.is_cancelled() && waited < 100 {
+            sleep(Duration::from_millis(10));
+            waited += 1;
+        }
+        assert!(token.is_cancelled());
+        drop(guard);
+        // the change is still reported by the original watcher
+        assert_eq!(watcher.changed_files(), vec![path]);
+    }
+}
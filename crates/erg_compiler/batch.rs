@@ -0,0 +1,159 @@
+//! Compiles multiple files in one invocation (`erg compile a.er b.er src/`).
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::error::ErrorDisplay;
+use erg_common::exit_code;
+use erg_common::pathutil::NormalizedPathBuf;
+use erg_common::traits::{ExitStatus, Stream};
+
+use crate::artifact::Buildable;
+use crate::check::{CheckResult, PackageChecker};
+use crate::module::SharedCompilerResource;
+use crate::ty::codeobj::CodeObj;
+use crate::writer::ArtifactWriter;
+use crate::Compiler;
+
+/// The result of a compiled file.
+/// The diagnostics of the imported modules are included in the result of the first file importing them.
+#[derive(Debug)]
+pub struct CompileResult {
+    pub check: CheckResult,
+    /// `None` if any errors are reported (in any of the files)
+    pub code: Option<CodeObj>,
+}
+
+impl CompileResult {
+    pub fn path(&self) -> &Path {
+        &self.check.path
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.check.is_ok()
+    }
+}
+
+/// Compiles multiple files sharing the module cache.
+/// All the files are checked first (so each imported module, including the files imported by the others, is checked only once),
+/// and then the code of each file is generated from the cached HIR.
+#[derive(Debug)]
+pub struct BatchCompiler {
+    checker: PackageChecker,
+}
+
+impl BatchCompiler {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            checker: PackageChecker::new(cfg),
+        }
+    }
+
+    pub fn inherit(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        Self {
+            checker: PackageChecker::inherit(cfg, shared),
+        }
+    }
+
+    pub fn cfg(&self) -> &ErgConfig {
+        self.checker.cfg()
+    }
+
+    pub fn shared(&self) -> &SharedCompilerResource {
+        self.checker.shared()
+    }
+
+    /// Expands `cfg.targets` (files, directories and glob patterns) into the files to be compiled.
+    /// The declaration files (`.d.er`) are not compiled.
+    pub fn collect_targets(&self) -> Vec<PathBuf> {
+        self.checker
+            .collect_targets()
+            .into_iter()
+            .filter(|path| !path.to_string_lossy().ends_with(".d.er"))
+            .collect()
+    }
+
+    /// No code is generated if any errors are reported, because the files may link the erroneous modules.
+    pub fn compile_files<P: AsRef<Path>>(&mut self, paths: &[P]) -> Vec<CompileResult> {
+        let checks = self.checker.check_files(paths);
+        let generates = checks.iter().all(CheckResult::is_ok);
+        checks
+            .into_iter()
+            .map(|check| {
+                if generates {
+                    self.generate(check)
+                } else {
+                    CompileResult { check, code: None }
+                }
+            })
+            .collect()
+    }
+
+    fn generate(&self, mut check: CheckResult) -> CompileResult {
+        let path = NormalizedPathBuf::from(check.path.as_path());
+        let hir = self
+            .shared()
+            .mod_cache
+            .get(&path)
+            .and_then(|entry| entry.hir.clone());
+        let Some(hir) = hir else {
+            return CompileResult { check, code: None };
+        };
+        let cfg = self.cfg().inherit(check.path.clone());
+        let shared = self.shared().inherit(check.path.clone());
+        let mut compiler = Compiler::inherit(cfg, shared);
+        match compiler.compile_hir(hir) {
+            Ok(code) => CompileResult {
+                check,
+                code: Some(code),
+            },
+            Err(artifact) => {
+                check.errors.extend(artifact.errors);
+                CompileResult { check, code: None }
+            }
+        }
+    }
+
+    /// Compiles the targets into `.pyc` files, and prints the diagnostics.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        if cfg.with_py {
+            eprintln!("`--with-py` can only be used to compile a single file");
+            return ExitStatus::ERR1;
+        }
+        let mut compiler = Self::new(cfg);
+        let targets = compiler.collect_targets();
+        if targets.is_empty() {
+            eprintln!("no files to compile");
+            return ExitStatus::ERR1;
+        }
+        let results = compiler.compile_files(&targets);
+        let checks = results
+            .iter()
+            .map(|result| &result.check)
+            .collect::<Vec<_>>();
+        let (mut num_errors, num_warns) = compiler.checker.report(&checks);
+        let mut code = exit_code::of_errors(
+            results
+                .iter()
+                .flat_map(|result| result.check.errors.iter().map(|err| err.core())),
+        );
+        for result in results {
+            let Some(codeobj) = result.code else {
+                continue;
+            };
+            let cfg = compiler.cfg().inherit(result.check.path);
+            let pyc = codeobj.into_pyc_bytes(cfg.py_magic_num);
+            if let Err(err) = ArtifactWriter::new(&cfg).write(&cfg.dump_pyc_path(), &pyc) {
+                err.write_to_stderr();
+                num_errors += 1;
+                code = ExitStatus::ERR1.code;
+            }
+        }
+        if compiler.cfg().verbose > 0 && !compiler.cfg().quiet && !compiler.cfg().json_output {
+            println!(
+                "compiled {} file(s): {num_errors} error(s), {num_warns} warning(s)",
+                targets.len()
+            );
+        }
+        ExitStatus::new(code, num_warns, num_errors)
+    }
+}
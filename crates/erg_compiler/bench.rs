@@ -0,0 +1,321 @@
+//! Runs the benchmarks of a package (`erg bench`).
+//!
+//! Benchmarks are the parameterless subroutines marked with `@Bench`.
+//! Unlike `erg test`, the files are compiled with the given optimization level (`-o`),
+//! so the effect of the optimizations can be compared.
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use erg_common::config::ErgConfig;
+use erg_common::pathutil::{erg_files, expand_targets, package_root};
+use erg_common::style::{colors::GREEN, colors::RED, RESET};
+use erg_common::traits::ExitStatus;
+
+use crate::hir::HIR;
+use crate::test_runner::{
+    collect_entry_points, compile_file, has_comptime_deco, run_harness, TestCase,
+};
+
+const BENCH_HARNESS: &str = r#"
+import statistics
+
+for case in CASES:
+    bench = module[case]
+    try:
+        with contextlib.redirect_stdout(io.StringIO()):
+            # warm up, and estimate the time of an iteration
+            iters = 0
+            start = time.perf_counter()
+            while True:
+                bench()
+                iters += 1
+                elapsed = time.perf_counter() - start
+                if elapsed >= WARMUP_TIME:
+                    break
+            iters_per_sample = max(1, int(MEASUREMENT_TIME / SAMPLES / (elapsed / iters)))
+            samples = []
+            start = time.perf_counter()
+            # slow benchmarks are stopped early (but at least 2 samples are taken)
+            while len(samples) < SAMPLES and (
+                len(samples) < 2 or time.perf_counter() - start < MEASUREMENT_TIME * 2
+            ):
+                sample_start = time.perf_counter()
+                for _ in range(iters_per_sample):
+                    bench()
+                samples.append((time.perf_counter() - sample_start) / iters_per_sample)
+        stats = [
+            statistics.mean(samples),
+            statistics.median(samples),
+            statistics.stdev(samples),
+            min(samples),
+            max(samples),
+        ]
+        results.append(["ok", case, str(iters_per_sample), str(len(samples))] + list(map(repr, stats)))
+    except (Exception, SystemExit) as err:
+        results.append(["failed", case, describe(err)])
+"#;
+
+/// The time is in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchOptions {
+    pub warmup_time: f64,
+    pub measurement_time: f64,
+    /// the (maximum) number of samples
+    pub samples: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            warmup_time: 0.5,
+            measurement_time: 2.0,
+            samples: 20,
+        }
+    }
+}
+
+/// The statistics of the time per iteration (in seconds).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchStats {
+    pub iters_per_sample: usize,
+    pub samples: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub case: TestCase,
+    /// `Err`: the error raised by the benchmark
+    pub outcome: Result<BenchStats, String>,
+}
+
+/// The results of the benchmarks in a file.
+#[derive(Debug)]
+pub struct FileBenchResult {
+    pub path: PathBuf,
+    pub results: Vec<BenchResult>,
+    /// the number of benchmarks excluded by `--filter`
+    pub filtered_out: usize,
+    /// compile errors, or an error raised while executing the top-level code of the module
+    pub error: Option<String>,
+}
+
+/// Returns the benchmarks defined at the top level of the module.
+pub fn collect_benches(hir: &HIR) -> Vec<TestCase> {
+    collect_entry_points(hir, |ident| has_comptime_deco(ident, "Bench"))
+}
+
+/// e.g. `1.23 µs`
+pub fn format_duration(secs: f64) -> String {
+    if secs >= 1.0 {
+        format!("{secs:.2} s")
+    } else if secs >= 1e-3 {
+        format!("{:.2} ms", secs * 1e3)
+    } else if secs >= 1e-6 {
+        format!("{:.2} µs", secs * 1e6)
+    } else {
+        format!("{:.2} ns", secs * 1e9)
+    }
+}
+
+#[derive(Debug)]
+pub struct BenchRunner {
+    cfg: ErgConfig,
+    options: BenchOptions,
+}
+
+impl BenchRunner {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self::with_options(cfg, BenchOptions::default())
+    }
+
+    pub const fn with_options(cfg: ErgConfig, options: BenchOptions) -> Self {
+        Self { cfg, options }
+    }
+
+    /// Runs the benchmarks in the file (that match `--filter`).
+    pub fn bench_file(&self, path: &Path) -> FileBenchResult {
+        let mut result = FileBenchResult {
+            path: path.to_path_buf(),
+            results: vec![],
+            filtered_out: 0,
+            error: None,
+        };
+        let (code, hir) = match compile_file(&self.cfg, path) {
+            Ok(compiled) => compiled,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        let (benches, filtered_out): (Vec<_>, Vec<_>) = collect_benches(&hir)
+            .into_iter()
+            .partition(|case| case.matches(self.cfg.test_filter));
+        result.filtered_out = filtered_out.len();
+        if benches.is_empty() {
+            return result;
+        }
+        let constants = [
+            ("WARMUP_TIME", self.options.warmup_time.to_string()),
+            (
+                "MEASUREMENT_TIME",
+                self.options.measurement_time.to_string(),
+            ),
+            ("SAMPLES", self.options.samples.max(2).to_string()),
+        ];
+        let rows = match run_harness(&self.cfg, path, code, &benches, &constants, BENCH_HARNESS) {
+            Ok(rows) => rows,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        for row in rows.iter() {
+            let Some(case) = row
+                .get(1)
+                .and_then(|py_name| benches.iter().find(|case| &case.py_name[..] == py_name))
+            else {
+                continue;
+            };
+            let outcome = match &row[..] {
+                [_, _, message] => Err(message.clone()),
+                [_, _, iters, samples, stats @ ..] if stats.len() == 5 => {
+                    let stats = stats
+                        .iter()
+                        .map(|stat| stat.parse().unwrap_or(0.0))
+                        .collect::<Vec<_>>();
+                    Ok(BenchStats {
+                        iters_per_sample: iters.parse().unwrap_or(0),
+                        samples: samples.parse().unwrap_or(0),
+                        mean: stats[0],
+                        median: stats[1],
+                        stddev: stats[2],
+                        min: stats[3],
+                        max: stats[4],
+                    })
+                }
+                _ => continue,
+            };
+            result.results.push(BenchResult {
+                case: case.clone(),
+                outcome,
+            });
+        }
+        result
+    }
+
+    /// The benchmark targets. If no files are given, the package (`src` and `benches`) is measured.
+    fn targets(&self) -> Vec<PathBuf> {
+        let mut targets = if !self.cfg.targets.is_empty() {
+            expand_targets(&self.cfg.targets)
+        } else {
+            let mut targets = expand_targets(&[]);
+            if let Some(root) = package_root(".") {
+                targets.extend(erg_files(root.join("benches")));
+            }
+            targets
+        };
+        // the declaration files of Python modules cannot be run
+        targets.retain(|path| !path.to_string_lossy().ends_with(".d.er"));
+        targets
+    }
+
+    fn report(&self, result: &FileBenchResult) -> Vec<String> {
+        let mut failures = vec![];
+        let path = result.path.display();
+        if let Some(error) = &result.error {
+            println!("{RED}error{RESET}: {path}: {}", error.trim_end());
+            failures.push(format!("{path}"));
+        }
+        if result.results.is_empty() {
+            return failures;
+        }
+        println!(
+            "\nrunning {} benchmark(s) in {path} (opt-level {})",
+            result.results.len(),
+            self.cfg.opt_level
+        );
+        let width = result
+            .results
+            .iter()
+            .map(|bench| bench.case.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for bench in result.results.iter() {
+            let name = &bench.case.name[..];
+            match &bench.outcome {
+                Ok(stats) => println!(
+                    "bench {name:width$} ... {GREEN}{:>10}/iter{RESET} (± {}) median {}, min {}, max {} [{} samples × {} iters]",
+                    format_duration(stats.mean),
+                    format_duration(stats.stddev),
+                    format_duration(stats.median),
+                    format_duration(stats.min),
+                    format_duration(stats.max),
+                    stats.samples,
+                    stats.iters_per_sample,
+                ),
+                Err(_) => println!("bench {name:width$} ... {RED}FAILED{RESET}"),
+            }
+        }
+        for bench in result.results.iter() {
+            let Err(message) = &bench.outcome else {
+                continue;
+            };
+            println!(
+                "\n---- {} ({path}:{}) ----",
+                bench.case.name, bench.case.line
+            );
+            print!("{message}");
+            failures.push(format!("{}:{}", path, bench.case.name));
+        }
+        failures
+    }
+
+    /// Runs the benchmarks of the targets (or the package) and reports the results.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let runner = Self::new(cfg);
+        let targets = runner.targets();
+        if targets.is_empty() {
+            eprintln!("no files to benchmark (not in a package, and no files are given)");
+            return ExitStatus::ERR1;
+        }
+        let start = Instant::now();
+        let (mut measured, mut filtered_out) = (0, 0);
+        let mut failures = vec![];
+        for path in targets.iter() {
+            let result = runner.bench_file(path);
+            measured += result
+                .results
+                .iter()
+                .filter(|bench| bench.outcome.is_ok())
+                .count();
+            filtered_out += result.filtered_out;
+            failures.extend(runner.report(&result));
+        }
+        if !failures.is_empty() {
+            println!("\nfailures:");
+            for failure in failures.iter() {
+                println!("    {failure}");
+            }
+        }
+        let status = if failures.is_empty() {
+            format!("{GREEN}ok{RESET}")
+        } else {
+            format!("{RED}FAILED{RESET}")
+        };
+        println!(
+            "\nbench result: {status}. {measured} measured; {} failed; {filtered_out} filtered out; finished in {:.2}s",
+            failures.len(),
+            start.elapsed().as_secs_f64()
+        );
+        if failures.is_empty() {
+            ExitStatus::OK
+        } else {
+            ExitStatus::new(1, 0, failures.len())
+        }
+    }
+}
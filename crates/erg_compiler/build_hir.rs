@@ -1,6 +1,7 @@
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
 use erg_common::error::MultiErrorDisplay;
+use erg_common::timing::{Phase, TIMINGS};
 use erg_common::traits::{ExitStatus, Runnable, Stream};
 use erg_common::Str;
 
@@ -90,6 +91,22 @@ impl Runnable for HIRBuilder {
         artifact.warns.write_all_stderr();
         Ok(artifact.object.to_string())
     }
+
+    fn complete(&self, code: &str) -> Vec<String> {
+        self.completion_candidates(code)
+    }
+
+    fn exec_command(&mut self, command: &str) -> Option<Result<String, Self::Errs>> {
+        self.lowerer.exec_repl_command(command)
+    }
+
+    fn module_names(&self) -> Vec<String> {
+        self.lowerer.module_names()
+    }
+
+    fn remove_defs(&mut self, names: &[String]) {
+        self.lowerer.remove_defs(names)
+    }
 }
 
 impl Buildable for HIRBuilder {
@@ -151,17 +168,24 @@ impl HIRBuilder {
 
     pub fn check(&mut self, ast: AST, mode: &str) -> Result<CompleteArtifact, IncompleteArtifact> {
         let mut artifact = self.lowerer.lower(ast, mode)?;
+        let path = self.cfg().input.path().to_path_buf();
         let effect_checker = SideEffectChecker::new(self.cfg().clone());
-        let hir = effect_checker
-            .check(artifact.object)
+        let hir = TIMINGS
+            .measure(Phase::EffectCheck, &path, || {
+                effect_checker.check(artifact.object)
+            })
+            .map_err(|(hir, errs)| {
+                self.lowerer.module.context.clear_invalid_vars();
+                IncompleteArtifact::new(Some(hir), errs, artifact.warns.take_all().into())
+            })?;
+        let hir = TIMINGS
+            .measure(Phase::OwnershipCheck, &path, || {
+                self.ownership_checker.check(hir)
+            })
             .map_err(|(hir, errs)| {
                 self.lowerer.module.context.clear_invalid_vars();
                 IncompleteArtifact::new(Some(hir), errs, artifact.warns.take_all().into())
             })?;
-        let hir = self.ownership_checker.check(hir).map_err(|(hir, errs)| {
-            self.lowerer.module.context.clear_invalid_vars();
-            IncompleteArtifact::new(Some(hir), errs, artifact.warns.take_all().into())
-        })?;
         Ok(CompleteArtifact::new(hir, artifact.warns))
     }
 
@@ -171,9 +195,29 @@ impl HIRBuilder {
         mode: &str,
     ) -> Result<CompleteArtifact, IncompleteArtifact> {
         let mut ast_builder = ASTBuilder::new(self.cfg().copy());
-        let artifact = ast_builder
-            .build(src)
-            .map_err(|iart| IncompleteArtifact::new(None, iart.errors.into(), iart.warns.into()))?;
+        let artifact = match ast_builder.build(src) {
+            Ok(artifact) => artifact,
+            Err(iart) => {
+                let mut errors = CompileErrors::from(iart.errors);
+                let Some(ast) = iart.ast else {
+                    return Err(IncompleteArtifact::new(None, errors, iart.warns.into()));
+                };
+                // The statements that failed to parse are replaced with `Dummy`s.
+                // The rest of the module is still checked to get the type information and the diagnostics.
+                self.lowerer.warns.extend(LowerWarnings::from(iart.warns));
+                return match self.check(ast, mode) {
+                    Ok(artifact) => Err(IncompleteArtifact::new(
+                        Some(artifact.object),
+                        errors,
+                        artifact.warns,
+                    )),
+                    Err(iart) => {
+                        errors.extend(iart.errors);
+                        Err(IncompleteArtifact::new(iart.object, errors, iart.warns))
+                    }
+                };
+            }
+        };
         self.lowerer
             .warns
             .extend(LowerWarnings::from(artifact.warns));
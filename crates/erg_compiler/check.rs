@@ -0,0 +1,362 @@
+//! Type-checks files and packages without generating code (`erg check`).
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::cancel::CancellationToken;
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::error::{ErrorDisplay, Location, MultiErrorDisplay};
+use erg_common::exit_code;
+use erg_common::pathutil::{expand_targets, NormalizedPathBuf};
+use erg_common::set::Set;
+use erg_common::style::remove_style;
+use erg_common::traits::{ExitStatus, Runnable, Stream};
+use erg_common::watch::{status_line, FileWatcher, CLEAR_SCREEN};
+use erg_common::{escape_json, set, switch_lang};
+
+use crate::artifact::Buildable;
+use crate::build_hir::HIRBuilder;
+use crate::error::{CompileError, CompileErrors};
+use crate::module::SharedCompilerResource;
+
+/// The diagnostics of a checked file.
+/// The diagnostics of the imported modules are also included.
+#[derive(Debug)]
+pub struct CheckResult {
+    pub path: PathBuf,
+    pub errors: CompileErrors,
+    pub warns: CompileErrors,
+}
+
+impl CheckResult {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn loc_json(loc: Location) -> String {
+    let (ln_begin, col_begin, ln_end, col_end) = match loc {
+        Location::Range {
+            ln_begin,
+            col_begin,
+            ln_end,
+            col_end,
+            ..
+        } => (
+            ln_begin.to_string(),
+            (col_begin + 1).to_string(),
+            ln_end.to_string(),
+            (col_end + 1).to_string(),
+        ),
+        Location::LineRange(ln_begin, ln_end) => (
+            ln_begin.to_string(),
+            "null".into(),
+            ln_end.to_string(),
+            "null".into(),
+        ),
+        Location::Line(ln) => (ln.to_string(), "null".into(), ln.to_string(), "null".into()),
+        Location::Unknown => ("null".into(), "null".into(), "null".into(), "null".into()),
+    };
+    let (byte_begin, byte_end) = match loc.byte_range() {
+        Some(range) => (range.start.to_string(), range.end.to_string()),
+        None => ("null".into(), "null".into()),
+    };
+    format!(
+        "\"line\":{ln_begin},\"column\":{col_begin},\"end_line\":{ln_end},\"end_column\":{col_end},\"byte_begin\":{byte_begin},\"byte_end\":{byte_end}"
+    )
+}
+
+/// A diagnostic as a line of JSON (for editors, CI and pre-commit hooks).
+/// Lines and columns are 1-origin, and the end column is exclusive.
+/// Columns count grapheme clusters. `byte_begin` and `byte_end` are 0-origin byte offsets in the file (`null` if unknown).
+pub fn diagnostic_json(path: &Path, err: &CompileError, code: &str) -> String {
+    let core = err.core();
+    let severity = if core.kind.is_warning() {
+        "warning"
+    } else {
+        "error"
+    };
+    let mut message = remove_style(&core.main_message);
+    if let Some(hint) = core.get_hint() {
+        message.push('\n');
+        message.push_str(&remove_style(hint));
+    }
+    let suggestions = core
+        .suggestions
+        .iter()
+        .map(|sug| {
+            format!(
+                "{{{},\"replacement\":{},\"applicability\":\"{:?}\"}}",
+                loc_json(sug.loc),
+                escape_json(&sug.replacement),
+                sug.applicability
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"path\":{},{},\"severity\":\"{severity}\",\"code\":{},\"message\":{},\"suggestions\":[{}]}}",
+        escape_json(&path.to_string_lossy()),
+        loc_json(core.get_loc_with_fallback()),
+        escape_json(code),
+        escape_json(&message),
+        suggestions.join(",")
+    )
+}
+
+/// Checks multiple files (parsing, lowering, type checking, ownership checking, etc.).
+/// The module cache is shared, so each imported module is checked only once.
+#[derive(Debug)]
+pub struct PackageChecker {
+    cfg: ErgConfig,
+    shared: SharedCompilerResource,
+}
+
+impl PackageChecker {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            shared: SharedCompilerResource::new(cfg.copy()),
+            cfg,
+        }
+    }
+
+    /// Shares the module cache with the other checkers/compilers.
+    pub fn inherit(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        Self { cfg, shared }
+    }
+
+    pub fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+
+    pub fn shared(&self) -> &SharedCompilerResource {
+        &self.shared
+    }
+
+    /// Expands `cfg.targets` (files, directories and glob patterns) into the files to be checked.
+    /// If no targets are given, the files of the package containing the current directory are returned.
+    pub fn collect_targets(&self) -> Vec<PathBuf> {
+        expand_targets(&self.cfg.targets)
+    }
+
+    pub fn check_file(&mut self, path: &Path) -> CheckResult {
+        let mut result = CheckResult {
+            path: path.to_path_buf(),
+            errors: CompileErrors::empty(),
+            warns: CompileErrors::empty(),
+        };
+        // already checked as a dependency
+        if self
+            .shared
+            .promises
+            .is_registered(&NormalizedPathBuf::from(path))
+        {
+            return result;
+        }
+        let cfg = self.cfg.inherit(path.to_path_buf());
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(err) => {
+                result.errors.push(CompileError::file_error(
+                    cfg.input.clone(),
+                    line!() as usize,
+                    switch_lang!(
+                        "japanese" => format!("{}を読み込めません: {err}", path.display()),
+                        "simplified_chinese" => format!("无法读取{}: {err}", path.display()),
+                        "traditional_chinese" => format!("無法讀取{}: {err}", path.display()),
+                        "english" => format!("cannot read {}: {err}", path.display()),
+                    ),
+                    Location::Unknown,
+                    "<module>".into(),
+                    None,
+                ));
+                return result;
+            }
+        };
+        let mode = if path.to_string_lossy().ends_with(".d.er") {
+            "declare"
+        } else {
+            "exec"
+        };
+        let mut builder = HIRBuilder::inherit(cfg, self.shared.clone());
+        let hir = match builder.build(src, mode) {
+            Ok(artifact) => {
+                result.warns = artifact.warns;
+                Some(artifact.object)
+            }
+            Err(artifact) => {
+                result.errors = artifact.errors;
+                result.warns = artifact.warns;
+                artifact.object
+            }
+        };
+        // register the module so that the modules importing it do not check it again
+        if let Some(ctx) = builder.pop_mod_ctx() {
+            let path = NormalizedPathBuf::from(path);
+            self.shared.mod_cache.register(path.clone(), hir, ctx);
+            self.shared.promises.mark_as_finished(path);
+        }
+        self.take_dependency_diagnostics(&mut result);
+        result
+    }
+
+    /// The diagnostics of the imported modules are stored in the shared resource.
+    fn take_dependency_diagnostics(&self, result: &mut CheckResult) {
+        self.shared.promises.join_all();
+        result.errors.extend(self.shared.errors.take());
+        result.warns.extend(self.shared.warns.take());
+    }
+
+    pub fn check_files<P: AsRef<Path>>(&mut self, paths: &[P]) -> Vec<CheckResult> {
+        paths
+            .iter()
+            .map(|path| self.check_file(path.as_ref()))
+            .collect()
+    }
+
+    /// Checks the source code given by `-c` or a pipe.
+    pub fn check_src(&mut self, src: String) -> CheckResult {
+        let mut builder = HIRBuilder::inherit(self.cfg.copy(), self.shared.clone());
+        let (errors, warns) = match builder.build(src, "exec") {
+            Ok(artifact) => (CompileErrors::empty(), artifact.warns),
+            Err(artifact) => (artifact.errors, artifact.warns),
+        };
+        let mut result = CheckResult {
+            path: PathBuf::from(self.cfg.input.filename()),
+            errors,
+            warns,
+        };
+        self.take_dependency_diagnostics(&mut result);
+        result
+    }
+
+    /// Removes the changed modules and the modules depending on them from the cache.
+    /// Returns the removed modules.
+    pub fn invalidate(&self, changed: &[PathBuf]) -> Set<NormalizedPathBuf> {
+        let mut invalidated = set! {};
+        let mut stack = changed
+            .iter()
+            .map(|path| NormalizedPathBuf::from(path.clone()))
+            .collect::<Vec<_>>();
+        while let Some(path) = stack.pop() {
+            if invalidated.contains(&path) {
+                continue;
+            }
+            stack.extend(self.shared.graph.children(&path));
+            self.shared.clear(&path);
+            self.shared.promises.remove(&path);
+            invalidated.insert(path);
+        }
+        invalidated
+    }
+
+    /// The targets and the Erg modules imported by them
+    fn watched_files(&self, targets: &[PathBuf]) -> Vec<PathBuf> {
+        let mut files = targets.to_vec();
+        for (path, _) in self.shared.mod_cache.ref_inner().iter() {
+            if path.to_string_lossy().ends_with(".er") && !files.iter().any(|f| f == &**path) {
+                files.push(path.to_path_buf());
+            }
+        }
+        files
+    }
+
+    pub(crate) fn report(&self, results: &[&CheckResult]) -> (usize, usize) {
+        let (mut num_errors, mut num_warns) = (0, 0);
+        for result in results.iter() {
+            if self.cfg.json_output {
+                for err in result.warns.iter().chain(result.errors.iter()) {
+                    let code = format!("{:?}", err.core.kind);
+                    println!("{}", diagnostic_json(err.input.path(), err, &code));
+                }
+            } else {
+                result.warns.write_all_stderr();
+                result.errors.write_all_stderr();
+            }
+            num_errors += result.errors.len();
+            num_warns += result.warns.len();
+        }
+        (num_errors, num_warns)
+    }
+
+    /// Checks the targets, and rechecks them whenever the files are changed (`erg check --watch`).
+    /// Only the changed modules and the modules depending on them are rechecked.
+    pub fn watch(cfg: ErgConfig) -> ExitStatus {
+        let mut checker = Self::new(cfg);
+        if checker.cfg.targets.is_empty() && checker.collect_targets().is_empty() {
+            return HIRBuilder::run(checker.cfg);
+        }
+        let mut results = Dict::<PathBuf, CheckResult>::new();
+        let mut watcher = FileWatcher::default();
+        let mut invalidated = set! {};
+        loop {
+            let targets = checker.collect_targets();
+            results.retain(|path, _| targets.contains(path));
+            // an edit during the check cancels it (and the check is restarted)
+            checker.cfg.cancel = CancellationToken::new();
+            watcher.set_paths(checker.watched_files(&targets));
+            let guard = watcher.cancel_on_change(&checker.cfg.cancel);
+            for target in targets.iter() {
+                let path = NormalizedPathBuf::from(target.clone());
+                if checker.cfg.cancel.is_cancelled() {
+                    break;
+                }
+                if !results.contains_key(target) || invalidated.contains(&path) {
+                    let result = checker.check_file(target);
+                    results.insert(target.clone(), result);
+                }
+            }
+            drop(guard);
+            if checker.cfg.cancel.is_cancelled() {
+                // the modules analyzed halfway may be cached, so everything is rechecked
+                checker.shared = SharedCompilerResource::new(checker.cfg.copy());
+                results.clear();
+                watcher.wait(|| checker.collect_targets());
+                continue;
+            }
+            print!("{CLEAR_SCREEN}");
+            let results_in_order = targets
+                .iter()
+                .filter_map(|target| results.get(target))
+                .collect::<Vec<_>>();
+            let (num_errors, num_warns) = checker.report(&results_in_order);
+            if !checker.cfg.quiet {
+                println!("{}", status_line(targets.len(), num_errors, num_warns));
+            }
+            watcher.set_paths(checker.watched_files(&targets));
+            let changed = watcher.wait(|| checker.collect_targets());
+            invalidated = checker.invalidate(&changed);
+        }
+    }
+
+    /// Checks the targets and prints the diagnostics and a summary.
+    /// If there is nothing to check, the REPL of the checker is started.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        if cfg.watch && cfg.input.is_repl() {
+            return Self::watch(cfg);
+        }
+        let mut checker = Self::new(cfg);
+        let results = if !checker.cfg.input.is_repl() {
+            let src = checker.cfg.input.read();
+            vec![checker.check_src(src)]
+        } else {
+            let targets = checker.collect_targets();
+            if targets.is_empty() && checker.cfg.targets.is_empty() {
+                return HIRBuilder::run(checker.cfg);
+            }
+            checker.check_files(&targets)
+        };
+        let (num_errors, num_warns) = checker.report(&results.iter().collect::<Vec<_>>());
+        if checker.cfg.verbose > 0 && !checker.cfg.quiet && !checker.cfg.json_output {
+            println!(
+                "checked {} file(s): {num_errors} error(s), {num_warns} warning(s)",
+                results.len()
+            );
+        }
+        let code = exit_code::of_errors(
+            results
+                .iter()
+                .flat_map(|result| result.errors.iter().map(|err| err.core())),
+        );
+        ExitStatus::new(code, num_warns, num_errors)
+    }
+}
@@ -24,7 +24,7 @@ use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{debug_power_assert, fn_name, fn_name_full, impl_stream, log, switch_unreachable};
 use erg_parser::ast::VisModifierSpec;
-use erg_parser::ast::{DefId, DefKind};
+use erg_parser::ast::{DefId, DefKind, DocComment};
 use CommonOpcode::*;
 
 use erg_parser::ast::{ParamPattern, TypeBoundSpecs, VarName};
@@ -87,10 +87,28 @@ fn escape_name(name: &str, vis: &VisibilityModifier, def_line: u32, def_col: u32
     }
 }
 
-fn escape_ident(ident: Identifier) -> Str {
+/// The keyword name in the generated Python code.
+/// Keywords passed to `**kwargs` are not escaped because they become dict keys.
+fn escape_keyword(kw: Str, is_py_api: bool, callee_t: &Type) -> Str {
+    let is_declared = |kw: &Str| {
+        callee_t
+            .non_default_params()
+            .into_iter()
+            .chain(callee_t.default_params())
+            .any(|params| params.iter().any(|pt| pt.name() == Some(kw)))
+    };
+    if is_py_api || (callee_t.kw_var_params().is_some() && !is_declared(&kw)) {
+        kw
+    } else {
+        escape_name(&kw, &VisibilityModifier::Private, 0, 0)
+    }
+}
+
+/// The name of the variable in the generated Python code.
+pub(crate) fn escape_ident(ident: Identifier) -> Str {
     let vis = ident.vis();
     if &ident.inspect()[..] == "Self" {
-        let Ok(ty) = <&Type>::try_from(ident.vi.t.singleton_value().unwrap()) else { unreachable!() };
+        let Ok(ty) = Type::try_from(ident.vi.t.singleton_value().unwrap().into_owned()) else { unreachable!() };
         escape_name(
             &ty.local_name(),
             &ident.vi.vis.modifier,
@@ -99,6 +117,9 @@ fn escape_ident(ident: Identifier) -> Str {
         )
     } else if let Some(py_name) = ident.vi.py_name {
         py_name
+    } else if ident.vi.kind.is_instance_attr() {
+        // the fields are initialized by the generated `__init__`, which does not know their locations
+        escape_name(ident.inspect(), vis, 0, 0)
     } else {
         escape_name(
             ident.inspect(),
@@ -170,16 +191,20 @@ impl_stream!(PyCodeGenStack, PyCodeGenUnit);
 #[derive(Debug, Default)]
 pub struct PyCodeGenerator {
     cfg: ErgConfig,
+    /// `co_filename` of the code objects of a linked module (`None` for the input)
+    filename: Option<Str>,
     pub(crate) py_version: PythonVersion,
     str_cache: CacheSet<str>,
     prelude_loaded: bool,
     mutate_op_loaded: bool,
     contains_op_loaded: bool,
+    is_failure_loaded: bool,
     record_type_loaded: bool,
     module_type_loaded: bool,
     control_loaded: bool,
     convertors_loaded: bool,
     abc_loaded: bool,
+    lru_cache_loaded: bool,
     unit_size: usize,
     units: PyCodeGenStack,
     fresh_gen: SharedFreshNameGenerator,
@@ -189,16 +214,19 @@ impl PyCodeGenerator {
     pub fn new(cfg: ErgConfig) -> Self {
         Self {
             py_version: cfg.target_version.unwrap_or_else(env_python_version),
+            filename: None,
             cfg,
             str_cache: CacheSet::new(),
             prelude_loaded: false,
             mutate_op_loaded: false,
             contains_op_loaded: false,
+            is_failure_loaded: false,
             record_type_loaded: false,
             module_type_loaded: false,
             control_loaded: false,
             convertors_loaded: false,
             abc_loaded: false,
+            lru_cache_loaded: false,
             unit_size: 0,
             units: PyCodeGenStack::empty(),
             fresh_gen: SharedFreshNameGenerator::new("codegen"),
@@ -208,22 +236,31 @@ impl PyCodeGenerator {
     pub fn inherit(&self) -> Self {
         Self {
             cfg: self.cfg.clone(),
+            filename: self.filename.clone(),
             py_version: self.py_version,
             str_cache: self.str_cache.clone(),
             prelude_loaded: false,
             mutate_op_loaded: false,
             contains_op_loaded: false,
+            is_failure_loaded: false,
             record_type_loaded: false,
             module_type_loaded: false,
             control_loaded: false,
             convertors_loaded: false,
             abc_loaded: false,
+            lru_cache_loaded: false,
             unit_size: 0,
             units: PyCodeGenStack::empty(),
             fresh_gen: self.fresh_gen.clone(),
         }
     }
 
+    fn filename(&self) -> Str {
+        self.filename
+            .clone()
+            .unwrap_or_else(|| Str::rc(self.cfg.input.enclosed_name()))
+    }
+
     pub fn clear(&mut self) {
         self.units.clear();
     }
@@ -232,11 +269,13 @@ impl PyCodeGenerator {
         self.prelude_loaded = false;
         self.mutate_op_loaded = false;
         self.contains_op_loaded = false;
+        self.is_failure_loaded = false;
         self.record_type_loaded = false;
         self.module_type_loaded = false;
         self.control_loaded = false;
         self.convertors_loaded = false;
         self.abc_loaded = false;
+        self.lru_cache_loaded = false;
     }
 
     #[inline]
@@ -938,6 +977,17 @@ impl PyCodeGenerator {
         }
     }
 
+    fn gen_var_args_flags(params: &Params) -> u32 {
+        let mut flags = 0;
+        if params.var_params.is_some() {
+            flags |= CodeObjFlags::VarArgs as u32;
+        }
+        if params.kw_var_params.is_some() {
+            flags |= CodeObjFlags::VarKeywords as u32;
+        }
+        flags
+    }
+
     fn gen_param_names(&self, params: &Params) -> Vec<Str> {
         params
             .non_defaults
@@ -957,6 +1007,14 @@ impl PyCodeGenerator {
                     .iter()
                     .map(|p| (p.inspect().map(|s| &s[..]).unwrap_or("_"), &p.sig.vi)),
             )
+            .chain(if let Some(kw_var_args) = &params.kw_var_params {
+                vec![(
+                    kw_var_args.inspect().map(|s| &s[..]).unwrap_or("_"),
+                    &kw_var_args.vi,
+                )]
+            } else {
+                vec![]
+            })
             .enumerate()
             .map(|(i, (s, vi))| {
                 if s == "_" {
@@ -1006,11 +1064,25 @@ impl PyCodeGenerator {
             return self.emit_trait_def(def);
         }
         match def.sig {
-            Signature::Subr(sig) => self.emit_subr_def(None, sig, def.body),
+            Signature::Subr(sig) => {
+                let ident = sig.ident.clone();
+                self.emit_subr_def(None, sig, def.body);
+                if let Some(doc) = def.doc {
+                    self.emit_store_doc(ident, doc);
+                }
+            }
             Signature::Var(sig) => self.emit_var_def(sig, def.body),
         }
     }
 
+    /// `f.__doc__ = "..."`
+    fn emit_store_doc(&mut self, ident: Identifier, doc: DocComment) {
+        log!(info "entered {} ({ident})", fn_name!());
+        self.emit_load_const(Str::from(doc.text()));
+        self.emit_load_name_instr(ident);
+        self.emit_store_instr(Identifier::public("__doc__"), UnboundAttr);
+    }
+
     fn emit_push_null(&mut self) {
         if self.py_version.minor >= Some(11) {
             self.write_instr(Opcode311::PUSH_NULL);
@@ -1114,7 +1186,7 @@ impl PyCodeGenerator {
             self.unit_size,
             self.py_version,
             vec![],
-            Str::rc(self.cfg.input.enclosed_name()),
+            self.filename(),
             &name,
             firstlineno,
             0,
@@ -1152,9 +1224,19 @@ impl PyCodeGenerator {
             self.mut_cur_block_codeobj().flags += CodeObjFlags::NewLocals as u32;
         }
         // end of flagging
+        let unit = self.pop_unit();
+        unit.codeobj
+    }
+
+    /// Pops the current unit and increases the line number of the parent unit to the last line of the unit.
+    /// In the coverage mode, the line number is not increased,
+    /// so that the definitions are attributed to their first lines (not to the last lines of their bodies).
+    fn pop_unit(&mut self) -> PyCodeGenUnit {
         let unit = self.units.pop().unwrap();
-        if !self.units.is_empty() {
-            let ld = unit.prev_lineno - self.cur_block().prev_lineno;
+        if !self.units.is_empty() && !self.cfg.coverage {
+            let ld = unit
+                .prev_lineno
+                .saturating_sub(self.cur_block().prev_lineno);
             if ld != 0 {
                 if let Some(l) = self.mut_cur_block_codeobj().lnotab.last_mut() {
                     *l += ld as u8;
@@ -1162,7 +1244,7 @@ impl PyCodeGenerator {
                 self.mut_cur_block().prev_lineno += ld;
             }
         }
-        unit.codeobj
+        unit
     }
 
     fn emit_empty_func(
@@ -1183,7 +1265,7 @@ impl PyCodeGenerator {
                 self.unit_size,
                 self.py_version,
                 vec![],
-                Str::rc(self.cfg.input.enclosed_name()),
+                self.filename(),
                 ident.inspect(),
                 ident.ln_begin().unwrap_or(0),
                 0,
@@ -1191,18 +1273,7 @@ impl PyCodeGenerator {
             self.emit_load_const(ValueObj::None);
             self.write_instr(RETURN_VALUE);
             self.write_arg(0);
-            let unit = self.units.pop().unwrap();
-            if !self.units.is_empty() {
-                let ld = unit
-                    .prev_lineno
-                    .saturating_sub(self.cur_block().prev_lineno);
-                if ld != 0 {
-                    if let Some(l) = self.mut_cur_block_codeobj().lnotab.last_mut() {
-                        *l += ld as u8;
-                    }
-                    self.mut_cur_block().prev_lineno += ld;
-                }
-            }
+            let unit = self.pop_unit();
             unit.codeobj
         };
         self.emit_load_const(code);
@@ -1313,8 +1384,26 @@ impl PyCodeGenerator {
     fn emit_subr_def(&mut self, class_name: Option<&str>, sig: SubrSignature, body: DefBody) {
         log!(info "entered {} ({sig} = {})", fn_name!(), body.block);
         let name = sig.ident.inspect().clone();
+        // the decorators are evaluated before the function is created
+        let decos_len = sig.decorators.len();
+        let cached = sig
+            .ident
+            .vi
+            .comptime_decos
+            .as_ref()
+            .is_some_and(|decos| decos.contains("Cache"));
+        for deco in sig.decorators {
+            self.emit_push_null();
+            self.emit_expr(deco);
+        }
+        // `@Cache` is the innermost decorator
+        if cached {
+            self.emit_push_null();
+            self.emit_lru_cache();
+        }
         let mut make_function_flag = 0;
         let params = self.gen_param_names(&sig.params);
+        let flags = Self::gen_var_args_flags(&sig.params);
         if !sig.params.defaults.is_empty() {
             let defaults_len = sig.params.defaults.len();
             sig.params
@@ -1326,11 +1415,6 @@ impl PyCodeGenerator {
             self.stack_dec_n(defaults_len - 1);
             make_function_flag += MakeFunctionFlags::Defaults as usize;
         }
-        let flags = if sig.params.var_params.is_some() {
-            CodeObjFlags::VarArgs as u32
-        } else {
-            0
-        };
         let code = self.emit_block(body.block, Some(name.clone()), params, flags);
         // code.flags += CodeObjFlags::Optimized as u32;
         self.register_cellvars(&mut make_function_flag);
@@ -1351,13 +1435,31 @@ impl PyCodeGenerator {
         if make_function_flag & MakeFunctionFlags::Defaults as usize != 0 {
             self.stack_dec();
         }
+        // the nearest decorator is applied first
+        for _ in 0..decos_len + usize::from(cached) {
+            self.emit_call_instr(1, Name);
+            self.stack_dec();
+        }
         self.emit_store_instr(sig.ident, Name);
     }
 
+    /// `functools.lru_cache(None)` (`functools.cache` is not available before Python 3.9)
+    fn emit_lru_cache(&mut self) {
+        if !self.lru_cache_loaded {
+            self.load_lru_cache();
+        }
+        self.emit_push_null();
+        self.emit_load_name_instr(Identifier::private("#lru_cache"));
+        self.emit_load_const(ValueObj::None);
+        self.emit_call_instr(1, Name);
+        self.stack_dec();
+    }
+
     fn emit_lambda(&mut self, lambda: Lambda) {
         log!(info "entered {} ({lambda})", fn_name!());
         let mut make_function_flag = 0;
         let params = self.gen_param_names(&lambda.params);
+        let flags = Self::gen_var_args_flags(&lambda.params);
         if !lambda.params.defaults.is_empty() {
             let defaults_len = lambda.params.defaults.len();
             lambda
@@ -1370,11 +1472,6 @@ impl PyCodeGenerator {
             self.stack_dec_n(defaults_len - 1);
             make_function_flag += MakeFunctionFlags::Defaults as usize;
         }
-        let flags = if lambda.params.var_params.is_some() {
-            CodeObjFlags::VarArgs as u32
-        } else {
-            0
-        };
         let code = self.emit_block(lambda.body, Some("<lambda>".into()), params, flags);
         self.register_cellvars(&mut make_function_flag);
         self.emit_load_const(code);
@@ -1413,13 +1510,16 @@ impl PyCodeGenerator {
 
     fn emit_unaryop(&mut self, unary: UnaryOp) {
         log!(info "entered {} ({unary})", fn_name!());
+        if unary.op.is(TokenKind::Try) {
+            return self.emit_early_return(unary);
+        }
         let val_t = unary
             .info
             .t
             .non_default_params()
-            .and_then(|tys| tys.get(0).map(|pt| pt.typ()))
-            .unwrap_or(Type::FAILURE);
-        let tycode = TypeCode::from(val_t);
+            .and_then(|tys| tys.get(0).map(|pt| pt.typ().clone()))
+            .unwrap_or(Type::Failure);
+        let tycode = TypeCode::from(&val_t);
         let instr = match &unary.op.kind {
             // TODO:
             TokenKind::PrePlus => UNARY_POSITIVE,
@@ -1462,6 +1562,96 @@ impl PyCodeGenerator {
         }
     }
 
+    /// `x?` (`x: T or NoneType`) =>
+    /// ```python
+    /// x
+    /// DUP_TOP
+    /// LOAD_CONST None
+    /// IS_OP 0
+    /// POP_JUMP_IF_FALSE (end)
+    /// RETURN_VALUE
+    /// end: ...
+    /// ```
+    /// `x?` (`x: T or Error`) =>
+    /// ```python
+    /// x
+    /// DUP_TOP
+    /// (PUSH_NULL, SWAP 2)
+    /// LOAD_NAME #is_failure
+    /// ROT_TWO
+    /// CALL_FUNCTION 1
+    /// POP_JUMP_IF_FALSE (end)
+    /// RETURN_VALUE
+    /// end: ...
+    /// ```
+    fn emit_early_return(&mut self, unary: UnaryOp) {
+        log!(info "entered {} ({unary})", fn_name!());
+        let init_stack_len = self.stack_len();
+        let only_none = Self::early_return_only_none(&unary.info.t);
+        self.emit_expr(*unary.expr);
+        self.dup_top();
+        if only_none || self.cfg.no_std {
+            self.emit_load_const(ValueObj::None);
+            // Opcode310::IS_OP == Opcode311::IS_OP
+            self.write_instr(Opcode310::IS_OP);
+            self.write_arg(0);
+            self.stack_dec();
+        } else {
+            if self.py_version.minor >= Some(11) {
+                self.emit_push_null();
+                self.rot2();
+            }
+            if !self.is_failure_loaded {
+                self.load_is_failure();
+            }
+            self.emit_load_name_instr(Identifier::private("#is_failure"));
+            self.rot2();
+            if self.py_version.minor >= Some(11) {
+                self.emit_precall_and_call(1);
+            } else {
+                self.write_instr(Opcode310::CALL_FUNCTION);
+                self.write_arg(1);
+            }
+            self.stack_dec();
+        }
+        let idx_pop_jump_if_false = self.lasti();
+        self.write_instr(EXTENDED_ARG);
+        self.write_arg(0);
+        // Opcode310::POP_JUMP_IF_FALSE == Opcode311::POP_JUMP_FORWARD_IF_FALSE
+        self.write_instr(Opcode310::POP_JUMP_IF_FALSE);
+        self.write_arg(0);
+        self.stack_dec();
+        // `x` (the failure value) is on the top of the stack
+        self.write_instr(RETURN_VALUE);
+        self.write_arg(0);
+        let idx_end = self.lasti();
+        if self.py_version.minor >= Some(11) {
+            self.fill_jump(
+                idx_pop_jump_if_false + 1,
+                idx_end - idx_pop_jump_if_false - 4,
+            );
+        } else {
+            self.fill_jump(idx_pop_jump_if_false + 1, idx_end);
+        }
+        debug_assert_eq!(self.stack_len(), init_stack_len + 1);
+    }
+
+    /// Whether `?` (`(T or E) -> T`) returns only `None` (`E == NoneType`).
+    fn early_return_only_none(t: &Type) -> bool {
+        let (Some(params), Some(ret_t)) = (t.non_default_params(), t.return_t()) else {
+            return true;
+        };
+        let Some(arg_t) = params.first().map(|pt| pt.typ()) else {
+            return true;
+        };
+        let success_types = ret_t.union_types();
+        arg_t
+            .union_types()
+            .iter()
+            .filter(|ty| !success_types.contains(ty))
+            .all(|ty| ty == &Type::NoneType)
+    }
+
     fn emit_binop(&mut self, bin: BinOp) {
         log!(info "entered {} ({bin})", fn_name!());
         // TODO: and/orのプリミティブ命令の実装
@@ -1502,15 +1692,15 @@ impl PyCodeGenerator {
             .info
             .t
             .non_default_params()
-            .and_then(|tys| tys.get(0).map(|pt| pt.typ()))
-            .unwrap_or(Type::FAILURE);
+            .and_then(|tys| tys.get(0).map(|pt| pt.typ().clone()))
+            .unwrap_or(Type::Failure);
         let rhs_t = bin
             .info
             .t
             .non_default_params()
-            .and_then(|tys| tys.get(1).map(|pt| pt.typ()))
-            .unwrap_or(Type::FAILURE);
-        let type_pair = TypePair::new(lhs_t, rhs_t);
+            .and_then(|tys| tys.get(1).map(|pt| pt.typ().clone()))
+            .unwrap_or(Type::Failure);
+        let type_pair = TypePair::new(&lhs_t, &rhs_t);
         self.emit_expr(*bin.lhs);
         self.emit_expr(*bin.rhs);
         self.emit_binop_instr(bin.op, type_pair);
@@ -1830,7 +2020,9 @@ impl PyCodeGenerator {
         // but after executing this instruction, stack_len should be 1
         // cannot detect where to jump to at this moment, so put as 0
         self.write_arg(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         // If there is nothing on the stack at the start, init_stack_len == 2 (an iterator and the first iterator value)
         let init_stack_len = self.stack_len();
         let params = self.gen_param_names(&lambda.params);
@@ -1884,7 +2076,9 @@ impl PyCodeGenerator {
         self.write_instr(Opcode310::POP_JUMP_IF_FALSE);
         self.write_arg(0);
         self.stack_dec();
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let init_stack_len = self.stack_len();
         let params = self.gen_param_names(&lambda.params);
         self.emit_frameless_block(lambda.body, params);
@@ -1935,18 +2129,29 @@ impl PyCodeGenerator {
                 todo!("default values in match expression are not supported yet")
             }
             let param = lambda.params.non_defaults.remove(0);
-            let pop_jump_point = self.emit_match_pattern(param, args.is_empty());
+            let is_last_arm = args.is_empty();
+            let mut pop_jump_points = vec![];
+            pop_jump_points.extend(self.emit_match_pattern(
+                param,
+                is_last_arm,
+                lambda.guard.is_some(),
+            ));
+            if let Some(guard) = lambda.guard {
+                pop_jump_points.extend(self.emit_match_guard(guard, is_last_arm));
+            }
             self.emit_frameless_block(lambda.body, Vec::new());
             // If we move on to the next arm, the stack size will increase
             // so `self.stack_dec();` for now (+1 at the end).
             self.stack_dec();
-            if let Some(pop_jump_point) = pop_jump_point {
-                let idx = match self.py_version.minor {
-                    Some(11) => self.lasti() - pop_jump_point,
-                    Some(10) => self.lasti() + 4,
-                    _ => self.lasti() + 4,
-                };
-                self.fill_jump(pop_jump_point + 1, idx); // jump to POP_TOP
+            if !pop_jump_points.is_empty() {
+                for pop_jump_point in pop_jump_points {
+                    let idx = match self.py_version.minor {
+                        Some(11) => self.lasti() - pop_jump_point,
+                        Some(10) => self.lasti() + 4,
+                        _ => self.lasti() + 4,
+                    };
+                    self.fill_jump(pop_jump_point + 1, idx); // jump to the next arm
+                }
                 jump_forward_points.push(self.lasti());
                 self.write_instr(EXTENDED_ARG);
                 self.write_arg(0);
@@ -1971,44 +2176,40 @@ impl PyCodeGenerator {
         &mut self,
         param: NonDefaultParamSignature,
         is_last_arm: bool,
+        has_guard: bool,
     ) -> Option<usize> {
         log!(info "entered {}", fn_name!());
         let mut pop_jump_point = None;
         // If it's the last arm, there's no need to inspect it
         match param.t_spec_as_expr {
             // _: {0, 1} => case 0 | 1
+            // (compiled as `arg in (0, 1)`)
             Some(Expr::Set(Set::Normal(set))) if !is_last_arm => {
-                let last = set.elems.pos_args.len() - 1;
-                for (i, elem) in set.elems.pos_args.into_iter().enumerate() {
-                    if i != 0 {
-                        self.rot2();
-                    }
+                let len = set.elems.pos_args.len();
+                for elem in set.elems.pos_args.into_iter() {
                     self.emit_expr(elem.expr);
-                    self.emit_binop_instr(
-                        Token::from_str(TokenKind::DblEq, "=="),
-                        TypePair::Others,
-                    );
-                    if i != 0 {
-                        self.stack_inc();
-                        self.emit_binop_instr(
-                            Token::from_str(TokenKind::OrOp, "or"),
-                            TypePair::Others,
-                        );
-                    }
-                    if i == last {
-                        pop_jump_point = Some(self.lasti());
-                        // HACK: match branches often jump very far (beyond the u8 range),
-                        // so the jump destination should be reserved as the u16 range.
-                        // Other jump instructions may need to be replaced by this way.
-                        self.write_instr(EXTENDED_ARG);
-                        self.write_arg(0);
-                        // in 3.11, POP_JUMP_IF_FALSE is replaced with POP_JUMP_FORWARD_IF_FALSE
-                        // but the numbers are the same, only the way the jumping points are calculated is different.
-                        self.write_instr(Opcode310::POP_JUMP_IF_FALSE); // jump to the next case
-                        self.write_arg(0);
-                    }
                 }
-                // self.stack_dec();
+                self.write_instr(BUILD_TUPLE);
+                self.write_arg(len);
+                self.stack_dec_n(len - 1);
+                if self.py_version.minor >= Some(9) {
+                    self.write_instr(Opcode309::CONTAINS_OP);
+                    self.write_arg(0);
+                } else {
+                    self.write_instr(Opcode308::COMPARE_OP);
+                    self.write_arg(6); // in
+                }
+                self.stack_dec();
+                pop_jump_point = Some(self.lasti());
+                // HACK: match branches often jump very far (beyond the u8 range),
+                // so the jump destination should be reserved as the u16 range.
+                // Other jump instructions may need to be replaced by this way.
+                self.write_instr(EXTENDED_ARG);
+                self.write_arg(0);
+                // in 3.11, POP_JUMP_IF_FALSE is replaced with POP_JUMP_FORWARD_IF_FALSE
+                // but the numbers are the same, only the way the jumping points are calculated is different.
+                self.write_instr(Opcode310::POP_JUMP_IF_FALSE); // jump to the next case
+                self.write_arg(0);
             }
             Some(t_spec) if !is_last_arm => {
                 // < v3.11:
@@ -2064,6 +2265,16 @@ impl PyCodeGenerator {
             }
             _ => {}
         }
+        if has_guard && !is_last_arm {
+            if pop_jump_point.is_some() {
+                // If the guard fails, the next arm inspects the target again
+                self.dup_top();
+            } else {
+                // The target has already been duplicated (by `emit_match_instr`),
+                // but the stack size should be the same as the arms with type checks
+                self.stack_inc();
+            }
+        }
         match param.raw.pat {
             ParamPattern::VarName(name) => {
                 let ident = erg_parser::ast::Identifier::private_from_varname(name);
@@ -2078,13 +2289,37 @@ impl PyCodeGenerator {
         pop_jump_point
     }
 
+    /// return `None` if the arm is the last one
+    ///
+    /// The guard of the last arm is not checked (exhaustiveness is ensured by the type checker),
+    /// but it is still executed because it binds the destructured variables.
+    fn emit_match_guard(&mut self, guard: Block, is_last_arm: bool) -> Option<usize> {
+        log!(info "entered {}", fn_name!());
+        self.emit_frameless_block(guard, Vec::new());
+        if is_last_arm {
+            self.emit_pop_top();
+            return None;
+        }
+        let pop_jump_point = self.lasti();
+        self.write_instr(EXTENDED_ARG);
+        self.write_arg(0);
+        self.write_instr(Opcode310::POP_JUMP_IF_FALSE); // jump to the next case
+        self.write_arg(0);
+        self.stack_dec();
+        // the guard is satisfied, so the target is no longer needed
+        self.emit_pop_top();
+        Some(pop_jump_point)
+    }
+
     fn emit_with_instr_311(&mut self, mut args: Args) {
         log!(info "entered {}", fn_name!());
         if !matches!(args.get(1).unwrap(), Expr::Lambda(_)) {
             return self.deopt_instr(ControlKind::With, args);
         }
         let expr = args.remove(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let params = self.gen_param_names(&lambda.params);
         self.emit_expr(expr);
         self.write_instr(Opcode311::BEFORE_WITH);
@@ -2132,7 +2367,9 @@ impl PyCodeGenerator {
             return self.deopt_instr(ControlKind::With, args);
         }
         let expr = args.remove(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let params = self.gen_param_names(&lambda.params);
         self.emit_expr(expr);
         let idx_setup_with = self.lasti();
@@ -2185,7 +2422,9 @@ impl PyCodeGenerator {
             return self.deopt_instr(ControlKind::With, args);
         }
         let expr = args.remove(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let params = self.gen_param_names(&lambda.params);
         self.emit_expr(expr);
         let idx_setup_with = self.lasti();
@@ -2238,7 +2477,9 @@ impl PyCodeGenerator {
             return self.deopt_instr(ControlKind::With, args);
         }
         let expr = args.remove(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let params = self.gen_param_names(&lambda.params);
         self.emit_expr(expr);
         let idx_setup_with = self.lasti();
@@ -2270,7 +2511,9 @@ impl PyCodeGenerator {
             return self.deopt_instr(ControlKind::With, args);
         }
         let expr = args.remove(0);
-        let Expr::Lambda(lambda) = args.remove(0) else { unreachable!() };
+        let Expr::Lambda(lambda) = args.remove(0) else {
+            unreachable!()
+        };
         let params = self.gen_param_names(&lambda.params);
         self.emit_expr(expr);
         let idx_setup_with = self.lasti();
@@ -2309,9 +2552,10 @@ impl PyCodeGenerator {
                 }
                 other => {
                     let is_py_api = other.is_py_api();
+                    let callee_t = other.t();
                     self.emit_push_null();
                     self.emit_expr(other);
-                    self.emit_args_311(call.args, Name, is_py_api);
+                    self.emit_args_311(call.args, Name, is_py_api, &callee_t);
                 }
             }
         }
@@ -2340,9 +2584,10 @@ impl PyCodeGenerator {
             // "pyimport" | "py" are here
             _ => {
                 let is_py_api = local.is_py_api();
+                let callee_t = local.t();
                 self.emit_push_null();
                 self.emit_load_name_instr(local);
-                self.emit_args_311(args, Name, is_py_api);
+                self.emit_args_311(args, Name, is_py_api, &callee_t);
             }
         }
     }
@@ -2364,15 +2609,75 @@ impl PyCodeGenerator {
             "yield" /* if obj.ref_t().is_callable() */ => {
                 return self.emit_yield_instr(args);
             }
+            "__getitem__" if Self::is_slice_args(&args) => {
+                return self.emit_subscr_slice(obj, args);
+            }
             _ => {}
         }
         if let Some(func_name) = debind(&method_name) {
             return self.emit_call_fake_method(obj, func_name, method_name, args);
         }
         let is_py_api = method_name.is_py_api();
+        let callee_t = method_name.t();
+        // `CALL_FUNCTION_EX` cannot call an unbound method loaded by `LOAD_METHOD`
+        if args.var_args.is_some() || args.kw_var.is_some() {
+            self.emit_push_null();
+            self.emit_expr(obj);
+            self.emit_load_attr_instr(method_name);
+            self.emit_args_311(args, Name, is_py_api, &callee_t);
+            return;
+        }
         self.emit_expr(obj);
         self.emit_load_method_instr(method_name);
-        self.emit_args_311(args, BoundAttr, is_py_api);
+        self.emit_args_311(args, BoundAttr, is_py_api, &callee_t);
+    }
+
+    /// `(slice(start, stop, step),)`
+    fn is_slice_args(args: &Args) -> bool {
+        let (
+            Some(PosArg {
+                expr: Expr::Call(call),
+            }),
+            1,
+        ) = (args.pos_args.first(), args.len())
+        else {
+            return false;
+        };
+        let Expr::Accessor(Accessor::Ident(ident)) = call.obj.as_ref() else {
+            return false;
+        };
+        call.attr_name.is_none()
+            && ident
+                .vi
+                .py_name
+                .as_ref()
+                .is_some_and(|name| &name[..] == "slice")
+            && call.args.kw_args.is_empty()
+            && call.args.var_args.is_none()
+            && call.args.kw_var.is_none()
+            && (2..=3).contains(&call.args.pos_args.len())
+    }
+
+    /// `x[start..stop; step]` => `x[slice(start, stop, step)]` => `BUILD_SLICE` + `BINARY_SUBSCR`
+    fn emit_subscr_slice(&mut self, obj: Expr, mut args: Args) {
+        log!(info "entered {}", fn_name!());
+        let Expr::Call(slice) = args.remove(0) else {
+            unreachable!()
+        };
+        self.emit_expr(obj);
+        let argc = slice.args.pos_args.len();
+        for arg in slice.args.pos_args {
+            self.emit_expr(arg.expr);
+        }
+        self.write_instr(BUILD_SLICE);
+        self.write_arg(argc);
+        self.stack_dec_n(argc - 1);
+        self.write_instr(BINARY_SUBSCR);
+        self.write_arg(0);
+        if self.py_version.minor >= Some(11) {
+            self.write_bytes(&[0; 8]);
+        }
+        self.stack_dec();
     }
 
     fn emit_var_args_311(&mut self, pos_len: usize, var_args: &PosArg) {
@@ -2401,10 +2706,9 @@ impl PyCodeGenerator {
         }
     }
 
-    fn emit_args_311(&mut self, mut args: Args, kind: AccessKind, is_py_api: bool) {
-        let argc = args.len();
+    /// `f(x, *xs, y := 1, **kws)` => `CALL_FUNCTION_EX(f, (x, *xs), {"y": 1, **kws})`
+    fn emit_args_ex(&mut self, mut args: Args, is_py_api: bool, callee_t: &Type) {
         let pos_len = args.pos_args.len();
-        let mut kws = Vec::with_capacity(args.kw_len());
         while let Some(arg) = args.try_remove_pos(0) {
             self.emit_expr(arg.expr);
         }
@@ -2414,13 +2718,67 @@ impl PyCodeGenerator {
             } else {
                 self.emit_var_args_308(pos_len, var_args);
             }
+        } else {
+            self.write_instr(BUILD_TUPLE);
+            self.write_arg(pos_len);
+            self.stack_inc();
+        }
+        // pos_len + 1 (var_args) objects -> 1 tuple
+        self.stack_dec_n(pos_len);
+        let has_kwargs = !args.kw_args.is_empty() || args.kw_var.is_some();
+        if has_kwargs {
+            let kw_len = args.kw_len();
+            while let Some(arg) = args.try_remove_kw(0) {
+                let kw = escape_keyword(arg.keyword.content, is_py_api, callee_t);
+                self.emit_load_const(kw);
+                self.emit_expr(arg.expr);
+            }
+            self.write_instr(BUILD_MAP);
+            self.write_arg(kw_len);
+            // kw_len * 2 (key, value) objects -> 1 dict
+            self.stack_dec_n(kw_len * 2);
+            self.stack_inc();
+            if let Some(kw_var) = args.kw_var.take() {
+                self.emit_expr(kw_var.expr);
+                if self.py_version.minor >= Some(10) {
+                    self.write_instr(Opcode310::DICT_MERGE);
+                    self.write_arg(1);
+                } else {
+                    self.write_instr(Opcode308::BUILD_MAP_UNPACK_WITH_CALL);
+                    self.write_arg(2);
+                }
+                self.stack_dec();
+            }
+        }
+        self.write_instr(CALL_FUNCTION_EX);
+        self.write_arg(has_kwargs as usize);
+        // (NULL +) subroutine + args tuple (+ kwargs dict) -> 1 return object
+        self.stack_dec();
+        if has_kwargs {
+            self.stack_dec();
+        }
+        if self.py_version.minor >= Some(11) {
+            self.stack_dec();
+        }
+    }
+
+    fn emit_args_311(
+        &mut self,
+        mut args: Args,
+        kind: AccessKind,
+        is_py_api: bool,
+        callee_t: &Type,
+    ) {
+        if args.var_args.is_some() || args.kw_var.is_some() {
+            return self.emit_args_ex(args, is_py_api, callee_t);
+        }
+        let argc = args.len();
+        let mut kws = Vec::with_capacity(args.kw_len());
+        while let Some(arg) = args.try_remove_pos(0) {
+            self.emit_expr(arg.expr);
         }
         while let Some(arg) = args.try_remove_kw(0) {
-            let kw = if is_py_api {
-                arg.keyword.content
-            } else {
-                escape_name(&arg.keyword.content, &VisibilityModifier::Private, 0, 0)
-            };
+            let kw = escape_keyword(arg.keyword.content, is_py_api, callee_t);
             kws.push(ValueObj::Str(kw));
             self.emit_expr(arg.expr);
         }
@@ -2433,16 +2791,7 @@ impl PyCodeGenerator {
                 1
             }
         } else {
-            if args.var_args.is_some() {
-                self.write_instr(CALL_FUNCTION_EX);
-                if kws.is_empty() {
-                    self.write_arg(0);
-                } else {
-                    self.write_arg(1);
-                }
-            } else {
-                self.emit_call_instr(argc, kind);
-            }
+            self.emit_call_instr(argc, kind);
             0
         };
         // (1 (subroutine) + argc + kwsc) input objects -> 1 return object
@@ -2454,7 +2803,9 @@ impl PyCodeGenerator {
     /// TODO: should be `X = X + 1` in the above case
     fn emit_call_update_311(&mut self, obj: Expr, mut args: Args) {
         log!(info "entered {}", fn_name!());
-        let Expr::Accessor(acc) = obj else { unreachable!() };
+        let Expr::Accessor(acc) = obj else {
+            unreachable!()
+        };
         let func = args.remove_left_or_key("f").unwrap();
         if !self.mutate_op_loaded {
             self.load_mutate_op();
@@ -2479,7 +2830,9 @@ impl PyCodeGenerator {
     /// X = X + 1
     fn emit_call_update_310(&mut self, obj: Expr, mut args: Args) {
         log!(info "entered {}", fn_name!());
-        let Expr::Accessor(acc) = obj else { unreachable!() };
+        let Expr::Accessor(acc) = obj else {
+            unreachable!()
+        };
         let func = args.remove_left_or_key("f").unwrap();
         if !self.mutate_op_loaded {
             self.load_mutate_op();
@@ -2535,7 +2888,7 @@ impl PyCodeGenerator {
         self.emit_push_null();
         self.emit_load_name_instr(method_name);
         args.insert_pos(0, PosArg::new(obj));
-        self.emit_args_311(args, Name, true);
+        self.emit_args_311(args, Name, true, &Type::Failure);
     }
 
     // assert takes 1 or 2 arguments (0: cond, 1: message)
@@ -2610,7 +2963,15 @@ impl PyCodeGenerator {
                 self.emit_binop_instr(Token::dummy(TokenKind::Star, "*"), TypePair::ArrayNat);
                 return;
             }
-            other => todo!("{other}"),
+            Array::Comprehension(arr) => {
+                self.emit_comprehension(
+                    BUILD_LIST,
+                    LIST_APPEND,
+                    arr.generators,
+                    arr.guards,
+                    vec![*arr.elem],
+                );
+            }
         }
         if !self.cfg.no_std {
             self.emit_call_instr(1, Name);
@@ -2659,6 +3020,15 @@ impl PyCodeGenerator {
                 self.write_instr(BUILD_SET);
                 self.write_arg(1);
             }
+            crate::hir::Set::Comprehension(st) => {
+                self.emit_comprehension(
+                    BUILD_SET,
+                    SET_ADD,
+                    st.generators,
+                    st.guards,
+                    vec![*st.elem],
+                );
+            }
         }
     }
 
@@ -2678,10 +3048,102 @@ impl PyCodeGenerator {
                     self.stack_dec_n(2 * len - 1);
                 }
             }
-            other => todo!("{other}"),
+            crate::hir::Dict::Comprehension(dic) => {
+                // Python 3.7: `MAP_ADD` takes the value first
+                let kv = if self.py_version.minor >= Some(8) {
+                    vec![*dic.key, *dic.value]
+                } else {
+                    vec![*dic.value, *dic.key]
+                };
+                self.emit_comprehension(BUILD_MAP, MAP_ADD, dic.generators, dic.guards, kv);
+            }
         }
     }
 
+    /// `[elem | x <- xs; y <- ys; guard]` =>
+    /// ```python
+    /// BUILD_LIST 0
+    /// LOAD xs; GET_ITER; FOR_ITER (exit); STORE x
+    ///     LOAD ys; GET_ITER; FOR_ITER (exit); STORE y
+    ///         guard; POP_JUMP_IF_FALSE (to the innermost FOR_ITER)
+    ///         elem; LIST_APPEND 3
+    ///     JUMP_BACKWARD (to FOR_ITER)
+    /// JUMP_BACKWARD (to FOR_ITER)
+    /// ```
+    fn emit_comprehension(
+        &mut self,
+        build: CommonOpcode,
+        add: CommonOpcode,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+        elems: Vec<Expr>,
+    ) {
+        log!(info "entered {}", fn_name!());
+        let init_stack_len = self.stack_len();
+        self.write_instr(build);
+        self.write_arg(0);
+        self.stack_inc();
+        let depth = generators.len();
+        let mut for_iter_points = vec![];
+        for (var, iter) in generators {
+            self.emit_expr(iter);
+            self.write_instr(GET_ITER);
+            self.write_arg(0);
+            let idx_for_iter = self.lasti();
+            self.write_instr(EXTENDED_ARG);
+            self.write_arg(0);
+            self.write_instr(FOR_ITER);
+            self.write_arg(0);
+            self.stack_inc();
+            self.emit_store_instr(var, Name);
+            for_iter_points.push(idx_for_iter);
+        }
+        let innermost = *for_iter_points.last().unwrap();
+        for guard in guards {
+            self.emit_expr(guard);
+            let idx = self.lasti();
+            self.write_instr(EXTENDED_ARG);
+            self.write_arg(0);
+            if self.py_version.minor >= Some(11) {
+                let arg = self.lasti() + 2 - innermost;
+                self.write_instr(Opcode311::POP_JUMP_BACKWARD_IF_FALSE);
+                self.write_arg(0);
+                self.fill_jump(idx + 1, arg);
+            } else {
+                self.write_instr(Opcode310::POP_JUMP_IF_FALSE);
+                self.write_arg(0);
+                self.fill_jump(idx + 1, innermost);
+            }
+            self.stack_dec();
+        }
+        let n_elems = elems.len();
+        for elem in elems {
+            self.emit_expr(elem);
+        }
+        self.write_instr(add);
+        self.write_arg(depth + 1);
+        self.stack_dec_n(n_elems);
+        for idx_for_iter in for_iter_points.into_iter().rev() {
+            let idx = self.lasti();
+            self.write_instr(EXTENDED_ARG);
+            self.write_arg(0);
+            if self.py_version.minor >= Some(11) {
+                self.write_instr(Opcode311::JUMP_BACKWARD);
+                self.write_arg(0);
+                self.fill_jump(idx + 1, self.lasti() - idx_for_iter);
+            } else {
+                self.write_instr(Opcode309::JUMP_ABSOLUTE);
+                self.write_arg(0);
+                self.fill_jump(idx + 1, idx_for_iter);
+            }
+            let idx_end = self.lasti();
+            self.fill_jump(idx_for_iter + 1, idx_end - idx_for_iter - 2 - 2);
+            // the exhausted iterator is popped
+            self.stack_dec();
+        }
+        debug_assert_eq!(self.stack_len(), init_stack_len + 1);
+    }
+
     #[allow(clippy::identity_op)]
     fn emit_record(&mut self, rec: Record) {
         log!(info "entered {} ({rec})", fn_name!());
@@ -2722,8 +3184,24 @@ impl PyCodeGenerator {
     }
 
     /// Emits independent code blocks (e.g., linked other modules)
+    /// The path of the module linked as `code` (see `HIRLinker::replace_erg_import`),
+    /// which is found from the definitions in the module.
+    fn linked_module_path(code: &Block) -> Option<&str> {
+        code.iter().find_map(|chunk| {
+            let ident = match chunk {
+                Expr::Def(def) => def.sig.ident(),
+                Expr::ClassDef(class_def) => class_def.sig.ident(),
+                _ => return None,
+            };
+            ident.vi.def_loc.module.as_ref()?.to_str()
+        })
+    }
+
     fn emit_code(&mut self, code: Block) {
         let mut gen = self.inherit();
+        if let Some(path) = Self::linked_module_path(&code) {
+            gen.filename = Some(Str::rc(path));
+        }
         let code = gen.emit_block(code, None, vec![], 0);
         self.emit_load_const(code);
     }
@@ -2782,6 +3260,10 @@ impl PyCodeGenerator {
     }
 
     fn push_lnotab(&mut self, expr: &Expr) {
+        // the lines of a linked module are of another file
+        if let Expr::Code(_) = expr {
+            return;
+        }
         let ln_begin = expr.ln_begin().unwrap_or(0);
         if ln_begin > self.cur_block().prev_lineno {
             let sd = self.lasti() - self.cur_block().prev_lasti;
@@ -2969,7 +3451,7 @@ impl PyCodeGenerator {
             self.unit_size,
             self.py_version,
             vec![],
-            Str::rc(self.cfg.input.enclosed_name()),
+            self.filename(),
             &name,
             firstlineno,
             0,
@@ -2980,6 +3462,10 @@ impl PyCodeGenerator {
         self.emit_store_instr(Identifier::public("__module__"), Name);
         self.emit_load_const(name);
         self.emit_store_instr(Identifier::public("__qualname__"), Name);
+        if let Some(doc) = class.doc.as_ref() {
+            self.emit_load_const(Str::from(doc.text()));
+            self.emit_store_instr(Identifier::public("__doc__"), Name);
+        }
         self.emit_init_method(&class.sig, class.__new__.clone());
         if class.need_to_gen_new {
             self.emit_new_func(&class.sig, class.__new__);
@@ -3010,29 +3496,21 @@ impl PyCodeGenerator {
             self.mut_cur_block_codeobj().flags += CodeObjFlags::NewLocals as u32;
         }
         // end of flagging
-        let unit = self.units.pop().unwrap();
-        if !self.units.is_empty() {
-            let ld = unit.prev_lineno - self.cur_block().prev_lineno;
-            if ld != 0 {
-                if let Some(l) = self.mut_cur_block_codeobj().lnotab.last_mut() {
-                    *l += ld as u8;
-                }
-                self.mut_cur_block().prev_lineno += ld;
-            }
-        }
+        let unit = self.pop_unit();
         unit.codeobj
     }
 
     fn emit_init_method(&mut self, sig: &Signature, __new__: Type) {
         log!(info "entered {}", fn_name!());
-        let new_first_param = __new__.non_default_params().unwrap().first();
+        let new_params = __new__.non_default_params().unwrap();
+        let new_first_param = new_params.first();
         let line = sig.ln_begin().unwrap_or(0);
         let class_name = sig.ident().inspect();
         let mut ident = Identifier::public_with_line(DOT, Str::ever("__init__"), line);
         ident.vi.t = __new__.clone();
         let self_param = VarName::from_str_and_line(Str::ever("self"), line);
         let vi = VarInfo::nd_parameter(
-            __new__.return_t().unwrap().clone(),
+            __new__.return_t().unwrap().into_owned(),
             ident.vi.def_loc.clone(),
             "?".into(),
         );
@@ -3053,7 +3531,7 @@ impl PyCodeGenerator {
                 "?".into(),
             );
             let param = NonDefaultParamSignature::new(raw, vi, None);
-            let params = Params::new(vec![self_param, param], None, vec![], None);
+            let params = Params::new(vec![self_param, param], None, vec![], None, None);
             (param_name, params)
         } else {
             ("_".into(), Params::single(self_param))
@@ -3179,7 +3657,7 @@ impl PyCodeGenerator {
             self.unit_size,
             self.py_version,
             params,
-            Str::rc(self.cfg.input.enclosed_name()),
+            self.filename(),
             name,
             firstlineno,
             flags,
@@ -3239,19 +3717,7 @@ impl PyCodeGenerator {
             self.edit_code(idx_copy_free_vars, CommonOpcode::NOP as usize);
         }
         // end of flagging
-        let unit = self.units.pop().unwrap();
-        // increase lineno
-        if !self.units.is_empty() {
-            let ld = unit
-                .prev_lineno
-                .saturating_sub(self.cur_block().prev_lineno);
-            if ld != 0 {
-                if let Some(l) = self.mut_cur_block_codeobj().lnotab.last_mut() {
-                    *l += ld as u8;
-                }
-                self.mut_cur_block().prev_lineno += ld;
-            }
-        }
+        let unit = self.pop_unit();
         unit.codeobj
     }
 
@@ -3279,6 +3745,18 @@ impl PyCodeGenerator {
         self.contains_op_loaded = true;
     }
 
+    fn load_is_failure(&mut self) {
+        let mod_name = Identifier::public("_erg_std_prelude");
+        self.emit_global_import_items(
+            mod_name,
+            vec![(
+                Identifier::public("is_failure"),
+                Some(Identifier::private("#is_failure")),
+            )],
+        );
+        self.is_failure_loaded = true;
+    }
+
     fn load_mutate_op(&mut self) {
         let mod_name = Identifier::public("_erg_std_prelude");
         self.emit_global_import_items(
@@ -3355,6 +3833,20 @@ impl PyCodeGenerator {
         );
     }
 
+    fn load_lru_cache(&mut self) {
+        self.emit_global_import_items(
+            Identifier::public("functools"),
+            vec![(
+                Identifier::public("lru_cache"),
+                Some(Identifier::private("#lru_cache")),
+            )],
+        );
+        // the import in a subroutine body may not be executed before other uses
+        if self.units.len() == 1 {
+            self.lru_cache_loaded = true;
+        }
+    }
+
     fn load_module_type(&mut self) {
         self.emit_global_import_items(
             Identifier::public("types"),
@@ -3372,7 +3864,7 @@ impl PyCodeGenerator {
             self.unit_size,
             self.py_version,
             vec![],
-            Str::rc(self.cfg.input.enclosed_name()),
+            self.filename(),
             "<module>",
             1,
             0,
@@ -3385,6 +3877,10 @@ impl PyCodeGenerator {
             self.load_prelude();
         }
         for chunk in hir.module.into_iter() {
+            // the caller discards the code object
+            if self.cfg.cancel.is_cancelled() {
+                break;
+            }
             self.emit_chunk(chunk);
             // TODO: discard
             if self.stack_len() == 1 {
@@ -3420,16 +3916,7 @@ impl PyCodeGenerator {
             self.mut_cur_block_codeobj().flags += CodeObjFlags::NewLocals as u32;
         }
         // end of flagging
-        let unit = self.units.pop().unwrap();
-        if !self.units.is_empty() {
-            let ld = unit.prev_lineno - self.cur_block().prev_lineno;
-            if ld != 0 {
-                if let Some(l) = self.mut_cur_block_codeobj().lnotab.last_mut() {
-                    *l += ld as u8;
-                }
-                self.mut_cur_block().prev_lineno += ld;
-            }
-        }
+        let unit = self.pop_unit();
         log!(info "the code-generating process has completed.{RESET}");
         unit.codeobj
     }
@@ -1,28 +1,33 @@
 //! defines `Compiler`.
 //!
 //! コンパイラーを定義する
+use std::io::{stdout, Write};
 use std::path::Path;
 
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::log;
+use erg_common::timing::{Phase, TIMINGS};
 use erg_common::traits::{ExitStatus, Runnable, Stream};
+use erg_common::Str;
 use erg_parser::ast::VarName;
 
-use crate::artifact::{CompleteArtifact, ErrorArtifact};
-use crate::context::{Context, ContextProvider};
+use crate::artifact::{Buildable, CompleteArtifact, ErrorArtifact, IncompleteArtifact};
+use crate::context::{Context, ContextProvider, ModuleContext};
 use crate::optimize::HIROptimizer;
+use crate::transpile::{PyScript, ScriptGenerator};
 use crate::ty::codeobj::CodeObj;
 
 use crate::build_hir::HIRBuilder;
 use crate::codegen::PyCodeGenerator;
 use crate::desugar_hir::HIRDesugarer;
 use crate::error::{CompileError, CompileErrors, CompileWarnings};
-use crate::hir::Expr;
+use crate::hir::{Expr, HIR};
 use crate::link_hir::HIRLinker;
 use crate::module::SharedCompilerResource;
 use crate::varinfo::VarInfo;
+use crate::writer::ArtifactWriter;
 
 /// * registered as global -> Global
 /// * defined in the toplevel scope (and called in the inner scope) -> Global
@@ -163,14 +168,34 @@ impl Runnable for Compiler {
     }
 
     fn exec(&mut self) -> Result<ExitStatus, Self::Errs> {
-        let path = self.cfg.dump_pyc_path();
         let src = self.cfg.input.read();
-        let warns = self
-            .compile_and_dump_as_pyc(path, src, "exec")
-            .map_err(|eart| {
+        let warns = if self.cfg.dumps_to_stdout() {
+            let arti = self.compile(src, "exec").map_err(|eart| {
                 eart.warns.write_all_stderr();
                 eart.errors
             })?;
+            let bytes = arti.object.into_pyc_bytes(self.cfg.py_magic_num);
+            let mut out = stdout().lock();
+            out.write_all(&bytes)
+                .and_then(|_| out.flush())
+                .expect("failed to write the .pyc to stdout");
+            arti.warns
+        } else if self.cfg.with_py {
+            let py_path = self.cfg.dump_path().with_extension("py");
+            let pyc_path = self.cfg.cache_pyc_path();
+            self.compile_and_dump_with_script(py_path, pyc_path, src, "exec")
+                .map_err(|eart| {
+                    eart.warns.write_all_stderr();
+                    eart.errors
+                })?
+        } else {
+            let path = self.cfg.dump_pyc_path();
+            self.compile_and_dump_as_pyc(path, src, "exec")
+                .map_err(|eart| {
+                    eart.warns.write_all_stderr();
+                    eart.errors
+                })?
+        };
         warns.write_all_stderr();
         Ok(ExitStatus::compile_passed(warns.len()))
     }
@@ -183,6 +208,22 @@ impl Runnable for Compiler {
         arti.warns.write_all_stderr();
         Ok(arti.object.code_info(Some(self.code_generator.py_version)))
     }
+
+    fn complete(&self, code: &str) -> Vec<String> {
+        self.completion_candidates(code)
+    }
+
+    fn exec_command(&mut self, command: &str) -> Option<Result<String, Self::Errs>> {
+        self.builder.exec_command(command)
+    }
+
+    fn module_names(&self) -> Vec<String> {
+        self.builder.module_names()
+    }
+
+    fn remove_defs(&mut self, names: &[String]) {
+        self.builder.remove_defs(names)
+    }
 }
 
 impl ContextProvider for Compiler {
@@ -199,6 +240,32 @@ impl ContextProvider for Compiler {
     }
 }
 
+impl Buildable<CodeObj> for Compiler {
+    fn inherit(cfg: ErgConfig, shared: SharedCompilerResource) -> Self {
+        let mod_name = Str::from(cfg.input.file_stem());
+        Self {
+            shared: shared.clone(),
+            builder: HIRBuilder::new_with_cache(cfg.copy(), mod_name, shared),
+            code_generator: PyCodeGenerator::new(cfg.copy()),
+            cfg,
+        }
+    }
+    fn build(
+        &mut self,
+        src: String,
+        mode: &str,
+    ) -> Result<CompleteArtifact<CodeObj>, IncompleteArtifact<CodeObj>> {
+        self.compile(src, mode)
+            .map_err(|err| IncompleteArtifact::new(None, err.errors, err.warns))
+    }
+    fn pop_context(&mut self) -> Option<ModuleContext> {
+        self.builder.pop_context()
+    }
+    fn get_context(&self) -> Option<&ModuleContext> {
+        self.builder.get_context()
+    }
+}
+
 impl Compiler {
     pub fn compile_and_dump_as_pyc<P: AsRef<Path>>(
         &mut self,
@@ -207,10 +274,36 @@ impl Compiler {
         mode: &str,
     ) -> Result<CompileWarnings, ErrorArtifact> {
         let arti = self.compile(src, mode)?;
-        arti.object
-            .dump_as_pyc(pyc_path, self.cfg.py_magic_num)
-            .expect("failed to dump a .pyc file (maybe permission denied)");
-        Ok(arti.warns)
+        let pyc = arti.object.into_pyc_bytes(self.cfg.py_magic_num);
+        match ArtifactWriter::new(&self.cfg).write(pyc_path.as_ref(), &pyc) {
+            Ok(()) => Ok(arti.warns),
+            Err(err) => Err(ErrorArtifact::new(CompileErrors::from(err), arti.warns)),
+        }
+    }
+
+    /// Writes the script to `py_path` and the code object to `pyc_path` as a hash-based .pyc of the script,
+    /// so Python loads the .pyc only while the script is unmodified.
+    pub fn compile_and_dump_with_script<P: AsRef<Path>, Q: AsRef<Path>>(
+        &mut self,
+        py_path: P,
+        pyc_path: Q,
+        src: String,
+        mode: &str,
+    ) -> Result<CompileWarnings, ErrorArtifact> {
+        let arti = self.compile_with_script(src, mode)?;
+        let (code, script) = arti.object;
+        let pyc = code.into_hash_based_pyc_bytes(self.cfg.py_magic_num, script.code.as_bytes());
+        let writer = ArtifactWriter::new(&self.cfg);
+        let written = pyc_path
+            .as_ref()
+            .parent()
+            .map_or(Ok(()), |dir| writer.create_dir_all(dir))
+            .and_then(|_| writer.write(py_path.as_ref(), script.code.as_bytes()))
+            .and_then(|_| writer.write(pyc_path.as_ref(), &pyc));
+        match written {
+            Ok(()) => Ok(arti.warns),
+            Err(err) => Err(ErrorArtifact::new(CompileErrors::from(err), arti.warns)),
+        }
     }
 
     pub fn eval_compile_and_dump_as_pyc<P: AsRef<Path>>(
@@ -221,9 +314,11 @@ impl Compiler {
     ) -> Result<CompleteArtifact<Option<Expr>>, ErrorArtifact> {
         let arti = self.eval_compile(src, mode)?;
         let (code, last) = arti.object;
-        code.dump_as_pyc(pyc_path, self.cfg.py_magic_num)
-            .expect("failed to dump a .pyc file (maybe permission denied)");
-        Ok(CompleteArtifact::new(last, arti.warns))
+        let pyc = code.into_pyc_bytes(self.cfg.py_magic_num);
+        match ArtifactWriter::new(&self.cfg).write(pyc_path.as_ref(), &pyc) {
+            Ok(()) => Ok(CompleteArtifact::new(last, arti.warns)),
+            Err(err) => Err(ErrorArtifact::new(CompileErrors::from(err), arti.warns)),
+        }
     }
 
     pub fn compile(
@@ -233,12 +328,32 @@ impl Compiler {
     ) -> Result<CompleteArtifact<CodeObj>, ErrorArtifact> {
         log!(info "the compiling process has started.");
         let arti = self.build_link_desugar_optimize(src, mode)?;
-        let codeobj = self.code_generator.emit(arti.object);
+        let codeobj = TIMINGS.measure(Phase::CodeGen, self.cfg.input.path(), || {
+            self.code_generator.emit(arti.object)
+        });
+        self.check_cancelled()?;
         log!(info "code object:\n{}", codeobj.code_info(Some(self.code_generator.py_version)));
         log!(info "the compiling process has completed");
         Ok(CompleteArtifact::new(codeobj, arti.warns))
     }
 
+    /// Generates the code object and the Python script from the same (linked, desugared and optimized) HIR,
+    /// so that the script behaves like the code object (`erg compile --with-py`).
+    pub fn compile_with_script(
+        &mut self,
+        src: String,
+        mode: &str,
+    ) -> Result<CompleteArtifact<(CodeObj, PyScript)>, ErrorArtifact> {
+        let arti = self.build_link_desugar_optimize(src, mode)?;
+        let path = self.cfg.input.path();
+        let (codeobj, script) = TIMINGS.measure(Phase::CodeGen, path, || {
+            let script = ScriptGenerator::new().transpile(arti.object.clone());
+            (self.code_generator.emit(arti.object), script)
+        });
+        self.check_cancelled()?;
+        Ok(CompleteArtifact::new((codeobj, script), arti.warns))
+    }
+
     pub fn compile_module(&mut self) -> Result<CompleteArtifact<CodeObj>, ErrorArtifact> {
         let src = self.cfg.input.read();
         self.compile(src, "exec")
@@ -252,12 +367,33 @@ impl Compiler {
         log!(info "the compiling process has started.");
         let arti = self.build_link_desugar_optimize(src, mode)?;
         let last = arti.object.module.last().cloned();
-        let codeobj = self.code_generator.emit(arti.object);
+        let codeobj = TIMINGS.measure(Phase::CodeGen, self.cfg.input.path(), || {
+            self.code_generator.emit(arti.object)
+        });
+        self.check_cancelled()?;
         log!(info "code object:\n{}", codeobj.code_info(Some(self.code_generator.py_version)));
         log!(info "the compiling process has completed");
         Ok(CompleteArtifact::new((codeobj, last), arti.warns))
     }
 
+    /// Same as `compile`, but the HIR (from which the code object is generated) is also returned.
+    pub fn compile_with_hir(
+        &mut self,
+        src: String,
+        mode: &str,
+    ) -> Result<CompleteArtifact<(CodeObj, HIR)>, ErrorArtifact> {
+        log!(info "the compiling process has started.");
+        let arti = self.build_link_desugar_optimize(src, mode)?;
+        let hir = arti.object.clone();
+        let codeobj = TIMINGS.measure(Phase::CodeGen, self.cfg.input.path(), || {
+            self.code_generator.emit(arti.object)
+        });
+        self.check_cancelled()?;
+        log!(info "code object:\n{}", codeobj.code_info(Some(self.code_generator.py_version)));
+        log!(info "the compiling process has completed");
+        Ok(CompleteArtifact::new((codeobj, hir), arti.warns))
+    }
+
     fn build_link_desugar_optimize(
         &mut self,
         src: String,
@@ -265,12 +401,46 @@ impl Compiler {
     ) -> Result<CompleteArtifact, ErrorArtifact> {
         let artifact = self.builder.build(src, mode)?;
         let linker = HIRLinker::new(&self.cfg, &self.shared.mod_cache);
-        let hir = linker.link(artifact.object);
-        let hir = HIRDesugarer::desugar(hir);
-        let hir = HIROptimizer::optimize(self.cfg.clone(), self.shared.clone(), hir);
+        let hir = self.link_desugar_optimize(&linker, artifact.object)?;
         Ok(CompleteArtifact::new(hir, artifact.warns))
     }
 
+    fn link_desugar_optimize(&self, linker: &HIRLinker, hir: HIR) -> Result<HIR, ErrorArtifact> {
+        let path = self.cfg.input.path();
+        let hir = TIMINGS.measure(Phase::Link, path, || linker.link(hir));
+        let hir = TIMINGS.measure(Phase::Desugar, path, || HIRDesugarer::desugar(hir));
+        let hir = TIMINGS.measure(Phase::Optimize, path, || {
+            HIROptimizer::optimize(self.cfg.clone(), self.shared.clone(), hir)
+        });
+        self.check_cancelled()?;
+        Ok(hir)
+    }
+
+    /// Generates the code object of a module that has already been checked (e.g. by `PackageChecker`).
+    /// The imported modules are linked from the cache, and are kept in it (see `BatchCompiler`).
+    pub fn compile_hir(&mut self, hir: HIR) -> Result<CodeObj, ErrorArtifact> {
+        let linker = HIRLinker::new(&self.cfg, &self.shared.mod_cache).retaining_mods();
+        let hir = self.link_desugar_optimize(&linker, hir)?;
+        let codeobj = TIMINGS.measure(Phase::CodeGen, self.cfg.input.path(), || {
+            self.code_generator.emit(hir)
+        });
+        self.check_cancelled()?;
+        Ok(codeobj)
+    }
+
+    /// A cancelled compilation produces no artifact (even if the code generation has been completed).
+    fn check_cancelled(&self) -> Result<(), ErrorArtifact> {
+        if self.cfg.cancel.is_cancelled() {
+            let err = CompileError::cancelled(self.cfg.input.clone());
+            Err(ErrorArtifact::new(
+                CompileErrors::from(err),
+                CompileWarnings::empty(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn initialize_generator(&mut self) {
         self.code_generator.initialize();
     }
@@ -3,6 +3,7 @@ use std::option::Option; // conflicting to Type::Option
 
 use erg_common::consts::DEBUG_MODE;
 use erg_common::dict::Dict;
+use erg_common::profile::PROFILER;
 use erg_common::style::colors::DEBUG_ERROR;
 use erg_common::traits::StructuralEq;
 use erg_common::{assume_unreachable, log};
@@ -113,6 +114,7 @@ impl Context {
 
     /// lhs :> rhs ?
     pub(crate) fn supertype_of(&self, lhs: &Type, rhs: &Type) -> bool {
+        let _scope = PROFILER.scope("supertype_of");
         let res = match Self::cheap_supertype_of(lhs, rhs) {
             (Absolutely, judge) => judge,
             (Maybe, judge) => {
@@ -368,10 +370,17 @@ impl Context {
                     .zip(rs.var_params.as_ref())
                     .map(|(l, r)| self.subtype_of(l.typ(), r.typ()))
                     .unwrap_or(true);
+                let kw_var_params_judge = ls
+                    .kw_var_params
+                    .as_ref()
+                    .zip(rs.kw_var_params.as_ref())
+                    .map(|(l, r)| self.subtype_of(l.typ(), r.typ()))
+                    .unwrap_or(true);
                 same_params_len
                     && return_t_judge
                     && non_defaults_judge
                     && var_params_judge
+                    && kw_var_params_judge
                     && default_check() // contravariant
             }
             // ?T(<: Int) :> ?U(:> Nat)
@@ -657,17 +666,10 @@ impl Context {
                 };
                 self.sub_unify(&inst, l, &(), None).is_ok()
             }
-            // Int or Str :> Str or Int == (Int :> Str && Str :> Int) || (Int :> Int && Str :> Str) == true
-            (Or(l_1, l_2), Or(r_1, r_2)) => {
-                if l_1.is_union_type() && self.supertype_of(l_1, rhs) {
-                    return true;
-                }
-                if l_2.is_union_type() && self.supertype_of(l_2, rhs) {
-                    return true;
-                }
-                (self.supertype_of(l_1, r_1) && self.supertype_of(l_2, r_2))
-                    || (self.supertype_of(l_1, r_2) && self.supertype_of(l_2, r_1))
-            }
+            // Int or Str :> Str or Int == (Int or Str :> Str) && (Int or Str :> Int) == true
+            // The operands may not correspond (e.g. (Int or Str) or NoneType :> Str or (NoneType or Int)),
+            // so the rhs is split first
+            (Or(_, _), Or(r_1, r_2)) => self.supertype_of(lhs, r_1) && self.supertype_of(lhs, r_2),
             // not Nat :> not Int == true
             (Not(l), Not(r)) => self.subtype_of(l, r),
             // (Int or Str) :> Nat == Int :> Nat || Str :> Nat == true
@@ -675,17 +677,8 @@ impl Context {
             (Or(l_or, r_or), rhs) => self.supertype_of(l_or, rhs) || self.supertype_of(r_or, rhs),
             // Int :> (Nat or Str) == Int :> Nat && Int :> Str == false
             (lhs, Or(l_or, r_or)) => self.supertype_of(lhs, l_or) && self.supertype_of(lhs, r_or),
-            (And(l_1, l_2), And(r_1, r_2)) => {
-                if l_1.is_intersection_type() && self.supertype_of(l_1, rhs) {
-                    return true;
-                }
-                if l_2.is_intersection_type() && self.supertype_of(l_2, rhs) {
-                    return true;
-                }
-                (self.supertype_of(l_1, r_1) && self.supertype_of(l_2, r_2))
-                    || (self.supertype_of(l_1, r_2) && self.supertype_of(l_2, r_1))
-            }
             // (Num and Show) :> Show == false
+            // (Eq and Show) :> (Show and Num) and Eq == (Eq :> ...) && (Show :> ...) == true
             (And(l_and, r_and), rhs) => {
                 self.supertype_of(l_and, rhs) && self.supertype_of(r_and, rhs)
             }
@@ -1496,13 +1489,11 @@ impl Context {
                 (self.is_super_pred_of(l1, l2) && self.is_super_pred_of(r1, r2))
                     || (self.is_super_pred_of(l1, r2) && self.is_super_pred_of(r1, l2))
             }
-            (Pred::Or(l1, r1), Pred::Or(l2, r2)) => {
-                (self.is_super_pred_of(l1, l2) && self.is_super_pred_of(r1, r2))
-                    || (self.is_super_pred_of(l1, r2) && self.is_super_pred_of(r1, l2))
-            }
             (lhs, Pred::And(l, r)) => {
                 self.is_super_pred_of(lhs, l) || self.is_super_pred_of(lhs, r)
             }
+            // the operands of the `or`s may not correspond (e.g. {(I == 0 or I == 1) or I == 2} :> {(I == 2 or I == 1) or I == 0}),
+            // so the rhs is split first
             (lhs, Pred::Or(l, r)) => self.is_super_pred_of(lhs, l) && self.is_super_pred_of(lhs, r),
             (Pred::Or(l, r), rhs) => self.is_super_pred_of(l, rhs) || self.is_super_pred_of(r, rhs),
             (Pred::And(l, r), rhs) => {
@@ -1,3 +1,4 @@
+use std::cell::Cell;
 use std::mem;
 use std::ops::Drop;
 
@@ -17,12 +18,13 @@ use erg_parser::ast::Set as AstSet;
 use erg_parser::ast::*;
 use erg_parser::desugar::Desugarer;
 use erg_parser::token::{Token, TokenKind};
+use erg_parser::visitor::{walk_call, Visitor};
 
 use crate::ty::constructors::{
     array_t, dict_t, mono, named_free_var, poly, proj, proj_call, ref_, ref_mut, refinement, set_t,
     subr_t, tp_enum, tuple_t, v_enum,
 };
-use crate::ty::free::{Constraint, FreeTyVar, HasLevel};
+use crate::ty::free::{Constraint, FreeKind, FreeTyVar, HasLevel};
 use crate::ty::typaram::{OpKind, TyParam};
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{ConstSubr, HasType, Predicate, SubrKind, Type, UserConstSubr, ValueArgs};
@@ -30,6 +32,7 @@ use crate::ty::{ConstSubr, HasType, Predicate, SubrKind, Type, UserConstSubr, Va
 use crate::context::instantiate_spec::ParamKind;
 use crate::context::{ClassDefType, Context, ContextKind, RegistrationMode};
 use crate::error::{EvalError, EvalErrors, EvalResult, SingleEvalResult};
+use crate::expand::is_comptime_call;
 
 use super::instantiate::TyVarCache;
 use Type::{Failure, Never, Subr};
@@ -53,6 +56,7 @@ pub fn type_from_token_kind(kind: TokenKind) -> Type {
         NatLit | BinLit | OctLit | HexLit => Type::Nat,
         IntLit => Type::Int,
         RatioLit => Type::Ratio,
+        FloatLit => Type::Float,
         StrLit | DocComment => Type::Str,
         BoolLit => Type::Bool,
         NoneLit => Type::NoneType,
@@ -92,6 +96,80 @@ fn op_to_name(op: OpKind) -> &'static str {
     }
 }
 
+/// The maximum number of user-defined subroutine calls in a single compile-time evaluation.
+const CONST_EVAL_FUEL: usize = 10_000;
+/// The maximum nesting depth of user-defined subroutine calls in a compile-time evaluation.
+const CONST_EVAL_MAX_DEPTH: usize = 64;
+
+thread_local! {
+    /// (current depth of calls, remaining fuel)
+    static CONST_EVAL_STATE: Cell<(usize, usize)> = const { Cell::new((0, CONST_EVAL_FUEL)) };
+}
+
+/// Consumes the fuel of compile-time evaluation while a user-defined subroutine is being called.
+/// The fuel is refilled when a new (outermost) evaluation starts.
+struct ConstCallGuard;
+
+impl Drop for ConstCallGuard {
+    fn drop(&mut self) {
+        CONST_EVAL_STATE.with(|state| {
+            let (depth, fuel) = state.get();
+            state.set((depth - 1, fuel));
+        });
+    }
+}
+
+impl ConstCallGuard {
+    fn enter() -> Option<Self> {
+        CONST_EVAL_STATE.with(|state| {
+            let (depth, fuel) = state.get();
+            let fuel = if depth == 0 { CONST_EVAL_FUEL } else { fuel };
+            if fuel == 0 || depth >= CONST_EVAL_MAX_DEPTH {
+                return None;
+            }
+            state.set((depth + 1, fuel - 1));
+            Some(Self)
+        })
+    }
+
+    /// Exhausts the fuel so that the ongoing evaluation stops as soon as possible.
+    fn exhaust() {
+        CONST_EVAL_STATE.with(|state| {
+            let (depth, _) = state.get();
+            state.set((depth, 0));
+        });
+    }
+}
+
+/// Finds a recursive call of `name` that is always evaluated,
+/// i.e. not in a branch of `if` nor in a lambda.
+struct UnguardedSelfCallFinder<'n> {
+    name: &'n str,
+    found: Option<Location>,
+}
+
+impl<'a> Visitor<'a> for UnguardedSelfCallFinder<'_> {
+    fn visit_call(&mut self, call: &'a Call) {
+        if self.found.is_some() {
+            return;
+        }
+        match call.obj.get_name().map(|name| &name[..]) {
+            Some(name) if call.attr_name.is_none() && name == self.name => {
+                self.found = Some(call.loc());
+            }
+            // only the condition is always evaluated
+            Some("if") if call.attr_name.is_none() => {
+                if let Some(cond) = call.args.pos_args().first() {
+                    self.visit_expr(&cond.expr);
+                }
+            }
+            _ => walk_call(self, call),
+        }
+    }
+
+    fn visit_lambda(&mut self, _lambda: &'a Lambda) {}
+}
+
 #[derive(Debug)]
 pub struct Substituter<'c> {
     ctx: &'c Context,
@@ -99,10 +177,13 @@ pub struct Substituter<'c> {
     #[allow(unused)]
     st: Type,
     child: Option<Box<Substituter<'c>>>,
+    /// the type variables linked by the unification of `st` and `qt`
+    undoable_links: Vec<TyParam>,
 }
 
 impl Drop for Substituter<'_> {
     fn drop(&mut self) {
+        Self::commit_links(&self.undoable_links);
         Self::undo_substitute_typarams(&self.qt);
     }
 }
@@ -114,6 +195,7 @@ impl<'c> Substituter<'c> {
             qt,
             st,
             child: None,
+            undoable_links: vec![],
         }
     }
 
@@ -257,7 +339,10 @@ impl<'c> Substituter<'c> {
         } else {
             qt
         };
-        if let Err(errs) = self.ctx.undoable_sub_unify(&st, &qt, &(), None) {
+        if let Err(errs) =
+            self.ctx
+                .undoable_sub_unify(&st, &qt, &(), None, &mut self.undoable_links)
+        {
             log!(err "{errs}");
         }
         Ok(())
@@ -325,12 +410,40 @@ impl<'c> Substituter<'c> {
         } else {
             qt
         };
-        if let Err(errs) = self.ctx.undoable_sub_unify(&st, &qt, &(), None) {
+        if let Err(errs) =
+            self.ctx
+                .undoable_sub_unify(&st, &qt, &(), None, &mut self.undoable_links)
+        {
             log!(err "{errs}");
         }
         Ok(())
     }
 
+    /// The unification results are kept (they are the inference results), but cannot be undone anymore
+    fn commit_links(links: &[TyParam]) {
+        for link in links {
+            match link {
+                TyParam::FreeVar(fv) if fv.is_undoable_linked() => {
+                    let t = fv.crack().clone();
+                    fv.replace(FreeKind::Linked(t));
+                }
+                TyParam::Type(t) => Self::commit_type_link(t),
+                _ => {}
+            }
+        }
+    }
+
+    fn commit_type_link(t: &Type) {
+        match t {
+            Type::FreeVar(fv) if fv.is_undoable_linked() => {
+                let t = fv.crack().clone();
+                fv.replace(FreeKind::Linked(t));
+            }
+            Type::Refinement(refine) => Self::commit_type_link(&refine.t),
+            _ => {}
+        }
+    }
+
     fn undo_substitute_typarams(substituted_q: &Type) {
         for tp in substituted_q.typarams().into_iter() {
             Self::undo_substitute_typaram(tp);
@@ -491,6 +604,14 @@ impl Context {
         let lhs = self.eval_const_expr(&bin.args[0])?;
         let rhs = self.eval_const_expr(&bin.args[1])?;
         let op = self.try_get_op_kind_from_token(&bin.op)?;
+        if matches!(op, Div | FloorDiv | Mod) && rhs.is_zero() {
+            return Err(EvalErrors::from(EvalError::zero_division_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                bin.args[1].loc(),
+                self.caused_by(),
+            )));
+        }
         self.eval_bin(op, lhs, rhs)
     }
 
@@ -515,6 +636,24 @@ impl Context {
     }
 
     fn eval_const_call(&self, call: &Call) -> EvalResult<ValueObj> {
+        if let (true, [arg]) = (is_comptime_call(call), call.args.pos_args()) {
+            return self.eval_const_expr(&arg.expr);
+        }
+        if let Some(attr_name) = &call.attr_name {
+            let obj = self.eval_const_expr(&call.obj)?;
+            let args = self.eval_args(&call.args)?;
+            return self.eval_const_method_call(obj, attr_name, args, call.loc());
+        }
+        match call.obj.get_name().map(|name| &name[..]) {
+            Some(name @ ("if" | "len")) if self.rec_get_const_obj(name).is_none() => {
+                return if name == "if" {
+                    self.eval_const_if(call)
+                } else {
+                    self.eval_const_len(call)
+                };
+            }
+            _ => {}
+        }
         let tp = self.tp_eval_const_call(call)?;
         ValueObj::try_from(tp).map_err(|_| {
             EvalErrors::from(EvalError::not_const_expr(
@@ -526,6 +665,124 @@ impl Context {
         })
     }
 
+    /// `if cond, do x, do y`: only the selected branch is evaluated.
+    fn eval_const_if(&self, call: &Call) -> EvalResult<ValueObj> {
+        let args = call.args.pos_args();
+        let Some(cond) = args.first() else {
+            return Err(EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                call.loc(),
+                self.caused_by(),
+            )));
+        };
+        let branch = match self.eval_const_expr(&cond.expr)? {
+            ValueObj::Bool(true) => args.get(1),
+            ValueObj::Bool(false) => args.get(2),
+            _ => {
+                return Err(EvalErrors::from(EvalError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    cond.loc(),
+                    self.caused_by(),
+                )))
+            }
+        };
+        match branch.map(|arg| &arg.expr) {
+            // `do x`
+            Some(Expr::Lambda(lambda)) if lambda.sig.params.is_empty() => {
+                if lambda.body.len() == 1 {
+                    self.eval_const_expr(lambda.body.first().unwrap())
+                } else {
+                    // HACK: should avoid cloning
+                    let mut branch_ctx = Context::instant(
+                        Str::ever("<if>"),
+                        self.cfg.clone(),
+                        0,
+                        self.shared.clone(),
+                        self.clone(),
+                    );
+                    branch_ctx.eval_const_block(&lambda.body)
+                }
+            }
+            Some(other) => self.eval_const_expr(other),
+            None => Ok(ValueObj::None),
+        }
+    }
+
+    fn eval_const_len(&self, call: &Call) -> EvalResult<ValueObj> {
+        let len = match self.eval_args(&call.args)?.pos_args.as_slice() {
+            [ValueObj::Str(s)] => s.chars().count(),
+            [ValueObj::Array(elems) | ValueObj::Tuple(elems)] => elems.len(),
+            [ValueObj::Set(elems)] => elems.len(),
+            [ValueObj::Dict(dict)] => dict.len(),
+            _ => {
+                return Err(EvalErrors::from(EvalError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    call.loc(),
+                    self.caused_by(),
+                )))
+            }
+        };
+        Ok(ValueObj::Nat(len as u64))
+    }
+
+    /// Evaluates pure methods of built-in values, e.g. `"a".upper()`.
+    fn eval_const_method_call(
+        &self,
+        obj: ValueObj,
+        attr_name: &Identifier,
+        args: ValueArgs,
+        loc: Location,
+    ) -> EvalResult<ValueObj> {
+        let res = match (&obj, &attr_name.inspect()[..], args.pos_args.as_slice()) {
+            (ValueObj::Str(s), "upper", []) => ValueObj::Str(Str::from(s.to_uppercase())),
+            (ValueObj::Str(s), "lower", []) => ValueObj::Str(Str::from(s.to_lowercase())),
+            (ValueObj::Str(s), "strip", []) => ValueObj::Str(Str::rc(s.trim())),
+            (ValueObj::Str(s), "replace", [ValueObj::Str(old), ValueObj::Str(new)]) => {
+                ValueObj::Str(Str::from(s.replace(&old[..], new)))
+            }
+            (ValueObj::Str(s), "startswith", [ValueObj::Str(prefix)]) => {
+                ValueObj::Bool(s.starts_with(&prefix[..]))
+            }
+            (ValueObj::Str(s), "endswith", [ValueObj::Str(suffix)]) => {
+                ValueObj::Bool(s.ends_with(&suffix[..]))
+            }
+            (ValueObj::Str(s), "split", [ValueObj::Str(sep)]) if !sep.is_empty() => {
+                let elems = s
+                    .split(&sep[..])
+                    .map(|elem| ValueObj::Str(Str::rc(elem)))
+                    .collect::<Vec<_>>();
+                ValueObj::Array(ArcArray::from(elems))
+            }
+            (ValueObj::Str(sep), "join", [ValueObj::Array(elems)]) => {
+                let mut strs = vec![];
+                for elem in elems.iter() {
+                    let ValueObj::Str(s) = elem else {
+                        return Err(EvalErrors::from(EvalError::not_const_expr(
+                            self.cfg.input.clone(),
+                            line!() as usize,
+                            loc,
+                            self.caused_by(),
+                        )));
+                    };
+                    strs.push(&s[..]);
+                }
+                ValueObj::Str(Str::from(strs.join(sep)))
+            }
+            _ => {
+                return Err(EvalErrors::from(EvalError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    self.caused_by(),
+                )))
+            }
+        };
+        Ok(res)
+    }
+
     fn tp_eval_const_call(&self, call: &Call) -> EvalResult<TyParam> {
         if let Expr::Accessor(acc) = call.obj.as_ref() {
             match acc {
@@ -588,6 +845,18 @@ impl Context {
     fn call(&self, subr: ConstSubr, args: ValueArgs, loc: Location) -> EvalResult<TyParam> {
         match subr {
             ConstSubr::User(user) => {
+                let Some(_guard) = ConstCallGuard::enter() else {
+                    ConstCallGuard::exhaust();
+                    return Err(EvalErrors::from(EvalError::comptime_eval_limit_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc,
+                        self.caused_by(),
+                        &user.name,
+                        CONST_EVAL_FUEL,
+                        CONST_EVAL_MAX_DEPTH,
+                    )));
+                };
                 // HACK: should avoid cloning
                 let mut subr_ctx = Context::instant(
                     user.name.clone(),
@@ -597,18 +866,42 @@ impl Context {
                     self.clone(),
                 );
                 // TODO: var_args
-                for (arg, sig) in args
-                    .pos_args
-                    .into_iter()
-                    .zip(user.params.non_defaults.iter())
-                {
+                let mut pos_args = args.pos_args.into_iter();
+                for (arg, sig) in pos_args.by_ref().zip(user.params.non_defaults.iter()) {
+                    let name = VarName::from_str(sig.inspect().unwrap().clone());
+                    subr_ctx.consts.insert(name, arg);
+                }
+                for (arg, sig) in pos_args.zip(user.params.defaults.iter()) {
                     let name = VarName::from_str(sig.inspect().unwrap().clone());
                     subr_ctx.consts.insert(name, arg);
                 }
                 for (name, arg) in args.kw_args.into_iter() {
                     subr_ctx.consts.insert(VarName::from_str(name), arg);
                 }
-                subr_ctx.eval_const_block(&user.block()).map(TyParam::value)
+                for sig in user.params.defaults.iter() {
+                    let name = VarName::from_str(sig.inspect().unwrap().clone());
+                    if !subr_ctx.consts.contains_key(&name) {
+                        let default = self.eval_const_expr(&sig.default_val)?;
+                        subr_ctx.consts.insert(name, default);
+                    }
+                }
+                let name = user.name.clone();
+                let block = user.block();
+                let mut finder = UnguardedSelfCallFinder {
+                    name: &name,
+                    found: None,
+                };
+                finder.visit_block(&block);
+                if let Some(rec_loc) = finder.found {
+                    return Err(EvalErrors::from(EvalError::unguarded_recursion_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        rec_loc,
+                        self.caused_by(),
+                        &name,
+                    )));
+                }
+                subr_ctx.eval_const_block(&block).map(TyParam::value)
             }
             ConstSubr::Builtin(builtin) => builtin.call(args, self).map_err(|mut e| {
                 if e.0.loc.is_unknown() {
@@ -822,6 +1115,19 @@ impl Context {
             )?;
             default_params.push(pt);
         }
+        let kw_var_params = if let Some(p) = lambda.sig.params.kw_var_params.as_ref() {
+            let pt = self.instantiate_param_ty(
+                p,
+                None,
+                &mut tmp_tv_cache,
+                RegistrationMode::Normal,
+                ParamKind::KwParams,
+                false,
+            )?;
+            Some(pt)
+        } else {
+            None
+        };
         // HACK: should avoid cloning
         let mut lambda_ctx = Context::instant(
             Str::ever("<lambda>"),
@@ -836,6 +1142,7 @@ impl Context {
             non_default_params.clone(),
             var_params,
             default_params.clone(),
+            kw_var_params,
             return_t,
         );
         let block =
@@ -870,9 +1177,40 @@ impl Context {
         })
     }
 
+    /// Only the values whose string representations are the same as Python's are embedded.
+    fn eval_const_str_interp(&self, interp: &StrInterp) -> EvalResult<ValueObj> {
+        let mut lits = interp.str_lits().into_iter();
+        let ValueObj::Str(head) = self.eval_lit(&lits.next().unwrap())? else {
+            unreachable!()
+        };
+        let mut res = head.to_string();
+        for (expr, lit) in interp.exprs.iter().zip(lits) {
+            match self.eval_const_expr(expr)? {
+                ValueObj::Str(s) => res.push_str(&s),
+                v @ (ValueObj::Int(_) | ValueObj::Nat(_) | ValueObj::Bool(_) | ValueObj::None) => {
+                    res.push_str(&v.to_string())
+                }
+                _ => {
+                    return Err(EvalErrors::from(EvalError::not_const_expr(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        expr.loc(),
+                        self.caused_by(),
+                    )))
+                }
+            }
+            let ValueObj::Str(s) = self.eval_lit(&lit)? else {
+                unreachable!()
+            };
+            res.push_str(&s);
+        }
+        Ok(ValueObj::Str(Str::from(res)))
+    }
+
     pub(crate) fn eval_const_expr(&self, expr: &Expr) -> EvalResult<ValueObj> {
         match expr {
             Expr::Literal(lit) => self.eval_lit(lit),
+            Expr::StrInterp(interp) => self.eval_const_str_interp(interp),
             Expr::Accessor(acc) => self.eval_const_acc(acc),
             Expr::BinOp(bin) => self.eval_const_bin(bin),
             Expr::UnaryOp(unary) => self.eval_const_unary(unary),
@@ -903,6 +1241,7 @@ impl Context {
             // TODO: ClassDef, PatchDef
             Expr::Def(def) => self.eval_const_def(def),
             Expr::Literal(lit) => self.eval_lit(lit),
+            Expr::StrInterp(interp) => self.eval_const_str_interp(interp),
             Expr::Accessor(acc) => self.eval_const_acc(acc),
             Expr::BinOp(bin) => self.eval_const_bin(bin),
             Expr::UnaryOp(unary) => self.eval_const_unary(unary),
@@ -967,6 +1306,13 @@ impl Context {
                     line!(),
                 ))
             }),
+            Pow => lhs.try_pow(rhs).ok_or_else(|| {
+                EvalErrors::from(EvalError::unreachable(
+                    self.cfg.input.clone(),
+                    fn_name!(),
+                    line!(),
+                ))
+            }),
             Gt => lhs.try_gt(rhs).ok_or_else(|| {
                 EvalErrors::from(EvalError::unreachable(
                     self.cfg.input.clone(),
@@ -1394,12 +1740,20 @@ impl Context {
                         Err((_, errs)) => return Err((Subr(subr), errs)),
                     };
                 }
+                if let Some(kw_var_args) = subr.kw_var_params.as_mut() {
+                    *kw_var_args.typ_mut() =
+                        match self.eval_t_params(mem::take(kw_var_args.typ_mut()), level, t_loc) {
+                            Ok(t) => t,
+                            Err((_, errs)) => return Err((Subr(subr), errs)),
+                        };
+                }
                 match self.eval_t_params(*subr.return_t, level, t_loc) {
                     Ok(return_t) => Ok(subr_t(
                         subr.kind,
                         subr.non_default_params,
                         subr.var_params.map(|v| *v),
                         subr.default_params,
+                        subr.kw_var_params.map(|v| *v),
                         return_t,
                     )),
                     Err((_, errs)) => {
@@ -1408,6 +1762,7 @@ impl Context {
                             subr.non_default_params,
                             subr.var_params.map(|v| *v),
                             subr.default_params,
+                            subr.kw_var_params.map(|v| *v),
                             Failure,
                         );
                         Err((subr, errs))
@@ -1958,7 +2313,7 @@ impl Context {
                 let new_fv = fv.detach();
                 let name = new_fv.unbound_name().unwrap();
                 if let Some(t) = tv_cache.get_tyvar(&name) {
-                    t.clone()
+                    t
                 } else {
                     let tv = Type::FreeVar(new_fv);
                     let varname = VarName::from_str(name.clone());
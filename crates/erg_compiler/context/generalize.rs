@@ -1,6 +1,7 @@
 use std::mem;
 
 use erg_common::consts::DEBUG_MODE;
+use erg_common::profile::PROFILER;
 use erg_common::set::Set;
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
@@ -16,7 +17,7 @@ use crate::ty::{HasType, Predicate, SubrType, Type};
 
 use crate::context::{Context, Variance};
 use crate::error::{TyCheckError, TyCheckErrors, TyCheckResult};
-use crate::{feature_error, hir};
+use crate::hir;
 
 use Type::*;
 use Variance::*;
@@ -140,7 +141,7 @@ impl Generalizer {
     /// ```
     fn generalize_t(&mut self, free_type: Type, uninit: bool) -> Type {
         match free_type {
-            FreeVar(fv) if fv.is_linked() => self.generalize_t(fv.unsafe_crack().clone(), uninit),
+            FreeVar(fv) if fv.is_linked() => self.generalize_t(fv.get_linked().unwrap(), uninit),
             FreeVar(fv) if fv.is_generalized() => Type::FreeVar(fv),
             // TODO: Polymorphic generalization
             FreeVar(fv) if fv.level().unwrap() > self.level => {
@@ -192,6 +193,10 @@ impl Generalizer {
                 subr.default_params.iter_mut().for_each(|d_param| {
                     *d_param.typ_mut() = self.generalize_t(mem::take(d_param.typ_mut()), uninit);
                 });
+                if let Some(kw_var_args) = &mut subr.kw_var_params {
+                    *kw_var_args.typ_mut() =
+                        self.generalize_t(mem::take(kw_var_args.typ_mut()), uninit);
+                }
                 self.variance = Covariant;
                 let return_t = self.generalize_t(*subr.return_t, uninit);
                 self.qnames = self.qnames.difference(&qnames);
@@ -200,6 +205,7 @@ impl Generalizer {
                     subr.non_default_params,
                     subr.var_params.map(|x| *x),
                     subr.default_params,
+                    subr.kw_var_params.map(|x| *x),
                     return_t,
                 )
             }
@@ -555,7 +561,13 @@ impl<'c, 'q, 'l, L: Locational> Dereferencer<'c, 'q, 'l, L> {
                             Ok(ty)
                         }
                         Err(errs) => {
-                            Type::FreeVar(fv).destructive_link(&Never);
+                            // a generalized type variable (e.g. `R` of a recursive function) is resolved to `Failure`
+                            // so that the error is reported only once
+                            if fv.is_generalized() {
+                                Type::FreeVar(fv).link_to_failure();
+                            } else {
+                                Type::FreeVar(fv).destructive_link(&Never);
+                            }
                             Err(errs)
                         }
                     }
@@ -862,6 +874,7 @@ impl Context {
     /// Quantification occurs only once in function types.
     /// Therefore, this method is called only once at the top level, and `generalize_t_inner` is called inside.
     pub(crate) fn generalize_t(&self, free_type: Type) -> Type {
+        let _scope = PROFILER.scope("generalize_t");
         let mut generalizer = Generalizer::new(self.level);
         let maybe_unbound_t = generalizer.generalize_t(free_type, false);
         if maybe_unbound_t.is_subr() && maybe_unbound_t.has_qvar() {
@@ -1045,6 +1058,24 @@ impl Context {
         Ok(())
     }
 
+    fn resolve_comprehension_clauses_t(
+        &self,
+        generators: &mut [(hir::Identifier, hir::Expr)],
+        guards: &mut [hir::Expr],
+        qnames: &Set<Str>,
+    ) -> TyCheckResult<()> {
+        for (var, iter) in generators.iter_mut() {
+            self.resolve_expr_t(iter, qnames)?;
+            let t = mem::take(&mut var.vi.t);
+            let mut dereferencer = Dereferencer::simple(self, qnames, var);
+            var.vi.t = dereferencer.deref_tyvar(t)?;
+        }
+        for guard in guards.iter_mut() {
+            self.resolve_expr_t(guard, qnames)?;
+        }
+        Ok(())
+    }
+
     fn resolve_expr_t(&self, expr: &mut hir::Expr, qnames: &Set<Str>) -> TyCheckResult<()> {
         match expr {
             hir::Expr::Lit(_) => Ok(()),
@@ -1081,13 +1112,18 @@ impl Context {
                     self.resolve_expr_t(&mut arr.len, qnames)?;
                     Ok(())
                 }
-                other => feature_error!(
-                    TyCheckErrors,
-                    TyCheckError,
-                    self,
-                    other.loc(),
-                    "resolve types of array comprehension"
-                ),
+                hir::Array::Comprehension(arr) => {
+                    let t = mem::take(&mut arr.t);
+                    let mut dereferencer = Dereferencer::simple(self, qnames, arr);
+                    arr.t = dereferencer.deref_tyvar(t)?;
+                    self.resolve_comprehension_clauses_t(
+                        &mut arr.generators,
+                        &mut arr.guards,
+                        qnames,
+                    )?;
+                    self.resolve_expr_t(&mut arr.elem, qnames)?;
+                    Ok(())
+                }
             },
             hir::Expr::Tuple(tuple) => match tuple {
                 hir::Tuple::Normal(tup) => {
@@ -1118,6 +1154,18 @@ impl Context {
                     self.resolve_expr_t(&mut st.len, qnames)?;
                     Ok(())
                 }
+                hir::Set::Comprehension(st) => {
+                    let t = mem::take(&mut st.t);
+                    let mut dereferencer = Dereferencer::simple(self, qnames, st);
+                    st.t = dereferencer.deref_tyvar(t)?;
+                    self.resolve_comprehension_clauses_t(
+                        &mut st.generators,
+                        &mut st.guards,
+                        qnames,
+                    )?;
+                    self.resolve_expr_t(&mut st.elem, qnames)?;
+                    Ok(())
+                }
             },
             hir::Expr::Dict(dict) => match dict {
                 hir::Dict::Normal(dic) => {
@@ -1130,13 +1178,19 @@ impl Context {
                     }
                     Ok(())
                 }
-                other => feature_error!(
-                    TyCheckErrors,
-                    TyCheckError,
-                    self,
-                    other.loc(),
-                    "resolve types of dict comprehension"
-                ),
+                hir::Dict::Comprehension(dic) => {
+                    let t = mem::take(&mut dic.t);
+                    let mut dereferencer = Dereferencer::simple(self, qnames, dic);
+                    dic.t = dereferencer.deref_tyvar(t)?;
+                    self.resolve_comprehension_clauses_t(
+                        &mut dic.generators,
+                        &mut dic.guards,
+                        qnames,
+                    )?;
+                    self.resolve_expr_t(&mut dic.key, qnames)?;
+                    self.resolve_expr_t(&mut dic.value, qnames)?;
+                    Ok(())
+                }
             },
             hir::Expr::Record(record) => {
                 let t = mem::take(&mut record.t);
@@ -1216,6 +1270,9 @@ impl Context {
                 let mut dereferencer = Dereferencer::simple(self, &qnames, lambda);
                 lambda.t = dereferencer.deref_tyvar(t)?;
                 self.resolve_params_t(&mut lambda.params, &qnames)?;
+                for chunk in lambda.guard.iter_mut().flat_map(|guard| guard.iter_mut()) {
+                    self.resolve_expr_t(chunk, &qnames)?;
+                }
                 for chunk in lambda.body.iter_mut() {
                     self.resolve_expr_t(chunk, &qnames)?;
                 }
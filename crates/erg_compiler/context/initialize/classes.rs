@@ -888,7 +888,11 @@ impl Context {
             Immutable,
             Visibility::BUILTIN_PUBLIC,
         );
-        let str_getitem_t = fn1_kw_met(Str, kw(KW_IDX, Nat | poly(RANGE, vec![ty_tp(Int)])), Str);
+        let str_getitem_t = fn1_kw_met(
+            Str,
+            kw(KW_IDX, Nat | poly(RANGE, vec![ty_tp(Int)]) | mono(SLICE)),
+            Str,
+        );
         str_.register_builtin_erg_impl(
             FUNDAMENTAL_GETITEM,
             str_getitem_t,
@@ -1020,6 +1024,15 @@ impl Context {
             Visibility::BUILTIN_PUBLIC,
         );
         type_.register_trait(Type, type_eq);
+        let mut type_show = Self::builtin_methods(Some(mono(SHOW)), 1);
+        type_show.register_builtin_py_impl(
+            TO_STR,
+            fn0_met(Type, Str),
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_STR),
+        );
+        type_.register_trait(Type, type_show);
         let mut class_type = Self::builtin_mono_class(CLASS_TYPE, 2);
         class_type.register_superclass(Type, &type_);
         class_type.register_marker_trait(self, mono(NAMED)).unwrap();
@@ -1249,6 +1262,8 @@ impl Context {
         );
         // __getitem__: |T, N|(self: [T; N], _: {I: Nat | I <= N}) -> T
         //              and (self: [T; N], _: Range(Int)) -> [T; _]
+        //              and (self: [T; N], _: Slice) -> [T; _]
+        //              and (self: [T; N], _: Int) -> T (checked at runtime)
         let array_getitem_t = (fn1_kw_met(
             array_t(T.clone(), N.clone()),
             anon(input.clone()),
@@ -1257,7 +1272,11 @@ impl Context {
             array_t(T.clone(), N.clone()),
             anon(poly(RANGE, vec![ty_tp(Int)])),
             unknown_len_array_t(T.clone()),
-        ))
+        ) & fn1_kw_met(
+            array_t(T.clone(), N.clone()),
+            anon(mono(SLICE)),
+            unknown_len_array_t(T.clone()),
+        ) & fn1_kw_met(array_t(T.clone(), N.clone()), anon(Int), T.clone()))
         .quantify();
         let get_item = ValueObj::Subr(ConstSubr::Builtin(BuiltinConstSubr::new(
             FUNDAMENTAL_GETITEM,
@@ -1372,9 +1391,34 @@ impl Context {
         /* Slice */
         let mut slice = Self::builtin_mono_class(SLICE, 3);
         slice.register_superclass(Obj, &obj);
-        slice.register_builtin_erg_impl(KW_START, Int, Immutable, Visibility::BUILTIN_PUBLIC);
-        slice.register_builtin_erg_impl(KW_STOP, Int, Immutable, Visibility::BUILTIN_PUBLIC);
-        slice.register_builtin_erg_impl(KW_STEP, Int, Immutable, Visibility::BUILTIN_PUBLIC);
+        slice.register_builtin_erg_impl(
+            KW_START,
+            Int | NoneType,
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+        );
+        slice.register_builtin_erg_impl(
+            KW_STOP,
+            Int | NoneType,
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+        );
+        slice.register_builtin_erg_impl(
+            KW_STEP,
+            Int | NoneType,
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+        );
+        /* Error */
+        let mut error = Self::builtin_mono_class(ERROR, 2);
+        error.register_superclass(Obj, &obj);
+        error.register_builtin_erg_impl(MESSAGE, Str, Immutable, Visibility::BUILTIN_PUBLIC);
+        error.register_builtin_erg_impl(
+            FUNDAMENTAL_CALL,
+            func1(Str, mono(ERROR)),
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+        );
         /* GenericSet */
         let mut generic_set = Self::builtin_mono_class(GENERIC_SET, 1);
         generic_set.register_superclass(Obj, &obj);
@@ -1477,6 +1521,15 @@ impl Context {
             Visibility::BUILTIN_PUBLIC,
         );
         generic_dict.register_trait(g_dict_t.clone(), generic_dict_eq);
+        let mut generic_dict_show = Self::builtin_methods(Some(mono(SHOW)), 1);
+        generic_dict_show.register_builtin_py_impl(
+            TO_STR,
+            fn0_met(g_dict_t.clone(), Str),
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_STR),
+        );
+        generic_dict.register_trait(g_dict_t.clone(), generic_dict_show);
         let D = mono_q_tp(TY_D, instanceof(mono(GENERIC_DICT)));
         // .get: _: T -> T or None
         let dict_get_t = fn1_met(g_dict_t.clone(), T.clone(), or(T.clone(), NoneType)).quantify();
@@ -1586,7 +1639,8 @@ impl Context {
                 mono(BYTES),
                 kw(KW_IDX, poly(RANGE, vec![ty_tp(Int)])),
                 mono(BYTES),
-            );
+            )
+            & fn1_kw_met(mono(BYTES), kw(KW_IDX, mono(SLICE)), mono(BYTES));
         bytes.register_builtin_erg_impl(
             FUNDAMENTAL_GETITEM,
             bytes_getitem_t,
@@ -1616,6 +1670,15 @@ impl Context {
             Visibility::BUILTIN_PUBLIC,
         );
         generic_tuple.register_trait(mono(GENERIC_TUPLE), tuple_eq);
+        let mut tuple_show = Self::builtin_methods(Some(mono(SHOW)), 1);
+        tuple_show.register_builtin_py_impl(
+            TO_STR,
+            fn0_met(mono(GENERIC_TUPLE), Str),
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_STR),
+        );
+        generic_tuple.register_trait(mono(GENERIC_TUPLE), tuple_show);
         let Ts = mono_q_tp(TY_TS, instanceof(array_t(Type, N.clone())));
         // Ts <: GenericArray
         let _tuple_t = poly(TUPLE, vec![Ts.clone()]);
@@ -1688,6 +1751,15 @@ impl Context {
         /* record */
         let mut record = Self::builtin_mono_class(RECORD, 2);
         record.register_superclass(Obj, &obj);
+        let mut record_show = Self::builtin_methods(Some(mono(SHOW)), 1);
+        record_show.register_builtin_py_impl(
+            TO_STR,
+            fn0_met(mono(RECORD), Str),
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_STR),
+        );
+        record.register_trait(mono(RECORD), record_show);
         /* Or (true or type) */
         let or_t = poly(OR, vec![ty_tp(L), ty_tp(R)]);
         let mut or = Self::builtin_poly_class(OR, vec![PS::t_nd(TY_L), PS::t_nd(TY_R)], 2);
@@ -2448,6 +2520,7 @@ impl Context {
         );
         self.register_builtin_type(arr_t, array_, vis.clone(), Const, Some(ARRAY));
         self.register_builtin_type(mono(SLICE), slice, vis.clone(), Const, Some(FUNC_SLICE));
+        self.register_builtin_type(mono(ERROR), error, vis.clone(), Const, Some(ERROR));
         self.register_builtin_type(
             mono(GENERIC_SET),
             generic_set,
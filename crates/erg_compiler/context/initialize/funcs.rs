@@ -43,6 +43,13 @@ impl Context {
             vec![kw(KW_MSG, Str)],
             NoneType,
         );
+        let t_assert_eq = func(
+            vec![kw(KW_LHS, T.clone()), kw(KW_RHS, T.clone())],
+            None,
+            vec![kw(KW_MSG, Str)],
+            NoneType,
+        )
+        .quantify();
         let t_bin = nd_func(vec![kw(KW_N, Int)], None, Str);
         // TODO: overload: Iterable(Int) -> Bytes
         let t_bytes = nd_func(
@@ -221,9 +228,9 @@ impl Context {
         .quantify();
         let t_round = nd_func(vec![kw(KW_NUMBER, Float)], None, Int);
         let t_slice = func(
-            vec![kw(KW_START, Int)],
+            vec![kw(KW_START, Int | NoneType)],
             None,
-            vec![kw(KW_STOP, Int), kw(KW_STEP, Int)],
+            vec![kw(KW_STOP, Int | NoneType), kw(KW_STEP, Int | NoneType)],
             mono(SLICE),
         );
         let t_sorted = nd_func(
@@ -258,6 +265,13 @@ impl Context {
         self.register_py_builtin(FUNC_ASCII, t_ascii, Some(FUNC_ASCII), 53);
         // Leave as `Const`, as it may negatively affect assert casting.
         self.register_builtin_erg_impl(FUNC_ASSERT, t_assert, Const, vis.clone());
+        self.register_builtin_py_impl(
+            FUNC_ASSERT_EQ,
+            t_assert_eq,
+            Immutable,
+            vis.clone(),
+            Some(FUNC_ASSERT_EQ),
+        );
         self.register_builtin_py_impl(FUNC_BIN, t_bin, Immutable, vis.clone(), Some(FUNC_BIN));
         self.register_builtin_py_impl(
             FUNC_BYTES,
@@ -586,6 +600,11 @@ impl Context {
             None,
         ));
         self.register_builtin_const(INHERITABLE, vis.clone(), ValueObj::Subr(inheritable));
+        // compile-time decorator factory (not applied at runtime)
+        let deprecated_t = nd_func(vec![kw(KW_REASON, Str)], None, func1(Obj, Obj));
+        self.register_builtin_erg_impl(DEPRECATED, deprecated_t, Immutable, vis.clone());
+        let available_t = nd_func(vec![kw(KW_PYTHON, Str)], None, func1(Obj, Obj));
+        self.register_builtin_erg_impl(AVAILABLE, available_t, Immutable, vis.clone());
         // TODO: register Del function object
         let t_del = nd_func(vec![kw(KW_OBJ, Obj)], None, NoneType);
         self.register_builtin_erg_impl(DEL, t_del, Immutable, vis.clone());
@@ -287,6 +287,8 @@ const FUNC: &str = "Func";
 const QUANTIFIED: &str = "Quantified";
 const QUANTIFIED_FUNC: &str = "QuantifiedFunc";
 const SLICE: &str = "Slice";
+const ERROR: &str = "Error";
+const MESSAGE: &str = "message";
 const FUNC_OBJECT: &str = "object";
 const FUNC_INT: &str = "int";
 const FUNC_INT__: &str = "int__";
@@ -321,6 +323,7 @@ const FUNC_ALL: &str = "all";
 const FUNC_ANY: &str = "any";
 const FUNC_ASCII: &str = "ascii";
 const FUNC_ASSERT: &str = "assert";
+const FUNC_ASSERT_EQ: &str = "assert_eq";
 const FUNC_BIN: &str = "bin";
 const FUNC_BYTES: &str = "bytes";
 const FUNC_CHR: &str = "chr";
@@ -356,6 +359,8 @@ const FUNC_TODO: &str = "todo";
 const SUBSUME: &str = "Subsume";
 const INHERIT: &str = "Inherit";
 const INHERITABLE: &str = "Inheritable";
+const DEPRECATED: &str = "Deprecated";
+const AVAILABLE: &str = "Available";
 const DEL: &str = "Del";
 const PATCH: &str = "Patch";
 const STRUCTURAL: &str = "Structural";
@@ -484,6 +489,8 @@ const KW_OBJECT: &str = "object";
 const KW_OBJECTS: &str = "objects";
 const KW_TEST: &str = "test";
 const KW_MSG: &str = "msg";
+const KW_REASON: &str = "reason";
+const KW_PYTHON: &str = "python";
 const KW_STR: &str = "str";
 const KW_I: &str = "i";
 const KW_SRC: &str = "src";
@@ -20,6 +20,7 @@ impl Context {
         };
         let T = mono_q("T", instanceof(Type));
         let U = mono_q("U", instanceof(Type));
+        let t_argv = proc(vec![], None, vec![], array_t(Str, TyParam::erased(Nat)));
         let t_dir = proc(
             vec![kw("obj", ref_(Obj))],
             None,
@@ -117,6 +118,7 @@ impl Context {
             U,
         )
         .quantify();
+        self.register_builtin_py_impl("argv!", t_argv, Immutable, vis.clone(), Some("argv"));
         self.register_builtin_py_impl("dir!", t_dir, Immutable, vis.clone(), Some("dir"));
         self.register_py_builtin("print!", t_print, Some("print"), 81);
         self.register_builtin_py_impl("id!", t_id, Immutable, vis.clone(), Some("id"));
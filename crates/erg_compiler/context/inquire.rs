@@ -1,4 +1,5 @@
 // (type) getters & validators
+use std::borrow::Cow;
 use std::option::Option; // conflicting to Type::Option
 use std::path::{Path, PathBuf};
 
@@ -6,6 +7,7 @@ use erg_common::consts::{ERG_MODE, PYTHON_MODE};
 use erg_common::error::{ErrorCore, Location, SubMessage};
 use erg_common::io::Input;
 use erg_common::levenshtein;
+use erg_common::profile::PROFILER;
 use erg_common::set::Set;
 use erg_common::traits::{Locational, NoTypeDisplay, Stream};
 use erg_common::triple::Triple;
@@ -17,7 +19,9 @@ use erg_common::{
 use erg_parser::ast::{self, Identifier, VarName};
 use erg_parser::token::Token;
 
-use crate::ty::constructors::{anon, fn_met, free_var, func, mono, poly, proc, proj, ref_, subr_t};
+use crate::ty::constructors::{
+    anon, fn_met, free_var, func, mono, poly, proc, proj, ref_, str_dict_t, subr_t,
+};
 use crate::ty::free::{Constraint, FreeTyParam};
 use crate::ty::typaram::TyParam;
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
@@ -175,8 +179,8 @@ impl Context {
                 // e.g. ident.t: {Int}
                 if let Type::Refinement(refine) = ident.ref_t() {
                     if let Predicate::Equal { rhs, .. } = refine.pred.as_ref() {
-                        if let Ok(t) = <&Type>::try_from(rhs) {
-                            if let Some(ctxs) = self.get_nominal_super_type_ctxs(t) {
+                        if let Ok(t) = Type::try_from(rhs.clone()) {
+                            if let Some(ctxs) = self.get_nominal_super_type_ctxs(&t) {
                                 return Ok(ctxs);
                             }
                         }
@@ -402,7 +406,10 @@ impl Context {
                 ParamKind::NonDefault,
                 false,
             )?;
-            union_pat_t = self.union(&union_pat_t, &rhs);
+            // guarded arms may not match, so they do not contribute to exhaustiveness
+            if lambda.guard.is_none() {
+                union_pat_t = self.union(&union_pat_t, &rhs);
+            }
             arm_ts.push(rhs);
         }
         // NG: expr_t: Nat, union_pat_t: {1, 2}
@@ -429,7 +436,7 @@ impl Context {
         let Some(mut return_t) = branch_ts.get(0).and_then(|branch| {
             branch.typ()
                 .return_t()
-                .cloned()
+                .map(Cow::into_owned)
         }) else {
             return Err(TyCheckErrors::from(TyCheckError::args_missing_error(
                 self.cfg.input.clone(),
@@ -442,7 +449,10 @@ impl Context {
         };
         for arg_t in branch_ts.iter().skip(1) {
             // TODO: handle unwrap errors
-            return_t = self.union(&return_t, arg_t.typ().return_t().unwrap_or(&Type::Never));
+            return_t = self.union(
+                &return_t,
+                arg_t.typ().return_t().as_deref().unwrap_or(&Type::Never),
+            );
         }
         let param_ty = ParamTy::Pos(match_target_expr_t.clone());
         let param_ts = [vec![param_ty], branch_ts.to_vec()].concat();
@@ -651,7 +661,7 @@ impl Context {
             Triple::Ok(vi) => {
                 if let Some(self_t) = vi.t.self_t() {
                     match self
-                        .sub_unify(obj.ref_t(), self_t, obj, Some(&"self".into()))
+                        .sub_unify(obj.ref_t(), &self_t, obj, Some(&"self".into()))
                         .map_err(|mut e| e.remove(0))
                     {
                         Ok(_) => {}
@@ -858,6 +868,7 @@ impl Context {
                     vec![],
                     Some(ParamTy::Pos(ref_(Obj))),
                     vec![],
+                    None,
                     Failure,
                 )),
                 ..VarInfo::default()
@@ -900,6 +911,7 @@ impl Context {
                     .iter()
                     .map(|kw| ParamTy::kw(kw.keyword.content.clone(), kw.expr.t()))
                     .collect(),
+                None,
                 Obj,
             );
             for ty in intersecs.iter() {
@@ -1118,6 +1130,19 @@ impl Context {
                     .search_method_info(obj, attr_name, pos_args, kw_args, input, namespace);
             }
         }
+        if self
+            .get_nullable_attr_owner(obj.ref_t(), attr_name.inspect())
+            .is_some()
+        {
+            return Err(TyCheckError::possibly_none_attr_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                obj.loc(),
+                namespace.name.to_string(),
+                attr_name.inspect(),
+                obj.ref_t(),
+            ));
+        }
         Err(TyCheckError::no_attr_error(
             self.cfg.input.clone(),
             line!() as usize,
@@ -1198,7 +1223,7 @@ impl Context {
                 )
             })?;
         let op = hir::Expr::Accessor(hir::Accessor::private(symbol, t));
-        self.get_call_t(&op, &None, args, &[], input, namespace)
+        self.get_call_t(&op, &None, args, None, &[], None, input, namespace)
             .map_err(|(_, errs)| {
                 let hir::Expr::Accessor(hir::Accessor::Ident(op_ident)) = op else {
                     return errs;
@@ -1242,7 +1267,7 @@ impl Context {
                 )
             })?;
         let op = hir::Expr::Accessor(hir::Accessor::private(symbol, vi));
-        self.get_call_t(&op, &None, args, &[], input, namespace)
+        self.get_call_t(&op, &None, args, None, &[], None, input, namespace)
             .map_err(|(_, errs)| {
                 let hir::Expr::Accessor(hir::Accessor::Ident(op_ident)) = op else {
                     return errs;
@@ -1334,18 +1359,28 @@ impl Context {
     /// ↓ don't substitute `Int` to `self`
     /// substitute_call(obj: Int, instance: ((self: Int, other: Int) -> Int), [1, 2]) => instance: (Int, Int) -> Int
     /// ```
+    /// `var_args` and `kw_var` are the unpacked arguments (`*xs`, `**kws`), whose lengths are unknown
+    #[allow(clippy::too_many_arguments)]
     fn substitute_call(
         &self,
         obj: &hir::Expr,
         attr_name: &Option<Identifier>,
         instance: &Type,
         pos_args: &[hir::PosArg],
+        var_args: Option<&hir::PosArg>,
         kw_args: &[hir::KwArg],
+        kw_var: Option<&hir::PosArg>,
     ) -> TyCheckResult<SubstituteResult> {
         match instance {
-            Type::FreeVar(fv) if fv.is_linked() => {
-                self.substitute_call(obj, attr_name, &fv.crack(), pos_args, kw_args)
-            }
+            Type::FreeVar(fv) if fv.is_linked() => self.substitute_call(
+                obj,
+                attr_name,
+                &fv.crack(),
+                pos_args,
+                var_args,
+                kw_args,
+                kw_var,
+            ),
             Type::FreeVar(fv) => {
                 if let Some(sub) = fv.get_sub() {
                     if !self.subtype_of(&sub, &mono("GenericCallable")) {
@@ -1356,11 +1391,14 @@ impl Context {
                         instance.coerce();
                         if instance.is_quantified_subr() {
                             let instance = self.instantiate(instance.clone(), obj)?;
-                            self.substitute_call(obj, attr_name, &instance, pos_args, kw_args)?;
+                            self.substitute_call(
+                                obj, attr_name, &instance, pos_args, var_args, kw_args, kw_var,
+                            )?;
                             return Ok(SubstituteResult::Coerced(instance));
                         } else if get_hash(instance) != hash {
-                            return self
-                                .substitute_call(obj, attr_name, instance, pos_args, kw_args);
+                            return self.substitute_call(
+                                obj, attr_name, instance, pos_args, var_args, kw_args, kw_var,
+                            );
                         }
                     }
                 }
@@ -1384,15 +1422,15 @@ impl Context {
                     };
                     let ret_t = free_var(self.level, Constraint::new_type_of(Type));
                     let non_default_params = pos_args.iter().map(|a| anon(a.expr.t())).collect();
-                    let subr_t = subr_t(kind, non_default_params, None, vec![], ret_t);
+                    let subr_t = subr_t(kind, non_default_params, None, vec![], None, ret_t);
                     self.occur(&subr_t, instance, obj)?;
                     instance.destructive_link(&subr_t);
                     Ok(SubstituteResult::Ok)
                 }
             }
-            Type::Refinement(refine) => {
-                self.substitute_call(obj, attr_name, &refine.t, pos_args, kw_args)
-            }
+            Type::Refinement(refine) => self.substitute_call(
+                obj, attr_name, &refine.t, pos_args, var_args, kw_args, kw_var,
+            ),
             // instance must be instantiated
             Type::Quantified(_) => unreachable_error!(TyCheckErrors, TyCheckError, self),
             Type::Subr(subr) => {
@@ -1414,7 +1452,9 @@ impl Context {
                     obj.clone()
                 };
                 let params_len = subr.non_default_params.len() + subr.default_params.len();
-                if (params_len < pos_args.len() || params_len < pos_args.len() + kw_args.len())
+                if (params_len < pos_args.len()
+                    || (params_len < pos_args.len() + kw_args.len()
+                        && subr.kw_var_params.is_none()))
                     && subr.var_params.is_none()
                 {
                     return Err(self.gen_too_many_args_error(&callee, subr, pos_args, kw_args));
@@ -1435,7 +1475,7 @@ impl Context {
                 let non_default_params_len = non_default_params.len();
                 let mut nth = 1;
                 if pos_args.len() >= non_default_params_len {
-                    let (non_default_args, var_args) = pos_args.split_at(non_default_params_len);
+                    let (non_default_args, rest_args) = pos_args.split_at(non_default_params_len);
                     for (nd_arg, nd_param) in non_default_args.iter().zip(non_default_params) {
                         if let Err(mut es) = self.substitute_pos_arg(
                             &callee,
@@ -1450,11 +1490,12 @@ impl Context {
                         nth += 1;
                     }
                     if let Some(var_param) = subr.var_params.as_ref() {
-                        for var_arg in var_args.iter() {
+                        for var_arg in rest_args.iter() {
                             if let Err(mut es) = self.substitute_var_arg(
                                 &callee,
                                 attr_name,
                                 &var_arg.expr,
+                                var_arg.expr.ref_t(),
                                 nth,
                                 var_param,
                             ) {
@@ -1462,8 +1503,27 @@ impl Context {
                             }
                             nth += 1;
                         }
+                        // `f(*xs)`: every element of `xs` is passed to `var_param`
+                        if let Some(var_arg) = var_args {
+                            match self.get_unpacked_elem_t(&var_arg.expr) {
+                                Ok(elem_t) => {
+                                    if let Err(mut es) = self.substitute_var_arg(
+                                        &callee,
+                                        attr_name,
+                                        &var_arg.expr,
+                                        &elem_t,
+                                        nth,
+                                        var_param,
+                                    ) {
+                                        errs.append(&mut es);
+                                    }
+                                }
+                                Err(mut es) => errs.append(&mut es),
+                            }
+                            nth += 1;
+                        }
                     } else {
-                        for (arg, pt) in var_args.iter().zip(subr.default_params.iter()) {
+                        for (arg, pt) in rest_args.iter().zip(subr.default_params.iter()) {
                             if let Err(mut es) = self.substitute_pos_arg(
                                 &callee,
                                 attr_name,
@@ -1531,6 +1591,37 @@ impl Context {
                         }
                         nth += 1;
                     }
+                    // `f(*xs)`: the remaining positional parameters are filled with the elements of `xs`
+                    if let Some(var_arg) = var_args {
+                        match self.get_unpacked_elem_t(&var_arg.expr) {
+                            Ok(elem_t) => {
+                                let rest_nd_params = params
+                                    .take(non_default_params_len - pos_args.len())
+                                    .filter(|pt| {
+                                        !pt.name().is_some_and(|name| passed_params.contains(name))
+                                    })
+                                    .collect::<Vec<_>>();
+                                for pt in rest_nd_params {
+                                    if let Err(mut es) = self.substitute_var_arg(
+                                        &callee,
+                                        attr_name,
+                                        &var_arg.expr,
+                                        &elem_t,
+                                        nth,
+                                        pt,
+                                    ) {
+                                        errs.append(&mut es);
+                                    }
+                                    let name = pt.name().cloned().unwrap_or_else(|| {
+                                        Str::from(format!("({} param)", ordinal_num(nth)))
+                                    });
+                                    passed_params.insert(name);
+                                    nth += 1;
+                                }
+                            }
+                            Err(mut es) => errs.append(&mut es),
+                        }
+                    }
                     let missing_params = subr
                         .non_default_params
                         .iter()
@@ -1542,7 +1633,8 @@ impl Context {
                         })
                         .filter(|pt| !passed_params.contains(pt))
                         .collect::<Vec<_>>();
-                    if !missing_params.is_empty() {
+                    // `f(**kws)`: the missing parameters may be passed by `kws`
+                    if !missing_params.is_empty() && kw_var.is_none() {
                         return Err(TyCheckErrors::from(TyCheckError::args_missing_error(
                             self.cfg.input.clone(),
                             line!() as usize,
@@ -1553,6 +1645,18 @@ impl Context {
                         )));
                     }
                 }
+                // `f(**kws)`: `kws` must be `{Str: T}` if `f` has `**kwargs: T`
+                if let Some((kw_var, kw_var_params)) = kw_var.zip(subr.kw_var_params.as_deref()) {
+                    let dict_t = str_dict_t(kw_var_params.typ().clone());
+                    if let Err(mut es) = self.sub_unify(
+                        kw_var.expr.ref_t(),
+                        &dict_t,
+                        &kw_var.expr,
+                        kw_var_params.name(),
+                    ) {
+                        errs.append(&mut es);
+                    }
+                }
                 if errs.is_empty() {
                     /*if subr.has_qvar() {
                         panic!("{subr} has qvar");
@@ -1563,17 +1667,22 @@ impl Context {
                 }
             }
             Type::Failure => Ok(SubstituteResult::Ok),
-            _ => self.substitute_dunder_call(obj, attr_name, instance, pos_args, kw_args),
+            _ => self.substitute_dunder_call(
+                obj, attr_name, instance, pos_args, var_args, kw_args, kw_var,
+            ),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn substitute_dunder_call(
         &self,
         obj: &hir::Expr,
         attr_name: &Option<Identifier>,
         instance: &Type,
         pos_args: &[hir::PosArg],
+        var_args: Option<&hir::PosArg>,
         kw_args: &[hir::KwArg],
+        kw_var: Option<&hir::PosArg>,
     ) -> TyCheckResult<SubstituteResult> {
         let ctxs = self
             .get_singular_ctxs_by_hir_expr(obj, self)
@@ -1603,7 +1712,9 @@ impl Context {
             if let Some(call_vi) = typ_ctx.get_current_scope_var(&VarName::from_static("__call__"))
             {
                 let instance = self.instantiate_def_type(&call_vi.t)?;
-                self.substitute_call(obj, attr_name, &instance, pos_args, kw_args)?;
+                self.substitute_call(
+                    obj, attr_name, &instance, pos_args, var_args, kw_args, kw_var,
+                )?;
                 return Ok(SubstituteResult::__Call__(instance));
             }
         }
@@ -1634,15 +1745,11 @@ impl Context {
         let mut passed_args: Vec<&hir::KwArg> = vec![];
         let mut duplicated_args = vec![];
         for kw_arg in kw_args.iter() {
-            if subr_ty
-                .non_default_params
-                .iter()
-                .all(|pt| pt.name() != Some(kw_arg.keyword.inspect()))
+            if subr_ty.kw_var_params.is_none()
                 && subr_ty
-                    .var_params
-                    .as_ref()
-                    .map(|pt| pt.name() != Some(kw_arg.keyword.inspect()))
-                    .unwrap_or(true)
+                    .non_default_params
+                    .iter()
+                    .all(|pt| pt.name() != Some(kw_arg.keyword.inspect()))
                 && subr_ty
                     .default_params
                     .iter()
@@ -1669,9 +1776,14 @@ impl Context {
                 kw_args.len(),
             ))
         } else {
+            let sig = self.readable_type(Type::Subr(subr_ty.clone()));
             let unknown_arg_errors = unknown_args.into_iter().map(|arg| {
-                let similar =
-                    levenshtein::get_similar_name(subr_ty.param_names(), arg.keyword.inspect());
+                let candidates = subr_ty.kw_param_names().filter(|name| {
+                    passed_args
+                        .iter()
+                        .all(|a| &a.keyword.inspect()[..] != *name)
+                });
+                let similar = levenshtein::get_similar_name(candidates, arg.keyword.inspect());
                 TyCheckError::unexpected_kw_arg_error(
                     self.cfg.input.clone(),
                     line!() as usize,
@@ -1680,6 +1792,7 @@ impl Context {
                     self.caused_by(),
                     arg.keyword.inspect(),
                     similar,
+                    Some(&sig),
                 )
             });
             let duplicated_arg_errors = duplicated_args.into_iter().map(|arg| {
@@ -1739,6 +1852,17 @@ impl Context {
                     param_t,
                     arg_t,
                 );
+                if self.get_nullable_arg_inner(arg_t, param_t).is_some() {
+                    return TyCheckErrors::from(TyCheckError::possibly_none_arg_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        arg.loc(),
+                        self.caused_by(),
+                        &name[..],
+                        Some(nth),
+                        arg_t,
+                    ));
+                }
                 let param_t = self.readable_type(param_t.clone());
                 let arg_t = self.readable_type(arg_t.clone());
                 TyCheckErrors::new(
@@ -1767,15 +1891,16 @@ impl Context {
         Ok(())
     }
 
+    /// `arg_t` is the type of `arg` itself, or the element type if `arg` is unpacked (`*arg`)
     fn substitute_var_arg(
         &self,
         callee: &hir::Expr,
         attr_name: &Option<Identifier>,
         arg: &hir::Expr,
+        arg_t: &Type,
         nth: usize,
         param: &ParamTy,
     ) -> TyCheckResult<()> {
-        let arg_t = arg.ref_t();
         let param_t = param.typ();
         self.sub_unify(arg_t, param_t, arg, param.name())
             .map_err(|errs| {
@@ -1786,6 +1911,17 @@ impl Context {
                     callee.show_acc().unwrap_or_default()
                 };
                 let name = name + "::" + param.name().map(|s| readable_name(&s[..])).unwrap_or("");
+                if self.get_nullable_arg_inner(arg_t, param_t).is_some() {
+                    return TyCheckErrors::from(TyCheckError::possibly_none_arg_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        arg.loc(),
+                        self.caused_by(),
+                        &name[..],
+                        Some(nth),
+                        arg_t,
+                    ));
+                }
                 let hint = self.get_simple_type_mismatch_hint(param_t, arg_t);
                 TyCheckErrors::new(
                     errs.into_iter()
@@ -1808,6 +1944,14 @@ impl Context {
             })
     }
 
+    /// `*xs: Iterable(T)` => `T`
+    fn get_unpacked_elem_t(&self, arg: &hir::Expr) -> TyCheckResult<Type> {
+        let elem_t = free_var(self.level, Constraint::new_type_of(Type));
+        let iterable = poly("Iterable", vec![TyParam::t(elem_t.clone())]);
+        self.sub_unify(arg.ref_t(), &iterable, arg, None)?;
+        Ok(elem_t)
+    }
+
     fn substitute_kw_arg(
         &self,
         callee: &hir::Expr,
@@ -1829,11 +1973,13 @@ impl Context {
                 arg.keyword.inspect(),
             )));
         }
+        // unknown keyword arguments are passed to `**kwargs` if any
         if let Some(pt) = subr_ty
             .non_default_params
             .iter()
             .chain(subr_ty.default_params.iter())
             .find(|pt| pt.name().as_ref() == Some(&kw_name))
+            .or(subr_ty.kw_var_params.as_deref())
         {
             let param_t = pt.typ();
             passed_params.insert(kw_name.clone());
@@ -1846,6 +1992,17 @@ impl Context {
                         callee.show_acc().unwrap_or_default()
                     };
                     let name = name + "::" + readable_name(kw_name);
+                    if self.get_nullable_arg_inner(arg_t, param_t).is_some() {
+                        return TyCheckErrors::from(TyCheckError::possibly_none_arg_error(
+                            self.cfg.input.clone(),
+                            line!() as usize,
+                            arg.loc(),
+                            self.caused_by(),
+                            &name[..],
+                            Some(nth),
+                            arg_t,
+                        ));
+                    }
                     let hint = self.get_simple_type_mismatch_hint(param_t, arg_t);
                     let param_t = self.readable_type(param_t.clone());
                     let arg_t = self.readable_type(arg_t.clone());
@@ -1869,8 +2026,11 @@ impl Context {
                     )
                 })?;
         } else {
-            let similar =
-                levenshtein::get_similar_name(subr_ty.param_names(), arg.keyword.inspect());
+            let candidates = subr_ty
+                .kw_param_names()
+                .filter(|name| !passed_params.contains(*name));
+            let similar = levenshtein::get_similar_name(candidates, arg.keyword.inspect());
+            let sig = self.readable_type(Type::Subr(subr_ty.clone()));
             return Err(TyCheckErrors::from(TyCheckError::unexpected_kw_arg_error(
                 self.cfg.input.clone(),
                 line!() as usize,
@@ -1879,20 +2039,25 @@ impl Context {
                 self.caused_by(),
                 kw_name,
                 similar,
+                Some(&sig),
             )));
         }
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn get_call_t(
         &self,
         obj: &hir::Expr,
         attr_name: &Option<Identifier>,
         pos_args: &[hir::PosArg],
+        var_args: Option<&hir::PosArg>,
         kw_args: &[hir::KwArg],
+        kw_var: Option<&hir::PosArg>,
         input: &Input,
         namespace: &Context,
     ) -> Result<VarInfo, (Option<VarInfo>, TyCheckErrors)> {
+        let _scope = PROFILER.scope("get_call_t");
         if let hir::Expr::Accessor(hir::Accessor::Ident(local)) = obj {
             if local.vis().is_private() {
                 match &local.inspect()[..] {
@@ -1926,7 +2091,9 @@ impl Context {
             fmt_slice(kw_args)
         );
         let instance = match self
-            .substitute_call(obj, attr_name, &instance, pos_args, kw_args)
+            .substitute_call(
+                obj, attr_name, &instance, pos_args, var_args, kw_args, kw_var,
+            )
             .map_err(|errs| {
                 (
                     Some(VarInfo {
@@ -2015,18 +2182,45 @@ impl Context {
         }
     }
 
+    /// Same as `dir`, but each variable has the distance of its scope (0 for `self`, 1 for the outer, ...).
+    /// The builtins are the farthest.
+    fn scoped_dir(&self) -> Vec<(usize, &VarName, &VarInfo)> {
+        let mut vars = vec![];
+        let mut depth = 0;
+        let mut ctx = self;
+        loop {
+            vars.extend(
+                ctx.type_dir(ctx)
+                    .into_iter()
+                    .map(|(vn, vi)| (depth, vn, vi)),
+            );
+            depth += 1;
+            if let Some(outer) = ctx.get_outer() {
+                ctx = outer;
+            } else {
+                if let Some(builtins) = ctx.get_builtins() {
+                    vars.extend(builtins.locals.iter().map(|(vn, vi)| (depth, vn, vi)));
+                }
+                break;
+            }
+        }
+        vars
+    }
+
     pub(crate) fn get_similar_name(&self, name: &str) -> Option<&str> {
-        levenshtein::get_similar_name(
-            self.dir().into_iter().map(|(vn, _)| &vn.inspect()[..]),
+        levenshtein::get_similar_name_in_scopes(
+            self.scoped_dir()
+                .into_iter()
+                .map(|(depth, vn, _)| (depth, &vn.inspect()[..])),
             name,
         )
     }
 
     pub(crate) fn get_similar_name_and_info(&self, name: &str) -> Option<(&VarInfo, &str)> {
-        levenshtein::get_similar_name_and_some(
-            self.dir()
+        levenshtein::get_similar_name_and_some_in_scopes(
+            self.scoped_dir()
                 .into_iter()
-                .map(|(vn, vi)| (vi, &vn.inspect()[..])),
+                .map(|(depth, vn, vi)| (depth, vi, &vn.inspect()[..])),
             name,
         )
     }
@@ -2068,6 +2262,35 @@ impl Context {
         None
     }
 
+    /// `T or NoneType` => `Some(T)` if `T` has the attribute `name`
+    pub(crate) fn get_nullable_attr_owner(&self, self_t: &Type, name: &str) -> Option<Type> {
+        let inner = self_t.without_none()?;
+        let name = VarName::from_str(Str::rc(name));
+        let found = self
+            .get_nominal_super_type_ctxs(&inner)?
+            .into_iter()
+            .any(|ctx| ctx.get_current_scope_var(&name).is_some());
+        found.then_some(inner)
+    }
+
+    /// `T or NoneType` => `Some(T)` if `T <: param_t`
+    pub(crate) fn get_nullable_arg_inner(&self, arg_t: &Type, param_t: &Type) -> Option<Type> {
+        let inner = arg_t.without_none()?;
+        // `?L(<: Add(?R))` => `Add(?R)`
+        let param_t = param_t.get_super().unwrap_or_else(|| param_t.clone());
+        if param_t.is_unbound_var() || self.subtype_of(&Type::NoneType, &param_t) {
+            return None;
+        }
+        // `Int <: Add(?R)` cannot be checked without unification
+        let implemented = || {
+            let name = param_t.qual_name();
+            self.get_nominal_super_type_ctxs(&inner)
+                .is_some_and(|ctxs| ctxs.into_iter().any(|ctx| ctx.name == name))
+        };
+        (self.subtype_of(&inner, &param_t) || (!param_t.typarams().is_empty() && implemented()))
+            .then_some(inner)
+    }
+
     // Returns what kind of variance the type has for each parameter Type.
     // Invariant for types not specified
     // selfが示す型が、各パラメータTypeに対してどのような変性Varianceを持つかを返す
@@ -2578,9 +2801,9 @@ impl Context {
             return None;
         };
         if mod_t.is_erg_module() {
-            self.cfg.input.resolve_path(Path::new(&path[..]))
+            self.cfg.resolve_path(Path::new(&path[..]))
         } else if mod_t.is_py_module() {
-            self.cfg.input.resolve_decl_path(Path::new(&path[..]))
+            self.cfg.resolve_decl_path(Path::new(&path[..]))
         } else {
             None
         }
@@ -2661,7 +2884,7 @@ impl Context {
             str_namespace.push_str(namespaces.remove(0));
         }
         let path = Path::new(&str_namespace);
-        let mut path = self.cfg.input.resolve_path(path)?;
+        let mut path = self.cfg.resolve_path(path)?;
         for p in namespaces.into_iter() {
             path = Input::try_push_path(path, Path::new(p)).ok()?;
         }
@@ -2982,6 +3205,7 @@ impl Context {
                                 subr.non_default_params.clone(),
                                 subr.var_params.as_ref().map(|p| *p.clone()),
                                 subr.default_params.clone(),
+                                subr.kw_var_params.as_ref().map(|p| *p.clone()),
                                 ret_t,
                             );
                             Type::Subr(subr)
@@ -3103,10 +3327,10 @@ impl Context {
         if let Some(tp) = tmp_tv_cache.get_typaram(name) {
             Some((tp.clone(), &tmp_tv_cache.var_infos[name]))
         } else if let Some(t) = tmp_tv_cache.get_tyvar(name) {
-            Some((TyParam::t(t.clone()), &tmp_tv_cache.var_infos[name]))
+            Some((TyParam::t(t), &tmp_tv_cache.var_infos[name]))
         } else if let Some(tv_ctx) = &self.tv_cache {
             if let Some(t) = tv_ctx.get_tyvar(name) {
-                Some((TyParam::t(t.clone()), &tv_ctx.var_infos[name]))
+                Some((TyParam::t(t), &tv_ctx.var_infos[name]))
             } else {
                 tv_ctx
                     .get_typaram(name)
@@ -6,6 +6,7 @@ use erg_common::dict::Dict;
 use erg_common::enum_unwrap;
 #[allow(unused)]
 use erg_common::log;
+use erg_common::profile::PROFILER;
 use erg_common::set::Set;
 use erg_common::traits::Locational;
 use erg_common::Str;
@@ -166,8 +167,8 @@ impl TyVarCache {
         if let Some(inst) = self.tyvar_instances.get(name) {
             self.update_tyvar(inst, tv, ctx);
         } else if let Some(inst) = self.typaram_instances.get(name) {
-            if let Ok(inst) = <&Type>::try_from(inst) {
-                self.update_tyvar(inst, tv, ctx);
+            if let Ok(inst) = Type::try_from(inst.clone()) {
+                self.update_tyvar(&inst, tv, ctx);
             } else if let TyParam::FreeVar(_fv) = inst {
                 inst.destructive_link(&TyParam::t(tv.clone()));
             } else {
@@ -185,8 +186,8 @@ impl TyVarCache {
         if let Some(inst) = self.tyvar_instances.get(name) {
             self.update_tyvar(inst, tv, ctx);
         } else if let Some(inst) = self.typaram_instances.get(name) {
-            if let Ok(inst) = <&Type>::try_from(inst) {
-                self.update_tyvar(inst, tv, ctx);
+            if let Ok(inst) = Type::try_from(inst.clone()) {
+                self.update_tyvar(&inst, tv, ctx);
             } else if let TyParam::FreeVar(_fv) = inst {
                 inst.destructive_link(&TyParam::t(tv.clone()));
             } else {
@@ -228,8 +229,8 @@ impl TyVarCache {
         if let Some(inst) = self.typaram_instances.get(name) {
             self.update_typaram(inst, tp, ctx);
         } else if let Some(inst) = self.tyvar_instances.get(name) {
-            if let Ok(tv) = <&Type>::try_from(tp) {
-                self.update_tyvar(inst, tv, ctx);
+            if let Ok(tv) = Type::try_from(tp.clone()) {
+                self.update_tyvar(inst, &tv, ctx);
             } else {
                 unreachable!()
             }
@@ -252,8 +253,8 @@ impl TyVarCache {
         if let Some(inst) = self.typaram_instances.get(name) {
             self.update_typaram(inst, tp, ctx);
         } else if let Some(inst) = self.tyvar_instances.get(name) {
-            if let Ok(tv) = <&Type>::try_from(tp) {
-                self.update_tyvar(inst, tv, ctx);
+            if let Ok(tv) = Type::try_from(tp.clone()) {
+                self.update_tyvar(inst, &tv, ctx);
             } else {
                 unreachable!()
             }
@@ -268,8 +269,8 @@ impl TyVarCache {
             return;
         }
         let Ok(free_inst) = <&FreeTyParam>::try_from(inst) else {
-            if let (Ok(inst), Ok(t)) = (<&Type>::try_from(inst), <&Type>::try_from(tp)) {
-                return self.update_tyvar(inst, t, ctx);
+            if let (Ok(inst), Ok(t)) = (Type::try_from(inst.clone()), Type::try_from(tp.clone())) {
+                return self.update_tyvar(&inst, &t, ctx);
             } else {
                 todo!("{inst}");
             }
@@ -289,10 +290,10 @@ impl TyVarCache {
         self.already_appeared.contains(name)
     }
 
-    pub(crate) fn get_tyvar(&self, name: &str) -> Option<&Type> {
-        self.tyvar_instances.get(name).or_else(|| {
+    pub(crate) fn get_tyvar(&self, name: &str) -> Option<Type> {
+        self.tyvar_instances.get(name).cloned().or_else(|| {
             self.typaram_instances.get(name).and_then(|tp| {
-                <&Type>::try_from(tp)
+                Type::try_from(tp.clone())
                     .map_err(|_| {
                         log!(err "cannot convert {tp} into a type");
                     })
@@ -334,7 +335,6 @@ impl Context {
                     }
                     Ok(tp)
                 } else if let Some(t) = tmp_tv_cache.get_tyvar(&name) {
-                    let t = t.clone();
                     if let Some(fv) = t.as_free() {
                         if fv
                             .constraint()
@@ -359,7 +359,7 @@ impl Context {
                         if let Some(tp) = tv_cache.get_typaram(&name) {
                             return Ok(tp.clone());
                         } else if let Some(t) = tv_cache.get_tyvar(&name) {
-                            return Ok(TyParam::t(t.clone()));
+                            return Ok(TyParam::t(t));
                         }
                     }
                     tmp_tv_cache.push_appeared(name.clone());
@@ -503,7 +503,6 @@ impl Context {
             FreeVar(fv) if fv.is_generalized() => {
                 let (name, constr) = (fv.unbound_name().unwrap(), fv.constraint().unwrap());
                 if let Some(t) = tmp_tv_cache.get_tyvar(&name) {
-                    let t = t.clone();
                     Ok(t)
                 } else if let Some(tp) = tmp_tv_cache.get_typaram(&name) {
                     if let TyParam::Type(t) = tp {
@@ -525,7 +524,7 @@ impl Context {
                     }
                     if let Some(tv_ctx) = &self.tv_cache {
                         if let Some(t) = tv_ctx.get_tyvar(&name) {
-                            return Ok(t.clone());
+                            return Ok(t);
                         } else if let Some(tp) = tv_ctx.get_typaram(&name) {
                             if let TyParam::Type(t) = tp {
                                 return Ok(*t.clone());
@@ -565,12 +564,20 @@ impl Context {
                     *pt.typ_mut() =
                         self.instantiate_t_inner(mem::take(pt.typ_mut()), tmp_tv_cache, loc)?;
                 }
+                if let Some(kw_var_args) = subr.kw_var_params.as_mut() {
+                    *kw_var_args.typ_mut() = self.instantiate_t_inner(
+                        mem::take(kw_var_args.typ_mut()),
+                        tmp_tv_cache,
+                        loc,
+                    )?;
+                }
                 let return_t = self.instantiate_t_inner(*subr.return_t, tmp_tv_cache, loc)?;
                 let res = subr_t(
                     subr.kind,
                     subr.non_default_params,
                     subr.var_params.map(|p| *p),
                     subr.default_params,
+                    subr.kw_var_params.map(|p| *p),
                     return_t,
                 );
                 Ok(res)
@@ -667,6 +674,7 @@ impl Context {
     }
 
     pub(crate) fn instantiate(&self, quantified: Type, callee: &hir::Expr) -> TyCheckResult<Type> {
+        let _scope = PROFILER.scope("instantiate");
         match quantified {
             FreeVar(fv) if fv.is_linked() => self.instantiate(fv.crack().clone(), callee),
             And(lhs, rhs) => {
@@ -678,7 +686,7 @@ impl Context {
                 let mut tmp_tv_cache = TyVarCache::new(self.level, self);
                 let ty = self.instantiate_t_inner(*quant, &mut tmp_tv_cache, callee)?;
                 if let Some(self_t) = ty.self_t() {
-                    self.sub_unify(callee.ref_t(), self_t, callee, Some(&Str::ever("self")))?;
+                    self.sub_unify(callee.ref_t(), &self_t, callee, Some(&Str::ever("self")))?;
                 }
                 if cfg!(feature = "debug") && ty.has_qvar() {
                     panic!("{ty} has qvar")
@@ -705,7 +713,7 @@ impl Context {
                         if let Some(self_t) = l.self_t() {
                             self.sub_unify(
                                 callee.ref_t(),
-                                self_t,
+                                &self_t,
                                 callee,
                                 Some(&Str::ever("self")),
                             )?;
@@ -713,7 +721,7 @@ impl Context {
                         if let Some(self_t) = r.self_t() {
                             self.sub_unify(
                                 callee.ref_t(),
-                                self_t,
+                                &self_t,
                                 callee,
                                 Some(&Str::ever("self")),
                             )?;
@@ -754,6 +762,7 @@ impl Context {
     }
 
     pub(crate) fn instantiate_def_type(&self, typ: &Type) -> TyCheckResult<Type> {
+        let _scope = PROFILER.scope("instantiate_def_type");
         let mut tv_cache = TyVarCache::new(self.level, self);
         self.instantiate_t_inner(typ.clone(), &mut tv_cache, &())
     }
@@ -2,6 +2,7 @@ use std::option::Option; // conflicting to Type::Option
 
 #[allow(unused)]
 use erg_common::log;
+use erg_common::profile::PROFILER;
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{assume_unreachable, dict, set, try_map_mut};
@@ -302,6 +303,28 @@ impl Context {
                 }
             }
         }
+        let kw_var_args = if let Some(kw_var_args) = sig.params.kw_var_params.as_ref() {
+            let opt_decl_t = opt_decl_sig_t
+                .as_ref()
+                .and_then(|subr| subr.kw_var_params.as_ref().map(|v| v.as_ref()));
+            let pt = match self.instantiate_param_ty(
+                kw_var_args,
+                opt_decl_t,
+                &mut tmp_tv_cache,
+                mode,
+                ParamKind::KwParams,
+                false,
+            ) {
+                Ok(pt) => pt,
+                Err(es) => {
+                    errs.extend(es);
+                    ParamTy::pos_or_kw(kw_var_args.inspect().cloned(), Type::Failure)
+                }
+            };
+            Some(pt)
+        } else {
+            None
+        };
         let spec_return_t = if let Some(t_spec) = sig.return_t_spec.as_ref() {
             let opt_decl_t = opt_decl_sig_t
                 .as_ref()
@@ -329,11 +352,19 @@ impl Context {
             free_var(level, Constraint::new_type_of(Type))
         };
         // tmp_tv_cache.warn_isolated_vars(self);
-        let typ = if sig.ident.is_procedural() {
-            proc(non_defaults, var_args, defaults, spec_return_t)
+        let kind = if sig.ident.is_procedural() {
+            SubrKind::Proc
         } else {
-            func(non_defaults, var_args, defaults, spec_return_t)
+            SubrKind::Func
         };
+        let typ = subr_t(
+            kind,
+            non_defaults,
+            var_args,
+            defaults,
+            kw_var_args,
+            spec_return_t,
+        );
         if errs.is_empty() {
             Ok(typ)
         } else {
@@ -387,6 +418,14 @@ impl Context {
                     &sig.t_spec.as_ref().ok_or(sig),
                     None,
                 )?;
+            } else if kind.is_kw_params() {
+                let spec_t = str_dict_t(spec_t.clone());
+                self.sub_unify(
+                    decl_pt.typ(),
+                    &spec_t,
+                    &sig.t_spec.as_ref().ok_or(sig),
+                    None,
+                )?;
             } else {
                 self.sub_unify(
                     decl_pt.typ(),
@@ -671,6 +710,27 @@ impl Context {
                 )?;
                 Ok(t.structuralize())
             }
+            // `Optional[T]` in Python
+            "Option" | "Optional" | "typing.Optional" => {
+                let mut args = poly_spec.args.pos_args();
+                let Some(first) = args.next() else {
+                    return Err(TyCheckErrors::from(TyCheckError::args_missing_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        poly_spec.args.loc(),
+                        "Option",
+                        self.caused_by(),
+                        vec![Str::from("T")],
+                    )));
+                };
+                let t = self.instantiate_const_expr_as_type(
+                    &first.expr,
+                    None,
+                    tmp_tv_cache,
+                    not_found_is_qvar,
+                )?;
+                Ok(self.union(&t, &Type::NoneType))
+            }
             other => {
                 let Some((typ, ctx)) = self.get_type(&Str::rc(other)) else {
                     return Err(TyCheckErrors::from(TyCheckError::no_type_error(
@@ -1187,6 +1247,7 @@ impl Context {
                     lambda.nd_params,
                     lambda.var_params,
                     lambda.d_params,
+                    None,
                     return_t,
                 );
                 Ok(Type::Subr(subr))
@@ -1564,6 +1625,7 @@ impl Context {
                     non_defaults,
                     var_params,
                     defaults,
+                    None,
                     return_t,
                 ))
             }
@@ -1604,6 +1666,7 @@ impl Context {
     }
 
     pub(crate) fn instantiate_typespec(&self, t_spec: &ast::TypeSpec) -> TyCheckResult<Type> {
+        let _scope = PROFILER.scope("instantiate_typespec");
         let mut dummy_tv_cache = TyVarCache::new(self.level, self);
         self.instantiate_typespec_with_tv_cache(t_spec, &mut dummy_tv_cache)
     }
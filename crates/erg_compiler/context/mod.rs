@@ -53,6 +53,76 @@ pub trait ContextProvider {
     fn dir(&self) -> Dict<&VarName, &VarInfo>;
     fn get_receiver_ctx(&self, receiver_name: &str) -> Option<&Context>;
     fn get_var_info(&self, name: &str) -> Option<(&VarName, &VarInfo)>;
+
+    /// Returns the names that complete the last word of `code` (e.g. `["sqrt"]` for `math.sq`).
+    /// Variables, attributes of modules/types and record fields are completed.
+    fn completion_candidates(&self, code: &str) -> Vec<String> {
+        let word_start = code
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_alphanumeric() || matches!(c, '_' | '!' | '.')))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &code[word_start..];
+        let (names, prefix) = match word.rsplit_once('.') {
+            Some((receiver, prefix)) => (attr_names(self, receiver), prefix),
+            None => {
+                let names = self
+                    .dir()
+                    .into_iter()
+                    .map(|(name, _)| name.inspect().to_string());
+                (names.collect(), word)
+            }
+        };
+        let mut names = names
+            .into_iter()
+            .filter(|name| name.starts_with(prefix) && !name.starts_with('%'))
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+fn record_field_names(t: &Type) -> Vec<String> {
+    match t.derefine() {
+        Record(rec) => rec
+            .keys()
+            .filter(|field| field.vis.is_public())
+            .map(|field| field.symbol.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// `receiver` is a dotted path (e.g. `foo.bar`).
+fn attr_names<P: ContextProvider + ?Sized>(provider: &P, receiver: &str) -> Vec<String> {
+    let mut attrs = receiver.split('.');
+    let first = attrs.next().unwrap_or_default();
+    let mut fields = provider
+        .get_var_info(first)
+        .map_or(vec![], |(_, vi)| record_field_names(&vi.t));
+    let mut ctx = provider.get_receiver_ctx(first);
+    for attr in attrs {
+        fields = ctx
+            .and_then(|ctx| ctx.get_var_info(attr))
+            .map_or(vec![], |(_, vi)| record_field_names(&vi.t));
+        ctx = ctx.and_then(|ctx| ctx.get_receiver_ctx(attr));
+    }
+    let Some(ctx) = ctx else {
+        return fields;
+    };
+    // the super classes cannot be resolved from the type context (e.g. `Int` of `Nat`)
+    let super_ctxs = ctx
+        .super_classes
+        .iter()
+        .filter_map(|sup| provider.get_receiver_ctx(&sup.local_name()));
+    let attrs = [ctx]
+        .into_iter()
+        .chain(super_ctxs)
+        .flat_map(|ctx| ctx.local_dir())
+        .filter(|(_, vi)| !vi.vis.is_private())
+        .map(|(name, _)| name.inspect().to_string());
+    fields.into_iter().chain(attrs).collect()
 }
 
 const BUILTINS: &Str = &Str::ever("<builtins>");
@@ -504,6 +574,8 @@ pub struct Context {
     pub(crate) tv_cache: Option<TyVarCache>,
     pub(crate) higher_order_caller: Vec<Str>,
     pub(crate) guards: Vec<GuardType>,
+    /// locations of `?` that return from this subroutine, and the types they may return
+    pub(crate) early_returns: Vec<(Location, Type)>,
     pub(crate) erg_to_py_names: Dict<Str, Str>,
     pub(crate) level: usize,
 }
@@ -594,6 +666,37 @@ impl Context {
         ctx.get_var_info(&typ.local_name())
     }
 
+    /// Returns the traits implemented by the type named `name` (e.g. `C` of `C = Class { ... }`).
+    pub fn get_impl_traits_by_name(&self, name: &str) -> Vec<Type> {
+        let Some(ValueObj::Type(typ)) = self.rec_get_const_obj(name) else {
+            return vec![];
+        };
+        self.get_nominal_type_ctx(typ.typ())
+            .map(|(_, ctx)| ctx.super_traits.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the type named `name` (e.g. `C` of `C = Class { ... }`).
+    pub fn get_type_by_name(&self, name: &str) -> Option<Type> {
+        let Some(ValueObj::Type(typ)) = self.rec_get_const_obj(name) else {
+            return None;
+        };
+        Some(typ.typ().clone())
+    }
+
+    /// Returns the direct supertypes (classes and traits) of `typ`.
+    pub fn get_super_types(&self, typ: &Type) -> Vec<Type> {
+        self.get_nominal_type_ctx(typ)
+            .map(|(_, ctx)| {
+                ctx.super_classes
+                    .iter()
+                    .chain(ctx.super_traits.iter())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     pub fn unregister(&mut self, name: &str) -> Option<VarInfo> {
         self.mono_types.remove(name);
         self.poly_types.remove(name);
@@ -703,6 +806,7 @@ impl Context {
             patches: Dict::default(),
             higher_order_caller: vec![],
             guards: vec![],
+            early_returns: vec![],
             erg_to_py_names: Dict::default(),
             level,
         }
@@ -1137,6 +1241,23 @@ impl Context {
         self.params.retain(|(_, v)| v.t != Failure);
     }
 
+    /// Removes the definition of `name` (a variable, a type or a patch) so that it can be redefined.
+    pub(crate) fn remove_def(&mut self, name: &str) {
+        self.locals.remove(name);
+        self.decls.remove(name);
+        self.consts.remove(name);
+        self.mono_types.remove(name);
+        self.poly_types.remove(name);
+        self.patches.remove(name);
+        for pairs in self
+            .method_to_classes
+            .values_mut()
+            .chain(self.method_to_traits.values_mut())
+        {
+            pairs.retain(|pair| &pair.definition_type.local_name()[..] != name);
+        }
+    }
+
     pub fn pop(&mut self) -> Context {
         self.check_types();
         if let Some(parent) = self.outer.as_mut() {
@@ -1268,6 +1389,24 @@ impl Context {
             .and_then(|caller| ControlKind::try_from(&caller[..]).ok())
     }
 
+    /// Returns the context of the subroutine that `?` returns from.
+    /// Lambdas passed to control flow subroutines (e.g. `if!`, `for!`) and comprehensions are inlined,
+    /// so they are skipped.
+    pub(crate) fn get_mut_early_return_target(&mut self) -> Option<&mut Context> {
+        let inlined = match self.kind {
+            ContextKind::Instant | ContextKind::Dummy => true,
+            ContextKind::Func | ContextKind::Proc => self
+                .get_outer()
+                .is_some_and(|outer| outer.control_kind().is_some()),
+            _ => return None,
+        };
+        if inlined {
+            self.get_mut_outer()?.get_mut_early_return_target()
+        } else {
+            Some(self)
+        }
+    }
+
     pub(crate) fn check_types(&self) {
         if DEBUG_MODE {
             for (_, (t, ctx)) in self.poly_types.iter() {
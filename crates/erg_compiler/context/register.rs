@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt;
 use std::fs::{metadata, remove_file, File};
 use std::io::{BufRead, BufReader};
@@ -15,7 +16,7 @@ use erg_common::erg_util::BUILTIN_ERG_MODS;
 use erg_common::io::Input;
 use erg_common::levenshtein::get_similar_name;
 use erg_common::pathutil::{DirKind, FileKind};
-use erg_common::python_util::BUILTIN_PYTHON_MODS;
+use erg_common::python_util::{PythonVersion, BUILTIN_PYTHON_MODS};
 use erg_common::set::Set;
 use erg_common::spawn::spawn_new_thread;
 use erg_common::traits::{Locational, Stream, StructuralEq};
@@ -27,15 +28,18 @@ use ast::{
     VarName,
 };
 use erg_parser::ast;
+use erg_parser::Parser;
 
 use crate::ty::constructors::{
-    free_var, func, func0, func1, proc, ref_, ref_mut, tp_enum, unknown_len_array_t, v_enum,
+    free_var, func, func0, func1, ref_, ref_mut, str_dict_t, subr_t, tp_enum, unknown_len_array_t,
+    v_enum,
 };
 use crate::ty::free::{Constraint, HasLevel};
 use crate::ty::typaram::TyParam;
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{
-    Field, GuardType, HasType, ParamTy, SubrType, Type, Variable, Visibility, VisibilityModifier,
+    ConstSubr, Field, GuardType, HasType, ParamTy, SubrKind, SubrType, Type, UserConstSubr,
+    Variable, Visibility, VisibilityModifier,
 };
 
 use crate::build_hir::HIRBuilder;
@@ -45,6 +49,7 @@ use crate::error::{
     CompileError, CompileErrors, CompileResult, TyCheckError, TyCheckErrors, TyCheckResult,
 };
 use crate::hir::Literal;
+use crate::module::RefKind;
 use crate::varinfo::{AbsLocation, Mutability, VarInfo, VarKind};
 use crate::{feature_error, hir};
 use Mutability::*;
@@ -59,6 +64,15 @@ pub fn valid_mod_name(name: &str) -> bool {
     !name.is_empty() && !name.starts_with('/') && name.trim() == name
 }
 
+/// The names of the compile-time decorators (including decorator factories such as `@Deprecated "reason"`)
+fn comptime_decos(decorators: &Set<Decorator>) -> Set<Str> {
+    decorators
+        .iter()
+        .filter(|deco| deco.is_comptime())
+        .filter_map(|deco| deco.name().map(|name| name.inspect().clone()))
+        .collect()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CheckStatus {
     Succeed,
@@ -232,6 +246,22 @@ impl Context {
         }
     }
 
+    /// The version specified by `@Available python:="3.10"`
+    pub(crate) fn get_available_since(&self, decorators: &Set<Decorator>) -> Option<PythonVersion> {
+        let deco = decorators.iter().find(|deco| {
+            deco.name()
+                .is_some_and(|name| &name.inspect()[..] == "Available")
+        })?;
+        let ast::Expr::Call(call) = deco.expr() else {
+            return None;
+        };
+        let version = call.args.nth_or_key(0, "python")?;
+        match self.eval_const_expr(version) {
+            Ok(ValueObj::Str(version)) => version.parse().ok(),
+            _ => None,
+        }
+    }
+
     pub(crate) fn declare_sub(
         &mut self,
         sig: &ast::SubrSignature,
@@ -241,16 +271,7 @@ impl Context {
         let vis = self.instantiate_vis_modifier(&sig.ident.vis)?;
         let muty = Mutability::from(&name[..]);
         let kind = id.map_or(VarKind::Declared, VarKind::Defined);
-        let comptime_decos = sig
-            .decorators
-            .iter()
-            .filter_map(|deco| match &deco.0 {
-                ast::Expr::Accessor(ast::Accessor::Ident(local)) if local.is_const() => {
-                    Some(local.inspect().clone())
-                }
-                _ => None,
-            })
-            .collect::<Set<_>>();
+        let comptime_decos = comptime_decos(&sig.decorators);
         let default_ts =
             vec![free_var(self.level, Constraint::new_type_of(Type::Type)); sig.params.len()];
         let (errs, t) = match self.instantiate_sub_sig_t(sig, default_ts, PreRegister) {
@@ -262,7 +283,7 @@ impl Context {
         } else {
             None
         };
-        let vi = VarInfo::new(
+        let mut vi = VarInfo::new(
             t,
             muty,
             Visibility::new(vis, self.name.clone()),
@@ -272,6 +293,7 @@ impl Context {
             py_name,
             self.absolutize(sig.ident.name.loc()),
         );
+        vi.available_since = self.get_available_since(&sig.decorators);
         self.index().register(sig.ident.inspect().clone(), &vi);
         if self
             .remove_class_attr(name)
@@ -427,6 +449,7 @@ impl Context {
         };
         let default = kind.default_info();
         let is_var_params = kind.is_var_params();
+        let is_kw_params = kind.is_kw_params();
         match &sig.raw.pat {
             // Literal patterns will be desugared to discard patterns
             ast::ParamPattern::Lit(_) => unreachable!(),
@@ -491,6 +514,8 @@ impl Context {
                     };
                     let spec_t = if is_var_params {
                         unknown_len_array_t(spec_t)
+                    } else if is_kw_params {
+                        str_dict_t(spec_t)
                     } else {
                         spec_t
                     };
@@ -691,6 +716,19 @@ impl Context {
                     errs.extend(es);
                 }
             }
+            if let Some(kw_var_params) = &mut params.kw_var_params {
+                if let Some(pt) = &decl_subr_t.kw_var_params {
+                    let pt = pt.clone().map_type(str_dict_t);
+                    if let Err(es) =
+                        self.assign_param(kw_var_params, Some(&pt), ParamKind::KwParams)
+                    {
+                        errs.extend(es);
+                    }
+                } else if let Err(es) = self.assign_param(kw_var_params, None, ParamKind::KwParams)
+                {
+                    errs.extend(es);
+                }
+            }
         } else {
             for non_default in params.non_defaults.iter_mut() {
                 if let Err(es) = self.assign_param(non_default, None, ParamKind::NonDefault) {
@@ -711,6 +749,11 @@ impl Context {
                     errs.extend(es);
                 }
             }
+            if let Some(kw_var_params) = &mut params.kw_var_params {
+                if let Err(es) = self.assign_param(kw_var_params, None, ParamKind::KwParams) {
+                    errs.extend(es);
+                }
+            }
         }
         if errs.is_empty() {
             Ok(())
@@ -752,11 +795,11 @@ impl Context {
         // FIXME: constでない関数
         let t = self.get_current_scope_var(name).map(|vi| &vi.t).unwrap();
         debug_assert!(t.is_subr(), "{t} is not subr");
-        let empty = vec![];
-        let non_default_params = t.non_default_params().unwrap_or(&empty);
-        let var_args = t.var_params();
-        let default_params = t.default_params().unwrap_or(&empty);
-        if let Some(spec_ret_t) = t.return_t() {
+        let non_default_params = t.non_default_params().map_or(vec![], Cow::into_owned);
+        let var_args = t.var_params().map(Cow::into_owned);
+        let default_params = t.default_params().map_or(vec![], Cow::into_owned);
+        let kw_var_args = t.kw_var_params().map(Cow::into_owned);
+        if let Some(spec_ret_t) = t.return_t().as_deref() {
             let unify_result = if let Some(t_spec) = sig.return_t_spec.as_ref() {
                 self.sub_unify(body_t, spec_ret_t, t_spec, None)
             } else {
@@ -794,22 +837,20 @@ impl Context {
             }
         }
         // NOTE: not `body_t.clone()` because the body may contain `return`
-        let return_t = t.return_t().unwrap().clone();
-        let sub_t = if sig.ident.is_procedural() {
-            proc(
-                non_default_params.clone(),
-                var_args.cloned(),
-                default_params.clone(),
-                return_t,
-            )
+        let return_t = t.return_t().unwrap().into_owned();
+        let kind = if sig.ident.is_procedural() {
+            SubrKind::Proc
         } else {
-            func(
-                non_default_params.clone(),
-                var_args.cloned(),
-                default_params.clone(),
-                return_t,
-            )
+            SubrKind::Func
         };
+        let sub_t = subr_t(
+            kind,
+            non_default_params,
+            var_args,
+            default_params,
+            kw_var_args,
+            return_t,
+        );
         sub_t.lift();
         let found_t = self.generalize_t(sub_t);
         // let found_t = self.eliminate_needless_quant(found_t, crate::context::Variance::Covariant, sig)?;
@@ -830,17 +871,8 @@ impl Context {
         } else {
             None
         };
-        let comptime_decos = sig
-            .decorators
-            .iter()
-            .filter_map(|deco| match &deco.0 {
-                ast::Expr::Accessor(ast::Accessor::Ident(local)) if local.is_const() => {
-                    Some(local.inspect().clone())
-                }
-                _ => None,
-            })
-            .collect();
-        let vi = VarInfo::new(
+        let comptime_decos = comptime_decos(&sig.decorators);
+        let mut vi = VarInfo::new(
             found_t,
             muty,
             Visibility::new(vis, self.name.clone()),
@@ -850,6 +882,7 @@ impl Context {
             py_name,
             self.absolutize(name.loc()),
         );
+        vi.available_since = self.get_available_since(&sig.decorators);
         let vis = if vi.vis.is_private() { "::" } else { "." };
         log!(info "Registered {}{}{name}: {}", self.name, vis, &vi.t);
         self.locals.insert(name.clone(), vi.clone());
@@ -883,15 +916,7 @@ impl Context {
         };
         let name = &ident.name;
         self.decls.remove(name);
-        let comptime_decos = decorators
-            .iter()
-            .filter_map(|deco| match &deco.0 {
-                ast::Expr::Accessor(ast::Accessor::Ident(local)) if local.is_const() => {
-                    Some(local.inspect().clone())
-                }
-                _ => None,
-            })
-            .collect();
+        let comptime_decos = comptime_decos(decorators);
         let vi = VarInfo::new(
             failure_t,
             muty,
@@ -989,7 +1014,10 @@ impl Context {
         let __name__ = def.sig.ident().map(|i| i.inspect()).unwrap_or(UBAR);
         match &def.sig {
             ast::Signature::Subr(sig) => {
-                if sig.is_const() {
+                if sig.is_const() && !sig.params.is_empty() && def.def_kind().is_other() {
+                    self.declare_sub(sig, id)?;
+                    self.register_const_subr(sig, &def.body);
+                } else if sig.is_const() {
                     let tv_cache = self.instantiate_ty_bounds(&sig.bounds, PreRegister)?;
                     let vis = self.instantiate_vis_modifier(sig.vis())?;
                     self.grow(__name__, ContextKind::Proc, vis, Some(tv_cache));
@@ -1059,7 +1087,21 @@ impl Context {
                         self.sub_unify(&const_t, &spec_t, &def.body, None)
                             .map_err(|errs| {
                                 self.pop();
-                                errs
+                                match (&obj, sig.ident()) {
+                                    (
+                                        ValueObj::Nat(_) | ValueObj::Int(_) | ValueObj::Float(_),
+                                        Some(ident),
+                                    ) => TyCheckErrors::from(TyCheckError::out_of_range_error(
+                                        self.cfg.input.clone(),
+                                        line!() as usize,
+                                        def.body.block.loc(),
+                                        self.caused_by(),
+                                        ident.inspect(),
+                                        &spec_t,
+                                        &obj,
+                                    )),
+                                    _ => errs,
+                                }
                             })?;
                     }
                     self.pop();
@@ -1074,6 +1116,25 @@ impl Context {
         Ok(())
     }
 
+    /// Registers a constant subroutine with parameters (e.g. `Double(X: Int): Int = X * 2`),
+    /// whose body is evaluated each time it is called at compile time.
+    /// If the body is not a constant expression, it is only available at runtime.
+    fn register_const_subr(&mut self, sig: &ast::SubrSignature, body: &ast::DefBody) {
+        let Ok(block) = Parser::validate_const_block(body.block.clone()) else {
+            return;
+        };
+        let Some(sig_t) = self.decls.get(&sig.ident.name).map(|vi| vi.t.clone()) else {
+            return;
+        };
+        let subr = ConstSubr::User(UserConstSubr::new(
+            sig.ident.inspect().clone(),
+            sig.params.clone(),
+            block,
+            sig_t,
+        ));
+        self.consts.insert(sig.ident.name.clone(), ValueObj::Subr(subr));
+    }
+
     /// e.g. .new
     fn register_auto_impl(
         &mut self,
@@ -1877,7 +1938,7 @@ impl Context {
     }
 
     fn import_erg_mod(&self, __name__: &Str, loc: &impl Locational) -> CompileResult<PathBuf> {
-        let path = match self.cfg.input.resolve_real_path(Path::new(&__name__[..])) {
+        let path = match self.cfg.resolve_real_path(Path::new(&__name__[..])) {
             Some(path) => path,
             None => {
                 return Err(self.import_err(line!(), __name__, loc));
@@ -2009,6 +2070,10 @@ impl Context {
             .input
             .try_read()
             .map_err(|_| self.import_err(line!(), __name__, loc))?;
+        // another thread may have started analyzing the module since `mod_registered` (e.g. cyclic imports)
+        if !self.shared().promises.reserve(&path) {
+            return Ok(path);
+        }
         let name = __name__.clone();
         let _path = path.clone();
         let shared = self.shared.as_ref().unwrap().inherit(path.clone());
@@ -2036,6 +2101,10 @@ impl Context {
         };
         let handle = spawn_new_thread(run, __name__);
         self.shared().promises.insert(path.clone(), handle);
+        // the ids of type variables and the generated names depend on the order of analysis
+        if self.cfg.deterministic {
+            let _ = self.shared().promises.join(&path);
+        }
         Ok(path)
     }
 
@@ -2104,7 +2173,7 @@ impl Context {
     }
 
     fn get_decl_path(&self, __name__: &Str, loc: &impl Locational) -> CompileResult<PathBuf> {
-        match self.cfg.input.resolve_decl_path(Path::new(&__name__[..])) {
+        match self.cfg.resolve_decl_path(Path::new(&__name__[..])) {
             Some(path) => {
                 if self.cfg.input.decl_file_is(&path) {
                     return Ok(path);
@@ -2174,7 +2243,7 @@ impl Context {
                 .spawn()
                 .and_then(|mut child| child.wait())
             {
-                if let Some(path) = self.cfg.input.resolve_decl_path(Path::new(&__name__[..])) {
+                if let Some(path) = self.cfg.resolve_decl_path(Path::new(&__name__[..])) {
                     let size = metadata(&path).unwrap().len();
                     // if pylyzer crashed
                     if !status.success() && size == 0 {
@@ -2318,9 +2387,20 @@ impl Context {
         vi: &VarInfo,
         loc: &L,
         namespace: &Context,
+    ) {
+        self.inc_ref_as(name, vi, loc, namespace, RefKind::Read);
+    }
+
+    pub(crate) fn inc_ref_as<L: Locational>(
+        &self,
+        name: &Str,
+        vi: &VarInfo,
+        loc: &L,
+        namespace: &Context,
+        kind: RefKind,
     ) {
         if let Some(index) = self.opt_index() {
-            index.inc_ref(name, vi, namespace.absolutize(loc.loc()));
+            index.inc_ref(name, vi, namespace.absolutize(loc.loc()), kind);
         }
     }
 
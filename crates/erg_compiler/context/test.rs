@@ -2,14 +2,38 @@
 use erg_common::traits::StructuralEq;
 use erg_common::Str;
 
-use crate::ty::constructors::{func1, mono, mono_q, poly, refinement, ty_tp};
+use crate::ty::constructors::{
+    free_var, func1, mono, mono_q, or, poly, refinement, ty_tp, unknown_len_array_t,
+};
 use crate::ty::free::Constraint;
 use crate::ty::typaram::TyParam;
 use crate::ty::{Predicate, Type};
 use Type::*;
 
+use crate::context::unify::enable_invariant_checks;
 use crate::context::Context;
 
+/// xorshift64, to make the generated types reproducible
+struct TypeGen(u64);
+
+impl TypeGen {
+    fn next(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+
+    fn closed(&mut self, depth: usize) -> Type {
+        let leaves = [Int, Nat, Bool, Str, Float, NoneType, Obj, Never];
+        match self.next(if depth == 0 { 8 } else { 10 }) {
+            8 => or(self.closed(depth - 1), self.closed(depth - 1)),
+            9 => unknown_len_array_t(self.closed(depth - 1)),
+            n => leaves[n as usize].clone(),
+        }
+    }
+}
+
 impl Context {
     pub fn assert_var_type(&self, varname: &str, ty: &Type) -> Result<(), ()> {
         let Some((_, vi)) = self.get_var_info(varname) else {
@@ -96,4 +120,55 @@ impl Context {
         assert!(self.subtype_of(&(Int | Str), &(Int | Str | Code | NoneType)));
         Ok(())
     }
+
+    /// Checks the properties of unification with randomly generated types (and the invariants of the unifier)
+    pub fn test_unification_properties(&self) -> Result<(), ()> {
+        enable_invariant_checks();
+        let mut gen = TypeGen(0x2545_f491_4f6c_dd1d);
+        for _ in 0..300 {
+            let (lhs, rhs) = (gen.closed(2), gen.closed(2));
+            // commutativity
+            let (l_r, r_l) = (self.unify(&lhs, &rhs), self.unify(&rhs, &lhs));
+            match (&l_r, &r_l) {
+                (Some(l_r), Some(r_l)) if self.same_type_of(l_r, r_l) => {}
+                (None, None) => {}
+                _ => {
+                    println!("unify({lhs}, {rhs}) = {l_r:?}, but unify({rhs}, {lhs}) = {r_l:?}");
+                    return Err(());
+                }
+            }
+            let (l_r, r_l) = (self.union(&lhs, &rhs), self.union(&rhs, &lhs));
+            if !self.same_type_of(&l_r, &r_l) {
+                println!("{lhs} or {rhs} = {l_r}, but {rhs} or {lhs} = {r_l}");
+                return Err(());
+            }
+            // without type variables, unification is subtyping
+            if self.sub_unify(&lhs, &rhs, &(), None).is_ok() != self.subtype_of(&lhs, &rhs) {
+                println!("sub_unify({lhs}, {rhs}) disagrees with subtyping");
+                return Err(());
+            }
+            // soundness and idempotence
+            let sub = gen.closed(1);
+            let sup = self.union(&sub, &gen.closed(1));
+            let constraint = Constraint::new_sandwiched(sub, sup);
+            for (l, r) in [
+                (free_var(self.level, constraint.clone()), lhs.clone()),
+                (lhs.clone(), free_var(self.level, constraint)),
+            ] {
+                if self.sub_unify(&l, &r, &(), None).is_err() {
+                    continue;
+                }
+                let (l_once, r_once) = (l.to_string(), r.to_string());
+                if !self.subtype_of(&l, &r)
+                    || self.sub_unify(&l, &r, &(), None).is_err()
+                    || l_once != l.to_string()
+                    || r_once != r.to_string()
+                {
+                    println!("sub_unify({l_once}, {r_once}) is not sound or idempotent: {l}, {r}");
+                    return Err(());
+                }
+            }
+        }
+        Ok(())
+    }
 }
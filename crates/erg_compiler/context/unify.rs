@@ -1,8 +1,13 @@
 //! provides type variable related operations
+use std::cell::RefCell;
+use std::env;
 use std::mem;
 use std::option::Option;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
 use erg_common::fresh::FRESH_GEN;
+use erg_common::profile::PROFILER;
 use erg_common::traits::Locational;
 use erg_common::Str;
 #[allow(unused_imports)]
@@ -30,6 +35,8 @@ pub struct Unifier<'c, 'l, L: Locational> {
     loc: &'l L,
     undoable: bool,
     param_name: Option<Str>,
+    /// the type variables linked in the undoable mode (in order)
+    undoable_links: RefCell<Vec<TyParam>>,
 }
 
 impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
@@ -39,10 +46,115 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
             loc,
             undoable,
             param_name,
+            undoable_links: RefCell::new(vec![]),
+        }
+    }
+
+    /// `fv.link(to)` (+ the invariant checks)
+    fn link(&self, fv: &Type, to: &Type) {
+        let before = invariant_checks_enabled().then(|| (fv.level(), subsup(fv)));
+        fv.link(to, self.undoable);
+        self.record_link(TyParam::t(fv.clone()));
+        if let Some((level, subsup)) = before {
+            self.check_link(fv, to, level, subsup);
+        }
+    }
+
+    /// `fv.update_constraint(new_constraint)` (+ the invariant checks)
+    fn update_constraint(&self, fv: &Type, new_constraint: Constraint, in_instantiation: bool) {
+        fv.update_constraint(new_constraint, self.undoable, in_instantiation);
+        self.record_link(TyParam::t(fv.clone()));
+        if invariant_checks_enabled() {
+            self.check_bounds(fv);
+        }
+    }
+
+    fn link_tp(&self, fv: &TyParam, to: &TyParam) {
+        fv.link(to, self.undoable);
+        self.record_link(fv.clone());
+    }
+
+    fn update_tp_constraint(&self, fv: &TyParam, new_constraint: Constraint) {
+        fv.update_constraint(new_constraint, self.undoable, false);
+        self.record_link(fv.clone());
+    }
+
+    fn record_link(&self, fv: TyParam) {
+        if self.undoable {
+            self.undoable_links.borrow_mut().push(fv);
+        }
+    }
+
+    /// * the level of the type variable does not increase (otherwise it would be generalized too early)
+    /// * the linked type satisfies the previous constraint
+    /// * the new constraint is consistent (`sub <: sup`)
+    fn check_link(&self, fv: &Type, to: &Type, level: Option<usize>, bounds: Option<(Type, Type)>) {
+        if let (Some(before), Some(after)) =
+            (level, to.is_unbound_var().then(|| to.level()).flatten())
+        {
+            if after > before {
+                invariant_violated("the level increased", &format!("{fv}: {before} => {after}"));
+            }
+        }
+        if let Some((sub, sup)) = bounds {
+            let (new_sub, new_sup) = subsup(to).unwrap_or((to.clone(), to.clone()));
+            if !self.ctx.subtype_of(&sub, &new_sub) || !self.ctx.subtype_of(&new_sup, &sup) {
+                invariant_violated(
+                    "the link loosened the constraint",
+                    &format!("(:> {sub}, <: {sup}) => {to}(:> {new_sub}, <: {new_sup})"),
+                );
+            }
+        }
+        self.check_bounds(fv);
+    }
+
+    /// * the unified types are in the subtyping relation
+    /// * the undoable links made in a (destructive) unification are all undone
+    fn check_result(&self, sub: &Type, sup: &Type, unified: bool, had_undoable_link: bool) {
+        // `Failure` is unified with anything
+        if unified && !sub.is_failure() && !sup.is_failure() && !self.ctx.subtype_of(sub, sup) {
+            invariant_violated("unified but sub !<: sup", &format!("{sub} / {sup}"));
+        }
+        if !had_undoable_link && (sub.has_undoable_linked_var() || sup.has_undoable_linked_var()) {
+            invariant_violated("dangling undoable link", &format!("{sub} / {sup}"));
+        }
+    }
+
+    fn check_bounds(&self, fv: &Type) {
+        if let Some((sub, sup)) = subsup(fv) {
+            if !self.ctx.subtype_of(&sub, &sup) {
+                invariant_violated("sub !<: sup", &format!("{fv}"));
+            }
         }
     }
 }
 
+fn subsup(t: &Type) -> Option<(Type, Type)> {
+    t.get_sub().zip(t.get_super())
+}
+
+static CHECK_INVARIANTS: OnceLock<AtomicBool> = OnceLock::new();
+
+fn check_invariants() -> &'static AtomicBool {
+    CHECK_INVARIANTS.get_or_init(|| AtomicBool::new(env::var_os("ERG_CHECK_UNIFY").is_some()))
+}
+
+/// Whether the unifier checks its invariants after each link.
+/// This is enabled by the environment variable `ERG_CHECK_UNIFY` (or `enable_invariant_checks`) in debug builds,
+/// and makes type checking several times slower.
+pub fn invariant_checks_enabled() -> bool {
+    cfg!(debug_assertions) && check_invariants().load(Ordering::Relaxed)
+}
+
+pub fn enable_invariant_checks() {
+    check_invariants().store(true, Ordering::Relaxed);
+}
+
+#[track_caller]
+fn invariant_violated(invariant: &str, detail: &str) -> ! {
+    panic!("the invariant of the unifier is violated ({invariant}): {detail}")
+}
+
 impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
     /// ```erg
     /// occur(?T, ?T) ==> OK
@@ -69,6 +181,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(var_params.typ(), maybe_sup)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(kw_var_params.typ(), maybe_sup)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(non_default_t, maybe_sup)?;
                 }
@@ -82,6 +197,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(maybe_sub, var_params.typ())?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(maybe_sub, kw_var_params.typ())?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(maybe_sub, non_default_t)?;
                 }
@@ -102,6 +220,11 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         self.occur(lhs.typ(), rhs.typ())?;
                     }
                 }
+                if let Some(lhs) = lhs.kw_var_params.as_ref() {
+                    if let Some(rhs) = rhs.kw_var_params.as_ref() {
+                        self.occur(lhs.typ(), rhs.typ())?;
+                    }
+                }
                 for (lhs, rhs) in lhs
                     .non_default_params
                     .iter()
@@ -178,6 +301,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(var_params.typ(), maybe_sup)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(kw_var_params.typ(), maybe_sup)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(non_default_t, maybe_sup)?;
                 }
@@ -191,6 +317,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(maybe_sub, var_params.typ())?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(maybe_sub, kw_var_params.typ())?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(maybe_sub, non_default_t)?;
                 }
@@ -211,6 +340,11 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         self.occur_inner(lhs.typ(), rhs.typ())?;
                     }
                 }
+                if let Some(lhs) = lhs.kw_var_params.as_ref() {
+                    if let Some(rhs) = rhs.kw_var_params.as_ref() {
+                        self.occur_inner(lhs.typ(), rhs.typ())?;
+                    }
+                }
                 for (lhs, rhs) in lhs
                     .non_default_params
                     .iter()
@@ -288,10 +422,10 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
             {
                 if sub_fv.level().unwrap() > sup_fv.level().unwrap() {
                     if !sub_fv.is_generalized() {
-                        maybe_sub.link(maybe_sup, self.undoable);
+                        self.link_tp(maybe_sub, maybe_sup);
                     }
                 } else if !sup_fv.is_generalized() {
-                    maybe_sup.link(maybe_sub, self.undoable);
+                    self.link_tp(maybe_sup, maybe_sub);
                 }
                 Ok(())
             }
@@ -314,10 +448,10 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                             .is_sub_constraint_of(&sub_fv.constraint().unwrap(), &new_constraint)
                             || sub_fv.constraint().unwrap().get_type() == Some(&Type)
                         {
-                            maybe_sub.update_constraint(new_constraint, self.undoable, false);
+                            self.update_tp_constraint(maybe_sub, new_constraint);
                         }
                     } else {
-                        maybe_sub.link(sup_tp, self.undoable);
+                        self.link_tp(maybe_sub, sup_tp);
                     }
                     Ok(())
                 } else if allow_divergence
@@ -325,7 +459,7 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         || self.ctx.eq_tp(sup_tp, &TyParam::value(NegInf)))
                     && self.ctx.subtype_of(&fv_t, &mono("Num"))
                 {
-                    maybe_sub.link(sup_tp, self.undoable);
+                    self.link_tp(maybe_sub, sup_tp);
                     Ok(())
                 } else {
                     Err(TyCheckErrors::from(TyCheckError::unreachable(
@@ -354,10 +488,10 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                             .is_sub_constraint_of(&sup_fv.constraint().unwrap(), &new_constraint)
                             || sup_fv.constraint().unwrap().get_type() == Some(&Type)
                         {
-                            maybe_sup.update_constraint(new_constraint, self.undoable, false);
+                            self.update_tp_constraint(maybe_sup, new_constraint);
                         }
                     } else {
-                        maybe_sup.link(sub_tp, self.undoable);
+                        self.link_tp(maybe_sup, sub_tp);
                     }
                     Ok(())
                 } else if allow_divergence
@@ -365,7 +499,7 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         || self.ctx.eq_tp(sub_tp, &TyParam::value(NegInf)))
                     && self.ctx.subtype_of(&fv_t, &mono("Num"))
                 {
-                    maybe_sup.link(sub_tp, self.undoable);
+                    self.link_tp(maybe_sup, sub_tp);
                     Ok(())
                 } else {
                     Err(TyCheckErrors::from(TyCheckError::unreachable(
@@ -595,7 +729,7 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                     let pred = Predicate::ge(FRESH_GEN.fresh_varname(), evaled);
                     let new_type = self.ctx.type_from_pred(pred);
                     let new_constr = Constraint::new_type_of(Type::from(new_type));
-                    target.update_constraint(new_constr, self.undoable, false);
+                    self.update_tp_constraint(target, new_constr);
                 }
                 Ok(())
             }
@@ -752,21 +886,21 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         .cmp(&sup_fv.level().unwrap_or(GENERIC_LEVEL))
                     {
                         std::cmp::Ordering::Less => {
-                            maybe_sub.link(&union, self.undoable);
-                            maybe_sup.link(maybe_sub, self.undoable);
+                            self.link(maybe_sub, &union);
+                            self.link(maybe_sup, maybe_sub);
                         }
                         std::cmp::Ordering::Greater => {
-                            maybe_sup.link(&union, self.undoable);
-                            maybe_sub.link(maybe_sup, self.undoable);
+                            self.link(maybe_sup, &union);
+                            self.link(maybe_sub, maybe_sup);
                         }
                         std::cmp::Ordering::Equal => {
                             // choose named one
                             if sup_fv.is_named_unbound() {
-                                maybe_sup.link(&union, self.undoable);
-                                maybe_sub.link(maybe_sup, self.undoable);
+                                self.link(maybe_sup, &union);
+                                self.link(maybe_sub, maybe_sup);
                             } else {
-                                maybe_sub.link(&union, self.undoable);
-                                maybe_sup.link(maybe_sub, self.undoable);
+                                self.link(maybe_sub, &union);
+                                self.link(maybe_sup, maybe_sub);
                             }
                         }
                     }
@@ -778,21 +912,21 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         .cmp(&sup_fv.level().unwrap_or(GENERIC_LEVEL))
                     {
                         std::cmp::Ordering::Less => {
-                            maybe_sub.update_constraint(new_constraint, self.undoable, false);
-                            maybe_sup.link(maybe_sub, self.undoable);
+                            self.update_constraint(maybe_sub, new_constraint, false);
+                            self.link(maybe_sup, maybe_sub);
                         }
                         std::cmp::Ordering::Greater => {
-                            maybe_sup.update_constraint(new_constraint, self.undoable, false);
-                            maybe_sub.link(maybe_sup, self.undoable);
+                            self.update_constraint(maybe_sup, new_constraint, false);
+                            self.link(maybe_sub, maybe_sup);
                         }
                         std::cmp::Ordering::Equal => {
                             // choose named one
                             if sup_fv.is_named_unbound() {
-                                maybe_sup.update_constraint(new_constraint, self.undoable, false);
-                                maybe_sub.link(maybe_sup, self.undoable);
+                                self.update_constraint(maybe_sup, new_constraint, false);
+                                self.link(maybe_sub, maybe_sup);
                             } else {
-                                maybe_sup.update_constraint(new_constraint, self.undoable, false);
-                                maybe_sup.link(maybe_sub, self.undoable);
+                                self.update_constraint(maybe_sup, new_constraint, false);
+                                self.link(maybe_sup, maybe_sub);
                             }
                         }
                     }
@@ -867,10 +1001,10 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 // self.sub_unify(&intersec, &lsup, loc, param_name)?;
                 // self.sub_unify(&lsub, &union, loc, param_name)?;
                 if union == intersec {
-                    maybe_sup.link(&union, self.undoable);
+                    self.link(maybe_sup, &union);
                 } else {
                     let new_constraint = Constraint::new_sandwiched(union, intersec);
-                    maybe_sup.update_constraint(new_constraint, self.undoable, false);
+                    self.update_constraint(maybe_sup, new_constraint, false);
                 }
             }
             // (Int or ?T) <: (?U or Int)
@@ -953,17 +1087,17 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         }
                     }
                     if sup.contains_union(&new_sub) {
-                        maybe_sup.link(&new_sub, self.undoable); // Bool <: ?T <: Bool or Y ==> ?T == Bool
+                        self.link(maybe_sup, &new_sub); // Bool <: ?T <: Bool or Y ==> ?T == Bool
                     } else {
                         let constr = Constraint::new_sandwiched(new_sub, mem::take(&mut sup));
-                        maybe_sup.update_constraint(constr, self.undoable, true);
+                        self.update_constraint(maybe_sup, constr, true);
                     }
                 }
                 // sub_unify(Nat, ?T(: Type)): (/* ?T(:> Nat) */)
                 else if let Some(ty) = sup_fv.get_type() {
                     if self.ctx.supertype_of(&Type, &ty) {
                         let constr = Constraint::new_supertype_of(maybe_sub.clone());
-                        maybe_sup.update_constraint(constr, self.undoable, true);
+                        self.update_constraint(maybe_sup, constr, true);
                     } else {
                         todo!("{maybe_sub} <: {maybe_sup}")
                     }
@@ -1020,17 +1154,17 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         && !new_sup.is_unbound_var()
                         && !sub.is_unbound_var()
                     {
-                        maybe_sub.link(&sub, self.undoable);
+                        self.link(maybe_sub, &sub);
                     } else {
                         let constr = Constraint::new_sandwiched(sub, new_sup);
-                        maybe_sub.update_constraint(constr, self.undoable, true);
+                        self.update_constraint(maybe_sub, constr, true);
                     }
                 }
                 // sub_unify(?T(: Type), Int): (?T(<: Int))
                 else if let Some(ty) = sub_fv.get_type() {
                     if self.ctx.supertype_of(&Type, &ty) {
                         let constr = Constraint::new_subtype_of(maybe_sup.clone());
-                        maybe_sub.update_constraint(constr, self.undoable, true);
+                        self.update_constraint(maybe_sub, constr, true);
                     } else {
                         todo!("{maybe_sub} <: {maybe_sup}")
                     }
@@ -1069,6 +1203,14 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                         // contravariant
                         self.sub_unify(sup.typ(), sub.typ())
                     })?;
+                sub_subr
+                    .kw_var_params
+                    .iter()
+                    .zip(sup_subr.kw_var_params.iter())
+                    .try_for_each(|(sub, sup)| {
+                        // contravariant
+                        self.sub_unify(sup.typ(), sub.typ())
+                    })?;
                 for sup_pt in sup_subr.default_params.iter() {
                     if let Some(sub_pt) = sub_subr
                         .default_params
@@ -1102,7 +1244,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 self.sub_unify(&sub_subr.return_t, &sup_subr.return_t)?;
             }
             (Quantified(sub_subr), Subr(sup_subr)) => {
-                let Ok(sub_subr) = <&SubrType>::try_from(sub_subr.as_ref()) else { unreachable!() };
+                let Ok(sub_subr) = <&SubrType>::try_from(sub_subr.as_ref()) else {
+                    unreachable!()
+                };
                 sub_subr
                     .non_default_params
                     .iter()
@@ -1137,7 +1281,9 @@ impl<'c, 'l, L: Locational> Unifier<'c, 'l, L> {
                 }
             }
             (Subr(sub_subr), Quantified(sup_subr)) => {
-                let Ok(sup_subr) = <&SubrType>::try_from(sup_subr.as_ref()) else { unreachable!() };
+                let Ok(sup_subr) = <&SubrType>::try_from(sup_subr.as_ref()) else {
+                    unreachable!()
+                };
                 sub_subr
                     .non_default_params
                     .iter()
@@ -1442,6 +1588,7 @@ impl Context {
         loc: &impl Locational,
         is_structural: bool,
     ) -> TyCheckResult<()> {
+        let _scope = PROFILER.scope("sub_unify_tp");
         let unifier = Unifier::new(self, loc, is_structural, None);
         unifier.sub_unify_tp(maybe_sub, maybe_sup, variance, is_structural)
     }
@@ -1453,19 +1600,31 @@ impl Context {
         loc: &impl Locational,
         param_name: Option<&Str>,
     ) -> TyCheckResult<()> {
+        let _scope = PROFILER.scope("sub_unify");
         let unifier = Unifier::new(self, loc, false, param_name.cloned());
-        unifier.sub_unify(maybe_sub, maybe_sup)
+        if !invariant_checks_enabled() {
+            return unifier.sub_unify(maybe_sub, maybe_sup);
+        }
+        let had_undoable_link =
+            maybe_sub.has_undoable_linked_var() || maybe_sup.has_undoable_linked_var();
+        let res = unifier.sub_unify(maybe_sub, maybe_sup);
+        unifier.check_result(maybe_sub, maybe_sup, res.is_ok(), had_undoable_link);
+        res
     }
 
+    /// The linked type variables are appended to `undoable_links`, and they must be undone in reverse order.
     pub(crate) fn undoable_sub_unify(
         &self,
         maybe_sub: &Type,
         maybe_sup: &Type,
         loc: &impl Locational,
         param_name: Option<&Str>,
+        undoable_links: &mut Vec<TyParam>,
     ) -> TyCheckResult<()> {
         let unifier = Unifier::new(self, loc, true, param_name.cloned());
-        unifier.sub_unify(maybe_sub, maybe_sup)
+        let res = unifier.sub_unify(maybe_sub, maybe_sup);
+        undoable_links.extend(unifier.undoable_links.into_inner());
+        res
     }
 
     pub(crate) fn unify(&self, lhs: &Type, rhs: &Type) -> Option<Type> {
@@ -0,0 +1,319 @@
+//! Line coverage of Erg programs (`erg test --coverage` and `erg coverage report`).
+//!
+//! With `--coverage`, the test harness runs the tests under a CPython tracer (`sys.settrace` with opcode events),
+//! which records the instructions executed in the code objects of Erg modules (those whose `co_filename` ends with `.er`).
+//! The instructions are mapped back to the Erg source lines with the line tables of the code objects.
+//! The harness is compiled in the coverage mode of the code generator, which attributes the definitions to their first lines
+//! and the code objects of the linked modules to the files of the modules.
+//! The executable lines are also taken from the line tables of the executed modules
+//! (including the subroutines that are never called).
+//!
+//! Each harness appends its records to `build/coverage.txt`, and `erg coverage report` merges them.
+//! A record is a line of `<absolute path>\t<executable lines>\t<executed lines>` (the lines are comma-separated).
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::pathutil::{expand_targets, package_root};
+use erg_common::style::{colors::GREEN, colors::RED, colors::YELLOW, RESET};
+use erg_common::traits::ExitStatus;
+
+use crate::test_runner::unescape;
+
+/// Installs the tracer. Inserted before the harness header (so that the top-level code of the module is traced).
+pub(crate) const COVERAGE_PRELUDE: &str = r#"
+import bisect
+import dis
+import os
+import sys
+import threading
+
+# code object -> executed instruction offsets
+COVERED = {}
+
+
+def _erg_trace_opcodes(frame, event, arg):
+    if event == "opcode":
+        COVERED[frame.f_code].add(frame.f_lasti)
+    return _erg_trace_opcodes
+
+
+def _erg_trace(frame, event, arg):
+    code = frame.f_code
+    if not code.co_filename.endswith(".er"):
+        return None
+    COVERED.setdefault(code, set())
+    frame.f_trace_lines = False
+    frame.f_trace_opcodes = True
+    return _erg_trace_opcodes
+
+
+sys.settrace(_erg_trace)
+threading.settrace(_erg_trace)
+"#;
+
+/// Maps the executed offsets to the lines and appends the records to `COVERAGE`. Inserted after the harness body.
+///
+/// Erg writes the line table in the `co_lnotab` format (pairs of the offset delta and the line delta) for all Python versions,
+/// so it is decoded here instead of `dis.findlinestarts` (which expects the format of the running Python).
+pub(crate) const COVERAGE_FOOTER: &str = r#"
+sys.settrace(None)
+threading.settrace(None)
+
+RETURN_VALUE = dis.opmap["RETURN_VALUE"]
+
+
+def _erg_line_starts(code):
+    table = code.co_linetable if hasattr(code, "co_linetable") else code.co_lnotab
+    offsets, lines = [0], [code.co_firstlineno]
+    offset, line = 0, code.co_firstlineno
+    for i in range(0, len(table) - 1, 2):
+        offset += table[i]
+        line += table[i + 1]
+        if offsets[-1] == offset:
+            lines[-1] = line
+        else:
+            offsets.append(offset)
+            lines.append(line)
+    return offsets, lines
+
+
+def _erg_code_objects(code, found):
+    if code not in found and code.co_filename.endswith(".er"):
+        found.add(code)
+        for const in code.co_consts:
+            if isinstance(const, type(code)):
+                _erg_code_objects(const, found)
+
+
+codes = set()
+for code in list(COVERED):
+    _erg_code_objects(code, codes)
+# path -> (executable lines, executed lines)
+files = {}
+for code in codes:
+    executable, executed = files.setdefault(os.path.realpath(code.co_filename), (set(), set()))
+    offsets, lines = _erg_line_starts(code)
+    ends = offsets[1:] + [len(code.co_code)]
+    executable.update(line for line, start, end in zip(lines, offsets, ends) if start < end)
+    # `RETURN_VALUE` is shared by the branches (e.g. of `if`), so it is attributed to the last line of the last branch
+    executed.update(
+        lines[bisect.bisect_right(offsets, offset) - 1]
+        for offset in COVERED.get(code, ())
+        if code.co_code[offset] != RETURN_VALUE
+    )
+with open(COVERAGE, "a", encoding="utf-8") as f:
+    for path, (executable, executed) in files.items():
+        executable = ",".join(map(str, sorted(executable)))
+        executed = ",".join(map(str, sorted(executed)))
+        f.write("\t".join([escape(path), executable, executed]) + "\n")
+"#;
+
+/// `build/coverage.txt` of the package (or the current directory).
+pub fn coverage_data_path() -> PathBuf {
+    package_root(".")
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("build")
+        .join("coverage.txt")
+}
+
+/// The lines of a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub executable: BTreeSet<u32>,
+    pub executed: BTreeSet<u32>,
+}
+
+impl FileCoverage {
+    pub fn covered(&self) -> usize {
+        self.executable.intersection(&self.executed).count()
+    }
+
+    pub fn percent(&self) -> f64 {
+        percent(self.covered(), self.executable.len())
+    }
+
+    /// The ranges of the executable lines not executed.
+    /// The lines between the ranges that are not executable (blank lines, comments, etc.) are included in a range.
+    pub fn missing(&self) -> Vec<(u32, u32)> {
+        let mut ranges: Vec<(u32, u32)> = vec![];
+        let mut extending = false;
+        for &line in self.executable.iter() {
+            if self.executed.contains(&line) {
+                extending = false;
+            } else if let Some(last) = ranges.last_mut().filter(|_| extending) {
+                last.1 = line;
+            } else {
+                ranges.push((line, line));
+                extending = true;
+            }
+        }
+        ranges
+    }
+}
+
+/// The coverage of the files, merged from the records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageData {
+    pub files: BTreeMap<PathBuf, FileCoverage>,
+}
+
+impl CoverageData {
+    /// Merges the records. Malformed records are ignored.
+    pub fn parse(records: &str) -> Self {
+        fn lines(field: &str) -> impl Iterator<Item = u32> + '_ {
+            field.split(',').filter_map(|line| line.parse().ok())
+        }
+        let mut data = Self::default();
+        for record in records.lines() {
+            let [path, executable, executed] = record.split('\t').collect::<Vec<_>>()[..] else {
+                continue;
+            };
+            let file = data.files.entry(PathBuf::from(unescape(path))).or_default();
+            file.executable.extend(lines(executable));
+            file.executed.extend(lines(executed));
+        }
+        data
+    }
+
+    /// The numbers of the executable lines and the covered lines of all the files.
+    pub fn total(&self) -> (usize, usize) {
+        self.files.values().fold((0, 0), |(lines, covered), file| {
+            (lines + file.executable.len(), covered + file.covered())
+        })
+    }
+}
+
+/// 100% if there are no executable lines.
+fn percent(covered: usize, lines: usize) -> f64 {
+    if lines == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / lines as f64
+    }
+}
+
+fn format_ranges(ranges: &[(u32, u32)]) -> String {
+    ranges
+        .iter()
+        .map(|&(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{first}-{last}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_path(path: &Path) -> String {
+    let cwd = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| cwd.canonicalize().ok());
+    cwd.and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn colored_percent(percent: f64) -> String {
+    let color = if percent >= 80.0 {
+        GREEN
+    } else if percent >= 50.0 {
+        YELLOW
+    } else {
+        RED
+    };
+    format!("{color}{percent:>6.1}%{RESET}")
+}
+
+#[derive(Debug)]
+pub struct CoverageReporter {
+    cfg: ErgConfig,
+}
+
+impl CoverageReporter {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self { cfg }
+    }
+
+    /// Restricts `data` to the given files. If no files are given,
+    /// the source files of the package never executed by the tests are added (with no lines).
+    fn select(&self, mut data: CoverageData) -> CoverageData {
+        let canonical = |paths: Vec<PathBuf>| {
+            paths
+                .into_iter()
+                .filter_map(|path| path.canonicalize().ok())
+                .collect::<BTreeSet<_>>()
+        };
+        if !self.cfg.targets.is_empty() {
+            let targets = canonical(expand_targets(&self.cfg.targets));
+            data.files.retain(|path, _| targets.contains(path));
+        } else {
+            for path in canonical(expand_targets(&[])) {
+                if !path.to_string_lossy().ends_with(".d.er") {
+                    data.files.entry(path).or_default();
+                }
+            }
+        }
+        data
+    }
+
+    pub fn report(&self, data: &CoverageData) {
+        let rows = data
+            .files
+            .iter()
+            .map(|(path, file)| (display_path(path), file))
+            .collect::<Vec<_>>();
+        let width = rows
+            .iter()
+            .map(|(path, _)| path.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("TOTAL".len());
+        println!(
+            "{:width$}  {:>6}  {:>7}  {:>7}  Missing",
+            "File", "Lines", "Covered", "Cover"
+        );
+        for (path, file) in rows.iter() {
+            if file.executable.is_empty() {
+                println!(
+                    "{path:width$}  {:>6}  {:>7}  {:>7}  (never executed)",
+                    "-", "-", "-"
+                );
+                continue;
+            }
+            println!(
+                "{path:width$}  {:>6}  {:>7}  {}  {}",
+                file.executable.len(),
+                file.covered(),
+                colored_percent(file.percent()),
+                format_ranges(&file.missing())
+            );
+        }
+        let (lines, covered) = data.total();
+        println!(
+            "{:width$}  {lines:>6}  {covered:>7}  {}",
+            "TOTAL",
+            colored_percent(percent(covered, lines))
+        );
+    }
+
+    /// Reports the coverage recorded by `erg test --coverage`.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let path = coverage_data_path();
+        let Ok(records) = fs::read_to_string(&path) else {
+            eprintln!(
+                "no coverage data in {} (run `erg test --coverage` first)",
+                path.display()
+            );
+            return ExitStatus::ERR1;
+        };
+        let reporter = Self::new(cfg);
+        let data = reporter.select(CoverageData::parse(&records));
+        reporter.report(&data);
+        ExitStatus::OK
+    }
+}
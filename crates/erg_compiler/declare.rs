@@ -152,7 +152,7 @@ impl ASTLowerer {
             ast::Signature::Var(sig) => self.declare_var(sig, def.body),
         };
         // self.pop_append_errs();
-        res
+        res.map(|hir_def| hir_def.with_doc(def.doc))
     }
 
     fn fake_lower_acc(&self, acc: ast::Accessor) -> LowerResult<hir::Accessor> {
@@ -201,7 +201,7 @@ impl ASTLowerer {
     }
 
     fn fake_lower_args(&self, args: ast::Args) -> LowerResult<hir::Args> {
-        let (pos_args_, var_args_, kw_args_, paren) = args.deconstruct();
+        let (pos_args_, var_args_, kw_args_, kw_var_, paren) = args.deconstruct();
         let mut pos_args = vec![];
         for arg in pos_args_.into_iter() {
             let arg = self.fake_lower_expr(arg.expr)?;
@@ -219,7 +219,14 @@ impl ASTLowerer {
             let expr = self.fake_lower_expr(kw_arg.expr)?;
             kw_args.push(hir::KwArg::new(kw_arg.keyword, expr));
         }
-        let args = hir::Args::new(pos_args, var_args, kw_args, paren);
+        let kw_var = match kw_var_ {
+            Some(kw_var) => {
+                let kw_var = self.fake_lower_expr(kw_var.expr)?;
+                Some(hir::PosArg::new(kw_var))
+            }
+            None => None,
+        };
+        let args = hir::Args::new(pos_args, var_args, kw_args, kw_var, paren);
         Ok(args)
     }
 
@@ -263,7 +270,7 @@ impl ASTLowerer {
                     let elem = self.fake_lower_expr(elem.expr)?;
                     elems.push(hir::PosArg::new(elem));
                 }
-                let elems = hir::Args::new(elems, None, vec![], None);
+                let elems = hir::Args::new(elems, None, vec![], None, None);
                 let t = array_t(Type::Failure, TyParam::value(elems.len()));
                 Ok(hir::Array::Normal(hir::NormalArray::new(
                     arr.l_sqbr, arr.r_sqbr, t, elems,
@@ -282,7 +289,7 @@ impl ASTLowerer {
         match tup {
             ast::Tuple::Normal(tup) => {
                 let mut elems = Vec::new();
-                let (elems_, _, _, paren) = tup.elems.deconstruct();
+                let (elems_, _, _, _, paren) = tup.elems.deconstruct();
                 for elem in elems_.into_iter() {
                     let elem = self.fake_lower_expr(elem.expr)?;
                     elems.push(hir::PosArg::new(elem));
@@ -409,7 +416,7 @@ impl ASTLowerer {
     }
 
     fn fake_lower_params(&self, params: ast::Params) -> LowerResult<hir::Params> {
-        let (non_defaults_, var_params_, defaults_, parens) = params.deconstruct();
+        let (non_defaults_, var_params_, defaults_, kw_var_params_, parens) = params.deconstruct();
         let mut non_defaults = vec![];
         for non_default_ in non_defaults_.into_iter() {
             let t_spec_as_expr = non_default_
@@ -455,7 +462,27 @@ impl ASTLowerer {
             let default = hir::DefaultParamSignature::new(sig, default_val);
             defaults.push(default);
         }
-        Ok(hir::Params::new(non_defaults, var_params, defaults, parens))
+        let kw_var_params = if let Some(kw_var_params) = kw_var_params_ {
+            let t_spec_as_expr = kw_var_params
+                .t_spec
+                .as_ref()
+                .map(|t_spec| self.fake_lower_expr(*t_spec.t_spec_as_expr.clone()))
+                .transpose()?;
+            Some(Box::new(hir::NonDefaultParamSignature::new(
+                *kw_var_params,
+                VarInfo::default(),
+                t_spec_as_expr,
+            )))
+        } else {
+            None
+        };
+        Ok(hir::Params::new(
+            non_defaults,
+            var_params,
+            defaults,
+            kw_var_params,
+            parens,
+        ))
     }
 
     fn fake_lower_block(&self, block: ast::Block) -> LowerResult<hir::Block> {
@@ -541,6 +568,7 @@ impl ASTLowerer {
     fn declare_ident(&mut self, tasc: ast::TypeAscription) -> LowerResult<hir::TypeAscription> {
         log!(info "entered {}({})", fn_name!(), tasc);
         let kind = tasc.kind();
+        let available_since = self.module.context.get_available_since(&tasc.decorators);
         match *tasc.expr {
             ast::Expr::Accessor(ast::Accessor::Ident(ident)) => {
                 let py_name = Str::rc(ident.inspect().trim_end_matches('!'));
@@ -553,6 +581,9 @@ impl ASTLowerer {
                 match kind {
                     AscriptionKind::TypeOf | AscriptionKind::AsCast => {
                         self.declare_instance(&ident, &t, py_name.clone())?;
+                        if let Some(vi) = self.module.context.locals.get_mut(ident.inspect()) {
+                            vi.available_since = available_since;
+                        }
                     }
                     AscriptionKind::SubtypeOf => {
                         self.declare_subtype(&ident, &t)?;
@@ -653,12 +684,16 @@ impl ASTLowerer {
                 } else {
                     ctx
                 };
-                let vi = ctx.assign_var_sig(
+                let mut vi = ctx.assign_var_sig(
                     &ast::VarSignature::new(ast::VarPattern::Ident(attr.ident.clone()), None),
                     &t,
                     ast::DefId(0),
                     Some(py_name.clone()),
                 )?;
+                if let Some(registered) = ctx.locals.get_mut(attr.ident.inspect()) {
+                    registered.available_since = available_since;
+                }
+                vi.available_since = available_since;
                 if let Some(types) = self
                     .module
                     .context
@@ -0,0 +1,283 @@
+//! Resolves variables that are declared first and initialized in the branches of a conditional statement.
+//!
+//! ```erg
+//! x: Int
+//! if! cond:
+//!     do!:
+//!         print! "then"
+//!         x = 1
+//!     do!:
+//!         x = 2
+//! ```
+//! is converted into
+//! ```erg
+//! x: Int = if! cond:
+//!     do!:
+//!         print! "then"
+//!         x = 1
+//!         x
+//!     do!:
+//!         x = 2
+//!         x
+//! ```
+//! Every branch (of `if`, `if!`, `match` and `match!`) must assign the variable exactly once,
+//! and `if`/`if!` must have an else branch. Conditional statements can be nested in the branches.
+use erg_common::config::ErgConfig;
+use erg_common::traits::{Locational, Stream};
+use erg_common::{get_hash, log, Str};
+
+use erg_parser::ast::{
+    Accessor, AscriptionKind, Block, Call, Def, DefBody, DefId, Expr, Identifier, Lambda, Module,
+    Signature, TypeSpecWithOp, VarPattern, VarSignature, AST,
+};
+use erg_parser::token::{Token, TokenKind};
+use erg_parser::visitor::{walk_lambda_mut, VisitorMut};
+
+use crate::error::{LowerError, LowerErrors};
+
+/// A variable declared (`x: T`) but not defined yet
+struct Pending {
+    ident: Identifier,
+    t_spec: Option<TypeSpecWithOp>,
+    /// The index of the declaration in the resolved chunks (`None` if declared in an outer block)
+    decl_idx: Option<usize>,
+}
+
+fn is_branching_call(call: &Call) -> bool {
+    call.attr_name.is_none()
+        && matches!(
+            call.obj.get_name().map(|name| &name[..]),
+            Some("if" | "if!" | "match" | "match!")
+        )
+        && call.args.pos_args().len() >= 2
+}
+
+fn is_if_call(call: &Call) -> bool {
+    matches!(
+        call.obj.get_name().map(|name| &name[..]),
+        Some("if" | "if!")
+    )
+}
+
+/// `x = ...` (returns `x`)
+fn defined_name(chunk: &Expr) -> Option<&Str> {
+    match chunk {
+        Expr::Def(Def {
+            sig:
+                Signature::Var(VarSignature {
+                    pat: VarPattern::Ident(ident),
+                    ..
+                }),
+            ..
+        }) => Some(ident.inspect()),
+        _ => None,
+    }
+}
+
+#[derive(Debug)]
+pub struct DefiniteAssigner {
+    cfg: ErgConfig,
+    pub errs: LowerErrors,
+}
+
+impl VisitorMut for DefiniteAssigner {
+    fn visit_module_mut(&mut self, module: &mut Module) {
+        let chunks = std::mem::take(module.ref_mut_payload());
+        *module.ref_mut_payload() = self.resolve_chunks(chunks, vec![]);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let chunks = std::mem::take(block.ref_mut_payload());
+        *block.ref_mut_payload() = self.resolve_chunks(chunks, vec![]);
+    }
+
+    fn visit_lambda_mut(&mut self, lambda: &mut Lambda) {
+        walk_lambda_mut(self, lambda);
+        self.check_last_def(&lambda.body);
+    }
+}
+
+impl DefiniteAssigner {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            cfg,
+            errs: LowerErrors::empty(),
+        }
+    }
+
+    pub fn resolve(&mut self, mut ast: AST) -> AST {
+        log!(info "the definite assignment process has started.");
+        self.visit_module_mut(&mut ast.module);
+        log!(info "the definite assignment process has completed.");
+        ast
+    }
+
+    /// The parser allows a lambda body to end with a definition, which is valid only if it initializes a declared variable.
+    fn check_last_def(&mut self, body: &Block) {
+        if let Some(last) = body.last().filter(|last| last.is_definition()) {
+            self.errs.push(LowerError::last_definition_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                last.loc(),
+                "".into(),
+            ));
+        }
+    }
+
+    /// `inherited`: the variables declared outside the block which may be initialized in it (the block is a branch)
+    fn resolve_chunks(&mut self, chunks: Vec<Expr>, inherited: Vec<Pending>) -> Vec<Expr> {
+        let mut pending = inherited;
+        let mut resolved: Vec<Option<Expr>> = Vec::with_capacity(chunks.len());
+        for mut chunk in chunks {
+            match &mut chunk {
+                Expr::TypeAscription(tasc) if matches!(tasc.kind(), AscriptionKind::TypeOf) => {
+                    if let Expr::Accessor(Accessor::Ident(ident)) = tasc.expr.as_ref() {
+                        pending.retain(|p| p.ident.inspect() != ident.inspect());
+                        pending.push(Pending {
+                            ident: ident.clone(),
+                            t_spec: Some(tasc.t_spec.clone()),
+                            decl_idx: Some(resolved.len()),
+                        });
+                    }
+                    self.visit_expr_mut(&mut chunk);
+                }
+                Expr::Def(_) => {
+                    if let Some(name) = defined_name(&chunk) {
+                        pending.retain(|p| p.ident.inspect() != name);
+                    }
+                    self.visit_expr_mut(&mut chunk);
+                }
+                Expr::Call(call) if !pending.is_empty() && is_branching_call(call) => {
+                    if let Some(idx) = self.resolve_branches(call, &pending) {
+                        let Pending {
+                            ident,
+                            t_spec,
+                            decl_idx,
+                        } = pending.remove(idx);
+                        // the declaration is merged into the definition
+                        if let Some(decl_idx) = decl_idx {
+                            resolved[decl_idx] = None;
+                        }
+                        chunk = Self::initialize(ident, t_spec, chunk);
+                    }
+                }
+                _ => self.visit_expr_mut(&mut chunk),
+            }
+            resolved.push(Some(chunk));
+        }
+        resolved.into_iter().flatten().collect()
+    }
+
+    /// Resolves the branches of `call` and returns the index of the (pending) variable initialized by it.
+    fn resolve_branches(&mut self, call: &mut Call, pending: &[Pending]) -> Option<usize> {
+        let loc = call.loc();
+        let is_if = is_if_call(call);
+        let (cond, rest) = call.args.pos_args_mut().split_at_mut(1);
+        self.visit_expr_mut(&mut cond[0].expr);
+        let mut branches = vec![];
+        for arg in rest.iter_mut() {
+            match &mut arg.expr {
+                Expr::Lambda(lambda) => branches.push(lambda),
+                other => self.visit_expr_mut(other),
+            }
+        }
+        let mut assigned = vec![];
+        for branch in branches.iter_mut() {
+            self.visit_params_mut(&mut branch.sig.params);
+            if let Some(guard) = &mut branch.sig.guard {
+                self.visit_block_mut(guard);
+            }
+            let inherited = pending
+                .iter()
+                .map(|p| Pending {
+                    ident: p.ident.clone(),
+                    t_spec: None,
+                    decl_idx: None,
+                })
+                .collect();
+            let body = std::mem::take(branch.body.ref_mut_payload());
+            *branch.body.ref_mut_payload() = self.resolve_chunks(body, inherited);
+            for name in branch.body.iter().filter_map(defined_name) {
+                if let Some(idx) = pending.iter().position(|p| p.ident.inspect() == name) {
+                    if !assigned.contains(&idx) {
+                        assigned.push(idx);
+                    }
+                }
+            }
+        }
+        let &[idx] = &assigned[..] else {
+            if assigned.len() > 1 {
+                let names = assigned
+                    .iter()
+                    .map(|&idx| pending[idx].ident.inspect().clone())
+                    .collect::<Vec<_>>();
+                self.errs.push(LowerError::multiple_deferred_init_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    "".into(),
+                    &names,
+                ));
+            } else {
+                for branch in branches {
+                    self.check_last_def(&branch.body);
+                }
+            }
+            return None;
+        };
+        let ident = &pending[idx].ident;
+        if is_if && branches.len() < 2 {
+            self.errs.push(LowerError::unassigned_path_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                "".into(),
+                ident.inspect(),
+                true,
+            ));
+        }
+        for branch in branches {
+            let mut defs = branch
+                .body
+                .iter()
+                .filter(|chunk| defined_name(chunk) == Some(ident.inspect()));
+            let Some(def) = defs.next() else {
+                self.errs.push(LowerError::unassigned_path_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    branch.loc(),
+                    "".into(),
+                    ident.inspect(),
+                    false,
+                ));
+                continue;
+            };
+            let ln_begin = def.ln_begin().unwrap_or(0);
+            let col_begin = def.col_begin().unwrap_or(0);
+            if let Some(redef) = defs.next() {
+                self.errs.push(LowerError::reassign_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    redef.loc(),
+                    "".into(),
+                    ident.inspect(),
+                ));
+            }
+            // the branch returns the value of the variable
+            branch
+                .body
+                .push(Expr::local(ident.inspect(), ln_begin, col_begin));
+            self.check_last_def(&branch.body);
+        }
+        Some(idx)
+    }
+
+    /// `x: T` + `if! ...` -> `x: T = if! ...`
+    fn initialize(ident: Identifier, t_spec: Option<TypeSpecWithOp>, expr: Expr) -> Expr {
+        let sig = Signature::Var(VarSignature::new(VarPattern::Ident(ident), t_spec));
+        let id = DefId(get_hash(&(&sig, expr.loc())));
+        let op = Token::from_str(TokenKind::Assign, "=");
+        let body = DefBody::new(op, Block::new(vec![expr]), id);
+        Expr::Def(Def::new(sig, body))
+    }
+}
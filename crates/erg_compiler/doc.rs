@@ -0,0 +1,1009 @@
+//! Generates the API documentation of modules (`erg doc`).
+//!
+//! The documentation is generated from the type-checked modules,
+//! so the signatures are the inferred types (not the type specifications written in the source code),
+//! and the implemented traits are collected from the type contexts.
+use std::fmt::Write as _;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::error::{ErrorDisplay, Location, MultiErrorDisplay};
+use erg_common::pathutil::{expand_targets, package_root, NormalizedPathBuf};
+use erg_common::traits::{ExitStatus, Locational, Stream};
+use erg_common::{escape_json, Str};
+use erg_parser::ast::{self, DocComment};
+
+use crate::check::{CheckResult, PackageChecker};
+use crate::context::{ClassDefType, Context, ModuleContext};
+use crate::error::CompileError;
+use crate::hir::{Expr, HIR};
+use crate::ty::value::ValueObj;
+use crate::ty::{HasType, Type};
+use crate::varinfo::VarInfo;
+use crate::writer::ArtifactWriter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocItemKind {
+    Class,
+    Trait,
+    Patch,
+    Function,
+    Procedure,
+    Variable,
+    Method,
+    Attribute,
+}
+
+impl DocItemKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Class => "class",
+            Self::Trait => "trait",
+            Self::Patch => "patch",
+            Self::Function => "function",
+            Self::Procedure => "procedure",
+            Self::Variable => "variable",
+            Self::Method => "method",
+            Self::Attribute => "attribute",
+        }
+    }
+
+    const fn section(&self) -> &'static str {
+        match self {
+            Self::Class => "Classes",
+            Self::Trait => "Traits",
+            Self::Patch => "Patches",
+            Self::Function => "Functions",
+            Self::Procedure => "Procedures",
+            Self::Variable => "Variables",
+            Self::Method => "Methods",
+            Self::Attribute => "Attributes",
+        }
+    }
+
+    fn of_var(vi: &VarInfo) -> Self {
+        if vi.t.is_class_type() {
+            Self::Class
+        } else if vi.t.is_trait_type() {
+            Self::Trait
+        } else if vi.t.is_procedure() {
+            Self::Procedure
+        } else if vi.t.is_subr() {
+            Self::Function
+        } else {
+            Self::Variable
+        }
+    }
+}
+
+/// A documented item (a public definition of a module, or an attribute of a type).
+#[derive(Debug, Clone)]
+pub struct DocItem {
+    pub name: Str,
+    pub kind: DocItemKind,
+    /// The type of the item.
+    /// For classes, this is the type of the constructor.
+    pub signature: String,
+    /// Markdown text (the doc comments right above the definition)
+    pub doc: String,
+    pub line: u32,
+    /// The fully qualified name of the type defined by this item (for classes and traits)
+    pub qual_name: Option<Str>,
+    pub bases: Vec<String>,
+    pub impls: Vec<String>,
+    /// attributes and methods (not including the trait implementations)
+    pub members: Vec<DocItem>,
+    pub trait_impls: Vec<TraitImplDoc>,
+}
+
+impl DocItem {
+    fn new(name: Str, kind: DocItemKind, signature: String, doc: String, line: u32) -> Self {
+        Self {
+            name,
+            kind,
+            signature,
+            doc,
+            line,
+            qual_name: None,
+            bases: vec![],
+            impls: vec![],
+            members: vec![],
+            trait_impls: vec![],
+        }
+    }
+}
+
+/// The methods defined in a `C|C <: T|.` block.
+#[derive(Debug, Clone)]
+pub struct TraitImplDoc {
+    pub trait_: String,
+    pub methods: Vec<DocItem>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleDoc {
+    /// e.g. `foo.bar` (for `src/foo/bar.er`)
+    pub name: String,
+    pub path: PathBuf,
+    pub doc: String,
+    pub items: Vec<DocItem>,
+    /// The name of the module context, which qualifies the types defined in the module.
+    /// This depends on how the module is imported (e.g. `../shapes`).
+    ctx_name: Str,
+}
+
+impl ModuleDoc {
+    pub fn page(&self) -> String {
+        format!("{}.html", self.name)
+    }
+
+    /// Extracts the public items of a type-checked module.
+    pub fn new(name: String, path: PathBuf, hir: &HIR, module: &ModuleContext) -> Self {
+        let (mut loose_docs, defs) = attach_docs(hir.module.iter());
+        let mut items = vec![];
+        for (i, (doc, expr)) in defs.into_iter().enumerate() {
+            // the doc comment at the top of the module (e.g. followed by `import`)
+            let doc =
+                if i == 0 && loose_docs.is_empty() && !doc.is_empty() && !is_documentable(expr) {
+                    loose_docs.push(doc);
+                    String::new()
+                } else {
+                    doc
+                };
+            if let Some(item) = item_doc(expr, doc, &module.context) {
+                items.push(item);
+            }
+        }
+        Self {
+            name,
+            path,
+            doc: loose_docs.join("\n\n"),
+            items,
+            ctx_name: module.context.name.clone(),
+        }
+    }
+}
+
+/// Replaces the module context names in the types with the module names (e.g. `../shapes.Point` => `shapes.Point`).
+fn requalify(modules: &mut [ModuleDoc]) {
+    let mut renames = modules
+        .iter()
+        .filter(|module| module.ctx_name[..] != module.name)
+        .map(|module| (format!("{}.", module.ctx_name), format!("{}.", module.name)))
+        .collect::<Vec<_>>();
+    if renames.is_empty() {
+        return;
+    }
+    renames.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+    let rename = |text: &str| {
+        let mut renamed = String::with_capacity(text.len());
+        let mut rest = text;
+        let mut at_boundary = true;
+        while let Some(c) = rest.chars().next() {
+            if at_boundary {
+                if let Some((from, to)) = renames.iter().find(|(from, _)| rest.starts_with(from)) {
+                    renamed.push_str(to);
+                    rest = &rest[from.len()..];
+                    continue;
+                }
+            }
+            renamed.push(c);
+            rest = &rest[c.len_utf8()..];
+            at_boundary = !(c.is_alphanumeric() || matches!(c, '_' | '.' | '/' | '!'));
+        }
+        renamed
+    };
+    fn rename_item(item: &mut DocItem, rename: &impl Fn(&str) -> String) {
+        item.signature = rename(&item.signature);
+        item.qual_name = item.qual_name.as_ref().map(|name| Str::from(rename(name)));
+        for t in item.bases.iter_mut().chain(item.impls.iter_mut()) {
+            *t = rename(t);
+        }
+        for member in item.members.iter_mut() {
+            rename_item(member, rename);
+        }
+        for imp in item.trait_impls.iter_mut() {
+            imp.trait_ = rename(&imp.trait_);
+            for method in imp.methods.iter_mut() {
+                rename_item(method, rename);
+            }
+        }
+    }
+    for module in modules.iter_mut() {
+        for item in module.items.iter_mut() {
+            rename_item(item, &rename);
+        }
+    }
+}
+
+/// Pairs the definitions with the doc comments attached to them (`'''...'''` or `#| ...` right above the definitions).
+/// The doc comments not attached to any definition are returned separately.
+fn attach_docs<'e>(
+    exprs: impl Iterator<Item = &'e Expr>,
+) -> (Vec<String>, Vec<(String, &'e Expr)>) {
+    let mut loose = vec![];
+    let mut defs = vec![];
+    let mut pending: Vec<ast::Literal> = vec![];
+    for expr in exprs {
+        match expr {
+            Expr::Lit(lit) if lit.is_doc_comment() => {
+                let lit = ast::Literal::new(lit.token.clone());
+                // separated by blank lines
+                if let Some(last) = pending.last() {
+                    if lit.ln_begin().unwrap_or(0) > last.ln_last() + 1 {
+                        loose.push(DocComment::new(mem::take(&mut pending)).text());
+                    }
+                }
+                pending.push(lit);
+            }
+            _ => {
+                if !pending.is_empty() {
+                    loose.push(DocComment::new(mem::take(&mut pending)).text());
+                }
+                let doc = match expr {
+                    Expr::Def(def) => def.doc.as_ref(),
+                    Expr::ClassDef(class_def) => class_def.doc.as_ref(),
+                    Expr::PatchDef(patch_def) => patch_def.doc.as_ref(),
+                    _ => None,
+                };
+                defs.push((doc.map_or(String::new(), DocComment::text), expr));
+            }
+        }
+    }
+    if !pending.is_empty() {
+        loose.push(DocComment::new(pending).text());
+    }
+    (loose, defs)
+}
+
+fn is_documentable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Def(def) => def.sig.vis().is_public() && !def.sig.ident().vi.t.is_module(),
+        Expr::ClassDef(class_def) => class_def.sig.vis().is_public(),
+        Expr::PatchDef(patch_def) => patch_def.sig.vis().is_public(),
+        _ => false,
+    }
+}
+
+fn item_doc(expr: &Expr, doc: String, ctx: &Context) -> Option<DocItem> {
+    if !is_documentable(expr) {
+        return None;
+    }
+    let line = expr.ln_begin().unwrap_or(0);
+    match expr {
+        Expr::Def(def) => {
+            let ident = def.sig.ident();
+            let kind = DocItemKind::of_var(&ident.vi);
+            let mut item = DocItem::new(
+                ident.inspect().clone(),
+                kind,
+                ident.vi.t.to_string(),
+                doc,
+                line,
+            );
+            if kind == DocItemKind::Trait {
+                if let Some(ValueObj::Type(trait_)) = ctx.rec_get_const_obj(ident.inspect()) {
+                    let trait_ = trait_.typ();
+                    item.signature = "Trait".into();
+                    item.qual_name = Some(trait_.qual_name());
+                    if let Some((_, trait_ctx)) = ctx.get_nominal_type_ctx(trait_) {
+                        item.bases = types(&trait_ctx.super_traits);
+                        item.members = trait_ctx
+                            .decls
+                            .iter()
+                            .chain(trait_ctx.locals.iter())
+                            .filter(|(name, vi)| vi.vis.is_public() && !name.inspect().is_empty())
+                            .map(|(name, vi)| {
+                                DocItem::new(
+                                    name.inspect().clone(),
+                                    DocItemKind::Method,
+                                    vi.t.to_string(),
+                                    String::new(),
+                                    vi.def_loc.loc.ln_begin().unwrap_or(0),
+                                )
+                            })
+                            .collect();
+                        sort_by_line(&mut item.members);
+                    }
+                }
+            }
+            Some(item)
+        }
+        Expr::ClassDef(class_def) => {
+            let ident = class_def.sig.ident();
+            let class = class_def.obj.typ();
+            let mut item = DocItem::new(
+                ident.inspect().clone(),
+                DocItemKind::Class,
+                class_def.__new__.to_string(),
+                doc,
+                line,
+            );
+            item.qual_name = Some(class.qual_name());
+            let (_, method_defs) = attach_docs(class_def.methods.iter());
+            let method_docs = method_defs
+                .into_iter()
+                .filter_map(|(doc, expr)| match expr {
+                    Expr::Def(def) => Some((def.sig.ident().vi.def_loc.loc, doc)),
+                    _ => None,
+                })
+                .collect::<Dict<_, _>>();
+            if let Some((_, class_ctx)) = ctx.get_nominal_type_ctx(class) {
+                item.bases = types(
+                    class_ctx
+                        .super_classes
+                        .iter()
+                        .filter(|sup| sup != &&Type::Obj),
+                );
+                item.impls = types(&class_ctx.super_traits);
+                item.members = attrs(class_ctx, DocItemKind::Attribute, &method_docs);
+                for (def_t, methods) in class_ctx.methods_list.iter() {
+                    let methods = attrs(methods, DocItemKind::Method, &method_docs);
+                    match def_t {
+                        ClassDefType::Simple(_) => item.members.extend(methods),
+                        ClassDefType::ImplTrait { impl_trait, .. } => {
+                            item.trait_impls.push(TraitImplDoc {
+                                trait_: impl_trait.to_string(),
+                                methods,
+                            });
+                        }
+                    }
+                }
+            }
+            Some(item)
+        }
+        Expr::PatchDef(patch_def) => {
+            let ident = patch_def.sig.ident();
+            let mut item = DocItem::new(
+                ident.inspect().clone(),
+                DocItemKind::Patch,
+                patch_def.base.ref_t().to_string(),
+                doc,
+                line,
+            );
+            let (_, method_defs) = attach_docs(patch_def.methods.iter());
+            for (doc, expr) in method_defs {
+                if let Expr::Def(def) = expr {
+                    if def.sig.vis().is_public() {
+                        let ident = def.sig.ident();
+                        item.members.push(DocItem::new(
+                            ident.inspect().clone(),
+                            DocItemKind::Method,
+                            ident.vi.t.to_string(),
+                            doc,
+                            expr.ln_begin().unwrap_or(0),
+                        ));
+                    }
+                }
+            }
+            Some(item)
+        }
+        _ => None,
+    }
+}
+
+fn types<'t>(types: impl IntoIterator<Item = &'t Type>) -> Vec<String> {
+    types.into_iter().map(|t| t.to_string()).collect()
+}
+
+/// The public attributes (or methods) of a type context
+fn attrs(ctx: &Context, kind: DocItemKind, docs: &Dict<Location, String>) -> Vec<DocItem> {
+    let mut attrs = ctx
+        .locals
+        .iter()
+        .chain(ctx.decls.iter())
+        .filter(|(_, vi)| {
+            vi.vis.is_public()
+                && !vi.kind.is_auto()
+                && vi.kind.is_instance_attr() == (kind == DocItemKind::Attribute)
+        })
+        .map(|(name, vi)| {
+            DocItem::new(
+                name.inspect().clone(),
+                kind,
+                vi.t.to_string(),
+                docs.get(&vi.def_loc.loc).cloned().unwrap_or_default(),
+                vi.def_loc.loc.ln_begin().unwrap_or(0),
+            )
+        })
+        .collect::<Vec<_>>();
+    sort_by_line(&mut attrs);
+    attrs
+}
+
+fn sort_by_line(items: &mut [DocItem]) {
+    items.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.name.cmp(&b.name)));
+}
+
+/// Resolves the names of the documented types (and the other items) to the links to their documentation.
+#[derive(Debug, Default)]
+pub struct DocIndex {
+    links: Dict<String, String>,
+}
+
+impl DocIndex {
+    pub fn new(modules: &[ModuleDoc]) -> Self {
+        let mut links = Dict::new();
+        for module in modules.iter() {
+            for item in module.items.iter() {
+                let url = format!("{}#{}", module.page(), item.name);
+                links.insert(format!("{}.{}", module.name, item.name), url);
+            }
+        }
+        Self { links }
+    }
+
+    /// e.g. `foo.C` -> `foo.html#C`
+    pub fn resolve(&self, name: &str) -> Option<&String> {
+        self.links.get(name)
+    }
+
+    /// Resolves a name written in the documentation of `module` (the items of `module` can be referred by the local names).
+    fn resolve_in(&self, module: &ModuleDoc, name: &str) -> Option<String> {
+        if let Some(url) = self.resolve(name) {
+            return Some(url.clone());
+        }
+        let (head, attr) = name.split_once('.').unwrap_or((name, ""));
+        let item = module.items.iter().find(|item| &item.name[..] == head)?;
+        let has_attr = item
+            .members
+            .iter()
+            .chain(item.trait_impls.iter().flat_map(|imp| imp.methods.iter()))
+            .any(|member| &member.name[..] == attr);
+        if has_attr {
+            Some(format!("{}#{head}.{attr}", module.page()))
+        } else {
+            Some(format!("{}#{head}", module.page()))
+        }
+    }
+
+    /// The names in `text` (e.g. a type) that refer to documented items
+    fn refs(&self, text: &str) -> Vec<(String, String)> {
+        let mut refs: Vec<(String, String)> = vec![];
+        for name in names(text) {
+            if let Some(url) = self.resolve(name) {
+                if !refs.iter().any(|(n, _)| n == name) {
+                    refs.push((name.to_string(), url.clone()));
+                }
+            }
+        }
+        refs
+    }
+
+    /// Escapes `text` and links the names of the documented items.
+    fn link(&self, text: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+        let mut html = String::new();
+        let mut rest = text;
+        for name in names(text) {
+            let offset = name.as_ptr() as usize - rest.as_ptr() as usize;
+            html.push_str(&escape_html(&rest[..offset]));
+            match resolve(name) {
+                Some(url) => {
+                    let _ = write!(html, "<a href=\"{url}\">{}</a>", escape_html(name));
+                }
+                None => html.push_str(&escape_html(name)),
+            }
+            rest = &rest[offset + name.len()..];
+        }
+        html.push_str(&escape_html(rest));
+        html
+    }
+}
+
+/// Splits `text` into the (possibly qualified) names, e.g. `(x: foo.C) -> Int` => `x`, `foo.C`, `Int`
+fn names(text: &str) -> impl Iterator<Item = &str> {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '!';
+    let mut names = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        let continues = is_name_char(c)
+            || (c == '.'
+                && start.is_some()
+                && text[i + 1..].starts_with(|c: char| c.is_alphabetic() || c == '_'));
+        match (start, continues) {
+            (None, true) if !c.is_ascii_digit() && c != '.' => start = Some(i),
+            (Some(s), false) => {
+                names.push(&text[s..i]);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        names.push(&text[s..]);
+    }
+    names.into_iter()
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn json_array<T>(elems: &[T], f: impl Fn(&T) -> String) -> String {
+    let elems = elems.iter().map(f).collect::<Vec<_>>();
+    format!("[{}]", elems.join(","))
+}
+
+/// Renders the documentation of the modules as a JSON (for external tools).
+/// The names in the signatures that refer to documented items are listed in `refs` with their URLs.
+pub fn to_json(modules: &[ModuleDoc], index: &DocIndex) -> String {
+    fn item_json(item: &DocItem, index: &DocIndex) -> String {
+        let refs = json_array(&index.refs(&item.signature), |(name, url)| {
+            format!(
+                "{{\"name\":{},\"url\":{}}}",
+                escape_json(name),
+                escape_json(url)
+            )
+        });
+        let mut json = format!(
+            "{{\"name\":{},\"kind\":{},\"signature\":{},\"doc\":{},\"line\":{},\"refs\":{refs}",
+            escape_json(&item.name),
+            escape_json(item.kind.as_str()),
+            escape_json(&item.signature),
+            escape_json(&item.doc),
+            item.line,
+        );
+        if let Some(qual_name) = &item.qual_name {
+            let _ = write!(json, ",\"qual_name\":{}", escape_json(qual_name));
+            let _ = write!(
+                json,
+                ",\"bases\":{},\"impls\":{},\"members\":{},\"trait_impls\":{}",
+                json_array(&item.bases, |t| escape_json(t)),
+                json_array(&item.impls, |t| escape_json(t)),
+                json_array(&item.members, |member| item_json(member, index)),
+                json_array(&item.trait_impls, |imp| format!(
+                    "{{\"trait\":{},\"methods\":{}}}",
+                    escape_json(&imp.trait_),
+                    json_array(&imp.methods, |method| item_json(method, index))
+                )),
+            );
+        } else if !item.members.is_empty() {
+            let _ = write!(
+                json,
+                ",\"members\":{}",
+                json_array(&item.members, |member| item_json(member, index))
+            );
+        }
+        json.push('}');
+        json
+    }
+    let modules = json_array(modules, |module| {
+        format!(
+            "{{\"name\":{},\"path\":{},\"page\":{},\"doc\":{},\"items\":{}}}",
+            escape_json(&module.name),
+            escape_json(&module.path.to_string_lossy()),
+            escape_json(&module.page()),
+            escape_json(&module.doc),
+            json_array(&module.items, |item| item_json(item, index)),
+        )
+    });
+    format!("{{\"modules\":{modules}}}\n")
+}
+
+/// A small subset of Markdown: paragraphs, headings, lists, fenced code blocks and inline code.
+/// Inline code that names a documented item is linked to it.
+fn markdown_to_html(text: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+    let inline = |line: &str| {
+        let mut html = String::new();
+        for (i, part) in line.split('`').enumerate() {
+            if i % 2 == 0 {
+                html.push_str(&escape_html(part));
+            } else if let Some(url) = resolve(part) {
+                let _ = write!(
+                    html,
+                    "<a href=\"{url}\"><code>{}</code></a>",
+                    escape_html(part)
+                );
+            } else {
+                let _ = write!(html, "<code>{}</code>", escape_html(part));
+            }
+        }
+        html
+    };
+    let mut html = String::new();
+    let mut paragraph = vec![];
+    let mut in_list = false;
+    let mut code: Option<Vec<&str>> = None;
+    let flush = |html: &mut String, paragraph: &mut Vec<String>, in_list: &mut bool| {
+        if !paragraph.is_empty() {
+            let _ = writeln!(html, "<p>{}</p>", paragraph.join("\n"));
+            paragraph.clear();
+        }
+        if *in_list {
+            html.push_str("</ul>\n");
+            *in_list = false;
+        }
+    };
+    let mut lang = "";
+    for line in text.lines() {
+        if let Some(code_lines) = code.as_mut() {
+            if line.trim_start().starts_with("```") {
+                let _ = writeln!(
+                    html,
+                    "<pre><code class=\"language-{lang}\">{}</code></pre>",
+                    escape_html(&code_lines.join("\n"))
+                );
+                code = None;
+            } else {
+                code_lines.push(line);
+            }
+            continue;
+        }
+        let trimmed = line.trim();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            flush(&mut html, &mut paragraph, &mut in_list);
+            lang = if info.is_empty() { "erg" } else { info };
+            code = Some(vec![]);
+        } else if trimmed.is_empty() {
+            flush(&mut html, &mut paragraph, &mut in_list);
+        } else if let Some(heading) = trimmed.strip_prefix("# ") {
+            flush(&mut html, &mut paragraph, &mut in_list);
+            let _ = writeln!(html, "<h4>{}</h4>", inline(heading));
+        } else if let Some(elem) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            if !in_list {
+                flush(&mut html, &mut paragraph, &mut in_list);
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            let _ = writeln!(html, "<li>{}</li>", inline(elem));
+        } else {
+            if in_list {
+                flush(&mut html, &mut paragraph, &mut in_list);
+            }
+            paragraph.push(inline(trimmed));
+        }
+    }
+    if let Some(code_lines) = code {
+        let _ = writeln!(
+            html,
+            "<pre><code class=\"language-{lang}\">{}</code></pre>",
+            escape_html(&code_lines.join("\n"))
+        );
+    }
+    flush(&mut html, &mut paragraph, &mut in_list);
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 0; display: flex; }
+nav { min-width: 14em; padding: 1em; background: #f4f4f4; min-height: 100vh; }
+nav ul { list-style: none; padding-left: 0.5em; }
+main { padding: 1em 2em; max-width: 60em; }
+pre, code { font-family: monospace; background: #f4f4f4; }
+pre { padding: 0.5em; overflow-x: auto; }
+pre.sig { background: #eef3fa; }
+.item { margin-bottom: 2em; }
+.member { margin-left: 1.5em; }
+.kind { color: #777; font-size: 0.8em; font-weight: normal; }
+a { color: #2a62b5; text-decoration: none; }
+";
+
+fn page(title: &str, modules: &[ModuleDoc], body: &str) -> String {
+    let mut nav = String::from("<nav><a href=\"index.html\">Index</a><ul>\n");
+    for module in modules.iter() {
+        let _ = writeln!(
+            nav,
+            "<li><a href=\"{}\">{}</a></li>",
+            escape_html(&module.page()),
+            escape_html(&module.name)
+        );
+    }
+    nav.push_str("</ul></nav>");
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+{nav}
+<main>
+{body}</main>
+</body>
+</html>
+",
+        escape_html(title)
+    )
+}
+
+fn item_html(
+    html: &mut String,
+    module: &ModuleDoc,
+    item: &DocItem,
+    anchor: &str,
+    index: &DocIndex,
+) {
+    let resolve = |name: &str| index.resolve_in(module, name);
+    let class = if anchor.contains('.') {
+        "item member"
+    } else {
+        "item"
+    };
+    let _ = writeln!(
+        html,
+        "<div class=\"{class}\" id=\"{}\">\n<h3><code>{}</code> <span class=\"kind\">{}</span></h3>",
+        escape_html(anchor),
+        escape_html(&item.name),
+        item.kind.as_str()
+    );
+    let _ = writeln!(
+        html,
+        "<pre class=\"sig\">{}</pre>",
+        index.link(&item.signature, |name| index.resolve(name).cloned())
+    );
+    if !item.bases.is_empty() {
+        let bases = item
+            .bases
+            .iter()
+            .map(|base| index.link(base, |name| index.resolve(name).cloned()))
+            .collect::<Vec<_>>();
+        let _ = writeln!(html, "<p>Base: <code>{}</code></p>", bases.join(", "));
+    }
+    if !item.impls.is_empty() {
+        let impls = item
+            .impls
+            .iter()
+            .map(|impl_| index.link(impl_, |name| index.resolve(name).cloned()))
+            .collect::<Vec<_>>();
+        let _ = writeln!(html, "<p>Implements: <code>{}</code></p>", impls.join(", "));
+    }
+    html.push_str(&markdown_to_html(&item.doc, resolve));
+    let mut kind = None;
+    for member in item.members.iter() {
+        if kind != Some(member.kind) {
+            let _ = writeln!(html, "<h4 class=\"member\">{}</h4>", member.kind.section());
+            kind = Some(member.kind);
+        }
+        item_html(
+            html,
+            module,
+            member,
+            &format!("{anchor}.{}", member.name),
+            index,
+        );
+    }
+    for imp in item.trait_impls.iter() {
+        let _ = writeln!(
+            html,
+            "<h4 class=\"member\">Implementation of <code>{}</code></h4>",
+            index.link(&imp.trait_, |name| index.resolve(name).cloned())
+        );
+        for method in imp.methods.iter() {
+            item_html(
+                html,
+                module,
+                method,
+                &format!("{anchor}.{}", method.name),
+                index,
+            );
+        }
+    }
+    html.push_str("</div>\n");
+}
+
+pub fn module_html(module: &ModuleDoc, modules: &[ModuleDoc], index: &DocIndex) -> String {
+    let mut body = format!(
+        "<h1>Module <code>{}</code></h1>\n",
+        escape_html(&module.name)
+    );
+    body.push_str(&markdown_to_html(&module.doc, |name| {
+        index.resolve_in(module, name)
+    }));
+    for kind in [
+        DocItemKind::Class,
+        DocItemKind::Trait,
+        DocItemKind::Patch,
+        DocItemKind::Function,
+        DocItemKind::Procedure,
+        DocItemKind::Variable,
+    ] {
+        let items = module
+            .items
+            .iter()
+            .filter(|item| item.kind == kind)
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            continue;
+        }
+        let _ = writeln!(body, "<h2>{}</h2>", kind.section());
+        for item in items {
+            item_html(&mut body, module, item, &item.name, index);
+        }
+    }
+    page(&module.name, modules, &body)
+}
+
+pub fn index_html(title: &str, modules: &[ModuleDoc]) -> String {
+    let mut body = format!("<h1>{}</h1>\n<ul>\n", escape_html(title));
+    for module in modules.iter() {
+        let summary = module.doc.lines().next().unwrap_or("");
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\"><code>{}</code></a> {}</li>",
+            escape_html(&module.page()),
+            escape_html(&module.name),
+            escape_html(summary)
+        );
+    }
+    body.push_str("</ul>\n");
+    page(title, modules, &body)
+}
+
+/// e.g. `src/foo/bar.er` => `foo.bar`, `src/foo/__init__.er` => `foo`
+fn module_name(path: &Path, base: &Path) -> String {
+    let rel = path.strip_prefix(base).unwrap_or(path);
+    let rel = rel.to_string_lossy().replace('\\', "/");
+    let rel = rel
+        .trim_start_matches("./")
+        .trim_end_matches(".er")
+        .trim_end_matches(".d")
+        .trim_end_matches("/__init__");
+    if rel.is_empty() {
+        "__init__".into()
+    } else {
+        rel.replace('/', ".")
+    }
+}
+
+/// The longest common directory of the paths
+fn common_dir(paths: &[PathBuf]) -> PathBuf {
+    let mut dirs = paths.iter().filter_map(|path| path.parent());
+    let Some(first) = dirs.next() else {
+        return PathBuf::new();
+    };
+    let mut common = first.to_path_buf();
+    for dir in dirs {
+        while !dir.starts_with(&common) {
+            if !common.pop() {
+                return PathBuf::new();
+            }
+        }
+    }
+    common
+}
+
+/// Type-checks the modules and generates their documentation (`erg doc`).
+#[derive(Debug)]
+pub struct DocGenerator {
+    checker: PackageChecker,
+}
+
+impl DocGenerator {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            checker: PackageChecker::new(cfg),
+        }
+    }
+
+    /// Checks the module and extracts its documentation.
+    /// The documentation is generated even if the module has errors (as far as it is type-checked).
+    pub fn document_file(&mut self, path: &Path, name: String) -> (Option<ModuleDoc>, CheckResult) {
+        let result = self.checker.check_file(path);
+        let entry = self
+            .checker
+            .shared()
+            .mod_cache
+            .get(&NormalizedPathBuf::from(path));
+        let doc = entry.and_then(|entry| {
+            let hir = entry.hir.as_ref()?;
+            Some(ModuleDoc::new(name, path.to_path_buf(), hir, &entry.module))
+        });
+        (doc, result)
+    }
+
+    /// Returns the documentation of the files (sorted by the module names) and the diagnostics.
+    pub fn document_files(&mut self, paths: &[PathBuf]) -> (Vec<ModuleDoc>, Vec<CheckResult>) {
+        // the module names are relative to the source directory of the package
+        let base = match package_root(".") {
+            Some(root) if self.checker.cfg().targets.is_empty() => {
+                let src = root.join("src");
+                if src.is_dir() {
+                    src
+                } else {
+                    root
+                }
+            }
+            _ => common_dir(paths),
+        };
+        let mut docs = vec![];
+        let mut results = vec![];
+        for path in paths.iter() {
+            let path = if base.is_absolute() {
+                path.canonicalize().unwrap_or(path.clone())
+            } else {
+                path.clone()
+            };
+            let (doc, result) = self.document_file(&path, module_name(&path, &base));
+            docs.extend(doc);
+            results.push(result);
+        }
+        docs.sort_by(|a, b| a.name.cmp(&b.name));
+        requalify(&mut docs);
+        (docs, results)
+    }
+
+    /// Writes `index.html`, `<module>.html` and `index.json` to `out_dir`.
+    pub fn write(
+        writer: &ArtifactWriter,
+        title: &str,
+        modules: &[ModuleDoc],
+        out_dir: &Path,
+    ) -> Result<(), CompileError> {
+        writer.create_dir_all(out_dir)?;
+        let index = DocIndex::new(modules);
+        writer.write(
+            &out_dir.join("index.html"),
+            index_html(title, modules).as_bytes(),
+        )?;
+        for module in modules.iter() {
+            writer.write(
+                &out_dir.join(module.page()),
+                module_html(module, modules, &index).as_bytes(),
+            )?;
+        }
+        writer.write(
+            &out_dir.join("index.json"),
+            to_json(modules, &index).as_bytes(),
+        )
+    }
+
+    /// Generates the documentation of the targets (or the package) into `--output-dir` (`build/doc` by default).
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let out_dir = match cfg.dist_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => package_root(".")
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("build")
+                .join("doc"),
+        };
+        let title = package_root(".")
+            .and_then(|root| Some(root.file_name()?.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "API documentation".into());
+        let mut generator = Self::new(cfg);
+        let targets = expand_targets(&generator.checker.cfg().targets);
+        if targets.is_empty() {
+            eprintln!("no files to document (not in a package, and no files are given)");
+            return ExitStatus::ERR1;
+        }
+        let (modules, results) = generator.document_files(&targets);
+        let mut num_errors = 0;
+        let mut num_warns = 0;
+        for result in results.iter() {
+            result.warns.write_all_stderr();
+            result.errors.write_all_stderr();
+            num_errors += result.errors.len();
+            num_warns += result.warns.len();
+        }
+        let writer = ArtifactWriter::new(generator.checker.cfg());
+        if let Err(err) = Self::write(&writer, &title, &modules, &out_dir) {
+            err.write_to_stderr();
+            return ExitStatus::new(1, num_warns, num_errors + 1);
+        }
+        if !generator.checker.cfg().quiet {
+            println!(
+                "documented {} module(s): {}",
+                modules.len(),
+                out_dir.join("index.html").display()
+            );
+        }
+        let code = if num_errors > 0 { 1 } else { 0 };
+        ExitStatus::new(code, num_warns, num_errors)
+    }
+}
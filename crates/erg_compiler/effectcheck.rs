@@ -9,7 +9,7 @@ use erg_common::Str;
 use erg_parser::token::TokenKind;
 
 use crate::error::{EffectError, EffectErrors};
-use crate::hir::{Array, Def, Dict, Expr, Params, Set, Signature, Tuple, HIR};
+use crate::hir::{Array, Def, Dict, Expr, Identifier, Params, Set, Signature, Tuple, HIR};
 use crate::ty::{HasType, Visibility};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -79,6 +79,21 @@ impl SideEffectChecker {
         }
     }
 
+    /// Returns the name of the innermost function that forbids side effects here.
+    /// `None` if it is a lambda.
+    fn enclosing_func_name(&self) -> Option<String> {
+        let idx = self
+            .block_stack
+            .iter()
+            .rposition(|kind| matches!(kind, Func | ConstFunc))?;
+        let name = &self.path_stack.get(idx)?.def_namespace;
+        if name.starts_with('<') {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+
     pub fn check(mut self, hir: HIR) -> Result<HIR, (HIR, EffectErrors)> {
         self.path_stack.push(Visibility::private(hir.name.clone()));
         self.block_stack.push(Module);
@@ -110,9 +125,15 @@ impl SideEffectChecker {
                     for parg in call.args.pos_args.iter() {
                         self.check_expr(&parg.expr);
                     }
+                    if let Some(var_args) = &call.args.var_args {
+                        self.check_expr(&var_args.expr);
+                    }
                     for kwarg in call.args.kw_args.iter() {
                         self.check_expr(&kwarg.expr);
                     }
+                    if let Some(kw_var) = &call.args.kw_var {
+                        self.check_expr(&kw_var.expr);
+                    }
                 }
                 Expr::BinOp(bin) => {
                     self.check_expr(&bin.lhs);
@@ -133,8 +154,8 @@ impl SideEffectChecker {
                         self.check_expr(&arr.len);
                     }
                     Array::Comprehension(arr) => {
+                        self.check_comprehension_clauses(&arr.generators, &arr.guards);
                         self.check_expr(&arr.elem);
-                        self.check_expr(&arr.guard);
                     }
                 },
                 Expr::Tuple(tuple) => match tuple {
@@ -164,6 +185,10 @@ impl SideEffectChecker {
                         self.check_expr(&set.elem);
                         self.check_expr(&set.len);
                     }
+                    Set::Comprehension(set) => {
+                        self.check_comprehension_clauses(&set.generators, &set.guards);
+                        self.check_expr(&set.elem);
+                    }
                 },
                 Expr::Dict(dict) => match dict {
                     Dict::Normal(dict) => {
@@ -172,7 +197,11 @@ impl SideEffectChecker {
                             self.check_expr(&kv.value);
                         }
                     }
-                    other => todo!("{other}"),
+                    Dict::Comprehension(dict) => {
+                        self.check_comprehension_clauses(&dict.generators, &dict.guards);
+                        self.check_expr(&dict.key);
+                        self.check_expr(&dict.value);
+                    }
                 },
                 Expr::TypeAsc(tasc) => {
                     self.check_expr(&tasc.expr);
@@ -188,6 +217,9 @@ impl SideEffectChecker {
                             .push(Visibility::private(Str::ever("<lambda>")));
                         self.block_stack.push(Func);
                     }
+                    if let Some(guard) = &lambda.guard {
+                        guard.iter().for_each(|chunk| self.check_expr(chunk));
+                    }
                     lambda.body.iter().for_each(|chunk| self.check_expr(chunk));
                     self.path_stack.pop();
                     self.block_stack.pop();
@@ -268,6 +300,21 @@ impl SideEffectChecker {
             }
         }
         if let Signature::Subr(sig) = &def.sig {
+            let cached = sig
+                .ident
+                .vi
+                .comptime_decos
+                .as_ref()
+                .is_some_and(|decos| decos.contains("Cache"));
+            if cached && is_procedural {
+                self.errs.push(EffectError::cache_proc_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    sig.ident.loc(),
+                    sig.ident.inspect(),
+                    self.full_path(),
+                ));
+            }
             self.check_params(&sig.params);
         }
         let last_idx = def.body.block.len() - 1;
@@ -343,8 +390,8 @@ impl SideEffectChecker {
                     self.check_expr(&arr.len);
                 }
                 Array::Comprehension(arr) => {
+                    self.check_comprehension_clauses(&arr.generators, &arr.guards);
                     self.check_expr(&arr.elem);
-                    self.check_expr(&arr.guard);
                 }
             },
             Expr::Tuple(tuple) => match tuple {
@@ -374,6 +421,10 @@ impl SideEffectChecker {
                     self.check_expr(&set.elem);
                     self.check_expr(&set.len);
                 }
+                Set::Comprehension(set) => {
+                    self.check_comprehension_clauses(&set.generators, &set.guards);
+                    self.check_expr(&set.elem);
+                }
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dict) => {
@@ -382,10 +433,18 @@ impl SideEffectChecker {
                         self.check_expr(&kv.value);
                     }
                 }
-                other => todo!("{other}"),
+                Dict::Comprehension(dict) => {
+                    self.check_comprehension_clauses(&dict.generators, &dict.guards);
+                    self.check_expr(&dict.key);
+                    self.check_expr(&dict.value);
+                }
             },
             Expr::Call(call) => {
                 if (call.obj.t().is_procedure()
+                    || call
+                        .signature_t()
+                        .map(|t| t.is_procedure())
+                        .unwrap_or(false)
                     || call
                         .attr_name
                         .as_ref()
@@ -393,21 +452,44 @@ impl SideEffectChecker {
                         .unwrap_or(false))
                     && !self.in_context_effects_allowed()
                 {
-                    self.errs.push(EffectError::has_effect(
-                        self.cfg.input.clone(),
-                        line!() as usize,
-                        expr,
-                        self.full_path(),
-                    ));
+                    let callee = if let Some(attr) = call.attr_name.as_ref() {
+                        Some(attr.inspect().to_string())
+                    } else {
+                        call.obj.show_acc()
+                    };
+                    if let Some(callee) = callee {
+                        let caller = self.enclosing_func_name();
+                        self.errs.push(EffectError::proc_call_error(
+                            self.cfg.input.clone(),
+                            line!() as usize,
+                            expr,
+                            &callee,
+                            caller.as_deref(),
+                            self.full_path(),
+                        ));
+                    } else {
+                        self.errs.push(EffectError::has_effect(
+                            self.cfg.input.clone(),
+                            line!() as usize,
+                            expr,
+                            self.full_path(),
+                        ));
+                    }
                 }
                 call.args
                     .pos_args
                     .iter()
                     .for_each(|parg| self.check_expr(&parg.expr));
+                if let Some(var_args) = &call.args.var_args {
+                    self.check_expr(&var_args.expr);
+                }
                 call.args
                     .kw_args
                     .iter()
                     .for_each(|kwarg| self.check_expr(&kwarg.expr));
+                if let Some(kw_var) = &call.args.kw_var {
+                    self.check_expr(&kw_var.expr);
+                }
             }
             Expr::UnaryOp(unary) => {
                 self.check_expr(&unary.expr);
@@ -438,6 +520,9 @@ impl SideEffectChecker {
                     self.block_stack.push(Func);
                 }
                 self.check_params(&lambda.params);
+                if let Some(guard) = &lambda.guard {
+                    guard.iter().for_each(|chunk| self.check_expr(chunk));
+                }
                 lambda.body.iter().for_each(|chunk| self.check_expr(chunk));
                 self.path_stack.pop();
                 self.block_stack.pop();
@@ -463,6 +548,15 @@ impl SideEffectChecker {
         }
     }
 
+    fn check_comprehension_clauses(&mut self, generators: &[(Identifier, Expr)], guards: &[Expr]) {
+        for (_, iter) in generators.iter() {
+            self.check_expr(iter);
+        }
+        for guard in guards.iter() {
+            self.check_expr(guard);
+        }
+    }
+
     pub(crate) fn is_impure(expr: &Expr) -> bool {
         match expr {
             Expr::Call(call) => {
@@ -482,6 +576,11 @@ impl SideEffectChecker {
                         .kw_args
                         .iter()
                         .any(|kwarg| Self::is_impure(&kwarg.expr))
+                    || call
+                        .args
+                        .kw_var
+                        .iter()
+                        .any(|kw_var| Self::is_impure(&kw_var.expr))
             }
             Expr::BinOp(bin) => Self::is_impure(&bin.lhs) || Self::is_impure(&bin.rhs),
             Expr::UnaryOp(unary) => Self::is_impure(&unary.expr),
@@ -492,7 +591,10 @@ impl SideEffectChecker {
                     .iter()
                     .any(|elem| Self::is_impure(&elem.expr)),
                 Array::WithLength(arr) => Self::is_impure(&arr.elem) || Self::is_impure(&arr.len),
-                _ => todo!(),
+                Array::Comprehension(arr) => {
+                    Self::is_impure_comprehension(&arr.generators, &arr.guards)
+                        || Self::is_impure(&arr.elem)
+                }
             },
             Expr::Tuple(tup) => match tup {
                 Tuple::Normal(tup) => tup
@@ -508,13 +610,21 @@ impl SideEffectChecker {
                     .iter()
                     .any(|elem| Self::is_impure(&elem.expr)),
                 Set::WithLength(set) => Self::is_impure(&set.elem) || Self::is_impure(&set.len),
+                Set::Comprehension(set) => {
+                    Self::is_impure_comprehension(&set.generators, &set.guards)
+                        || Self::is_impure(&set.elem)
+                }
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dict) => dict
                     .kvs
                     .iter()
                     .any(|kv| Self::is_impure(&kv.key) || Self::is_impure(&kv.value)),
-                _ => todo!(),
+                Dict::Comprehension(dict) => {
+                    Self::is_impure_comprehension(&dict.generators, &dict.guards)
+                        || Self::is_impure(&dict.key)
+                        || Self::is_impure(&dict.value)
+                }
             },
             Expr::Lambda(lambda) => {
                 lambda.op.is_procedural() || lambda.body.iter().any(Self::is_impure)
@@ -532,6 +642,11 @@ impl SideEffectChecker {
         }
     }
 
+    fn is_impure_comprehension(generators: &[(Identifier, Expr)], guards: &[Expr]) -> bool {
+        generators.iter().any(|(_, iter)| Self::is_impure(iter))
+            || guards.iter().any(Self::is_impure)
+    }
+
     pub(crate) fn is_pure(expr: &Expr) -> bool {
         !Self::is_impure(expr)
     }
@@ -0,0 +1,384 @@
+//! Writes the intermediate representations of each stage (`--emit=ast,hir,typed-hir,bytecode,pyc,py,pyi`).
+use std::io::{stdout, Write};
+use std::path::{Path, PathBuf};
+
+use erg_common::config::{EmitKind, EmitTarget, ErgConfig};
+use erg_common::deterministic;
+use erg_common::dict::Dict;
+use erg_common::error::{ErrorDisplay, MultiErrorDisplay};
+use erg_common::traits::{ExitStatus, NoTypeDisplay, Runnable, Stream};
+
+use erg_parser::build_ast::ASTBuilder;
+
+use crate::artifact::{CompleteArtifact, ErrorArtifact};
+use crate::build_hir::HIRBuilder;
+use crate::codegen::{escape_ident, PyCodeGenerator};
+use crate::desugar_hir::HIRDesugarer;
+use crate::error::{CompileError, CompileErrors};
+use crate::hir::{Expr, HIR};
+use crate::link_hir::HIRLinker;
+use crate::module::SharedCompilerResource;
+use crate::optimize::HIROptimizer;
+use crate::transpile::ScriptGenerator;
+use crate::ty::typaram::TyParam;
+use crate::ty::{ParamTy, SubrType, Type};
+use crate::writer::ArtifactWriter;
+
+/// An emitted representation
+#[derive(Debug)]
+pub struct Emitted {
+    pub kind: EmitKind,
+    pub content: Vec<u8>,
+}
+
+impl Emitted {
+    fn text(kind: EmitKind, text: String) -> Self {
+        Self {
+            kind,
+            content: text.into_bytes(),
+        }
+    }
+}
+
+fn py_param(idx: usize, param: &ParamTy, default: bool) -> String {
+    let name = param
+        .name()
+        .map_or_else(|| format!("_{idx}"), |name| name.to_string());
+    if idx == 0 && name == "self" {
+        return name;
+    }
+    let default = if default { " = ..." } else { "" };
+    format!("{name}: {}{default}", py_type_hint(param.typ()))
+}
+
+fn py_params(subr: &SubrType) -> Vec<String> {
+    let mut params = subr
+        .non_default_params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| py_param(i, param, false))
+        .collect::<Vec<_>>();
+    if let Some(var_params) = subr.var_params.as_ref() {
+        params.push(format!("*{}", py_param(params.len(), var_params, false)));
+    }
+    for param in subr.default_params.iter() {
+        params.push(py_param(params.len(), param, true));
+    }
+    if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+        params.push(format!(
+            "**{}",
+            py_param(params.len(), kw_var_params, false)
+        ));
+    }
+    params
+}
+
+fn py_type_args(params: &[TyParam]) -> Vec<String> {
+    params
+        .iter()
+        .map(|tp| <&Type>::try_from(tp).map_or("Any".into(), py_type_hint))
+        .collect()
+}
+
+fn py_builtin_name(name: &str) -> Option<&'static str> {
+    match name.trim_end_matches('!') {
+        "Int" | "Nat" => Some("int"),
+        "Ratio" | "Float" => Some("float"),
+        "Complex" => Some("complex"),
+        "Bool" => Some("bool"),
+        "Str" => Some("str"),
+        "NoneType" => Some("None"),
+        "Obj" => Some("object"),
+        "Bytes" => Some("bytes"),
+        "Type" | "ClassType" | "TraitType" => Some("type"),
+        "Never" => Some("NoReturn"),
+        _ => None,
+    }
+}
+
+/// Converts an Erg type to a Python type hint (`Any` if there is no counterpart).
+pub fn py_type_hint(t: &Type) -> String {
+    match t {
+        Type::FreeVar(fv) if fv.is_linked() => py_type_hint(&fv.crack()),
+        Type::Refinement(refine) => py_type_hint(&refine.t),
+        Type::Ref(t) => py_type_hint(t),
+        Type::RefMut { before, .. } => py_type_hint(before),
+        Type::Quantified(t) => py_type_hint(t),
+        Type::Or(l, r) => format!("{} | {}", py_type_hint(l), py_type_hint(r)),
+        Type::Subr(subr) => {
+            let params = subr
+                .non_default_params
+                .iter()
+                .chain(subr.default_params.iter())
+                .map(|param| py_type_hint(param.typ()))
+                .collect::<Vec<_>>();
+            if subr.var_params.is_some() || subr.kw_var_params.is_some() {
+                format!("Callable[..., {}]", py_type_hint(&subr.return_t))
+            } else {
+                format!(
+                    "Callable[[{}], {}]",
+                    params.join(", "),
+                    py_type_hint(&subr.return_t)
+                )
+            }
+        }
+        Type::Callable { param_ts, return_t } => format!(
+            "Callable[[{}], {}]",
+            param_ts
+                .iter()
+                .map(py_type_hint)
+                .collect::<Vec<_>>()
+                .join(", "),
+            py_type_hint(return_t)
+        ),
+        Type::Poly { name, params } => match name.trim_end_matches('!') {
+            "Array" | "List" => format!(
+                "list[{}]",
+                py_type_args(&params[..1.min(params.len())]).join("")
+            ),
+            "Set" => format!(
+                "set[{}]",
+                py_type_args(&params[..1.min(params.len())]).join("")
+            ),
+            "Tuple" => match params.first().cloned().map(Vec::try_from) {
+                Some(Ok(elems)) if !elems.is_empty() => {
+                    format!("tuple[{}]", py_type_args(&elems).join(", "))
+                }
+                _ => "tuple[Any, ...]".into(),
+            },
+            "Dict" => match params.first().cloned().map(Dict::try_from) {
+                Some(Ok(dict)) if dict.len() == 1 => {
+                    let (k, v) = dict.into_iter().next().unwrap();
+                    let kv = py_type_args(&[k, v]);
+                    format!("dict[{}, {}]", kv[0], kv[1])
+                }
+                _ => "dict[Any, Any]".into(),
+            },
+            _ => "Any".into(),
+        },
+        Type::Mono(name) => py_builtin_name(name)
+            .map_or_else(|| t.local_name().to_string(), |name| name.to_string()),
+        other => py_builtin_name(&other.qual_name())
+            .unwrap_or("Any")
+            .to_string(),
+    }
+}
+
+fn py_def(name: &str, t: &Type, indent: &str) -> String {
+    let subr = match t {
+        Type::Subr(subr) => Some(subr),
+        Type::Quantified(t) => match t.as_ref() {
+            Type::Subr(subr) => Some(subr),
+            _ => None,
+        },
+        _ => None,
+    };
+    match subr {
+        Some(subr) => format!(
+            "{indent}def {name}({}) -> {}: ...\n",
+            py_params(subr).join(", "),
+            py_type_hint(&subr.return_t)
+        ),
+        None => format!("{indent}{name}: {}\n", py_type_hint(t)),
+    }
+}
+
+/// Generates a Python stub file (`.pyi`) of the public API (the variables and the classes declared with `.`).
+pub fn py_stub(hir: &HIR) -> String {
+    let mut stub = String::new();
+    for chunk in hir.module.iter() {
+        match chunk {
+            Expr::Def(def) if def.sig.vis().is_public() && !def.sig.ident().vi.t.is_module() => {
+                let ident = def.sig.ident();
+                stub += &py_def(&escape_ident(ident.clone()), &ident.vi.t, "");
+            }
+            Expr::ClassDef(class_def) if class_def.sig.vis().is_public() => {
+                stub += &format!("\nclass {}:\n", escape_ident(class_def.sig.ident().clone()));
+                let mut members = String::new();
+                for method in class_def.methods.iter() {
+                    if let Expr::Def(def) = method {
+                        if def.sig.vis().is_public() {
+                            let ident = def.sig.ident();
+                            members += &py_def(&escape_ident(ident.clone()), &ident.vi.t, "    ");
+                        }
+                    }
+                }
+                if members.is_empty() {
+                    members = "    ...\n".into();
+                }
+                stub += &members;
+                stub.push('\n');
+            }
+            _ => {}
+        }
+    }
+    let imports = ["Any", "Callable", "NoReturn"]
+        .into_iter()
+        .filter(|name| {
+            stub.split(|c: char| !c.is_alphanumeric() && c != '_')
+                .any(|word| word == *name)
+        })
+        .collect::<Vec<_>>();
+    if imports.is_empty() {
+        stub
+    } else {
+        format!("from typing import {}\n\n{stub}", imports.join(", "))
+    }
+}
+
+/// Builds the source code and returns the requested representations (`cfg.emit`).
+/// The later stages are not performed if they are not needed
+/// (e.g. `--emit=ast` does not type-check the code).
+#[derive(Debug)]
+pub struct Emitter {
+    cfg: ErgConfig,
+    shared: SharedCompilerResource,
+    builder: HIRBuilder,
+    code_generator: PyCodeGenerator,
+}
+
+impl Emitter {
+    pub fn new(cfg: ErgConfig) -> Self {
+        let shared = SharedCompilerResource::new(cfg.copy());
+        Self {
+            builder: HIRBuilder::new_with_cache(cfg.copy(), "<module>", shared.clone()),
+            code_generator: PyCodeGenerator::new(cfg.copy()),
+            shared,
+            cfg,
+        }
+    }
+
+    pub fn cfg(&self) -> &ErgConfig {
+        &self.cfg
+    }
+
+    fn requires(&self, kinds: &[EmitKind]) -> bool {
+        self.cfg
+            .emit
+            .iter()
+            .any(|target| kinds.contains(&target.kind))
+    }
+
+    pub fn emit(&mut self, src: String) -> Result<CompleteArtifact<Vec<Emitted>>, ErrorArtifact> {
+        let mut emitted = vec![];
+        let artifact = ASTBuilder::new(self.cfg.copy())
+            .build(src)
+            .map_err(|iart| ErrorArtifact::new(iart.errors.into(), iart.warns.into()))?;
+        if self.requires(&[EmitKind::Ast]) {
+            emitted.push(Emitted::text(EmitKind::Ast, artifact.ast.to_string()));
+        }
+        let mut warns = CompileErrors::from(artifact.warns);
+        use EmitKind::*;
+        if !self.requires(&[Hir, TypedHir, Bytecode, Pyc, Py, Pyi]) {
+            return Ok(CompleteArtifact::new(emitted, warns));
+        }
+        let hir = match self.builder.check(artifact.ast, "exec") {
+            Ok(artifact) => {
+                warns.extend(artifact.warns);
+                artifact.object
+            }
+            Err(artifact) => {
+                warns.extend(artifact.warns);
+                return Err(ErrorArtifact::new(artifact.errors, warns));
+            }
+        };
+        if self.requires(&[Hir]) {
+            emitted.push(Emitted::text(Hir, hir.to_string_notype()));
+        }
+        if self.requires(&[TypedHir]) {
+            emitted.push(Emitted::text(TypedHir, hir.to_string()));
+        }
+        if self.requires(&[Pyi]) {
+            emitted.push(Emitted::text(Pyi, py_stub(&hir)));
+        }
+        if self.requires(&[Bytecode, Pyc, Py]) {
+            let linker = HIRLinker::new(&self.cfg, &self.shared.mod_cache);
+            let mut hir = HIRDesugarer::desugar(linker.link(hir));
+            // `py` is transpiled from the HIR that `pyc` is generated from, so that they behave the same
+            if self.requires(&[Bytecode, Pyc]) {
+                hir = HIROptimizer::optimize(self.cfg.clone(), self.shared.clone(), hir);
+            }
+            if self.requires(&[Py]) {
+                let script = ScriptGenerator::new().transpile(hir.clone());
+                emitted.push(Emitted::text(Py, script.code));
+            }
+            if self.requires(&[Bytecode, Pyc]) {
+                let codeobj = self.code_generator.emit(hir);
+                let py_ver = self.code_generator.py_version;
+                if self.requires(&[Bytecode]) {
+                    emitted.push(Emitted::text(Bytecode, codeobj.code_info(Some(py_ver))));
+                }
+                if self.requires(&[Pyc]) {
+                    emitted.push(Emitted {
+                        kind: Pyc,
+                        content: codeobj.into_pyc_bytes(self.cfg.py_magic_num),
+                    });
+                }
+            }
+        }
+        Ok(CompleteArtifact::new(emitted, warns))
+    }
+
+    /// `None` means stdout.
+    /// If the path is not specified, the file next to the input (or `--output-dir`) is used,
+    /// but the text representations of a non-file input are printed to stdout.
+    fn dest(&self, target: &EmitTarget) -> Option<PathBuf> {
+        match &target.path {
+            _ if target.is_stdout() => None,
+            Some(path) => Some(path.clone()),
+            None if self.cfg.dumps_to_stdout() => None,
+            None if target.kind == EmitKind::Pyc => Some(self.cfg.dump_pyc_path()),
+            None if self.cfg.input.is_file() => {
+                Some(self.cfg.dump_path().with_extension(target.kind.extension()))
+            }
+            None => None,
+        }
+    }
+
+    fn write(&self, emitted: &[Emitted]) -> Result<(), CompileError> {
+        let writer = ArtifactWriter::new(&self.cfg);
+        for target in self.cfg.emit.iter() {
+            let Some(emitted) = emitted.iter().find(|emitted| emitted.kind == target.kind) else {
+                continue;
+            };
+            let content = if self.cfg.deterministic && !target.kind.is_binary() {
+                let text = String::from_utf8_lossy(&emitted.content);
+                deterministic::normalize(&text).into_bytes()
+            } else {
+                emitted.content.clone()
+            };
+            match self.dest(target) {
+                Some(path) => writer.write(&path, &content)?,
+                None => {
+                    let mut out = stdout().lock();
+                    let mut written = out.write_all(&content);
+                    if written.is_ok() && !target.kind.is_binary() && !content.ends_with(b"\n") {
+                        written = out.write_all(b"\n");
+                    }
+                    written.map_err(|err| writer.error(Path::new("<stdout>"), err))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let mut emitter = Self::new(cfg);
+        let src = emitter.cfg.input.read();
+        match emitter.emit(src) {
+            Ok(artifact) => {
+                artifact.warns.write_all_stderr();
+                if let Err(err) = emitter.write(&artifact.object) {
+                    err.write_to_stderr();
+                    return ExitStatus::new(1, artifact.warns.len(), 1);
+                }
+                ExitStatus::compile_passed(artifact.warns.len())
+            }
+            Err(artifact) => {
+                artifact.warns.write_all_stderr();
+                artifact.errors.write_all_stderr();
+                ExitStatus::new(1, artifact.warns.len(), artifact.errors.len())
+            }
+        }
+    }
+}
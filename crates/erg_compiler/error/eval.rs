@@ -1,5 +1,6 @@
 use erg_common::error::{ErrorCore, ErrorKind::*, Location, SubMessage};
 use erg_common::io::Input;
+use erg_common::style::StyledString;
 use erg_common::switch_lang;
 
 use crate::error::*;
@@ -47,4 +48,115 @@ impl EvalError {
             caused_by,
         )
     }
+
+    pub fn comptime_value_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        value: &str,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("コンパイル時の値{value}はコードに埋め込めません(リテラル, 配列, タプル, セット, 辞書のみ埋め込めます)"),
+                    "simplified_chinese" => format!("编译时的值{value}不能嵌入代码(只能嵌入字面量、数组、元组、集合和字典)"),
+                    "traditional_chinese" => format!("編譯時的值{value}不能嵌入程式碼(只能嵌入字面量、陣列、元組、集合和字典)"),
+                    "english" => format!("the compile-time value {value} cannot be embedded in the code (only literals, arrays, tuples, sets and dicts can be embedded)"),
+                ),
+                errno,
+                NotConstExpr,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn zero_division_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => "コンパイル時に0で除算しました",
+                    "simplified_chinese" => "在编译时除以0",
+                    "traditional_chinese" => "在編譯時除以0",
+                    "english" => "division by zero at compile time",
+                ),
+                errno,
+                ZeroDivisionError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn comptime_eval_limit_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        subr_name: &str,
+        fuel: usize,
+        max_depth: usize,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => format!("評価が制限(呼び出し{fuel}回、ネスト{max_depth}段)を超えました。実行時に計算してください"),
+            "simplified_chinese" => format!("求值超出了限制(调用{fuel}次、嵌套{max_depth}层)，请在运行时计算"),
+            "traditional_chinese" => format!("求值超出了限制(呼叫{fuel}次、巢狀{max_depth}層)，請在執行時計算"),
+            "english" => format!("the evaluation exceeded the limits ({fuel} calls, {max_depth} nested calls), compute it at runtime instead"),
+        );
+        Self::non_termination_error(input, errno, loc, caused_by, subr_name, hint)
+    }
+
+    pub fn unguarded_recursion_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        subr_name: &str,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => "この再帰呼び出しは`if`の分岐の中にないため、常に評価されます",
+            "simplified_chinese" => "此递归调用不在`if`的分支中，因此总是会被求值",
+            "traditional_chinese" => "此遞迴呼叫不在`if`的分支中，因此總是會被求值",
+            "english" => "this recursive call is always evaluated because it is not in a branch of `if`",
+        );
+        Self::non_termination_error(input, errno, loc, caused_by, subr_name, hint.into())
+    }
+
+    fn non_termination_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        subr_name: &str,
+        hint: String,
+    ) -> Self {
+        let subr_name = StyledString::new(subr_name, Some(ERR), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("{subr_name}はコンパイル時に停止しない可能性があります"),
+                    "simplified_chinese" => format!("{subr_name}在编译时可能不会终止"),
+                    "traditional_chinese" => format!("{subr_name}在編譯時可能不會終止"),
+                    "english" => format!("{subr_name} may not terminate at compile time"),
+                ),
+                errno,
+                NotConstExpr,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
 }
@@ -1,5 +1,6 @@
 use erg_common::error::{ErrorCore, ErrorKind::*, Location, SubMessage};
 use erg_common::io::Input;
+use erg_common::python_util::PythonVersion;
 use erg_common::style::{StyledStr, StyledString, StyledStrings, Stylize};
 use erg_common::traits::Locational;
 use erg_common::{switch_lang, Str};
@@ -577,6 +578,32 @@ impl LowerError {
         )
     }
 
+    pub fn reserved_name_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = StyledStr::new(readable_name(name), Some(WARN), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("{name}は予約された名前なので定義できません"),
+                    "simplified_chinese" => format!("{name}是保留名称，不能定义"),
+                    "traditional_chinese" => format!("{name}是保留名稱，不能定義"),
+                    "english" => format!("{name} is a reserved name and cannot be defined"),
+                ),
+                errno,
+                AssignError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn reassign_error(
         input: Input,
         errno: usize,
@@ -603,6 +630,148 @@ impl LowerError {
         )
     }
 
+    pub fn unassigned_path_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        missing_else: bool,
+    ) -> Self {
+        let name = StyledStr::new(readable_name(name), Some(WARN), Some(ATTR));
+        let hint = if missing_else {
+            switch_lang!(
+                "japanese" => format!("{name}に代入するelse節を追加してください"),
+                "simplified_chinese" => format!("请添加为{name}赋值的else分支"),
+                "traditional_chinese" => format!("請添加為{name}賦值的else分支"),
+                "english" => format!("add an else branch that assigns {name}"),
+            )
+        } else {
+            switch_lang!(
+                "japanese" => format!("全ての分岐で{name}にちょうど一度代入してください"),
+                "simplified_chinese" => format!("每个分支都必须为{name}恰好赋值一次"),
+                "traditional_chinese" => format!("每個分支都必須為{name}恰好賦值一次"),
+                "english" => format!("every branch must assign {name} exactly once"),
+            )
+        };
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("この経路では変数{name}に代入されていません"),
+                    "simplified_chinese" => format!("变量{name}在此路径上未被赋值"),
+                    "traditional_chinese" => format!("變量{name}在此路徑上未被賦值"),
+                    "english" => format!("variable {name} is not assigned on this path"),
+                ),
+                errno,
+                AssignError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn multiple_deferred_init_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        names: &[Str],
+    ) -> Self {
+        let names = names
+            .iter()
+            .map(|name| {
+                readable_name(name)
+                    .with_color_and_attr(WARN, ATTR)
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("条件文で初期化できる変数は1つだけですが、{names}に代入されています"),
+                    "simplified_chinese" => format!("条件语句只能初始化一个变量，但为{names}赋了值"),
+                    "traditional_chinese" => format!("條件語句只能初始化一個變量，但為{names}賦了值"),
+                    "english" => format!("a conditional statement can initialize only one variable, but {names} are assigned"),
+                ),
+                errno,
+                FeatureError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn last_definition_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => "分岐の中で初期化する場合は、条件文の前で変数を宣言してください",
+            "simplified_chinese" => "如果要在分支中初始化变量，请在条件语句之前声明它",
+            "traditional_chinese" => "如果要在分支中初始化變量，請在條件語句之前宣告它",
+            "english" => "to initialize a variable in the branches, declare it before the conditional statement",
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => "ブロックの終端で変数を定義することは出来ません",
+                    "simplified_chinese" => "无法在块的末尾定义变量",
+                    "traditional_chinese" => "無法在塊的末尾定義變量",
+                    "english" => "cannot define a variable at the end of a block",
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn unavailable_api_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        required: &PythonVersion,
+        target: &PythonVersion,
+    ) -> Self {
+        let name = StyledString::new(readable_name(name), Some(WARN), Some(ATTR));
+        let required = StyledString::new(format!("{required}"), Some(HINT), Some(ATTR));
+        let target = StyledString::new(format!("{target}"), Some(ERR), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => format!("{required}以降を対象にしてください"),
+            "simplified_chinese" => format!("请以{required}或更高版本为目标"),
+            "traditional_chinese" => format!("請以{required}或更高版本為目標"),
+            "english" => format!("target Python {required} or later"),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("{name}はPython {required}で追加されたため、Python {target}では使用できません"),
+                    "simplified_chinese" => format!("{name}是在Python {required}中添加的，无法在Python {target}中使用"),
+                    "traditional_chinese" => format!("{name}是在Python {required}中添加的，無法在Python {target}中使用"),
+                    "english" => format!("{name} is not available in Python {target} (it requires Python {required} or later)"),
+                ),
+                errno,
+                EnvironmentError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn del_error(
         input: Input,
         errno: usize,
@@ -1002,6 +1171,132 @@ impl LowerError {
         )
     }
 
+    pub fn inner_macro_def_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("マクロはトップレベルで定義されなければなりません"),
+                    "simplified_chinese" => format!("宏必须在顶层定义"),
+                    "traditional_chinese" => format!("宏必須在頂層定義"),
+                    "english" => format!("macros must be defined at the top level"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn invalid_macro_def_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("マクロの引数は名前のみ指定できます(型指定, デフォルト引数, 可変長引数は使用できません)"),
+                    "simplified_chinese" => format!("宏的参数只能是名称(不能使用类型指定、默认参数和可变参数)"),
+                    "traditional_chinese" => format!("宏的參數只能是名稱(不能使用類型指定、預設參數和可變參數)"),
+                    "english" => format!("the parameters of a macro must be plain names (type specifications, default parameters and variable-length parameters are not allowed)"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn invalid_macro_arg_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = readable_name(name).with_color_and_attr(WARN, ATTR);
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("{name}はこの引数を定義の名前として使用するので、識別子を渡す必要があります"),
+                    "simplified_chinese" => format!("{name}将此参数用作定义的名称，因此必须传递标识符"),
+                    "traditional_chinese" => format!("{name}將此參數用作定義的名稱，因此必須傳遞識別符"),
+                    "english" => format!("{name} uses this argument as the name of a definition, so it must be an identifier"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn macro_expr_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = readable_name(name).with_color_and_attr(WARN, ATTR);
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("{name}は複数の文に展開されるので、式として使用できません"),
+                    "simplified_chinese" => format!("{name}展开为多个语句，因此不能用作表达式"),
+                    "traditional_chinese" => format!("{name}展開為多個語句，因此不能用作表達式"),
+                    "english" => format!("{name} expands to multiple statements, so it cannot be used as an expression"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn macro_recursion_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = readable_name(name).with_color_and_attr(WARN, ATTR);
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("{name}の展開が深すぎます(マクロは再帰的に展開できません)"),
+                    "simplified_chinese" => format!("{name}的展开太深(宏不能递归展开)"),
+                    "traditional_chinese" => format!("{name}的展開太深(宏不能遞迴展開)"),
+                    "english" => format!("the expansion of {name} is too deep (macros cannot be expanded recursively)"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn declare_error(input: Input, errno: usize, loc: Location, caused_by: String) -> Self {
         Self::new(
             ErrorCore::new(
@@ -1021,6 +1316,96 @@ impl LowerError {
         )
     }
 
+    pub fn propagation_outside_subr_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+    ) -> Self {
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("`?`はサブルーチンの中でのみ使用できます"),
+                    "simplified_chinese" => format!("`?`只能在子程序中使用"),
+                    "traditional_chinese" => format!("`?`只能在子程序中使用"),
+                    "english" => format!("`?` can only be used inside a subroutine"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn invalid_propagation_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        found: &Type,
+    ) -> Self {
+        let found = StyledString::new(format!("{found}"), Some(ERR), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => "`?`を適用できるのは`T or NoneType`型か`T or Error`型の値です",
+            "simplified_chinese" => "`?`只能应用于`T or NoneType`或`T or Error`类型的值",
+            "traditional_chinese" => "`?`只能應用於`T or NoneType`或`T or Error`類型的值",
+            "english" => "`?` can only be applied to values of type `T or NoneType` or `T or Error`",
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("{found}型の値には`?`を適用できません"),
+                    "simplified_chinese" => format!("不能对{found}类型的值使用`?`"),
+                    "traditional_chinese" => format!("不能對{found}類型的值使用`?`"),
+                    "english" => format!("`?` cannot be applied to a value of type {found}"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn propagation_return_type_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        return_t: &Type,
+        failure_t: &Type,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => format!("戻り値の型を`{return_t} or {failure_t}`にしてください"),
+            "simplified_chinese" => format!("请将返回类型改为`{return_t} or {failure_t}`"),
+            "traditional_chinese" => format!("請將返回類型改為`{return_t} or {failure_t}`"),
+            "english" => format!("change the return type to `{return_t} or {failure_t}`"),
+        );
+        let return_t = StyledString::new(format!("{return_t}"), Some(ERR), Some(ATTR));
+        let failure_t = StyledString::new(format!("{failure_t}"), Some(ERR), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("`?`は{failure_t}型の値を返す可能性がありますが、戻り値の型{return_t}はそれを含みません"),
+                    "simplified_chinese" => format!("`?`可能返回{failure_t}类型的值，但返回类型{return_t}不包含它"),
+                    "traditional_chinese" => format!("`?`可能返回{failure_t}類型的值，但返回類型{return_t}不包含它"),
+                    "english" => format!("`?` may return a value of type {failure_t}, but the return type {return_t} does not include it"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn invalid_type_cast_error(
         input: Input,
@@ -1081,6 +1466,106 @@ impl LowerWarning {
         )
     }
 
+    pub fn unused_param_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        name: &str,
+        caused_by: String,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => format!("意図的に使用しない場合は`_{name}`に改名してください"),
+            "simplified_chinese" => format!("如果是有意不使用的，请重命名为`_{name}`"),
+            "traditional_chinese" => format!("如果是有意不使用的，請重新命名為`_{name}`"),
+            "english" => format!("if this is intentional, rename it to `_{name}`"),
+        );
+        let name = StyledString::new(readable_name(name), Some(WARN), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("引数{name}は使用されていません"),
+                    "simplified_chinese" => format!("参数{name}未使用"),
+                    "traditional_chinese" => format!("參數{name}未使用"),
+                    "english" => format!("the parameter {name} is not used"),
+                ),
+                errno,
+                UnusedWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn unused_field_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        name: &str,
+        caused_by: String,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => "このプライベートなフィールドはどこからも読まれていません",
+            "simplified_chinese" => "此私有字段从未被读取",
+            "traditional_chinese" => "此私有欄位從未被讀取",
+            "english" => "this private field is never read",
+        );
+        let name = StyledString::new(readable_name(name), Some(WARN), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("フィールド{name}は使用されていません"),
+                    "simplified_chinese" => format!("字段{name}未使用"),
+                    "traditional_chinese" => format!("欄位{name}未使用"),
+                    "english" => format!("the field {name} is not used"),
+                ),
+                errno,
+                UnusedWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn dead_def_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        name: &str,
+        lines: u32,
+        caused_by: String,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => format!("この定義({lines}行)は安全に削除できます"),
+            "simplified_chinese" => format!("可以安全地删除此定义({lines}行)"),
+            "traditional_chinese" => format!("可以安全地刪除此定義({lines}行)"),
+            "english" => {
+                let s = if lines == 1 { "" } else { "s" };
+                format!("this definition ({lines} line{s}) can be safely removed")
+            },
+        );
+        let name = StyledString::new(readable_name(name), Some(WARN), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("{name}は公開された定義やエントリーポイントから到達できません"),
+                    "simplified_chinese" => format!("{name}无法从任何公开定义或入口点到达"),
+                    "traditional_chinese" => format!("{name}無法從任何公開定義或入口點到達"),
+                    "english" => format!("{name} is not reachable from any public definition or entry point"),
+                ),
+                errno,
+                UnusedWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn union_return_type_warning(
         input: Input,
         errno: usize,
@@ -1114,6 +1599,137 @@ impl LowerWarning {
         )
     }
 
+    pub fn cache_suggestion_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        fn_name: &str,
+    ) -> Self {
+        let fn_name = fn_name.with_color(Color::Yellow);
+        let hint = switch_lang!(
+            "japanese" => "`@Cache`をつけると結果がメモ化されます",
+            "simplified_chinese" => "添加`@Cache`以记忆化其结果",
+            "traditional_chinese" => "添加`@Cache`以記憶化其結果",
+            "english" => "add `@Cache` to memoize its results",
+        );
+        LowerError::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("純粋な関数{fn_name}は自身を複数回再帰呼び出ししています"),
+                    "simplified_chinese" => format!("纯函数{fn_name}多次递归调用自身"),
+                    "traditional_chinese" => format!("純函數{fn_name}多次遞迴呼叫自身"),
+                    "english" => format!("the pure function {fn_name} calls itself recursively more than once"),
+                ),
+                errno,
+                Warning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn non_exhaustive_if_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        var_name: &str,
+        remaining: &Type,
+    ) -> Self {
+        let var_name = var_name.with_color(Color::Yellow);
+        let remaining = remaining.to_string().with_color(Color::Yellow);
+        let hint = switch_lang!(
+            "japanese" => "else節を追加するか、残りのケースを処理してください",
+            "simplified_chinese" => "请添加else分支或处理剩余的情况",
+            "traditional_chinese" => "請添加else分支或處理剩餘的情況",
+            "english" => "add an else branch or handle the remaining cases",
+        );
+        LowerError::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("このifチェーンは{var_name}の全てのケースを網羅していません({remaining}が未処理です)"),
+                    "simplified_chinese" => format!("此if链未覆盖{var_name}的所有情况(未处理: {remaining})"),
+                    "traditional_chinese" => format!("此if鏈未覆蓋{var_name}的所有情況(未處理: {remaining})"),
+                    "english" => format!("this if chain does not cover all cases of {var_name} ({remaining} is not handled)"),
+                ),
+                errno,
+                Warning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn zero_division_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        divisor_t: &Type,
+    ) -> Self {
+        let divisor_t = StyledString::new(format!("{divisor_t}"), Some(WARN), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => "除数を0以外の値に絞り込む(例: `if y != 0:`)か、篩型で0を除外してください",
+            "simplified_chinese" => "请将除数限定为非零值(例如`if y != 0:`)或使用筛型排除0",
+            "traditional_chinese" => "請將除數限定為非零值(例如`if y != 0:`)或使用篩型排除0",
+            "english" => "narrow the divisor (e.g. `if y != 0:`) or exclude 0 with a refinement type",
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("除数({divisor_t}型)が0でないことを証明できません、実行時に失敗する可能性があります"),
+                    "simplified_chinese" => format!("无法证明除数(类型为{divisor_t})不为0,可能在运行时失败"),
+                    "traditional_chinese" => format!("無法證明除數(類型為{divisor_t})不為0,可能在執行時失敗"),
+                    "english" => format!("the divisor (of type {divisor_t}) cannot be proven to be non-zero, the division may fail at runtime"),
+                ),
+                errno,
+                TypeWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn unchecked_index_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        index_t: &Type,
+        len: u64,
+    ) -> Self {
+        let index_t = StyledString::new(format!("{index_t}"), Some(WARN), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => format!("インデックスを`0..<{len}`に絞り込むと、範囲が静的に検査されます"),
+            "simplified_chinese" => format!("将索引限定为`0..<{len}`即可静态检查范围"),
+            "traditional_chinese" => format!("將索引限定為`0..<{len}`即可靜態檢查範圍"),
+            "english" => format!("narrow the index to `0..<{len}` to check the bound statically"),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("インデックス({index_t}型)が配列(長さ{len})の範囲内であることを証明できません、実行時に検査されます"),
+                    "simplified_chinese" => format!("无法证明索引(类型为{index_t})在数组(长度为{len})的范围内,将在运行时检查"),
+                    "traditional_chinese" => format!("無法證明索引(類型為{index_t})在陣列(長度為{len})的範圍內,將在執行時檢查"),
+                    "english" => format!("the index (of type {index_t}) cannot be proven to be in the range of the array (of length {len}), it is checked at runtime"),
+                ),
+                errno,
+                TypeWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn builtin_exists_warning(
         input: Input,
         errno: usize,
@@ -1159,6 +1775,32 @@ impl LowerWarning {
         )
     }
 
+    pub fn unreachable_arm_warning(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        covered: &Type,
+    ) -> Self {
+        let covered = StyledString::new(format!("{covered}"), Some(WARN), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("このアームには到達しません(前のアームが{covered}型を網羅しています)"),
+                    "simplified_chinese" => format!("此分支不可达(之前的分支已覆盖{covered}类型)"),
+                    "traditional_chinese" => format!("此分支不可達(之前的分支已覆蓋{covered}類型)"),
+                    "english" => format!("this arm is unreachable (the previous arms cover {covered})"),
+                ),
+                errno,
+                UnusedWarning,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn same_name_instance_attr_warning(
         input: Input,
         errno: usize,
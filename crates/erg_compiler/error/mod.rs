@@ -336,6 +336,10 @@ caused from: {fn_name}"),
             "".to_owned(),
         )
     }
+
+    pub fn cancelled(input: Input) -> Self {
+        Self::new(ErrorCore::cancelled(line!() as usize), input, "".to_owned())
+    }
 }
 
 pub type EffectError = TyCheckError;
@@ -361,6 +365,84 @@ impl EffectError {
         )
     }
 
+    /// `callee` is the procedure being called, `caller` is the enclosing function (`None` for lambdas).
+    pub fn proc_call_error(
+        input: Input,
+        errno: usize,
+        expr: &Expr,
+        callee: &str,
+        caller: Option<&str>,
+        caused_by: String,
+    ) -> Self {
+        let callee = StyledString::new(callee, Some(ERR), Some(ATTR));
+        let msg = switch_lang!(
+            "japanese" => format!("{callee}はプロシージャです"),
+            "simplified_chinese" => format!("{callee}是一个过程"),
+            "traditional_chinese" => format!("{callee}是一個程序"),
+            "english" => format!("{callee} is a procedure"),
+        );
+        let hint = if let Some(caller) = caller {
+            let proc_name = StyledString::new(format!("{caller}!"), Some(HINT), Some(ATTR));
+            switch_lang!(
+                "japanese" => format!("関数からプロシージャを呼び出すことはできません。{proc_name}と名付けてプロシージャにしてください"),
+                "simplified_chinese" => format!("函数不能调用过程，请将其命名为{proc_name}使其成为过程"),
+                "traditional_chinese" => format!("函數不能呼叫程序，請將其命名為{proc_name}使其成為程序"),
+                "english" => format!("functions cannot call procedures, rename it to {proc_name} to make it a procedure"),
+            )
+        } else {
+            let arrow = StyledString::new("=>", Some(HINT), Some(ATTR));
+            switch_lang!(
+                "japanese" => format!("関数からプロシージャを呼び出すことはできません。{arrow}を使ってプロシージャにしてください"),
+                "simplified_chinese" => format!("函数不能调用过程，请使用{arrow}使其成为过程"),
+                "traditional_chinese" => format!("函數不能呼叫程序，請使用{arrow}使其成為程序"),
+                "english" => format!("functions cannot call procedures, use {arrow} to make it a procedure"),
+            )
+        };
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(expr.loc(), vec![msg], Some(hint))],
+                switch_lang!(
+                    "japanese" => "この式には副作用があります",
+                    "simplified_chinese" => "此表达式会产生副作用",
+                    "traditional_chinese" => "此表達式會產生副作用",
+                    "english" => "this expression causes a side-effect",
+                ),
+                errno,
+                HasEffect,
+                expr.loc(),
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn cache_proc_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        name: &str,
+        caused_by: String,
+    ) -> Self {
+        let name = StyledString::new(name, Some(ERR), Some(ATTR));
+        let cache = StyledString::new("@Cache", Some(HINT), Some(ATTR));
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::only_loc(loc)],
+                switch_lang!(
+                    "japanese" => format!("プロシージャ{name}に{cache}は使えません。副作用のない関数の結果のみキャッシュできます"),
+                    "simplified_chinese" => format!("不能对过程{name}使用{cache}，只能缓存没有副作用的函数的结果"),
+                    "traditional_chinese" => format!("不能對程序{name}使用{cache}，只能快取沒有副作用的函數的結果"),
+                    "english" => format!("{cache} cannot be applied to the procedure {name}, only the results of functions without side-effects can be cached"),
+                ),
+                errno,
+                HasEffect,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn proc_assign_error(input: Input, errno: usize, loc: Location, caused_by: String) -> Self {
         let hint = Some(
             switch_lang!(
@@ -445,9 +527,30 @@ impl OwnershipError {
         caused_by: String,
     ) -> Self {
         let found = StyledString::new(name, Some(ERR), Some(ATTR));
+        let used = switch_lang!(
+            "japanese" => "移動後にここで使用されています",
+            "simplified_chinese" => "移动后在此处使用",
+            "traditional_chinese" => "移動後在此處使用",
+            "english" => "used here after the move",
+        );
+        let moved = switch_lang!(
+            "japanese" => format!("{found}はここで移動されています"),
+            "simplified_chinese" => format!("{found}在此处被移动"),
+            "traditional_chinese" => format!("{found}在此處被移動"),
+            "english" => format!("{found} was moved here"),
+        );
+        let hint = switch_lang!(
+            "japanese" => format!("移動する代わりに`{name}.clone()`を渡すか、引数の型を`Ref(T)`/`RefMut(T)`にして借用するか、最後の使用の後で移動するようにしてください"),
+            "simplified_chinese" => format!("请传递`{name}.clone()`而不是移动它，或将参数类型设为`Ref(T)`/`RefMut(T)`以借用它，或在最后一次使用之后再移动它"),
+            "traditional_chinese" => format!("請傳遞`{name}.clone()`而不是移動它，或將參數類型設為`Ref(T)`/`RefMut(T)`以借用它，或在最後一次使用之後再移動它"),
+            "english" => format!("pass `{name}.clone()` instead of moving it, borrow it by declaring the parameter as `Ref(T)`/`RefMut(T)`, or move it after its last use"),
+        );
         Self::new(
             ErrorCore::new(
-                vec![SubMessage::only_loc(name_loc)],
+                vec![
+                    SubMessage::ambiguous_new(name_loc, vec![used.to_string()], None),
+                    SubMessage::ambiguous_new(moved_loc, vec![moved], Some(hint)),
+                ],
                 switch_lang!(
                     "japanese" => format!(
                         "{found}は{}行目ですでに移動されています",
@@ -474,6 +577,101 @@ impl OwnershipError {
             caused_by,
         )
     }
+
+    /// `first_loc` is the earlier borrow, `second_loc` the conflicting one.
+    /// At least one of them must be a mutable borrow.
+    #[allow(clippy::too_many_arguments)]
+    pub fn borrow_error(
+        input: Input,
+        errno: usize,
+        name: &str,
+        first_loc: Location,
+        first_is_mut: bool,
+        second_loc: Location,
+        second_is_mut: bool,
+        caused_by: String,
+    ) -> Self {
+        let found = StyledString::new(name, Some(ERR), Some(ATTR));
+        let first = if first_is_mut && second_is_mut {
+            switch_lang!(
+                "japanese" => "1つ目の可変借用はここです",
+                "simplified_chinese" => "第一次可变借用在此处",
+                "traditional_chinese" => "第一次可變借用在此處",
+                "english" => "first mutable borrow occurs here",
+            )
+        } else if first_is_mut {
+            switch_lang!(
+                "japanese" => "可変借用はここです",
+                "simplified_chinese" => "可变借用在此处",
+                "traditional_chinese" => "可變借用在此處",
+                "english" => "mutable borrow occurs here",
+            )
+        } else {
+            switch_lang!(
+                "japanese" => "不変借用はここです",
+                "simplified_chinese" => "不可变借用在此处",
+                "traditional_chinese" => "不可變借用在此處",
+                "english" => "immutable borrow occurs here",
+            )
+        };
+        let second = if first_is_mut && second_is_mut {
+            switch_lang!(
+                "japanese" => "2つ目の可変借用はここです",
+                "simplified_chinese" => "第二次可变借用在此处",
+                "traditional_chinese" => "第二次可變借用在此處",
+                "english" => "second mutable borrow occurs here",
+            )
+        } else if second_is_mut {
+            switch_lang!(
+                "japanese" => "可変借用はここです",
+                "simplified_chinese" => "可变借用在此处",
+                "traditional_chinese" => "可變借用在此處",
+                "english" => "mutable borrow occurs here",
+            )
+        } else {
+            switch_lang!(
+                "japanese" => "不変借用はここです",
+                "simplified_chinese" => "不可变借用在此处",
+                "traditional_chinese" => "不可變借用在此處",
+                "english" => "immutable borrow occurs here",
+            )
+        };
+        let hint = switch_lang!(
+            "japanese" => format!("片方に`{name}.clone()`を渡すか、呼び出しを分割してください"),
+            "simplified_chinese" => format!("请为其中一个参数传递`{name}.clone()`，或拆分此调用"),
+            "traditional_chinese" => format!("請為其中一個參數傳遞`{name}.clone()`，或拆分此呼叫"),
+            "english" => format!("pass `{name}.clone()` to one of the parameters, or split the call"),
+        );
+        let main_msg = if first_is_mut && second_is_mut {
+            switch_lang!(
+                "japanese" => format!("{found}を同時に2回以上可変借用することはできません"),
+                "simplified_chinese" => format!("不能同时多次可变借用{found}"),
+                "traditional_chinese" => format!("不能同時多次可變借用{found}"),
+                "english" => format!("cannot borrow {found} as mutable more than once at a time"),
+            )
+        } else {
+            switch_lang!(
+                "japanese" => format!("{found}は不変借用されているため、可変借用することはできません"),
+                "simplified_chinese" => format!("{found}已被不可变借用，因此不能再可变借用"),
+                "traditional_chinese" => format!("{found}已被不可變借用，因此不能再可變借用"),
+                "english" => format!("cannot borrow {found} as mutable because it is also borrowed as immutable"),
+            )
+        };
+        Self::new(
+            ErrorCore::new(
+                vec![
+                    SubMessage::ambiguous_new(first_loc, vec![first.to_string()], None),
+                    SubMessage::ambiguous_new(second_loc, vec![second.to_string()], Some(hint)),
+                ],
+                main_msg,
+                errno,
+                MoveError,
+                second_loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -537,6 +735,7 @@ mod test {
     use crate::{
         error::*,
         hir::Identifier,
+        test_support::assert_rendered,
         ty::{Predicate, Type},
         varinfo::{AbsLocation, VarInfo},
     };
@@ -547,17 +746,44 @@ mod test {
     // This test make sure sub_msg and hint are displayed correctly.
     #[test]
     fn default_error_format_confirmation() {
-        let mut errors = Vec::new();
-
+        // the snapshots assume the default features
+        if cfg!(any(
+            feature = "japanese",
+            feature = "simplified_chinese",
+            feature = "traditional_chinese",
+            feature = "unicode",
+            feature = "pretty"
+        )) {
+            return;
+        }
         let input = Input::pipe("stack bug error".to_owned());
         let loc = Location::Line(1);
         let err = CompileError::stack_bug(input, loc, 0, 0, "FileName");
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1,
+
+ 1 | stack bug error
+
+CompilerSystemError: the number of elements in the stack is invalid (num of elems: 0, block id: 0)
+
+this is a bug of the Erg compiler, please report it to https://github.com/erg-lang/erg
+caused from: FileName"#,
+        );
 
         let input = Input::pipe("checker bug error".to_owned());
         let errno = 0;
         let err = TyCheckError::checker_bug(input, errno, Location::Unknown, "name", 1);
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>,
+
+ ? | checker bug error
+
+CompilerSystemError: this is a bug of the Erg compiler, please report it to https://github.com/erg-lang/erg
+
+caused from: name:1"#,
+        );
 
         let loc = Location::LineRange(1, 3);
         let input = Input::pipe("args\nmissing\nerror".to_string());
@@ -570,14 +796,22 @@ mod test {
             caused_by.into(),
             vec!["sample".into(), "args".into(), "here".into()],
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1..3, <caused_by>
 
-        let loc = Location::Range {
-            ln_begin: 1,
-            col_begin: 0,
-            ln_end: 1,
-            col_end: 17,
-        };
+1 | args
+  : ----
+2 | missing
+  : -------
+3 | error
+  : -----
+
+TypeError: missing 3 positional argument(s) for "Callee name here"
+missing: sample, args, here"#,
+        );
+
+        let loc = Location::range(1, 0, 1, 17);
         let expect = Type::Nat;
         let found = Type::Int;
         let input = Input::pipe("return type error".to_string());
@@ -592,14 +826,19 @@ mod test {
             &found,
             None,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
 
-        let loc = Location::Range {
-            ln_begin: 1,
-            col_begin: 0,
-            ln_end: 1,
-            col_end: 4,
-        };
+1 | return type error
+  : -----------------
+  :                 |- expected: Nat
+  :                 `- but found: Int
+
+TypeError: the return type of name is mismatched"#,
+        );
+
+        let loc = Location::range(1, 0, 1, 4);
         let expect = Type::Nat;
         let found = Type::Int;
         let input = Input::pipe("type mismatch error".to_string());
@@ -615,7 +854,18 @@ mod test {
             None,
             Some("hint message here".to_owned()),
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | type mismatch error
+  : ----
+  :    |- expected: Nat
+  :    |- but found: Int
+  :    `- hint message here
+
+TypeError: the type of name (the 1st argument) is mismatched"#,
+        );
 
         let input = Input::pipe(
             "too_many_args_error(some_long_name_variable_1,
@@ -639,12 +889,40 @@ mod test {
             pos_args_len,
             kw_args_len,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1..4, <caused_by>
+
+1 | too_many_args_error(some_long_name_variable_1,
+  : ----------------------------------------------
+2 |     some_long_name_variable_2,
+  : ------------------------------
+3 |     some_long_name_variable_3,
+  : ------------------------------
+4 |     some_long_name_variable_4) =
+  : --------------------------------
+
+TypeError: too many arguments for callee name
+
+total expected params:  3
+passed positional args: 4
+passed keyword args:    4"#,
+        );
 
         let input = Input::pipe("argument error".to_string());
         let loc = Location::range(1, 0, 1, 8);
         let err = TyCheckError::argument_error(input, errno, loc, caused_by.to_string(), 1, 2);
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | argument error
+  : --------
+  :        |- expected: 1
+  :        `- but found: 2
+
+TypeError: the number of positional arguments is mismatched"#,
+        );
 
         let input = Input::pipe("Nat <: Int <: Ratio".to_string());
         let loc = Location::range(1, 0, 1, 10);
@@ -652,7 +930,18 @@ mod test {
         let sup_t = &Type::Int;
         let err =
             TyCheckError::subtyping_error(input, errno, sub_t, sup_t, loc, caused_by.to_string());
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | Nat <: Int <: Ratio
+  : ----------
+  :          |- subtype: Nat
+  :          |- supertype: Int
+  :          `- The type checker may fail to inference types. Please try to explicitly specify the type.
+
+TypeError: the subtype constraint in this expression cannot be satisfied"#,
+        );
 
         let input = Input::pipe("pred unification error".to_string());
         let lhs = &Predicate::Const("Str".into());
@@ -665,16 +954,20 @@ mod test {
             Location::Unknown,
             caused_by.to_string(),
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, <caused_by>
+
+ ? | pred unification error
+lhs: Str
+rhs: Nat
+
+TypeError: predicate unification failed"#,
+        );
 
         let input = Input::pipe("Trait member type error".to_string());
         let errno = 0;
-        let loc = Location::Range {
-            ln_begin: 1,
-            col_begin: 0,
-            ln_end: 1,
-            col_end: 5,
-        };
+        let loc = Location::range(1, 0, 1, 5);
         let t_ty = &Type::Float;
         let exp = &Type::Nat;
         let fnd = &Type::Obj;
@@ -689,7 +982,18 @@ mod test {
             fnd,
             Some("hint message here".to_string()),
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | Trait member type error
+  : -----
+  :     |- declared in Float: Nat
+  :     |- but found: Obj
+  :     `- hint message here
+
+TypeError: the type of member name is mismatched"#,
+        );
 
         let input = Input::pipe("trait member not defined error".to_string());
         let member_name = "member name";
@@ -706,15 +1010,18 @@ mod test {
             hint,
             Location::Unknown,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, <caused_by>
+
+ ? | trait member not defined error
+hint message here
+
+TypeError: member name of ClassType is not implemented in Ellipsis"#,
+        );
 
         let input = Input::pipe("singular no attribute error".to_string());
-        let loc = Location::Range {
-            ln_begin: 1,
-            col_begin: 0,
-            ln_end: 1,
-            col_end: 8,
-        };
+        let loc = Location::range(1, 0, 1, 8);
         let obj_name = "ojb name";
         let obj_t = Type::Bool;
         let name = "name";
@@ -729,7 +1036,16 @@ mod test {
             name,
             similar_name,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | singular no attribute error
+  : --------
+  :        `- has a similar name attribute: similar name
+
+AttributeError: ojb name(: Bool) has no attribute name"#,
+        );
 
         let input = Input::pipe("ambiguous type error".to_string());
         let raw = erg_parser::ast::Identifier::new(
@@ -753,7 +1069,18 @@ mod test {
         let candidates = &[Type::Nat, Type::Inf, Type::Bool];
         let err =
             EvalError::ambiguous_type_error(input, errno, &expr, candidates, caused_by.to_string());
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, <caused_by>
+
+ ? | ambiguous type error
+specify the type
+candidates: Nat, Inf, Bool
+if it is a polymorphic function, like f|T := Int|
+if it is a type attribute, like f|T := Trait|.X
+
+TypeError: cannot determine the type of ::variable_name(: Nat)"#,
+        );
 
         let input = Input::pipe("invalid type cast error".to_string());
         let loc = Location::range(1, 8, 1, 17);
@@ -769,7 +1096,16 @@ mod test {
             &cast_to,
             hint,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | invalid type cast error
+  :         ---------
+  :                 `- hint message here
+
+TypeError: name: Error cannot be cast to Error"#,
+        );
 
         let input = Input::pipe("override error".to_string());
         let name_loc = Location::range(1, 0, 1, 8);
@@ -782,14 +1118,31 @@ mod test {
             superclass,
             caused_by.to_string(),
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | override error
+  : --------
+  :        |- cannot override by default
+  :        `- use @Override decorator
+
+NameError: name is already defined in Failure"#,
+        );
 
         let input = Input::pipe("visibility error".to_string());
         let loc = Location::Line(1);
         let vis = crate::ty::Visibility::DUMMY_PRIVATE;
         let err =
             TyCheckError::visibility_error(input, errno, loc, caused_by.to_string(), name, vis);
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+ 1 | visibility error
+
+VisibilityError: name is private variable"#,
+        );
 
         let input = Input::pipe("import nunpy as np".to_string());
         let errno = 0;
@@ -806,7 +1159,18 @@ mod test {
             similar_erg_mod.clone(),
             similar_py_mod.clone(),
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | import nunpy as np
+  :        -----
+  :            |- similar name erg module exists: numpyer
+  :            |- similar name python module exists: numpy
+  :            `- to import python modules, use pyimport
+
+ImportError: nunpy is not defined"#,
+        );
 
         let err = TyCheckError::import_error(
             input.clone(),
@@ -817,7 +1181,17 @@ mod test {
             None,
             similar_py_mod,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | import nunpy as np
+  :        -----
+  :            |- similar name python module exists: numpy
+  :            `- to import python modules, use pyimport (not import)
+
+ImportError: nunpy is not defined"#,
+        );
 
         let err = TyCheckError::import_error(
             input.clone(),
@@ -828,14 +1202,28 @@ mod test {
             similar_erg_mod,
             None,
         );
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
+
+1 | import nunpy as np
+  :        -----
+  :            |- similar name erg module exists: numpyer
+  :            `- to import erg modules, use import (not pyimport)
+
+ImportError: nunpy is not defined"#,
+        );
 
         let err =
             TyCheckError::import_error(input, errno, desc, loc, caused_by.to_string(), None, None);
-        errors.push(err);
+        assert_rendered(
+            &err,
+            r#"Error[#]: File <stdin>, line 1, <caused_by>
 
-        for err in errors.into_iter() {
-            print!("{err}");
-        }
+1 | import nunpy as np
+  :        -----
+
+ImportError: nunpy is not defined"#,
+        );
     }
 }
@@ -169,6 +169,141 @@ impl TyCheckError {
         )
     }
 
+    /// `found` is `T or NoneType` and `T` is compatible with the parameter
+    pub fn possibly_none_arg_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        nth_param: Option<usize>,
+        found: &Type,
+    ) -> Self {
+        let ord = match nth_param {
+            Some(pos) => switch_lang!(
+                "japanese" => format!("({pos}番目の引数)"),
+                "simplified_chinese" => format!("(第{pos}个参数)"),
+                "traditional_chinese" => format!("(第{pos}個參數)"),
+                "english" => format!(" (the {} argument)", ordinal_num(pos)),
+            ),
+            None => "".to_owned(),
+        };
+        let name = format!("{name}{ord}").with_color_and_attr(WARN, ATTR);
+        let found_s = format!("{found}").with_color_and_attr(ERR, ATTR);
+        Self::possibly_none_error(
+            input,
+            errno,
+            loc,
+            caused_by,
+            switch_lang!(
+                "japanese" => format!("{name}に渡された値はNoneの可能性があります(型: {found_s})"),
+                "simplified_chinese" => format!("传递给{name}的值可能为None(类型: {found_s})"),
+                "traditional_chinese" => format!("傳遞給{name}的值可能為None(類型: {found_s})"),
+                "english" => format!("the value passed to {name} may be None (of type {found_s})"),
+            ),
+        )
+    }
+
+    /// `found` is `T or NoneType` and `T` has the attribute
+    pub fn possibly_none_attr_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        attr_name: &str,
+        found: &Type,
+    ) -> Self {
+        let attr_name = readable_name(attr_name).with_color_and_attr(WARN, ATTR);
+        let found_s = format!("{found}").with_color_and_attr(ERR, ATTR);
+        Self::possibly_none_error(
+            input,
+            errno,
+            loc,
+            caused_by,
+            switch_lang!(
+                "japanese" => format!("属性{attr_name}にアクセスされたオブジェクトはNoneの可能性があります(型: {found_s})"),
+                "simplified_chinese" => format!("访问属性{attr_name}的对象可能为None(类型: {found_s})"),
+                "traditional_chinese" => format!("存取屬性{attr_name}的物件可能為None(類型: {found_s})"),
+                "english" => format!("the object whose attribute {attr_name} is accessed may be None (of type {found_s})"),
+            ),
+        )
+    }
+
+    fn possibly_none_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        msg: String,
+    ) -> Self {
+        let hint = switch_lang!(
+            "japanese" => "使用する前に値がNoneでないことを確かめてください(例: `if x != None:`)",
+            "simplified_chinese" => "请在使用前确认该值不为None(例如`if x != None:`)",
+            "traditional_chinese" => "請在使用前確認該值不為None(例如`if x != None:`)",
+            "english" => "check that the value is not None before using it (e.g. `if x != None:`)",
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                msg,
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    /// `value` is the result of constant folding (e.g. `x: {I: Int | I < 256} = 200 + 100`)
+    pub fn out_of_range_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        expect: &Type,
+        value: impl Display,
+    ) -> Self {
+        let name = name.with_color_and_attr(WARN, ATTR);
+        let mut expct = StyledStrings::default();
+        switch_lang!(
+            "japanese" => expct.push_str("予期した型: "),
+            "simplified_chinese" =>expct.push_str("预期: "),
+            "traditional_chinese" => expct.push_str("預期: "),
+            "english" => expct.push_str("expected: "),
+        );
+        expct.push_str_with_color_and_attr(format!("{expect}"), HINT, ATTR);
+        let mut fnd = StyledStrings::default();
+        switch_lang!(
+            "japanese" => fnd.push_str("計算された値: "),
+            "simplified_chinese" => fnd.push_str("计算出的值: "),
+            "traditional_chinese" => fnd.push_str("計算出的值: "),
+            "english" => fnd.push_str("computed value: "),
+        );
+        fnd.push_str_with_color_and_attr(format!("{value}"), ERR, ATTR);
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(
+                    loc,
+                    vec![expct.to_string(), fnd.to_string()],
+                    None,
+                )],
+                switch_lang!(
+                    "japanese" => format!("{name}の値({value})が型の範囲外です"),
+                    "simplified_chinese" => format!("{name}的值({value})超出了类型的范围"),
+                    "traditional_chinese" => format!("{name}的值({value})超出了類型的範圍"),
+                    "english" => format!("the value of {name} ({value}) is out of the range of the type"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn return_type_error(
         input: Input,
@@ -586,6 +721,7 @@ passed keyword args:    {kw_args_len}"
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn unexpected_kw_arg_error(
         input: Input,
         errno: usize,
@@ -594,9 +730,21 @@ passed keyword args:    {kw_args_len}"
         caused_by: String,
         param_name: &str,
         similar_name: Option<&str>,
+        signature: Option<&Type>,
     ) -> Self {
         let name = StyledStr::new(readable_name(callee_name), Some(WARN), Some(ATTR));
         let found = param_name.with_color_and_attr(ERR, ATTR);
+        let msgs = signature.map_or(vec![], |sig| {
+            let mut expct = StyledStrings::default();
+            switch_lang!(
+                "japanese" => expct.push_str("予期したシグネチャ: "),
+                "simplified_chinese" => expct.push_str("预期签名: "),
+                "traditional_chinese" => expct.push_str("預期簽名: "),
+                "english" => expct.push_str("expected signature: "),
+            );
+            expct.push_str_with_color_and_attr(format!("{sig}"), HINT, ATTR);
+            vec![expct.to_string()]
+        });
         let hint = match similar_name {
             Some(similar_name) => {
                 let similar_name = similar_name.with_color_and_attr(HINT, ATTR);
@@ -611,7 +759,7 @@ passed keyword args:    {kw_args_len}"
         };
         Self::new(
             ErrorCore::new(
-                vec![SubMessage::ambiguous_new(loc, vec![], hint)],
+                vec![SubMessage::ambiguous_new(loc, msgs, hint)],
                 switch_lang!(
                     "japanese" => format!("{name}に予期しないキーワード引数{found}が渡されています"),
                     "simplified_chinese" => format!("{name}得到了意外的关键字参数{found}"),
@@ -0,0 +1,766 @@
+//! Expands macros (subroutines decorated with `@Macro`) and embeds compile-time values (`comptime`).
+//!
+//! A macro is defined at the top level of a module and is expanded before linking and lowering:
+//! ```erg
+//! @Macro
+//! twice!(action) =
+//!     action
+//!     action
+//!
+//! twice! print! "hello"
+//! ```
+//! The arguments are not evaluated, but passed as AST fragments and substituted for the parameters.
+//! The names defined in the macro body (variables, subroutines and their parameters) are renamed on each expansion,
+//! so they never capture or shadow the names of the call site (hygiene).
+//! A parameter used as the name of a definition is replaced with the given identifier,
+//! so a macro can define things the call site names (e.g. `getter!(name, value) = name() = value`).
+//!
+//! The expanded code keeps the locations of the macro definition,
+//! and the errors in it get a sub-message pointing at the call site (see `annotate_errors`).
+//!
+//! `comptime expr` evaluates `expr` with the constant evaluator and embeds the value in the code
+//! (e.g. `SECS_PER_DAY = comptime 60 * 60 * 24`). This is done while lowering (see `value_to_expr`).
+//! `comptime` is not a keyword but a reserved name: it cannot be defined in Erg mode (see `COMPTIME`).
+use erg_common::config::ErgConfig;
+use erg_common::consts::ERG_MODE;
+use erg_common::dict::Dict;
+use erg_common::error::{Location, SubMessage};
+use erg_common::set::Set;
+use erg_common::traits::{Locational, Stream};
+use erg_common::{get_hash, log, switch_lang, Str};
+
+use erg_parser::ast::{
+    Accessor, Args, Array, Block, Call, Def, Dict as AstDict, Dummy, Expr, Identifier, KeyValue,
+    Lambda, Literal, Module, NormalArray, NormalDict, NormalSet, NormalTuple, ParamPattern, Params,
+    PosArg, Set as AstSet, Signature, Tuple, VarName, VarPattern, AST,
+};
+use erg_parser::token::{Token, TokenKind};
+use erg_parser::visitor::{
+    walk_expr_mut, walk_lambda_mut, walk_params, walk_signature, walk_signature_mut, Visitor,
+    VisitorMut,
+};
+
+use crate::error::{CompileErrors, LowerError, LowerErrors};
+use crate::ty::value::ValueObj;
+
+/// Nested expansions deeper than this are considered infinite recursion.
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+/// The decorator that marks a subroutine definition as a macro
+const MACRO_DECORATOR: &str = "Macro";
+/// The name of the compile-time evaluation (`comptime expr`).
+/// Calls of it are always treated as `comptime`, so the name cannot be defined by the user (`reserved_name_error`).
+pub(crate) const COMPTIME: &str = "comptime";
+
+fn is_macro_def(def: &Def) -> bool {
+    def.sig.decorators().is_some_and(|decos| {
+        decos.iter().any(|deco| {
+            matches!(&deco.0, Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == MACRO_DECORATOR)
+        })
+    })
+}
+
+/// `comptime expr` (`comptime` cannot be redefined)
+pub(crate) fn is_comptime_call(call: &Call) -> bool {
+    ERG_MODE
+        && call.attr_name.is_none()
+        && matches!(call.obj.as_ref(), Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == COMPTIME)
+}
+
+fn is_private(ident: &Identifier) -> bool {
+    ident.vis.is_private()
+}
+
+/// `inner` is in `outer` (line ranges are treated as whole lines)
+fn loc_contains(outer: Location, inner: Location) -> bool {
+    let (Some(outer_ln_begin), Some(outer_ln_end), Some(inner_ln_begin), Some(inner_ln_end)) = (
+        outer.ln_begin(),
+        outer.ln_end(),
+        inner.ln_begin(),
+        inner.ln_end(),
+    ) else {
+        return false;
+    };
+    let outer_begin = (outer_ln_begin, outer.col_begin().unwrap_or(0));
+    let outer_end = (outer_ln_end, outer.col_end().unwrap_or(u32::MAX));
+    let inner_begin = (inner_ln_begin, inner.col_begin().unwrap_or(0));
+    let inner_end = (inner_ln_end, inner.col_end().unwrap_or(u32::MAX));
+    outer_begin <= inner_begin && inner_end <= outer_end
+}
+
+#[derive(Debug, Clone)]
+struct MacroDef {
+    params: Vec<Str>,
+    body: Block,
+    loc: Location,
+}
+
+/// A record of an expansion, used to map the errors in the generated code to the call site.
+#[derive(Debug, Clone)]
+pub struct MacroExpansion {
+    pub name: Str,
+    /// the location of the macro call
+    pub call: Location,
+    /// the location of the macro definition (the expanded code is located here)
+    pub def: Location,
+}
+
+fn loc_begin(loc: Location) -> Location {
+    match (loc.ln_begin(), loc.col_begin()) {
+        (Some(ln), Some(col)) => Location::range(ln, col, ln, col),
+        (Some(ln), None) => Location::Line(ln),
+        _ => Location::Unknown,
+    }
+}
+
+fn loc_end(loc: Location) -> Location {
+    match (loc.ln_end(), loc.col_end()) {
+        (Some(ln), Some(col)) => Location::range(ln, col, ln, col),
+        (Some(ln), None) => Location::Line(ln),
+        _ => Location::Unknown,
+    }
+}
+
+/// Maps the errors in the expanded code to the call sites.
+///
+/// * An error in the macro body gets a sub-message pointing at the call site.
+///   The body is shared by all the expansions, so every call site of the macro is pointed at.
+/// * An error spanning both the macro body and an argument (e.g. `x + "a"` where `x` is `1`)
+///   has an inconsistent location, so it is moved to the call site.
+pub fn annotate_errors(errs: &mut CompileErrors, expansions: &[MacroExpansion]) {
+    for err in errs.iter_mut() {
+        let loc = err.core.loc;
+        let (begin, end) = (loc_begin(loc), loc_end(loc));
+        for expansion in expansions.iter() {
+            let in_def = |loc| loc_contains(expansion.def, loc);
+            let in_call = |loc| loc_contains(expansion.call, loc);
+            let is_mixed = (in_def(begin) && in_call(end)) || (in_call(begin) && in_def(end));
+            if !is_mixed && !in_def(loc) {
+                continue;
+            }
+            if is_mixed {
+                err.core.loc = expansion.call;
+                for sub in err.core.sub_messages.iter_mut() {
+                    if sub.loc == loc {
+                        sub.loc = expansion.call;
+                    }
+                }
+            }
+            let name = &expansion.name;
+            let hint = switch_lang!(
+                "japanese" => format!("マクロ{name}の展開内で発生しました"),
+                "simplified_chinese" => format!("发生在宏{name}的展开中"),
+                "traditional_chinese" => format!("發生在宏{name}的展開中"),
+                "english" => format!("in the expansion of the macro {name}"),
+            );
+            err.core.sub_messages.push(SubMessage::ambiguous_new(
+                expansion.call,
+                vec![],
+                Some(hint),
+            ));
+            if is_mixed {
+                break;
+            }
+        }
+    }
+}
+
+/// Collects the private names defined in the macro body.
+#[derive(Debug, Default)]
+struct BindingCollector {
+    names: Set<Str>,
+}
+
+impl BindingCollector {
+    /// e.g. `x`, `[x, y]`, `{name; age}`, `Data::{x; y}`
+    fn collect_var_pattern(&mut self, pat: &VarPattern) {
+        match pat {
+            VarPattern::Ident(ident) => {
+                if is_private(ident) {
+                    self.names.insert(ident.inspect().clone());
+                }
+            }
+            VarPattern::Array(array) => {
+                for sig in array.iter() {
+                    self.collect_var_pattern(&sig.pat);
+                }
+            }
+            VarPattern::Tuple(tuple) => {
+                for sig in tuple.iter() {
+                    self.collect_var_pattern(&sig.pat);
+                }
+            }
+            // the attribute names (`lhs`) are not bindings
+            VarPattern::Record(record) => {
+                for attr in record.attrs().iter() {
+                    self.collect_var_pattern(&attr.rhs.pat);
+                }
+            }
+            VarPattern::DataPack(pack) => {
+                for attr in pack.args.attrs().iter() {
+                    self.collect_var_pattern(&attr.rhs.pat);
+                }
+            }
+            VarPattern::Discard(_) => {}
+        }
+    }
+
+    /// e.g. `x`, `ref x`, `[x, y]`, `{name; age}`, `x := [y, z]`
+    fn collect_param_pattern(&mut self, pat: &ParamPattern) {
+        match pat {
+            ParamPattern::VarName(name) | ParamPattern::Ref(name) | ParamPattern::RefMut(name) => {
+                self.names.insert(name.inspect().clone());
+            }
+            ParamPattern::Array(array) => self.collect_params(&array.elems),
+            ParamPattern::Tuple(tuple) => self.collect_params(&tuple.elems),
+            ParamPattern::Record(record) => {
+                for attr in record.elems().iter() {
+                    self.collect_param_pattern(&attr.rhs.pat);
+                }
+            }
+            ParamPattern::Bind(bind) => {
+                self.names.insert(bind.name.inspect().clone());
+                self.collect_param_pattern(&bind.pat);
+            }
+            ParamPattern::Discard(_) | ParamPattern::Lit(_) => {}
+        }
+    }
+
+    fn collect_params(&mut self, params: &Params) {
+        let non_defaults = params
+            .non_defaults
+            .iter()
+            .chain(params.var_params.as_deref())
+            .chain(params.kw_var_params.as_deref())
+            .map(|param| &param.pat);
+        let defaults = params.defaults.iter().map(|param| &param.sig.pat);
+        for pat in non_defaults.chain(defaults) {
+            self.collect_param_pattern(pat);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for BindingCollector {
+    fn visit_signature(&mut self, sig: &'a Signature) {
+        match sig {
+            Signature::Var(var) => self.collect_var_pattern(&var.pat),
+            Signature::Subr(subr) => {
+                if is_private(&subr.ident) {
+                    self.names.insert(subr.ident.inspect().clone());
+                }
+            }
+        }
+        walk_signature(self, sig);
+    }
+
+    fn visit_params(&mut self, params: &'a Params) {
+        self.collect_params(params);
+        walk_params(self, params);
+    }
+}
+
+/// Instantiates the macro body: renames the local names and substitutes the arguments for the parameters.
+struct Instantiator<'m> {
+    cfg: &'m ErgConfig,
+    macro_name: &'m Str,
+    renames: Dict<Str, Str>,
+    args: Dict<Str, Expr>,
+    expansion_id: usize,
+    errs: LowerErrors,
+}
+
+impl Instantiator<'_> {
+    fn rename_varname(&self, name: &mut VarName) {
+        if let Some(new) = self.renames.get(name.inspect()) {
+            let mut token = name.token().clone();
+            token.content = new.clone();
+            *name = VarName::new(token);
+        }
+    }
+
+    /// Renames the names bound by the pattern (see `BindingCollector::collect_var_pattern`).
+    fn rename_var_pattern(&mut self, pat: &mut VarPattern) {
+        match pat {
+            VarPattern::Ident(ident) => self.substitute_def_name(ident),
+            VarPattern::Array(array) => {
+                for sig in array.iter_mut() {
+                    self.rename_var_pattern(&mut sig.pat);
+                }
+            }
+            VarPattern::Tuple(tuple) => {
+                for sig in tuple.iter_mut() {
+                    self.rename_var_pattern(&mut sig.pat);
+                }
+            }
+            VarPattern::Record(record) => {
+                for attr in record.attrs_mut().iter_mut() {
+                    self.rename_var_pattern(&mut attr.rhs.pat);
+                }
+            }
+            VarPattern::DataPack(pack) => {
+                for attr in pack.args.attrs_mut().iter_mut() {
+                    self.rename_var_pattern(&mut attr.rhs.pat);
+                }
+            }
+            VarPattern::Discard(_) => {}
+        }
+    }
+
+    /// Renames the names bound by the pattern (see `BindingCollector::collect_param_pattern`).
+    fn rename_param_pattern(&mut self, pat: &mut ParamPattern) {
+        match pat {
+            ParamPattern::VarName(name) | ParamPattern::Ref(name) | ParamPattern::RefMut(name) => {
+                self.rename_varname(name);
+            }
+            ParamPattern::Array(array) => self.rename_params(&mut array.elems),
+            ParamPattern::Tuple(tuple) => self.rename_params(&mut tuple.elems),
+            ParamPattern::Record(record) => {
+                for attr in record.elems_mut().iter_mut() {
+                    self.rename_param_pattern(&mut attr.rhs.pat);
+                }
+            }
+            ParamPattern::Bind(bind) => {
+                self.rename_varname(&mut bind.name);
+                self.rename_param_pattern(&mut bind.pat);
+            }
+            ParamPattern::Discard(_) | ParamPattern::Lit(_) => {}
+        }
+    }
+
+    fn rename_params(&mut self, params: &mut Params) {
+        for param in params
+            .non_defaults
+            .iter_mut()
+            .chain(params.var_params.as_deref_mut())
+            .chain(params.kw_var_params.as_deref_mut())
+        {
+            self.rename_param_pattern(&mut param.pat);
+        }
+        for param in params.defaults.iter_mut() {
+            self.rename_param_pattern(&mut param.sig.pat);
+        }
+    }
+
+    /// The parameter is used as the name of a definition: replace it with the given identifier.
+    fn substitute_def_name(&mut self, ident: &mut Identifier) {
+        let Some(arg) = self.args.get(ident.inspect()) else {
+            self.rename_varname(&mut ident.name);
+            return;
+        };
+        match arg {
+            Expr::Accessor(Accessor::Ident(arg)) => {
+                *ident = arg.clone();
+            }
+            other => {
+                self.errs.push(LowerError::invalid_macro_arg_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    other.loc(),
+                    "".into(),
+                    self.macro_name,
+                ));
+            }
+        }
+    }
+}
+
+impl VisitorMut for Instantiator<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Accessor(Accessor::Ident(ident)) = expr {
+            if let Some(arg) = self.args.get(ident.inspect()) {
+                // the arguments belong to the call site, so they are not renamed
+                *expr = arg.clone();
+                return;
+            }
+        }
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_accessor_mut(&mut self, acc: &mut Accessor) {
+        match acc {
+            Accessor::Ident(ident) => self.rename_varname(&mut ident.name),
+            // attribute names are not renamed
+            Accessor::Attr(attr) => self.visit_expr_mut(&mut attr.obj),
+            Accessor::TupleAttr(attr) => self.visit_expr_mut(&mut attr.obj),
+            Accessor::Subscr(subscr) => {
+                self.visit_expr_mut(&mut subscr.obj);
+                self.visit_expr_mut(&mut subscr.index);
+            }
+            Accessor::TypeApp(app) => self.visit_expr_mut(&mut app.obj),
+        }
+    }
+
+    fn visit_call_mut(&mut self, call: &mut Call) {
+        self.visit_expr_mut(&mut call.obj);
+        self.visit_args_mut(&mut call.args);
+    }
+
+    fn visit_def_mut(&mut self, def: &mut Def) {
+        def.body.id.0 = get_hash(&(def.body.id.0, self.expansion_id));
+        self.visit_signature_mut(&mut def.sig);
+        self.visit_block_mut(&mut def.body.block);
+    }
+
+    fn visit_lambda_mut(&mut self, lambda: &mut Lambda) {
+        lambda.id.0 = get_hash(&(lambda.id.0, self.expansion_id));
+        walk_lambda_mut(self, lambda);
+    }
+
+    fn visit_signature_mut(&mut self, sig: &mut Signature) {
+        match sig {
+            Signature::Var(var) => self.rename_var_pattern(&mut var.pat),
+            Signature::Subr(subr) => self.substitute_def_name(&mut subr.ident),
+        }
+        walk_signature_mut(self, sig);
+    }
+
+    fn visit_params_mut(&mut self, params: &mut Params) {
+        self.rename_params(params);
+        for default in params.defaults.iter_mut() {
+            self.visit_expr_mut(&mut default.default_val);
+        }
+    }
+}
+
+/// Expands the macros in a module. The macro definitions are removed from the module.
+#[derive(Debug)]
+pub struct MacroExpander {
+    cfg: ErgConfig,
+    macros: Dict<Str, MacroDef>,
+    depth: usize,
+    pub expansions: Vec<MacroExpansion>,
+    pub errs: LowerErrors,
+}
+
+impl MacroExpander {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            cfg,
+            macros: Dict::new(),
+            depth: 0,
+            expansions: vec![],
+            errs: LowerErrors::empty(),
+        }
+    }
+
+    pub fn expand(&mut self, mut ast: AST) -> AST {
+        log!(info "the macro expansion process has started.");
+        let chunks = std::mem::take(ast.module.ref_mut_payload());
+        let mut rest = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            match chunk {
+                Expr::Def(def) if is_macro_def(&def) => self.register(def),
+                other => rest.push(other),
+            }
+        }
+        if self.macros.is_empty() {
+            *ast.module.ref_mut_payload() = rest;
+            return ast;
+        }
+        *ast.module.ref_mut_payload() = self.expand_chunks(rest);
+        log!(info "the macro expansion process has completed.");
+        ast
+    }
+
+    fn register(&mut self, def: Def) {
+        let loc = def.loc();
+        let Signature::Subr(subr) = def.sig else {
+            return;
+        };
+        let params = &subr.params;
+        let mut param_names = vec![];
+        for param in params.non_defaults.iter() {
+            match &param.pat {
+                ParamPattern::VarName(name) if param.t_spec.is_none() => {
+                    param_names.push(name.inspect().clone());
+                }
+                _ => {
+                    self.errs.push(LowerError::invalid_macro_def_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        param.loc(),
+                        "".into(),
+                    ));
+                    return;
+                }
+            }
+        }
+        if let Some(param) = params.var_params.as_ref().or(params.kw_var_params.as_ref()) {
+            self.errs.push(LowerError::invalid_macro_def_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                param.loc(),
+                "".into(),
+            ));
+            return;
+        }
+        if let Some(param) = params.defaults.first() {
+            self.errs.push(LowerError::invalid_macro_def_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                param.loc(),
+                "".into(),
+            ));
+            return;
+        }
+        self.macros.insert(
+            subr.ident.inspect().clone(),
+            MacroDef {
+                params: param_names,
+                body: def.body.block,
+                loc,
+            },
+        );
+    }
+
+    fn macro_name(&self, expr: &Expr) -> Option<Str> {
+        let Expr::Call(call) = expr else {
+            return None;
+        };
+        match (call.obj.as_ref(), &call.attr_name) {
+            (Expr::Accessor(Accessor::Ident(ident)), None)
+                if is_private(ident) && self.macros.contains_key(ident.inspect()) =>
+            {
+                Some(ident.inspect().clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn expand_chunks(&mut self, chunks: Vec<Expr>) -> Vec<Expr> {
+        let mut new = Vec::with_capacity(chunks.len());
+        for mut chunk in chunks {
+            if let Expr::Def(def) = &chunk {
+                if is_macro_def(def) {
+                    self.errs.push(LowerError::inner_macro_def_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        def.loc(),
+                        "".into(),
+                    ));
+                    continue;
+                }
+            }
+            if self.macro_name(&chunk).is_some() {
+                let Expr::Call(call) = chunk else {
+                    unreachable!()
+                };
+                match self.expand_call(call) {
+                    Ok(expanded) => {
+                        self.depth += 1;
+                        new.extend(self.expand_chunks(expanded));
+                        self.depth -= 1;
+                    }
+                    Err(dummy) => new.push(dummy),
+                }
+            } else {
+                self.visit_expr_mut(&mut chunk);
+                new.push(chunk);
+            }
+        }
+        new
+    }
+
+    /// Returns the expanded chunks, or a `Dummy` if the expansion failed.
+    fn expand_call(&mut self, call: Call) -> Result<Vec<Expr>, Expr> {
+        let loc = call.loc();
+        let dummy = Expr::Dummy(Dummy::new(Some(loc), vec![]));
+        let Expr::Accessor(Accessor::Ident(ident)) = call.obj.as_ref() else {
+            return Err(dummy);
+        };
+        let name = ident.inspect().clone();
+        if self.depth >= MAX_EXPANSION_DEPTH {
+            self.errs.push(LowerError::macro_recursion_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                "".into(),
+                &name,
+            ));
+            return Err(dummy);
+        }
+        let macro_def = self.macros.get(&name).unwrap().clone();
+        let (pos_args, var_args, kw_args, kw_var, _) = call.args.deconstruct();
+        if let Some(kw_arg) = kw_args.first() {
+            self.errs.push(LowerError::unexpected_kw_arg_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                kw_arg.loc(),
+                &name,
+                "".into(),
+                kw_arg.keyword.inspect(),
+                None,
+                None,
+            ));
+            return Err(dummy);
+        }
+        if pos_args.len() > macro_def.params.len() || var_args.is_some() || kw_var.is_some() {
+            self.errs.push(LowerError::too_many_args_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                &name,
+                "".into(),
+                macro_def.params.len(),
+                pos_args.len() + var_args.iter().count() + kw_var.iter().count(),
+                0,
+            ));
+            return Err(dummy);
+        }
+        if pos_args.len() < macro_def.params.len() {
+            self.errs.push(LowerError::args_missing_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                &name,
+                "".into(),
+                macro_def.params[pos_args.len()..].to_vec(),
+            ));
+            return Err(dummy);
+        }
+        let expansion_id = self.expansions.len();
+        let mut collector = BindingCollector::default();
+        collector.visit_block(&macro_def.body);
+        let renames = collector
+            .names
+            .into_iter()
+            .filter(|name| !macro_def.params.contains(name))
+            .map(|name| {
+                // `%` cannot be used in the source code, so the new name never conflicts
+                let new = match name.strip_suffix('!') {
+                    Some(stem) => format!("{stem}%{expansion_id}!"),
+                    None => format!("{name}%{expansion_id}"),
+                };
+                (name, Str::from(new))
+            })
+            .collect();
+        let args = macro_def
+            .params
+            .iter()
+            .cloned()
+            .zip(pos_args.into_iter().map(|arg| arg.expr))
+            .collect();
+        let mut instantiator = Instantiator {
+            cfg: &self.cfg,
+            macro_name: &name,
+            renames,
+            args,
+            expansion_id,
+            errs: LowerErrors::empty(),
+        };
+        let mut body = macro_def.body;
+        instantiator.visit_block_mut(&mut body);
+        let errs = instantiator.errs;
+        self.expansions.push(MacroExpansion {
+            name,
+            call: loc,
+            def: macro_def.loc,
+        });
+        if !errs.is_empty() {
+            self.errs.extend(errs);
+            return Err(dummy);
+        }
+        Ok(body.into_iter().collect())
+    }
+}
+
+impl VisitorMut for MacroExpander {
+    fn visit_module_mut(&mut self, module: &mut Module) {
+        let chunks = std::mem::take(module.ref_mut_payload());
+        *module.ref_mut_payload() = self.expand_chunks(chunks);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let chunks = std::mem::take(block.ref_mut_payload());
+        *block.ref_mut_payload() = self.expand_chunks(chunks);
+    }
+
+    fn visit_dummy_mut(&mut self, dummy: &mut Dummy) {
+        self.visit_block_mut(&mut dummy.exprs);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let Some(name) = self.macro_name(expr) else {
+            walk_expr_mut(self, expr);
+            return;
+        };
+        let Expr::Call(call) = std::mem::replace(expr, Expr::Dummy(Dummy::new(None, vec![])))
+        else {
+            unreachable!()
+        };
+        let loc = call.loc();
+        match self.expand_call(call) {
+            Ok(mut expanded) if expanded.len() == 1 => {
+                *expr = expanded.remove(0);
+                self.depth += 1;
+                self.visit_expr_mut(expr);
+                self.depth -= 1;
+            }
+            Ok(_) => {
+                self.errs.push(LowerError::macro_expr_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    "".into(),
+                    &name,
+                ));
+                *expr = Expr::Dummy(Dummy::new(Some(loc), vec![]));
+            }
+            Err(dummy) => *expr = dummy,
+        }
+    }
+}
+
+/// Converts the compile-time value into an expression (for `comptime`).
+/// The tokens are located at `loc`.
+pub(crate) fn value_to_expr(value: &ValueObj, loc: Location) -> Option<Expr> {
+    let lineno = loc.ln_begin().unwrap_or(0);
+    let col_begin = loc.col_begin().unwrap_or(0);
+    let token = |kind: TokenKind, content: String| Token::new(kind, content, lineno, col_begin);
+    let lit = |kind: TokenKind, content: String| Expr::Literal(Literal::new(token(kind, content)));
+    let args = |values: &[ValueObj]| -> Option<Args> {
+        let elems = values
+            .iter()
+            .map(|value| value_to_expr(value, loc).map(PosArg::new))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Args::pos_only(elems, None))
+    };
+    let expr = match value {
+        ValueObj::Nat(n) => lit(TokenKind::NatLit, n.to_string()),
+        ValueObj::Int(i) if *i >= 0 => lit(TokenKind::NatLit, i.to_string()),
+        ValueObj::Int(i) => lit(TokenKind::IntLit, i.to_string()),
+        ValueObj::Float(f) if f.is_finite() => lit(TokenKind::RatioLit, format!("{f:?}")),
+        ValueObj::Str(s) => lit(TokenKind::StrLit, format!("\"{s}\"")),
+        ValueObj::Bool(b) => lit(TokenKind::BoolLit, if *b { "True" } else { "False" }.into()),
+        ValueObj::None => lit(TokenKind::NoneLit, "None".into()),
+        ValueObj::Array(elems) => Expr::Array(Array::Normal(NormalArray::new(
+            token(TokenKind::LSqBr, "[".into()),
+            token(TokenKind::RSqBr, "]".into()),
+            args(elems)?,
+        ))),
+        ValueObj::Tuple(elems) => Expr::Tuple(Tuple::Normal(NormalTuple::new(args(elems)?))),
+        ValueObj::Set(elems) => Expr::Set(AstSet::Normal(NormalSet::new(
+            token(TokenKind::LBrace, "{".into()),
+            token(TokenKind::RBrace, "}".into()),
+            args(&elems.iter().cloned().collect::<Vec<_>>())?,
+        ))),
+        ValueObj::Dict(dict) => {
+            let kvs = dict
+                .iter()
+                .map(|(key, value)| {
+                    Some(KeyValue::new(
+                        value_to_expr(key, loc)?,
+                        value_to_expr(value, loc)?,
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Expr::Dict(AstDict::Normal(NormalDict::new(
+                token(TokenKind::LBrace, "{".into()),
+                token(TokenKind::RBrace, "}".into()),
+                kvs,
+            )))
+        }
+        _ => return None,
+    };
+    Some(expr)
+}
@@ -0,0 +1,73 @@
+//! Entry points of the fuzz targets (enabled by the `fuzzing` feature).
+//! The targets are in the `fuzz` directory of the repository (`cargo fuzz run parse`, etc.).
+//!
+//! Each entry point runs in a thread spawned by `spawn_new_thread` (so that too deeply nested input is reported as an error
+//! instead of overflowing the stack), and the panics of the thread are propagated to the fuzzer.
+//! Diagnostics are not failures; only panics, aborts and timeouts are.
+use std::panic;
+use std::sync::OnceLock;
+
+use erg_common::config::ErgConfig;
+use erg_common::io::{Input, Output};
+use erg_common::spawn::spawn_new_thread;
+use erg_common::traits::Runnable;
+
+use erg_parser::build_ast::ASTBuilder;
+
+use crate::artifact::Buildable;
+use crate::build_hir::HIRBuilder;
+use crate::context::Context;
+use crate::module::SharedCompilerResource;
+use crate::ty::Type;
+
+fn run(name: &str, f: impl FnOnce() + Send + 'static) {
+    if let Err(payload) = spawn_new_thread(f, name).join() {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// The builtins are initialized only once (initializing them for each input is slow, and the memory is not reclaimed).
+fn shared() -> SharedCompilerResource {
+    static SHARED: OnceLock<SharedCompilerResource> = OnceLock::new();
+    let shared = SHARED.get_or_init(|| SharedCompilerResource::new(ErgConfig::default()));
+    shared.clear_all();
+    shared.clone()
+}
+
+fn cfg(src: &str) -> ErgConfig {
+    ErgConfig {
+        input: Input::str(src.to_string()),
+        output: Output::Null,
+        ..ErgConfig::default()
+    }
+}
+
+/// Lexes, parses and desugars `src`.
+pub fn parse(src: &str) {
+    let src = src.to_string();
+    run("fuzz_parse", move || {
+        let mut builder = ASTBuilder::new(cfg(&src));
+        let _ = builder.build(src);
+    });
+}
+
+/// Lowers (type-checks) `src` as a module.
+pub fn lower(src: &str) {
+    let src = src.to_string();
+    run("fuzz_lower", move || {
+        let mut builder = HIRBuilder::inherit(cfg(&src), shared());
+        let _ = builder.build(src, "exec");
+    });
+}
+
+/// Compares and unifies `lhs` and `rhs` in an empty module.
+pub fn unify(lhs: Type, rhs: Type) {
+    run("fuzz_unify", move || {
+        let ctx = Context::new_module("<module>", ErgConfig::default(), shared());
+        let _ = ctx.subtype_of(&lhs, &rhs);
+        let _ = ctx.union(&lhs, &rhs);
+        let _ = ctx.intersection(&lhs, &rhs);
+        let _ = ctx.unify(&lhs, &rhs);
+        let _ = ctx.sub_unify(&lhs, &rhs, &(), None);
+    });
+}
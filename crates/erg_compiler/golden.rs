@@ -0,0 +1,721 @@
+//! Compares the code objects generated by the Erg backend with the ones CPython compiles from the transpiled script.
+//!
+//! The two backends emit different instructions for the same program (name mangling, runtime imports, jumps, etc.),
+//! so the code objects are not compared byte by byte. Each code object is normalized to a `CodeShape`
+//! (the signature, the flags, the literal constants, the names, and the operations),
+//! and the shapes are compared. A difference suggests that one of the backends changed the meaning of the program.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process;
+use std::process::Stdio;
+
+use erg_common::config::ErgConfig;
+use erg_common::error::MultiErrorDisplay;
+use erg_common::io::Input;
+use erg_common::python_util::exec_pyc;
+use erg_common::traits::{Runnable, Stream};
+
+use crate::sandbox::panic_message;
+use crate::test_runner::{compile_file, py_str_literal, unescape};
+use crate::ty::codeobj::CodeObjFlags;
+use crate::Transpiler;
+
+/// Dumps the code objects of `PYC` (compiled by Erg) and `PY` (compiled by CPython) with `dis`.
+/// Each code object is written as a `code` row, its instructions, its inner code objects, and an `end` row.
+const GOLDEN_HARNESS: &str = r#"
+import dis
+import marshal
+import types
+
+
+def escape(s):
+    return s.replace("\\", "\\\\").replace("\t", "\\t").replace("\n", "\\n")
+
+
+def write_row(f, row):
+    f.write("\t".join(map(escape, row)) + "\n")
+
+
+def arg(instr):
+    if isinstance(instr.argval, types.CodeType):
+        return "<code>"
+    elif instr.opcode in dis.hasconst:
+        return repr(instr.argval)
+    elif instr.opname == "BINARY_OP":
+        return instr.argrepr
+    else:
+        return "" if instr.arg is None else str(instr.argval)
+
+
+# CPython folds the constant containers (`[1, 2]` is `BUILD_LIST 0; LOAD_CONST (1, 2); LIST_EXTEND 1`),
+# but Erg builds them from the elements
+EXTEND = {"BUILD_LIST": "LIST_EXTEND", "BUILD_SET": "SET_UPDATE"}
+
+
+def unfold(instrs):
+    i = 0
+    while i < len(instrs):
+        instr = instrs[i]
+        if (
+            instr.opname in EXTEND
+            and instr.arg == 0
+            and [ins.opname for ins in instrs[i + 1 : i + 3]] == ["LOAD_CONST", EXTEND[instr.opname]]
+            and isinstance(instrs[i + 1].argval, (tuple, frozenset))
+        ):
+            elems = instrs[i + 1].argval
+            for elem in elems:
+                yield ["LOAD_CONST", repr(elem)]
+            yield [instr.opname, str(len(elems))]
+            i += 3
+            continue
+        is_import_operand = i + 1 < len(instrs) and instrs[i + 1].opname == "IMPORT_NAME"
+        if instr.opname == "LOAD_CONST" and type(instr.argval) is tuple and not is_import_operand:
+            for elem in instr.argval:
+                yield ["LOAD_CONST", repr(elem)]
+            yield ["BUILD_TUPLE", str(len(instr.argval))]
+        else:
+            yield [instr.opname, arg(instr)]
+        i += 1
+
+
+def dump(code, f):
+    args = [code.co_argcount, code.co_posonlyargcount, code.co_kwonlyargcount, code.co_flags]
+    write_row(f, ["code", code.co_name] + list(map(str, args)))
+    for row in unfold(list(dis.get_instructions(code))):
+        write_row(f, ["instr"] + row)
+    for const in code.co_consts:
+        if isinstance(const, types.CodeType):
+            dump(const, f)
+    write_row(f, ["end"])
+
+
+with open(RESULTS, "w", encoding="utf-8") as f:
+    with open(PYC, "rb") as pyc:
+        erg = marshal.loads(pyc.read()[16:])
+    try:
+        with open(PY, encoding="utf-8") as py:
+            python = compile(py.read(), PY, "exec")
+    except SyntaxError as err:
+        write_row(f, ["error", f"the transpiled script is invalid: {err}"])
+    else:
+        dump(erg, f)
+        dump(python, f)
+"#;
+
+/// The flags that change the behavior of a code object (the others are implementation details, e.g. `NewLocals`).
+const SEMANTIC_FLAGS: u32 = CodeObjFlags::VarArgs as u32
+    | CodeObjFlags::VarKeywords as u32
+    | CodeObjFlags::Generator as u32
+    | CodeObjFlags::Coroutine as u32
+    | CodeObjFlags::IterableCoroutine as u32
+    | CodeObjFlags::AsyncGenerator as u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Instr {
+    opname: String,
+    /// the resolved argument (e.g. the name, the `repr` of the constant)
+    arg: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RawCode {
+    name: String,
+    /// `argcount/posonlyargcount/kwonlyargcount`
+    signature: String,
+    flags: u32,
+    instrs: Vec<Instr>,
+    inner: Vec<RawCode>,
+}
+
+impl RawCode {
+    fn parse(rows: &mut dyn Iterator<Item = Vec<String>>) -> Result<Self, String> {
+        match rows.next() {
+            Some(row) => Self::parse_from(row, rows),
+            None => Err("unexpected end of the dump".into()),
+        }
+    }
+
+    /// `header`: the `code` row
+    fn parse_from(
+        header: Vec<String>,
+        rows: &mut dyn Iterator<Item = Vec<String>>,
+    ) -> Result<Self, String> {
+        let [kind, name, argcount, posonly, kwonly, flags] = &header[..] else {
+            return Err(format!("invalid row: {header:?}"));
+        };
+        if kind != "code" {
+            return Err(format!("expected a code object, but found: {header:?}"));
+        }
+        let mut code = RawCode {
+            name: name.clone(),
+            signature: format!("{argcount}/{posonly}/{kwonly}"),
+            flags: flags
+                .parse()
+                .map_err(|_| format!("invalid flags: {flags}"))?,
+            instrs: vec![],
+            inner: vec![],
+        };
+        loop {
+            let Some(row) = rows.next() else {
+                return Err("unexpected end of the dump".into());
+            };
+            match &row[..] {
+                [kind, opname, arg] if kind == "instr" => {
+                    code.instrs.push(Instr {
+                        opname: opname.clone(),
+                        arg: arg.clone(),
+                    });
+                }
+                [kind] if kind == "end" => return Ok(code),
+                [kind, ..] if kind == "code" => {
+                    code.inner.push(Self::parse_from(row, rows)?);
+                }
+                _ => return Err(format!("invalid row: {row:?}")),
+            }
+        }
+    }
+}
+
+/// The escaped symbols of the transpiler (see `transpile::replace_non_symbolic`)
+const ESCAPED_SYMBOLS: [(&str, &str); 10] = [
+    ("__single_quote__", "'"),
+    ("__space__", " "),
+    ("__plus__", "+"),
+    ("__minus__", "-"),
+    ("__star__", "*"),
+    ("__slash__", "/"),
+    ("__percent__", "%"),
+    ("__erg_proc__", "!"),
+    ("__erg_shared__", "$"),
+    ("erg_shared__", "$"),
+];
+
+/// Restores the name in the source code from the names in the code objects.
+///
+/// * Erg: `::x_L1_C4` (private names are prefixed with `::` and suffixed with the defined position), `::#path` (runtime)
+/// * transpiled: `x__` (private names are suffixed with `__`), `NamedTuple__` (runtime)
+fn normalize_name(name: &str) -> String {
+    let mut name = name.to_string();
+    for (escaped, symbol) in ESCAPED_SYMBOLS {
+        name = name.replace(escaped, symbol);
+    }
+    let is_dunder = name.len() > 4 && name.starts_with("__") && name.ends_with("__");
+    if let Some(private) = name.strip_prefix("::") {
+        let private = private.trim_start_matches('#');
+        strip_position(private).to_string()
+    } else if !is_dunder && name.len() > 2 && name.ends_with("__") {
+        name[..name.len() - 2].to_string()
+    } else {
+        name
+    }
+}
+
+/// `x_L1_C4` -> `x`, `x_L1` -> `x`, `x_C4` -> `x`
+fn strip_position(name: &str) -> &str {
+    fn strip_num<'n>(name: &'n str, prefix: &str) -> Option<&'n str> {
+        let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        if trimmed.len() < name.len() {
+            trimmed.strip_suffix(prefix)
+        } else {
+            None
+        }
+    }
+    let name = strip_num(name, "_C").unwrap_or(name);
+    strip_num(name, "_L").unwrap_or(name)
+}
+
+/// The names in strings are also mangled (e.g. the name of a class passed to `__build_class__`).
+fn normalize_const(repr: &str) -> String {
+    let is_name = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '#' | '!'))
+    };
+    match repr.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(s) if is_name(s) => format!("'{}'", normalize_name(s)),
+        _ => repr.to_string(),
+    }
+}
+
+fn is_class_attr(name: &str) -> bool {
+    matches!(name, "__module__" | "__qualname__")
+}
+
+fn is_runtime_name(name: &str) -> bool {
+    name.starts_with("::#")
+}
+
+/// Marks the instructions that load the runtime of Erg
+/// (`from _erg_std_prelude import *`, `from collections import namedtuple as ::#NamedTuple`, `::#path.append(...)`, etc.).
+/// They have no counterpart in the transpiled script, which defines the runtime in its prelude.
+fn runtime_instrs(instrs: &[Instr]) -> Vec<bool> {
+    let mut runtime = vec![false; instrs.len()];
+    let mut i = 0;
+    while i < instrs.len() {
+        let instr = &instrs[i];
+        if instr.opname == "IMPORT_NAME" {
+            // `level` and `fromlist` are loaded before `IMPORT_NAME`
+            let start = i.saturating_sub(2);
+            let mut end = i + 1;
+            let mut is_runtime = instr.arg.starts_with("_erg_");
+            while let Some(next) = instrs.get(end) {
+                match &next.opname[..] {
+                    "IMPORT_FROM" | "IMPORT_STAR" | "POP_TOP" => {}
+                    "STORE_NAME" | "STORE_GLOBAL" | "STORE_FAST" => {
+                        is_runtime |= is_runtime_name(&next.arg);
+                    }
+                    _ => break,
+                }
+                end += 1;
+            }
+            if is_runtime {
+                runtime[start..end].fill(true);
+            }
+            i = end;
+        } else if instr.opname.starts_with("LOAD_")
+            && is_runtime_name(&instr.arg)
+            && normalize_name(&instr.arg) == "path"
+        {
+            // `::#path.append(erg_std_path)`
+            let end = instrs[i..]
+                .iter()
+                .position(|instr| instr.opname == "POP_TOP")
+                .map_or(instrs.len(), |pos| i + pos + 1);
+            runtime[i..end].fill(true);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    runtime
+}
+
+/// The operation performed by the instruction (the instructions that only move values are ignored).
+fn operation(instr: &Instr) -> Option<String> {
+    match &instr.opname[..] {
+        "BINARY_OP"
+        | "COMPARE_OP"
+        | "CONTAINS_OP"
+        | "IS_OP"
+        | "BUILD_TUPLE"
+        | "BUILD_LIST"
+        | "BUILD_SET"
+        | "BUILD_MAP"
+        | "BUILD_CONST_KEY_MAP"
+        | "BUILD_SLICE"
+        | "BUILD_STRING"
+        | "UNPACK_SEQUENCE"
+        | "UNPACK_EX"
+        | "RAISE_VARARGS" => Some(format!("{} {}", instr.opname, instr.arg)),
+        "FOR_ITER" | "GET_ITER" | "YIELD_VALUE" | "LOAD_BUILD_CLASS" | "IMPORT_STAR"
+        | "LIST_APPEND" | "SET_ADD" | "MAP_ADD" => Some(instr.opname.clone()),
+        op if op.starts_with("UNARY_")
+            || op.starts_with("INPLACE_")
+            || op.starts_with("BINARY_")
+            || op.ends_with("_SUBSCR") =>
+        {
+            Some(instr.opname.clone())
+        }
+        _ => None,
+    }
+}
+
+/// The local variables are not compared, because the code generators name the temporary variables differently.
+fn is_name_op(opname: &str) -> bool {
+    matches!(
+        opname,
+        "LOAD_NAME"
+            | "LOAD_GLOBAL"
+            | "LOAD_ATTR"
+            | "LOAD_METHOD"
+            | "STORE_NAME"
+            | "STORE_GLOBAL"
+            | "STORE_ATTR"
+            | "DELETE_NAME"
+            | "DELETE_GLOBAL"
+            | "DELETE_ATTR"
+            | "IMPORT_NAME"
+            | "IMPORT_FROM"
+    )
+}
+
+/// The classes that values are converted into (e.g. `Nat(1)`, `Array([...])`).
+/// The Erg backend converts the values more eagerly than the transpiler,
+/// so the loads of these classes are compared separately (as `conversions`).
+const RUNTIME_CLASSES: [&str; 13] = [
+    "Int", "Nat", "Bool", "Str", "Float", "Bytes", "Array", "Dict", "Set", "tuple", "Range",
+    "IntMut", "NatMut",
+];
+
+/// The normalized code object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeShape {
+    pub name: String,
+    /// `argcount/posonlyargcount/kwonlyargcount`
+    pub signature: String,
+    /// only `SEMANTIC_FLAGS`
+    pub flags: u32,
+    /// the `repr`s of the loaded literals (`None` is excluded, because every code object may return it)
+    pub consts: Vec<String>,
+    /// the (demangled) global names and attributes, without duplicates
+    pub names: Vec<String>,
+    /// the `RUNTIME_CLASSES` loaded, without duplicates
+    pub conversions: Vec<String>,
+    pub ops: Vec<String>,
+}
+
+impl CodeShape {
+    fn new(raw: &RawCode) -> Self {
+        let runtime = runtime_instrs(&raw.instrs);
+        let instrs = raw
+            .instrs
+            .iter()
+            .zip(runtime)
+            .filter_map(|(instr, runtime)| (!runtime).then_some(instr))
+            .collect::<Vec<_>>();
+        let mut consts = vec![];
+        let mut names = vec![];
+        let mut conversions = vec![];
+        let mut ops = vec![];
+        for (i, instr) in instrs.iter().enumerate() {
+            // `__module__ = __name__` and `__qualname__ = "C"` in class bodies
+            let is_class_attr =
+                |instr: &Instr| instr.opname == "STORE_NAME" && is_class_attr(&instr.arg);
+            if is_class_attr(instr) || instrs.get(i + 1).is_some_and(|next| is_class_attr(next)) {
+                continue;
+            }
+            let is_import_operand = instrs
+                .get(i + 1)
+                .is_some_and(|next| next.opname == "IMPORT_NAME")
+                || instrs
+                    .get(i + 2)
+                    .is_some_and(|next| next.opname == "IMPORT_NAME");
+            if instr.opname == "LOAD_CONST" {
+                if instr.arg != "None" && instr.arg != "<code>" && !is_import_operand {
+                    consts.push(normalize_const(&instr.arg));
+                }
+            } else if is_name_op(&instr.opname) {
+                let name = normalize_name(&instr.arg);
+                // the temporary variables of the code generators
+                if name.starts_with('%') || is_synthesized(&name) {
+                    continue;
+                } else if instr.opname.starts_with("LOAD_") && RUNTIME_CLASSES.contains(&&name[..])
+                {
+                    conversions.push(name);
+                } else {
+                    names.push(name);
+                }
+            } else if let Some(op) = operation(instr) {
+                ops.push(op);
+            }
+        }
+        consts.sort();
+        names.sort();
+        names.dedup();
+        conversions.sort();
+        conversions.dedup();
+        ops.sort();
+        Self {
+            name: normalize_name(&raw.name),
+            signature: raw.signature.clone(),
+            flags: raw.flags & SEMANTIC_FLAGS,
+            consts,
+            names,
+            conversions,
+            ops,
+        }
+    }
+}
+
+/// A difference between the code objects compiled by Erg and by CPython.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// the path of the code object (e.g. `<module>.f.<lambda>`)
+    pub code: String,
+    /// `signature`, `flags`, `consts`, `names`, `conversions`, `ops` or `code object`
+    pub field: &'static str,
+    /// the value (or the elements only found) in the code object compiled by Erg
+    pub erg: String,
+    /// the value (or the elements only found) in the code object compiled by CPython
+    pub python: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} differ (Erg: {}, CPython: {})",
+            self.code, self.field, self.erg, self.python
+        )
+    }
+}
+
+/// Returns the elements only found in `lhs`, and those only found in `rhs` (`lhs` and `rhs` are sorted).
+fn multiset_diff(lhs: &[String], rhs: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut counts = BTreeMap::<&String, isize>::new();
+    for l in lhs.iter() {
+        *counts.entry(l).or_default() += 1;
+    }
+    for r in rhs.iter() {
+        *counts.entry(r).or_default() -= 1;
+    }
+    let mut only_lhs = vec![];
+    let mut only_rhs = vec![];
+    for (elem, count) in counts {
+        let only = if count > 0 {
+            &mut only_lhs
+        } else {
+            &mut only_rhs
+        };
+        only.extend(std::iter::repeat_n(elem.clone(), count.unsigned_abs()));
+    }
+    (only_lhs, only_rhs)
+}
+
+/// The code objects that only implement an expression
+/// (the comprehensions, and the temporary functions of the transpiler for `if` and `match`).
+fn is_synthesized(name: &str) -> bool {
+    matches!(
+        name,
+        "<listcomp>" | "<setcomp>" | "<dictcomp>" | "<genexpr>"
+    ) || name.contains("_tmp_func_")
+}
+
+/// Erg compiles the expressions of `is_synthesized` code objects inline, so they are merged into the enclosing code object.
+fn inline_synthesized(code: &RawCode) -> RawCode {
+    let mut inlined = RawCode {
+        name: code.name.clone(),
+        signature: code.signature.clone(),
+        flags: code.flags,
+        instrs: code.instrs.clone(),
+        inner: vec![],
+    };
+    for inner in code.inner.iter() {
+        let inner = inline_synthesized(inner);
+        if is_synthesized(&inner.name) {
+            inlined.instrs.extend(inner.instrs);
+            inlined.inner.extend(inner.inner);
+        } else {
+            inlined.inner.push(inner);
+        }
+    }
+    inlined
+}
+
+fn compare_code(path: &str, erg: &RawCode, python: &RawCode, divergences: &mut Vec<Divergence>) {
+    let (erg_shape, py_shape) = (CodeShape::new(erg), CodeShape::new(python));
+    let diverge = |divergences: &mut Vec<Divergence>, field, erg: String, python: String| {
+        divergences.push(Divergence {
+            code: path.to_string(),
+            field,
+            erg,
+            python,
+        });
+    };
+    if erg_shape.signature != py_shape.signature {
+        diverge(
+            divergences,
+            "signature",
+            erg_shape.signature,
+            py_shape.signature,
+        );
+    }
+    if erg_shape.flags != py_shape.flags {
+        diverge(
+            divergences,
+            "flags",
+            format!("{:#x}", erg_shape.flags),
+            format!("{:#x}", py_shape.flags),
+        );
+    }
+    for (field, erg, python) in [
+        ("consts", &erg_shape.consts, &py_shape.consts),
+        ("names", &erg_shape.names, &py_shape.names),
+        ("conversions", &erg_shape.conversions, &py_shape.conversions),
+        ("ops", &erg_shape.ops, &py_shape.ops),
+    ] {
+        let (only_erg, only_py) = multiset_diff(erg, python);
+        if !only_erg.is_empty() || !only_py.is_empty() {
+            diverge(
+                divergences,
+                field,
+                format!("{only_erg:?}"),
+                format!("{only_py:?}"),
+            );
+        }
+    }
+    // the inner code objects are matched by the name (in order)
+    let mut py_inner = python.inner.iter().collect::<Vec<_>>();
+    for erg_inner in erg.inner.iter() {
+        let name = normalize_name(&erg_inner.name);
+        let inner_path = format!("{path}.{name}");
+        if let Some(pos) = py_inner
+            .iter()
+            .position(|py| normalize_name(&py.name) == name)
+        {
+            let py = py_inner.remove(pos);
+            compare_code(&inner_path, erg_inner, py, divergences);
+        } else {
+            diverge(divergences, "code object", inner_path, "-".into());
+        }
+    }
+    for py in py_inner {
+        let inner_path = format!("{path}.{}", normalize_name(&py.name));
+        diverge(divergences, "code object", "-".into(), inner_path);
+    }
+}
+
+/// Compiles `path` with the Erg backend and with CPython (from the transpiled script), and compares the code objects.
+/// Returns the divergences, or an error if the file cannot be compiled.
+pub fn compare_file(cfg: &ErgConfig, path: &Path) -> Result<Vec<Divergence>, String> {
+    // the transpiler does not optimize the code
+    let mut cfg = cfg.copy();
+    cfg.opt_level = 0;
+    cfg.input = Input::file(path.to_path_buf());
+    let src = fs::read_to_string(path).map_err(|err| format!("cannot read: {err}"))?;
+    let (code, script) = panic::catch_unwind(AssertUnwindSafe(|| {
+        let (code, _hir) = compile_file(&cfg, path)?;
+        let mut transpiler = Transpiler::new(cfg.copy());
+        let script = transpiler.transpile(src, "exec").map_err(|eart| {
+            eart.errors.write_all_stderr();
+            format!("{} transpile error(s)", eart.errors.len())
+        })?;
+        Ok::<_, String>((code, script.object))
+    }))
+    .map_err(|payload| format!("the compiler panicked: {}", panic_message(payload)))??;
+    // the runtime is defined in the prelude of the script, and is not compared
+    let script = &script.code[script.prelude_len..];
+    let tmp = std::env::temp_dir().join(format!(
+        "erg_golden_{}_{}",
+        process::id(),
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ));
+    let pyc = tmp.with_extension("pyc");
+    let py = tmp.with_extension("py");
+    let harness = tmp.with_file_name(format!(
+        "{}_harness.py",
+        tmp.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let results = tmp.with_extension("txt");
+    let mut harness_src = String::new();
+    for (name, file) in [("PYC", &pyc), ("PY", &py), ("RESULTS", &results)] {
+        harness_src += &format!("{name} = {}\n", py_str_literal(&file.to_string_lossy()));
+    }
+    harness_src += GOLDEN_HARNESS;
+    let written = code
+        .dump_as_pyc(&pyc, cfg.py_magic_num)
+        .and_then(|_| fs::write(&py, script))
+        .and_then(|_| fs::write(&harness, harness_src));
+    let output = written.map(|_| {
+        let _ = fs::remove_file(&results);
+        exec_pyc(
+            harness.to_string_lossy(),
+            cfg.py_command,
+            &[],
+            Stdio::inherit(),
+        );
+        fs::read_to_string(&results)
+    });
+    for file in [&pyc, &py, &harness, &results] {
+        let _ = fs::remove_file(file);
+    }
+    let output = match output {
+        Ok(Ok(output)) => output,
+        Ok(Err(_)) => return Err("the harness was terminated abnormally".into()),
+        Err(err) => return Err(format!("cannot write a temporary file: {err}")),
+    };
+    let mut rows = output
+        .lines()
+        .map(|line| line.split('\t').map(unescape).collect::<Vec<_>>())
+        .peekable();
+    if let Some([status, error]) = rows.peek().map(|row| &row[..]) {
+        if status == "error" {
+            return Err(error.clone());
+        }
+    }
+    let erg = RawCode::parse(&mut rows)?;
+    let python = RawCode::parse(&mut rows)?;
+    let (erg, python) = (inline_synthesized(&erg), inline_synthesized(&python));
+    let mut divergences = vec![];
+    compare_code(&normalize_name(&erg.name), &erg, &python, &mut divergences);
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("::add_L1"), "add");
+        assert_eq!(normalize_name("::x_L12_C4"), "x");
+        assert_eq!(normalize_name("::#NamedTuple"), "NamedTuple");
+        assert_eq!(normalize_name("add__"), "add");
+        assert_eq!(normalize_name("NamedTuple__"), "NamedTuple");
+        assert_eq!(normalize_name("__init__"), "__init__");
+        assert_eq!(normalize_name("___"), "_");
+        assert_eq!(normalize_name("tmp__percent__1"), "tmp%1");
+        assert_eq!(normalize_name("print__erg_proc__"), "print!");
+        assert_eq!(normalize_name("Nat"), "Nat");
+    }
+
+    #[test]
+    fn test_multiset_diff() {
+        let strs = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let (l, r) = multiset_diff(&strs(&["1", "1", "2"]), &strs(&["1", "3"]));
+        assert_eq!(l, strs(&["1", "2"]));
+        assert_eq!(r, strs(&["3"]));
+    }
+
+    fn code(name: &str, instrs: &[(&str, &str)], inner: Vec<RawCode>) -> RawCode {
+        RawCode {
+            name: name.to_string(),
+            signature: "0/0/0".to_string(),
+            flags: 0,
+            instrs: instrs
+                .iter()
+                .map(|(opname, arg)| Instr {
+                    opname: opname.to_string(),
+                    arg: arg.to_string(),
+                })
+                .collect(),
+            inner,
+        }
+    }
+
+    #[test]
+    fn test_compare_code() {
+        let add = [("LOAD_CONST", "1"), ("LOAD_CONST", "2"), ("BINARY_OP", "+")];
+        let sub = [("LOAD_CONST", "1"), ("LOAD_CONST", "2"), ("BINARY_OP", "-")];
+        let mut divergences = vec![];
+        compare_code(
+            "<module>",
+            &code("<module>", &add, vec![]),
+            &code("<module>", &add, vec![]),
+            &mut divergences,
+        );
+        assert!(divergences.is_empty());
+        compare_code(
+            "<module>",
+            &code("<module>", &add, vec![]),
+            &code("<module>", &sub, vec![]),
+            &mut divergences,
+        );
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].field, "ops");
+        // the comprehensions are compiled inline by Erg
+        let listcomp = code("<listcomp>", &add, vec![]);
+        let python = inline_synthesized(&code("<module>", &[], vec![listcomp]));
+        let mut divergences = vec![];
+        compare_code(
+            "<module>",
+            &code("<module>", &add, vec![]),
+            &python,
+            &mut divergences,
+        );
+        assert!(divergences.is_empty());
+    }
+}
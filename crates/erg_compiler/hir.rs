@@ -1,4 +1,5 @@
 /// defines High-level Intermediate Representation
+use std::borrow::Cow;
 use std::fmt;
 
 use erg_common::consts::ERG_MODE;
@@ -168,6 +169,7 @@ pub struct Args {
     pub pos_args: Vec<PosArg>,
     pub var_args: Option<Box<PosArg>>,
     pub kw_args: Vec<KwArg>,
+    pub kw_var: Option<Box<PosArg>>,
     pub paren: Option<(Token, Token)>,
 }
 
@@ -183,6 +185,10 @@ impl NestedDisplay for Args {
         if !self.kw_args.is_empty() {
             fmt_lines(self.kw_args.iter(), f, level)?;
         }
+        if let Some(kw_var) = &self.kw_var {
+            writeln!(f, "**")?;
+            kw_var.fmt_nest(f, level)?;
+        }
         Ok(())
     }
 }
@@ -207,6 +213,9 @@ impl NoTypeDisplay for Args {
                 .map(|x| x.to_string_notype())
                 .fold("".to_string(), |acc, s| acc + &s + ", ");
         }
+        if let Some(kw_var) = &self.kw_var {
+            s += &format!(", **{}", kw_var.to_string_notype());
+        }
         s
     }
 }
@@ -223,16 +232,22 @@ impl Locational for Args {
                 return loc;
             }
         }
-        match (
-            self.pos_args.first(),
-            self.var_args.as_ref(),
-            self.kw_args.last(),
-        ) {
-            (Some(l), _, Some(r)) => Location::concat(l, r),
-            (Some(l), Some(r), None) => Location::concat(l, r.as_ref()),
-            (Some(l), None, None) => Location::concat(l, self.pos_args.last().unwrap()),
-            (None, Some(l), Some(r)) => Location::concat(l.as_ref(), r),
-            (None, None, Some(r)) => Location::concat(self.kw_args.first().unwrap(), r),
+        let first = self
+            .pos_args
+            .first()
+            .map(|a| a.loc())
+            .or_else(|| self.var_args.as_ref().map(|a| a.loc()))
+            .or_else(|| self.kw_args.first().map(|a| a.loc()))
+            .or_else(|| self.kw_var.as_ref().map(|a| a.loc()));
+        let last = self
+            .kw_var
+            .as_ref()
+            .map(|a| a.loc())
+            .or_else(|| self.kw_args.last().map(|a| a.loc()))
+            .or_else(|| self.var_args.as_ref().map(|a| a.loc()))
+            .or_else(|| self.pos_args.last().map(|a| a.loc()));
+        match (first, last) {
+            (Some(l), Some(r)) => Location::concat(&l, &r),
             _ => Location::Unknown,
         }
     }
@@ -243,12 +258,14 @@ impl Args {
         pos_args: Vec<PosArg>,
         var_args: Option<PosArg>,
         kw_args: Vec<KwArg>,
+        kw_var: Option<PosArg>,
         paren: Option<(Token, Token)>,
     ) -> Self {
         Self {
             pos_args,
             var_args: var_args.map(Box::new),
             kw_args,
+            kw_var: kw_var.map(Box::new),
             paren,
         }
     }
@@ -262,23 +279,28 @@ impl Args {
     }
 
     pub fn pos_only(pos_args: Vec<PosArg>, paren: Option<(Token, Token)>) -> Self {
-        Self::new(pos_args, None, vec![], paren)
+        Self::new(pos_args, None, vec![], None, paren)
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     #[inline]
     pub fn len(&self) -> usize {
         #[allow(clippy::bool_to_int_with_if)]
         let var_argc = if self.var_args.is_none() { 0 } else { 1 };
-        self.pos_args.len() + var_argc + self.kw_args.len()
+        #[allow(clippy::bool_to_int_with_if)]
+        let kw_var_argc = if self.kw_var.is_none() { 0 } else { 1 };
+        self.pos_args.len() + var_argc + self.kw_args.len() + kw_var_argc
     }
 
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.pos_args.is_empty() && self.var_args.is_none() && self.kw_args.is_empty()
+        self.pos_args.is_empty()
+            && self.var_args.is_none()
+            && self.kw_args.is_empty()
+            && self.kw_var.is_none()
     }
 
     #[inline]
@@ -709,19 +731,44 @@ impl ArrayWithLength {
     }
 }
 
-// TODO: generators
+/// `x <- xs, y <- ys, guard1, guard2`
+fn fmt_comprehension_clauses(generators: &[(Identifier, Expr)], guards: &[Expr]) -> String {
+    generators
+        .iter()
+        .map(|(var, iter)| format!("{var} <- {iter}"))
+        .chain(guards.iter().map(|guard| guard.to_string()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn fmt_comprehension_clauses_notype(generators: &[(Identifier, Expr)], guards: &[Expr]) -> String {
+    generators
+        .iter()
+        .map(|(var, iter)| format!("{} <- {}", var.to_string_notype(), iter.to_string_notype()))
+        .chain(guards.iter().map(|guard| guard.to_string_notype()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ArrayComprehension {
     pub l_sqbr: Token,
     pub r_sqbr: Token,
     pub t: Type,
     pub elem: Box<Expr>,
-    pub guard: Box<Expr>,
+    pub generators: Vec<(Identifier, Expr)>,
+    pub guards: Vec<Expr>,
 }
 
 impl NestedDisplay for ArrayComprehension {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        write!(f, "[{} | {}](: {})", self.elem, self.guard, self.t)
+        write!(
+            f,
+            "[{} | {}](: {})",
+            self.elem,
+            fmt_comprehension_clauses(&self.generators, &self.guards),
+            self.t
+        )
     }
 }
 
@@ -730,7 +777,7 @@ impl NoTypeDisplay for ArrayComprehension {
         format!(
             "[{} | {}]",
             self.elem.to_string_notype(),
-            self.guard.to_string_notype()
+            fmt_comprehension_clauses_notype(&self.generators, &self.guards)
         )
     }
 }
@@ -739,6 +786,26 @@ impl_display_from_nested!(ArrayComprehension);
 impl_locational!(ArrayComprehension, l_sqbr, elem, r_sqbr);
 impl_t!(ArrayComprehension);
 
+impl ArrayComprehension {
+    pub fn new(
+        l_sqbr: Token,
+        r_sqbr: Token,
+        t: Type,
+        elem: Expr,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+    ) -> Self {
+        Self {
+            l_sqbr,
+            r_sqbr,
+            t,
+            elem: Box::new(elem),
+            generators,
+            guards,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct NormalArray {
     pub l_sqbr: Token,
@@ -933,20 +1000,24 @@ impl NormalDict {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DictComprehension {
-    pub l_sqbr: Token,
-    pub r_sqbr: Token,
+    pub l_brace: Token,
+    pub r_brace: Token,
     pub t: Type,
     pub key: Box<Expr>,
     pub value: Box<Expr>,
-    pub guard: Box<Expr>,
+    pub generators: Vec<(Identifier, Expr)>,
+    pub guards: Vec<Expr>,
 }
 
 impl NestedDisplay for DictComprehension {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
         write!(
             f,
-            "[{}: {} | {}](: {})",
-            self.key, self.value, self.guard, self.t
+            "{{{}: {} | {}}}(: {})",
+            self.key,
+            self.value,
+            fmt_comprehension_clauses(&self.generators, &self.guards),
+            self.t
         )
     }
 }
@@ -954,18 +1025,40 @@ impl NestedDisplay for DictComprehension {
 impl NoTypeDisplay for DictComprehension {
     fn to_string_notype(&self) -> String {
         format!(
-            "[{}: {} | {}]",
+            "{{{}: {} | {}}}",
             self.key.to_string_notype(),
             self.value.to_string_notype(),
-            self.guard.to_string_notype()
+            fmt_comprehension_clauses_notype(&self.generators, &self.guards)
         )
     }
 }
 
 impl_display_from_nested!(DictComprehension);
-impl_locational!(DictComprehension, l_sqbr, key, r_sqbr);
+impl_locational!(DictComprehension, l_brace, key, r_brace);
 impl_t!(DictComprehension);
 
+impl DictComprehension {
+    pub fn new(
+        l_brace: Token,
+        r_brace: Token,
+        t: Type,
+        key: Expr,
+        value: Expr,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+    ) -> Self {
+        Self {
+            l_brace,
+            r_brace,
+            t,
+            key: Box::new(key),
+            value: Box::new(value),
+            generators,
+            guards,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Dict {
     Normal(NormalDict),
@@ -1065,17 +1158,74 @@ impl SetWithLength {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SetComprehension {
+    pub l_brace: Token,
+    pub r_brace: Token,
+    pub t: Type,
+    pub elem: Box<Expr>,
+    pub generators: Vec<(Identifier, Expr)>,
+    pub guards: Vec<Expr>,
+}
+
+impl NestedDisplay for SetComprehension {
+    fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
+        write!(
+            f,
+            "{{{} | {}}}(: {})",
+            self.elem,
+            fmt_comprehension_clauses(&self.generators, &self.guards),
+            self.t
+        )
+    }
+}
+
+impl NoTypeDisplay for SetComprehension {
+    fn to_string_notype(&self) -> String {
+        format!(
+            "{{{} | {}}}",
+            self.elem.to_string_notype(),
+            fmt_comprehension_clauses_notype(&self.generators, &self.guards)
+        )
+    }
+}
+
+impl_display_from_nested!(SetComprehension);
+impl_locational!(SetComprehension, l_brace, elem, r_brace);
+impl_t!(SetComprehension);
+
+impl SetComprehension {
+    pub fn new(
+        l_brace: Token,
+        r_brace: Token,
+        t: Type,
+        elem: Expr,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+    ) -> Self {
+        Self {
+            l_brace,
+            r_brace,
+            t,
+            elem: Box::new(elem),
+            generators,
+            guards,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Set {
     Normal(NormalSet),
     WithLength(SetWithLength),
+    Comprehension(SetComprehension),
 }
 
-impl_nested_display_for_enum!(Set; Normal, WithLength);
-impl_no_type_display_for_enum!(Set; Normal, WithLength);
-impl_display_for_enum!(Set; Normal, WithLength);
-impl_locational_for_enum!(Set; Normal, WithLength);
-impl_t_for_enum!(Set; Normal, WithLength);
+impl_nested_display_for_enum!(Set; Normal, WithLength, Comprehension);
+impl_no_type_display_for_enum!(Set; Normal, WithLength, Comprehension);
+impl_display_for_enum!(Set; Normal, WithLength, Comprehension);
+impl_locational_for_enum!(Set; Normal, WithLength, Comprehension);
+impl_t_for_enum!(Set; Normal, WithLength, Comprehension);
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RecordAttrs(Vec<Def>);
@@ -1212,20 +1362,33 @@ impl NoTypeDisplay for BinOp {
     }
 }
 
+/// The return type of an operator or a call as a reference.
+/// The return type of a linked type variable is not borrowable (see `Type::return_t`), so use `t()` in that case.
+fn borrowed_or_failure<'t>(return_t: Cow<'t, Type>) -> &'t Type {
+    match return_t {
+        Cow::Borrowed(t) => t,
+        Cow::Owned(_) => Type::FAILURE,
+    }
+}
+
 impl HasType for BinOp {
     #[inline]
     fn ref_t(&self) -> &Type {
-        self.info.t.return_t().unwrap()
+        borrowed_or_failure(self.info.t.return_t().unwrap())
     }
     fn ref_mut_t(&mut self) -> &mut Type {
         self.info.t.mut_return_t().unwrap()
     }
     #[inline]
-    fn lhs_t(&self) -> &Type {
+    fn t(&self) -> Type {
+        self.info.t.return_t().unwrap().into_owned()
+    }
+    #[inline]
+    fn lhs_t(&self) -> Type {
         self.info.t.lhs_t()
     }
     #[inline]
-    fn rhs_t(&self) -> &Type {
+    fn rhs_t(&self) -> Type {
         self.info.t.rhs_t()
     }
     #[inline]
@@ -1262,17 +1425,21 @@ pub struct UnaryOp {
 impl HasType for UnaryOp {
     #[inline]
     fn ref_t(&self) -> &Type {
-        self.info.t.return_t().unwrap()
+        borrowed_or_failure(self.info.t.return_t().unwrap())
     }
     fn ref_mut_t(&mut self) -> &mut Type {
         self.info.t.mut_return_t().unwrap()
     }
     #[inline]
-    fn lhs_t(&self) -> &Type {
-        self.expr.ref_t()
+    fn t(&self) -> Type {
+        self.info.t.return_t().unwrap().into_owned()
+    }
+    #[inline]
+    fn lhs_t(&self) -> Type {
+        self.expr.t()
     }
     #[inline]
-    fn rhs_t(&self) -> &Type {
+    fn rhs_t(&self) -> Type {
         panic!("invalid operation")
     }
     #[inline]
@@ -1299,7 +1466,16 @@ impl NoTypeDisplay for UnaryOp {
 }
 
 impl_display_from_nested!(UnaryOp);
-impl_locational!(UnaryOp, op, expr);
+impl Locational for UnaryOp {
+    fn loc(&self) -> Location {
+        // `?` is a postfix operator
+        if self.op.is(TokenKind::Try) {
+            Location::concat(self.expr.as_ref(), &self.op)
+        } else {
+            Location::concat(&self.op, self.expr.as_ref())
+        }
+    }
+}
 
 impl UnaryOp {
     pub fn new(op: Token, expr: Expr, info: VarInfo) -> Self {
@@ -1346,11 +1522,7 @@ impl_display_from_nested!(Call);
 impl HasType for Call {
     #[inline]
     fn ref_t(&self) -> &Type {
-        if let Some(attr) = self.attr_name.as_ref() {
-            attr.ref_t().return_t().unwrap_or(Type::FAILURE)
-        } else {
-            self.obj.ref_t().return_t().unwrap_or(Type::FAILURE)
-        }
+        self.return_t().map_or(Type::FAILURE, borrowed_or_failure)
     }
     #[inline]
     fn ref_mut_t(&mut self) -> &mut Type {
@@ -1361,7 +1533,11 @@ impl HasType for Call {
         }
     }
     #[inline]
-    fn lhs_t(&self) -> &Type {
+    fn t(&self) -> Type {
+        self.return_t().map_or(Type::Failure, Cow::into_owned)
+    }
+    #[inline]
+    fn lhs_t(&self) -> Type {
         if let Some(attr) = self.attr_name.as_ref() {
             attr.ref_t().lhs_t()
         } else {
@@ -1369,7 +1545,7 @@ impl HasType for Call {
         }
     }
     #[inline]
-    fn rhs_t(&self) -> &Type {
+    fn rhs_t(&self) -> Type {
         if let Some(attr) = self.attr_name.as_ref() {
             attr.ref_t().rhs_t()
         } else {
@@ -1442,7 +1618,7 @@ impl Call {
         })
     }
 
-    pub fn return_t(&self) -> Option<&Type> {
+    pub fn return_t(&self) -> Option<Cow<'_, Type>> {
         if let Some(attr) = self.attr_name.as_ref() {
             attr.ref_t().return_t()
         } else {
@@ -1712,6 +1888,7 @@ pub struct Params {
     pub non_defaults: Vec<NonDefaultParamSignature>,
     pub var_params: Option<Box<NonDefaultParamSignature>>,
     pub defaults: Vec<DefaultParamSignature>,
+    pub kw_var_params: Option<Box<NonDefaultParamSignature>>,
     pub parens: Option<(Token, Token)>,
 }
 
@@ -1719,10 +1896,11 @@ impl fmt::Display for Params {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "({}, {}, {})",
+            "({}, {}, {}{})",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
-            fmt_vec(&self.defaults)
+            fmt_vec(&self.defaults),
+            fmt_option!(pre ", **", &self.kw_var_params),
         )
     }
 }
@@ -1730,13 +1908,14 @@ impl fmt::Display for Params {
 impl NoTypeDisplay for Params {
     fn to_string_notype(&self) -> String {
         format!(
-            "({}, {}, {})",
+            "({}, {}, {}{})",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
             self.defaults
                 .iter()
                 .map(|p| p.to_string_notype())
-                .fold("".to_string(), |acc, e| acc + &e + ", ")
+                .fold("".to_string(), |acc, e| acc + &e + ", "),
+            fmt_option!(pre ", **", &self.kw_var_params),
         )
     }
 }
@@ -1749,17 +1928,22 @@ impl Locational for Params {
                 return loc;
             }
         }
-        match (
-            self.non_defaults.first(),
-            self.var_params.as_ref(),
-            self.defaults.last(),
-        ) {
-            (Some(l), _, Some(r)) => Location::concat(l, r),
-            (Some(l), Some(r), None) => Location::concat(l, r.as_ref()),
-            (None, Some(l), Some(r)) => Location::concat(l.as_ref(), r),
-            (Some(l), None, None) => Location::concat(l, self.non_defaults.last().unwrap()),
-            (None, Some(var), None) => var.loc(),
-            (None, None, Some(r)) => Location::concat(self.defaults.first().unwrap(), r),
+        let first = self
+            .non_defaults
+            .first()
+            .map(|p| p.loc())
+            .or_else(|| self.var_params.as_ref().map(|p| p.loc()))
+            .or_else(|| self.defaults.first().map(|p| p.loc()))
+            .or_else(|| self.kw_var_params.as_ref().map(|p| p.loc()));
+        let last = self
+            .kw_var_params
+            .as_ref()
+            .map(|p| p.loc())
+            .or_else(|| self.defaults.last().map(|p| p.loc()))
+            .or_else(|| self.var_params.as_ref().map(|p| p.loc()))
+            .or_else(|| self.non_defaults.last().map(|p| p.loc()));
+        match (first, last) {
+            (Some(l), Some(r)) => Location::concat(&l, &r),
             _ => Location::Unknown,
         }
     }
@@ -1769,6 +1953,7 @@ type RawParams = (
     Vec<NonDefaultParamSignature>,
     Option<Box<NonDefaultParamSignature>>,
     Vec<DefaultParamSignature>,
+    Option<Box<NonDefaultParamSignature>>,
     Option<(Token, Token)>,
 );
 
@@ -1776,6 +1961,7 @@ type RefRawParams<'a> = (
     &'a Vec<NonDefaultParamSignature>,
     &'a Option<Box<NonDefaultParamSignature>>,
     &'a Vec<DefaultParamSignature>,
+    &'a Option<Box<NonDefaultParamSignature>>,
     &'a Option<(Token, Token)>,
 );
 
@@ -1784,22 +1970,24 @@ impl Params {
         non_defaults: Vec<NonDefaultParamSignature>,
         var_params: Option<Box<NonDefaultParamSignature>>,
         defaults: Vec<DefaultParamSignature>,
+        kw_var_params: Option<Box<NonDefaultParamSignature>>,
         parens: Option<(Token, Token)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params,
             defaults,
+            kw_var_params,
             parens,
         }
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     pub fn single(sig: NonDefaultParamSignature) -> Self {
-        Self::new(vec![sig], None, vec![], None)
+        Self::new(vec![sig], None, vec![], None, None)
     }
 
     pub const fn ref_deconstruct(&self) -> RefRawParams {
@@ -1807,6 +1995,7 @@ impl Params {
             &self.non_defaults,
             &self.var_params,
             &self.defaults,
+            &self.kw_var_params,
             &self.parens,
         )
     }
@@ -1816,6 +2005,7 @@ impl Params {
             self.non_defaults,
             self.var_params,
             self.defaults,
+            self.kw_var_params,
             self.parens,
         )
     }
@@ -1833,6 +2023,8 @@ impl Params {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SubrSignature {
+    /// runtime decorators (compile-time decorators are recorded in `VarInfo::comptime_decos`)
+    pub decorators: Vec<Expr>,
     pub ident: Identifier,
     pub bounds: TypeBoundSpecs,
     pub params: Params,
@@ -1891,6 +2083,7 @@ impl SubrSignature {
         return_t_spec: Option<TypeSpecWithOp>,
     ) -> Self {
         Self {
+            decorators: Vec::new(),
             ident,
             bounds,
             params,
@@ -1898,6 +2091,11 @@ impl SubrSignature {
         }
     }
 
+    pub fn with_decorators(mut self, decorators: Vec<Expr>) -> Self {
+        self.decorators = decorators;
+        self
+    }
+
     pub fn is_procedural(&self) -> bool {
         self.ident.is_procedural()
     }
@@ -1911,6 +2109,8 @@ impl SubrSignature {
 pub struct Lambda {
     pub params: Params,
     pub op: Token,
+    /// `(x | x > 0) -> ...` (match arms only)
+    pub guard: Option<Block>,
     pub body: Block,
     pub id: usize,
     pub t: Type,
@@ -1918,19 +2118,40 @@ pub struct Lambda {
 
 impl NestedDisplay for Lambda {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
-        writeln!(f, "{} {} (: {})", self.params, self.op.content, self.t)?;
+        if let Some(guard) = &self.guard {
+            writeln!(
+                f,
+                "({} | {}) {} (: {})",
+                self.params,
+                guard.to_string_notype(),
+                self.op.content,
+                self.t
+            )?;
+        } else {
+            writeln!(f, "{} {} (: {})", self.params, self.op.content, self.t)?;
+        }
         self.body.fmt_nest(f, level + 1)
     }
 }
 
 impl NoTypeDisplay for Lambda {
     fn to_string_notype(&self) -> String {
-        format!(
-            "{} {} {}",
-            self.params.to_string_notype(),
-            self.op.content,
-            self.body.to_string_notype()
-        )
+        if let Some(guard) = &self.guard {
+            format!(
+                "({} | {}) {} {}",
+                self.params.to_string_notype(),
+                guard.to_string_notype(),
+                self.op.content,
+                self.body.to_string_notype()
+            )
+        } else {
+            format!(
+                "{} {} {}",
+                self.params.to_string_notype(),
+                self.op.content,
+                self.body.to_string_notype()
+            )
+        }
     }
 }
 
@@ -1944,11 +2165,17 @@ impl Lambda {
             id,
             params,
             op,
+            guard: None,
             body,
             t,
         }
     }
 
+    pub fn with_guard(mut self, guard: Option<Block>) -> Self {
+        self.guard = guard;
+        self
+    }
+
     pub fn is_procedural(&self) -> bool {
         self.op.is(TokenKind::ProcArrow)
     }
@@ -2072,6 +2299,7 @@ impl DefBody {
 pub struct Def {
     pub sig: Signature,
     pub body: DefBody,
+    pub doc: Option<ast::DocComment>,
 }
 
 impl NestedDisplay for Def {
@@ -2116,7 +2344,16 @@ impl HasType for Def {
 
 impl Def {
     pub const fn new(sig: Signature, body: DefBody) -> Self {
-        Self { sig, body }
+        Self {
+            sig,
+            body,
+            doc: None,
+        }
+    }
+
+    pub fn with_doc(mut self, doc: Option<ast::DocComment>) -> Self {
+        self.doc = doc;
+        self
     }
 
     pub fn def_kind(&self) -> DefKind {
@@ -2212,6 +2449,7 @@ pub struct ClassDef {
     pub need_to_gen_new: bool,
     pub __new__: Type,
     pub methods: Block,
+    pub doc: Option<ast::DocComment>,
 }
 
 impl NestedDisplay for ClassDef {
@@ -2259,6 +2497,7 @@ impl ClassDef {
         need_to_gen_new: bool,
         __new__: Type,
         methods: Block,
+        doc: Option<ast::DocComment>,
     ) -> Self {
         Self {
             obj,
@@ -2267,6 +2506,7 @@ impl ClassDef {
             need_to_gen_new,
             __new__,
             methods,
+            doc,
         }
     }
 }
@@ -2276,6 +2516,7 @@ pub struct PatchDef {
     pub sig: Signature,
     pub base: Box<Expr>,
     pub methods: Block,
+    pub doc: Option<ast::DocComment>,
 }
 
 impl NestedDisplay for PatchDef {
@@ -2322,11 +2563,12 @@ impl HasType for PatchDef {
 }
 
 impl PatchDef {
-    pub fn new(sig: Signature, base: Expr, methods: Block) -> Self {
+    pub fn new(sig: Signature, base: Expr, methods: Block, doc: Option<ast::DocComment>) -> Self {
         Self {
             sig,
             base: Box::new(base),
             methods,
+            doc,
         }
     }
 }
@@ -2686,6 +2928,16 @@ impl Default for HIR {
     }
 }
 
+impl NoTypeDisplay for HIR {
+    fn to_string_notype(&self) -> String {
+        self.module
+            .iter()
+            .map(|chunk| chunk.to_string_notype())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 impl HIR {
     pub const fn new(name: Str, module: Module) -> Self {
         Self { name, module }
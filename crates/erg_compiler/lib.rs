@@ -5,27 +5,47 @@ extern crate erg_common;
 pub extern crate erg_parser;
 
 pub mod artifact;
+pub mod batch;
+pub mod bench;
 pub mod build_hir;
+pub mod check;
 mod compile;
 pub use compile::*;
 mod codegen;
 pub mod context;
+pub mod coverage;
 pub mod declare;
+pub mod definite_assign;
 pub mod desugar_hir;
+pub mod doc;
 pub mod effectcheck;
+pub mod emit;
 pub mod error;
+pub mod expand;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod golden;
 pub mod hir;
 pub mod link_ast;
 pub mod link_hir;
 pub mod lint;
 pub mod lower;
+pub mod minimize;
 pub mod module;
 pub mod optimize;
+pub mod sandbox;
+pub mod stable_id;
 pub mod ownercheck;
+pub mod test_runner;
+#[cfg(any(test, feature = "test_support"))]
+pub mod test_support;
 pub mod transpile;
 pub mod ty;
 pub mod varinfo;
+pub mod visitor;
+pub mod writer;
 
 pub use build_hir::HIRBuilder;
+pub use check::PackageChecker;
 pub use erg_parser::build_ast::ASTBuilder;
 pub use transpile::Transpiler;
@@ -3,6 +3,18 @@ from _erg_range import Range
 
 from collections import namedtuple
 
+def _is_record(y):
+    return isinstance(y, tuple) and hasattr(y, "_fields")
+
+# Int, 1..10, {1, 2}, (Int, Str), [Int, {1}], {.x = Int}, ...
+def _is_type_spec(y):
+    if type(y) == type or isinstance(y, (Range, set)):
+        return True
+    elif isinstance(y, (list, tuple)):
+        return len(y) > 0 and all(map(_is_type_spec, y))
+    else:
+        return False
+
 # (elem in y) == contains_operator(y, elem)
 def contains_operator(y, elem):
     # 1 in Int
@@ -13,23 +25,22 @@ def contains_operator(y, elem):
             return True
         # TODO: trait check
         return False
-    # [1] in [Int]
-    elif isinstance(y, list) and isinstance(elem, list) and (
-        type(y[0]) == type or isinstance(y[0], Range)
-    ):
-        # FIXME:
-        type_check = contains_operator(y[0], elem[0])
-        len_check = len(elem) == len(y)
-        return type_check and len_check
-    # (1, 2) in (Int, Int)
-    elif isinstance(y, tuple) and isinstance(elem, tuple) and (
-        type(y[0]) == type or isinstance(y[0], Range)
-    ):
-        if not hasattr(elem, "__iter__"):
+    # {.x = 1; .y = "a"} in {.x = Int}
+    elif _is_record(y) and _is_type_spec(y):
+        return all(
+            hasattr(elem, field) and contains_operator(getattr(y, field), getattr(elem, field))
+            for field in y._fields
+        )
+    # [1, [2]] in [Int, [Int]]
+    elif isinstance(y, list) and _is_type_spec(y):
+        if not isinstance(elem, list) or len(elem) != len(y):
             return False
-        type_check = all(map(lambda x: contains_operator(x[0], x[1]), zip(y, elem)))
-        len_check = len(elem) == len(y)
-        return type_check and len_check
+        return all(map(lambda x: contains_operator(x[0], x[1]), zip(y, elem)))
+    # (1, (2, "a")) in (Int, (Int, Str))
+    elif isinstance(y, tuple) and _is_type_spec(y):
+        if not isinstance(elem, tuple) or len(elem) != len(y):
+            return False
+        return all(map(lambda x: contains_operator(x[0], x[1]), zip(y, elem)))
     # {1: 2} in {Int: Int}
     elif isinstance(y, dict) and isinstance(elem, dict) and isinstance(next(iter(y.keys())), type):
         # TODO:
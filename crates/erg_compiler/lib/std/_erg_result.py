@@ -19,3 +19,7 @@ class Error:
 
 def is_ok(obj) -> bool:
     return not isinstance(obj, Error)
+
+
+def is_failure(obj) -> bool:
+    return obj is None or isinstance(obj, Error)
@@ -6,7 +6,7 @@ from _erg_range import (
     ClosedRange,
     RangeIterator,
 )
-from _erg_result import Error, is_ok
+from _erg_result import Error, is_ok, is_failure
 from _erg_float import Float, FloatMut
 from _erg_int import Int, IntMut
 from _erg_nat import Nat, NatMut
@@ -18,6 +18,8 @@ from _erg_dict import Dict
 from _erg_set import Set
 from _erg_contains_operator import contains_operator
 from _erg_mutate_operator import mutate_operator
+from _erg_sys import argv
+from _erg_testing import assert_eq
 
 
 class Never:
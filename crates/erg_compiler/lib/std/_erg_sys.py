@@ -0,0 +1,9 @@
+import sys as _sys
+
+from _erg_array import Array
+from _erg_str import Str
+
+
+def argv():
+    # `sys.argv[0]` is the script name
+    return Array(map(Str, _sys.argv))
@@ -0,0 +1,14 @@
+class AssertEqError(AssertionError):
+    # `erg test` shows the diff of `left` and `right`
+    def __init__(self, left, right, msg=None):
+        self.left = left
+        self.right = right
+        message = "assertion `left == right` failed"
+        if msg is not None:
+            message += f": {msg}"
+        super().__init__(f"{message}\n  left: {left!r}\n right: {right!r}")
+
+
+def assert_eq(lhs, rhs, msg=None):
+    if lhs != rhs:
+        raise AssertEqError(lhs, rhs, msg)
@@ -143,7 +143,8 @@ impl ASTLinker {
                     };
                     let attr = Identifier::new(methods.vis.clone(), ident.name);
                     let expr = class.clone().attr_expr(attr);
-                    let decl = TypeAscription::new(expr, decl.t_spec);
+                    let decl =
+                        TypeAscription::new(expr, decl.t_spec).with_decorators(decl.decorators);
                     new.push(Expr::TypeAscription(decl));
                 }
                 ClassAttr::Doc(doc) => {
@@ -1,12 +1,12 @@
-use std::cell::RefCell;
+use std::env::current_dir;
 use std::mem::{replace, take};
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
 
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict as Dic;
 use erg_common::fresh::SharedFreshNameGenerator;
 use erg_common::pathutil::squash;
+use erg_common::shared::Shared;
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{enum_unwrap, log};
@@ -40,8 +40,10 @@ impl Mod {
 pub struct HIRLinker<'a> {
     cfg: &'a ErgConfig,
     mod_cache: &'a SharedModuleCache,
-    removed_mods: Rc<RefCell<Dic<PathBuf, Mod>>>,
+    removed_mods: Shared<Dic<PathBuf, Mod>>,
     fresh_gen: SharedFreshNameGenerator,
+    /// the linked modules are kept in the cache (for the other modules linking them)
+    retains_mods: bool,
 }
 
 impl<'a> HIRLinker<'a> {
@@ -49,17 +51,26 @@ impl<'a> HIRLinker<'a> {
         Self {
             cfg,
             mod_cache,
-            removed_mods: Rc::new(RefCell::new(Dic::new())),
+            removed_mods: Shared::new(Dic::new()),
             fresh_gen: SharedFreshNameGenerator::new("hir_linker"),
+            retains_mods: false,
         }
     }
 
+    /// The linked modules are not removed from the cache, so that they can be linked into other modules
+    /// (e.g. in `erg compile a.er b.er`, see `BatchCompiler`).
+    pub fn retaining_mods(mut self) -> Self {
+        self.retains_mods = true;
+        self
+    }
+
     fn inherit(&self, cfg: &'a ErgConfig) -> Self {
         Self {
             cfg,
             mod_cache: self.mod_cache,
             removed_mods: self.removed_mods.clone(),
             fresh_gen: self.fresh_gen.clone(),
+            retains_mods: self.retains_mods,
         }
     }
 
@@ -132,7 +143,15 @@ impl<'a> HIRLinker<'a> {
                     Self::resolve_pymod_path(&mut arr.elem);
                     Self::resolve_pymod_path(&mut arr.len);
                 }
-                _ => todo!(),
+                Array::Comprehension(arr) => {
+                    for (_, iter) in arr.generators.iter_mut() {
+                        Self::resolve_pymod_path(iter);
+                    }
+                    for guard in arr.guards.iter_mut() {
+                        Self::resolve_pymod_path(guard);
+                    }
+                    Self::resolve_pymod_path(&mut arr.elem);
+                }
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
@@ -151,6 +170,15 @@ impl<'a> HIRLinker<'a> {
                     Self::resolve_pymod_path(&mut st.elem);
                     Self::resolve_pymod_path(&mut st.len);
                 }
+                Set::Comprehension(st) => {
+                    for (_, iter) in st.generators.iter_mut() {
+                        Self::resolve_pymod_path(iter);
+                    }
+                    for guard in st.guards.iter_mut() {
+                        Self::resolve_pymod_path(guard);
+                    }
+                    Self::resolve_pymod_path(&mut st.elem);
+                }
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dic) => {
@@ -159,7 +187,16 @@ impl<'a> HIRLinker<'a> {
                         Self::resolve_pymod_path(&mut elem.value);
                     }
                 }
-                other => todo!("{other}"),
+                Dict::Comprehension(dic) => {
+                    for (_, iter) in dic.generators.iter_mut() {
+                        Self::resolve_pymod_path(iter);
+                    }
+                    for guard in dic.guards.iter_mut() {
+                        Self::resolve_pymod_path(guard);
+                    }
+                    Self::resolve_pymod_path(&mut dic.key);
+                    Self::resolve_pymod_path(&mut dic.value);
+                }
             },
             Expr::Record(record) => {
                 for attr in record.attrs.iter_mut() {
@@ -190,6 +227,9 @@ impl<'a> HIRLinker<'a> {
                 }
             }
             Expr::Lambda(lambda) => {
+                for chunk in lambda.guard.iter_mut().flat_map(|guard| guard.iter_mut()) {
+                    Self::resolve_pymod_path(chunk);
+                }
                 for chunk in lambda.body.iter_mut() {
                     Self::resolve_pymod_path(chunk);
                 }
@@ -254,7 +294,15 @@ impl<'a> HIRLinker<'a> {
                     self.replace_import(&mut arr.elem);
                     self.replace_import(&mut arr.len);
                 }
-                _ => todo!(),
+                Array::Comprehension(arr) => {
+                    for (_, iter) in arr.generators.iter_mut() {
+                        self.replace_import(iter);
+                    }
+                    for guard in arr.guards.iter_mut() {
+                        self.replace_import(guard);
+                    }
+                    self.replace_import(&mut arr.elem);
+                }
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
@@ -273,6 +321,15 @@ impl<'a> HIRLinker<'a> {
                     self.replace_import(&mut st.elem);
                     self.replace_import(&mut st.len);
                 }
+                Set::Comprehension(st) => {
+                    for (_, iter) in st.generators.iter_mut() {
+                        self.replace_import(iter);
+                    }
+                    for guard in st.guards.iter_mut() {
+                        self.replace_import(guard);
+                    }
+                    self.replace_import(&mut st.elem);
+                }
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dic) => {
@@ -281,7 +338,16 @@ impl<'a> HIRLinker<'a> {
                         self.replace_import(&mut elem.value);
                     }
                 }
-                other => todo!("{other}"),
+                Dict::Comprehension(dic) => {
+                    for (_, iter) in dic.generators.iter_mut() {
+                        self.replace_import(iter);
+                    }
+                    for guard in dic.guards.iter_mut() {
+                        self.replace_import(guard);
+                    }
+                    self.replace_import(&mut dic.key);
+                    self.replace_import(&mut dic.value);
+                }
             },
             Expr::Record(record) => {
                 for attr in record.attrs.iter_mut() {
@@ -310,11 +376,14 @@ impl<'a> HIRLinker<'a> {
                         self.replace_import(&mut arg.expr);
                     }
                     if let Some(arg) = call.args.var_args.as_deref_mut() {
-                        self.replace_py_import(&mut arg.expr);
+                        self.replace_import(&mut arg.expr);
                     }
                     for arg in call.args.kw_args.iter_mut() {
                         self.replace_import(&mut arg.expr);
                     }
+                    if let Some(arg) = call.args.kw_var.as_deref_mut() {
+                        self.replace_import(&mut arg.expr);
+                    }
                 }
             },
             Expr::Def(def) => {
@@ -323,6 +392,9 @@ impl<'a> HIRLinker<'a> {
                 }
             }
             Expr::Lambda(lambda) => {
+                for chunk in lambda.guard.iter_mut().flat_map(|guard| guard.iter_mut()) {
+                    self.replace_import(chunk);
+                }
                 for chunk in lambda.body.iter_mut() {
                     self.replace_import(chunk);
                 }
@@ -377,7 +449,7 @@ impl<'a> HIRLinker<'a> {
             unreachable!()
         };
         let path = Path::new(&path[..]);
-        let path = self.cfg.input.resolve_real_path(path).unwrap();
+        let path = self.cfg.resolve_real_path(path).unwrap();
         // # module.er
         // self = import "module"
         // ↓
@@ -393,6 +465,15 @@ impl<'a> HIRLinker<'a> {
             self.mod_cache
                 .get(path.as_path())
                 .and_then(|entry| entry.hir.clone().map(|hir| (hir, entry.cfg().clone())))
+        } else if self.retains_mods {
+            // a module is linked only once (the other imports refer to it)
+            if self.removed_mods.borrow().contains_key(&path) {
+                None
+            } else {
+                self.mod_cache
+                    .get(path.as_path())
+                    .and_then(|entry| entry.hir.clone().map(|hir| (hir, entry.cfg().clone())))
+            }
         } else {
             self.mod_cache
                 .remove(path.as_path())
@@ -455,7 +536,6 @@ impl<'a> HIRLinker<'a> {
         let mut dir = self.cfg.input.dir();
         let mod_path = self
             .cfg
-            .input
             .resolve_decl_path(Path::new(&mod_name_str[..]))
             .unwrap();
         if !mod_path
@@ -465,6 +545,14 @@ impl<'a> HIRLinker<'a> {
         {
             dir = PathBuf::new();
         }
+        // `__import__` searches the module from the current directory
+        // (the files of a package are given as absolute paths)
+        if let Some(rel) = current_dir()
+            .ok()
+            .and_then(|cwd| dir.strip_prefix(cwd).ok().map(PathBuf::from))
+        {
+            dir = rel;
+        }
         let mod_name_str = if let Some(stripped) = mod_name_str.strip_prefix("./") {
             stripped
         } else {
@@ -2,8 +2,13 @@
 //! What is implemented here affects subsequent optimizations,
 //! and `erg_linter` does linting that does not affect optimizations.
 
+use std::path::Path;
+
 #[allow(unused_imports)]
 use erg_common::log;
+use erg_common::pathutil::is_test_file;
+use erg_common::set;
+use erg_common::set::Set;
 use erg_common::traits::{Locational, Runnable, Stream};
 use erg_common::Str;
 use erg_parser::ast::AST;
@@ -12,14 +17,122 @@ use erg_parser::lex::Lexer;
 
 use crate::context::ContextKind;
 use crate::link_ast::ASTLinker;
-use crate::ty::{HasType, Type, ValueObj, VisibilityModifier};
+use crate::ty::constructors::{singleton, v_enum};
+use crate::ty::value::GenTypeObj;
+use crate::ty::{HasType, Predicate, Type, ValueObj, Variable, VisibilityModifier};
 
 use crate::error::{
     CompileErrors, LowerError, LowerResult, LowerWarning, LowerWarnings, SingleLowerResult,
 };
 use crate::hir::{self, Expr, Signature, HIR};
 use crate::lower::ASTLowerer;
-use crate::varinfo::VarInfo;
+use crate::module::RefKind;
+use crate::optimize::HIROptimizer;
+use crate::varinfo::{AbsLocation, VarInfo};
+use crate::visitor::{walk_call, walk_def, Visitor};
+
+/// Tests (benchmarks) are called by `erg test` (`erg bench`), so they are used even if not referenced.
+fn is_test_def(name: &str, vi: &VarInfo, path: &Path) -> bool {
+    vi.comptime_decos
+        .as_ref()
+        .is_some_and(|decos| decos.contains("Test") || decos.contains("Bench"))
+        || (name.starts_with("test_") && is_test_file(path))
+}
+
+/// Counts the calls of the subroutine named `name`.
+struct SelfCallCounter<'n> {
+    name: &'n str,
+    count: usize,
+}
+
+impl<'a> Visitor<'a> for SelfCallCounter<'_> {
+    fn visit_call(&mut self, call: &'a hir::Call) {
+        if call.attr_name.is_none() && call.obj.show_acc().as_deref() == Some(self.name) {
+            self.count += 1;
+        }
+        walk_call(self, call);
+    }
+}
+
+/// Collects the definition locations of the parameters required by traits or base classes
+/// (the parameters of trait implementations and `@Override` methods), which may be unused.
+#[derive(Default)]
+struct RequiredParamCollector {
+    locs: Set<AbsLocation>,
+}
+
+impl<'a> Visitor<'a> for RequiredParamCollector {
+    fn visit_def(&mut self, def: &'a hir::Def) {
+        if let Signature::Subr(subr) = &def.sig {
+            let vi = &subr.ident.vi;
+            let is_override = vi
+                .comptime_decos
+                .as_ref()
+                .is_some_and(|decos| decos.contains("Override"));
+            if vi.impl_of.is_some() || is_override {
+                let (non_defaults, var_params, defaults, kw_var_params, _) =
+                    subr.params.ref_deconstruct();
+                let params = non_defaults
+                    .iter()
+                    .chain(var_params.as_deref())
+                    .chain(defaults.iter().map(|default| &default.sig))
+                    .chain(kw_var_params.as_deref());
+                for param in params {
+                    self.locs.insert(param.vi.def_loc.clone());
+                }
+            }
+        }
+        walk_def(self, def);
+    }
+}
+
+/// Collects `if` chains (`if c1, do: ..., do: if c2, do: ...`) whose last link has no else branch.
+struct IfChainCollector<'a> {
+    chains: Vec<Vec<&'a hir::Call>>,
+}
+
+impl<'a> IfChainCollector<'a> {
+    fn is_if(call: &hir::Call) -> bool {
+        call.attr_name.is_none()
+            && matches!(call.obj.show_acc().as_deref(), Some("if" | "if!"))
+            && call.args.pos_args.len() >= 2
+    }
+
+    /// `do: if ...` -> `if ...`
+    fn next_link(call: &'a hir::Call) -> Option<&'a hir::Call> {
+        let Expr::Lambda(lambda) = &call.args.pos_args.get(2)?.expr else {
+            return None;
+        };
+        match lambda.body.first() {
+            Some(Expr::Call(next)) if lambda.body.len() == 1 && Self::is_if(next) => Some(next),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for IfChainCollector<'a> {
+    fn visit_call(&mut self, call: &'a hir::Call) {
+        if !Self::is_if(call) {
+            walk_call(self, call);
+            return;
+        }
+        let mut chain = vec![call];
+        let mut last = call;
+        while let Some(next) = Self::next_link(last) {
+            chain.push(next);
+            last = next;
+        }
+        for link in chain.iter() {
+            self.visit_expr(&link.args.pos_args[0].expr);
+            self.visit_expr(&link.args.pos_args[1].expr);
+        }
+        if let Some(els) = last.args.pos_args.get(2) {
+            self.visit_expr(&els.expr);
+        } else if chain.len() >= 2 {
+            self.chains.push(chain);
+        }
+    }
+}
 
 impl ASTLowerer {
     pub(crate) fn var_result_t_check(
@@ -50,6 +163,61 @@ impl ASTLowerer {
             })
     }
 
+    /// Checks the value of a constant expression, which is more precise than its type
+    /// (e.g. `x: {I: Int | I < 256} = 200 + 100`, the type of `200 + 100` is `Nat`).
+    pub(crate) fn folded_value_check(
+        &self,
+        loc: &impl Locational,
+        name: &Str,
+        expect: &Type,
+        value: &ValueObj,
+    ) -> SingleLowerResult<()> {
+        let found = v_enum(set! { value.clone() });
+        if self.module.context.subtype_of(&found, expect) {
+            Ok(())
+        } else if !self.module.context.subtype_of(expect, &Type::Float) {
+            // not a range error (e.g. `x: Str or NoneType = 1`)
+            self.var_result_t_check(loc, name, expect, &found)
+        } else {
+            Err(LowerError::out_of_range_error(
+                self.cfg().input.clone(),
+                line!() as usize,
+                loc.loc(),
+                self.module.context.caused_by(),
+                name,
+                expect,
+                value,
+            ))
+        }
+    }
+
+    /// Warns if the divisor of `/` is not provably non-zero.
+    /// `folded` is the value of the divisor if it is a constant expression.
+    /// Divisors whose types are narrowed by a dominating check (e.g. `if y != 0:`) are also regarded as non-zero.
+    pub(crate) fn divisor_check(&mut self, divisor: &hir::Expr, folded: Option<&ValueObj>) {
+        let ctx = &self.module.context;
+        let divisor_t = divisor.ref_t();
+        let non_zero = match folded {
+            Some(value) => !value.is_zero(),
+            None => !ctx.subtype_of(&v_enum(set! { ValueObj::Nat(0) }), divisor_t),
+        };
+        // only numeric divisions are checked (`__div__` may be overloaded)
+        if non_zero
+            || divisor_t.has_unbound_var()
+            || divisor_t.is_failure()
+            || !ctx.subtype_of(divisor_t, &Type::Float)
+        {
+            return;
+        }
+        self.warns.push(LowerWarning::zero_division_warning(
+            self.cfg().input.clone(),
+            line!() as usize,
+            divisor.loc(),
+            ctx.caused_by(),
+            divisor_t,
+        ));
+    }
+
     pub(crate) fn warn_unused_expr(&mut self, module: &hir::Module, mode: &str) {
         if mode == "eval" {
             return;
@@ -150,6 +318,11 @@ impl ASTLowerer {
                         warns.extend(ws);
                     }
                 }
+                if let Some(kw_var) = &call.args.kw_var {
+                    if let Err(ws) = self.block_use_check(&kw_var.expr) {
+                        warns.extend(ws);
+                    }
+                }
             }
             // TODO: unary, binary, array, ...
             _ => {}
@@ -161,16 +334,47 @@ impl ASTLowerer {
         }
     }
 
+    /// Reports the use of an API which is not available in `--target-version` (`@Available python:="3.10"`).
+    pub(crate) fn availability_check<L: Locational>(&mut self, name: &Str, vi: &VarInfo, loc: &L) {
+        let (Some(target), Some(required)) = (&self.cfg.target_version, &vi.available_since) else {
+            return;
+        };
+        if !required.le(target) {
+            self.errs.push(LowerError::unavailable_api_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc.loc(),
+                self.module.context.caused_by(),
+                name,
+                required,
+                target,
+            ));
+        }
+    }
+
     pub(crate) fn inc_ref<L: Locational>(&self, name: &Str, vi: &VarInfo, loc: &L) {
         self.module
             .context
             .inc_ref(name, vi, loc, &self.module.context);
     }
 
-    pub(crate) fn warn_unused_local_vars(&mut self, mode: &str) {
+    /// Re-tags the use of `ident` (which has already been lowered) as `kind`.
+    pub(crate) fn tag_ref(&self, ident: &hir::Identifier, kind: RefKind) {
+        self.module.context.inc_ref_as(
+            ident.inspect(),
+            &ident.vi,
+            &ident.raw.name,
+            &self.module.context,
+            kind,
+        );
+    }
+
+    pub(crate) fn warn_unused_local_vars(&mut self, hir: &HIR, mode: &str) {
         if mode == "eval" {
             return;
         }
+        let mut required_params = RequiredParamCollector::default();
+        required_params.visit_module(&hir.module);
         let self_path = self.module.context.module_path();
         for (referee, value) in self.module.context.index().members().iter() {
             if referee.module.as_deref() != Some(self_path) {
@@ -178,23 +382,145 @@ impl ASTLowerer {
             }
             let name_is_auto = &value.name[..] == "_"
                 || !Lexer::is_valid_start_symbol_ch(value.name.chars().next().unwrap_or(' '));
-            if value.referrers.is_empty() && value.vi.vis.is_private() && !name_is_auto {
+            let is_test = is_test_def(&value.name, &value.vi, self_path);
+            // `self` and `_x` are unused intentionally, and some parameters are required by traits
+            let is_exempt_param = value.vi.kind.is_parameter()
+                && (&value.name[..] == "self"
+                    || value.name.starts_with('_')
+                    || required_params.locs.contains(referee));
+            if value.referrers.is_empty()
+                && value.vi.vis.is_private()
+                && !name_is_auto
+                && !is_test
+                && !is_exempt_param
+            {
                 let input = referee
                     .module
                     .as_ref()
                     .map_or(self.input().clone(), |path| path.as_path().into());
-                let warn = LowerWarning::unused_warning(
-                    input,
-                    line!() as usize,
-                    referee.loc,
-                    &value.name,
-                    self.module.context.caused_by(),
-                );
+                let warn = if value.vi.kind.is_parameter() {
+                    LowerWarning::unused_param_warning(
+                        input,
+                        line!() as usize,
+                        referee.loc,
+                        &value.name,
+                        self.module.context.caused_by(),
+                    )
+                } else if value.vi.kind.is_instance_attr() {
+                    LowerWarning::unused_field_warning(
+                        input,
+                        line!() as usize,
+                        referee.loc,
+                        &value.name,
+                        self.module.context.caused_by(),
+                    )
+                } else {
+                    LowerWarning::unused_warning(
+                        input,
+                        line!() as usize,
+                        referee.loc,
+                        &value.name,
+                        self.module.context.caused_by(),
+                    )
+                };
                 self.warns.push(warn);
             }
         }
     }
 
+    /// Warns about private top-level definitions that are referenced,
+    /// but only from definitions that are themselves unreachable.
+    /// Public definitions, tests and top-level statements are the roots.
+    /// Definitions that are never referenced at all are reported by `warn_unused_local_vars`.
+    pub(crate) fn warn_dead_private_defs(&mut self, hir: &HIR, mode: &str) {
+        if mode == "eval" {
+            return;
+        }
+        let self_path = self.module.context.module_path();
+        let defs = hir
+            .module
+            .iter()
+            .filter_map(|chunk| match chunk {
+                Expr::Def(def) => Some((def.sig.ident(), def.loc(), false)),
+                // subclasses may be instantiated reflectively (e.g. `unittest.TestCase`)
+                Expr::ClassDef(class_def) => Some((
+                    class_def.sig.ident(),
+                    class_def.loc(),
+                    matches!(class_def.obj, GenTypeObj::Subclass(_)),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let index = self.module.context.index();
+        // `None`: referenced from a top-level statement or another module
+        let referrers = defs
+            .iter()
+            .map(|(ident, _, _)| {
+                let Some(value) = index.get_refs(&ident.vi.def_loc) else {
+                    return Some(vec![]);
+                };
+                let mut owners = vec![];
+                for referrer in value.referrers.iter() {
+                    if referrer.module.as_deref() != Some(self_path) {
+                        return None;
+                    }
+                    let owner = defs
+                        .iter()
+                        .position(|(_, span, _)| span.contains(referrer.loc))?;
+                    owners.push(owner);
+                }
+                Some(owners)
+            })
+            .collect::<Vec<_>>();
+        let mut alive = defs
+            .iter()
+            .zip(referrers.iter())
+            .map(|((ident, _, is_root), owners)| {
+                // definitions generated by macro expansion (`name%N`) have the locations of the macro body
+                *is_root
+                    || owners.is_none()
+                    || ident.vi.vis.is_public()
+                    || ident.inspect().contains('%')
+                    || is_test_def(ident.inspect(), &ident.vi, self_path)
+            })
+            .collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, owners) in referrers.iter().enumerate() {
+                if alive[i] {
+                    continue;
+                }
+                let owners = owners.as_deref().unwrap_or_default();
+                if owners.iter().any(|&owner| owner != i && alive[owner]) {
+                    alive[i] = true;
+                    changed = true;
+                }
+            }
+        }
+        for (((ident, span, _), owners), alive) in defs.iter().zip(referrers).zip(alive) {
+            let name = ident.inspect();
+            let name_is_auto = &name[..] == "_"
+                || !Lexer::is_valid_start_symbol_ch(name.chars().next().unwrap_or(' '));
+            // unreferenced definitions are already reported as unused
+            if alive || name_is_auto || owners.is_none_or(|owners| owners.is_empty()) {
+                continue;
+            }
+            let lines = span
+                .ln_end()
+                .zip(span.ln_begin())
+                .map_or(1, |(end, begin)| end - begin + 1);
+            self.warns.push(LowerWarning::dead_def_warning(
+                self.input().clone(),
+                line!() as usize,
+                ident.loc(),
+                name,
+                lines,
+                self.module.context.caused_by(),
+            ));
+        }
+    }
+
     pub(crate) fn check_doc_comments(&mut self, hir: &HIR) {
         for chunk in hir.module.iter() {
             self.check_doc_comment(chunk);
@@ -204,37 +530,26 @@ impl ASTLowerer {
     fn check_doc_comment(&mut self, chunk: &Expr) {
         match chunk {
             Expr::Lit(lit) if lit.is_doc_comment() => {
-                let first_line = lit.ln_begin().unwrap_or(1);
                 let ValueObj::Str(content) = &lit.value else { return; };
-                if content.starts_with("erg\n") {
-                    let code = content.trim_start_matches("erg\n");
-                    let indent = code.chars().take_while(|c| c.is_whitespace()).count();
-                    let code = if indent > 0 {
-                        format!(
-                            "{}_ =\n{code}\n{}None",
-                            "\n".repeat(first_line as usize - 1),
-                            " ".repeat(indent)
-                        )
-                    } else {
-                        format!("{}{code}", "\n".repeat(first_line as usize))
-                    };
-                    match ASTBuilder::new(self.cfg().clone()).build(code) {
-                        Ok(artifact) => {
-                            self.check_doc_ast(artifact.ast);
-                        }
-                        Err(iart) => {
-                            self.errs.extend(CompileErrors::from(iart.errors));
-                            self.warns.extend(CompileErrors::from(iart.warns));
-                        }
-                    }
+                self.check_doc_code(lit.ln_begin().unwrap_or(1), content);
+            }
+            Expr::Def(def) => {
+                for lit in def.doc.iter().flat_map(|doc| doc.lits.iter()) {
+                    self.check_doc_code(lit.ln_begin().unwrap_or(1), &lit.token.content);
                 }
             }
             Expr::ClassDef(class_def) => {
+                for lit in class_def.doc.iter().flat_map(|doc| doc.lits.iter()) {
+                    self.check_doc_code(lit.ln_begin().unwrap_or(1), &lit.token.content);
+                }
                 for chunk in class_def.methods.iter() {
                     self.check_doc_comment(chunk);
                 }
             }
             Expr::PatchDef(patch_def) => {
+                for lit in patch_def.doc.iter().flat_map(|doc| doc.lits.iter()) {
+                    self.check_doc_code(lit.ln_begin().unwrap_or(1), &lit.token.content);
+                }
                 for chunk in patch_def.methods.iter() {
                     self.check_doc_comment(chunk);
                 }
@@ -243,6 +558,32 @@ impl ASTLowerer {
         }
     }
 
+    /// Checks the code examples in a doc comment (`erg\n...`)
+    fn check_doc_code(&mut self, first_line: u32, content: &str) {
+        if content.starts_with("erg\n") {
+            let code = content.trim_start_matches("erg\n");
+            let indent = code.chars().take_while(|c| c.is_whitespace()).count();
+            let code = if indent > 0 {
+                format!(
+                    "{}_ =\n{code}\n{}None",
+                    "\n".repeat(first_line as usize - 1),
+                    " ".repeat(indent)
+                )
+            } else {
+                format!("{}{code}", "\n".repeat(first_line as usize))
+            };
+            match ASTBuilder::new(self.cfg().clone()).build(code) {
+                Ok(artifact) => {
+                    self.check_doc_ast(artifact.ast);
+                }
+                Err(iart) => {
+                    self.errs.extend(CompileErrors::from(iart.errors));
+                    self.warns.extend(CompileErrors::from(iart.warns));
+                }
+            }
+        }
+    }
+
     fn check_doc_ast(&mut self, ast: AST) {
         let Ok(ast) = ASTLinker::new(self.cfg().clone()).link(ast, "exec") else {
             return;
@@ -286,6 +627,140 @@ impl ASTLowerer {
         self.module.context.pop();
     }
 
+    /// Suggests `@Cache` for pure functions with multiple recursive calls (e.g. `fib`),
+    /// whose running time grows exponentially without memoization.
+    /// With `-O2` or higher, such functions are memoized automatically.
+    pub(crate) fn warn_memoizable_funcs(&mut self, hir: &HIR) {
+        if self.cfg().opt_level >= 2 {
+            return;
+        }
+        for chunk in hir.module.iter() {
+            let Expr::Def(def) = chunk else {
+                continue;
+            };
+            let ident = def.sig.ident();
+            let cached = ident
+                .vi
+                .comptime_decos
+                .as_ref()
+                .is_some_and(|decos| decos.contains("Cache"));
+            if cached || !HIROptimizer::is_memoizable(def) {
+                continue;
+            }
+            let mut counter = SelfCallCounter {
+                name: ident.inspect(),
+                count: 0,
+            };
+            counter.visit_block(&def.body.block);
+            if counter.count >= 2 {
+                let warn = LowerWarning::cache_suggestion_warning(
+                    self.input().clone(),
+                    line!() as usize,
+                    ident.loc(),
+                    self.module.context.caused_by(),
+                    ident.inspect(),
+                );
+                self.warns.push(warn);
+            }
+        }
+    }
+
+    /// Warns if an else-less `if` chain narrowing the same variable
+    /// does not cover all variants of its union/enum type, like a non-exhaustive `match`.
+    pub(crate) fn warn_non_exhaustive_if_chains(&mut self, hir: &HIR) {
+        let mut collector = IfChainCollector { chains: vec![] };
+        collector.visit_module(&hir.module);
+        for chain in collector.chains {
+            self.check_if_chain_exhaustiveness(&chain);
+        }
+    }
+
+    fn check_if_chain_exhaustiveness(&mut self, chain: &[&hir::Call]) {
+        let mut var_name = None;
+        let mut covered = vec![];
+        for link in chain.iter() {
+            let Type::Guard(guard) = link.args.pos_args[0].expr.ref_t() else {
+                return;
+            };
+            let Variable::Var(name, _) = &guard.var else {
+                return;
+            };
+            if var_name.get_or_insert(name) != &name {
+                return;
+            }
+            covered.push(guard.to.as_ref());
+        }
+        let Some(var_name) = var_name else {
+            return;
+        };
+        let Some(var_t) = Self::guarded_var_t(&chain[0].args.pos_args[0].expr, var_name) else {
+            return;
+        };
+        let Some(variants) = Self::variants(var_t) else {
+            return;
+        };
+        let remaining = variants
+            .into_iter()
+            .filter(|variant| {
+                !covered
+                    .iter()
+                    .any(|to| self.module.context.subtype_of(variant, to))
+            })
+            .reduce(|l, r| self.module.context.union(&l, &r));
+        if let Some(remaining) = remaining {
+            let remaining = self.module.context.readable_type(remaining);
+            let last = chain.last().unwrap();
+            let warn = LowerWarning::non_exhaustive_if_warning(
+                self.input().clone(),
+                line!() as usize,
+                last.loc(),
+                self.module.context.caused_by(),
+                var_name,
+                &remaining,
+            );
+            self.warns.push(warn);
+        }
+    }
+
+    /// Returns the type of `var_name` in a guard condition (`x == 1`, `x in T`, `isinstance(x, T)`).
+    fn guarded_var_t<'e>(cond: &'e Expr, var_name: &str) -> Option<&'e Type> {
+        let operands: Vec<&Expr> = match cond {
+            Expr::BinOp(bin) => vec![&bin.lhs, &bin.rhs],
+            Expr::Call(call) => call.args.pos_args.iter().map(|arg| &arg.expr).collect(),
+            _ => return None,
+        };
+        operands.into_iter().find_map(|expr| match expr {
+            Expr::Accessor(hir::Accessor::Ident(ident)) if ident.inspect() == var_name => {
+                Some(ident.ref_t())
+            }
+            _ => None,
+        })
+    }
+
+    /// `{"a", "b"} or Int` -> `[{"a"}, {"b"}, Int]`
+    /// Returns `None` if `t` is neither an enum nor a union.
+    fn variants(t: &Type) -> Option<Vec<Type>> {
+        match t {
+            Type::FreeVar(fv) if fv.is_linked() => Self::variants(&fv.crack()),
+            Type::Refinement(refine) => {
+                let mut variants = vec![];
+                for pred in refine.pred.ors() {
+                    let Predicate::Equal { rhs, .. } = pred else {
+                        return None;
+                    };
+                    variants.push(singleton(refine.t.as_ref().clone(), rhs.clone()));
+                }
+                (variants.len() >= 2).then_some(variants)
+            }
+            Type::Or(l, r) => {
+                let mut variants = Self::variants(l).unwrap_or_else(|| vec![l.as_ref().clone()]);
+                variants.extend(Self::variants(r).unwrap_or_else(|| vec![r.as_ref().clone()]));
+                Some(variants)
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) fn warn_implicit_union(&mut self, hir: &HIR) {
         for chunk in hir.module.iter() {
             self.warn_implicit_union_chunk(chunk);
@@ -306,7 +781,7 @@ impl ASTLowerer {
             }
             Expr::Def(def) => {
                 if let Signature::Subr(subr) = &def.sig {
-                    let return_t = subr.ref_t().return_t().unwrap();
+                    let return_t = subr.ref_t().return_t().unwrap().into_owned();
                     if return_t.union_pair().is_some() && subr.return_t_spec.is_none() {
                         let typ = if cfg!(feature = "debug") {
                             return_t.clone()
@@ -7,10 +7,12 @@ use erg_common::config::{ErgConfig, ErgMode};
 use erg_common::consts::{ELS, ERG_MODE, PYTHON_MODE};
 use erg_common::dict;
 use erg_common::dict::Dict;
-use erg_common::error::{Location, MultiErrorDisplay};
+use erg_common::error::{ErrorCore, Location, MultiErrorDisplay};
 use erg_common::fresh::FreshNameGenerator;
 use erg_common::set;
 use erg_common::set::Set;
+use erg_common::spawn::is_stack_exhausted;
+use erg_common::timing::{Counter, Phase, TIMINGS};
 use erg_common::traits::{ExitStatus, Locational, NoTypeDisplay, Runnable, Stream};
 use erg_common::triple::Triple;
 use erg_common::{fmt_option, fn_name, log, switch_lang, Str};
@@ -24,23 +26,31 @@ use erg_parser::Parser;
 
 use crate::artifact::{CompleteArtifact, IncompleteArtifact};
 use crate::context::instantiate::TyVarCache;
-use crate::module::SharedCompilerResource;
+use crate::context::instantiate_spec::ParamKind;
+use crate::module::{RefKind, SharedCompilerResource};
 use crate::ty::constructors::{
-    array_t, free_var, func, guard, mono, poly, proc, refinement, set_t, singleton, ty_tp, v_enum,
+    array_t, dict_t, free_var, func1, guard, int_interval, mono, poly, refinement, set_t,
+    singleton, subr_t, ty_tp, unknown_len_array_t, v_enum,
 };
 use crate::ty::free::Constraint;
-use crate::ty::typaram::TyParam;
+use crate::ty::typaram::{IntervalOp, TyParam};
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
-use crate::ty::{GuardType, HasType, ParamTy, Predicate, Type, Variable, VisibilityModifier};
+use crate::ty::{
+    GuardType, HasType, ParamTy, Predicate, SubrKind, Type, Variable, VisibilityModifier,
+};
 
 use crate::context::{
     ClassDefType, Context, ContextKind, ContextProvider, ControlKind, ModuleContext,
     RegistrationMode, TraitImpl,
 };
+use crate::definite_assign::DefiniteAssigner;
 use crate::error::{
     CompileError, CompileErrors, CompileWarning, LowerError, LowerErrors, LowerResult,
     LowerWarning, LowerWarnings, SingleLowerResult,
 };
+use crate::expand::{
+    annotate_errors, is_comptime_call, value_to_expr, MacroExpander, MacroExpansion, COMPTIME,
+};
 use crate::hir;
 use crate::hir::HIR;
 use crate::link_ast::ASTLinker;
@@ -77,6 +87,8 @@ pub struct ASTLowerer {
     pub(crate) errs: LowerErrors,
     pub(crate) warns: LowerWarnings,
     fresh_gen: FreshNameGenerator,
+    /// the macro expansions of the current module (to map the errors in the expanded code)
+    macro_expansions: Vec<MacroExpansion>,
 }
 
 impl Default for ASTLowerer {
@@ -150,6 +162,22 @@ impl Runnable for ASTLowerer {
         artifact.warns.write_all_stderr();
         Ok(format!("{}", artifact.object))
     }
+
+    fn complete(&self, code: &str) -> Vec<String> {
+        self.completion_candidates(code)
+    }
+
+    fn exec_command(&mut self, command: &str) -> Option<Result<String, Self::Errs>> {
+        self.exec_repl_command(command)
+    }
+
+    fn module_names(&self) -> Vec<String> {
+        self.module_names()
+    }
+
+    fn remove_defs(&mut self, names: &[String]) {
+        self.remove_defs(names)
+    }
 }
 
 impl ContextProvider for ASTLowerer {
@@ -180,6 +208,7 @@ impl ASTLowerer {
             errs: LowerErrors::empty(),
             warns: LowerWarnings::empty(),
             fresh_gen: FreshNameGenerator::new("lower"),
+            macro_expansions: vec![],
         }
     }
 
@@ -190,6 +219,7 @@ impl ASTLowerer {
             errs: LowerErrors::empty(),
             warns: LowerWarnings::empty(),
             fresh_gen: FreshNameGenerator::new("lower"),
+            macro_expansions: vec![],
         }
     }
 
@@ -229,6 +259,170 @@ impl ASTLowerer {
     pub fn unregister(&mut self, name: &str) -> Option<VarInfo> {
         self.module.context.unregister(name)
     }
+
+    /// Takes out the errors and warnings accumulated so far (e.g. by `lower_chunk`).
+    pub fn take_diagnostics(&mut self) -> (LowerErrors, LowerWarnings) {
+        (
+            mem::replace(&mut self.errs, LowerErrors::empty()),
+            mem::replace(&mut self.warns, LowerWarnings::empty()),
+        )
+    }
+
+    /// Executes a REPL command (`:type expr`, `:info name`, `:browse module`).
+    /// Returns `None` if the command is unknown.
+    pub fn exec_repl_command(&mut self, command: &str) -> Option<Result<String, CompileErrors>> {
+        let (command, arg) = command.split_once(' ').unwrap_or((command, ""));
+        match command {
+            ":type" | ":t" => Some(
+                self.infer_type(arg.trim().to_string())
+                    .map(|t| format!("{}: {t}", arg.trim())),
+            ),
+            ":info" | ":i" => Some(Ok(self.describe(arg.trim()).unwrap_or_else(|| {
+                switch_lang!(
+                    "japanese" => format!("{}は定義されていません", arg.trim()),
+                    "simplified_chinese" => format!("{}未定义", arg.trim()),
+                    "traditional_chinese" => format!("{}未定義", arg.trim()),
+                    "english" => format!("{} is not defined", arg.trim()),
+                )
+            }))),
+            ":browse" | ":b" => Some(Ok(self.browse(arg.trim()).unwrap_or_else(|| {
+                switch_lang!(
+                    "japanese" => format!("{}はモジュールではありません", arg.trim()),
+                    "simplified_chinese" => format!("{}不是模块", arg.trim()),
+                    "traditional_chinese" => format!("{}不是模組", arg.trim()),
+                    "english" => format!("{} is not a module", arg.trim()),
+                )
+            }))),
+            _ => None,
+        }
+    }
+
+    /// Returns the names defined in the module.
+    pub fn module_names(&self) -> Vec<String> {
+        self.module
+            .context
+            .local_dir()
+            .into_iter()
+            .map(|(name, _)| name.inspect().to_string())
+            .collect()
+    }
+
+    /// Removes the definitions of `names` from the module so that they can be redefined.
+    pub fn remove_defs(&mut self, names: &[String]) {
+        for name in names {
+            self.module.context.remove_def(name);
+        }
+    }
+
+    /// Lists the public names of the module `module` (e.g. `math`) with their types.
+    pub fn browse(&self, module: &str) -> Option<String> {
+        let ctx = self.get_receiver_ctx(module)?;
+        let mut names = ctx
+            .local_dir()
+            .into_iter()
+            .filter(|(_, vi)| vi.vis.is_public())
+            .collect::<Vec<_>>();
+        names.sort_by(|(l, _), (r, _)| l.inspect().cmp(r.inspect()));
+        let names = names
+            .into_iter()
+            .map(|(name, vi)| format!("{name}: {}", vi.t))
+            .collect::<Vec<_>>();
+        Some(names.join("\n"))
+    }
+
+    /// Infers the type of the expression `src` without evaluating it.
+    /// Definitions are not allowed.
+    pub fn infer_type(&mut self, src: String) -> Result<Type, CompileErrors> {
+        let mut ast_builder = ASTBuilder::new(self.cfg.copy());
+        let artifact = ast_builder.build(src).map_err(|artifact| artifact.errors)?;
+        let loc = artifact.ast.module.loc();
+        let mut chunks = artifact.ast.module.into_iter();
+        let expr = match (chunks.next(), chunks.next()) {
+            (Some(expr), None)
+                if !matches!(
+                    expr,
+                    ast::Expr::Def(_)
+                        | ast::Expr::ClassDef(_)
+                        | ast::Expr::PatchDef(_)
+                        | ast::Expr::ReDef(_)
+                ) =>
+            {
+                expr
+            }
+            _ => {
+                return Err(LowerErrors::from(LowerError::syntax_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    String::from(&self.module.context.name[..]),
+                    switch_lang!(
+                        "japanese" => "式が1つ必要です",
+                        "simplified_chinese" => "需要一个表达式",
+                        "traditional_chinese" => "需要一個表達式",
+                        "english" => "an expression is expected",
+                    )
+                    .to_owned(),
+                    None,
+                )));
+            }
+        };
+        let res = self.lower_expr(expr);
+        let (mut errs, _warns) = self.take_diagnostics();
+        match res {
+            Ok(expr) if errs.is_empty() => Ok(expr.t()),
+            Ok(_) => Err(errs),
+            Err(es) => {
+                errs.extend(es);
+                Err(errs)
+            }
+        }
+    }
+
+    /// Describes the variable `name` (e.g. `Foo`, `math.pi`): its type, definition location and implemented traits.
+    pub fn describe(&self, name: &str) -> Option<String> {
+        let (ctx, local_name) = match name.rsplit_once('.') {
+            Some((receiver, attr)) => (self.get_receiver_ctx(receiver)?, attr),
+            None => (&self.module.context as &Context, name),
+        };
+        let (_, vi) = ctx.get_var_info(local_name)?;
+        let mut info = vec![format!("{name}: {}", vi.t)];
+        match vi.def_loc.module.as_ref() {
+            Some(path) => info.push(format!(
+                "defined at: {}:{}",
+                path.display(),
+                vi.def_loc.loc.ln_begin().unwrap_or(1)
+            )),
+            None if vi.kind.is_builtin() => info.push("defined at: <builtins>".into()),
+            None => {}
+        }
+        if vi.t.is_class_type() {
+            if let Some((_, type_ctx)) = ctx
+                .get_type_by_name(local_name)
+                .and_then(|t| ctx.get_nominal_type_ctx(&t))
+            {
+                let classes = type_ctx
+                    .super_classes
+                    .iter()
+                    .filter(|t| t != &&Type::Obj)
+                    .map(|t| t.local_name().to_string())
+                    .collect::<Vec<_>>();
+                if !classes.is_empty() {
+                    info.push(format!("inherits: {}", classes.join(", ")));
+                }
+            }
+            let mut traits = vec![];
+            for t in ctx.get_impl_traits_by_name(local_name) {
+                let name = t.local_name().to_string();
+                if !traits.contains(&name) {
+                    traits.push(name);
+                }
+            }
+            if !traits.is_empty() {
+                info.push(format!("implements: {}", traits.join(", ")));
+            }
+        }
+        Some(info.join("\n    "))
+    }
 }
 
 impl ASTLowerer {
@@ -245,6 +439,62 @@ impl ASTLowerer {
         Ok(lit)
     }
 
+    /// `"a\{x}b\{y}c"` ==> `"a" + str(x) + "b" + str(y) + "c"`
+    ///
+    /// The embedded expressions must implement `Show`.
+    fn lower_str_interp(&mut self, interp: ast::StrInterp) -> LowerResult<hir::Expr> {
+        log!(info "entered {}({interp})", fn_name!());
+        let mut lits = interp.str_lits().into_iter();
+        let mut expr = ast::Expr::Literal(lits.next().unwrap());
+        for (embedded, lit) in interp.exprs.into_iter().zip(lits) {
+            let (ln, col) = (embedded.ln_begin().unwrap(), embedded.col_begin().unwrap());
+            let str_call = ast::Expr::local("str", ln, col)
+                .call_expr(ast::Args::single(ast::PosArg::new(embedded)));
+            let op = Token::new(TokenKind::Plus, "+", ln, col);
+            expr = ast::Expr::BinOp(ast::BinOp::new(op, expr, str_call));
+            let op = Token::new(
+                TokenKind::Plus,
+                "+",
+                lit.ln_begin().unwrap(),
+                lit.col_begin().unwrap(),
+            );
+            expr = ast::Expr::BinOp(ast::BinOp::new(op, expr, ast::Expr::Literal(lit)));
+        }
+        let hir = self.lower_expr(expr)?;
+        // check if the embedded expressions are Show
+        let mut cur = &hir;
+        while let hir::Expr::BinOp(bin) = cur {
+            if let hir::Expr::Call(call) = bin.rhs.as_ref() {
+                let embedded = call.args.get_left_or_key("object").unwrap();
+                let show = mono("Show");
+                if self
+                    .module
+                    .context
+                    .sub_unify(embedded.ref_t(), &show, embedded, None)
+                    .is_err()
+                {
+                    let hint = switch_lang!(
+                        "japanese" => "文字列補間で埋め込む値はShowを実装している必要があります",
+                        "simplified_chinese" => "字符串插值中嵌入的值必须实现Show",
+                        "traditional_chinese" => "字串插值中嵌入的值必須實現Show",
+                        "english" => "the values embedded in a string interpolation must implement Show",
+                    );
+                    self.errs.push(LowerError::no_trait_impl_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        embedded.ref_t(),
+                        &show,
+                        embedded.loc(),
+                        self.module.context.caused_by(),
+                        Some(hint.to_string()),
+                    ));
+                }
+            }
+            cur = bin.lhs.as_ref();
+        }
+        Ok(hir)
+    }
+
     fn lower_array(&mut self, array: ast::Array) -> LowerResult<hir::Array> {
         log!(info "entered {}({array})", fn_name!());
         match array {
@@ -252,14 +502,80 @@ impl ASTLowerer {
             ast::Array::WithLength(arr) => {
                 Ok(hir::Array::WithLength(self.lower_array_with_length(arr)?))
             }
-            other => feature_error!(
-                LowerErrors,
-                LowerError,
-                self.module.context,
-                other.loc(),
-                "array comprehension"
-            ),
+            ast::Array::Comprehension(arr) => Ok(hir::Array::Comprehension(
+                self.lower_array_comprehension(arr)?,
+            )),
+        }
+    }
+
+    /// Lowers the bind clauses (`x <- xs`) and the guard clauses of a comprehension.
+    /// The caller must grow the comprehension scope in advance.
+    #[allow(clippy::type_complexity)]
+    fn lower_comprehension_clauses(
+        &mut self,
+        generators: Vec<(ast::Identifier, ast::Expr)>,
+        guards: Vec<ast::Expr>,
+    ) -> LowerResult<(Vec<(hir::Identifier, hir::Expr)>, Vec<hir::Expr>)> {
+        let mut hir_generators = vec![];
+        for (ident, iter) in generators {
+            let iter = self.lower_expr(iter)?;
+            let sig = ast::NonDefaultParamSignature::new(
+                ast::ParamPattern::VarName(ident.name.clone()),
+                None,
+            );
+            let sig = self.lower_non_default_param(sig)?;
+            let mut params = hir::Params::new(vec![sig], None, vec![], None, None);
+            if let Err(errs) = self.module.context.assign_params(&mut params, None) {
+                self.errs.extend(errs);
+            }
+            let vi = params.non_defaults.remove(0).vi;
+            let iterable_t = poly("Iterable", vec![ty_tp(vi.t.clone())]);
+            if let Err(errs) = self
+                .module
+                .context
+                .sub_unify(iter.ref_t(), &iterable_t, &iter, None)
+            {
+                self.errs.extend(errs);
+            }
+            hir_generators.push((hir::Identifier::new(ident, None, vi), iter));
+        }
+        let mut hir_guards = vec![];
+        for guard in guards {
+            let guard = self.lower_expr(guard)?;
+            if let Err(errs) =
+                self.module
+                    .context
+                    .sub_unify(guard.ref_t(), &Type::Bool, &guard, None)
+            {
+                self.errs.extend(errs);
+            }
+            hir_guards.push(guard);
         }
+        Ok((hir_generators, hir_guards))
+    }
+
+    fn lower_array_comprehension(
+        &mut self,
+        array: ast::ArrayComprehension,
+    ) -> LowerResult<hir::ArrayComprehension> {
+        log!(info "entered {}({array})", fn_name!());
+        self.module
+            .context
+            .grow("<comprehension>", ContextKind::Instant, Private, None);
+        let res = self
+            .lower_comprehension_clauses(array.generators, array.guards)
+            .and_then(|clauses| Ok((clauses, self.lower_expr(*array.elem)?)));
+        self.pop_append_errs();
+        let ((generators, guards), elem) = res?;
+        let t = unknown_len_array_t(elem.t());
+        Ok(hir::ArrayComprehension::new(
+            array.l_sqbr,
+            array.r_sqbr,
+            t,
+            elem,
+            generators,
+            guards,
+        ))
     }
 
     fn elem_err(&self, l: &Type, r: &Type, elem: &hir::Expr) -> LowerErrors {
@@ -414,16 +730,46 @@ impl ASTLowerer {
         match set {
             ast::Set::Normal(set) => Ok(hir::Set::Normal(self.lower_normal_set(set)?)),
             ast::Set::WithLength(set) => Ok(hir::Set::WithLength(self.lower_set_with_length(set)?)),
-            ast::Set::Comprehension(set) => feature_error!(
+            ast::Set::Comprehension(set) if set.is_refinement() => feature_error!(
                 LowerErrors,
                 LowerError,
                 self.module.context,
                 set.loc(),
-                "set comprehension"
+                "refinement types as values"
             ),
+            ast::Set::Comprehension(set) => {
+                Ok(hir::Set::Comprehension(self.lower_set_comprehension(set)?))
+            }
         }
     }
 
+    fn lower_set_comprehension(
+        &mut self,
+        set: ast::SetComprehension,
+    ) -> LowerResult<hir::SetComprehension> {
+        log!(info "entered {}({set})", fn_name!());
+        let Some(layout) = set.layout else {
+            unreachable!()
+        };
+        self.module
+            .context
+            .grow("<comprehension>", ContextKind::Instant, Private, None);
+        let res = self
+            .lower_comprehension_clauses(set.generators, set.guards)
+            .and_then(|clauses| Ok((clauses, self.lower_expr(*layout)?)));
+        self.pop_append_errs();
+        let ((generators, guards), elem) = res?;
+        let t = set_t(elem.t(), TyParam::erased(Type::Nat));
+        Ok(hir::SetComprehension::new(
+            set.l_brace,
+            set.r_brace,
+            t,
+            elem,
+            generators,
+            guards,
+        ))
+    }
+
     fn lower_normal_set(&mut self, set: ast::NormalSet) -> LowerResult<hir::NormalSet> {
         log!(info "entered {}({set})", fn_name!());
         let (elems, ..) = set.elems.deconstruct();
@@ -529,17 +875,50 @@ impl ASTLowerer {
         log!(info "enter {}({dict})", fn_name!());
         match dict {
             ast::Dict::Normal(set) => Ok(hir::Dict::Normal(self.lower_normal_dict(set)?)),
-            other => feature_error!(
-                LowerErrors,
-                LowerError,
-                self.module.context,
-                other.loc(),
-                "dict comprehension"
-            ),
+            ast::Dict::Comprehension(dict) => Ok(hir::Dict::Comprehension(
+                self.lower_dict_comprehension(dict)?,
+            )),
             // ast::Dict::WithLength(set) => Ok(hir::Dict::WithLength(self.lower_dict_with_length(set)?)),
         }
     }
 
+    fn lower_dict_comprehension(
+        &mut self,
+        dict: ast::DictComprehension,
+    ) -> LowerResult<hir::DictComprehension> {
+        log!(info "enter {}({dict})", fn_name!());
+        self.module
+            .context
+            .grow("<comprehension>", ContextKind::Instant, Private, None);
+        let res = self
+            .lower_comprehension_clauses(dict.generators, dict.guards)
+            .and_then(|clauses| {
+                let key = self.lower_expr(*dict.key)?;
+                let value = self.lower_expr(*dict.value)?;
+                Ok((clauses, key, value))
+            });
+        self.pop_append_errs();
+        let ((generators, guards), key, value) = res?;
+        let loc = &(&dict.l_brace, &dict.r_brace);
+        if let Err(errs) = self
+            .module
+            .context
+            .sub_unify(key.ref_t(), &mono("Eq"), loc, None)
+        {
+            self.errs.extend(errs);
+        }
+        let t = dict_t(TyParam::Dict(dict! { ty_tp(key.t()) => ty_tp(value.t()) }));
+        Ok(hir::DictComprehension::new(
+            dict.l_brace,
+            dict.r_brace,
+            t,
+            key,
+            value,
+            generators,
+            guards,
+        ))
+    }
+
     fn lower_normal_dict(&mut self, dict: ast::NormalDict) -> LowerResult<hir::NormalDict> {
         log!(info "enter {}({dict})", fn_name!());
         let mut union = dict! {};
@@ -644,6 +1023,24 @@ impl ASTLowerer {
                         self.errs.push(errs);
                         VarInfo::ILLEGAL
                     }
+                    Triple::None
+                        if self
+                            .module
+                            .context
+                            .get_nullable_attr_owner(obj.ref_t(), attr.ident.inspect())
+                            .is_some() =>
+                    {
+                        let err = LowerError::possibly_none_attr_error(
+                            self.cfg.input.clone(),
+                            line!() as usize,
+                            obj.loc(),
+                            self.module.context.caused_by(),
+                            attr.ident.inspect(),
+                            obj.ref_t(),
+                        );
+                        self.errs.push(err);
+                        VarInfo::ILLEGAL
+                    }
                     Triple::None => {
                         let self_t = obj.t();
                         let (similar_info, similar_name) = self
@@ -666,6 +1063,7 @@ impl ASTLowerer {
                     }
                 };
                 self.inc_ref(attr.ident.inspect(), &vi, &attr.ident.name);
+                self.availability_check(attr.ident.inspect(), &vi, &attr.ident);
                 let ident = hir::Identifier::new(attr.ident, None, vi);
                 let acc = hir::Accessor::Attr(hir::Attribute::new(obj, ident));
                 Ok(acc)
@@ -735,6 +1133,7 @@ impl ASTLowerer {
             )
         };
         self.inc_ref(ident.inspect(), &vi, &ident.name);
+        self.availability_check(ident.inspect(), &vi, &ident);
         let ident = hir::Identifier::new(ident, __name__, vi);
         Ok(ident)
     }
@@ -768,7 +1167,13 @@ impl ASTLowerer {
                 let value = self.module.context.expr_to_value(rhs.clone())?;
                 let t = value.class();
                 let varname = self.fresh_gen.fresh_varname();
-                let pred = Predicate::gt(varname.clone(), TyParam::value(value));
+                // x > 1 (x: Int) => x >= 2
+                let pred = if matches!(value, ValueObj::Nat(_) | ValueObj::Int(_)) {
+                    let succ = value.try_add(ValueObj::Nat(1))?;
+                    Predicate::ge(varname.clone(), TyParam::value(succ))
+                } else {
+                    Predicate::gt(varname.clone(), TyParam::value(value))
+                };
                 let refine = refinement(varname, t, pred);
                 Some(guard(var, refine))
             }
@@ -784,7 +1189,13 @@ impl ASTLowerer {
                 let value = self.module.context.expr_to_value(rhs.clone())?;
                 let t = value.class();
                 let varname = self.fresh_gen.fresh_varname();
-                let pred = Predicate::lt(varname.clone(), TyParam::value(value));
+                // x < 3 (x: Int) => x <= 2
+                let pred = if matches!(value, ValueObj::Nat(_) | ValueObj::Int(_)) {
+                    let pred = value.try_sub(ValueObj::Nat(1))?;
+                    Predicate::le(varname.clone(), TyParam::value(pred))
+                } else {
+                    Predicate::lt(varname.clone(), TyParam::value(value))
+                };
                 let refine = refinement(varname, t, pred);
                 Some(guard(var, refine))
             }
@@ -806,6 +1217,11 @@ impl ASTLowerer {
         let lhs = *args.next().unwrap();
         let rhs = *args.next().unwrap();
         let guard = self.get_guard_type(&bin.op, &lhs, &rhs);
+        let folded_divisor = if bin.op.is(TokenKind::Slash) {
+            self.module.context.eval_const_expr(&rhs).ok()
+        } else {
+            None
+        };
         let lhs = self.lower_expr(lhs).unwrap_or_else(|errs| {
             self.errs.extend(errs);
             hir::Expr::Dummy(hir::Dummy::new(vec![]))
@@ -815,6 +1231,9 @@ impl ASTLowerer {
             self.errs.extend(errs);
             hir::Expr::Dummy(hir::Dummy::new(vec![]))
         });
+        if bin.op.is(TokenKind::Slash) {
+            self.divisor_check(&rhs, folded_divisor.as_ref());
+        }
         let rhs = hir::PosArg::new(rhs);
         let args = [lhs, rhs];
         let mut vi = self
@@ -855,6 +1274,9 @@ impl ASTLowerer {
                 self.errs.extend(errs);
                 hir::Expr::Dummy(hir::Dummy::new(vec![]))
             });
+        if unary.op.is(TokenKind::Try) {
+            return self.lower_early_return(unary.op, arg);
+        }
         let args = [hir::PosArg::new(arg)];
         let t = self
             .module
@@ -869,12 +1291,70 @@ impl ASTLowerer {
         hir::UnaryOp::new(unary.op, expr, t)
     }
 
+    /// `x?`: returns the failure part of `x` (`None` or an `Error`) from the enclosing subroutine.
+    /// The type of `x?` is `T` if `x: T or E` (`E <: NoneType or Error`).
+    fn lower_early_return(&mut self, op: Token, arg: hir::Expr) -> hir::UnaryOp {
+        let loc = Location::concat(&arg, &op);
+        let arg_t = arg.t();
+        let (success_t, failure_t) = self.split_failure(&arg_t);
+        let ret_t = if arg_t.is_failure() {
+            Type::Failure
+        } else if failure_t == Type::Never {
+            self.errs.push(LowerError::invalid_propagation_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                self.module.context.caused_by(),
+                &arg_t,
+            ));
+            Type::Failure
+        } else if let Some(ctx) = self.module.context.get_mut_early_return_target() {
+            ctx.early_returns.push((loc, failure_t));
+            success_t
+        } else {
+            self.errs.push(LowerError::propagation_outside_subr_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                loc,
+                self.module.context.caused_by(),
+            ));
+            success_t
+        };
+        let vi = VarInfo {
+            t: func1(arg_t, ret_t),
+            ..VarInfo::default()
+        };
+        hir::UnaryOp::new(op, arg, vi)
+    }
+
+    /// Splits `t` into the success part and the failure part (`NoneType` and the subtypes of `Error`).
+    /// ```erg
+    /// split_failure(Int or NoneType) == (Int, NoneType)
+    /// split_failure(Int or Error) == (Int, Error)
+    /// split_failure(Int) == (Int, Never)
+    /// ```
+    fn split_failure(&self, t: &Type) -> (Type, Type) {
+        let error = mono("Error");
+        let (mut success_t, mut failure_t) = (Type::Never, Type::Never);
+        for ty in t.union_types() {
+            let is_failure = !ty.is_unbound_var()
+                && (ty == Type::NoneType || self.module.context.subtype_of(&ty, &error));
+            if is_failure {
+                failure_t = self.module.context.union(&failure_t, &ty);
+            } else {
+                success_t = self.module.context.union(&success_t, &ty);
+            }
+        }
+        (success_t, failure_t)
+    }
+
     fn lower_args(&mut self, args: ast::Args, errs: &mut LowerErrors) -> hir::Args {
-        let (pos_args, var_args, kw_args, paren) = args.deconstruct();
+        let (pos_args, var_args, kw_args, kw_var, paren) = args.deconstruct();
         let mut hir_args = hir::Args::new(
             Vec::with_capacity(pos_args.len()),
             None,
             Vec::with_capacity(kw_args.len()),
+            None,
             paren,
         );
         for (nth, arg) in pos_args.into_iter().enumerate() {
@@ -913,6 +1393,16 @@ impl ASTLowerer {
                 }
             }
         }
+        if let Some(kw_var) = kw_var {
+            match self.lower_expr(kw_var.expr) {
+                Ok(expr) => hir_args.kw_var = Some(Box::new(hir::PosArg::new(expr))),
+                Err(es) => {
+                    errs.extend(es);
+                    let dummy = hir::Expr::Dummy(hir::Dummy::empty());
+                    hir_args.kw_var = Some(Box::new(hir::PosArg::new(dummy)));
+                }
+            }
+        }
         hir_args
     }
 
@@ -962,6 +1452,16 @@ impl ASTLowerer {
         } else {
             None
         };
+        let slice_bounds = self.eval_slice_bounds(&call);
+        let is_getitem = call
+            .attr_name
+            .as_ref()
+            .is_some_and(|name| &name.inspect()[..] == "__getitem__");
+        let folded_index = if is_getitem && slice_bounds.is_empty() {
+            self.eval_index(&call)
+        } else {
+            None
+        };
         let hir_args = self.lower_args(call.args, &mut errs);
         let mut obj = match self.lower_expr(*call.obj) {
             Ok(obj) => obj,
@@ -971,11 +1471,18 @@ impl ASTLowerer {
                 return Err(errs);
             }
         };
+        if !slice_bounds.is_empty() {
+            self.check_slice_bounds(obj.ref_t(), slice_bounds, &mut errs);
+        } else if is_getitem {
+            self.check_index_bounds(obj.ref_t(), &hir_args, folded_index, &mut errs);
+        }
         let mut vi = match self.module.context.get_call_t(
             &obj,
             &call.attr_name,
             &hir_args.pos_args,
+            hir_args.var_args.as_deref(),
             &hir_args.kw_args,
+            hir_args.kw_var.as_deref(),
             &self.cfg.input,
             &self.module.context,
         ) {
@@ -993,7 +1500,7 @@ impl ASTLowerer {
             debug_assert!(
                 self.module
                     .context
-                    .subtype_of(vi.t.return_t().unwrap(), &Type::Bool),
+                    .subtype_of(&vi.t.return_t().unwrap(), &Type::Bool),
                 "{} is not a subtype of Bool",
                 vi.t.return_t().unwrap()
             );
@@ -1003,8 +1510,17 @@ impl ASTLowerer {
                 *ref_t = guard;
             }
         }
+        if errs.is_empty()
+            && call.attr_name.is_none()
+            && obj
+                .show_acc()
+                .is_some_and(|name| name == "match" || name == "match!")
+        {
+            self.warn_unreachable_arms(&hir_args);
+        }
         let attr_name = if let Some(attr_name) = call.attr_name {
             self.inc_ref(attr_name.inspect(), &vi, &attr_name.name);
+            self.availability_check(attr_name.inspect(), &vi, &attr_name);
             Some(hir::Identifier::new(attr_name, None, vi))
         } else {
             if let hir::Expr::Call(call) = &obj {
@@ -1025,6 +1541,191 @@ impl ASTLowerer {
         Ok(call)
     }
 
+    /// `x[a..b]` => `x.__getitem__(slice(a, b + 1))`
+    /// returns the statically known bounds (`a`, `b + 1`) of the slice
+    fn eval_slice_bounds(&self, call: &ast::Call) -> Vec<(Location, ValueObj)> {
+        let (
+            Some(attr_name),
+            Some(ast::PosArg {
+                expr: ast::Expr::Call(slice),
+            }),
+        ) = (&call.attr_name, call.args.pos_args().first())
+        else {
+            return vec![];
+        };
+        let is_slice = slice.attr_name.is_none()
+            && slice
+                .obj
+                .get_name()
+                .is_some_and(|name| &name[..] == "slice");
+        if &attr_name.inspect()[..] != "__getitem__" || !is_slice {
+            return vec![];
+        }
+        slice
+            .args
+            .pos_args()
+            .iter()
+            .take(2)
+            .filter_map(|arg| {
+                let val = self.module.context.eval_const_expr(&arg.expr).ok()?;
+                matches!(val, ValueObj::Nat(_) | ValueObj::Int(_)).then(|| (arg.loc(), val))
+            })
+            .collect()
+    }
+
+    /// If the length of the array is statically known, each bound of the slice must be in `-N..N`.
+    fn check_slice_bounds(
+        &self,
+        arr_t: &Type,
+        bounds: Vec<(Location, ValueObj)>,
+        errs: &mut LowerErrors,
+    ) {
+        if &arr_t.qual_name()[..] != "Array" {
+            return;
+        }
+        let Some(TyParam::Value(ValueObj::Nat(len))) = arr_t.typarams().get(1).cloned() else {
+            return;
+        };
+        let len = len as i32;
+        let expect = int_interval(IntervalOp::Closed, -len, len);
+        for (loc, bound) in bounds {
+            let found = v_enum(set! { bound });
+            if !self.module.context.subtype_of(&found, &expect) {
+                errs.push(LowerError::type_mismatch_error(
+                    self.input().clone(),
+                    line!() as usize,
+                    loc,
+                    self.module.context.caused_by(),
+                    "__getitem__",
+                    None,
+                    &expect,
+                    &found,
+                    None,
+                    Some(switch_lang!(
+                        "japanese" => format!("配列の長さは{len}です"),
+                        "simplified_chinese" => format!("数组的长度为{len}"),
+                        "traditional_chinese" => format!("數組的長度為{len}"),
+                        "english" => format!("the length of the array is {len}"),
+                    )),
+                ));
+            }
+        }
+    }
+
+    /// `x[i]` => `x.__getitem__(i)`
+    /// returns the statically known value of `i`
+    fn eval_index(&self, call: &ast::Call) -> Option<ValueObj> {
+        let [ast::PosArg { expr: index }] = call.args.pos_args() else {
+            return None;
+        };
+        let val = self.module.context.eval_const_expr(index).ok()?;
+        matches!(val, ValueObj::Nat(_) | ValueObj::Int(_)).then_some(val)
+    }
+
+    /// If the length of the array is statically known, the index must be in `0..<N`.
+    /// If the index is not provably in the range, it is checked at runtime (with a warning).
+    fn check_index_bounds(
+        &mut self,
+        arr_t: &Type,
+        args: &hir::Args,
+        folded: Option<ValueObj>,
+        errs: &mut LowerErrors,
+    ) {
+        // the length of `Array!` is also tracked statically
+        if !matches!(&arr_t.qual_name()[..], "Array" | "Array!") {
+            return;
+        }
+        let Some(TyParam::Value(ValueObj::Nat(len))) = arr_t.typarams().get(1).cloned() else {
+            return;
+        };
+        let [index] = &args.pos_args[..] else {
+            return;
+        };
+        let expect = int_interval(IntervalOp::Closed, 0, len as i32 - 1);
+        let index_t = index.expr.ref_t();
+        if let Some(value) = folded {
+            let found = v_enum(set! { value });
+            if !self.module.context.subtype_of(&found, &expect) {
+                errs.push(LowerError::type_mismatch_error(
+                    self.input().clone(),
+                    line!() as usize,
+                    index.loc(),
+                    self.module.context.caused_by(),
+                    "__getitem__",
+                    None,
+                    &expect,
+                    &found,
+                    None,
+                    Some(switch_lang!(
+                        "japanese" => format!("配列の長さは{len}です"),
+                        "simplified_chinese" => format!("数组的长度为{len}"),
+                        "traditional_chinese" => format!("數組的長度為{len}"),
+                        "english" => format!("the length of the array is {len}"),
+                    )),
+                ));
+            }
+        } else if !index_t.has_unbound_var()
+            && self.module.context.subtype_of(index_t, &Type::Int)
+            && !self.module.context.subtype_of(index_t, &expect)
+        {
+            self.warns.push(LowerWarning::unchecked_index_warning(
+                self.input().clone(),
+                line!() as usize,
+                index.loc(),
+                self.module.context.caused_by(),
+                index_t,
+                len,
+            ));
+        }
+    }
+
+    /// An arm is unreachable if its pattern is covered by the previous (unguarded) arms.
+    /// e.g. `match x: (i: Int) -> ...; 1 -> ...`
+    fn warn_unreachable_arms(&mut self, args: &hir::Args) {
+        let mut covered = Type::Never;
+        for arm in args.pos_args.iter().skip(1) {
+            let hir::Expr::Lambda(lambda) = &arm.expr else {
+                return;
+            };
+            let Some(param) = lambda.params.non_defaults.first() else {
+                return;
+            };
+            // `x -> ...`, `_ -> ...`
+            let arm_t = if param.raw.t_spec.is_none() {
+                Type::Obj
+            } else {
+                let mut dummy_tv_cache =
+                    TyVarCache::new(self.module.context.level, &self.module.context);
+                let Ok(arm_t) = self.module.context.instantiate_param_sig_t(
+                    &param.raw,
+                    None,
+                    &mut dummy_tv_cache,
+                    RegistrationMode::Normal,
+                    ParamKind::NonDefault,
+                    false,
+                ) else {
+                    return;
+                };
+                arm_t
+            };
+            // cannot be decided statically, e.g. `(y: T) -> ...`, `(a, b) -> ...`
+            if arm_t.has_qvar() || arm_t.has_unbound_var() {
+                continue;
+            }
+            if covered != Type::Never && self.module.context.subtype_of(&arm_t, &covered) {
+                self.warns.push(LowerWarning::unreachable_arm_warning(
+                    self.input().clone(),
+                    line!() as usize,
+                    arm.loc(),
+                    self.module.context.caused_by(),
+                    &covered,
+                ));
+            } else if lambda.guard.is_none() {
+                covered = self.module.context.union(&covered, &arm_t);
+            }
+        }
+    }
+
     /// importing is done in [preregister](https://github.com/erg-lang/erg/blob/ffd33015d540ff5a0b853b28c01370e46e0fcc52/crates/erg_compiler/context/register.rs#L819)
     fn exec_additional_op(&mut self, call: &mut hir::Call) -> LowerResult<()> {
         match call.additional_operation() {
@@ -1050,7 +1751,7 @@ impl ASTLowerer {
                 let ret_t = match callable_t {
                     Type::Subr(subr) => *subr.return_t.clone(),
                     Type::FreeVar(fv) if fv.is_unbound() => {
-                        fv.get_sub().unwrap().return_t().unwrap().clone()
+                        fv.get_sub().unwrap().return_t().unwrap().into_owned()
                     }
                     other => {
                         log!(err "todo: {other}");
@@ -1082,6 +1783,63 @@ impl ASTLowerer {
         }
     }
 
+    /// `comptime expr`: evaluates `expr` at compile time and embeds the value in the code.
+    fn lower_comptime(&mut self, call: ast::Call) -> LowerResult<hir::Expr> {
+        log!(info "entered {}({call})", fn_name!());
+        let loc = call.loc();
+        let (mut pos_args, var_args, kw_args, _, _) = call.args.deconstruct();
+        if let Some(kw_arg) = kw_args.first() {
+            return Err(LowerErrors::from(LowerError::unexpected_kw_arg_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                kw_arg.loc(),
+                "comptime",
+                self.module.context.caused_by(),
+                kw_arg.keyword.inspect(),
+                None,
+                None,
+            )));
+        }
+        if pos_args.len() != 1 || var_args.is_some() {
+            let err = if pos_args.is_empty() {
+                LowerError::args_missing_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    "comptime",
+                    self.module.context.caused_by(),
+                    vec![Str::ever("expr")],
+                )
+            } else {
+                LowerError::too_many_args_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    "comptime",
+                    self.module.context.caused_by(),
+                    1,
+                    pos_args.len() + var_args.iter().count(),
+                    0,
+                )
+            };
+            return Err(LowerErrors::from(err));
+        }
+        let expr = pos_args.remove(0).expr;
+        // type-check the expression (and mark the variables as used)
+        self.lower_expr(expr.clone())?;
+        let value = self.module.context.eval_const_expr(&expr)?;
+        let Some(expr) = value_to_expr(&value, expr.loc()) else {
+            return Err(LowerErrors::from(LowerError::comptime_value_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                expr.loc(),
+                self.module.context.caused_by(),
+                &value.to_string(),
+            )));
+        };
+        self.lower_expr(expr)
+    }
+
     fn lower_pack(&mut self, pack: ast::DataPack) -> LowerResult<hir::Call> {
         log!(info "entered {}({pack})", fn_name!());
         let class = self.lower_expr(*pack.class)?;
@@ -1105,7 +1863,9 @@ impl ASTLowerer {
             &class,
             &Some(attr_name.clone()),
             &args,
+            None,
             &[],
+            None,
             &self.cfg.input,
             &self.module.context,
         ) {
@@ -1174,6 +1934,16 @@ impl ASTLowerer {
                 Err(es) => errs.extend(es),
             }
         }
+        let hir_kw_var_params = match params.kw_var_params {
+            Some(kw_var_params) => match self.lower_non_default_param(*kw_var_params) {
+                Ok(sig) => Some(Box::new(sig)),
+                Err(es) => {
+                    errs.extend(es);
+                    None
+                }
+            },
+            None => None,
+        };
         if !errs.is_empty() {
             Err(errs)
         } else {
@@ -1181,6 +1951,7 @@ impl ASTLowerer {
                 hir_non_defaults,
                 hir_var_params,
                 hir_defaults,
+                hir_kw_var_params,
                 params.parens,
             );
             Ok(hir_params)
@@ -1195,6 +1966,24 @@ impl ASTLowerer {
                 .context
                 .control_kind()
                 .map_or(false, |k| k.makes_scope());
+        if let Some(guard) = &lambda.sig.guard {
+            if !matches!(self.module.context.control_kind(), Some(ControlKind::Match)) {
+                return Err(LowerErrors::from(LowerError::syntax_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    guard.loc(),
+                    self.module.context.caused_by(),
+                    switch_lang!(
+                        "japanese" => "パターンガードはmatchの腕でのみ使用できます",
+                        "simplified_chinese" => "模式守卫只能在match分支中使用",
+                        "traditional_chinese" => "模式守衛只能在match分支中使用",
+                        "english" => "pattern guards can only be used in match arms",
+                    )
+                    .to_owned(),
+                    None,
+                )));
+            }
+        }
         let is_procedural = lambda.is_procedural();
         let id = lambda.id.0;
         let name = format!("<lambda_{id}>");
@@ -1235,6 +2024,28 @@ impl ASTLowerer {
             }
             overwritten
         };
+        // the guard is evaluated in the same scope as the body, just before it
+        let guard = if let Some(guard) = lambda.sig.guard {
+            if let Err(errs) = self.module.context.preregister(&guard) {
+                self.errs.extend(errs);
+            }
+            let guard = self.lower_block(guard).map_err(|errs| {
+                if !in_statement {
+                    self.pop_append_errs();
+                }
+                errs
+            })?;
+            if let Err(errs) =
+                self.module
+                    .context
+                    .sub_unify(guard.ref_t(), &Type::Bool, &guard, None)
+            {
+                self.errs.extend(errs);
+            }
+            Some(guard)
+        } else {
+            None
+        };
         if let Err(errs) = self.module.context.preregister(&lambda.body) {
             self.errs.extend(errs);
         }
@@ -1280,6 +2091,28 @@ impl ASTLowerer {
             .params
             .iter()
             .partition(|(_, vi)| !vi.kind.has_default());
+        let (kw_var_params, non_default_params): (Vec<_>, Vec<_>) =
+            non_default_params.into_iter().partition(|(name, _)| {
+                params
+                    .kw_var_params
+                    .as_ref()
+                    .is_some_and(|kw_var| kw_var.name() == name.as_ref())
+            });
+        // vi.t: `{Str: T}`
+        // pt: `name: T`
+        let kw_var_params = kw_var_params.first().map(|(name, vi)| {
+            let value_t = match vi.t.typarams().first() {
+                Some(TyParam::Dict(dict)) => dict
+                    .values()
+                    .next()
+                    .and_then(|tp| Type::try_from(tp.clone()).ok()),
+                _ => None,
+            };
+            ParamTy::pos_or_kw(
+                name.as_ref().map(|n| n.inspect().clone()),
+                value_t.unwrap_or(Type::Obj),
+            )
+        });
         #[cfg(not(feature = "py_compat"))]
         let (var_params, non_default_params) = {
             let (var_params, non_default_params): (Vec<_>, Vec<_>) = non_default_params
@@ -1345,26 +2178,37 @@ impl ASTLowerer {
                     }
                 }
             }
+        }
+        // `?` in the body may return the failure part of the operands
+        let return_t = if !in_statement {
+            self.module
+                .context
+                .early_returns
+                .iter()
+                .fold(body.t(), |return_t, (_, failure_t)| {
+                    self.module.context.union(&return_t, failure_t)
+                })
         } else {
+            body.t()
+        };
+        if !in_statement {
             self.pop_append_errs();
         }
-        let ty = if is_procedural {
-            proc(
-                non_default_param_tys,
-                var_params,
-                default_param_tys,
-                body.t(),
-            )
+        let kind = if is_procedural {
+            SubrKind::Proc
         } else {
-            func(
-                non_default_param_tys,
-                var_params,
-                default_param_tys,
-                body.t(),
-            )
+            SubrKind::Func
         };
+        let ty = subr_t(
+            kind,
+            non_default_param_tys,
+            var_params,
+            default_param_tys,
+            kw_var_params,
+            return_t,
+        );
         let t = if ty.has_qvar() { ty.quantify() } else { ty };
-        Ok(hir::Lambda::new(id, params, lambda.op, body, t))
+        Ok(hir::Lambda::new(id, params, lambda.op, body, t).with_guard(guard))
     }
 
     fn lower_def(&mut self, def: ast::Def) -> LowerResult<hir::Def> {
@@ -1396,6 +2240,14 @@ impl ASTLowerer {
                     &name,
                 ),
             ));
+        } else if ERG_MODE && &name[..] == COMPTIME {
+            return Err(LowerErrors::from(LowerError::reserved_name_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                def.sig.loc(),
+                self.module.context.caused_by(),
+                &name,
+            )));
         } else if self
             .module
             .context
@@ -1433,18 +2285,21 @@ impl ASTLowerer {
             .instantiate_vis_modifier(def.sig.vis())?;
         let res = match def.sig {
             ast::Signature::Subr(sig) => {
+                let decos = self.lower_decorators(&sig);
                 let tv_cache = self
                     .module
                     .context
                     .instantiate_ty_bounds(&sig.bounds, RegistrationMode::Normal)?;
                 self.module.context.grow(&name, kind, vis, Some(tv_cache));
                 self.lower_subr_def(sig, def.body)
+                    .map(|hir_def| self.check_decorators(hir_def, decos))
             }
             ast::Signature::Var(sig) => {
                 self.module.context.grow(&name, kind, vis, None);
                 self.lower_var_def(sig, def.body)
             }
-        };
+        }
+        .map(|hir_def| hir_def.with_doc(def.doc));
         // TODO: Context上の関数に型境界情報を追加
         self.pop_append_errs();
         // remove from decls regardless of success or failure to lower
@@ -1461,9 +2316,22 @@ impl ASTLowerer {
         if let Err(errs) = self.module.context.preregister(&body.block) {
             self.errs.extend(errs);
         }
+        // numeric constant expressions are folded to check them against refinement types
+        let folded = sig
+            .t_spec
+            .as_ref()
+            .and_then(|_| body.block.first().filter(|_| body.block.len() == 1))
+            .and_then(|expr| self.module.context.eval_const_expr(expr).ok())
+            .filter(|value| {
+                matches!(
+                    value,
+                    ValueObj::Nat(_) | ValueObj::Int(_) | ValueObj::Float(_)
+                )
+            });
         match self.lower_block(body.block) {
             Ok(block) => {
-                let found_body_t = block.ref_t();
+                let folded_t = folded.as_ref().map(|value| v_enum(set! { value.clone() }));
+                let found_body_t = folded_t.as_ref().unwrap_or(block.ref_t());
                 let outer = self.module.context.outer.as_ref().unwrap();
                 let opt_expect_body_t = sig
                     .ident()
@@ -1492,12 +2360,17 @@ impl ASTLowerer {
                     // TODO: expect_body_t is smaller for constants
                     // TODO: 定数の場合、expect_body_tのほうが小さくなってしまう
                     if !sig.is_const() {
-                        if let Err(e) = self.var_result_t_check(
-                            &sig,
-                            ident.inspect(),
-                            &expect_body_t,
-                            found_body_t,
-                        ) {
+                        let res = if let Some(value) = &folded {
+                            self.folded_value_check(&sig, ident.inspect(), &expect_body_t, value)
+                        } else {
+                            self.var_result_t_check(
+                                &sig,
+                                ident.inspect(),
+                                &expect_body_t,
+                                found_body_t,
+                            )
+                        };
+                        if let Err(e) = res {
                             self.errs.push(e);
                         }
                     }
@@ -1509,6 +2382,14 @@ impl ASTLowerer {
                     None,
                 )?;
                 let ident = hir::Identifier::new(ident, None, vi);
+                // `{x;} = import "foo"` is desugared into `x = %v_desugar.x`
+                if let Some(hir::Expr::Accessor(hir::Accessor::Attr(attr))) =
+                    block.first().filter(|_| block.len() == 1)
+                {
+                    if attr.obj.ref_t().is_module() && attr.ident.loc() == ident.loc() {
+                        self.tag_ref(&attr.ident, RefKind::Import);
+                    }
+                }
                 let t_spec = if let Some(ts) = sig.t_spec {
                     let spec_t = self.module.context.instantiate_typespec(&ts.t_spec)?;
                     let expr = self.fake_lower_expr(*ts.t_spec_as_expr.clone())?;
@@ -1532,7 +2413,99 @@ impl ASTLowerer {
         }
     }
 
+    /// Lowers the decorators of `sig` in the scope where the subroutine is defined.
+    /// Only the arguments of the compile-time decorators are checked, and the others are returned to be applied at runtime.
+    fn lower_decorators(&mut self, sig: &ast::SubrSignature) -> Vec<hir::Expr> {
+        let mut decos = vec![];
+        for deco in sig.ordered_decorators() {
+            if deco.is_comptime() {
+                // e.g. `@Deprecated "reason"` (unknown compile-time decorators are just recorded)
+                let is_known = deco.name().is_some_and(|name| {
+                    self.module
+                        .context
+                        .rec_get_var_info(
+                            name,
+                            AccessKind::Name,
+                            &self.cfg.input,
+                            &self.module.context,
+                        )
+                        .ok()
+                        .is_some()
+                });
+                if !is_known || !matches!(deco.expr(), ast::Expr::Call(_)) {
+                    continue;
+                }
+                if let Err(errs) = self.lower_expr(deco.expr().clone()) {
+                    self.errs.extend(errs);
+                }
+            } else {
+                match self.lower_expr(deco.expr().clone()) {
+                    Ok(deco) => decos.push(deco),
+                    Err(errs) => self.errs.extend(errs),
+                }
+            }
+        }
+        decos
+    }
+
+    /// Checks that the runtime decorators can take the subroutine, and attaches them to the definition.
+    fn check_decorators(&mut self, mut def: hir::Def, decos: Vec<hir::Expr>) -> hir::Def {
+        let hir::Signature::Subr(sig) = &mut def.sig else {
+            return def;
+        };
+        for deco in decos.iter() {
+            let Some(param_t) = deco
+                .ref_t()
+                .non_default_params()
+                .and_then(|params| params.first().cloned())
+            else {
+                continue;
+            };
+            if !self.module.context.subtype_of(sig.ref_t(), param_t.typ()) {
+                self.errs.push(LowerError::type_mismatch_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    deco.loc(),
+                    self.module.context.caused_by(),
+                    sig.ident.inspect(),
+                    None,
+                    param_t.typ(),
+                    sig.ref_t(),
+                    None,
+                    None,
+                ));
+            }
+        }
+        sig.decorators = decos;
+        def
+    }
+
     // NOTE: Note that this is in the inner scope while being called.
+    /// If the body contains `?`, the subroutine may return the failure part of the operands.
+    /// If the return type is declared, it must absorb them.
+    fn absorb_early_returns(&mut self, body_t: Type, declared_return_t: Option<&Type>) -> Type {
+        let early_returns = mem::take(&mut self.module.context.early_returns);
+        let mut return_t = body_t;
+        for (loc, failure_t) in early_returns {
+            match declared_return_t {
+                Some(declared_t) if !self.module.context.subtype_of(&failure_t, declared_t) => {
+                    self.errs.push(LowerError::propagation_return_type_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc,
+                        self.module.context.caused_by(),
+                        declared_t,
+                        &failure_t,
+                    ));
+                }
+                _ => {
+                    return_t = self.module.context.union(&return_t, &failure_t);
+                }
+            }
+        }
+        return_t
+    }
+
     fn lower_subr_def(
         &mut self,
         sig: ast::SubrSignature,
@@ -1550,6 +2523,8 @@ impl ASTLowerer {
             .unwrap_or(Type::Failure);
         match registered_t {
             Type::Subr(subr_t) => {
+                let declared_return_t =
+                    (!subr_t.return_t.is_unbound_var()).then(|| subr_t.return_t.as_ref().clone());
                 let mut params = self.lower_params(sig.params.clone())?;
                 if let Err(errs) = self.module.context.assign_params(&mut params, Some(subr_t)) {
                     self.errs.extend(errs);
@@ -1560,6 +2535,8 @@ impl ASTLowerer {
                 match self.lower_block(body.block) {
                     Ok(block) => {
                         let found_body_t = self.module.context.squash_tyvar(block.t());
+                        let found_body_t =
+                            self.absorb_early_returns(found_body_t, declared_return_t.as_ref());
                         let vi = match self.module.context.outer.as_mut().unwrap().assign_subr(
                             &sig,
                             body.id,
@@ -1648,6 +2625,7 @@ impl ASTLowerer {
     fn lower_class_def(&mut self, class_def: ast::ClassDef) -> LowerResult<hir::ClassDef> {
         log!(info "entered {}({class_def})", fn_name!());
         let mut hir_def = self.lower_def(class_def.def)?;
+        self.register_field_locs(&hir_def);
         let mut hir_methods = hir::Block::empty();
         for mut methods in class_def.methods_list.into_iter() {
             let (class, impl_trait) = self.get_class_and_impl_trait(&methods.class)?;
@@ -1785,9 +2763,51 @@ impl ASTLowerer {
             need_to_gen_new,
             __new__.t.clone(),
             hir_methods,
+            hir_def.doc,
         ))
     }
 
+    /// Records the definition locations of the fields (e.g. `x` of `C = Class { x = Int }`) in the reference index,
+    /// so that the accesses to them are tracked (and unused private fields are detected).
+    fn register_field_locs(&mut self, class_def: &hir::Def) {
+        let Some(hir::Expr::Call(call)) = class_def.body.block.first() else {
+            return;
+        };
+        if call.obj.show_acc().as_deref() != Some("Class") {
+            return;
+        }
+        let Some(hir::Expr::Record(rec)) = call.args.get_left_or_key("Requirement") else {
+            return;
+        };
+        let class = self.module.context.gen_type(&class_def.sig.ident().raw);
+        let field_locs = rec
+            .attrs
+            .iter()
+            .map(|attr| {
+                let ident = attr.sig.ident();
+                let loc = self.module.context.absolutize(ident.loc());
+                (ident.inspect().clone(), loc)
+            })
+            .collect::<Vec<_>>();
+        let Some((_, class_ctx)) = self.module.context.get_mut_nominal_type_ctx(&class) else {
+            return;
+        };
+        let mut fields = vec![];
+        for (name, loc) in field_locs {
+            if let Some(vi) = class_ctx
+                .decls
+                .get_mut(&name[..])
+                .filter(|vi| vi.kind.is_instance_attr())
+            {
+                vi.def_loc = loc;
+                fields.push((name, vi.clone()));
+            }
+        }
+        for (name, vi) in fields {
+            self.module.context.index().register(name, &vi);
+        }
+    }
+
     fn get_class_and_impl_trait<'c>(
         &mut self,
         class_spec: &'c ast::TypeSpec,
@@ -1938,12 +2958,21 @@ impl ASTLowerer {
             }
             self.push_patch();
         }
-        Ok(hir::PatchDef::new(hir_def.sig, base, hir_methods))
+        Ok(hir::PatchDef::new(
+            hir_def.sig,
+            base,
+            hir_methods,
+            hir_def.doc,
+        ))
     }
 
     fn lower_redef(&mut self, redef: ast::ReDef) -> LowerResult<hir::ReDef> {
         log!(info "entered {}({redef})", fn_name!());
         let mut attr = self.lower_acc(redef.attr)?;
+        match &attr {
+            hir::Accessor::Ident(ident) => self.tag_ref(ident, RefKind::Write),
+            hir::Accessor::Attr(attr) => self.tag_ref(&attr.ident, RefKind::Write),
+        }
         let expr = self.lower_expr(*redef.expr)?;
         if let Err(err) =
             self.var_result_t_check(&attr, &Str::from(attr.show()), attr.ref_t(), expr.ref_t())
@@ -2420,8 +3449,16 @@ impl ASTLowerer {
     // so turn off type checking (check=false)
     fn lower_expr(&mut self, expr: ast::Expr) -> LowerResult<hir::Expr> {
         log!(info "entered {}", fn_name!());
-        match expr {
+        if is_stack_exhausted() {
+            return Err(LowerErrors::from(LowerError::new(
+                ErrorCore::too_deeply_nested(line!() as usize, expr.loc()),
+                self.input().clone(),
+                self.module.context.caused_by(),
+            )));
+        }
+        let res = match expr {
             ast::Expr::Literal(lit) => Ok(hir::Expr::Lit(self.lower_literal(lit)?)),
+            ast::Expr::StrInterp(interp) => self.lower_str_interp(interp),
             ast::Expr::Array(arr) => Ok(hir::Expr::Array(self.lower_array(arr)?)),
             ast::Expr::Tuple(tup) => Ok(hir::Expr::Tuple(self.lower_tuple(tup)?)),
             ast::Expr::Record(rec) => Ok(hir::Expr::Record(self.lower_record(rec)?)),
@@ -2430,6 +3467,7 @@ impl ASTLowerer {
             ast::Expr::Accessor(acc) => Ok(hir::Expr::Accessor(self.lower_acc(acc)?)),
             ast::Expr::BinOp(bin) => Ok(hir::Expr::BinOp(self.lower_bin(bin))),
             ast::Expr::UnaryOp(unary) => Ok(hir::Expr::UnaryOp(self.lower_unary(unary))),
+            ast::Expr::Call(call) if is_comptime_call(&call) => self.lower_comptime(call),
             ast::Expr::Call(call) => Ok(hir::Expr::Call(self.lower_call(call)?)),
             ast::Expr::DataPack(pack) => Ok(hir::Expr::Call(self.lower_pack(pack)?)),
             ast::Expr::Lambda(lambda) => Ok(hir::Expr::Lambda(self.lower_lambda(lambda)?)),
@@ -2440,7 +3478,11 @@ impl ASTLowerer {
                 log!(err "unreachable: {other}");
                 unreachable_error!(LowerErrors, LowerError, self.module.context)
             }
+        };
+        if res.is_ok() {
+            TIMINGS.count(Counter::HirNodes);
         }
+        res
     }
 
     /// The meaning of TypeAscription changes between chunk and expr.
@@ -2448,14 +3490,16 @@ impl ASTLowerer {
     /// but as chunk, it declares that `x` is of type `Int`, and is valid even before `x` is defined.
     pub fn lower_chunk(&mut self, chunk: ast::Expr) -> LowerResult<hir::Expr> {
         log!(info "entered {}", fn_name!());
-        match chunk {
-            ast::Expr::Def(def) => Ok(hir::Expr::Def(self.lower_def(def)?)),
-            ast::Expr::ClassDef(defs) => Ok(hir::Expr::ClassDef(self.lower_class_def(defs)?)),
-            ast::Expr::PatchDef(defs) => Ok(hir::Expr::PatchDef(self.lower_patch_def(defs)?)),
-            ast::Expr::ReDef(redef) => Ok(hir::Expr::ReDef(self.lower_redef(redef)?)),
-            ast::Expr::TypeAscription(tasc) => Ok(hir::Expr::TypeAsc(self.lower_decl(tasc)?)),
-            other => self.lower_expr(other),
-        }
+        let chunk = match chunk {
+            ast::Expr::Def(def) => hir::Expr::Def(self.lower_def(def)?),
+            ast::Expr::ClassDef(defs) => hir::Expr::ClassDef(self.lower_class_def(defs)?),
+            ast::Expr::PatchDef(defs) => hir::Expr::PatchDef(self.lower_patch_def(defs)?),
+            ast::Expr::ReDef(redef) => hir::Expr::ReDef(self.lower_redef(redef)?),
+            ast::Expr::TypeAscription(tasc) => hir::Expr::TypeAsc(self.lower_decl(tasc)?),
+            other => return self.lower_expr(other),
+        };
+        TIMINGS.count(Counter::HirNodes);
+        Ok(chunk)
     }
 
     fn lower_block(&mut self, ast_block: ast::Block) -> LowerResult<hir::Block> {
@@ -2486,6 +3530,7 @@ impl ASTLowerer {
 
     fn return_incomplete_artifact(&mut self, hir: HIR) -> IncompleteArtifact {
         self.module.context.clear_invalid_vars();
+        self.annotate_macro_errors();
         IncompleteArtifact::new(
             Some(hir),
             LowerErrors::from(self.errs.take_all()),
@@ -2493,26 +3538,62 @@ impl ASTLowerer {
         )
     }
 
+    /// The diagnostics of a cancelled lowering are incomplete, so they are discarded.
+    fn return_cancelled_artifact(&mut self) -> IncompleteArtifact {
+        self.module.context.clear_invalid_vars();
+        self.errs.clear();
+        self.warns.clear();
+        IncompleteArtifact::new(
+            None,
+            LowerErrors::from(CompileError::cancelled(self.cfg.input.clone())),
+            LowerWarnings::empty(),
+        )
+    }
+
+    fn annotate_macro_errors(&mut self) {
+        annotate_errors(&mut self.errs, &self.macro_expansions);
+        annotate_errors(&mut self.warns, &self.macro_expansions);
+    }
+
     pub(crate) fn lint(&mut self, hir: &HIR, mode: &str) {
         self.warn_implicit_union(hir);
+        self.warn_memoizable_funcs(hir);
+        self.warn_non_exhaustive_if_chains(hir);
         self.warn_unused_expr(&hir.module, mode);
         self.check_doc_comments(hir);
-        self.warn_unused_local_vars(mode);
+        self.warn_unused_local_vars(hir, mode);
+        self.warn_dead_private_defs(hir, mode);
     }
 
     pub fn lower(&mut self, ast: AST, mode: &str) -> Result<CompleteArtifact, IncompleteArtifact> {
         log!(info "the AST lowering process has started.");
         log!(info "the type-checking process has started.");
-        let path = self.cfg.input.path();
+        let path = self.cfg.input.path().to_path_buf();
         let graph = &self.module.context.shared().graph;
-        graph.add_node_if_none(path);
-        let ast = ASTLinker::new(self.cfg.clone())
-            .link(ast, mode)
+        graph.add_node_if_none(&path);
+        let mut expander = MacroExpander::new(self.cfg.clone());
+        let ast = TIMINGS.measure(Phase::Lower, &path, || expander.expand(ast));
+        self.errs.extend(expander.errs);
+        self.macro_expansions = expander.expansions;
+        let mut assigner = DefiniteAssigner::new(self.cfg.clone());
+        let ast = TIMINGS.measure(Phase::Lower, &path, || assigner.resolve(ast));
+        if !assigner.errs.is_empty() {
+            self.errs.extend(assigner.errs);
+            return Err(IncompleteArtifact::new(
+                None,
+                LowerErrors::from(self.errs.take_all()),
+                LowerWarnings::from(self.warns.take_all()),
+            ));
+        }
+        let ast = TIMINGS
+            .measure(Phase::Lower, &path, || {
+                ASTLinker::new(self.cfg.clone()).link(ast, mode)
+            })
             .map_err(|errs| {
                 IncompleteArtifact::new(None, errs, LowerWarnings::from(self.warns.take_all()))
             })?;
         if mode == "declare" {
-            let hir = self.declare_module(ast);
+            let hir = TIMINGS.measure(Phase::Lower, &path, || self.declare_module(ast));
             if self.errs.is_empty() {
                 log!(info "HIR:\n{hir}");
                 log!(info "the declaring process has completed.");
@@ -2525,27 +3606,36 @@ impl ASTLowerer {
                 return Err(self.return_incomplete_artifact(hir));
             }
         }
-        let mut module = hir::Module::with_capacity(ast.module.len());
-        if let Err(errs) = self.module.context.preregister(ast.module.block()) {
-            self.errs.extend(errs);
-        }
-        for chunk in ast.module.into_iter() {
-            match self.lower_chunk(chunk) {
-                Ok(chunk) => {
-                    module.push(chunk);
+        let hir = TIMINGS.measure(Phase::Lower, &path, || {
+            let mut module = hir::Module::with_capacity(ast.module.len());
+            if let Err(errs) = self.module.context.preregister(ast.module.block()) {
+                self.errs.extend(errs);
+            }
+            for chunk in ast.module.into_iter() {
+                if self.cfg.cancel.is_cancelled() {
+                    break;
                 }
-                Err(errs) => {
-                    self.errs.extend(errs);
+                match self.lower_chunk(chunk) {
+                    Ok(chunk) => {
+                        module.push(chunk);
+                    }
+                    Err(errs) => {
+                        self.errs.extend(errs);
+                    }
                 }
             }
-        }
-        self.module.context.clear_invalid_vars();
-        self.module.context.check_decls().unwrap_or_else(|errs| {
-            self.errs.extend(errs);
+            self.module.context.clear_invalid_vars();
+            self.module.context.check_decls().unwrap_or_else(|errs| {
+                self.errs.extend(errs);
+            });
+            HIR::new(ast.name, module)
         });
-        let hir = HIR::new(ast.name, module);
+        if self.cfg.cancel.is_cancelled() {
+            return Err(self.return_cancelled_artifact());
+        }
         log!(info "HIR (not resolved, current errs: {}):\n{hir}", self.errs.len());
-        let hir = match self.module.context.resolve(hir) {
+        let resolved = TIMINGS.measure(Phase::Resolve, &path, || self.module.context.resolve(hir));
+        let hir = match resolved {
             Ok(hir) => {
                 log!(info "HIR (resolved):\n{hir}");
                 hir
@@ -2568,8 +3658,12 @@ impl ASTLowerer {
             self.errs.extend(errs);
             self.warns.extend(warns);
         }
+        if self.cfg.cancel.is_cancelled() {
+            return Err(self.return_cancelled_artifact());
+        }
         if self.errs.is_empty() {
             log!(info "the AST lowering process has completed.");
+            self.annotate_macro_errors();
             Ok(CompleteArtifact::new(
                 hir,
                 LowerWarnings::from(self.warns.take_all()),
@@ -0,0 +1,470 @@
+//! Minimizes a source that crashes the compiler (`erg minimize`).
+//!
+//! The minimizer repeatedly tries to remove the chunks of the source (statements, definitions and methods)
+//! and to replace the operations with their operands (e.g. `f(x + 1)` -> `f(x)`).
+//! A change is kept if the compiler still panics with the same message (or reports the same first error).
+//! Finally, the lines are removed one by one, which also reduces the sources that cannot be parsed.
+use std::fmt;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use erg_common::cancel::CancellationToken;
+use erg_common::config::ErgConfig;
+use erg_common::deterministic;
+use erg_common::error::Location;
+use erg_common::io::{Input, Output};
+use erg_common::spawn::spawn_new_thread;
+use erg_common::style::remove_style;
+use erg_common::traits::{ExitStatus, Locational, Runnable, Stream};
+
+use erg_parser::ast::{BinOp, Block, Methods, Module, UnaryOp};
+use erg_parser::build_ast::ASTBuilder;
+use erg_parser::visitor::Visitor;
+use erg_parser::visitor::{walk_bin_op, walk_block, walk_methods, walk_module, walk_unary_op};
+
+use crate::artifact::Buildable;
+use crate::module::SharedCompilerResource;
+use crate::Compiler;
+
+/// The trials that take longer than this are regarded as not reproducing the crash
+/// (the changes can make the compiler loop infinitely).
+const TRIAL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the minimized source has to reproduce.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CrashSignature {
+    /// The first line of the panic message
+    Panic(String),
+    /// The kind and the message of the first error
+    Error(String),
+}
+
+impl fmt::Display for CrashSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(msg) => write!(f, "panic: {msg}"),
+            Self::Error(msg) => write!(f, "error: {msg}"),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        &msg[..]
+    } else {
+        "<unknown>"
+    };
+    // the IDs of the type variables differ between the trials
+    deterministic::normalize(msg.lines().next().unwrap_or(""))
+}
+
+/// A change of the source. Lines are 1-origin, and columns are 0-origin char indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// Removes the lines `first..=last`.
+    RemoveLines { first: usize, last: usize },
+    /// Replaces the columns `range` of `line` with the columns `operand` of the same line.
+    Hoist {
+        line: usize,
+        range: (usize, usize),
+        operand: (usize, usize),
+    },
+}
+
+impl Edit {
+    fn size(&self) -> usize {
+        match self {
+            Self::RemoveLines { first, last } => last - first + 1,
+            Self::Hoist { .. } => 0,
+        }
+    }
+}
+
+/// Collects the edits applicable to a parsed source.
+struct EditCollector<'s> {
+    lines: &'s [String],
+    edits: Vec<Edit>,
+}
+
+impl EditCollector<'_> {
+    fn collect(lines: &[String], module: &Module) -> Vec<Edit> {
+        let mut collector = EditCollector {
+            lines,
+            edits: vec![],
+        };
+        collector.visit_module(module);
+        // larger chunks first, then the hoists
+        collector
+            .edits
+            .sort_by_key(|edit| std::cmp::Reverse(edit.size()));
+        collector.edits.dedup();
+        collector.edits
+    }
+
+    /// The chunk can be removed if it starts its line (e.g. the body of `f x = x + 1` cannot).
+    fn add_chunk(&mut self, chunk: &impl Locational) {
+        let (Some(first), Some(last), Some(col)) =
+            (chunk.ln_begin(), chunk.ln_end(), chunk.col_begin())
+        else {
+            return;
+        };
+        let (first, last) = (first as usize, last as usize);
+        let Some(line) = first.checked_sub(1).and_then(|i| self.lines.get(i)) else {
+            return;
+        };
+        if line.chars().take(col as usize).all(char::is_whitespace) && first <= last {
+            self.edits.push(Edit::RemoveLines { first, last });
+        }
+    }
+
+    /// `loc` is the location of the whole operation.
+    fn add_hoist(&mut self, loc: Location, operand: &impl Locational) {
+        let operand = operand.loc();
+        let (Some(line), Some(line_end)) = (loc.ln_begin(), loc.ln_end()) else {
+            return;
+        };
+        if line != line_end || operand.ln_begin() != Some(line) || operand.ln_end() != Some(line) {
+            return;
+        }
+        let (Some(begin), Some(end), Some(op_begin), Some(op_end)) = (
+            loc.col_begin(),
+            loc.col_end(),
+            operand.col_begin(),
+            operand.col_end(),
+        ) else {
+            return;
+        };
+        self.edits.push(Edit::Hoist {
+            line: line as usize,
+            range: (begin as usize, end as usize),
+            operand: (op_begin as usize, op_end as usize),
+        });
+    }
+}
+
+impl<'a> Visitor<'a> for EditCollector<'_> {
+    fn visit_module(&mut self, module: &'a Module) {
+        for chunk in module.iter() {
+            self.add_chunk(chunk);
+        }
+        walk_module(self, module);
+    }
+
+    fn visit_block(&mut self, block: &'a Block) {
+        for chunk in block.iter() {
+            self.add_chunk(chunk);
+        }
+        walk_block(self, block);
+    }
+
+    fn visit_methods(&mut self, methods: &'a Methods) {
+        for attr in methods.attrs.iter() {
+            self.add_chunk(attr);
+        }
+        walk_methods(self, methods);
+    }
+
+    fn visit_bin_op(&mut self, bin: &'a BinOp) {
+        // the location of `BinOp` starts at the operator
+        let loc = Location::concat(bin.args[0].as_ref(), bin.args[1].as_ref());
+        for arg in bin.args.iter() {
+            self.add_hoist(loc, arg.as_ref());
+        }
+        walk_bin_op(self, bin);
+    }
+
+    fn visit_unary_op(&mut self, unary: &'a UnaryOp) {
+        self.add_hoist(unary.loc(), unary.args[0].as_ref());
+        walk_unary_op(self, unary);
+    }
+}
+
+/// The lines of the source being minimized. The removed lines are `None`.
+#[derive(Debug, Clone)]
+struct Lines {
+    lines: Vec<Option<String>>,
+    /// The lines changed by hoisting (their columns are stale)
+    changed: Vec<bool>,
+}
+
+impl Lines {
+    fn new(src: &str) -> Self {
+        let lines = src
+            .lines()
+            .map(|line| Some(line.to_string()))
+            .collect::<Vec<_>>();
+        let changed = vec![false; lines.len()];
+        Self { lines, changed }
+    }
+
+    fn source(&self) -> String {
+        let mut src = String::new();
+        for line in self.lines.iter().flatten() {
+            src.push_str(line);
+            src.push('\n');
+        }
+        src
+    }
+
+    /// Applies `edit`. Returns `None` if it is not applicable (overlaps the previous edits).
+    fn apply(&self, edit: Edit) -> Option<Self> {
+        let mut applied = self.clone();
+        match edit {
+            Edit::RemoveLines { first, last } => {
+                let lines = applied.lines.get_mut(first - 1..last)?;
+                if lines.iter().all(Option::is_none) {
+                    return None;
+                }
+                lines.fill(None);
+            }
+            Edit::Hoist {
+                line,
+                range: (begin, end),
+                operand: (op_begin, op_end),
+            } => {
+                if *self.changed.get(line - 1)? {
+                    return None;
+                }
+                let chars = self.lines[line - 1].as_ref()?.chars().collect::<Vec<_>>();
+                if end > chars.len() || op_begin > op_end || op_end > chars.len() {
+                    return None;
+                }
+                let mut hoisted = chars[..begin].iter().collect::<String>();
+                hoisted.extend(&chars[op_begin..op_end]);
+                hoisted.extend(&chars[end..]);
+                applied.lines[line - 1] = Some(hoisted);
+                applied.changed[line - 1] = true;
+            }
+        }
+        Some(applied)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minimized {
+    pub src: String,
+    pub signature: CrashSignature,
+    /// The number of the compilations tried
+    pub trials: usize,
+    /// The minimization was cancelled (by Ctrl-C), and `src` is the smallest reproducer found so far.
+    pub cancelled: bool,
+}
+
+#[derive(Debug)]
+pub struct Minimizer {
+    cfg: ErgConfig,
+    shared: SharedCompilerResource,
+    trials: usize,
+}
+
+impl Minimizer {
+    pub fn new(cfg: ErgConfig) -> Self {
+        let shared = SharedCompilerResource::new(cfg.copy());
+        Self {
+            cfg,
+            shared,
+            trials: 0,
+        }
+    }
+
+    fn trial_cfg(&self, src: String, cancel: CancellationToken) -> ErgConfig {
+        ErgConfig {
+            input: Input::buffer(self.cfg.input.path().to_path_buf(), src),
+            output: Output::Null,
+            quiet: true,
+            cancel,
+            ..self.cfg.copy()
+        }
+    }
+
+    /// Compiles `src` and returns how the compiler fails (`None` if it succeeds or times out).
+    pub fn signature(&mut self, src: &str) -> Option<CrashSignature> {
+        self.trials += 1;
+        let cancel = CancellationToken::new();
+        let cfg = self.trial_cfg(src.to_string(), cancel.clone());
+        let shared = self.shared.clone();
+        shared.clear_all();
+        let (sender, receiver) = mpsc::channel();
+        let src = src.to_string();
+        let handle = spawn_new_thread(
+            move || {
+                let mut compiler = Compiler::inherit(cfg, shared);
+                let error = compiler.build(src, "exec").err().and_then(|artifact| {
+                    let err = artifact.errors.into_iter().next()?;
+                    let msg = remove_style(&err.core.main_message);
+                    Some(format!(
+                        "{:?}: {}",
+                        err.core.kind,
+                        deterministic::normalize(&msg)
+                    ))
+                });
+                let _ = sender.send(error);
+            },
+            "minimize",
+        );
+        match receiver.recv_timeout(TRIAL_TIMEOUT) {
+            Ok(error) => {
+                let _ = handle.join();
+                error.map(CrashSignature::Error)
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let payload = handle.join().err()?;
+                // the shared resource may be left inconsistent by the panic
+                self.shared = SharedCompilerResource::new(self.cfg.copy());
+                Some(CrashSignature::Panic(panic_message(payload.as_ref())))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // the thread is left running (if it does not check the token) with the old shared resource
+                cancel.cancel();
+                self.shared = SharedCompilerResource::new(self.cfg.copy());
+                None
+            }
+        }
+    }
+
+    fn reproduces(&mut self, lines: &Lines, signature: &CrashSignature) -> bool {
+        self.signature(&lines.source()).as_ref() == Some(signature)
+    }
+
+    fn edits(&self, lines: &Lines) -> Vec<Edit> {
+        let src = lines.source();
+        let compacted = src.lines().map(str::to_string).collect::<Vec<_>>();
+        let cfg = self.trial_cfg(src.clone(), CancellationToken::new());
+        // the crash may be in the parser
+        let handle = spawn_new_thread(
+            move || {
+                let mut builder = ASTBuilder::new(cfg);
+                let ast = match builder.build_without_desugaring(src) {
+                    Ok(artifact) => artifact.ast,
+                    Err(artifact) => artifact.ast?,
+                };
+                Some(EditCollector::collect(&compacted, &ast.module))
+            },
+            "minimize_parse",
+        );
+        handle.join().ok().flatten().unwrap_or_default()
+    }
+
+    /// Applies the edits one by one, keeping those that reproduce `signature`.
+    fn reduce(
+        &mut self,
+        mut lines: Lines,
+        edits: impl IntoIterator<Item = Edit>,
+        signature: &CrashSignature,
+    ) -> (Lines, bool) {
+        let mut reduced = false;
+        for edit in edits {
+            if self.cfg.cancel.is_cancelled() {
+                break;
+            }
+            let Some(applied) = lines.apply(edit) else {
+                continue;
+            };
+            if self.reproduces(&applied, signature) {
+                lines = applied;
+                reduced = true;
+            }
+        }
+        (lines, reduced)
+    }
+
+    /// Minimizes `src` while the compiler fails in the same way.
+    /// Returns `Err` if the compiler does not fail with `src`.
+    pub fn minimize(&mut self, src: &str) -> Result<Minimized, String> {
+        self.trials = 0;
+        let Some(signature) = self.signature(src) else {
+            return Err("the compiler neither panics nor reports errors".into());
+        };
+        let mut lines = Lines::new(src);
+        let blank_or_comment = (1..=lines.lines.len())
+            .filter(|&i| {
+                let line = lines.lines[i - 1].as_deref().unwrap_or("").trim();
+                line.is_empty() || line.starts_with('#')
+            })
+            .map(|i| Edit::RemoveLines { first: i, last: i })
+            .collect::<Vec<_>>();
+        // try removing all of them at once first
+        let mut without = Some(lines.clone());
+        for edit in blank_or_comment.iter() {
+            without = without.and_then(|lines| lines.apply(*edit));
+        }
+        if let Some(without) = without.filter(|without| self.reproduces(without, &signature)) {
+            lines = without;
+        }
+        loop {
+            // the AST-based edits refer to the compacted source
+            lines = Lines::new(&lines.source());
+            let edits = self.edits(&lines);
+            let (reduced_lines, mut reduced) = self.reduce(lines, edits, &signature);
+            let line_edits = (1..=reduced_lines.lines.len())
+                .rev()
+                .map(|i| Edit::RemoveLines { first: i, last: i });
+            let (reduced_lines, line_reduced) = self.reduce(reduced_lines, line_edits, &signature);
+            reduced |= line_reduced;
+            lines = reduced_lines;
+            if !reduced || self.cfg.cancel.is_cancelled() {
+                break;
+            }
+        }
+        Ok(Minimized {
+            src: lines.source(),
+            signature,
+            trials: self.trials,
+            cancelled: self.cfg.cancel.is_cancelled(),
+        })
+    }
+
+    /// `crash.er` -> `crash.min.er`
+    pub fn output_path(path: &Path) -> PathBuf {
+        path.with_extension("min.er")
+    }
+
+    /// Minimizes the input file and writes the result to `<file stem>.min.er`.
+    pub fn run(mut cfg: ErgConfig) -> ExitStatus {
+        if !cfg.input.is_file() {
+            eprintln!("no file to minimize (usage: erg minimize <file>)");
+            return ExitStatus::ERR1;
+        }
+        let path = cfg.input.path().to_path_buf();
+        let src = cfg.input.read();
+        let mut minimizer = Self::new(cfg);
+        let start = Instant::now();
+        let hook = panic::take_hook();
+        // the panics are expected
+        panic::set_hook(Box::new(|_| {}));
+        let result = minimizer.minimize(&src);
+        panic::set_hook(hook);
+        let minimized = match result {
+            Ok(minimized) => minimized,
+            Err(msg) => {
+                eprintln!("{}: {msg}", path.display());
+                return ExitStatus::ERR1;
+            }
+        };
+        let output = Self::output_path(&path);
+        if let Err(err) = fs::write(&output, &minimized.src) {
+            eprintln!("failed to write {}: {err}", output.display());
+            return ExitStatus::ERR1;
+        }
+        if minimized.cancelled {
+            eprintln!("cancelled, the smallest reproducer found so far is written");
+        }
+        println!("{}", minimized.signature);
+        println!(
+            "minimized {}: {} -> {} lines ({} trials, {:.2}s), written to {}\n",
+            path.display(),
+            src.lines().count(),
+            minimized.src.lines().count(),
+            minimized.trials,
+            start.elapsed().as_secs_f64(),
+            output.display()
+        );
+        print!("{}", minimized.src);
+        ExitStatus::OK
+    }
+}
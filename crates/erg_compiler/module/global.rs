@@ -1,6 +1,8 @@
 use std::path::{Path, PathBuf};
 
 use erg_common::config::ErgConfig;
+use erg_common::timing::{Phase, TIMINGS};
+use erg_common::Str;
 
 use crate::context::Context;
 
@@ -44,7 +46,10 @@ impl SharedCompilerResource {
             errors: SharedCompileErrors::new(),
             warns: SharedCompileWarnings::new(),
         };
-        Context::init_builtins(cfg, self_.clone());
+        TIMINGS.measure(Phase::Builtins, Path::new("<builtins>"), || {
+            Context::init_builtins(cfg, self_.clone())
+        });
+        self_.trait_impls.freeze_builtins();
         self_
     }
 
@@ -62,6 +67,7 @@ impl SharedCompilerResource {
         self.trait_impls.initialize();
         self.errors.clear();
         self.warns.clear();
+        Str::clear_interned();
     }
 
     pub fn clear(&self, path: &Path) {
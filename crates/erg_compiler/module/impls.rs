@@ -19,6 +19,8 @@ pub struct TraitImpls {
     /// * key: trait qualified name
     /// * value: set of trait impls
     cache: Dict<Str, Set<TraitImpl>>,
+    /// the impls of the builtin types, which are restored by `initialize`
+    builtins: Dict<Str, Set<TraitImpl>>,
 }
 
 impl fmt::Display for TraitImpls {
@@ -33,7 +35,10 @@ impl fmt::Display for TraitImpls {
 
 impl TraitImpls {
     pub fn new() -> Self {
-        Self { cache: Dict::new() }
+        Self {
+            cache: Dict::new(),
+            builtins: Dict::new(),
+        }
     }
 
     pub fn get<P: Eq + Hash + ?Sized>(&self, path: &P) -> Option<&Set<TraitImpl>>
@@ -61,8 +66,13 @@ impl TraitImpls {
         self.cache.remove(path)
     }
 
+    /// Records the current impls as those of the builtin types.
+    pub fn freeze_builtins(&mut self) {
+        self.builtins = self.cache.clone();
+    }
+
     pub fn initialize(&mut self) {
-        self.cache.clear();
+        self.cache = self.builtins.clone();
     }
 }
 
@@ -127,6 +137,10 @@ impl SharedTraitImpls {
         RwLockReadGuard::map(self.0.borrow(), |tis| &tis.cache)
     }
 
+    pub fn freeze_builtins(&self) {
+        self.0.borrow_mut().freeze_builtins();
+    }
+
     pub fn initialize(&self) {
         self.0.borrow_mut().initialize();
     }
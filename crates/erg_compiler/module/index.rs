@@ -1,5 +1,6 @@
 use std::collections::hash_map::{Iter, Keys, Values};
 use std::fmt;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
 use erg_common::dict::Dict;
@@ -8,6 +9,7 @@ use erg_common::set::Set;
 use erg_common::shared::{MappedRwLockReadGuard, RwLockReadGuard, Shared};
 use erg_common::Str;
 
+use crate::stable_id::{module_key, StableDefId};
 use crate::varinfo::{AbsLocation, VarInfo};
 
 pub struct Members<'a>(MappedRwLockReadGuard<'a, Dict<AbsLocation, ModuleIndexValue>>);
@@ -26,11 +28,34 @@ impl<'a> Members<'a> {
     }
 }
 
+/// How a variable is used at a referrer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RefKind {
+    #[default]
+    Read,
+    /// reassignment (e.g. `x.y = 1`)
+    Write,
+    /// e.g. `{x;} = import "foo"`
+    Import,
+}
+
+impl fmt::Display for RefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+            Self::Import => write!(f, "import"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ModuleIndexValue {
     pub name: Str,
     pub vi: VarInfo,
     pub referrers: Set<AbsLocation>,
+    /// the kinds of the uses of `referrers`
+    pub ref_kinds: Dict<AbsLocation, RefKind>,
 }
 
 impl fmt::Display for ModuleIndexValue {
@@ -44,22 +69,41 @@ impl fmt::Display for ModuleIndexValue {
 }
 
 impl ModuleIndexValue {
-    pub const fn new(name: Str, vi: VarInfo, referrers: Set<AbsLocation>) -> Self {
+    pub fn new(name: Str, vi: VarInfo, referrers: Set<AbsLocation>) -> Self {
+        let ref_kinds = referrers
+            .iter()
+            .map(|referrer| (referrer.clone(), RefKind::Read))
+            .collect();
         Self {
             name,
             vi,
             referrers,
+            ref_kinds,
         }
     }
 
-    pub fn push_ref(&mut self, referrer: AbsLocation) {
+    pub fn push_ref(&mut self, referrer: AbsLocation, kind: RefKind) {
+        self.ref_kinds.insert(referrer.clone(), kind);
         self.referrers.insert(referrer);
     }
+
+    pub fn ref_kind(&self, referrer: &AbsLocation) -> RefKind {
+        self.ref_kinds.get(referrer).copied().unwrap_or_default()
+    }
+
+    fn retain_refs(&mut self, mut f: impl FnMut(&AbsLocation) -> bool) {
+        self.referrers.retain(|referrer| f(referrer));
+        self.ref_kinds.retain(|referrer, _| f(referrer));
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct ModuleIndex {
     members: Dict<AbsLocation, ModuleIndexValue>,
+    /// the definitions by the stable IDs (see `crate::stable_id`)
+    ids: Dict<StableDefId, AbsLocation>,
+    /// cache of `module_key`
+    module_keys: Dict<PathBuf, String>,
 }
 
 impl fmt::Display for ModuleIndex {
@@ -72,16 +116,37 @@ impl ModuleIndex {
     pub fn new() -> Self {
         Self {
             members: Dict::new(),
+            ids: Dict::new(),
+            module_keys: Dict::new(),
         }
     }
 
-    pub fn inc_ref(&mut self, name: &Str, vi: &VarInfo, referrer: AbsLocation) {
+    fn stable_id(&mut self, name: &str, vi: &VarInfo) -> StableDefId {
+        let module = vi.def_loc.module.as_ref().map_or(String::new(), |path| {
+            self.module_keys
+                .entry(path.clone())
+                .or_insert_with(|| module_key(path))
+                .clone()
+        });
+        StableDefId::new(&module, &vi.vis.def_namespace, name)
+    }
+
+    fn insert(&mut self, referee: AbsLocation, value: ModuleIndexValue) {
+        let id = self.stable_id(&value.name, &value.vi);
+        self.ids.insert(id, referee.clone());
+        self.members.insert(referee, value);
+    }
+
+    /// Registers a use of `vi` at `referrer`.
+    /// If the use is already registered, only its kind is updated.
+    pub fn inc_ref(&mut self, name: &Str, vi: &VarInfo, referrer: AbsLocation, kind: RefKind) {
         let referee = vi.def_loc.clone();
         if let Some(referrers) = self.members.get_mut(&referee) {
-            referrers.push_ref(referrer);
+            referrers.push_ref(referrer, kind);
         } else {
-            let value = ModuleIndexValue::new(name.clone(), vi.clone(), set! {referrer});
-            self.members.insert(referee, value);
+            let mut value = ModuleIndexValue::new(name.clone(), vi.clone(), set! {});
+            value.push_ref(referrer, kind);
+            self.insert(referee, value);
         }
     }
 
@@ -91,24 +156,42 @@ impl ModuleIndex {
         }
         let referee = vi.def_loc.clone();
         let value = ModuleIndexValue::new(name, vi.clone(), set! {});
-        self.members.insert(referee, value);
+        self.insert(referee, value);
     }
 
     pub fn get_refs(&self, referee: &AbsLocation) -> Option<&ModuleIndexValue> {
         self.members.get(referee)
     }
 
+    /// The location of the definition with `id`.
+    /// If the name is redefined, the location of the last registered definition is returned.
+    pub fn get_def_loc(&self, id: &StableDefId) -> Option<&AbsLocation> {
+        self.ids.get(id)
+    }
+
     pub fn initialize(&mut self) {
         self.members.clear();
+        self.ids.clear();
     }
 
     pub fn remove_path(&mut self, path: &Path) {
         self.members.retain(|loc, value| {
-            value
-                .referrers
-                .retain(|ref_loc| ref_loc.module.as_deref() != Some(path));
+            value.retain_refs(|ref_loc| ref_loc.module.as_deref() != Some(path));
             loc.module.as_deref() != Some(path)
         });
+        self.ids
+            .retain(|_, loc| loc.module.as_deref() != Some(path));
+        self.module_keys.remove(path);
+    }
+
+    /// Removes the uses in `lines` of `path` (e.g. the edited definition), keeping the definitions.
+    pub fn remove_refs_in(&mut self, path: &Path, lines: RangeInclusive<u32>) {
+        for value in self.members.values_mut() {
+            value.retain_refs(|ref_loc| {
+                ref_loc.module.as_deref() != Some(path)
+                    || !ref_loc.loc.ln_begin().is_some_and(|ln| lines.contains(&ln))
+            });
+        }
     }
 
     pub fn rename_path(&mut self, old: &Path, new: PathBuf) {
@@ -118,17 +201,22 @@ impl ModuleIndex {
                 value.vi.def_loc.module = Some(new.clone());
             }
             let mut new_referrers = set! {};
+            let mut new_ref_kinds = Dict::new();
             for referee in value.referrers.into_iter() {
-                if referee.module.as_deref() == Some(old) {
-                    new_referrers.insert(AbsLocation {
+                let kind = value.ref_kinds.remove(&referee).unwrap_or_default();
+                let referee = if referee.module.as_deref() == Some(old) {
+                    AbsLocation {
                         module: Some(new.clone()),
                         ..referee
-                    });
+                    }
                 } else {
-                    new_referrers.insert(referee);
-                }
+                    referee
+                };
+                new_ref_kinds.insert(referee.clone(), kind);
+                new_referrers.insert(referee);
             }
             value.referrers = new_referrers;
+            value.ref_kinds = new_ref_kinds;
             if loc.module.as_deref() != Some(old) {
                 new_members.insert(loc.clone(), value.clone());
             } else {
@@ -141,7 +229,12 @@ impl ModuleIndex {
                 );
             }
         }
-        self.members = new_members;
+        // the IDs of the moved definitions are changed
+        self.module_keys.remove(old);
+        self.ids.clear();
+        for (loc, value) in std::mem::take(&mut new_members) {
+            self.insert(loc, value);
+        }
     }
 }
 
@@ -159,8 +252,8 @@ impl SharedModuleIndex {
         Self(Shared::new(ModuleIndex::new()))
     }
 
-    pub fn inc_ref(&self, name: &Str, vi: &VarInfo, referrer: AbsLocation) {
-        self.0.borrow_mut().inc_ref(name, vi, referrer);
+    pub fn inc_ref(&self, name: &Str, vi: &VarInfo, referrer: AbsLocation, kind: RefKind) {
+        self.0.borrow_mut().inc_ref(name, vi, referrer, kind);
     }
 
     pub fn register(&self, name: Str, vi: &VarInfo) {
@@ -180,6 +273,10 @@ impl SharedModuleIndex {
         }
     }
 
+    pub fn get_def_loc(&self, id: &StableDefId) -> Option<AbsLocation> {
+        self.0.borrow().get_def_loc(id).cloned()
+    }
+
     pub fn members(&self) -> Members {
         Members(RwLockReadGuard::map(self.0.borrow(), |mi| &mi.members))
     }
@@ -192,6 +289,10 @@ impl SharedModuleIndex {
         self.0.borrow_mut().remove_path(path);
     }
 
+    pub fn remove_refs_in(&self, path: &Path, lines: RangeInclusive<u32>) {
+        self.0.borrow_mut().remove_refs_in(path, lines);
+    }
+
     pub fn rename_path(&self, old: &Path, new: PathBuf) {
         self.0.borrow_mut().rename_path(old, new);
     }
@@ -91,15 +91,43 @@ impl SharedPromises {
         }
     }
 
+    /// Reserves the analysis of `path` before spawning it.
+    /// Returns `false` if it has already been registered (or reserved) by another thread.
+    pub fn reserve<P: Into<NormalizedPathBuf>>(&self, path: P) -> bool {
+        let mut promises = self.promises.borrow_mut();
+        let path = path.into();
+        if promises.get(&path).is_some() {
+            return false;
+        }
+        // `join` waits until the running promise is inserted
+        promises.insert(path, Promise::Joining);
+        true
+    }
+
     pub fn insert<P: Into<NormalizedPathBuf>>(&self, path: P, handle: JoinHandle<()>) {
         let path = path.into();
-        if self.promises.borrow().get(&path).is_some() {
+        let mut promises = self.promises.borrow_mut();
+        // a reserved promise is replaced with the running one
+        if promises
+            .get(&path)
+            .is_some_and(|promise| !matches!(promise, Promise::Joining))
+        {
             // panic!("already registered: {}", path.display());
             return;
         }
+        promises.insert(path, Promise::running(handle));
+    }
+
+    /// Registers a module analyzed without importing (e.g. a file passed to `erg check`),
+    /// so that the modules importing it do not analyze it again.
+    pub fn mark_as_finished<P: Into<NormalizedPathBuf>>(&self, path: P) {
         self.promises
             .borrow_mut()
-            .insert(path, Promise::running(handle));
+            .insert(path.into(), Promise::Finished);
+    }
+
+    pub fn remove(&self, path: &Path) {
+        self.promises.borrow_mut().remove(path);
     }
 
     pub fn is_registered(&self, path: &Path) -> bool {
@@ -1,8 +1,11 @@
 use erg_common::config::ErgConfig;
+use erg_common::set::Set;
+use erg_common::Str;
 
 use crate::effectcheck::SideEffectChecker;
 use crate::hir::*;
 use crate::module::SharedCompilerResource;
+use crate::ty::{HasType, Type};
 // use crate::erg_common::traits::Stream;
 
 /// Optimizes a `HIR`.
@@ -20,7 +23,12 @@ impl HIROptimizer {
         if optimizer.cfg.opt_level == 0 || optimizer.cfg.input.is_repl() {
             return hir;
         }
-        optimizer.eliminate_dead_code(hir)
+        let hir = optimizer.eliminate_dead_code(hir);
+        if optimizer.cfg.opt_level >= 2 {
+            optimizer.memoize_pure_functions(hir)
+        } else {
+            hir
+        }
     }
 
     fn _fold_constants(&mut self, mut _hir: HIR) -> HIR {
@@ -37,13 +45,22 @@ impl HIROptimizer {
     fn eliminate_unused_def(&mut self, expr: &mut Expr) {
         match expr {
             Expr::Def(def) => {
-                if self
-                    .shared
-                    .index
-                    .get_refs(&def.sig.ident().vi.def_loc)
-                    .unwrap()
-                    .referrers
-                    .is_empty()
+                // called by `erg test`, `erg bench`
+                let is_entry_point = def
+                    .sig
+                    .ident()
+                    .vi
+                    .comptime_decos
+                    .as_ref()
+                    .is_some_and(|decos| decos.contains("Test") || decos.contains("Bench"));
+                if !is_entry_point
+                    && self
+                        .shared
+                        .index
+                        .get_refs(&def.sig.ident().vi.def_loc)
+                        .unwrap()
+                        .referrers
+                        .is_empty()
                     && SideEffectChecker::is_pure(expr)
                 {
                     *expr = Expr::Dummy(Dummy::empty());
@@ -68,6 +85,62 @@ impl HIROptimizer {
         }
     }
 
+    /// Marks the memoizable functions with `@Cache`, so the backend caches their results.
+    fn memoize_pure_functions(&mut self, mut hir: HIR) -> HIR {
+        for chunk in hir.module.iter_mut() {
+            if let Expr::Def(def) = chunk {
+                if Self::is_memoizable(def) {
+                    def.sig
+                        .ident_mut()
+                        .vi
+                        .comptime_decos
+                        .get_or_insert_with(Set::new)
+                        .insert(Str::ever("Cache"));
+                }
+            }
+        }
+        hir
+    }
+
+    /// A function is memoizable if it has no side effects
+    /// and its parameters and return value are immutable, hashable values.
+    pub(crate) fn is_memoizable(def: &Def) -> bool {
+        let Signature::Subr(sig) = &def.sig else {
+            return false;
+        };
+        if sig.is_procedural()
+            || sig.params.var_params.is_some()
+            || sig.params.kw_var_params.is_some()
+            || (sig.params.non_defaults.is_empty() && sig.params.defaults.is_empty())
+            || !def.body.block.iter().all(SideEffectChecker::is_pure)
+        {
+            return false;
+        }
+        let params_hashable = sig
+            .params
+            .non_defaults
+            .iter()
+            .all(|param| Self::is_hashable_value(&param.vi.t))
+            && sig
+                .params
+                .defaults
+                .iter()
+                .all(|param| Self::is_hashable_value(&param.sig.vi.t));
+        params_hashable
+            && sig
+                .ref_t()
+                .return_t()
+                .as_deref()
+                .is_some_and(Self::is_hashable_value)
+    }
+
+    fn is_hashable_value(t: &Type) -> bool {
+        matches!(
+            &t.derefine().qual_name()[..],
+            "Int" | "Nat" | "Float" | "Ratio" | "Complex" | "Bool" | "Str" | "NoneType"
+        )
+    }
+
     fn eliminate_dead_code(&mut self, hir: HIR) -> HIR {
         let hir = self.eliminate_discarded_variables(hir);
         self.eliminate_unused_variables(hir)
@@ -10,7 +10,7 @@ use erg_common::Str;
 use erg_common::{impl_display_from_debug, log};
 use erg_parser::ast::{ParamPattern, VarName};
 
-use crate::ty::{HasType, Ownership, Visibility};
+use crate::ty::{ArgsOwnership, HasType, Ownership, Visibility};
 
 use crate::error::{OwnershipError, OwnershipErrors};
 use crate::hir::{self, Accessor, Array, Block, Def, Expr, Identifier, Signature, Tuple, HIR};
@@ -107,7 +107,8 @@ impl OwnershipChecker {
                 self.dict
                     .insert(Str::from(self.full_path()), LocalVars::default());
                 if let Signature::Subr(subr) = &def.sig {
-                    let (nd_params, var_params, d_params, _) = subr.params.ref_deconstruct();
+                    let (nd_params, var_params, d_params, kw_var_params, _) =
+                        subr.params.ref_deconstruct();
                     for param in nd_params {
                         if let ParamPattern::VarName(name) = &param.raw.pat {
                             self.define_param(name);
@@ -123,6 +124,11 @@ impl OwnershipChecker {
                             self.define_param(name);
                         }
                     }
+                    if let Some(kw_var) = kw_var_params {
+                        if let ParamPattern::VarName(name) = &kw_var.raw.pat {
+                            self.define_param(name);
+                        }
+                    }
                 }
                 self.check_block(&def.body.block);
                 self.path_stack.pop();
@@ -150,6 +156,7 @@ impl OwnershipChecker {
                     return;
                 }
                 let args_owns = sig_t.args_ownership();
+                self.check_aliasing(call, &args_owns);
                 let non_defaults_len = if call.is_method_call() {
                     args_owns.non_defaults.len() - 1
                 } else {
@@ -187,10 +194,19 @@ impl OwnershipChecker {
                         .find(|(k, _)| k.as_ref() == Some(kw_arg.keyword.inspect()))
                     {
                         self.check_expr(&kw_arg.expr, *ownership, false);
+                    } else if let Some((_, ownership)) = args_owns.kw_var_params.as_ref() {
+                        self.check_expr(&kw_arg.expr, *ownership, false);
                     } else {
                         todo!()
                     }
                 }
+                // unpacked arguments (`*xs`, `**kws`) are always moved
+                if let Some(var_args) = call.args.var_args.as_ref() {
+                    self.check_expr(&var_args.expr, Ownership::Owned, false);
+                }
+                if let Some(kw_var) = call.args.kw_var.as_ref() {
+                    self.check_expr(&kw_var.expr, Ownership::Owned, false);
+                }
             }
             // TODO: referenced
             Expr::BinOp(binop) => {
@@ -210,7 +226,10 @@ impl OwnershipChecker {
                     self.check_expr(&arr.elem, ownership, false);
                     self.check_expr(&arr.len, ownership, false);
                 }
-                _ => todo!(),
+                Array::Comprehension(arr) => {
+                    self.check_comprehension_clauses(&arr.generators, &arr.guards, ownership);
+                    self.check_expr(&arr.elem, ownership, false);
+                }
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(arr) => {
@@ -226,7 +245,11 @@ impl OwnershipChecker {
                         self.check_expr(&kv.value, ownership, false);
                     }
                 }
-                other => todo!("{other}"),
+                hir::Dict::Comprehension(dic) => {
+                    self.check_comprehension_clauses(&dic.generators, &dic.guards, ownership);
+                    self.check_expr(&dic.key, ownership, false);
+                    self.check_expr(&dic.value, ownership, false);
+                }
             },
             Expr::Record(rec) => {
                 for def in rec.attrs.iter() {
@@ -245,6 +268,10 @@ impl OwnershipChecker {
                     self.check_expr(&st.elem, ownership, false);
                     self.check_expr(&st.len, ownership, false);
                 }
+                hir::Set::Comprehension(st) => {
+                    self.check_comprehension_clauses(&st.generators, &st.guards, ownership);
+                    self.check_expr(&st.elem, ownership, false);
+                }
             },
             // TODO: capturing
             Expr::Lambda(lambda) => {
@@ -253,6 +280,9 @@ impl OwnershipChecker {
                 self.path_stack.push(name_and_vis);
                 self.dict
                     .insert(Str::from(self.full_path()), LocalVars::default());
+                if let Some(guard) = &lambda.guard {
+                    self.check_block(guard);
+                }
                 self.check_block(&lambda.body);
                 self.path_stack.pop();
             }
@@ -263,6 +293,21 @@ impl OwnershipChecker {
         }
     }
 
+    fn check_comprehension_clauses(
+        &mut self,
+        generators: &[(Identifier, Expr)],
+        guards: &[Expr],
+        ownership: Ownership,
+    ) {
+        for (var, iter) in generators.iter() {
+            self.check_expr(iter, ownership, false);
+            self.define_param(&var.raw.name);
+        }
+        for guard in guards.iter() {
+            self.check_expr(guard, ownership, false);
+        }
+    }
+
     fn check_acc(&mut self, acc: &Accessor, ownership: Ownership, chunk: bool) {
         match acc {
             Accessor::Ident(ident) => {
@@ -283,6 +328,79 @@ impl OwnershipChecker {
 
     /// TODO: このメソッドを呼ぶとき、スコープを再帰的に検索する
     #[inline]
+    /// References only live for the duration of a call,
+    /// so a variable must not be passed as `RefMut` together with any other borrow in the same call.
+    fn check_aliasing<'a>(&mut self, call: &'a hir::Call, args_owns: &ArgsOwnership) {
+        let mut borrows: Vec<(&Str, Location, Ownership)> = vec![];
+        let mut push_borrow = |expr: &'a Expr, ownership: Ownership| {
+            if let Expr::Accessor(Accessor::Ident(ident)) = expr {
+                if ownership.is_ref() || ownership.is_refmut() {
+                    borrows.push((ident.inspect(), ident.loc(), ownership));
+                }
+            }
+        };
+        let mut non_defaults = args_owns.non_defaults.iter();
+        if call.is_method_call() {
+            if let Some((_, ownership)) = non_defaults.next() {
+                push_borrow(&call.obj, *ownership);
+            }
+        }
+        let mut defaults = args_owns.defaults.iter();
+        for arg in call.args.pos_args.iter() {
+            let ownership = if let Some((_, ownership)) = non_defaults.next() {
+                *ownership
+            } else if let Some((_, ownership)) = args_owns.var_params.as_ref() {
+                *ownership
+            } else if let Some((_, ownership)) = defaults.next() {
+                *ownership
+            } else {
+                break;
+            };
+            push_borrow(&arg.expr, ownership);
+        }
+        for kw_arg in call.args.kw_args.iter() {
+            let name = kw_arg.keyword.inspect();
+            let ownership = args_owns
+                .defaults
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, o)| *o)
+                .or_else(|| {
+                    args_owns
+                        .non_defaults
+                        .iter()
+                        .find(|(k, _)| k.as_ref() == Some(name))
+                        .map(|(_, o)| *o)
+                })
+                .or_else(|| args_owns.kw_var_params.as_ref().map(|(_, o)| *o));
+            if let Some(ownership) = ownership {
+                push_borrow(&kw_arg.expr, ownership);
+            }
+        }
+        let mut reported = Set::new();
+        for (i, (name, second_loc, second)) in borrows.iter().enumerate() {
+            if reported.contains(*name) {
+                continue;
+            }
+            let conflict = borrows[..i].iter().find(|(other, _, first)| {
+                other == name && (first.is_refmut() || second.is_refmut())
+            });
+            if let Some((_, first_loc, first)) = conflict {
+                reported.insert((*name).clone());
+                self.errs.push(OwnershipError::borrow_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    name,
+                    *first_loc,
+                    first.is_refmut(),
+                    *second_loc,
+                    second.is_refmut(),
+                    self.full_path(),
+                ));
+            }
+        }
+    }
+
     fn current_scope(&mut self) -> &mut LocalVars {
         self.dict.get_mut(&self.full_path()[..]).unwrap()
     }
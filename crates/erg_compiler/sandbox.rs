@@ -0,0 +1,265 @@
+//! Checks untrusted code (e.g. submitted to a playground, or generated by a fuzzer) with resource limits.
+//!
+//! The compiler is not hardened against every malformed input, so a crash must not take the host down:
+//!
+//! * `Isolation::Thread` runs the checker in a thread with its own stack. Panics are caught,
+//!   but this is not enough if the host is built with `panic = "abort"`.
+//! * `Isolation::Process` runs `erg check --json-only -` in a child process, which is killed on timeout.
+//!
+//! Too large sources are rejected before being parsed, and too deeply nested code is reported as an error
+//! (`RecursionError`) instead of overflowing the stack.
+//!
+//! ```
+//! # use erg_compiler::sandbox::{Sandbox, SandboxOutcome};
+//! let outcome = Sandbox::default().check("x: Int = \"a\"");
+//! let SandboxOutcome::Checked { errors, .. } = outcome else {
+//!     panic!("{outcome:?}");
+//! };
+//! assert_eq!(errors.len(), 1);
+//! assert!(errors[0].contains("\"severity\":\"error\""));
+//! ```
+use std::any::Any;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use erg_common::cancel::CancellationToken;
+use erg_common::config::ErgConfig;
+use erg_common::exit_code;
+use erg_common::io::{Input, Output};
+use erg_common::spawn::spawn_new_thread_with_stack;
+
+use crate::check::{diagnostic_json, PackageChecker};
+use crate::error::CompileErrors;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SandboxLimits {
+    /// in bytes
+    pub max_source_len: usize,
+    pub timeout: Duration,
+    /// the stack size of the checker thread (`Isolation::Thread` only)
+    pub stack_size: usize,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        Self {
+            max_source_len: 1024 * 1024,
+            timeout: Duration::from_secs(10),
+            stack_size: 8 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Isolation {
+    #[default]
+    Thread,
+    /// The path of the `erg` executable
+    Process(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxOutcome {
+    /// The diagnostics as lines of JSON (see `check::diagnostic_json`)
+    Checked {
+        errors: Vec<String>,
+        warns: Vec<String>,
+    },
+    /// The source is longer than `SandboxLimits::max_source_len`
+    TooLarge(usize),
+    TimedOut,
+    /// The checker panicked (or was killed by a signal)
+    Crashed(String),
+}
+
+impl SandboxOutcome {
+    pub fn is_crashed(&self) -> bool {
+        matches!(self, Self::Crashed(_))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Sandbox {
+    cfg: ErgConfig,
+    limits: SandboxLimits,
+    isolation: Isolation,
+}
+
+impl Sandbox {
+    /// `cfg.input`, `cfg.output` and `cfg.cancel` are overwritten for each check.
+    pub fn new(cfg: ErgConfig, limits: SandboxLimits, isolation: Isolation) -> Self {
+        Self {
+            cfg,
+            limits,
+            isolation,
+        }
+    }
+
+    pub fn limits(&self) -> &SandboxLimits {
+        &self.limits
+    }
+
+    pub fn check(&self, src: &str) -> SandboxOutcome {
+        if src.len() > self.limits.max_source_len {
+            return SandboxOutcome::TooLarge(src.len());
+        }
+        match &self.isolation {
+            Isolation::Thread => self.check_in_thread(src),
+            Isolation::Process(erg) => self.check_in_process(erg, src),
+        }
+    }
+
+    fn check_in_thread(&self, src: &str) -> SandboxOutcome {
+        let cancel = CancellationToken::new();
+        let cfg = ErgConfig {
+            input: Input::str(src.to_string()),
+            output: Output::Null,
+            cancel: cancel.clone(),
+            ..self.cfg.copy()
+        };
+        let src = src.to_string();
+        let (sender, receiver) = mpsc::channel();
+        let handle = spawn_new_thread_with_stack(
+            move || {
+                let result = PackageChecker::new(cfg).check_src(src);
+                let to_json = |errs: &CompileErrors| {
+                    errs.iter()
+                        .map(|err| {
+                            let code = format!("{:?}", err.core.kind);
+                            diagnostic_json(err.input.path(), err, &code)
+                        })
+                        .collect::<Vec<_>>()
+                };
+                let outcome = SandboxOutcome::Checked {
+                    errors: to_json(&result.errors),
+                    warns: to_json(&result.warns),
+                };
+                // the receiver may have given up (timed out)
+                let _ = sender.send(outcome);
+            },
+            "sandbox",
+            self.limits.stack_size,
+        );
+        match receiver.recv_timeout(self.limits.timeout) {
+            Ok(outcome) => outcome,
+            Err(RecvTimeoutError::Timeout) => {
+                // the thread stops at the next checkpoint (it is not joined)
+                cancel.cancel();
+                SandboxOutcome::TimedOut
+            }
+            // the sender was dropped without sending, i.e. the thread panicked
+            Err(RecvTimeoutError::Disconnected) => match handle.join() {
+                Err(payload) => SandboxOutcome::Crashed(panic_message(payload)),
+                Ok(()) => SandboxOutcome::Crashed("the checker exited unexpectedly".into()),
+            },
+        }
+    }
+
+    fn check_in_process(&self, erg: &Path, src: &str) -> SandboxOutcome {
+        let child = Command::new(erg)
+            .args(["check", "--json-only", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(err) => return SandboxOutcome::Crashed(format!("failed to spawn {erg:?}: {err}")),
+        };
+        // the pipes are read in other threads so that the child is not blocked by a full pipe
+        let stdin = child.stdin.take();
+        let src = src.to_string();
+        thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(src.as_bytes());
+            }
+        });
+        let read_all = |pipe: Option<Box<dyn Read + Send>>| {
+            thread::spawn(move || {
+                let mut buf = String::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_string(&mut buf);
+                }
+                buf
+            })
+        };
+        let stdout = read_all(child.stdout.take().map(|p| Box::new(p) as _));
+        let stderr = read_all(child.stderr.take().map(|p| Box::new(p) as _));
+        let deadline = Instant::now() + self.limits.timeout;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(5)),
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return SandboxOutcome::TimedOut;
+                }
+                Err(err) => return SandboxOutcome::Crashed(err.to_string()),
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        match status.code() {
+            // killed by a signal (e.g. aborted by a panic or a stack overflow)
+            None => SandboxOutcome::Crashed(format!("{status}\n{stderr}")),
+            Some(exit_code::INTERNAL_ERROR) => SandboxOutcome::Crashed(stderr),
+            Some(_) => {
+                let (warns, errors) = stdout
+                    .lines()
+                    .map(String::from)
+                    .partition(|line| line.contains("\"severity\":\"warning\""));
+                SandboxOutcome::Checked { errors, warns }
+            }
+        }
+    }
+}
+
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "the checker panicked".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_limits() {
+        let limits = SandboxLimits {
+            max_source_len: 16,
+            ..SandboxLimits::default()
+        };
+        let sandbox = Sandbox::new(ErgConfig::default(), limits, Isolation::Thread);
+        assert_eq!(
+            sandbox.check("print! \"too long source\""),
+            SandboxOutcome::TooLarge(24)
+        );
+        assert_eq!(
+            sandbox.check("print! 1"),
+            SandboxOutcome::Checked {
+                errors: vec![],
+                warns: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_sandbox_deeply_nested() {
+        let src = format!("x = {}1{}", "[".repeat(1000), "]".repeat(1000));
+        let SandboxOutcome::Checked { errors, .. } = Sandbox::default().check(&src) else {
+            panic!()
+        };
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("\"code\":\"RecursionError\""));
+    }
+}
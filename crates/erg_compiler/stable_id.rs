@@ -0,0 +1,178 @@
+//! Stable, serializable IDs of the definitions and the types.
+//!
+//! `ast::DefId` (a counter of the parser) and the ids of the type variables (`?123`) change in each session,
+//! and `AbsLocation` changes when the lines above the definition are edited.
+//! The IDs defined here are derived from the contents (the module, the namespace and the name of a definition, or the type itself),
+//! so the data cached across runs and the external tools (e.g. the language server clients) can refer to the entities reliably.
+//!
+//! The IDs are 64-bit FNV-1a hashes, which do not depend on the platform or the Rust version.
+//! They are written (and parsed) as 16 hex digits.
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use erg_common::deterministic;
+use erg_common::env::erg_path;
+use erg_common::pathutil::package_root;
+
+use crate::ty::Type;
+use crate::varinfo::VarInfo;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The FNV-1a hash of `parts`. The parts are separated (`["ab", "c"]` and `["a", "bc"]` are different).
+pub(crate) fn stable_hash(parts: &[&[u8]]) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for part in parts {
+        for byte in part.iter().chain(&[0xff]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+fn parse_hex(s: &str) -> Result<u64, String> {
+    if s.len() != 16 {
+        return Err(format!("invalid stable ID (16 hex digits expected): {s}"));
+    }
+    u64::from_str_radix(s, 16).map_err(|err| format!("invalid stable ID: {s} ({err})"))
+}
+
+/// The machine-independent name of the module at `path`:
+/// the path relative to the package root (or the Erg installation), with `/` as the separator.
+/// Otherwise, the path itself.
+pub fn module_key(path: &Path) -> String {
+    let relative = if let Ok(rel) = path.strip_prefix(erg_path()) {
+        Some(Path::new("<erg>").join(rel))
+    } else {
+        path.parent()
+            .and_then(package_root)
+            .and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+    };
+    let key = relative.as_deref().unwrap_or(path).to_string_lossy();
+    key.replace('\\', "/")
+}
+
+/// The ID of a definition (a variable, a subroutine, a parameter, an attribute, a class, etc.),
+/// derived from its module, namespace and name.
+///
+/// The redefinitions of a name in the same namespace (e.g. `x = 1; x = 2`) share the ID,
+/// and the ID does not change when the definition is moved in the module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StableDefId(u64);
+
+impl fmt::Display for StableDefId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for StableDefId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hex(s).map(Self)
+    }
+}
+
+impl StableDefId {
+    /// `module` is a key of `module_key`. `namespace` is the name of the defining context (e.g. `<module>::C`).
+    pub fn new(module: &str, namespace: &str, name: &str) -> Self {
+        Self(stable_hash(&[
+            module.as_bytes(),
+            namespace.as_bytes(),
+            name.as_bytes(),
+        ]))
+    }
+
+    /// The ID of the definition of `name` (`vi`).
+    pub fn of(name: &str, vi: &VarInfo) -> Self {
+        let module = vi
+            .def_loc
+            .module
+            .as_deref()
+            .map_or(String::new(), module_key);
+        Self::new(&module, &vi.vis.def_namespace, name)
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The ID of a type, derived from its structure.
+///
+/// The free type variables are numbered in the order of appearance (`?1`, `?2`, ...),
+/// so the types equal up to the renaming of the type variables share the ID.
+/// The nominal types are identified by their (qualified) names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StableTypeId(u64);
+
+impl fmt::Display for StableTypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for StableTypeId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hex(s).map(Self)
+    }
+}
+
+impl StableTypeId {
+    pub fn of(t: &Type) -> Self {
+        let canonical = deterministic::normalize(&t.to_string());
+        Self(stable_hash(&[canonical.as_bytes()]))
+    }
+
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ty::constructors::{free_var, func1, proc1};
+    use crate::ty::free::Constraint;
+
+    #[test]
+    fn test_stable_def_id() {
+        let id = StableDefId::new("src/foo.er", "<module>::C", "f");
+        assert_eq!(id.to_string(), format!("{:016x}", id.as_u64()));
+        assert_eq!(id, StableDefId::new("src/foo.er", "<module>::C", "f"));
+        assert_ne!(id, StableDefId::new("src/foo.er", "<module>::C.f", ""));
+        assert_ne!(id, StableDefId::new("src/bar.er", "<module>::C", "f"));
+        assert_eq!(id.to_string().parse::<StableDefId>(), Ok(id));
+        assert!("xyz".parse::<StableDefId>().is_err());
+        // must not change across runs, platforms and versions
+        assert_eq!(stable_hash(&[]), FNV_OFFSET_BASIS);
+        assert_eq!(stable_hash(&[b"a"]), 0x089b_c907_b544_c769);
+    }
+
+    #[test]
+    fn test_stable_type_id() {
+        let a = free_var(1, Constraint::new_type_of(Type::Type));
+        let b = free_var(1, Constraint::new_type_of(Type::Type));
+        assert_ne!(a.to_string(), b.to_string());
+        let int_to_int = func1(Type::Int, Type::Int);
+        assert_eq!(
+            StableTypeId::of(&int_to_int),
+            StableTypeId::of(&func1(Type::Int, Type::Int))
+        );
+        assert_ne!(
+            StableTypeId::of(&int_to_int),
+            StableTypeId::of(&proc1(Type::Int, Type::Int))
+        );
+        assert_eq!(
+            StableTypeId::of(&func1(a.clone(), a)),
+            StableTypeId::of(&func1(b.clone(), b))
+        );
+    }
+}
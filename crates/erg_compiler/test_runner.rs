@@ -0,0 +1,512 @@
+//! Runs the tests of a package (`erg test`).
+//!
+//! Tests are the parameterless subroutines marked with `@Test`
+//! (and the `test_*` subroutines in the test files, see `erg_common::pathutil::is_test_file`).
+//! Each file is compiled (without optimization, so that unused tests are not removed)
+//! and executed by a Python harness, which calls the tests one by one and captures their outputs.
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::process::Stdio;
+use std::time::Instant;
+
+use erg_common::config::ErgConfig;
+use erg_common::diff::unified_diff;
+use erg_common::error::MultiErrorDisplay;
+use erg_common::io::Input;
+use erg_common::pathutil::{erg_files, expand_targets, is_test_file, package_root};
+use erg_common::python_util::exec_pyc;
+use erg_common::style::{colors::GREEN, colors::RED, RESET};
+use erg_common::traits::{ExitStatus, Locational, Runnable, Stream};
+use erg_common::Str;
+
+use crate::codegen::escape_ident;
+use crate::coverage::{coverage_data_path, COVERAGE_FOOTER, COVERAGE_PRELUDE};
+use crate::hir::{Expr, Identifier, Signature, HIR};
+use crate::ty::codeobj::CodeObj;
+use crate::Compiler;
+
+/// A test function found in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: Str,
+    /// the (mangled) name in the generated Python code
+    pub py_name: Str,
+    pub line: u32,
+}
+
+impl TestCase {
+    /// `filter`: `--filter`
+    pub fn matches(&self, filter: Option<&str>) -> bool {
+        filter.is_none_or(|filter| self.name.contains(filter))
+    }
+}
+
+/// Whether the subroutine is marked with the compile-time decorator (e.g. `@Test`).
+pub(crate) fn has_comptime_deco(ident: &Identifier, deco: &str) -> bool {
+    ident
+        .vi
+        .comptime_decos
+        .as_ref()
+        .is_some_and(|decos| decos.contains(deco))
+}
+
+/// Returns the parameterless subroutines defined at the top level of the module that satisfy `is_entry`.
+pub(crate) fn collect_entry_points(
+    hir: &HIR,
+    is_entry: impl Fn(&Identifier) -> bool,
+) -> Vec<TestCase> {
+    let mut cases = vec![];
+    for chunk in hir.module.iter() {
+        let Expr::Def(def) = chunk else {
+            continue;
+        };
+        let Signature::Subr(sig) = &def.sig else {
+            continue;
+        };
+        let takes_no_args = sig.params.non_defaults.is_empty() && sig.params.var_params.is_none();
+        if takes_no_args && is_entry(&sig.ident) {
+            cases.push(TestCase {
+                name: sig.ident.inspect().clone(),
+                py_name: escape_ident(sig.ident.clone()),
+                line: sig.ident.ln_begin().unwrap_or(0),
+            });
+        }
+    }
+    cases
+}
+
+/// Returns the tests defined at the top level of the module.
+pub fn collect_tests(hir: &HIR, path: &Path) -> Vec<TestCase> {
+    let in_test_file = is_test_file(path);
+    collect_entry_points(hir, |ident| {
+        has_comptime_deco(ident, "Test") || (in_test_file && ident.inspect().starts_with("test_"))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    /// `diff`: the left and right values of a failed `assert_eq`
+    Failed {
+        message: String,
+        diff: Option<(String, String)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub case: TestCase,
+    pub outcome: TestOutcome,
+    /// in seconds
+    pub elapsed: f64,
+    /// the captured standard output
+    pub output: String,
+}
+
+impl TestResult {
+    pub const fn is_passed(&self) -> bool {
+        matches!(self.outcome, TestOutcome::Passed)
+    }
+}
+
+/// The results of the tests in a file.
+#[derive(Debug)]
+pub struct FileTestResult {
+    pub path: PathBuf,
+    pub results: Vec<TestResult>,
+    /// the number of tests excluded by `--filter`
+    pub filtered_out: usize,
+    /// compile errors, or an error raised while executing the top-level code of the module
+    pub error: Option<String>,
+}
+
+/// Loads the module and defines the helpers. `CASES` are the (mangled) names of the subroutines to be called.
+/// If the top-level code of the module raises an error, only the error is reported.
+const HARNESS_HEADER: &str = r#"
+import contextlib
+import io
+import marshal
+import pprint
+import sys
+import time
+import traceback
+
+
+def escape(s):
+    return s.replace("\\", "\\\\").replace("\t", "\\t").replace("\n", "\\n")
+
+
+def show(obj):
+    return obj if isinstance(obj, str) else pprint.pformat(obj)
+
+
+def describe(err):
+    # `traceback.extract_tb` cannot be used because Erg code objects have no position tables
+    frames = []
+    tb = err.__traceback__.tb_next  # skip the harness
+    while tb is not None:
+        code = tb.tb_frame.f_code
+        if not code.co_filename.endswith("_erg_testing.py"):
+            line = f", line {tb.tb_lineno}" if (tb.tb_lineno or 0) > 0 else ""
+            frames.append(f'  File "{code.co_filename}"{line}, in {code.co_name}\n')
+        tb = tb.tb_next
+    if hasattr(err, "left"):
+        summary = str(err).splitlines()[0] + "\n"
+    else:
+        summary = "".join(traceback.format_exception_only(type(err), err))
+    return "".join(frames) + summary
+
+
+def write_results(results):
+    with open(RESULTS, "w", encoding="utf-8") as f:
+        for result in results:
+            f.write("\t".join(map(escape, result)) + "\n")
+
+
+with open(PYC, "rb") as f:
+    code = marshal.loads(f.read()[16:])
+sys.path.insert(0, MODULE_DIR)
+# the Python modules of `pyimport` are imported relative to the current directory
+sys.path.insert(1, "")
+module = {"__name__": "__erg_test__"}
+results = []
+out = io.StringIO()
+try:
+    with contextlib.redirect_stdout(out):
+        exec(code, module)
+except (Exception, SystemExit) as err:
+    write_results([["error", describe(err) + out.getvalue()]])
+    sys.exit(0)
+"#;
+
+const HARNESS_FOOTER: &str = "write_results(results)\n";
+
+const TEST_HARNESS: &str = r#"
+for case in CASES:
+    out = io.StringIO()
+    start = time.perf_counter()
+    try:
+        with contextlib.redirect_stdout(out):
+            module[case]()
+        status, message, left, right = "ok", "", "", ""
+    except (Exception, SystemExit) as err:
+        message = describe(err)
+        if hasattr(err, "left"):
+            status, left, right = "failed_eq", show(err.left), show(err.right)
+        else:
+            status, left, right = "failed", "", ""
+    elapsed = time.perf_counter() - start
+    results.append([status, case, f"{elapsed:.3f}", message, left, right, out.getvalue()])
+"#;
+
+pub(crate) fn py_str_literal(s: &str) -> String {
+    format!(
+        "'{}'",
+        s.replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\n', "\\n")
+    )
+}
+
+pub(crate) fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Compiles the file and returns the code object and the HIR.
+/// The diagnostics are written to stderr.
+pub(crate) fn compile_file(cfg: &ErgConfig, path: &Path) -> Result<(CodeObj, HIR), String> {
+    let src = fs::read_to_string(path).map_err(|err| format!("cannot read: {err}"))?;
+    let mut cfg = cfg.copy();
+    cfg.input = Input::file(path.to_path_buf());
+    let mut compiler = Compiler::new(cfg);
+    let arti = compiler.compile_with_hir(src, "exec").map_err(|eart| {
+        eart.warns.write_all_stderr();
+        eart.errors.write_all_stderr();
+        format!("{} compile error(s)", eart.errors.len())
+    })?;
+    arti.warns.write_all_stderr();
+    Ok(arti.object)
+}
+
+/// Executes the compiled module of `path` and `body` (Python code calling `CASES`) with the Python harness.
+/// `constants` are defined before the harness (e.g. `[("WARMUP_TIME", "0.5")]`).
+/// Returns the rows written by the harness, or the error raised by the top-level code of the module.
+pub(crate) fn run_harness(
+    cfg: &ErgConfig,
+    path: &Path,
+    code: CodeObj,
+    cases: &[TestCase],
+    constants: &[(&str, String)],
+    body: &str,
+) -> Result<Vec<Vec<String>>, String> {
+    let tmp = std::env::temp_dir().join(format!(
+        "erg_{}_{}_{}",
+        <&str>::from(cfg.mode),
+        process::id(),
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ));
+    let pyc = tmp.with_extension("pyc");
+    let harness = tmp.with_extension("py");
+    let results = tmp.with_extension("txt");
+    let module_dir = path.parent().map_or(PathBuf::from("."), Path::to_path_buf);
+    let mut script = String::new();
+    let _ = writeln!(script, "PYC = {}", py_str_literal(&pyc.to_string_lossy()));
+    let _ = writeln!(
+        script,
+        "RESULTS = {}",
+        py_str_literal(&results.to_string_lossy())
+    );
+    let _ = writeln!(
+        script,
+        "MODULE_DIR = {}",
+        py_str_literal(&module_dir.to_string_lossy())
+    );
+    let names = cases
+        .iter()
+        .map(|case| py_str_literal(&case.py_name))
+        .collect::<Vec<_>>();
+    let _ = writeln!(script, "CASES = [{}]", names.join(", "));
+    for (name, value) in constants.iter() {
+        let _ = writeln!(script, "{name} = {value}");
+    }
+    if cfg.coverage {
+        let _ = writeln!(
+            script,
+            "COVERAGE = {}",
+            py_str_literal(&coverage_data_path().to_string_lossy())
+        );
+        script.push_str(COVERAGE_PRELUDE);
+    }
+    script.push_str(HARNESS_HEADER);
+    script.push_str(body);
+    if cfg.coverage {
+        script.push_str(COVERAGE_FOOTER);
+    }
+    script.push_str(HARNESS_FOOTER);
+    let written = code
+        .dump_as_pyc(&pyc, cfg.py_magic_num)
+        .and_then(|_| fs::write(&harness, script));
+    if let Err(err) = written {
+        return Err(format!("cannot write a temporary file: {err}"));
+    }
+    let _ = fs::remove_file(&results);
+    exec_pyc(
+        harness.to_string_lossy(),
+        cfg.py_command,
+        &[],
+        Stdio::inherit(),
+    );
+    let output = fs::read_to_string(&results);
+    for file in [&pyc, &harness, &results] {
+        let _ = fs::remove_file(file);
+    }
+    let Ok(output) = output else {
+        return Err("the harness was terminated abnormally".into());
+    };
+    let rows = output
+        .lines()
+        .map(|line| line.split('\t').map(unescape).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    match rows.first().map(|row| &row[..]) {
+        Some([status, error]) if status == "error" => Err(error.clone()),
+        _ => Ok(rows),
+    }
+}
+
+#[derive(Debug)]
+pub struct TestRunner {
+    cfg: ErgConfig,
+}
+
+impl TestRunner {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self { cfg }
+    }
+
+    /// Runs the tests in the file (that match `--filter`).
+    /// The file is compiled without optimization.
+    pub fn test_file(&self, path: &Path) -> FileTestResult {
+        let mut result = FileTestResult {
+            path: path.to_path_buf(),
+            results: vec![],
+            filtered_out: 0,
+            error: None,
+        };
+        let mut cfg = self.cfg.copy();
+        cfg.opt_level = 0;
+        let (code, hir) = match compile_file(&cfg, path) {
+            Ok(compiled) => compiled,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        let (tests, filtered_out): (Vec<_>, Vec<_>) = collect_tests(&hir, path)
+            .into_iter()
+            .partition(|case| case.matches(self.cfg.test_filter));
+        result.filtered_out = filtered_out.len();
+        if tests.is_empty() {
+            return result;
+        }
+        let rows = match run_harness(&cfg, path, code, &tests, &[], TEST_HARNESS) {
+            Ok(rows) => rows,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        for row in rows.iter() {
+            let [status, py_name, elapsed, message, left, right, output] = &row[..] else {
+                continue;
+            };
+            let Some(case) = tests.iter().find(|case| &case.py_name[..] == py_name) else {
+                continue;
+            };
+            let outcome = match &status[..] {
+                "ok" => TestOutcome::Passed,
+                "failed_eq" => TestOutcome::Failed {
+                    message: message.clone(),
+                    diff: Some((left.clone(), right.clone())),
+                },
+                _ => TestOutcome::Failed {
+                    message: message.clone(),
+                    diff: None,
+                },
+            };
+            result.results.push(TestResult {
+                case: case.clone(),
+                outcome,
+                elapsed: elapsed.parse().unwrap_or(0.0),
+                output: output.clone(),
+            });
+        }
+        result
+    }
+
+    /// The test targets. If no files are given, the package (`src` and `tests`) is tested.
+    fn targets(&self) -> Vec<PathBuf> {
+        let mut targets = if !self.cfg.targets.is_empty() {
+            expand_targets(&self.cfg.targets)
+        } else {
+            let mut targets = expand_targets(&[]);
+            if let Some(root) = package_root(".") {
+                targets.extend(erg_files(root.join("tests")));
+            }
+            targets
+        };
+        // the declaration files of Python modules cannot be run
+        targets.retain(|path| !path.to_string_lossy().ends_with(".d.er"));
+        targets
+    }
+
+    fn report(result: &FileTestResult) -> Vec<String> {
+        let mut failures = vec![];
+        let path = result.path.display();
+        if let Some(error) = &result.error {
+            println!("{RED}error{RESET}: {path}: {}", error.trim_end());
+            failures.push(format!("{path}"));
+        }
+        if result.results.is_empty() {
+            return failures;
+        }
+        println!("\nrunning {} test(s) in {path}", result.results.len());
+        for test in result.results.iter() {
+            if test.is_passed() {
+                println!("test {} ... {GREEN}ok{RESET}", test.case.name);
+            } else {
+                println!("test {} ... {RED}FAILED{RESET}", test.case.name);
+            }
+        }
+        for test in result.results.iter() {
+            let TestOutcome::Failed { message, diff } = &test.outcome else {
+                continue;
+            };
+            println!("\n---- {} ({path}:{}) ----", test.case.name, test.case.line);
+            if !test.output.is_empty() {
+                println!("---- stdout ----\n{}", test.output.trim_end());
+            }
+            print!("{message}");
+            if let Some((left, right)) = diff {
+                print!("{}", unified_diff(left, right, "left", "right", 3));
+            }
+            failures.push(format!("{}:{}", path, test.case.name));
+        }
+        failures
+    }
+
+    /// Runs the tests of the targets (or the package) and reports the results.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let runner = Self::new(cfg);
+        let targets = runner.targets();
+        if targets.is_empty() {
+            eprintln!("no files to test (not in a package, and no files are given)");
+            return ExitStatus::ERR1;
+        }
+        let coverage = runner.cfg.coverage.then(coverage_data_path);
+        if let Some(path) = &coverage {
+            // the records of the previous run are discarded
+            let _ = fs::remove_file(path);
+            if let Some(Err(err)) = path.parent().map(fs::create_dir_all) {
+                eprintln!("cannot create {}: {err}", path.display());
+                return ExitStatus::ERR1;
+            }
+        }
+        let start = Instant::now();
+        let (mut passed, mut filtered_out, mut errors) = (0, 0, 0);
+        let mut failures = vec![];
+        for path in targets.iter() {
+            let result = runner.test_file(path);
+            passed += result
+                .results
+                .iter()
+                .filter(|test| test.is_passed())
+                .count();
+            filtered_out += result.filtered_out;
+            errors += result.error.is_some() as usize;
+            failures.extend(Self::report(&result));
+        }
+        if !failures.is_empty() {
+            println!("\nfailures:");
+            for failure in failures.iter() {
+                println!("    {failure}");
+            }
+        }
+        let failed = failures.len() - errors;
+        let status = if failures.is_empty() {
+            format!("{GREEN}ok{RESET}")
+        } else {
+            format!("{RED}FAILED{RESET}")
+        };
+        println!(
+            "\ntest result: {status}. {passed} passed; {failed} failed; {filtered_out} filtered out; finished in {:.2}s",
+            start.elapsed().as_secs_f64()
+        );
+        if let Some(path) = coverage {
+            println!(
+                "coverage data is written to {} (see `erg coverage report`)",
+                path.display()
+            );
+        }
+        if failures.is_empty() {
+            ExitStatus::OK
+        } else {
+            ExitStatus::new(1, 0, failures.len())
+        }
+    }
+}
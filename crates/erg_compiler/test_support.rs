@@ -0,0 +1,157 @@
+//! Snapshot testing of diagnostics (enabled by the `test_support` feature).
+//!
+//! The diagnostics are rendered as `erg check` prints them, but without colors,
+//! and the parts that change whenever the compiler is modified are normalized:
+//!
+//! * the error numbers (`Error[#0341]` becomes `Error[#]`)
+//! * the ids of the type variables (`?123`, `%123`) and the addresses (see `erg_common::deterministic::normalize`)
+//! * the trailing whitespaces
+//!
+//! The renderings depend on the language features (`japanese`, etc.) and the `unicode`/`pretty` features.
+//! The snapshots in this crate assume the default features.
+//!
+//! ```
+//! # use erg_compiler::test_support::check;
+//! let diags = check("x: Int = \"a\"");
+//! assert_eq!(diags.errors.len(), 1);
+//! assert!(diags.errors[0].starts_with("Error[#]: File <string>, line 1, <module>"));
+//! ```
+use erg_common::config::ErgConfig;
+use erg_common::deterministic;
+use erg_common::error::ErrorDisplay;
+use erg_common::io::{Input, Output};
+use erg_common::traits::Runnable;
+
+use crate::build_hir::HIRBuilder;
+use crate::error::{CompileError, CompileErrors};
+
+/// The rendered diagnostics of a compilation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub errors: Vec<String>,
+    pub warns: Vec<String>,
+}
+
+/// Removes ANSI escape sequences (colors and attributes).
+pub fn strip_ansi(s: &str) -> String {
+    let mut stripped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // CSI sequences end with an alphabetic character
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Replaces the error number in the header (e.g. `Error[#0341]`) with `#`.
+fn normalize_errno(header: &str) -> String {
+    let Some(pos) = header.find('#') else {
+        return header.to_string();
+    };
+    let digits = header[pos + 1..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(header.len() - pos - 1);
+    format!("{}#{}", &header[..pos], &header[pos + 1 + digits..])
+}
+
+/// Normalizes a rendered diagnostic (see the module documentation).
+pub fn normalize(rendered: &str) -> String {
+    let stripped = strip_ansi(rendered);
+    let normalized = deterministic::normalize(&stripped);
+    let mut lines = normalized.lines().map(str::trim_end);
+    let mut result = lines.next().map(normalize_errno).unwrap_or_default();
+    for line in lines {
+        result.push('\n');
+        result.push_str(line);
+    }
+    result.trim_end().to_string()
+}
+
+/// Renders a diagnostic as `erg check` prints it, and normalizes it.
+pub fn render(err: &CompileError) -> String {
+    normalize(&err.show())
+}
+
+pub fn render_all(errs: &CompileErrors) -> Vec<String> {
+    errs.iter().map(render).collect()
+}
+
+/// Checks `src` (as a module) and renders the diagnostics.
+pub fn check(src: &str) -> Diagnostics {
+    check_with(ErgConfig::default(), src)
+}
+
+/// Same as `check`, but with the configuration (e.g. to enable lints).
+/// `cfg.input` and `cfg.output` are overwritten.
+pub fn check_with(cfg: ErgConfig, src: &str) -> Diagnostics {
+    let cfg = ErgConfig {
+        input: Input::str(src.to_string()),
+        output: Output::Null,
+        ..cfg
+    };
+    let mut builder = HIRBuilder::new(cfg);
+    match builder.build(src.to_string(), "exec") {
+        Ok(artifact) => Diagnostics {
+            errors: vec![],
+            warns: render_all(&artifact.warns),
+        },
+        Err(artifact) => Diagnostics {
+            errors: render_all(&artifact.errors),
+            warns: render_all(&artifact.warns),
+        },
+    }
+}
+
+/// Asserts that the rendering of `err` is `expected`.
+/// Both are compared after normalization, so `expected` may contain trailing whitespaces.
+#[track_caller]
+pub fn assert_rendered(err: &CompileError, expected: &str) {
+    let actual = render(err);
+    let expected = normalize(expected);
+    assert!(
+        actual == expected,
+        "the rendering of the diagnostic differs from the snapshot\n--- expected\n{expected}\n--- actual\n{actual}\n"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let rendered = "\x1b[91m\x1b[1mError[#2057]\x1b[m: File <string>, line 1, <module>  \n\n1 | f x\n\nTypeError: ?52 and ?61(<: ?52)\n\n";
+        assert_eq!(
+            normalize(rendered),
+            "Error[#]: File <string>, line 1, <module>\n\n1 | f x\n\nTypeError: ?1 and ?2(<: ?1)"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let diags = check("x: Int = \"a\"\nprint! x\n");
+        assert_eq!(diags.warns, Vec::<String>::new());
+        assert_eq!(
+            diags.errors,
+            vec![
+                r#"Error[#]: File <string>, line 1, <module>::x
+
+1 | x: Int = "a"
+  : ------
+  :      |- expected: Int
+  :      `- but found: {"a"}
+
+TypeError: the type of x is mismatched"#
+            ]
+        );
+        assert_eq!(check("print! 1\n"), Diagnostics::default());
+    }
+}
@@ -1,19 +1,42 @@
-use erg_common::config::ErgConfig;
-use erg_common::error::MultiErrorDisplay;
-use erg_common::io::Output;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::{EmitKind, EmitTarget, ErgConfig};
+use erg_common::error::{ErrorKind, MultiErrorDisplay};
+use erg_common::fresh::SharedFreshNameGenerator;
+use erg_common::io::{Input, Output};
+use erg_common::python_util::{env_magic_number, exec_py, exec_py_code};
+use erg_common::serialize::get_source_hash_bytes;
 use erg_common::set;
+use erg_common::shared::Shared;
 use erg_common::spawn::exec_new_thread;
-use erg_common::traits::Runnable;
+use erg_common::traits::{Runnable, Stream};
 
+use erg_compiler::batch::BatchCompiler;
+use erg_compiler::bench::{format_duration, BenchOptions, BenchRunner};
+use erg_compiler::check::PackageChecker;
 use erg_compiler::context::{Context, ModuleContext};
+use erg_compiler::coverage::{coverage_data_path, CoverageData};
+use erg_compiler::doc::{to_json, DocGenerator, DocIndex, DocItemKind};
+use erg_compiler::emit::Emitter;
 use erg_compiler::error::CompileErrors;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::minimize::{CrashSignature, Minimizer};
+use erg_compiler::module::{
+    ModuleCache, RefKind, SharedCompileErrors, SharedCompilerResource, SharedModuleCache,
+};
+use erg_compiler::stable_id::StableDefId;
+use erg_compiler::test_runner::{TestOutcome, TestRunner};
+use erg_compiler::writer::ArtifactWriter;
+use erg_compiler::Compiler;
 
+use erg_compiler::ty::codeobj::CodeObj;
 use erg_compiler::ty::constructors::{
     array_t, func0, func1, func2, kw, mono, nd_func, nd_proc, or, poly, proc1, subtype_q, ty_tp,
     type_q, v_enum,
 };
-use erg_compiler::ty::Type::*;
+use erg_compiler::ty::value::ValueObj;
+use erg_compiler::ty::Type::{self, *};
 
 fn load_file(path: &'static str) -> Result<ModuleContext, CompileErrors> {
     let mut cfg = ErgConfig::with_main_path(path.into());
@@ -102,6 +125,611 @@ fn test_intersection() -> Result<(), ()> {
     Ok(())
 }
 
+#[test]
+fn test_unification_properties() -> Result<(), ()> {
+    let context = Context::default_with_name("<module>");
+    context.test_unification_properties()?;
+    Ok(())
+}
+
+#[test]
+fn test_completion_candidates() -> Result<(), ()> {
+    exec_new_thread(_test_completion_candidates, "test_completion_candidates")
+}
+
+fn _test_completion_candidates() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let mut lowerer = ASTLowerer::new(cfg);
+    lowerer
+        .eval("longname = 1\nrec = {.foo = 1; .bar = 2; baz = 3}".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    assert_eq!(lowerer.complete("print! longn"), vec!["longname"]);
+    let attrs = lowerer.complete("rec.");
+    assert!(attrs.contains(&"foo".to_string()) && !attrs.contains(&"baz".to_string()));
+    assert_eq!(lowerer.complete("rec.f"), vec!["foo"]);
+    assert!(lowerer.complete("longname.").contains(&"abs".to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_repl_commands() -> Result<(), ()> {
+    exec_new_thread(_test_repl_commands, "test_repl_commands")
+}
+
+fn _test_repl_commands() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let mut lowerer = ASTLowerer::new(cfg);
+    lowerer
+        .eval("x = 1".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    let t = lowerer
+        .infer_type("x + 1.0".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    assert_eq!(t, Float);
+    assert!(lowerer.infer_type("y = 1".into()).is_err());
+    assert!(lowerer.infer_type("undefined_name".into()).is_err());
+    let info = lowerer.describe("Nat").unwrap();
+    assert!(info.starts_with("Nat: ") && info.contains("inherits: Int"));
+    assert!(lowerer.describe("undefined_name").is_none());
+    assert_eq!(
+        lowerer.exec_repl_command(":t x").unwrap().ok(),
+        Some("x: {1}".to_string())
+    );
+    assert!(lowerer.exec_repl_command(":unknown").is_none());
+    Ok(())
+}
+
+#[test]
+fn test_package_checker() -> Result<(), ()> {
+    exec_new_thread(_test_package_checker, "test_package_checker")
+}
+
+fn _test_package_checker() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        targets: vec!["tests/*.er".into(), "tests/not_exist.er".into()],
+        ..ErgConfig::default()
+    };
+    let mut checker = PackageChecker::new(cfg);
+    let targets = checker.collect_targets();
+    assert!(targets.contains(&PathBuf::from("tests/infer.er")));
+    assert!(targets.contains(&PathBuf::from("tests/fib.er")));
+    assert_eq!(targets.last(), Some(&PathBuf::from("tests/not_exist.er")));
+    let results = checker.check_files(&["tests/infer.er", "tests/not_exist.er"]);
+    assert!(results[0].is_ok());
+    assert!(!results[1].is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_check_buffer() -> Result<(), ()> {
+    exec_new_thread(_test_check_buffer, "test_check_buffer")
+}
+
+/// `erg check - --path tests/buffer.er`
+fn _test_check_buffer() -> Result<(), ()> {
+    let src = "infer = import \"infer\"\nx: Int = \"a\"\n".to_string();
+    let cfg = ErgConfig {
+        output: Output::Null,
+        input: Input::buffer(PathBuf::from("tests/buffer.er"), src.clone()),
+        ..ErgConfig::default()
+    };
+    assert!(cfg.dumps_to_stdout());
+    let mut checker = PackageChecker::new(cfg);
+    // `infer` is resolved relative to the path
+    let result = checker.check_src(src);
+    assert_eq!(result.errors.len(), 1, "{}", result.errors);
+    assert_eq!(result.errors[0].input.path(), Path::new("tests/buffer.er"));
+    Ok(())
+}
+
+#[test]
+fn test_stable_ids() -> Result<(), ()> {
+    exec_new_thread(_test_stable_ids, "test_stable_ids")
+}
+
+/// The IDs of the definitions do not change when the lines above are edited.
+fn _test_stable_ids() -> Result<(), ()> {
+    let check = |src: &str| {
+        let cfg = ErgConfig {
+            output: Output::Null,
+            input: Input::buffer(PathBuf::from("tests/stable_ids.er"), src.to_string()),
+            ..ErgConfig::default()
+        };
+        let mut lowerer = ASTLowerer::new(cfg);
+        lowerer.exec().map_err(|errs| errs.write_all_stderr())?;
+        let (_, vi) = lowerer.get_var_info("f").ok_or(())?;
+        let id = vi.stable_id("f");
+        let def_loc = lowerer.get_mod_ctx().context.index().get_def_loc(&id);
+        assert_eq!(def_loc.as_ref(), Some(&vi.def_loc));
+        Ok((id, vi.def_loc.clone()))
+    };
+    let (id, def_loc) = check("f x = x\ng = f 1\n")?;
+    let (moved_id, moved_loc) = check("# comment\n\nf x = x\ng = f 1\n")?;
+    assert_eq!(id, moved_id);
+    assert_ne!(def_loc, moved_loc);
+    assert_eq!(id.to_string().parse::<StableDefId>(), Ok(id));
+    Ok(())
+}
+
+#[test]
+fn test_ref_kinds() -> Result<(), ()> {
+    exec_new_thread(_test_ref_kinds, "test_ref_kinds")
+}
+
+/// The uses in the usage index are tagged with their kinds and can be removed per definition.
+fn _test_ref_kinds() -> Result<(), ()> {
+    let path = PathBuf::from("tests/ref_kinds.er");
+    let cfg = ErgConfig {
+        output: Output::Null,
+        input: Input::buffer(
+            path.clone(),
+            "{Point;} = import \"doc\"\np = Point.new { .x = 1.0; .y = 2.0 }\n".into(),
+        ),
+        ..ErgConfig::default()
+    };
+    let mut lowerer = ASTLowerer::new(cfg);
+    lowerer.exec().map_err(|errs| errs.write_all_stderr())?;
+    let index = lowerer.get_mod_ctx().context.index();
+    let kinds_in = |ln: u32| {
+        index
+            .members()
+            .values()
+            .flat_map(|value| {
+                value
+                    .referrers
+                    .iter()
+                    .filter(|referrer| {
+                        referrer.module.as_deref() == Some(path.as_path())
+                            && referrer.loc.ln_begin() == Some(ln)
+                    })
+                    .map(|referrer| (value.name.to_string(), value.ref_kind(referrer)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert!(kinds_in(1).contains(&("Point".to_string(), RefKind::Import)));
+    assert!(kinds_in(2).contains(&("Point".to_string(), RefKind::Read)));
+    index.remove_refs_in(&path, 2..=2);
+    assert!(kinds_in(2).is_empty());
+    assert!(!kinds_in(1).is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_cancelled_compilation() -> Result<(), ()> {
+    exec_new_thread(_test_cancelled_compilation, "test_cancelled_compilation")
+}
+
+fn _test_cancelled_compilation() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    cfg.cancel.cancel();
+    let mut compiler = Compiler::new(cfg);
+    // a cancelled compilation reports only the cancellation (not the type error)
+    let errs = compiler
+        .compile("x: Int = \"a\"\n".into(), "exec")
+        .unwrap_err()
+        .errors;
+    assert_eq!(errs.len(), 1, "{errs}");
+    assert!(errs[0].core.is_cancellation());
+    Ok(())
+}
+
+#[test]
+fn test_doc_generator() -> Result<(), ()> {
+    exec_new_thread(_test_doc_generator, "test_doc_generator")
+}
+
+fn _test_doc_generator() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let mut generator = DocGenerator::new(cfg);
+    let (modules, results) = generator.document_files(&[PathBuf::from("tests/doc.er")]);
+    assert!(results[0].is_ok());
+    let module = &modules[0];
+    assert_eq!(module.name, "doc");
+    assert_eq!(module.doc, "Geometric shapes.");
+    let names = module
+        .items
+        .iter()
+        .map(|item| (&item.name[..], item.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("HasArea", DocItemKind::Trait),
+            ("Point", DocItemKind::Class),
+            ("Circle", DocItemKind::Class),
+            ("area", DocItemKind::Function),
+        ]
+    );
+    let point = &module.items[1];
+    assert_eq!(point.doc, "A point in 2D space.");
+    let norm = point
+        .members
+        .iter()
+        .find(|m| &m.name[..] == "norm")
+        .unwrap();
+    assert_eq!(norm.doc, "The distance from the origin.");
+    let circle = &module.items[2];
+    assert_eq!(circle.impls, vec!["doc.HasArea"]);
+    assert_eq!(circle.trait_impls[0].trait_, "doc.HasArea");
+    assert_eq!(&circle.trait_impls[0].methods[0].name[..], "area");
+    let area = &module.items[3];
+    assert_eq!(area.signature, "(c: doc.Circle) -> Float");
+    let index = DocIndex::new(&modules);
+    assert_eq!(index.resolve("doc.Circle").unwrap(), "doc.html#Circle");
+    let json = to_json(&modules, &index);
+    assert!(json.contains(r#"{"name":"doc.Circle","url":"doc.html#Circle"}"#));
+    Ok(())
+}
+
+#[test]
+fn test_emit() -> Result<(), ()> {
+    exec_new_thread(_test_emit, "test_emit")
+}
+
+fn _test_emit() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        emit: EmitTarget::parse_list("pyi,ast,typed-hir=-").unwrap(),
+        ..ErgConfig::with_main_path(PathBuf::from("tests/doc.er"))
+    };
+    let mut emitter = Emitter::new(cfg);
+    let artifact = emitter
+        .emit(fs::read_to_string("tests/doc.er").unwrap())
+        .unwrap();
+    // in the order of the stages
+    let kinds = artifact
+        .object
+        .iter()
+        .map(|emitted| emitted.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![EmitKind::Ast, EmitKind::TypedHir, EmitKind::Pyi]
+    );
+    let pyi = String::from_utf8(artifact.object[2].content.clone()).unwrap();
+    assert!(pyi.contains("class Point:\n    def norm(self) -> float: ..."));
+    assert!(pyi.contains("def area(c: Circle) -> float: ..."));
+    assert!(!pyi.contains("private"));
+    // `--emit=ast` does not type-check the code
+    let cfg = ErgConfig {
+        output: Output::Null,
+        emit: EmitTarget::parse_list("ast=-").unwrap(),
+        ..ErgConfig::default()
+    };
+    let artifact = Emitter::new(cfg).emit("x: Int = \"a\"".into()).unwrap();
+    assert_eq!(artifact.object.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_test_runner() -> Result<(), ()> {
+    exec_new_thread(_test_test_runner, "test_test_runner")
+}
+
+fn _test_test_runner() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let runner = TestRunner::new(cfg);
+    let result = runner.test_file(Path::new("tests/testing.er"));
+    assert_eq!(result.error, None);
+    let outcomes = result
+        .results
+        .iter()
+        .map(|test| (&test.case.name[..], test.is_passed()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("check_add", true),
+            ("test_add_wrong!", false),
+            ("check_lines", false)
+        ]
+    );
+    assert_eq!(result.results[1].output, "adding\n");
+    let TestOutcome::Failed { message, diff } = &result.results[2].outcome else {
+        unreachable!()
+    };
+    assert!(message.contains("lines differ"));
+    assert_eq!(diff, &Some(("a\nb".into(), "a\nc".into())));
+    let cfg = ErgConfig {
+        test_filter: Some("add"),
+        ..ErgConfig::default()
+    };
+    let result = TestRunner::new(cfg).test_file(Path::new("tests/testing.er"));
+    assert_eq!(result.results.len(), 2);
+    assert_eq!(result.filtered_out, 1);
+    Ok(())
+}
+
+#[test]
+fn test_batch_compiler() -> Result<(), ()> {
+    exec_new_thread(_test_batch_compiler, "test_batch_compiler")
+}
+
+fn _test_batch_compiler() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        targets: vec![PathBuf::from("tests/batch")],
+        ..ErgConfig::default()
+    };
+    let mut compiler = BatchCompiler::new(cfg.copy());
+    let targets = compiler.collect_targets();
+    assert_eq!(
+        targets,
+        ["lib.er", "main.er", "util.er"].map(|file| Path::new("tests/batch").join(file))
+    );
+    let results = compiler.compile_files(&targets);
+    assert!(results
+        .iter()
+        .all(|result| result.is_ok() && result.code.is_some()));
+    // `util` is linked into `main` (it is not removed from the cache by compiling `lib`)
+    fn linked_files(code: &CodeObj, files: &mut Vec<String>) {
+        for obj in code.consts.iter() {
+            if let ValueObj::Code(code) = obj {
+                files.push(code.filename.to_string());
+                linked_files(code, files);
+            }
+        }
+    }
+    let mut files = vec![];
+    linked_files(results[1].code.as_ref().unwrap(), &mut files);
+    assert!(files.iter().any(|file| file.ends_with("util.er")));
+    // no code is generated if any of the files has errors
+    let mut compiler = BatchCompiler::new(cfg);
+    let results =
+        compiler.compile_files(&["tests/batch/main.er", "../../tests/should_err/addition.er"]);
+    assert!(results[0].is_ok() && !results[1].is_ok());
+    assert!(results.iter().all(|result| result.code.is_none()));
+    Ok(())
+}
+
+#[test]
+fn test_compile_with_script() -> Result<(), ()> {
+    exec_new_thread(_test_compile_with_script, "test_compile_with_script")
+}
+
+fn _test_compile_with_script() -> Result<(), ()> {
+    // `with_py.er` uses the constructs that the backends emit differently (`?`, guards, comprehensions)
+    for name in ["script", "with_py"] {
+        let input = PathBuf::from(format!("tests/{name}.er"));
+        let cfg = ErgConfig {
+            output: Output::Null,
+            with_py: true,
+            dist_dir: Some("build/with_py"),
+            ..ErgConfig::with_main_path(input.clone())
+        };
+        let py_path = PathBuf::from(format!("build/with_py/{name}.py"));
+        let pyc_path = cfg.cache_pyc_path();
+        assert_eq!(
+            pyc_path.parent().unwrap(),
+            Path::new("build/with_py/__pycache__")
+        );
+        let src = fs::read_to_string(&input).unwrap();
+        let result =
+            Compiler::new(cfg).compile_and_dump_with_script(&py_path, &pyc_path, src, "exec");
+        assert!(result.is_ok(), "{name}");
+        // the .pyc is valid only for the .py written with it
+        let script = fs::read(&py_path).unwrap();
+        let pyc = fs::read(&pyc_path).unwrap();
+        assert_eq!(pyc[4..8], [3, 0, 0, 0]);
+        assert_eq!(
+            pyc[8..16],
+            get_source_hash_bytes(env_magic_number(), &script)
+        );
+        // the .pyc is loaded by `import`, and the .py is executed directly
+        let status = exec_py_code(
+            &format!("import sys; sys.path.insert(0, 'build/with_py'); import {name}"),
+            &[],
+            Output::Null,
+        )
+        .unwrap();
+        assert!(status.success(), "{name}");
+        assert_eq!(exec_py(&py_path.to_string_lossy()), Some(0), "{name}");
+    }
+    let _ = fs::remove_dir_all("build/with_py");
+    Ok(())
+}
+
+#[test]
+fn test_artifact_writer() -> Result<(), ()> {
+    let dir = Path::new("build/writer");
+    let _ = fs::remove_dir_all(dir);
+    let pyc = dir.join("x.pyc");
+    // `--dry-run` checks the path without writing anything
+    let cfg = ErgConfig {
+        dry_run: true,
+        quiet: true,
+        ..ErgConfig::default()
+    };
+    let writer = ArtifactWriter::new(&cfg);
+    let err = writer.write(&pyc, b"").unwrap_err();
+    assert_eq!(err.core.kind, ErrorKind::IoError);
+    assert!(err.core.get_hint().unwrap().contains("does not exist"));
+    assert!(writer.create_dir_all(dir).is_ok());
+    assert!(writer.write(&pyc, b"").is_ok());
+    assert!(!dir.exists());
+    // the IO failures are reported instead of panicking
+    let cfg = ErgConfig {
+        output: Output::Null,
+        dist_dir: Some("build/writer"),
+        ..ErgConfig::with_main_path(PathBuf::from("tests/script.er"))
+    };
+    let src = fs::read_to_string("tests/script.er").unwrap();
+    let pyc = cfg.dump_pyc_path();
+    let result = exec_new_thread(
+        move || {
+            let err = Compiler::new(cfg)
+                .compile_and_dump_as_pyc(&pyc, src, "exec")
+                .unwrap_err();
+            let err = err.errors.first().unwrap();
+            assert_eq!(err.core.kind, ErrorKind::IoError);
+            assert!(err.core.main_message.contains("build/writer/script.pyc"));
+            Ok(())
+        },
+        "test_artifact_writer",
+    );
+    let writer = ArtifactWriter::new(&ErgConfig::default());
+    writer.create_dir_all(dir).unwrap();
+    writer.write(&dir.join("x.pyc"), b"").unwrap();
+    assert!(dir.join("x.pyc").is_file());
+    let _ = fs::remove_dir_all(dir);
+    result
+}
+
+#[test]
+fn test_bench_runner() -> Result<(), ()> {
+    exec_new_thread(_test_bench_runner, "test_bench_runner")
+}
+
+fn _test_bench_runner() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let options = BenchOptions {
+        warmup_time: 0.01,
+        measurement_time: 0.05,
+        samples: 5,
+    };
+    let runner = BenchRunner::with_options(cfg, options);
+    let result = runner.bench_file(Path::new("tests/bench.er"));
+    assert_eq!(result.error, None);
+    assert_eq!(result.results.len(), 2);
+    let stats = result.results[0].outcome.as_ref().unwrap();
+    assert_eq!(&result.results[0].case.name[..], "fib10");
+    assert_eq!(stats.samples, 5);
+    assert!(stats.min <= stats.median && stats.median <= stats.max);
+    let message = result.results[1].outcome.as_ref().unwrap_err();
+    assert!(message.contains("oops"));
+    assert_eq!(format_duration(0.0012), "1.20 ms");
+    Ok(())
+}
+
+#[test]
+fn test_coverage() -> Result<(), ()> {
+    exec_new_thread(_test_coverage, "test_coverage")
+}
+
+fn _test_coverage() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        coverage: true,
+        ..ErgConfig::default()
+    };
+    let path = coverage_data_path();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let _ = fs::remove_file(&path);
+    let result = TestRunner::new(cfg).test_file(Path::new("tests/coverage.er"));
+    let records = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+    assert_eq!(result.error, None);
+    let data = CoverageData::parse(&records);
+    let file = &data.files[&Path::new("tests/coverage.er").canonicalize().unwrap()];
+    assert_eq!(file.executable, [1, 2, 4, 6, 8, 10, 11].into());
+    assert_eq!(file.missing(), vec![(6, 6)]);
+    assert_eq!(data.total(), (7, 6));
+    // the records of the harnesses are merged
+    let data = CoverageData::parse("a.er\t1,2,3\t1\na.er\t1,2,3\t3\nmalformed\n");
+    assert_eq!(data.files.len(), 1);
+    assert_eq!(data.files[Path::new("a.er")].missing(), vec![(2, 2)]);
+    Ok(())
+}
+
+#[test]
+fn test_minimizer() -> Result<(), ()> {
+    exec_new_thread(_test_minimizer, "test_minimizer")
+}
+
+fn _test_minimizer() -> Result<(), ()> {
+    let path = PathBuf::from("tests/minimize.er");
+    let cfg = ErgConfig {
+        input: Input::file(path.clone()),
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let mut minimizer = Minimizer::new(cfg);
+    let src = fs::read_to_string(&path).unwrap();
+    let minimized = minimizer.minimize(&src).unwrap();
+    assert_eq!(
+        minimized.signature,
+        CrashSignature::Error(
+            "TypeError: the type of g::s (the 1st argument) is mismatched".into()
+        )
+    );
+    assert_eq!(minimized.src, "x = 1\ng(s: Str): Str = s\nprint! g(x)\n");
+    assert!(!minimized.cancelled);
+    assert!(minimizer.minimize("x = 1\nprint! x\n").is_err());
+    assert_eq!(
+        Minimizer::output_path(&path),
+        PathBuf::from("tests/minimize.min.er")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_redefinition_after_remove_defs() -> Result<(), ()> {
+    exec_new_thread(
+        _test_redefinition_after_remove_defs,
+        "test_redefinition_after_remove_defs",
+    )
+}
+
+fn _test_redefinition_after_remove_defs() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    };
+    let mut lowerer = ASTLowerer::new(cfg);
+    lowerer
+        .eval("x = 1\nC = Class { .a = Int }".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    assert!(lowerer.eval("x = \"a\"".into()).is_err());
+    lowerer.clear();
+    lowerer.remove_defs(&["x".into(), "C".into()]);
+    assert!(!lowerer.module_names().contains(&"x".to_string()));
+    lowerer
+        .eval("x = \"a\"\nC = Class { .b = Str }\nc = C.new { .b = x }".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    lowerer
+        .eval("math = pyimport \"math\"".into())
+        .map_err(|errs| errs.write_all_stderr())?;
+    assert!(lowerer
+        .browse("math")
+        .unwrap()
+        .contains("sqrt: (Float) -> Float"));
+    Ok(())
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+/// The shared primitives can be passed to other threads (e.g. the handlers of the language server).
+#[test]
+fn test_send_sync() {
+    assert_send_sync::<Shared<ModuleCache>>();
+    assert_send_sync::<SharedFreshNameGenerator>();
+    assert_send_sync::<SharedCompileErrors>();
+    assert_send_sync::<SharedModuleCache>();
+    assert_send_sync::<SharedCompilerResource>();
+    assert_send_sync::<Type>();
+    assert_send_sync::<Compiler>();
+}
+
 /*
 #[test]
 fn test_patch() -> Result<(), ()> {
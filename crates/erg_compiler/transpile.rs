@@ -1,10 +1,8 @@
-use std::fs::File;
-use std::io::Write;
-
 use erg_common::config::ErgConfig;
 use erg_common::dict::Dict as HashMap;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::log;
+use erg_common::timing::{Phase, TIMINGS};
 use erg_common::traits::{ExitStatus, Runnable, Stream};
 use erg_common::Str;
 
@@ -26,8 +24,47 @@ use crate::hir::{
 use crate::link_hir::HIRLinker;
 use crate::module::SharedCompilerResource;
 use crate::ty::value::ValueObj;
-use crate::ty::Type;
+use crate::ty::{HasType, Type};
 use crate::varinfo::VarInfo;
+use crate::visitor::{walk_unary_op, Visitor};
+use crate::writer::ArtifactWriter;
+
+/// `?` cannot be a statement in expressions (and the blocks of `if`, `match` are transpiled into functions),
+/// so it raises `EarlyReturn__` and the subroutine returns the value of the exception.
+const EARLY_RETURN: &str = "class EarlyReturn__(Exception):
+    def __init__(self, value):
+        self.value = value
+def try__(obj):
+    if is_failure(obj):
+        raise EarlyReturn__(obj)
+    return obj
+def catch_early_return__(f):
+    def wrapper(*args, **kwargs):
+        try:
+            return f(*args, **kwargs)
+        except EarlyReturn__ as err:
+            return err.value
+    return wrapper
+";
+
+/// Finds `?` in a subroutine
+#[derive(Default)]
+struct EarlyReturnFinder {
+    found: bool,
+}
+
+impl<'a> Visitor<'a> for EarlyReturnFinder {
+    fn visit_unary_op(&mut self, unary: &'a UnaryOp) {
+        self.found |= unary.op.is(TokenKind::Try);
+        walk_unary_op(self, unary);
+    }
+}
+
+fn has_early_return(block: &Block) -> bool {
+    let mut finder = EarlyReturnFinder::default();
+    finder.visit_block(block);
+    finder.found
+}
 
 /// patch method -> function
 /// patch attr -> variable
@@ -89,6 +126,8 @@ impl LastLineOperation {
 pub struct PyScript {
     pub filename: Str,
     pub code: String,
+    /// the length of the prelude (the definitions of the runtime, e.g. `Nat`, `if__`) at the beginning of `code`
+    pub prelude_len: usize,
 }
 
 /// Generates a `PyScript` from an String or other File inputs.
@@ -152,8 +191,13 @@ impl Runnable for Transpiler {
             eart.errors
         })?;
         artifact.warns.write_all_stderr();
-        let mut f = File::create(path).unwrap();
-        f.write_all(artifact.object.code.as_bytes()).unwrap();
+        if self.cfg.dumps_to_stdout() {
+            print!("{}", artifact.object.code);
+        } else {
+            ArtifactWriter::new(&self.cfg)
+                .write(&path, artifact.object.code.as_bytes())
+                .map_err(CompileErrors::from)?;
+        }
         Ok(ExitStatus::compile_passed(artifact.warns.len()))
     }
 
@@ -165,6 +209,22 @@ impl Runnable for Transpiler {
         artifact.warns.write_all_stderr();
         Ok(artifact.object.code)
     }
+
+    fn complete(&self, code: &str) -> Vec<String> {
+        self.completion_candidates(code)
+    }
+
+    fn exec_command(&mut self, command: &str) -> Option<Result<String, Self::Errs>> {
+        self.builder.exec_command(command)
+    }
+
+    fn module_names(&self) -> Vec<String> {
+        self.builder.module_names()
+    }
+
+    fn remove_defs(&mut self, names: &[String]) {
+        self.builder.remove_defs(names)
+    }
 }
 
 impl ContextProvider for Transpiler {
@@ -217,7 +277,9 @@ impl Transpiler {
     ) -> Result<CompleteArtifact<PyScript>, ErrorArtifact> {
         log!(info "the transpiling process has started.");
         let artifact = self.build_link_desugar(src, mode)?;
-        let script = self.script_generator.transpile(artifact.object);
+        let script = TIMINGS.measure(Phase::CodeGen, self.cfg.input.path(), || {
+            self.script_generator.transpile(artifact.object)
+        });
         log!(info "code:\n{}", script.code);
         log!(info "the transpiling process has completed");
         Ok(CompleteArtifact::new(script, artifact.warns))
@@ -234,9 +296,10 @@ impl Transpiler {
         mode: &str,
     ) -> Result<CompleteArtifact, ErrorArtifact> {
         let artifact = self.builder.build(src, mode)?;
+        let path = self.cfg.input.path();
         let linker = HIRLinker::new(&self.cfg, &self.shared.mod_cache);
-        let hir = linker.link(artifact.object);
-        let desugared = HIRDesugarer::desugar(hir);
+        let hir = TIMINGS.measure(Phase::Link, path, || linker.link(artifact.object));
+        let desugared = TIMINGS.measure(Phase::Desugar, path, || HIRDesugarer::desugar(hir));
         Ok(CompleteArtifact::new(desugared, artifact.warns))
     }
 
@@ -268,6 +331,8 @@ pub struct ScriptGenerator {
     builtin_types_loaded: bool,
     builtin_control_loaded: bool,
     convertors_loaded: bool,
+    lru_cache_loaded: bool,
+    early_return_loaded: bool,
     prelude: String,
 }
 
@@ -283,6 +348,8 @@ impl ScriptGenerator {
             builtin_types_loaded: false,
             builtin_control_loaded: false,
             convertors_loaded: false,
+            lru_cache_loaded: false,
+            early_return_loaded: false,
             prelude: String::new(),
         }
     }
@@ -293,10 +360,12 @@ impl ScriptGenerator {
             code += &self.transpile_expr(chunk);
             code.push('\n');
         }
+        let prelude_len = self.prelude.len();
         code = std::mem::take(&mut self.prelude) + &code;
         PyScript {
             filename: hir.name,
             code,
+            prelude_len,
         }
     }
 
@@ -316,6 +385,7 @@ impl ScriptGenerator {
             .replace("from _erg_range import Range", "")
             .replace("from _erg_result import Error", "")
             .replace("from _erg_result import is_ok", "")
+            .replace("from _erg_result import is_failure", "")
             .replace("from _erg_control import then__", "")
     }
 
@@ -388,6 +458,22 @@ impl ScriptGenerator {
         }
     }
 
+    fn load_lru_cache_if_not(&mut self) {
+        if !self.lru_cache_loaded {
+            self.prelude += "from functools import lru_cache as lru_cache__\n";
+            self.lru_cache_loaded = true;
+        }
+    }
+
+    fn load_early_return_if_not(&mut self) {
+        if !self.early_return_loaded {
+            // `is_failure` is defined in `_erg_result.py`
+            self.load_builtin_types_if_not();
+            self.prelude += EARLY_RETURN;
+            self.early_return_loaded = true;
+        }
+    }
+
     fn load_convertors_if_not(&mut self) {
         if !self.convertors_loaded {
             self.prelude += &Self::replace_import(include_str!("lib/std/_erg_convertors.py"));
@@ -418,6 +504,11 @@ impl ScriptGenerator {
                     code += "]";
                     code
                 }
+                Array::Comprehension(arr) => {
+                    let elem = self.transpile_expr(*arr.elem);
+                    let clauses = self.transpile_comprehension_clauses(arr.generators, arr.guards);
+                    format!("[{elem}{clauses}]")
+                }
                 other => todo!("transpiling {other}"),
             },
             Expr::Set(set) => match set {
@@ -429,6 +520,11 @@ impl ScriptGenerator {
                     code += "}";
                     code
                 }
+                Set::Comprehension(st) => {
+                    let elem = self.transpile_expr(*st.elem);
+                    let clauses = self.transpile_comprehension_clauses(st.generators, st.guards);
+                    format!("{{{elem}{clauses}}}")
+                }
                 other => todo!("transpiling {other}"),
             },
             Expr::Record(rec) => self.transpile_record(rec),
@@ -455,7 +551,12 @@ impl ScriptGenerator {
                     code += "}";
                     code
                 }
-                other => todo!("transpiling {other}"),
+                Dict::Comprehension(dic) => {
+                    let key = self.transpile_expr(*dic.key);
+                    let value = self.transpile_expr(*dic.value);
+                    let clauses = self.transpile_comprehension_clauses(dic.generators, dic.guards);
+                    format!("{{({key}): ({value}){clauses}}}")
+                }
             },
             Expr::Accessor(acc) => self.transpile_acc(acc),
             Expr::Def(def) => self.transpile_def(def),
@@ -487,11 +588,38 @@ impl ScriptGenerator {
         }
     }
 
+    /// `x <- xs; y <- ys; guard` => ` for x in xs for y in ys if guard`
+    fn transpile_comprehension_clauses(
+        &mut self,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+    ) -> String {
+        let mut code = String::new();
+        for (var, iter) in generators {
+            code += &format!(
+                " for {} in ({})",
+                Self::transpile_ident(var),
+                self.transpile_expr(iter)
+            );
+        }
+        for guard in guards {
+            code += &format!(" if ({})", self.transpile_expr(guard));
+        }
+        code
+    }
+
     fn transpile_lit(&mut self, lit: Literal) -> String {
+        // the tokens of interpolated strings (`"\{x} and \{y}"`) are not string literals by themselves,
+        // so the literal is built from the value
+        if let ValueObj::Str(s) = &lit.value {
+            self.load_builtin_types_if_not();
+            let escaped = Self::escape_str(&s.replace('\\', "\\\\")).replace('"', "\\\"");
+            return format!("Str(\"{escaped}\")");
+        }
         let escaped = Self::escape_str(&lit.token.content);
         if matches!(
             &lit.value,
-            ValueObj::Bool(_) | ValueObj::Int(_) | ValueObj::Nat(_) | ValueObj::Str(_)
+            ValueObj::Bool(_) | ValueObj::Int(_) | ValueObj::Nat(_)
         ) {
             self.load_builtin_types_if_not();
             format!("{}({escaped})", lit.value.class())
@@ -568,6 +696,9 @@ impl ScriptGenerator {
         if unary.op.kind == TokenKind::Mutate {
             self.load_mutate_op_if_not();
             code += "mutate_operator(";
+        } else if unary.op.kind == TokenKind::Try {
+            self.load_early_return_if_not();
+            code += "try__(";
         } else {
             code += "(";
             code += &unary.op.content;
@@ -581,7 +712,7 @@ impl ScriptGenerator {
         match acc {
             Accessor::Ident(ident) => {
                 match &ident.inspect()[..] {
-                    "Str" | "Bool" | "Nat" | "Array" => {
+                    "Str" | "Bool" | "Nat" | "Array" | "Error" => {
                         self.load_builtin_types_if_not();
                     }
                     "if" | "if!" | "for!" | "while" | "discard" => {
@@ -629,7 +760,7 @@ impl ScriptGenerator {
                     ParamPattern::Discard(token) => token,
                     _ => unreachable!(),
                 };
-                code += &format!("{}__ ", &param.content);
+                code += &format!("{}__ ", replace_non_symbolic(&param.content));
                 code += &format!("in {}:\n", self.transpile_expr(iter));
                 code += &self.transpile_block(block.body, Discard);
                 code
@@ -704,74 +835,143 @@ impl ScriptGenerator {
         self.fresh_var_n += 1;
         let tmp_func = Str::from(format!("match_tmp_func_{}__", self.fresh_var_n));
         self.fresh_var_n += 1;
-        let mut code = format!("def {tmp_func}():\n");
+        // the target is passed as an argument, since `tmp_func` cannot see the local variables of the caller
+        let mut code = format!("def {tmp_func}(match_target__):\n");
         self.level += 1;
         code += &"    ".repeat(self.level);
-        code += "match ";
+        code += "match match_target__:\n";
         let cond = call.args.remove(0);
-        code += &format!("{}:\n", self.transpile_expr(cond));
-        while let Some(Expr::Lambda(arm)) = call.args.try_remove(0) {
+        let cond = self.transpile_expr(cond);
+        while let Some(Expr::Lambda(mut arm)) = call.args.try_remove(0) {
             self.level += 1;
             code += &"    ".repeat(self.level);
-            let target = arm.params.non_defaults.get(0).unwrap();
-            match &target.raw.pat {
-                ParamPattern::VarName(param) => {
-                    code += &format!("case {}__:\n", &param.token().content);
-                    code += &self.transpile_block(arm.body, StoreTmp(tmp.clone()));
-                    self.level -= 1;
-                }
-                ParamPattern::Discard(_) => {
-                    match target.raw.t_spec.as_ref().map(|t| &t.t_spec) {
-                        Some(TypeSpec::Enum(enum_t)) => {
-                            let values = ValueObj::vec_from_const_args(enum_t.clone());
-                            if values.len() == 1 {
-                                code += &format!("case {}:\n", values[0]);
-                            } else {
-                                todo!()
-                            }
-                        }
-                        Some(_) => todo!(),
-                        None => {
-                            code += "case _:\n";
-                        }
-                    }
-                    code += &self.transpile_block(arm.body, StoreTmp(tmp.clone()));
-                    self.level -= 1;
+            let is_last_arm = call.args.is_empty();
+            let target = arm.params.non_defaults.remove(0);
+            // n @ (1 or 2) -> ... => case (1 | 2) as n__:
+            // 1 or 2 -> ... => case 1 | 2:
+            let values = match target.raw.t_spec.as_ref().map(|t| &t.t_spec) {
+                Some(TypeSpec::Enum(enum_t)) => Some(
+                    ValueObj::vec_from_const_args(enum_t.clone())
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" | "),
+                ),
+                _ => None,
+            };
+            // If it's the last arm, there's no need to inspect it (same as the bytecode)
+            let t_spec = target
+                .t_spec_as_expr
+                .filter(|_| values.is_none() && !is_last_arm);
+            let name = match &target.raw.pat {
+                ParamPattern::VarName(param) => Some(format!(
+                    "{}__",
+                    replace_non_symbolic(&param.token().content)
+                )),
+                ParamPattern::Discard(_) if t_spec.is_some() || arm.guard.is_some() => {
+                    let name = format!("match_target_{}__", self.fresh_var_n);
+                    self.fresh_var_n += 1;
+                    Some(name)
                 }
+                ParamPattern::Discard(_) => None,
                 _ => todo!(),
+            };
+            let capture = name.clone().unwrap_or_else(|| "_".to_string());
+            let mut conds = vec![];
+            // x: T -> ... => case x__ if contains_operator(T, x__):
+            if let Some(t_spec) = t_spec {
+                self.load_contains_op_if_not();
+                conds.push(format!(
+                    "contains_operator({}, {capture})",
+                    self.transpile_expr(t_spec)
+                ));
+            }
+            match arm.guard.take() {
+                Some(mut guard) if guard.len() == 1 => {
+                    conds.push(self.transpile_expr(guard.remove(0)));
+                }
+                // the destructured variables are bound in the guard (`((a, b) | a == b) -> ...`),
+                // so the guard is transpiled into a function that binds them and then tests the condition
+                Some(guard) => {
+                    let guard_func = format!("match_guard_{}__", self.fresh_var_n);
+                    self.fresh_var_n += 1;
+                    let mut code = format!("def {guard_func}({capture}):\n");
+                    let level = self.level;
+                    self.level = 0;
+                    code += &self.transpile_block(guard, Return);
+                    self.level = level;
+                    self.prelude += &code;
+                    conds.push(format!("{guard_func}({capture})"));
+                }
+                None => {}
             }
+            let pat = match (values, name.clone()) {
+                (Some(values), Some(name)) => format!("({values}) as {name}"),
+                (Some(values), None) => values,
+                (None, Some(name)) => name,
+                (None, None) => "_".to_string(),
+            };
+            if conds.is_empty() {
+                code += &format!("case {pat}:\n");
+            } else {
+                code += &format!("case {pat} if {}:\n", conds.join(" and "));
+            }
+            if arm.body.len() > 1 {
+                // the variables bound in the body are declared as `global`,
+                // so each body is transpiled into a function not to conflict with the other arms
+                let arm_func = format!("match_arm_{}__", self.fresh_var_n);
+                self.fresh_var_n += 1;
+                let param = name.unwrap_or_default();
+                let mut arm_code = format!("def {arm_func}({param}):\n");
+                let level = self.level;
+                self.level = 0;
+                arm_code += &self.transpile_block(arm.body, Return);
+                self.level = level;
+                self.prelude += &arm_code;
+                code += &"    ".repeat(self.level + 1);
+                code += &format!("{tmp} = {arm_func}({param})\n");
+            } else {
+                code += &self.transpile_block(arm.body, StoreTmp(tmp.clone()));
+            }
+            self.level -= 1;
         }
         code += &"    ".repeat(self.level);
         code += &format!("return {tmp}\n");
         self.prelude += &code;
         self.level -= 1;
-        format!("{tmp_func}()")
+        format!("{tmp_func}({cond})")
     }
 
     fn transpile_simple_call(&mut self, call: Call) -> String {
-        let is_py_api = if let Some(attr) = &call.attr_name {
+        let (is_py_api, callee_t) = if let Some(attr) = &call.attr_name {
             let is_py_api = attr.is_py_api();
             if let Some(name) = debind(attr) {
                 let name = demangle(&name);
                 return format!(
                     "{name}({}, {})",
                     self.transpile_expr(*call.obj),
-                    self.transpile_args(call.args, is_py_api, false)
+                    self.transpile_args(call.args, is_py_api, &attr.t(), false)
                 );
             }
-            is_py_api
+            (is_py_api, attr.t())
         } else {
-            call.obj.is_py_api()
+            (call.obj.is_py_api(), call.obj.t())
         };
         let mut code = format!("({})", self.transpile_expr(*call.obj));
         if let Some(attr) = call.attr_name {
             code += &format!(".{}", Self::transpile_ident(attr));
         }
-        code += &self.transpile_args(call.args, is_py_api, true);
+        code += &self.transpile_args(call.args, is_py_api, &callee_t, true);
         code
     }
 
-    fn transpile_args(&mut self, mut args: Args, is_py_api: bool, paren: bool) -> String {
+    fn transpile_args(
+        &mut self,
+        mut args: Args,
+        is_py_api: bool,
+        callee_t: &Type,
+        paren: bool,
+    ) -> String {
         let mut code = String::new();
         if paren {
             code.push('(');
@@ -780,14 +980,34 @@ impl ScriptGenerator {
             code += &self.transpile_expr(arg.expr);
             code.push(',');
         }
+        if let Some(var_args) = args.var_args.take() {
+            code += &format!("*{},", self.transpile_expr(var_args.expr));
+        }
         while let Some(arg) = args.try_remove_kw(0) {
-            let escape = if is_py_api { "" } else { "__" };
+            // keywords passed to `**kwargs` are not escaped
+            let is_declared = callee_t
+                .non_default_params()
+                .into_iter()
+                .chain(callee_t.default_params())
+                .any(|params| {
+                    params
+                        .iter()
+                        .any(|pt| pt.name() == Some(&arg.keyword.content))
+                });
+            let escape = if is_py_api || (callee_t.kw_var_params().is_some() && !is_declared) {
+                ""
+            } else {
+                "__"
+            };
             code += &format!(
                 "{}{escape}={},",
                 arg.keyword.content,
                 self.transpile_expr(arg.expr)
             );
         }
+        if let Some(kw_var) = args.kw_var.take() {
+            code += &format!("**{},", self.transpile_expr(kw_var.expr));
+        }
         if paren {
             code.push(')');
         }
@@ -821,6 +1041,9 @@ impl ScriptGenerator {
                 _ => unreachable!(),
             }
         }
+        if let Some(var_params) = params.var_params {
+            code += &format!("*{},", self.transpile_var_param(var_params.raw.pat));
+        }
         for default in params.defaults {
             match default.sig.raw.pat {
                 ParamPattern::VarName(param) => {
@@ -838,9 +1061,26 @@ impl ScriptGenerator {
                 _ => unreachable!(),
             }
         }
+        if let Some(kw_var_params) = params.kw_var_params {
+            code += &format!("**{},", self.transpile_var_param(kw_var_params.raw.pat));
+        }
         code
     }
 
+    fn transpile_var_param(&mut self, pat: ParamPattern) -> String {
+        match pat {
+            ParamPattern::VarName(param) => {
+                format!("{}__", replace_non_symbolic(&param.into_token().content))
+            }
+            ParamPattern::Discard(_) => {
+                let n = self.fresh_var_n;
+                self.fresh_var_n += 1;
+                format!("_{n}")
+            }
+            _ => unreachable!(),
+        }
+    }
+
     fn transpile_block(&mut self, block: Block, last_op: LastLineOperation) -> String {
         self.level += 1;
         let mut code = String::new();
@@ -866,10 +1106,19 @@ impl ScriptGenerator {
     }
 
     fn transpile_lambda(&mut self, lambda: Lambda) -> String {
+        let early_return = has_early_return(&lambda.body);
+        if early_return {
+            self.load_early_return_if_not();
+        }
         if lambda.body.len() > 1 {
             let name = format!("lambda_{}__", self.fresh_var_n);
             self.fresh_var_n += 1;
-            let mut code = format!("def {name}({}):\n", self.transpile_params(lambda.params));
+            let mut code = if early_return {
+                "@catch_early_return__\n".to_string()
+            } else {
+                String::new()
+            };
+            code += &format!("def {name}({}):\n", self.transpile_params(lambda.params));
             code += &self.transpile_block(lambda.body, Return);
             self.prelude += &code;
             name
@@ -878,14 +1127,19 @@ impl ScriptGenerator {
             code += &self.transpile_block(lambda.body, Discard);
             code.pop(); // \n
             code.push(')');
-            code
+            if early_return {
+                format!("catch_early_return__{code}")
+            } else {
+                code
+            }
         }
     }
 
     // TODO: trait definition
     fn transpile_def(&mut self, mut def: Def) -> String {
         // HACK: allow reference to local variables in tmp functions
-        let mut code = if self.level == 0 {
+        // `_` (e.g. `{.age = _: Nat}`) is not referenced
+        let mut code = if self.level == 0 || &def.sig.ident().inspect()[..] == "_" {
             "".to_string()
         } else {
             format!(
@@ -911,6 +1165,27 @@ impl ScriptGenerator {
                 }
             }
             Signature::Subr(subr) => {
+                for deco in subr.decorators {
+                    code += &format!(
+                        "@{}\n{}",
+                        self.transpile_expr(deco),
+                        "    ".repeat(self.level)
+                    );
+                }
+                let cached = subr
+                    .ident
+                    .vi
+                    .comptime_decos
+                    .as_ref()
+                    .is_some_and(|decos| decos.contains("Cache"));
+                if cached {
+                    self.load_lru_cache_if_not();
+                    code += &format!("@lru_cache__(None)\n{}", "    ".repeat(self.level));
+                }
+                if has_early_return(&def.body.block) {
+                    self.load_early_return_if_not();
+                    code += &format!("@catch_early_return__\n{}", "    ".repeat(self.level));
+                }
                 code += &format!(
                     "def {}({}):\n",
                     Self::transpile_ident(subr.ident),
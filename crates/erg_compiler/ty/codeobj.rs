@@ -18,6 +18,8 @@ use erg_common::python_util::{env_magic_number, exec_py_code, PythonVersion};
 use erg_common::serialize::*;
 use erg_common::Str;
 
+use crate::stable_id::stable_hash;
+
 use super::deserialize::{DeserializeResult, Deserializer};
 use super::value::ValueObj;
 use super::{HasType, Type, TypePair};
@@ -222,11 +224,18 @@ impl HasType for CodeObj {
 }
 
 impl fmt::Debug for CodeObj {
+    /// Instead of the address, the hash of the contents is shown (so the dumps are reproducible).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = stable_hash(&[
+            self.filename.as_bytes(),
+            self.name.as_bytes(),
+            &self.firstlineno.to_le_bytes(),
+            &self.code,
+        ]);
         write!(
             f,
-            "<code object {} at {:p}, file \"{}\", line {}>",
-            self.name, self, self.filename, self.firstlineno
+            "<code object {} at {id:#018x}, file \"{}\", line {}>",
+            self.name, self.filename, self.firstlineno
         )
     }
 }
@@ -268,8 +277,9 @@ impl CodeObj {
     ) -> Self {
         let name = name.into();
         let var_args_defined = (flags & CodeObjFlags::VarArgs as u32 != 0) as u32;
+        let var_kwargs_defined = (flags & CodeObjFlags::VarKeywords as u32 != 0) as u32;
         Self {
-            argcount: params.len() as u32 - var_args_defined,
+            argcount: params.len() as u32 - var_args_defined - var_kwargs_defined,
             posonlyargcount: 0,
             kwonlyargcount: 0,
             nlocals: params.len() as u32,
@@ -429,6 +439,12 @@ impl CodeObj {
         py_magic_num: Option<u32>,
     ) -> std::io::Result<()> {
         let mut file = File::create(path)?;
+        file.write_all(&self.into_pyc_bytes(py_magic_num)[..])?;
+        Ok(())
+    }
+
+    /// The content of a .pyc file (header + marshalled code object)
+    pub fn into_pyc_bytes(self, py_magic_num: Option<u32>) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(16);
         let py_magic_num = py_magic_num.unwrap_or_else(env_magic_number);
         let python_ver = get_ver_from_magic_num(py_magic_num);
@@ -437,8 +453,20 @@ impl CodeObj {
         bytes.append(&mut get_timestamp_bytes().to_vec());
         bytes.append(&mut vec![0; 4]); // padding
         bytes.append(&mut self.into_bytes(python_ver));
-        file.write_all(&bytes[..])?;
-        Ok(())
+        bytes
+    }
+
+    /// The content of a checked hash-based .pyc file (PEP 552).
+    /// Python recompiles the source file instead of loading the .pyc if the hash of the source does not match.
+    pub fn into_hash_based_pyc_bytes(self, py_magic_num: Option<u32>, source: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16);
+        let py_magic_num = py_magic_num.unwrap_or_else(env_magic_number);
+        let python_ver = get_ver_from_magic_num(py_magic_num);
+        bytes.append(&mut get_magic_num_bytes(py_magic_num).to_vec());
+        bytes.append(&mut 0b11u32.to_le_bytes().to_vec()); // flags: hash-based | check_source
+        bytes.append(&mut get_source_hash_bytes(py_magic_num, source).to_vec());
+        bytes.append(&mut self.into_bytes(python_ver));
+        bytes
     }
 
     pub fn executable_code(self, py_magic_num: Option<u32>) -> String {
@@ -453,8 +481,19 @@ impl CodeObj {
         format!("import marshal; exec(marshal.loads(b'{bytecode}'))")
     }
 
-    pub fn exec(self, py_magic_num: Option<u32>, output: Output) -> std::io::Result<ExitStatus> {
-        exec_py_code(&self.executable_code(py_magic_num), output)
+    /// `argv`: `sys.argv` of the program (`argv[0]` is the script name)
+    pub fn exec(
+        self,
+        py_magic_num: Option<u32>,
+        argv: &[&str],
+        output: Output,
+    ) -> std::io::Result<ExitStatus> {
+        // `sys.argv[0]` is `-c` (or the temporary file) when executed by `exec_py_code`
+        let code = format!(
+            "import sys; del sys.argv[0]; {}",
+            self.executable_code(py_magic_num)
+        );
+        exec_py_code(&code, argv, output)
     }
 
     fn tables_info(&self) -> String {
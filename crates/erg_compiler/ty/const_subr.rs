@@ -267,6 +267,7 @@ impl ConstSubr {
                             subr.non_default_params.clone(),
                             var_params.cloned(),
                             subr.default_params.clone(),
+                            subr.kw_var_params.as_deref().cloned(),
                             return_t,
                         );
                         return Some(subr_t);
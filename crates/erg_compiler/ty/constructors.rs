@@ -70,6 +70,15 @@ pub fn dict_t(dict: TyParam) -> Type {
     poly("Dict", vec![dict])
 }
 
+/// `{Str: value_t}`: the type of `**kwargs`
+pub fn str_dict_t(value_t: Type) -> Type {
+    dict_t(TyParam::Dict(
+        [(TyParam::t(Type::Str), TyParam::t(value_t))]
+            .into_iter()
+            .collect(),
+    ))
+}
+
 #[inline]
 pub fn range(t: Type) -> Type {
     poly("Range", vec![TyParam::t(t)])
@@ -190,6 +199,7 @@ pub fn subr_t(
     non_default_params: Vec<ParamTy>,
     var_params: Option<ParamTy>,
     default_params: Vec<ParamTy>,
+    kw_var_params: Option<ParamTy>,
     return_t: Type,
 ) -> Type {
     Type::Subr(SubrType::new(
@@ -197,6 +207,7 @@ pub fn subr_t(
         non_default_params,
         var_params,
         default_params,
+        kw_var_params,
         return_t,
     ))
 }
@@ -212,6 +223,7 @@ pub fn func(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -259,6 +271,7 @@ pub fn proc(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -293,6 +306,7 @@ pub fn fn_met(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -322,6 +336,7 @@ pub fn pr_met(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -1,10 +1,11 @@
-use std::cell::{Ref, RefMut};
+use std::cell::{Ref, RefCell, RefMut};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::mem;
 use std::sync::atomic::AtomicUsize;
 
 use erg_common::shared::Forkable;
+use erg_common::timing::{Counter, TIMINGS};
 use erg_common::traits::{LimitedDisplay, StructuralEq};
 use erg_common::Str;
 use erg_common::{addr_eq, log};
@@ -623,13 +624,6 @@ impl<T: Send + Clone> Free<T> {
     pub fn borrow_mut(&self) -> RefMut<'_, FreeKind<T>> {
         self.0.borrow_mut()
     }
-    /// very unsafe, use `force_replace` instead whenever possible
-    pub fn as_ptr(&self) -> *mut FreeKind<T> {
-        self.0.as_ptr()
-    }
-    pub fn forced_as_ref(&self) -> &FreeKind<T> {
-        unsafe { self.as_ptr().as_ref() }.unwrap()
-    }
 }
 
 impl Free<Type> {
@@ -768,6 +762,7 @@ impl<T: Send + Clone> Free<T> {
 
     pub fn new_unbound(level: Level, constraint: Constraint) -> Self {
         UNBOUND_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        TIMINGS.count(Counter::FreeVars);
         Self(Forkable::new(FreeKind::unbound(
             UNBOUND_ID.load(std::sync::atomic::Ordering::SeqCst),
             level,
@@ -776,6 +771,7 @@ impl<T: Send + Clone> Free<T> {
     }
 
     pub fn new_named_unbound(name: Str, level: Level, constraint: Constraint) -> Self {
+        TIMINGS.count(Counter::FreeVars);
         Self(Forkable::new(FreeKind::named_unbound(
             name, level, constraint,
         )))
@@ -807,17 +803,9 @@ impl<T: Send + Clone> Free<T> {
         })
     }
 
-    pub fn unsafe_crack(&self) -> &T {
-        match unsafe { self.as_ptr().as_ref().unwrap() } {
-            FreeKind::Linked(t) | FreeKind::UndoableLinked { t, .. } => t,
-            FreeKind::Unbound { .. } | FreeKind::NamedUnbound { .. } => {
-                panic!("the value is unbounded")
-            }
-        }
-    }
-
+    /// Whether `self` and `other` are the same type variable (not only equal).
     pub fn addr_eq(&self, other: &Self) -> bool {
-        self.as_ptr() == other.as_ptr()
+        std::ptr::eq::<RefCell<FreeKind<T>>>(&*self.0, &*other.0)
     }
 }
 
@@ -1115,4 +1103,20 @@ mod tests {
         assert_eq!(t, t);
         assert_eq!(t, u);
     }
+
+    #[test]
+    fn relink_while_borrowed() {
+        let t = free_var(1, Constraint::new_type_of(Type::Type));
+        let Type::FreeVar(fv) = t.clone() else { unreachable!() };
+        fv.link(&func1(Type::Int, Type::Nat));
+        let return_t = t.return_t().unwrap();
+        let params = t.non_default_params().unwrap();
+        // the link can be replaced while the values read through it are alive
+        fv.link(&func1(Type::Str, Type::Bool));
+        assert_eq!(*return_t, Type::Nat);
+        assert_eq!(*params[0].typ(), Type::Int);
+        assert_eq!(*t.return_t().unwrap(), Type::Bool);
+        assert!(fv.addr_eq(&fv.clone()));
+        assert!(!fv.addr_eq(&FreeTyVar::new_unbound(1, Constraint::Uninited)));
+    }
 }
@@ -17,6 +17,7 @@ pub mod typaram;
 pub mod value;
 pub mod vis;
 
+use std::borrow::Cow;
 use std::cell::RefMut;
 use std::fmt;
 use std::ops::{BitAnd, BitOr, Deref, Not, Range, RangeInclusive};
@@ -28,6 +29,7 @@ use erg_common::fresh::FRESH_GEN;
 #[allow(unused_imports)]
 use erg_common::log;
 use erg_common::set::Set;
+use erg_common::timing::{Counter, TIMINGS};
 use erg_common::traits::{LimitedDisplay, Locational, StructuralEq};
 use erg_common::{enum_unwrap, fmt_option, ref_addr_eq, set, Str};
 
@@ -69,12 +71,12 @@ pub trait HasType {
         self.ref_t().inner_ts()
     }
     #[inline]
-    fn lhs_t(&self) -> &Type {
-        self.ref_t().non_default_params().unwrap()[0].typ()
+    fn lhs_t(&self) -> Type {
+        self.ref_t().non_default_params().unwrap()[0].typ().clone()
     }
     #[inline]
-    fn rhs_t(&self) -> &Type {
-        self.ref_t().non_default_params().unwrap()[1].typ()
+    fn rhs_t(&self) -> Type {
+        self.ref_t().non_default_params().unwrap()[1].typ().clone()
     }
 }
 
@@ -136,6 +138,11 @@ macro_rules! impl_t_for_enum {
                     $($Enum::$Variant(v) => v.ref_mut_t(),)*
                 }
             }
+            fn t(&self) -> Type {
+                match self {
+                    $($Enum::$Variant(v) => v.t(),)*
+                }
+            }
             fn signature_t(&self) -> Option<&Type> {
                 match self {
                     $($Enum::$Variant(v) => v.signature_t(),)*
@@ -256,7 +263,7 @@ pub struct SubrType {
     pub non_default_params: Vec<ParamTy>,
     pub var_params: Option<Box<ParamTy>>, // TODO: need to have a position (var_params can be specified after default_params)
     pub default_params: Vec<ParamTy>,
-    // var_kw_params: Option<(Str, Box<Type>)>,
+    pub kw_var_params: Option<Box<ParamTy>>,
     pub return_t: Box<Type>,
 }
 
@@ -279,11 +286,12 @@ impl TryFrom<Type> for SubrType {
     }
 }
 
+/// Linked type variables are not dereferenced (the link may be replaced while the reference is alive).
+/// Use `SubrType::try_from(t.clone())` if `t` may be a linked type variable.
 impl<'t> TryFrom<&'t Type> for &'t SubrType {
     type Error = ();
     fn try_from(t: &'t Type) -> Result<&'t SubrType, ()> {
         match t {
-            Type::FreeVar(fv) if fv.is_linked() => Self::try_from(fv.unsafe_crack()),
             Type::Subr(st) => Ok(st),
             Type::Quantified(quant) => <&SubrType>::try_from(quant.as_ref()),
             Type::Refinement(refine) => Self::try_from(refine.t.as_ref()),
@@ -326,6 +334,19 @@ impl LimitedDisplay for SubrType {
             write!(f, "{} := ", pt.name().unwrap())?;
             pt.typ().limited_fmt(f, limit - 1)?;
         }
+        if let Some(kw_var_params) = &self.kw_var_params {
+            if !self.non_default_params.is_empty()
+                || self.var_params.is_some()
+                || !self.default_params.is_empty()
+            {
+                write!(f, ", ")?;
+            }
+            write!(f, "**")?;
+            if let Some(name) = kw_var_params.name() {
+                write!(f, "{}: ", name)?;
+            }
+            kw_var_params.typ().limited_fmt(f, limit - 1)?;
+        }
         write!(f, ") {} ", self.kind.arrow())?;
         self.return_t.limited_fmt(f, limit - 1)
     }
@@ -359,8 +380,17 @@ impl StructuralEq for SubrType {
             .iter()
             .zip(other.var_params.iter())
             .all(|(l, r)| l.typ().structural_eq(r.typ()));
+        let kw_var_params_judge = self
+            .kw_var_params
+            .iter()
+            .zip(other.kw_var_params.iter())
+            .all(|(l, r)| l.typ().structural_eq(r.typ()));
         let return_t_judge = self.return_t.structural_eq(&other.return_t);
-        non_defaults_judge && var_params_judge && return_t_judge && kw_check()
+        non_defaults_judge
+            && var_params_judge
+            && kw_var_params_judge
+            && return_t_judge
+            && kw_check()
     }
 }
 
@@ -370,6 +400,7 @@ impl SubrType {
         non_default_params: Vec<ParamTy>,
         var_params: Option<ParamTy>,
         default_params: Vec<ParamTy>,
+        kw_var_params: Option<ParamTy>,
         return_t: Type,
     ) -> Self {
         Self {
@@ -377,6 +408,7 @@ impl SubrType {
             non_default_params,
             var_params: var_params.map(Box::new),
             default_params,
+            kw_var_params: kw_var_params.map(Box::new),
             return_t: Box::new(return_t),
         }
     }
@@ -394,6 +426,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().contains_tvar(target))
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().contains_tvar(target))
+                .unwrap_or(false)
             || self.return_t.contains_tvar(target)
     }
 
@@ -410,6 +447,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().contains_type(target))
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().contains_type(target))
+                .unwrap_or(false)
             || self.return_t.contains_type(target)
     }
 
@@ -426,6 +468,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().contains_tp(target))
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().contains_tp(target))
+                .unwrap_or(false)
             || self.return_t.contains_tp(target)
     }
 
@@ -440,6 +487,9 @@ impl SubrType {
         for pt in self.default_params.iter() {
             qvars.extend(pt.typ().qvars());
         }
+        if let Some(kw_var_params) = &self.kw_var_params {
+            qvars.extend(kw_var_params.typ().qvars());
+        }
         qvars.extend(self.return_t.qvars());
         qvars
     }
@@ -463,6 +513,7 @@ impl SubrType {
             .map(|pt| pt.typ().qnames())
             .chain(self.var_params.iter().map(|pt| pt.typ().qnames()))
             .chain(self.default_params.iter().map(|pt| pt.typ().qnames()))
+            .chain(self.kw_var_params.iter().map(|pt| pt.typ().qnames()))
             .chain([self.return_t.qnames()]);
         Set::multi_intersection(qnames_sets).extended(structural_qname)
     }
@@ -475,6 +526,11 @@ impl SubrType {
                 .map(|pt| pt.typ().has_qvar())
                 .unwrap_or(false)
             || self.default_params.iter().any(|pt| pt.typ().has_qvar())
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().has_qvar())
+                .unwrap_or(false)
             || self.return_t.has_qvar()
     }
 
@@ -491,6 +547,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().has_undoable_linked_var())
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().has_undoable_linked_var())
+                .unwrap_or(false)
             || self.return_t.has_undoable_linked_var()
     }
 
@@ -509,6 +570,11 @@ impl SubrType {
                 .iter()
                 .map(|pt| TyParam::t(pt.typ().clone()))
                 .collect(),
+            self.kw_var_params
+                .as_ref()
+                .map(|pt| TyParam::t(pt.typ().clone()))
+                .into_iter()
+                .collect(),
         ]
         .concat()
     }
@@ -560,6 +626,14 @@ impl SubrType {
             .chain(self.default_params.iter())
             .map(|pt| pt.name().map_or("_", |s| &s[..]))
     }
+
+    /// Names of the parameters that can be passed as keyword arguments (variadic parameters cannot).
+    pub fn kw_param_names(&self) -> impl Iterator<Item = &str> + Clone {
+        self.non_default_params
+            .iter()
+            .chain(self.default_params.iter())
+            .filter_map(|pt| pt.name().map(|s| &s[..]))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -704,6 +778,7 @@ pub struct ArgsOwnership {
     pub non_defaults: Vec<(Option<Str>, Ownership)>,
     pub var_params: Option<(Option<Str>, Ownership)>,
     pub defaults: Vec<(Str, Ownership)>,
+    pub kw_var_params: Option<(Option<Str>, Ownership)>,
 }
 
 impl fmt::Display for ArgsOwnership {
@@ -730,6 +805,14 @@ impl fmt::Display for ArgsOwnership {
         for (name, o) in self.defaults.iter() {
             write!(f, ", {name} := {o:?}")?;
         }
+        if let Some((name, o)) = self.kw_var_params.as_ref() {
+            write!(f, ", **")?;
+            if let Some(name) = name {
+                write!(f, "{name}: {o:?}")?;
+            } else {
+                write!(f, "{o:?}")?;
+            }
+        }
         write!(f, ")")?;
         Ok(())
     }
@@ -740,11 +823,13 @@ impl ArgsOwnership {
         non_defaults: Vec<(Option<Str>, Ownership)>,
         var_params: Option<(Option<Str>, Ownership)>,
         defaults: Vec<(Str, Ownership)>,
+        kw_var_params: Option<(Option<Str>, Ownership)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params,
             defaults,
+            kw_var_params,
         }
     }
 }
@@ -825,7 +910,7 @@ impl GuardType {
     }
 }
 
-#[derive(Debug, Clone, Hash, Default)]
+#[derive(Debug, Hash, Default)]
 pub enum Type {
     /* Monomorphic (builtin) types */
     Obj, // {=}
@@ -906,6 +991,79 @@ pub enum Type {
     Uninited,
 }
 
+// implemented manually to count the clones (`--memory-stats`)
+impl Clone for Type {
+    fn clone(&self) -> Self {
+        TIMINGS.count(Counter::TypeClones);
+        match self {
+            Self::Obj => Self::Obj,
+            Self::Int => Self::Int,
+            Self::Nat => Self::Nat,
+            Self::Ratio => Self::Ratio,
+            Self::Float => Self::Float,
+            Self::Complex => Self::Complex,
+            Self::Bool => Self::Bool,
+            Self::Str => Self::Str,
+            Self::NoneType => Self::NoneType,
+            Self::Code => Self::Code,
+            Self::Frame => Self::Frame,
+            Self::Error => Self::Error,
+            Self::Inf => Self::Inf,
+            Self::NegInf => Self::NegInf,
+            Self::Type => Self::Type,
+            Self::ClassType => Self::ClassType,
+            Self::TraitType => Self::TraitType,
+            Self::Patch => Self::Patch,
+            Self::NotImplementedType => Self::NotImplementedType,
+            Self::Ellipsis => Self::Ellipsis,
+            Self::Never => Self::Never,
+            Self::Mono(name) => Self::Mono(name.clone()),
+            Self::Ref(t) => Self::Ref(t.clone()),
+            Self::RefMut { before, after } => Self::RefMut {
+                before: before.clone(),
+                after: after.clone(),
+            },
+            Self::Subr(subr) => Self::Subr(subr.clone()),
+            Self::Callable { param_ts, return_t } => Self::Callable {
+                param_ts: param_ts.clone(),
+                return_t: return_t.clone(),
+            },
+            Self::Record(rec) => Self::Record(rec.clone()),
+            Self::Refinement(refine) => Self::Refinement(refine.clone()),
+            Self::Quantified(t) => Self::Quantified(t.clone()),
+            Self::And(l, r) => Self::And(l.clone(), r.clone()),
+            Self::Or(l, r) => Self::Or(l.clone(), r.clone()),
+            Self::Not(t) => Self::Not(t.clone()),
+            Self::Poly { name, params } => Self::Poly {
+                name: name.clone(),
+                params: params.clone(),
+            },
+            Self::Proj { lhs, rhs } => Self::Proj {
+                lhs: lhs.clone(),
+                rhs: rhs.clone(),
+            },
+            Self::ProjCall {
+                lhs,
+                attr_name,
+                args,
+            } => Self::ProjCall {
+                lhs: lhs.clone(),
+                attr_name: attr_name.clone(),
+                args: args.clone(),
+            },
+            Self::Structural(t) => Self::Structural(t.clone()),
+            Self::Guard(guard) => Self::Guard(guard.clone()),
+            Self::Bounded { sub, sup } => Self::Bounded {
+                sub: sub.clone(),
+                sup: sup.clone(),
+            },
+            Self::FreeVar(fv) => Self::FreeVar(fv.clone()),
+            Self::Failure => Self::Failure,
+            Self::Uninited => Self::Uninited,
+        }
+    }
+}
+
 impl PartialEq for Type {
     fn eq(&self, other: &Self) -> bool {
         if ref_addr_eq!(self, other) {
@@ -1341,6 +1499,7 @@ impl HasType for Type {
                         .into_iter(),
                 )
                 .chain(sub.non_default_params.iter().map(|pt| pt.typ().clone()))
+                .chain(sub.kw_var_params.as_deref().map(|pt| pt.typ().clone()))
                 .chain([*sub.return_t.clone()].into_iter())
                 .collect(),
             Self::Callable { param_ts, .. } => param_ts.clone(),
@@ -1394,8 +1553,13 @@ impl HasLevel for Type {
                     .iter()
                     .filter_map(|p| p.typ().level())
                     .min();
+                let kv_min = subr
+                    .kw_var_params
+                    .iter()
+                    .filter_map(|p| p.typ().level())
+                    .min();
                 let ret_min = subr.return_t.level();
-                [nd_min, v_min, d_min, ret_min]
+                [nd_min, v_min, d_min, kv_min, ret_min]
                     .iter()
                     .filter_map(|o| *o)
                     .min()
@@ -1482,6 +1646,9 @@ impl HasLevel for Type {
                 for pt in subr.default_params.iter() {
                     pt.typ().set_level(level);
                 }
+                if let Some(pt) = subr.kw_var_params.as_ref() {
+                    pt.typ().set_level(level);
+                }
                 subr.return_t.set_level(level);
             }
             Self::Quantified(quant) => {
@@ -1719,7 +1886,7 @@ impl Type {
             Self::Refinement(refine) => refine.t.is_value_class(),
             Self::Poly { name, params } => {
                 if &name[..] == "Array" || &name[..] == "Set" {
-                    let elem_t = <&Type>::try_from(params.first().unwrap()).unwrap();
+                    let elem_t = Type::try_from(params.first().unwrap().clone()).unwrap();
                     elem_t.is_value_class()
                 } else {
                     false
@@ -1819,6 +1986,7 @@ impl Type {
                 .map(|pt| pt.typ().union_size())
                 .chain(subr.var_params.as_ref().map(|pt| pt.typ().union_size()))
                 .chain(subr.default_params.iter().map(|pt| pt.typ().union_size()))
+                .chain(subr.kw_var_params.as_ref().map(|pt| pt.typ().union_size()))
                 .max()
                 .unwrap_or(1)
                 .max(subr.return_t.union_size()),
@@ -1944,6 +2112,15 @@ impl Type {
         }
     }
 
+    pub fn is_trait_type(&self) -> bool {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().is_trait_type(),
+            Self::Refinement(refine) => refine.t.is_trait_type(),
+            Self::TraitType => true,
+            _ => false,
+        }
+    }
+
     pub fn as_free(&self) -> Option<&FreeTyVar> {
         <&FreeTyVar>::try_from(self).ok()
     }
@@ -1951,11 +2128,9 @@ impl Type {
     pub fn contains_tvar(&self, target: &FreeTyVar) -> bool {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => fv.crack().contains_tvar(target),
-            Self::FreeVar(fv) if fv.constraint_is_typeof() => {
-                ref_addr_eq!(fv.forced_as_ref(), target.forced_as_ref())
-            }
+            Self::FreeVar(fv) if fv.constraint_is_typeof() => fv.addr_eq(target),
             Self::FreeVar(fv) => {
-                ref_addr_eq!(fv.forced_as_ref(), target.forced_as_ref())
+                fv.addr_eq(target)
                     || fv
                         .get_subsup()
                         .map(|(sub, sup)| {
@@ -2117,7 +2292,11 @@ impl Type {
                     };
                     d_args.push((d_param.name().unwrap().clone(), ownership));
                 }
-                ArgsOwnership::new(nd_args, var_args, d_args)
+                let kw_var_args = subr
+                    .kw_var_params
+                    .as_ref()
+                    .map(|t| (t.name().cloned(), t.typ().ownership()));
+                ArgsOwnership::new(nd_args, var_args, d_args, kw_var_args)
             }
             Self::Quantified(quant) => quant.args_ownership(),
             other => todo!("{other}"),
@@ -2280,6 +2459,19 @@ impl Type {
         }
     }
 
+    /// `T or NoneType` => `Some(T)`
+    /// `NoneType`, `T` => `None`
+    pub fn without_none(&self) -> Option<Type> {
+        let types = self.union_types();
+        if types.len() < 2 || !types.contains(&Type::NoneType) {
+            return None;
+        }
+        types
+            .into_iter()
+            .filter(|t| t != &Type::NoneType)
+            .reduce(constructors::or)
+    }
+
     /// assert!((A or B).contains_union(B))
     pub fn contains_union(&self, typ: &Type) -> bool {
         match self {
@@ -2318,9 +2510,7 @@ impl Type {
 
     pub fn q_constraint(&self) -> Option<Constraint> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().unwrap().q_constraint()
-            }
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().q_constraint(),
             Self::FreeVar(fv) if fv.is_generalized() => fv.constraint(),
             _ => None,
         }
@@ -2454,7 +2644,7 @@ impl Type {
             Type::Not(l) => l.coerce(),
             Type::Poly { params, .. } => {
                 for p in params {
-                    if let Ok(t) = <&Type>::try_from(p) {
+                    if let Ok(t) = Type::try_from(p.clone()) {
                         t.coerce();
                     }
                 }
@@ -2465,7 +2655,7 @@ impl Type {
 
     pub fn qvars(&self) -> Set<(Str, Constraint)> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => fv.unsafe_crack().qvars(),
+            Self::FreeVar(fv) if fv.is_linked() => fv.get_linked().unwrap().qvars(),
             Self::FreeVar(fv) if !fv.constraint_is_uninited() => {
                 let base = set! {(fv.unbound_name().unwrap(), fv.constraint().unwrap())};
                 if let Some((sub, sup)) = fv.get_subsup() {
@@ -2573,9 +2763,15 @@ impl Type {
             Self::FreeVar(fv) if fv.is_linked() => fv.crack().has_undoable_linked_var(),
             Self::FreeVar(fv) => {
                 if let Some((sub, sup)) = fv.get_subsup() {
-                    fv.do_avoiding_recursion(|| {
-                        sub.has_undoable_linked_var() || sup.has_undoable_linked_var()
-                    })
+                    // `do_avoiding_recursion` cannot be used here (its placeholder is an undoable link)
+                    if !self.is_recursive() {
+                        return sub.has_undoable_linked_var() || sup.has_undoable_linked_var();
+                    }
+                    let unbound = fv.clone_inner();
+                    fv.replace(FreeKind::Linked(Type::Failure));
+                    let res = sub.has_undoable_linked_var() || sup.has_undoable_linked_var();
+                    fv.replace(unbound);
+                    res
                 } else {
                     let opt_t = fv.get_type();
                     opt_t.map_or(false, |t| t.has_undoable_linked_var())
@@ -2655,6 +2851,11 @@ impl Type {
                         .default_params
                         .iter()
                         .any(|pt| pt.typ().has_unbound_var())
+                    || subr
+                        .kw_var_params
+                        .as_ref()
+                        .map(|pt| pt.typ().has_unbound_var())
+                        .unwrap_or(false)
                     || subr.return_t.has_unbound_var()
             }
             Self::Record(r) => r.values().any(|t| t.has_unbound_var()),
@@ -2688,6 +2889,7 @@ impl Type {
                 subr.non_default_params.len()
                     + subr.var_params.as_ref().map(|_| 1).unwrap_or(0)
                     + subr.default_params.len()
+                    + subr.kw_var_params.as_ref().map(|_| 1).unwrap_or(0)
                     + 1,
             ),
             Self::Callable { param_ts, .. } => Some(param_ts.len() + 1),
@@ -2699,18 +2901,21 @@ impl Type {
         }
     }
 
-    pub fn singleton_value(&self) -> Option<&TyParam> {
+    pub fn singleton_value(&self) -> Option<Cow<'_, TyParam>> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => fv.unsafe_crack().singleton_value(),
+            Self::FreeVar(fv) if fv.is_linked() => fv
+                .crack()
+                .singleton_value()
+                .map(|tp| Cow::Owned(tp.into_owned())),
             Self::Refinement(refine) => {
                 if let Predicate::Equal { rhs, .. } = refine.pred.as_ref() {
-                    Some(rhs)
+                    Some(Cow::Borrowed(rhs))
                 } else {
                     None
                 }
             }
-            Self::NoneType => Some(&TyParam::Value(ValueObj::None)),
-            Self::Ellipsis => Some(&TyParam::Value(ValueObj::Ellipsis)),
+            Self::NoneType => Some(Cow::Borrowed(&TyParam::Value(ValueObj::None))),
+            Self::Ellipsis => Some(Cow::Borrowed(&TyParam::Value(ValueObj::Ellipsis))),
             _ => None,
         }
     }
@@ -2756,82 +2961,99 @@ impl Type {
         }
     }
 
-    pub fn self_t(&self) -> Option<&Type> {
+    /// The following accessors return an owned value if `self` is a linked type variable,
+    /// because the link may be replaced while the reference is alive.
+    pub fn self_t(&self) -> Option<Cow<'_, Type>> {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().and_then(|t| t.self_t())
+                fv.crack().self_t().map(|t| Cow::Owned(t.into_owned()))
             }
             Self::Refinement(refine) => refine.t.self_t(),
-            Self::Subr(subr) => subr.self_t(),
+            Self::Subr(subr) => subr.self_t().map(Cow::Borrowed),
             Self::Quantified(quant) => quant.self_t(),
             _ => None,
         }
     }
 
-    pub fn non_default_params(&self) -> Option<&Vec<ParamTy>> {
+    pub fn non_default_params(&self) -> Option<Cow<'_, Vec<ParamTy>>> {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => fv
-                .forced_as_ref()
-                .linked()
-                .and_then(|t| t.non_default_params()),
+                .crack()
+                .non_default_params()
+                .map(|params| Cow::Owned(params.into_owned())),
             Self::Refinement(refine) => refine.t.non_default_params(),
             Self::Subr(SubrType {
                 non_default_params, ..
-            }) => Some(non_default_params),
+            }) => Some(Cow::Borrowed(non_default_params)),
             Self::Quantified(quant) => quant.non_default_params(),
             Self::Callable { param_ts: _, .. } => todo!(),
             _ => None,
         }
     }
 
-    pub fn var_params(&self) -> Option<&ParamTy> {
+    pub fn var_params(&self) -> Option<Cow<'_, ParamTy>> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().and_then(|t| t.var_params())
-            }
+            Self::FreeVar(fv) if fv.is_linked() => fv
+                .crack()
+                .var_params()
+                .map(|pt| Cow::Owned(pt.into_owned())),
             Self::Refinement(refine) => refine.t.var_params(),
             Self::Subr(SubrType {
                 var_params: var_args,
                 ..
-            }) => var_args.as_deref(),
+            }) => var_args.as_deref().map(Cow::Borrowed),
             Self::Quantified(quant) => quant.var_params(),
             Self::Callable { param_ts: _, .. } => todo!(),
             _ => None,
         }
     }
 
-    pub fn default_params(&self) -> Option<&Vec<ParamTy>> {
+    pub fn kw_var_params(&self) -> Option<Cow<'_, ParamTy>> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().and_then(|t| t.default_params())
-            }
+            Self::FreeVar(fv) if fv.is_linked() => fv
+                .crack()
+                .kw_var_params()
+                .map(|pt| Cow::Owned(pt.into_owned())),
+            Self::Refinement(refine) => refine.t.kw_var_params(),
+            Self::Subr(SubrType { kw_var_params, .. }) => {
+                kw_var_params.as_deref().map(Cow::Borrowed)
+            }
+            Self::Quantified(quant) => quant.kw_var_params(),
+            _ => None,
+        }
+    }
+
+    pub fn default_params(&self) -> Option<Cow<'_, Vec<ParamTy>>> {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => fv
+                .crack()
+                .default_params()
+                .map(|params| Cow::Owned(params.into_owned())),
             Self::Refinement(refine) => refine.t.default_params(),
-            Self::Subr(SubrType { default_params, .. }) => Some(default_params),
+            Self::Subr(SubrType { default_params, .. }) => Some(Cow::Borrowed(default_params)),
             Self::Quantified(quant) => quant.default_params(),
             _ => None,
         }
     }
 
-    pub fn non_var_params(&self) -> Option<impl Iterator<Item = &ParamTy> + Clone> {
+    pub fn non_var_params(&self) -> Option<Vec<ParamTy>> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().and_then(|t| t.non_var_params())
-            }
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().non_var_params(),
             Self::Refinement(refine) => refine.t.non_var_params(),
-            Self::Subr(subr) => Some(subr.non_var_params()),
+            Self::Subr(subr) => Some(subr.non_var_params().cloned().collect()),
             Self::Quantified(quant) => quant.non_var_params(),
             _ => None,
         }
     }
 
-    pub fn return_t(&self) -> Option<&Type> {
+    pub fn return_t(&self) -> Option<Cow<'_, Type>> {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => {
-                fv.forced_as_ref().linked().and_then(|t| t.return_t())
+                fv.crack().return_t().map(|t| Cow::Owned(t.into_owned()))
             }
             Self::Refinement(refine) => refine.t.return_t(),
             Self::Subr(SubrType { return_t, .. }) | Self::Callable { return_t, .. } => {
-                Some(return_t)
+                Some(Cow::Borrowed(return_t))
             }
             // NOTE: Quantified could return a quantified type variable.
             // At least in situations where this function is needed, self cannot be Quantified.
@@ -2841,7 +3063,7 @@ impl Type {
                 }
                 quant.return_t()
             }
-            Self::Failure => Some(&Type::Failure),
+            Self::Failure => Some(Cow::Borrowed(Type::FAILURE)),
             _ => None,
         }
     }
@@ -2981,12 +3203,17 @@ impl Type {
                             .map_type(|t| t.replace(&Self::Failure, &Self::Obj))
                     })
                     .collect();
+                let kw_var_params = subr.kw_var_params.as_ref().map(|pt| {
+                    pt.clone()
+                        .map_type(|t| t.replace(&Self::Failure, &Self::Obj))
+                });
                 let return_t = subr.return_t.clone().replace(&Self::Failure, &Self::Never);
                 subr_t(
                     subr.kind,
                     non_default_params,
                     var_params,
                     default_params,
+                    kw_var_params,
                     return_t,
                 )
             }
@@ -3038,6 +3265,10 @@ impl Type {
                 for d in subr.default_params.iter_mut() {
                     *d.typ_mut() = std::mem::take(d.typ_mut())._replace(target, to);
                 }
+                if let Some(kw_var) = subr.kw_var_params.as_mut() {
+                    *kw_var.as_mut().typ_mut() =
+                        std::mem::take(kw_var.as_mut().typ_mut())._replace(target, to);
+                }
                 subr.return_t = Box::new(subr.return_t._replace(target, to));
                 Self::Subr(subr)
             }
@@ -3105,6 +3336,10 @@ impl Type {
                 for d in subr.default_params.iter_mut() {
                     *d.typ_mut() = std::mem::take(d.typ_mut()).normalize();
                 }
+                if let Some(kw_var) = subr.kw_var_params.as_mut() {
+                    *kw_var.as_mut().typ_mut() =
+                        std::mem::take(kw_var.as_mut().typ_mut()).normalize();
+                }
                 subr.return_t = Box::new(subr.return_t.normalize());
                 Self::Subr(subr)
             }
@@ -3171,6 +3406,18 @@ impl Type {
         }
     }
 
+    /// interior-mut
+    ///
+    /// Unlike `destructive_link`, generalized type variables can also be linked (to resolve the invalid ones).
+    pub(crate) fn link_to_failure(&self) {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().link_to_failure(),
+            Self::FreeVar(fv) => fv.link(&Self::Failure),
+            Self::Refinement(refine) => refine.t.link_to_failure(),
+            _ => {}
+        }
+    }
+
     /// interior-mut
     ///
     /// `inc/dec_undo_count` due to the number of `substitute_typarams/undo_typarams` must be matched
@@ -3255,12 +3502,12 @@ impl Type {
     }
 }
 
-pub struct ReplaceTable<'t> {
-    rules: Vec<(&'t Type, &'t Type)>,
+pub struct ReplaceTable {
+    rules: Vec<(Type, Type)>,
 }
 
-impl<'t> ReplaceTable<'t> {
-    pub fn make(target: &'t Type, to: &'t Type) -> Self {
+impl ReplaceTable {
+    pub fn make(target: &Type, to: &Type) -> Self {
         let mut self_ = ReplaceTable { rules: vec![] };
         self_.iterate(target, to);
         self_
@@ -3273,7 +3520,7 @@ impl<'t> ReplaceTable<'t> {
         ty
     }
 
-    fn iterate(&mut self, target: &'t Type, to: &'t Type) {
+    fn iterate(&mut self, target: &Type, to: &Type) {
         match (target, to) {
             (
                 Type::Poly { name, params },
@@ -3300,6 +3547,9 @@ impl<'t> ReplaceTable<'t> {
                 for (ld, rd) in lsub.default_params.iter().zip(rsub.default_params.iter()) {
                     self.iterate(ld.typ(), rd.typ());
                 }
+                for (lk, rk) in lsub.kw_var_params.iter().zip(rsub.kw_var_params.iter()) {
+                    self.iterate(lk.typ(), rk.typ());
+                }
                 self.iterate(lsub.return_t.as_ref(), rsub.return_t.as_ref());
             }
             (Type::Quantified(quant), Type::Quantified(quant2)) => {
@@ -3358,12 +3608,14 @@ impl<'t> ReplaceTable<'t> {
             }
             _ => {}
         }
-        self.rules.push((target, to));
+        self.rules.push((target.clone(), to.clone()));
     }
 
-    fn iterate_tp(&mut self, target: &'t TyParam, to: &'t TyParam) {
+    fn iterate_tp(&mut self, target: &TyParam, to: &TyParam) {
         match (target, to) {
-            (TyParam::FreeVar(fv), to) if fv.is_linked() => self.iterate_tp(fv.unsafe_crack(), to),
+            (TyParam::FreeVar(fv), to) if fv.is_linked() => {
+                self.iterate_tp(&fv.get_linked().unwrap(), to)
+            }
             (TyParam::Value(ValueObj::Type(target)), TyParam::Value(ValueObj::Type(to))) => {
                 self.iterate(target.typ(), to.typ());
             }
@@ -661,13 +661,25 @@ impl TryFrom<TyParam> for Vec<TyParam> {
     }
 }
 
+impl TryFrom<TyParam> for Type {
+    type Error = ();
+    fn try_from(tp: TyParam) -> Result<Type, ()> {
+        match tp {
+            TyParam::FreeVar(fv) if fv.is_linked() => Type::try_from(fv.crack().clone()),
+            TyParam::Type(t) => Ok(*t),
+            TyParam::Value(v) => <&Type>::try_from(&v).cloned(),
+            // TODO: Array, Dict, Set
+            _ => Err(()),
+        }
+    }
+}
+
+/// Linked type variables are not dereferenced (the link may be replaced while the reference is alive).
+/// Use `Type::try_from(tp.clone())` if `tp` may be a linked type variable.
 impl<'a> TryFrom<&'a TyParam> for &'a Type {
     type Error = ();
     fn try_from(tp: &'a TyParam) -> Result<&'a Type, ()> {
         match tp {
-            TyParam::FreeVar(fv) if fv.is_linked() => {
-                <&'a Type>::try_from(fv.forced_as_ref().linked().unwrap())
-            }
             TyParam::Type(t) => Ok(t.as_ref()),
             TyParam::Value(v) => <&Type>::try_from(v),
             // TODO: Array, Dict, Set
@@ -996,7 +1008,7 @@ impl TyParam {
 
     pub fn qvars(&self) -> Set<(Str, Constraint)> {
         match self {
-            Self::FreeVar(fv) if fv.is_linked() => fv.forced_as_ref().linked().unwrap().qvars(),
+            Self::FreeVar(fv) if fv.is_linked() => fv.get_linked().unwrap().qvars(),
             Self::FreeVar(fv) if !fv.constraint_is_uninited() => {
                 let base = set! {(fv.unbound_name().unwrap(), fv.constraint().unwrap())};
                 if let Some(ty) = fv.get_type() {
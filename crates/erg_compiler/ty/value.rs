@@ -880,6 +880,26 @@ impl HasType for ValueObj {
     }
 }
 
+/// Splits a numeric literal into its sign, radix and digits (`_` and the type suffix are removed).
+/// e.g. `-0xdead_beef` -> `(true, 16, "deadbeef")`, `1_000n` -> `(false, 10, "1000")`
+fn split_num_literal(content: &str) -> (bool, u32, String) {
+    let (negative, content) = match content.strip_prefix('-') {
+        Some(content) => (true, content),
+        None => (false, content),
+    };
+    let content = content.replace('_', "");
+    match content.get(..2) {
+        Some("0b" | "0B") => (negative, 2, content[2..].to_string()),
+        Some("0o" | "0O") => (negative, 8, content[2..].to_string()),
+        // `f` is a hex digit, hex literals have no suffix
+        Some("0x" | "0X") => (negative, 16, content[2..].to_string()),
+        _ => {
+            let digits = content.trim_end_matches(['f', 'i', 'n']);
+            (negative, 10, digits.to_string())
+        }
+    }
+}
+
 impl ValueObj {
     pub const fn builtin_class(t: Type) -> Self {
         ValueObj::Type(TypeObj::Builtin {
@@ -943,41 +963,24 @@ impl ValueObj {
 
     pub fn from_str(t: Type, mut content: Str) -> Option<Self> {
         match t {
-            Type::Int => content.replace('_', "").parse::<i32>().ok().map(Self::Int),
+            Type::Int => {
+                let (negative, radix, digits) = split_num_literal(&content);
+                let n = i64::from_str_radix(&digits, radix).ok()?;
+                i32::try_from(if negative { -n } else { n })
+                    .ok()
+                    .map(Self::Int)
+            }
             Type::Nat => {
-                let content = content
-                    .trim_start_matches('-') // -0 -> 0
-                    .replace('_', "");
-                if content.len() <= 1 {
-                    return content.parse::<u64>().ok().map(Self::Nat);
-                }
-                match &content[0..=1] {
-                    pre @ ("0b" | "0B") => {
-                        let content = content.trim_start_matches(pre);
-                        u64::from_str_radix(content, 2).ok().map(Self::Nat)
-                    }
-                    pre @ ("0o" | "0O") => {
-                        let content = content.trim_start_matches(pre);
-                        u64::from_str_radix(content, 8).ok().map(Self::Nat)
-                    }
-                    pre @ ("0x" | "0X") => {
-                        let content = content.trim_start_matches(pre);
-                        u64::from_str_radix(content, 16).ok().map(Self::Nat)
-                    }
-                    _ => content.parse::<u64>().ok().map(Self::Nat),
-                }
+                // -0 -> 0
+                let (_, radix, digits) = split_num_literal(&content);
+                u64::from_str_radix(&digits, radix).ok().map(Self::Nat)
+            }
+            // TODO: Ratio
+            Type::Float | Type::Ratio => {
+                let (negative, _, digits) = split_num_literal(&content);
+                let f = digits.parse::<f64>().ok()?;
+                Some(Self::Float(if negative { -f } else { f }))
             }
-            Type::Float => content
-                .replace('_', "")
-                .parse::<f64>()
-                .ok()
-                .map(Self::Float),
-            // TODO:
-            Type::Ratio => content
-                .replace('_', "")
-                .parse::<f64>()
-                .ok()
-                .map(Self::Float),
             Type::Str => {
                 if &content[..] == "\"\"" {
                     Some(Self::Str(Str::from("")))
@@ -1221,7 +1224,21 @@ impl ValueObj {
         }
     }
 
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Nat(n) => *n == 0,
+            Self::Int(i) => *i == 0,
+            Self::Float(f) => *f == 0.0,
+            Self::Bool(b) => !*b,
+            _ => false,
+        }
+    }
+
+    /// Returns `None` if `other` is zero (it will raise `ZeroDivisionError` at runtime)
     pub fn try_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
         match (self, other) {
             (Self::Int(l), Self::Int(r)) => Some(Self::Float(l as f64 / r as f64)),
             (Self::Nat(l), Self::Nat(r)) => Some(Self::Float(l as f64 / r as f64)),
@@ -1237,7 +1254,11 @@ impl ValueObj {
         }
     }
 
+    /// Returns `None` if `other` is zero (it will raise `ZeroDivisionError` at runtime)
     pub fn try_floordiv(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
         match (self, other) {
             (Self::Int(l), Self::Int(r)) => Some(Self::Int(l / r)),
             (Self::Nat(l), Self::Nat(r)) => Some(Self::Nat(l / r)),
@@ -1253,6 +1274,18 @@ impl ValueObj {
         }
     }
 
+    pub fn try_pow(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Nat(l), Self::Nat(r)) => l.checked_pow(u32::try_from(r).ok()?).map(Self::Nat),
+            (Self::Int(l), Self::Nat(r)) => l.checked_pow(u32::try_from(r).ok()?).map(Self::Int),
+            (Self::Float(l), Self::Nat(r)) => Some(Self::Float(l.powi(i32::try_from(r).ok()?))),
+            (Self::Float(l), Self::Float(r)) => Some(Self::Float(l.powf(r))),
+            (Self::Nat(l), Self::Float(r)) => Some(Self::Float((l as f64).powf(r))),
+            (Self::Int(l), Self::Float(r)) => Some(Self::Float((l as f64).powf(r))),
+            _ => None,
+        }
+    }
+
     pub fn try_gt(self, other: Self) -> Option<Self> {
         match (self, other) {
             (Self::Int(l), Self::Int(r)) => Some(Self::from(l > r)),
@@ -2,12 +2,14 @@ use std::fmt;
 use std::path::PathBuf;
 
 use erg_common::error::Location;
+use erg_common::python_util::PythonVersion;
 use erg_common::set::Set;
 use erg_common::{switch_lang, Str};
 
 use erg_parser::ast::DefId;
 
 use crate::context::DefaultInfo;
+use crate::stable_id::StableDefId;
 use crate::ty::{Field, HasType, Type, Visibility};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -98,7 +100,7 @@ impl VarKind {
         matches!(self, Self::InstanceAttr)
     }
 
-    pub const fn display(&self) -> &'static str {
+    pub fn display(&self) -> &'static str {
         match self {
             Self::Auto | Self::FixedAuto => switch_lang!(
                 "japanese" => "自動",
@@ -198,6 +200,8 @@ pub struct VarInfo {
     pub impl_of: Option<Type>,
     pub py_name: Option<Str>,
     pub def_loc: AbsLocation,
+    /// The Python version in which the API was introduced (`@Available python:="3.10"`)
+    pub available_since: Option<PythonVersion>,
 }
 
 impl fmt::Display for VarInfo {
@@ -284,9 +288,15 @@ impl VarInfo {
             impl_of,
             py_name,
             def_loc,
+            available_since: None,
         }
     }
 
+    /// The ID of this definition (as `name`), which is stable across sessions (see `crate::stable_id`).
+    pub fn stable_id(&self, name: &str) -> StableDefId {
+        StableDefId::of(name, self)
+    }
+
     pub fn same_id_as(&self, id: DefId) -> bool {
         match self.kind {
             VarKind::Defined(i) | VarKind::Parameter { def_id: i, .. } => id == i,
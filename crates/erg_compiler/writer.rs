@@ -0,0 +1,214 @@
+//! Writes the artifacts of the compiler (`.pyc`, `.py`, `--emit`, `erg doc`).
+//!
+//! The IO failures are reported as `CompileError`s with the path and a hint (instead of panicking),
+//! and with `--dry-run`, the writer only checks that the artifacts can be written.
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::error::Location;
+use erg_common::io::Input;
+use erg_common::switch_lang;
+
+use crate::error::CompileError;
+
+#[derive(Debug, Clone)]
+pub struct ArtifactWriter {
+    input: Input,
+    dry_run: bool,
+    quiet: bool,
+    /// the directories that would be created by `create_dir_all` (with `--dry-run`)
+    planned_dirs: RefCell<Vec<PathBuf>>,
+}
+
+impl ArtifactWriter {
+    pub fn new(cfg: &ErgConfig) -> Self {
+        Self {
+            input: cfg.input.clone(),
+            dry_run: cfg.dry_run,
+            quiet: cfg.quiet || cfg.json_output,
+            planned_dirs: RefCell::new(vec![]),
+        }
+    }
+
+    /// Creates the directory and its parents.
+    /// With `--dry-run`, checks that the nearest existing ancestor is a writable directory.
+    pub fn create_dir_all(&self, dir: &Path) -> Result<(), CompileError> {
+        let result = if self.dry_run {
+            let result = writable_dir(nearest_existing(dir));
+            if result.is_ok() && !dir.exists() {
+                self.planned_dirs.borrow_mut().push(dir.to_path_buf());
+            }
+            result
+        } else {
+            fs::create_dir_all(dir)
+        };
+        result.map_err(|err| self.create_dir_error(dir, err))
+    }
+
+    /// With `--dry-run`, checks that `path` can be written, and shows the path instead of writing it.
+    pub fn write(&self, path: &Path, content: &[u8]) -> Result<(), CompileError> {
+        if self.dry_run {
+            self.check_writable(path)
+                .map_err(|err| self.error(path, err))?;
+            if !self.quiet {
+                println!("would write {} ({} bytes)", path.display(), content.len());
+            }
+            Ok(())
+        } else {
+            fs::write(path, content).map_err(|err| self.error(path, err))
+        }
+    }
+
+    /// Checks that `path` can be written without modifying it.
+    fn check_writable(&self, path: &Path) -> io::Result<()> {
+        let parent = parent_of(path);
+        if path.is_dir() {
+            Err(io::Error::from(ErrorKind::IsADirectory))
+        } else if path.exists() {
+            // opening without truncation does not change the file
+            OpenOptions::new().write(true).open(path).map(|_| ())
+        } else if self
+            .planned_dirs
+            .borrow()
+            .iter()
+            .any(|dir| parent.starts_with(dir))
+        {
+            Ok(())
+        } else {
+            writable_dir(parent)
+        }
+    }
+
+    /// An error of writing `path`.
+    pub fn error(&self, path: &Path, err: io::Error) -> CompileError {
+        let path_s = path.display();
+        CompileError::file_error(
+            self.input.clone(),
+            line!() as usize,
+            switch_lang!(
+                "japanese" => format!("{path_s}に書き込めません: {err}"),
+                "simplified_chinese" => format!("无法写入{path_s}: {err}"),
+                "traditional_chinese" => format!("無法寫入{path_s}: {err}"),
+                "english" => format!("cannot write {path_s}: {err}"),
+            ),
+            Location::Unknown,
+            "<module>".into(),
+            hint(path, &err),
+        )
+    }
+
+    fn create_dir_error(&self, dir: &Path, err: io::Error) -> CompileError {
+        let dir_s = dir.display();
+        CompileError::file_error(
+            self.input.clone(),
+            line!() as usize,
+            switch_lang!(
+                "japanese" => format!("ディレクトリ{dir_s}を作成できません: {err}"),
+                "simplified_chinese" => format!("无法创建目录{dir_s}: {err}"),
+                "traditional_chinese" => format!("無法創建目錄{dir_s}: {err}"),
+                "english" => format!("cannot create the directory {dir_s}: {err}"),
+            ),
+            Location::Unknown,
+            "<module>".into(),
+            hint(nearest_existing(dir).join("_").as_path(), &err),
+        )
+    }
+}
+
+fn parent_of(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => Path::new("."),
+    }
+}
+
+/// The directory itself if it exists, otherwise the nearest ancestor that exists
+fn nearest_existing(dir: &Path) -> &Path {
+    let mut dir = dir;
+    while !dir.exists() {
+        match dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => dir = parent,
+            _ => return Path::new("."),
+        }
+    }
+    dir
+}
+
+fn writable_dir(dir: &Path) -> io::Result<()> {
+    let meta = fs::metadata(dir)?;
+    if !meta.is_dir() {
+        Err(io::Error::from(ErrorKind::NotADirectory))
+    } else if meta.permissions().readonly() {
+        Err(io::Error::from(ErrorKind::PermissionDenied))
+    } else {
+        Ok(())
+    }
+}
+
+fn hint(path: &Path, err: &io::Error) -> Option<String> {
+    let path_s = path.display();
+    let parent = parent_of(path);
+    let parent_s = parent.display();
+    let hint = match err.kind() {
+        ErrorKind::NotFound if !parent.exists() => switch_lang!(
+            "japanese" => format!("ディレクトリ{parent_s}が存在しません(作成するか、--output-dirで出力先を変更してください)"),
+            "simplified_chinese" => format!("目录{parent_s}不存在(请创建它, 或使用 --output-dir 更改输出目录)"),
+            "traditional_chinese" => format!("目錄{parent_s}不存在(請創建它, 或使用 --output-dir 更改輸出目錄)"),
+            "english" => format!("the directory {parent_s} does not exist (create it, or change the output directory with --output-dir)"),
+        ),
+        ErrorKind::PermissionDenied
+            if fs::metadata(parent).is_ok_and(|meta| meta.permissions().readonly()) =>
+        {
+            switch_lang!(
+                "japanese" => format!("ディレクトリ{parent_s}は読み取り専用です"),
+                "simplified_chinese" => format!("目录{parent_s}是只读的"),
+                "traditional_chinese" => format!("目錄{parent_s}是唯讀的"),
+                "english" => format!("the directory {parent_s} is read-only"),
+            )
+        }
+        ErrorKind::PermissionDenied => switch_lang!(
+            "japanese" => format!("{path_s}への書き込み権限がありません(ファイルとディレクトリのパーミッションを確認してください)"),
+            "simplified_chinese" => format!("没有写入{path_s}的权限(请检查文件和目录的权限)"),
+            "traditional_chinese" => format!("沒有寫入{path_s}的權限(請檢查檔案和目錄的權限)"),
+            "english" => format!("you do not have the permission to write {path_s} (check the permissions of the file and the directory)"),
+        ),
+        ErrorKind::ReadOnlyFilesystem => switch_lang!(
+            "japanese" => format!("{parent_s}は読み取り専用のファイルシステム上にあります"),
+            "simplified_chinese" => format!("{parent_s}位于只读文件系统上"),
+            "traditional_chinese" => format!("{parent_s}位於唯讀檔案系統上"),
+            "english" => format!("{parent_s} is on a read-only file system"),
+        ),
+        ErrorKind::IsADirectory => switch_lang!(
+            "japanese" => format!("{path_s}はディレクトリです"),
+            "simplified_chinese" => format!("{path_s}是一个目录"),
+            "traditional_chinese" => format!("{path_s}是一個目錄"),
+            "english" => format!("{path_s} is a directory"),
+        ),
+        ErrorKind::NotADirectory => switch_lang!(
+            "japanese" => format!("{}はディレクトリではありません", nearest_file(parent).display()),
+            "simplified_chinese" => format!("{}不是目录", nearest_file(parent).display()),
+            "traditional_chinese" => format!("{}不是目錄", nearest_file(parent).display()),
+            "english" => format!("{} is not a directory", nearest_file(parent).display()),
+        ),
+        ErrorKind::StorageFull => switch_lang!(
+            "japanese" => "デバイスに空き容量がありません".to_string(),
+            "simplified_chinese" => "设备上没有剩余空间".to_string(),
+            "traditional_chinese" => "裝置上沒有剩餘空間".to_string(),
+            "english" => "no space is left on the device".to_string(),
+        ),
+        _ => return None,
+    };
+    Some(hint)
+}
+
+/// The ancestor of `dir` that is a file (the cause of `NotADirectory`)
+fn nearest_file(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| ancestor.is_file())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
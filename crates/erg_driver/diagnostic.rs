@@ -0,0 +1,201 @@
+//! Errors and warnings as plain data.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use erg_common::error::Location;
+use erg_common::style::remove_style;
+use erg_compiler::error::{CompileError, CompileErrors};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A range of the source. Lines and columns are 1-origin, and the end column is exclusive.
+/// The columns are `None` if the diagnostic refers to whole lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub line: u32,
+    pub column: Option<u32>,
+    pub end_line: u32,
+    pub end_column: Option<u32>,
+}
+
+impl Span {
+    fn from_loc(loc: Location) -> Option<Self> {
+        match loc {
+            Location::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                ..
+            } => Some(Self {
+                line: ln_begin,
+                column: Some(col_begin + 1),
+                end_line: ln_end,
+                end_column: Some(col_end + 1),
+            }),
+            Location::LineRange(ln_begin, ln_end) => Some(Self {
+                line: ln_begin,
+                column: None,
+                end_line: ln_end,
+                end_column: None,
+            }),
+            Location::Line(ln) => Some(Self {
+                line: ln,
+                column: None,
+                end_line: ln,
+                end_column: None,
+            }),
+            Location::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    code: String,
+    message: String,
+    hint: Option<String>,
+    path: PathBuf,
+    span: Option<Span>,
+}
+
+/// e.g. `<string>:1:10: error[TypeError]: ...`
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(span) = self.span {
+            write!(f, ":{}", span.line)?;
+            if let Some(column) = span.column {
+                write!(f, ":{column}")?;
+            }
+        }
+        write!(f, ": {}[{}]: {}", self.severity, self.code, self.message)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\nhint: {hint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    fn new(err: &CompileError, severity: Severity) -> Self {
+        Self {
+            severity,
+            code: format!("{:?}", err.core.kind),
+            message: remove_style(&err.core.main_message),
+            hint: err.core.get_hint().map(remove_style),
+            path: err.input.path().to_path_buf(),
+            span: Span::from_loc(err.core.get_loc_with_fallback()),
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The kind of the diagnostic (e.g. `TypeError`, `UnusedWarning`).
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The message (without the ANSI escape sequences). It is localized if the crate is built with a language feature.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// The path of the module, `Options::with_path` or `<string>` for the given source.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+/// The diagnostics of a check or a compilation (including those of the imported modules).
+/// The errors come first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diag in self.diagnostics.iter() {
+            writeln!(f, "{diag}")?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Report {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+impl Report {
+    pub(crate) fn new(errors: &CompileErrors, warns: &CompileErrors) -> Self {
+        let errors = errors
+            .iter()
+            .map(|err| Diagnostic::new(err, Severity::Error));
+        let warns = warns
+            .iter()
+            .map(|warn| Diagnostic::new(warn, Severity::Warning));
+        Self {
+            diagnostics: errors.chain(warns).collect(),
+        }
+    }
+
+    /// Merges the reports (the errors come first).
+    pub(crate) fn concat<'a>(reports: impl Iterator<Item = &'a Report> + Clone) -> Self {
+        let diags = reports.flat_map(|report| report.diagnostics.iter());
+        let errors = diags.clone().filter(|diag| diag.is_error());
+        let warns = diags.filter(|diag| !diag.is_error());
+        Self {
+            diagnostics: errors.chain(warns).cloned().collect(),
+        }
+    }
+
+    /// No errors are reported (there may be warnings).
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diag| diag.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(|diag| !diag.is_error())
+    }
+}
@@ -0,0 +1,38 @@
+//! A stable API to embed the Erg compiler (e.g. in build systems, notebooks and web playgrounds).
+//!
+//! The internal types of `erg_compiler` (the configuration, the HIR, the errors, etc.) change in every release.
+//! This crate exposes only plain, owned data (diagnostics, `.pyc` bytes and Python scripts),
+//! so the embedders do not have to follow those changes.
+//!
+//! ```
+//! use erg_driver::{Artifact, Options, Session, Target};
+//!
+//! let report = erg_driver::check_str("x: Int = \"a\"");
+//! let error = report.errors().next().unwrap();
+//! assert_eq!(error.code(), "TypeError");
+//! assert_eq!(error.span().unwrap().line, 1);
+//!
+//! let mut session = Session::new(Options::default().with_target(Target::Python));
+//! let compilation = session.compile_str("print! 1 + 1");
+//! let Some(Artifact::Script(script)) = compilation.artifact() else {
+//!     panic!("{}", compilation.report());
+//! };
+//! assert!(script.contains("print"));
+//! ```
+//!
+//! The compiler recurses deeply on nested code, so run it on a thread with a large stack (8 MiB or more).
+pub mod diagnostic;
+pub mod session;
+
+pub use diagnostic::{Diagnostic, Report, Severity, Span};
+pub use session::{Artifact, BatchCompilation, Compilation, Options, Session, Target};
+
+/// Type-checks `src` with the default options.
+pub fn check_str(src: &str) -> Report {
+    Session::default().check_str(src)
+}
+
+/// Compiles `src` into a `.pyc` with the default options.
+pub fn compile_str(src: &str) -> Compilation {
+    Session::default().compile_str(src)
+}
@@ -0,0 +1,252 @@
+//! Options and sessions of the compilation.
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::io::{Input, Output};
+use erg_common::pathutil::NormalizedPathBuf;
+use erg_common::serialize::get_ver_from_magic_num;
+use erg_common::traits::Stream;
+use erg_common::Str;
+use erg_compiler::artifact::{Buildable, CompleteArtifact, IncompleteArtifact};
+use erg_compiler::batch::BatchCompiler;
+use erg_compiler::error::CompileErrors;
+use erg_compiler::module::SharedCompilerResource;
+use erg_compiler::transpile::Transpiler;
+use erg_compiler::{Compiler, HIRBuilder};
+
+use crate::diagnostic::Report;
+
+fn config(options: &Options, src: String) -> ErgConfig {
+    let input = match &options.path {
+        Some(path) => Input::buffer(path.clone(), src),
+        None => Input::str(src),
+    };
+    let mut cfg = ErgConfig {
+        input,
+        output: Output::Null,
+        quiet: true,
+        ..ErgConfig::default()
+    };
+    if let Some(opt_level) = options.opt_level {
+        cfg.opt_level = opt_level;
+    }
+    if let Some(num) = options.py_magic_num {
+        cfg.py_magic_num = Some(num);
+        cfg.target_version = Some(get_ver_from_magic_num(num));
+    }
+    cfg
+}
+
+/// What `Session::compile_str` generates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The content of a `.pyc` file
+    #[default]
+    Bytecode,
+    /// A Python script
+    Python,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    target: Target,
+    path: Option<PathBuf>,
+    opt_level: Option<u8>,
+    py_magic_num: Option<u32>,
+}
+
+impl Options {
+    pub fn with_target(mut self, target: Target) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// The path of the source, used in the diagnostics and to resolve the relative imports.
+    /// The source is not read from the path.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_opt_level(mut self, opt_level: u8) -> Self {
+        self.opt_level = Some(opt_level);
+        self
+    }
+
+    /// The magic number of the target Python (e.g. 3495 for Python 3.11).
+    /// By default, the magic number of the Python in the environment is used.
+    pub fn with_py_magic_num(mut self, py_magic_num: u32) -> Self {
+        self.py_magic_num = Some(py_magic_num);
+        self
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Artifact {
+    Pyc(Vec<u8>),
+    Script(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compilation {
+    artifact: Option<Artifact>,
+    report: Report,
+}
+
+impl Compilation {
+    pub fn is_ok(&self) -> bool {
+        self.artifact.is_some()
+    }
+
+    /// `None` if any errors are reported.
+    pub fn artifact(&self) -> Option<&Artifact> {
+        self.artifact.as_ref()
+    }
+
+    pub fn into_artifact(self) -> Option<Artifact> {
+        self.artifact
+    }
+
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+}
+
+/// The compilations of the files given to `Session::compile_files`, in the order of the files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchCompilation {
+    files: Vec<(PathBuf, Compilation)>,
+}
+
+impl BatchCompilation {
+    /// No errors are reported in any of the files.
+    pub fn is_ok(&self) -> bool {
+        self.files
+            .iter()
+            .all(|(_, compilation)| compilation.is_ok())
+    }
+
+    pub fn files(&self) -> &[(PathBuf, Compilation)] {
+        &self.files
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<&Compilation> {
+        self.files
+            .iter()
+            .find(|(file, _)| file == path.as_ref())
+            .map(|(_, compilation)| compilation)
+    }
+
+    /// The diagnostics of all the files (the errors come first).
+    pub fn report(&self) -> Report {
+        Report::concat(
+            self.files
+                .iter()
+                .map(|(_, compilation)| compilation.report()),
+        )
+    }
+}
+
+/// A session holds the builtin and imported modules, so the consecutive checks/compilations are faster.
+/// The definitions of a source do not leak into the next one.
+#[derive(Debug)]
+pub struct Session {
+    options: Options,
+    shared: SharedCompilerResource,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new(Options::default())
+    }
+}
+
+impl Session {
+    pub fn new(options: Options) -> Self {
+        let shared = SharedCompilerResource::new(config(&options, String::new()));
+        Self { options, shared }
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Discards the cached modules (e.g. after the imported files are edited).
+    pub fn reset(&mut self) {
+        self.shared = SharedCompilerResource::new(config(&self.options, String::new()));
+        Str::clear_interned();
+    }
+
+    pub fn check_str(&mut self, src: &str) -> Report {
+        let (_, report) = self.run::<HIRBuilder, _>(src);
+        report
+    }
+
+    pub fn compile_str(&mut self, src: &str) -> Compilation {
+        let (artifact, report) = match self.options.target {
+            Target::Bytecode => {
+                let (code, report) = self.run::<Compiler, _>(src);
+                let pyc =
+                    code.map(|code| Artifact::Pyc(code.into_pyc_bytes(self.options.py_magic_num)));
+                (pyc, report)
+            }
+            Target::Python => {
+                let (script, report) = self.run::<Transpiler, _>(src);
+                (script.map(|script| Artifact::Script(script.code)), report)
+            }
+        };
+        Compilation { artifact, report }
+    }
+
+    /// Compiles the files, directories and glob patterns into `.pyc`s (regardless of `Options::with_target`).
+    /// The imported modules are checked only once, and the files are not written.
+    /// If any errors are reported, no artifacts are generated (the files may import the erroneous modules).
+    pub fn compile_files<P: AsRef<Path>>(&mut self, targets: &[P]) -> BatchCompilation {
+        let cfg = ErgConfig {
+            targets: targets.iter().map(|t| t.as_ref().to_path_buf()).collect(),
+            ..config(&self.options, String::new())
+        };
+        let mut compiler = BatchCompiler::inherit(cfg, self.shared.clone());
+        let paths = compiler.collect_targets();
+        // the files may have been edited since the last compilation
+        for path in paths.iter() {
+            let path = NormalizedPathBuf::from(path.as_path());
+            self.shared.clear(&path);
+            self.shared.promises.remove(&path);
+        }
+        let files = compiler
+            .compile_files(&paths)
+            .into_iter()
+            .map(|result| {
+                let report = Report::new(&result.check.errors, &result.check.warns);
+                let artifact = result
+                    .code
+                    .map(|code| Artifact::Pyc(code.into_pyc_bytes(self.options.py_magic_num)));
+                (result.check.path, Compilation { artifact, report })
+            })
+            .collect();
+        BatchCompilation { files }
+    }
+
+    fn run<B: Buildable<T>, T>(&mut self, src: &str) -> (Option<T>, Report) {
+        let cfg = config(&self.options, src.to_string());
+        self.shared.clear(cfg.input.path());
+        let shared = self.shared.inherit(cfg.input.path().to_path_buf());
+        let mut builder = B::inherit(cfg, shared);
+        let (object, mut errors, mut warns) = match builder.build(src.to_string(), "exec") {
+            Ok(CompleteArtifact { object, warns }) => (Some(object), CompileErrors::empty(), warns),
+            Err(IncompleteArtifact { errors, warns, .. }) => (None, errors, warns),
+        };
+        // the errors of the imported modules
+        self.shared.promises.join_all();
+        errors.extend(self.shared.errors.take());
+        warns.extend(self.shared.warns.take());
+        let report = Report::new(&errors, &warns);
+        let object = object.filter(|_| report.is_ok());
+        (object, report)
+    }
+}
@@ -0,0 +1,87 @@
+use erg_common::spawn::exec_new_thread;
+
+use erg_driver::{Artifact, Options, Session, Severity, Target};
+
+#[test]
+fn test_check_str() -> Result<(), ()> {
+    exec_new_thread(_test_check_str, "test_check_str")
+}
+
+fn _test_check_str() -> Result<(), ()> {
+    let mut session = Session::new(Options::default().with_path("foo.er"));
+    let report = session.check_str("x = 1\ny: Int = \"a\"");
+    assert!(!report.is_ok());
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    let error = errors[0];
+    assert_eq!(error.severity(), Severity::Error);
+    assert_eq!(error.code(), "TypeError");
+    assert_eq!(error.path().to_str(), Some("foo.er"));
+    assert_eq!(error.span().map(|span| span.line), Some(2));
+    assert!(!error.message().contains('\x1b'));
+    assert!(error.to_string().starts_with("foo.er:2:"));
+    assert!(session.check_str("print! 1").is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_compile_str() -> Result<(), ()> {
+    exec_new_thread(_test_compile_str, "test_compile_str")
+}
+
+fn _test_compile_str() -> Result<(), ()> {
+    let mut session = Session::new(Options::default().with_py_magic_num(3495));
+    let compilation = session.compile_str("x = 1\nprint! x + 1");
+    let Some(Artifact::Pyc(pyc)) = compilation.artifact() else {
+        panic!("{}", compilation.report());
+    };
+    assert_eq!(&pyc[..4], &[0xa7, 0x0d, 0x0d, 0x0a]);
+    let compilation = session.compile_str("print! 1 + \"a\"");
+    assert!(compilation.artifact().is_none());
+    assert!(!compilation.report().is_ok());
+
+    let mut session = Session::new(Options::default().with_target(Target::Python));
+    let compilation = session.compile_str("f x = x + 1\nprint! f 1");
+    let Some(Artifact::Script(script)) = compilation.into_artifact() else {
+        panic!("script expected");
+    };
+    assert!(script.contains("def f"));
+    Ok(())
+}
+
+#[test]
+fn test_session_reuse() -> Result<(), ()> {
+    exec_new_thread(_test_session_reuse, "test_session_reuse")
+}
+
+fn _test_session_reuse() -> Result<(), ()> {
+    let mut session = Session::default();
+    assert!(session.check_str("x = 1").is_ok());
+    // `x` of the previous source is not visible
+    let report = session.check_str("print! x");
+    assert_eq!(
+        report.errors().next().map(|err| err.code()),
+        Some("NameError")
+    );
+    session.reset();
+    assert!(session.check_str("x = 1\nprint! x").is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_compile_files() -> Result<(), ()> {
+    exec_new_thread(_test_compile_files, "test_compile_files")
+}
+
+fn _test_compile_files() -> Result<(), ()> {
+    let mut session = Session::default();
+    let batch = session.compile_files(&["../erg_compiler/tests/batch"]);
+    assert!(batch.is_ok(), "{}", batch.report());
+    assert_eq!(batch.files().len(), 3);
+    let main = batch.get("../erg_compiler/tests/batch/main.er").unwrap();
+    assert!(matches!(main.artifact(), Some(Artifact::Pyc(_))));
+    // the files are checked again in the same session
+    let batch = session.compile_files(&["../erg_compiler/tests/batch/*.er"]);
+    assert!(batch.files().iter().all(|(_, c)| c.is_ok()));
+    Ok(())
+}
@@ -0,0 +1,138 @@
+//! Applies the machine-applicable suggestions in place (`erg fix`).
+//! Both the suggestions of the compiler and those of the lints are applied.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::diff::unified_diff;
+use erg_common::error::{ErrorDisplay, MultiErrorDisplay};
+use erg_common::exit_code;
+use erg_common::switch_lang;
+use erg_common::traits::{ExitStatus, Stream};
+
+use crate::runner::{validate_allowed_lints, FileLintResult, LintRunner};
+
+/// The changes made to a file by `erg fix`.
+#[derive(Debug)]
+pub struct FileFixResult {
+    pub path: PathBuf,
+    /// the source code before the fixes are applied
+    pub before: String,
+    /// the source code after the fixes are applied
+    pub after: String,
+    /// the diagnostics remaining after the fixes
+    pub remaining: FileLintResult,
+}
+
+impl FileFixResult {
+    /// the number of the fixes applied
+    pub fn fixed(&self) -> usize {
+        self.remaining.fixed
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+
+    pub fn diff(&self) -> String {
+        let name = self.path.to_string_lossy();
+        unified_diff(&self.before, &self.after, &name, &name, 3)
+    }
+}
+
+#[derive(Debug)]
+pub struct FixRunner {
+    runner: LintRunner,
+}
+
+impl FixRunner {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            runner: LintRunner::new(cfg),
+        }
+    }
+
+    pub fn cfg(&self) -> &ErgConfig {
+        self.runner.cfg()
+    }
+
+    /// Re-checks the file and applies the fixes until a fixpoint (or the round limit) is reached.
+    /// Files with errors are left as they are.
+    pub fn fix_file(&mut self, path: &Path) -> FileFixResult {
+        let before = fs::read_to_string(path).unwrap_or_default();
+        let remaining = self.runner.fix_file(path);
+        let after = fs::read_to_string(path).unwrap_or_else(|_| before.clone());
+        FileFixResult {
+            path: path.to_path_buf(),
+            before,
+            after,
+            remaining,
+        }
+    }
+
+    /// Fixes the targets (or the package) and prints the diff of each changed file.
+    /// The exit code is not 0 if any file cannot be fixed because of errors.
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        if !validate_allowed_lints(&cfg) {
+            return ExitStatus::new(exit_code::USAGE_ERROR, 0, 0);
+        }
+        let mut fixer = Self::new(cfg);
+        if !fixer.cfg().input.is_repl() {
+            eprintln!(
+                "{}",
+                switch_lang!(
+                    "japanese" => "erg fixはファイルを書き換えるため、ファイルを指定してください",
+                    "simplified_chinese" => "erg fix 会改写文件，请指定文件",
+                    "traditional_chinese" => "erg fix 會改寫檔案，請指定檔案",
+                    "english" => "erg fix rewrites files, so please give the files to fix",
+                )
+            );
+            return ExitStatus::ERR1;
+        }
+        let targets = fixer.runner.collect_targets();
+        if targets.is_empty() {
+            eprintln!(
+                "{}",
+                switch_lang!(
+                    "japanese" => "修正するファイルがありません(パッケージ外で、ファイルも指定されていません)",
+                    "simplified_chinese" => "没有要修复的文件(不在包中，也没有指定文件)",
+                    "traditional_chinese" => "沒有要修正的檔案(不在套件中，也沒有指定檔案)",
+                    "english" => "no files to fix (not in a package, and no files are given)",
+                )
+            );
+            return ExitStatus::ERR1;
+        }
+        let (mut num_errors, mut fixed, mut changed) = (0, 0, 0);
+        let mut code = exit_code::OK;
+        for path in targets.iter() {
+            let result = fixer.fix_file(path);
+            if !result.remaining.errors.is_empty() {
+                // the fixes are not applied to the files with errors
+                result.remaining.errors.write_all_stderr();
+                num_errors += result.remaining.errors.len();
+                code = code.max(exit_code::of_errors(
+                    result.remaining.errors.iter().map(|err| err.core()),
+                ));
+            }
+            if result.is_changed() {
+                if !fixer.cfg().quiet {
+                    print!(
+                        "{}: {} fix(es) applied\n{}",
+                        result.path.display(),
+                        result.fixed(),
+                        result.diff()
+                    );
+                }
+                changed += 1;
+            }
+            fixed += result.fixed();
+        }
+        if !fixer.cfg().quiet {
+            println!(
+                "fixed {fixed} issue(s) in {changed} file(s) ({} file(s) checked)",
+                targets.len()
+            );
+        }
+        ExitStatus::new(code, 0, num_errors)
+    }
+}
@@ -0,0 +1,11 @@
+//! The Erg linter (`erg lint`, `erg fix`).
+//! The lints here do not affect the compilation (cf. `erg_compiler::lint`).
+pub mod fix;
+pub mod lint;
+pub mod runner;
+pub mod warning;
+
+pub use fix::FixRunner;
+pub use lint::{Lint, Linter};
+pub use runner::LintRunner;
+pub use warning::LintWarning;
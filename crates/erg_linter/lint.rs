@@ -1,19 +1,306 @@
-use erg_common::Str;
+use std::f64::consts::{E, PI, TAU};
+use std::fmt;
 
-use crate::error::CompileWarnings;
-use crate::hir::HIR;
+use erg_common::error::Location;
+use erg_common::grapheme::col_to_byte;
+use erg_common::io::Input;
+use erg_common::traits::{Locational, Stream};
 
-#[derive(Debug, Default)]
+use erg_compiler::erg_parser::token::TokenKind;
+use erg_compiler::hir::{Accessor, Def, Expr, Literal, Signature, HIR};
+use erg_compiler::ty::{HasType, Type, ValueObj};
+use erg_compiler::visitor::{walk_expr, Visitor};
+
+use crate::warning::LintWarning;
+
+/// The lints performed by `erg lint`.
+/// Unlike the warnings of the compiler (e.g. unused variables), they do not affect the compilation,
+/// so they are not checked when building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// e.g. `3.14` (should be `math.pi`)
+    ApproxConstant,
+    /// e.g. `x == True` (should be `x`)
+    BoolComparison,
+    /// e.g. `f!(x) = x + 1` (should be a function)
+    PureProcedure,
+    /// subroutines with more than `MAX_PARAMS` parameters
+    TooManyParams,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Lint {
+    pub const ALL: [Lint; 4] = [
+        Self::ApproxConstant,
+        Self::BoolComparison,
+        Self::PureProcedure,
+        Self::TooManyParams,
+    ];
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ApproxConstant => "approx_constant",
+            Self::BoolComparison => "bool_comparison",
+            Self::PureProcedure => "pure_procedure",
+            Self::TooManyParams => "too_many_params",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+/// The maximum number of parameters of a subroutine (`too_many_params`)
+pub const MAX_PARAMS: usize = 7;
+
+const KNOWN_CONSTANTS: [(&str, f64); 3] = [("math.pi", PI), ("math.tau", TAU), ("math.e", E)];
+
+/// Returns the constant approximated by the float literal (e.g. `3.14` -> `math.pi`).
+/// Literals with less than 3 significant digits (e.g. `3.1`) are not regarded as approximations.
+fn approximated_constant(literal: &str) -> Option<&'static str> {
+    let (int, frac) = literal.split_once('.')?;
+    if int.len() + frac.len() < 3 || !(int.chars().chain(frac.chars())).all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let digits = frac.len();
+    KNOWN_CONSTANTS.iter().find_map(|(name, value)| {
+        let rounded = format!("{value:.digits$}");
+        let truncated = format!("{value:.15}");
+        let truncated = &truncated[..int.len() + 1 + digits.min(15)];
+        (literal == rounded || literal == truncated).then_some(*name)
+    })
+}
+
+/// `Bool`, `{True}`, `{False}` or a type guard
+fn is_bool(t: &Type) -> bool {
+    matches!(t, Type::Guard(_)) || t.derefine() == Type::Bool
+}
+
+/// Expressions that can be used as an operand without parentheses
+fn is_simple(expr: &Expr) -> bool {
+    matches!(expr, Expr::Lit(_) | Expr::Accessor(_) | Expr::Call(_))
+}
+
+fn is_balanced(code: &str) -> bool {
+    let mut depth = 0i32;
+    for c in code.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0
+}
+
+/// Conservative: the expression has no side effects if it does not call or define any procedure,
+/// does not access any mutable variable, and does not reassign any attribute.
+fn has_side_effect(expr: &Expr) -> bool {
+    let mut found = false;
+    walk(expr, &mut |expr| match expr {
+        Expr::Accessor(Accessor::Ident(ident)) if ident.is_procedural() => found = true,
+        Expr::Accessor(Accessor::Attr(attr)) if attr.ident.is_procedural() => found = true,
+        Expr::Call(call) => {
+            let procedural = call
+                .attr_name
+                .as_ref()
+                .is_some_and(|attr| attr.is_procedural());
+            if procedural || call.signature_t().is_some_and(|t| t.is_procedure()) {
+                found = true;
+            }
+        }
+        Expr::Lambda(lambda) if lambda.op.is_procedural() => found = true,
+        Expr::Def(def) if def.sig.is_procedural() => found = true,
+        Expr::ReDef(_) => found = true,
+        _ => {}
+    });
+    found
+}
+
+/// Calls `f` for `expr` and all the sub-expressions of it.
+fn walk(expr: &Expr, f: &mut impl FnMut(&Expr)) {
+    struct ExprWalker<F>(F);
+
+    impl<'a, F: FnMut(&Expr)> Visitor<'a> for ExprWalker<F> {
+        fn visit_expr(&mut self, expr: &'a Expr) {
+            (self.0)(expr);
+            walk_expr(self, expr);
+        }
+    }
+
+    ExprWalker(f).visit_expr(expr);
+}
+
+/// Performs the lints that the compiler does not perform.
+#[derive(Debug)]
 pub struct Linter {
-    _used: Vec<Str>,
+    input: Input,
+    src: String,
+    warns: Vec<LintWarning>,
 }
 
 impl Linter {
-    pub fn new() -> Self {
-        Self { _used: Vec::new() }
+    /// `src`: the source code of `hir` (used to make suggestions)
+    pub fn new(input: Input, src: String) -> Self {
+        Self {
+            input,
+            src,
+            warns: vec![],
+        }
+    }
+
+    pub fn lint(&mut self, hir: &HIR) -> Vec<LintWarning> {
+        for chunk in hir.module.iter() {
+            walk(chunk, &mut |expr| self.lint_expr(expr));
+        }
+        let mut warns = std::mem::take(&mut self.warns);
+        warns.sort_by_key(|warn| (warn.loc().ln_begin(), warn.loc().col_begin()));
+        warns
+    }
+
+    fn caused_by(&self) -> String {
+        self.input.file_stem()
+    }
+
+    /// The source code at `loc` (only if it is in a line)
+    fn snippet(&self, loc: Location) -> Option<&str> {
+        if let Some(snippet) = loc
+            .byte_range()
+            .filter(|_| loc.ln_begin() == loc.ln_end())
+            .and_then(|range| self.src.get(range))
+        {
+            return Some(snippet);
+        }
+        let (ln, col_begin, col_end) = match loc {
+            Location::Range {
+                ln_begin,
+                col_begin,
+                ln_end,
+                col_end,
+                ..
+            } if ln_begin == ln_end => (ln_begin, col_begin as usize, col_end as usize),
+            _ => return None,
+        };
+        let line = self.src.lines().nth((ln as usize).checked_sub(1)?)?;
+        let start = col_to_byte(line, col_begin);
+        let end = col_to_byte(line, col_end);
+        line.get(start..end)
+    }
+
+    fn lint_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Lit(lit) => self.lint_literal(lit),
+            Expr::BinOp(bin) => {
+                let eq = match bin.op.kind {
+                    TokenKind::DblEq => true,
+                    TokenKind::NotEq => false,
+                    _ => return,
+                };
+                let (value, other) = match (bin.lhs.as_ref(), bin.rhs.as_ref()) {
+                    (
+                        other,
+                        Expr::Lit(Literal {
+                            value: ValueObj::Bool(b),
+                            ..
+                        }),
+                    )
+                    | (
+                        Expr::Lit(Literal {
+                            value: ValueObj::Bool(b),
+                            ..
+                        }),
+                        other,
+                    ) => (*b, other),
+                    _ => return,
+                };
+                if !is_bool(other.ref_t()) {
+                    return;
+                }
+                // the location of `(x) == True` does not include the opening parenthesis
+                let in_parens = self
+                    .snippet(expr.loc())
+                    .is_none_or(|code| !is_balanced(code));
+                let replacement = if is_simple(other) && !in_parens {
+                    self.snippet(other.loc()).map(|other| {
+                        if value == eq {
+                            other.to_string()
+                        } else {
+                            format!("not({other})")
+                        }
+                    })
+                } else {
+                    None
+                };
+                self.warns.push(LintWarning::bool_comparison(
+                    self.input.clone(),
+                    line!() as usize,
+                    expr.loc(),
+                    self.caused_by(),
+                    replacement,
+                ));
+            }
+            Expr::Def(def) => self.lint_def(def),
+            _ => {}
+        }
+    }
+
+    fn lint_literal(&mut self, lit: &Literal) {
+        if !matches!(lit.value, ValueObj::Float(_)) {
+            return;
+        }
+        if let Some(constant) = approximated_constant(&lit.token.content) {
+            self.warns.push(LintWarning::approx_constant(
+                self.input.clone(),
+                line!() as usize,
+                lit.loc(),
+                self.caused_by(),
+                &lit.token.content,
+                constant,
+            ));
+        }
     }
 
-    pub fn lint(&mut self, _hir: &HIR) -> CompileWarnings {
-        CompileWarnings::empty()
+    fn lint_def(&mut self, def: &Def) {
+        let Signature::Subr(subr) = &def.sig else {
+            return;
+        };
+        let params = subr.params.non_defaults.len()
+            + subr.params.var_params.iter().len()
+            + subr.params.defaults.len()
+            + subr.params.kw_var_params.iter().len();
+        if params > MAX_PARAMS {
+            self.warns.push(LintWarning::too_many_params(
+                self.input.clone(),
+                line!() as usize,
+                subr.ident.loc(),
+                self.caused_by(),
+                subr.ident.inspect(),
+                params,
+                MAX_PARAMS,
+            ));
+        }
+        // `f!: () => NoneType` (declaration) has no body
+        if subr.ident.is_procedural()
+            && !def.body.block.is_empty()
+            && !def.body.block.iter().any(has_side_effect)
+        {
+            self.warns.push(LintWarning::pure_procedure(
+                self.input.clone(),
+                line!() as usize,
+                subr.ident.loc(),
+                self.caused_by(),
+                subr.ident.inspect(),
+            ));
+        }
     }
 }
@@ -1,3 +0,0 @@
-mod lint;
-
-pub use lint::Linter;
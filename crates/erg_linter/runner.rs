@@ -0,0 +1,268 @@
+//! Runs the lints on files and packages (`erg lint`).
+//! The files are parsed, lowered and checked, but no code is generated.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::ErgConfig;
+use erg_common::error::{apply_suggestions, ErrorDisplay, MultiErrorDisplay, Suggestion};
+use erg_common::exit_code;
+use erg_common::levenshtein::get_similar_name;
+use erg_common::pathutil::NormalizedPathBuf;
+use erg_common::switch_lang;
+use erg_common::traits::{ExitStatus, Stream};
+
+use erg_compiler::artifact::Buildable;
+pub use erg_compiler::check::diagnostic_json;
+use erg_compiler::check::{CheckResult, PackageChecker};
+use erg_compiler::error::{CompileError, CompileErrors, CompileWarnings};
+use erg_compiler::hir::HIR;
+use erg_compiler::HIRBuilder;
+
+use crate::lint::{Lint, Linter};
+use crate::warning::LintWarning;
+
+/// `erg lint --fix` applies the fixes repeatedly (a fix may enable another fix),
+/// but stops after this number of rounds.
+const MAX_FIX_ROUNDS: usize = 10;
+
+/// The diagnostics of a linted file.
+/// Unlike `CheckResult`, the diagnostics of the imported modules are not included.
+#[derive(Debug)]
+pub struct FileLintResult {
+    pub path: PathBuf,
+    pub errors: CompileErrors,
+    /// the warnings of the compiler (e.g. unused variables)
+    pub warns: CompileWarnings,
+    pub lint_warns: Vec<LintWarning>,
+    /// the number of the fixes applied by `--fix`
+    pub fixed: usize,
+}
+
+impl FileLintResult {
+    pub fn num_warns(&self) -> usize {
+        self.warns.len() + self.lint_warns.len()
+    }
+
+    /// The machine-applicable suggestions of the diagnostics
+    pub fn fixes(&self) -> Vec<&Suggestion> {
+        self.errors
+            .iter()
+            .chain(self.warns.iter())
+            .chain(self.lint_warns.iter().map(|warn| &warn.warn))
+            .flat_map(|err| err.core.suggestions.iter())
+            .filter(|sug| sug.is_machine_applicable())
+            .collect()
+    }
+}
+
+/// Reports the unknown lint names given by `--allow`.
+pub(crate) fn validate_allowed_lints(cfg: &ErgConfig) -> bool {
+    if let Some(unknown) = cfg
+        .allowed_lints
+        .iter()
+        .find(|name| Lint::from_name(name).is_none())
+    {
+        let lints = Lint::ALL.map(|lint| lint.name());
+        if let Some(similar) = get_similar_name(lints.iter().copied(), unknown) {
+            eprintln!("unknown lint: `{unknown}` (did you mean `{similar}`?)");
+        } else {
+            eprintln!(
+                "unknown lint: `{unknown}` (expected one of {})",
+                lints.join(", ")
+            );
+        }
+        return false;
+    }
+    true
+}
+
+#[derive(Debug)]
+pub struct LintRunner {
+    checker: PackageChecker,
+}
+
+impl LintRunner {
+    pub fn new(cfg: ErgConfig) -> Self {
+        Self {
+            checker: PackageChecker::new(cfg),
+        }
+    }
+
+    pub fn cfg(&self) -> &ErgConfig {
+        self.checker.cfg()
+    }
+
+    /// The files given on the command line (or the files of the package)
+    pub fn collect_targets(&self) -> Vec<PathBuf> {
+        self.checker.collect_targets()
+    }
+
+    fn lint_hir(&self, cfg: &ErgConfig, src: String, hir: Option<&HIR>) -> Vec<LintWarning> {
+        let Some(hir) = hir else {
+            return vec![];
+        };
+        Linter::new(cfg.input.clone(), src)
+            .lint(hir)
+            .into_iter()
+            .filter(|warn| !cfg.allowed_lints.contains(&warn.lint.name()))
+            .collect()
+    }
+
+    /// Checks and lints the file.
+    pub fn lint_file(&mut self, path: &Path) -> FileLintResult {
+        // recheck the file even if it has been checked as a dependency of another file
+        self.checker.invalidate(&[path.to_path_buf()]);
+        let CheckResult { errors, warns, .. } = self.checker.check_file(path);
+        let normalized = NormalizedPathBuf::from(path);
+        let is_in_file =
+            |err: &CompileError| NormalizedPathBuf::from(err.input.path()) == normalized;
+        let src = fs::read_to_string(path).unwrap_or_default();
+        let cfg = self.cfg().inherit(path.to_path_buf());
+        let lint_warns = {
+            let entry = self.checker.shared().mod_cache.get(&normalized);
+            self.lint_hir(
+                &cfg,
+                src,
+                entry.as_ref().and_then(|entry| entry.hir.as_ref()),
+            )
+        };
+        FileLintResult {
+            path: path.to_path_buf(),
+            errors: CompileErrors::from(errors.into_iter().filter(is_in_file).collect::<Vec<_>>()),
+            warns: CompileErrors::from(warns.into_iter().filter(is_in_file).collect::<Vec<_>>()),
+            lint_warns,
+            fixed: 0,
+        }
+    }
+
+    /// Lints the file and applies the machine-applicable fixes until no fixes can be applied.
+    /// Files with errors are not fixed.
+    pub fn fix_file(&mut self, path: &Path) -> FileLintResult {
+        let mut fixed = 0;
+        for _ in 0..MAX_FIX_ROUNDS {
+            let result = self.lint_file(path);
+            let fixes = result.fixes();
+            if !result.errors.is_empty() || fixes.is_empty() {
+                return FileLintResult { fixed, ..result };
+            }
+            let Ok(src) = fs::read_to_string(path) else {
+                return FileLintResult { fixed, ..result };
+            };
+            let (new_src, applied) = apply_suggestions(&src, fixes);
+            if applied == 0 || new_src == src || fs::write(path, new_src).is_err() {
+                return FileLintResult { fixed, ..result };
+            }
+            fixed += applied;
+        }
+        FileLintResult {
+            fixed,
+            ..self.lint_file(path)
+        }
+    }
+
+    /// Lints the source code given by `-c` or a pipe (fixes are not applied).
+    pub fn lint_src(&mut self, src: String) -> FileLintResult {
+        let cfg = self.cfg().copy();
+        let mut builder = HIRBuilder::inherit(cfg.copy(), self.checker.shared().clone());
+        let (errors, warns, hir) = match builder.build(src.clone(), "exec") {
+            Ok(artifact) => (
+                CompileErrors::empty(),
+                artifact.warns,
+                Some(artifact.object),
+            ),
+            Err(artifact) => (artifact.errors, artifact.warns, artifact.object),
+        };
+        FileLintResult {
+            path: PathBuf::from(cfg.input.filename()),
+            errors,
+            warns,
+            lint_warns: self.lint_hir(&cfg, src, hir.as_ref()),
+            fixed: 0,
+        }
+    }
+
+    fn report(&self, result: &FileLintResult) {
+        if self.cfg().json_output {
+            for err in result.errors.iter().chain(result.warns.iter()) {
+                println!(
+                    "{}",
+                    diagnostic_json(&result.path, err, &format!("{:?}", err.core.kind))
+                );
+            }
+            for warn in result.lint_warns.iter() {
+                println!(
+                    "{}",
+                    diagnostic_json(&result.path, &warn.warn, warn.lint.name())
+                );
+            }
+        } else {
+            result.errors.write_all_stderr();
+            result.warns.write_all_stderr();
+            for warn in result.lint_warns.iter() {
+                warn.warn.write_to_stderr();
+            }
+        }
+    }
+
+    /// Lints the targets (or the package) and prints the diagnostics.
+    /// The exit code is not 0 if any error or warning remains (for CI and pre-commit hooks).
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        if !validate_allowed_lints(&cfg) {
+            return ExitStatus::new(exit_code::USAGE_ERROR, 0, 0);
+        }
+        let mut runner = Self::new(cfg);
+        let results = if !runner.cfg().input.is_repl() {
+            let src = runner.cfg().input.clone().read();
+            vec![runner.lint_src(src)]
+        } else {
+            let targets = runner.collect_targets();
+            if targets.is_empty() {
+                eprintln!(
+                    "{}",
+                    switch_lang!(
+                        "japanese" => "リントするファイルがありません(パッケージ外で、ファイルも指定されていません)",
+                        "simplified_chinese" => "没有要检查的文件(不在包中，也没有指定文件)",
+                        "traditional_chinese" => "沒有要檢查的檔案(不在套件中，也沒有指定檔案)",
+                        "english" => "no files to lint (not in a package, and no files are given)",
+                    )
+                );
+                return ExitStatus::ERR1;
+            }
+            targets
+                .iter()
+                .map(|path| {
+                    if runner.cfg().fix {
+                        runner.fix_file(path)
+                    } else {
+                        runner.lint_file(path)
+                    }
+                })
+                .collect()
+        };
+        let (mut num_errors, mut num_warns, mut fixed) = (0, 0, 0);
+        for result in results.iter() {
+            runner.report(result);
+            num_errors += result.errors.len();
+            num_warns += result.num_warns();
+            fixed += result.fixed;
+        }
+        if runner.cfg().verbose > 0 && !runner.cfg().quiet && !runner.cfg().json_output {
+            let fixed = if runner.cfg().fix {
+                format!(", {fixed} fix(es) applied")
+            } else {
+                "".into()
+            };
+            println!(
+                "linted {} file(s): {num_errors} error(s), {num_warns} warning(s){fixed}",
+                results.len()
+            );
+        }
+        // only warnings -> `exit_code::WARNINGS` (see `exit_code::of_status`)
+        let code = exit_code::of_errors(
+            results
+                .iter()
+                .flat_map(|result| result.errors.iter().map(|err| err.core())),
+        );
+        ExitStatus::new(code, num_warns, num_errors)
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use erg_common::config::ErgConfig;
+use erg_common::io::Output;
+use erg_common::spawn::exec_new_thread;
+use erg_common::traits::Stream;
+
+use erg_linter::{FixRunner, Lint, LintRunner};
+
+fn lint_cfg() -> ErgConfig {
+    ErgConfig {
+        output: Output::Null,
+        ..ErgConfig::default()
+    }
+}
+
+#[test]
+fn test_lint() -> Result<(), ()> {
+    exec_new_thread(_test_lint, "test_lint")
+}
+
+fn _test_lint() -> Result<(), ()> {
+    let mut runner = LintRunner::new(lint_cfg());
+    let result = runner.lint_file(Path::new("tests/lint.er"));
+    assert!(result.errors.is_empty());
+    let lints = result
+        .lint_warns
+        .iter()
+        .map(|warn| (warn.lint, warn.warn.core.loc.ln_begin().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        vec![
+            (Lint::BoolComparison, 3),
+            (Lint::BoolComparison, 4),
+            (Lint::BoolComparison, 5),
+            (Lint::ApproxConstant, 6),
+            (Lint::TooManyParams, 7),
+            (Lint::PureProcedure, 9),
+        ]
+    );
+    // `(flag) == False` cannot be fixed safely
+    assert_eq!(result.fixes().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_lint_fix() -> Result<(), ()> {
+    exec_new_thread(_test_lint_fix, "test_lint_fix")
+}
+
+fn _test_lint_fix() -> Result<(), ()> {
+    let path = std::env::temp_dir().join(format!("erg_lint_fix_{}.er", std::process::id()));
+    fs::copy("tests/lint.er", &path).unwrap();
+    let mut cfg = lint_cfg();
+    cfg.fix = true;
+    let mut runner = LintRunner::new(cfg);
+    let result = runner.fix_file(&path);
+    let fixed = fs::read_to_string(&path).unwrap();
+    let _ = fs::remove_file(&path);
+    assert_eq!(result.fixed, 2);
+    assert!(fixed.contains("checked = if flag, do 1, do 2"));
+    assert!(fixed.contains("negated = if not(flag), do 1, do 2"));
+    // `MaybeIncorrect` suggestions are not applied
+    assert!(fixed.contains("3.14"));
+    assert_eq!(result.lint_warns.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_fix() -> Result<(), ()> {
+    exec_new_thread(_test_fix, "test_fix")
+}
+
+fn _test_fix() -> Result<(), ()> {
+    let path = std::env::temp_dir().join(format!("erg_fix_{}.er", std::process::id()));
+    fs::copy("tests/lint.er", &path).unwrap();
+    let mut fixer = FixRunner::new(lint_cfg());
+    let result = fixer.fix_file(&path);
+    let again = fixer.fix_file(&path);
+    let _ = fs::remove_file(&path);
+    assert!(result.is_changed());
+    assert_eq!(result.fixed(), 2);
+    let diff = erg_common::style::remove_style(&result.diff());
+    assert!(diff.contains("-checked = if flag == True, do 1, do 2"));
+    assert!(diff.contains("+checked = if flag, do 1, do 2"));
+    // already at a fixpoint
+    assert!(!again.is_changed());
+    assert_eq!(again.fixed(), 0);
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use erg_common::error::{Applicability, ErrorCore, ErrorKind::*, Location, SubMessage, Suggestion};
+use erg_common::io::Input;
+use erg_common::style::{Attribute, Color, StyledStr, StyledString, THEME};
+use erg_common::switch_lang;
+use erg_common::traits::Locational;
+
+use erg_compiler::error::CompileWarning;
+
+use crate::lint::Lint;
+
+const WARN: Color = THEME.colors.warning;
+const HINT: Color = THEME.colors.hint;
+const ATTR: Attribute = Attribute::Bold;
+
+/// A warning of the linter.
+#[derive(Debug, Clone)]
+pub struct LintWarning {
+    pub lint: Lint,
+    pub warn: CompileWarning,
+}
+
+impl Locational for LintWarning {
+    fn loc(&self) -> Location {
+        self.warn.core.loc
+    }
+}
+
+impl LintWarning {
+    fn new(lint: Lint, core: ErrorCore, input: Input, caused_by: String) -> Self {
+        Self {
+            lint,
+            warn: CompileWarning::new(core, input, caused_by),
+        }
+    }
+
+    /// e.g. `(lint: approx_constant)`
+    fn hint_with_lint(hint: String, lint: Lint) -> String {
+        let lint = StyledStr::new(lint.name(), Some(HINT), Some(ATTR));
+        format!("{hint} (lint: {lint})")
+    }
+
+    pub fn approx_constant(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        literal: &str,
+        constant: &str,
+    ) -> Self {
+        let found = StyledString::new(literal, Some(WARN), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => format!("代わりに`{constant}`を使用してください(`math = import \"math\"`)"),
+            "simplified_chinese" => format!("请改用`{constant}`(`math = import \"math\"`)"),
+            "traditional_chinese" => format!("請改用`{constant}`(`math = import \"math\"`)"),
+            "english" => format!("use `{constant}` instead (`math = import \"math\"`)"),
+        );
+        let hint = Self::hint_with_lint(hint, Lint::ApproxConstant);
+        let core = ErrorCore::new(
+            vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+            switch_lang!(
+                "japanese" => format!("{found}は`{constant}`の近似値です"),
+                "simplified_chinese" => format!("{found}是`{constant}`的近似值"),
+                "traditional_chinese" => format!("{found}是`{constant}`的近似值"),
+                "english" => format!("{found} is an approximation of `{constant}`"),
+            ),
+            errno,
+            Warning,
+            loc,
+        )
+        .with_suggestion(Suggestion::new(
+            loc,
+            constant,
+            Applicability::MaybeIncorrect,
+        ));
+        Self::new(Lint::ApproxConstant, core, input, caused_by)
+    }
+
+    /// `replacement`: `None` if the expression cannot be simplified safely (e.g. it has parentheses)
+    pub fn bool_comparison(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        replacement: Option<String>,
+    ) -> Self {
+        let hint = if let Some(replacement) = replacement.as_ref() {
+            switch_lang!(
+                "japanese" => format!("`{replacement}`と書けます"),
+                "simplified_chinese" => format!("可以写成`{replacement}`"),
+                "traditional_chinese" => format!("可以寫成`{replacement}`"),
+                "english" => format!("this can be written as `{replacement}`"),
+            )
+        } else {
+            switch_lang!(
+                "japanese" => "比較を取り除くか、`not`を使ってください",
+                "simplified_chinese" => "请删除比较，或者使用`not`",
+                "traditional_chinese" => "請刪除比較，或者使用`not`",
+                "english" => "remove the comparison, or use `not`",
+            )
+            .to_string()
+        };
+        let hint = Self::hint_with_lint(hint, Lint::BoolComparison);
+        let mut core = ErrorCore::new(
+            vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+            switch_lang!(
+                "japanese" => "Bool値と`True`/`False`を比較しています",
+                "simplified_chinese" => "将Bool值与`True`/`False`进行比较",
+                "traditional_chinese" => "將Bool值與`True`/`False`進行比較",
+                "english" => "comparing a Bool value with `True`/`False`",
+            ),
+            errno,
+            Warning,
+            loc,
+        );
+        if let Some(replacement) = replacement {
+            core = core.with_suggestion(Suggestion::new(
+                loc,
+                replacement,
+                Applicability::MachineApplicable,
+            ));
+        }
+        Self::new(Lint::BoolComparison, core, input, caused_by)
+    }
+
+    pub fn pure_procedure(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = StyledString::new(name, Some(WARN), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => "`!`を取り除いて関数にできます",
+            "simplified_chinese" => "可以删除`!`并将其定义为函数",
+            "traditional_chinese" => "可以刪除`!`並將其定義為函式",
+            "english" => "this can be a function (remove the `!`)",
+        )
+        .to_string();
+        let hint = Self::hint_with_lint(hint, Lint::PureProcedure);
+        let core = ErrorCore::new(
+            vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+            switch_lang!(
+                "japanese" => format!("プロシージャ{name}は副作用を持ちません"),
+                "simplified_chinese" => format!("过程{name}没有副作用"),
+                "traditional_chinese" => format!("程序{name}沒有副作用"),
+                "english" => format!("the procedure {name} has no side effects"),
+            ),
+            errno,
+            Warning,
+            loc,
+        );
+        Self::new(Lint::PureProcedure, core, input, caused_by)
+    }
+
+    pub fn too_many_params(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+        params: usize,
+        max: usize,
+    ) -> Self {
+        let name = StyledString::new(name, Some(WARN), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => "引数をレコードやクラスにまとめることを検討してください",
+            "simplified_chinese" => "请考虑将参数整合为记录或类",
+            "traditional_chinese" => "請考慮將參數整合為記錄或類別",
+            "english" => "consider grouping the parameters into a record or a class",
+        )
+        .to_string();
+        let hint = Self::hint_with_lint(hint, Lint::TooManyParams);
+        let core = ErrorCore::new(
+            vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+            switch_lang!(
+                "japanese" => format!("{name}の引数が多すぎます({params}個、最大{max}個)"),
+                "simplified_chinese" => format!("{name}的参数太多({params}个，最多{max}个)"),
+                "traditional_chinese" => format!("{name}的參數太多({params}個，最多{max}個)"),
+                "english" => format!("{name} has too many parameters ({params}, max: {max})"),
+            ),
+            errno,
+            Warning,
+            loc,
+        );
+        Self::new(Lint::TooManyParams, core, input, caused_by)
+    }
+}
@@ -5,6 +5,7 @@ use std::fmt::Write as _;
 
 use erg_common::consts::ERG_MODE;
 use erg_common::error::Location;
+use erg_common::lang::LanguageCode;
 use erg_common::set::Set as HashSet;
 // use erg_common::dict::Dict as HashMap;
 use erg_common::traits::{Locational, NestedDisplay, Stream};
@@ -103,6 +104,183 @@ impl Literal {
     pub fn is_doc_comment(&self) -> bool {
         self.token.is(TokenKind::DocComment)
     }
+
+    /// The last line of the literal (the location of a token is only its first line)
+    pub fn ln_last(&self) -> u32 {
+        self.ln_begin().unwrap_or(0) + self.token.content.matches('\n').count() as u32
+    }
+}
+
+/// String interpolation
+/// e.g. `"1 + 1 = \{1 + 1}!"`
+///
+/// `lits` are the tokens surrounding the embedded expressions (`"1 + 1 = \{`, `}!"`),
+/// so `lits.len() == exprs.len() + 1`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StrInterp {
+    pub lits: Vec<Token>,
+    pub exprs: Vec<Expr>,
+}
+
+impl NestedDisplay for StrInterp {
+    fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
+        for (lit, expr) in self.lits.iter().zip(self.exprs.iter()) {
+            write!(f, "{}{expr}", lit.content)?;
+        }
+        write!(f, "{}", self.lits.last().unwrap().content)
+    }
+}
+
+impl_display_from_nested!(StrInterp);
+
+impl Locational for StrInterp {
+    fn loc(&self) -> Location {
+        Location::concat(self.lits.first().unwrap(), self.lits.last().unwrap())
+    }
+}
+
+impl StrInterp {
+    pub fn new(lits: Vec<Token>, exprs: Vec<Expr>) -> Self {
+        debug_assert_eq!(lits.len(), exprs.len() + 1);
+        Self { lits, exprs }
+    }
+
+    /// Converts the surrounding tokens into string literals.
+    /// e.g. `"1 + 1 = \{` => `"1 + 1 = "`, `}!"` => `"!"`
+    pub fn str_lits(&self) -> Vec<Literal> {
+        self.lits
+            .iter()
+            .map(|lit| {
+                let content = match lit.kind {
+                    TokenKind::StrInterpLeft => {
+                        format!("{}\"", lit.content.trim_end_matches("\\{"))
+                    }
+                    TokenKind::StrInterpMid => format!(
+                        "\"{}\"",
+                        lit.content.trim_start_matches('}').trim_end_matches("\\{")
+                    ),
+                    _ => format!("\"{}", lit.content.trim_start_matches('}')),
+                };
+                let mut token = lit.clone();
+                token.content = Str::from(content);
+                token.kind = TokenKind::StrLit;
+                Literal::from(token)
+            })
+            .collect()
+    }
+}
+
+/// Doc comments right above a definition
+/// e.g.
+/// ```erg
+/// '''
+/// Returns the sum of `x` and `y`.
+/// '''
+/// add x, y = x + y
+///
+/// #| Returns the difference of `x` and `y`.
+/// sub x, y = x - y
+/// ```
+///
+/// Doc comments can be written for each language (e.g. `'''ja ...'''`) and code examples (`'''erg ...'''`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DocComment {
+    pub lits: Vec<Literal>,
+}
+
+impl NestedDisplay for DocComment {
+    fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
+        fmt_lines(self.lits.iter(), f, 0)
+    }
+}
+
+impl_display_from_nested!(DocComment);
+
+impl Locational for DocComment {
+    fn loc(&self) -> Location {
+        Location::concat(self.lits.first().unwrap(), self.lits.last().unwrap())
+    }
+}
+
+impl DocComment {
+    pub fn new(lits: Vec<Literal>) -> Self {
+        debug_assert!(!lits.is_empty());
+        Self { lits }
+    }
+
+    /// `'''doc'''` => `doc`, `#| doc` => `doc`
+    pub fn content_of(token: &Token) -> String {
+        if token.content.starts_with("#|") {
+            token
+                .content
+                .lines()
+                .map(|line| {
+                    let line = line.trim_start().trim_start_matches("#|");
+                    line.strip_prefix(' ').unwrap_or(line)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            token
+                .content
+                .trim_start_matches("'''")
+                .trim_end_matches("'''")
+                .to_string()
+        }
+    }
+
+    /// Selects the description for the current language (English if not found) and appends the code examples.
+    pub fn text(&self) -> String {
+        let mut description = None;
+        let mut fallback = None;
+        let mut examples = vec![];
+        for lit in self.lits.iter() {
+            let doc = Self::content_of(&lit.token);
+            let (first, rest) = doc.split_once('\n').unwrap_or((&doc, ""));
+            let (lang, text) = match first.trim().parse::<LanguageCode>() {
+                Ok(lang) => (lang, rest),
+                Err(_) => (LanguageCode::English, &doc[..]),
+            };
+            let text = dedent(text);
+            if lang.is_pl() {
+                examples.push(format!("```{}\n{text}\n```", <&str>::from(lang)));
+            } else if lang.matches_feature() {
+                description = Some(text);
+            } else if lang.is_en() {
+                fallback = Some(text);
+            }
+        }
+        description
+            .or(fallback)
+            .into_iter()
+            .chain(examples)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// Removes the blank lines at both ends and the common indentation.
+fn dedent(text: &str) -> String {
+    let lines = text
+        .lines()
+        .skip_while(|line| line.trim().is_empty())
+        .collect::<Vec<_>>();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    let lines = &lines[..end];
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()).trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -162,6 +340,7 @@ pub struct Args {
     pos_args: Vec<PosArg>,
     pub(crate) var_args: Option<Box<PosArg>>,
     kw_args: Vec<KwArg>,
+    pub(crate) kw_var_args: Option<Box<PosArg>>,
     // these are for ELS
     pub paren: Option<(Token, Token)>,
 }
@@ -169,8 +348,17 @@ pub struct Args {
 impl NestedDisplay for Args {
     fn fmt_nest(&self, f: &mut std::fmt::Formatter<'_>, level: usize) -> std::fmt::Result {
         fmt_lines(self.pos_args.iter(), f, level)?;
+        if let Some(var_args) = &self.var_args {
+            writeln!(f)?;
+            write!(f, "{}*{}", "    ".repeat(level), var_args.expr)?;
+        }
         writeln!(f)?;
-        fmt_lines(self.kw_args.iter(), f, level)
+        fmt_lines(self.kw_args.iter(), f, level)?;
+        if let Some(kw_var_args) = &self.kw_var_args {
+            writeln!(f)?;
+            write!(f, "{}**{}", "    ".repeat(level), kw_var_args.expr)?;
+        }
+        Ok(())
     }
 }
 
@@ -184,10 +372,22 @@ impl Locational for Args {
                 return loc;
             }
         }
-        match (self.pos_args.first(), self.kw_args.last()) {
-            (Some(l), Some(r)) => Location::concat(l, r),
-            (Some(l), None) => Location::concat(l, self.pos_args.last().unwrap()),
-            (None, Some(r)) => Location::concat(self.kw_args.first().unwrap(), r),
+        let first = self
+            .pos_args
+            .first()
+            .map(|a| a.loc())
+            .or_else(|| self.var_args.as_ref().map(|a| a.loc()))
+            .or_else(|| self.kw_args.first().map(|a| a.loc()))
+            .or_else(|| self.kw_var_args.as_ref().map(|a| a.loc()));
+        let last = self
+            .kw_var_args
+            .as_ref()
+            .map(|a| a.loc())
+            .or_else(|| self.kw_args.last().map(|a| a.loc()))
+            .or_else(|| self.var_args.as_ref().map(|a| a.loc()))
+            .or_else(|| self.pos_args.last().map(|a| a.loc()));
+        match (first, last) {
+            (Some(l), Some(r)) => Location::concat(&l, &r),
             _ => Location::Unknown,
         }
     }
@@ -200,18 +400,20 @@ impl Args {
         pos_args: Vec<PosArg>,
         var_args: Option<PosArg>,
         kw_args: Vec<KwArg>,
+        kw_var_args: Option<PosArg>,
         paren: Option<(Token, Token)>,
     ) -> Self {
         Self {
             pos_args,
             var_args: var_args.map(Box::new),
             kw_args,
+            kw_var_args: kw_var_args.map(Box::new),
             paren,
         }
     }
 
     pub fn pos_only(pos_arg: Vec<PosArg>, paren: Option<(Token, Token)>) -> Self {
-        Self::new(pos_arg, None, vec![], paren)
+        Self::new(pos_arg, None, vec![], None, paren)
     }
 
     pub fn single(pos_args: PosArg) -> Self {
@@ -219,7 +421,7 @@ impl Args {
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     // for replacing to hir::Args
@@ -230,18 +432,23 @@ impl Args {
         Vec<PosArg>,
         Option<PosArg>,
         Vec<KwArg>,
+        Option<PosArg>,
         Option<(Token, Token)>,
     ) {
         (
             self.pos_args,
             self.var_args.map(|x| *x),
             self.kw_args,
+            self.kw_var_args.map(|x| *x),
             self.paren,
         )
     }
 
     pub fn is_empty(&self) -> bool {
-        self.pos_args.is_empty() && self.kw_args.is_empty()
+        self.pos_args.is_empty()
+            && self.var_args.is_none()
+            && self.kw_args.is_empty()
+            && self.kw_var_args.is_none()
     }
 
     pub fn len(&self) -> usize {
@@ -260,6 +467,14 @@ impl Args {
         &self.kw_args[..]
     }
 
+    pub fn pos_args_mut(&mut self) -> &mut [PosArg] {
+        &mut self.pos_args[..]
+    }
+
+    pub fn kw_args_mut(&mut self) -> &mut [KwArg] {
+        &mut self.kw_args[..]
+    }
+
     pub fn has_pos_arg(&self, pa: &PosArg) -> bool {
         self.pos_args.contains(pa)
     }
@@ -296,6 +511,18 @@ impl Args {
         self.var_args = Some(Box::new(arg));
     }
 
+    pub fn var_args(&self) -> Option<&PosArg> {
+        self.var_args.as_deref()
+    }
+
+    pub fn set_kw_var_args(&mut self, arg: PosArg) {
+        self.kw_var_args = Some(Box::new(arg));
+    }
+
+    pub fn kw_var_args(&self) -> Option<&PosArg> {
+        self.kw_var_args.as_deref()
+    }
+
     pub fn push_kw(&mut self, arg: KwArg) {
         self.kw_args.push(arg);
     }
@@ -777,28 +1004,49 @@ impl NormalDict {
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DictComprehension {
-    l_brace: Token,
-    r_brace: Token,
-    pub attrs: Args,
-    guards: Vec<Expr>,
+    pub l_brace: Token,
+    pub r_brace: Token,
+    pub key: Box<Expr>,
+    pub value: Box<Expr>,
+    pub generators: Vec<(Identifier, Expr)>,
+    pub guards: Vec<Expr>,
 }
 
-// TODO:
 impl NestedDisplay for DictComprehension {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        write!(f, "{{{} | {}}}", self.attrs, fmt_vec(&self.guards))
+        let mut generators = String::new();
+        for (name, gen) in self.generators.iter() {
+            write!(generators, "{name} <- {gen}, ")?;
+        }
+        write!(
+            f,
+            "{{{}: {} | {}{}}}",
+            self.key,
+            self.value,
+            generators,
+            fmt_vec(&self.guards)
+        )
     }
 }
 
 impl_display_from_nested!(DictComprehension);
-impl_locational!(DictComprehension, l_brace, attrs, r_brace);
+impl_locational!(DictComprehension, l_brace, key, r_brace);
 
 impl DictComprehension {
-    pub const fn new(l_brace: Token, r_brace: Token, attrs: Args, guards: Vec<Expr>) -> Self {
+    pub fn new(
+        l_brace: Token,
+        r_brace: Token,
+        key: Expr,
+        value: Expr,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
+    ) -> Self {
         Self {
             l_brace,
             r_brace,
-            attrs,
+            key: Box::new(key),
+            value: Box::new(value),
+            generators,
             guards,
         }
     }
@@ -1075,23 +1323,31 @@ impl SetWithLength {
     }
 }
 
+/// `{x | x <- xs; x > 0}` or `{X: Int | X > 0}` (refinement, `layout` is `None`)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SetComprehension {
     pub l_brace: Token,
     pub r_brace: Token,
-    pub var: Token,
-    pub op: Token, // <- or :
-    pub iter: Box<Expr>,
-    pub pred: Box<Expr>,
+    pub layout: Option<Box<Expr>>,
+    pub generators: Vec<(Identifier, Expr)>,
+    pub guards: Vec<Expr>,
 }
 
 impl NestedDisplay for SetComprehension {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        write!(
-            f,
-            "{{{} {} {} | {}}}",
-            self.var, self.op, self.iter, self.pred
-        )
+        if let Some(layout) = &self.layout {
+            let mut generators = String::new();
+            for (name, gen) in self.generators.iter() {
+                write!(generators, "{name} <- {gen}, ")?;
+            }
+            write!(f, "{{{layout} | {generators}{}}}", fmt_vec(&self.guards))
+        } else {
+            let mut generators = String::new();
+            for (name, gen) in self.generators.iter() {
+                write!(generators, "{name}: {gen}, ")?;
+            }
+            write!(f, "{{{generators}| {}}}", fmt_vec(&self.guards))
+        }
     }
 }
 
@@ -1102,20 +1358,23 @@ impl SetComprehension {
     pub fn new(
         l_brace: Token,
         r_brace: Token,
-        var: Token,
-        op: Token,
-        iter: Expr,
-        pred: Expr,
+        layout: Option<Expr>,
+        generators: Vec<(Identifier, Expr)>,
+        guards: Vec<Expr>,
     ) -> Self {
         Self {
             l_brace,
             r_brace,
-            var,
-            op,
-            iter: Box::new(iter),
-            pred: Box::new(pred),
+            layout: layout.map(Box::new),
+            generators,
+            guards,
         }
     }
+
+    /// `{X: Int | X > 0}`
+    pub fn is_refinement(&self) -> bool {
+        self.layout.is_none() && self.generators.len() == 1 && self.guards.len() == 1
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1183,7 +1442,12 @@ impl_display_from_nested!(UnaryOp);
 
 impl Locational for UnaryOp {
     fn loc(&self) -> Location {
-        Location::concat(&self.op, self.args[0].as_ref())
+        // `?` is a postfix operator
+        if self.op.is(TokenKind::Try) {
+            Location::concat(self.args[0].as_ref(), &self.op)
+        } else {
+            Location::concat(&self.op, self.args[0].as_ref())
+        }
     }
 }
 
@@ -1687,10 +1951,12 @@ impl ConstSetComprehension {
         SetComprehension::new(
             self.l_brace,
             self.r_brace,
-            self.var,
-            self.op,
-            self.iter.downgrade(),
-            self.pred.downgrade(),
+            None,
+            vec![(
+                Identifier::private_from_token(self.var),
+                self.iter.downgrade(),
+            )],
+            vec![self.pred.downgrade()],
         )
     }
 }
@@ -2296,6 +2562,7 @@ impl ConstArgs {
                 // TODO t_spec
                 .map(|arg| KwArg::new(arg.keyword, None, arg.expr.downgrade()))
                 .collect(),
+            None,
             paren,
         )
     }
@@ -2765,6 +3032,19 @@ impl TypeSpec {
         Self::Or(Box::new(lhs), Box::new(rhs))
     }
 
+    /// `?`, `(Int, ?)`, `[?; 2]`, ...
+    pub fn has_infer(&self) -> bool {
+        match self {
+            Self::Infer(_) => true,
+            Self::Array(arr) => arr.ty.has_infer(),
+            Self::Tuple(tup) => tup.tys.iter().any(|t| t.has_infer()),
+            Self::Record(attrs) => attrs.iter().any(|(_, t)| t.has_infer()),
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) => lhs.has_infer() || rhs.has_infer(),
+            Self::Not(t) => t.has_infer(),
+            _ => false,
+        }
+    }
+
     pub const fn interval(op: Token, lhs: ConstExpr, rhs: ConstExpr) -> Self {
         Self::Interval { op, lhs, rhs }
     }
@@ -2926,6 +3206,24 @@ impl Decorator {
     pub fn into_expr(self) -> Expr {
         self.0
     }
+
+    /// The name of the decorator (`deco` of `@deco` or `@deco args`)
+    pub fn name(&self) -> Option<&Identifier> {
+        let obj = match &self.0 {
+            Expr::Call(call) if call.attr_name.is_none() => call.obj.as_ref(),
+            other => other,
+        };
+        match obj {
+            Expr::Accessor(Accessor::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
+    /// Compile-time decorators (e.g. `@Override`, `@Deprecated "reason"`) are named in PascalCase.
+    /// They are recorded as metadata and are not applied at runtime.
+    pub fn is_comptime(&self) -> bool {
+        self.name().is_some_and(|ident| ident.is_const())
+    }
 }
 
 /// symbol as a left value
@@ -3401,6 +3699,14 @@ impl VarRecordPattern {
             r_brace,
         }
     }
+
+    pub const fn attrs(&self) -> &VarRecordAttrs {
+        &self.attrs
+    }
+
+    pub fn attrs_mut(&mut self) -> &mut VarRecordAttrs {
+        &mut self.attrs
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -3634,6 +3940,33 @@ impl ParamTuplePattern {
     }
 }
 
+/// `name @ pattern`: binds the whole value to `name` and also matches it against `pattern`
+///
+/// This pattern is eliminated by the desugarer
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParamBindPattern {
+    pub name: VarName,
+    pub pat: Box<ParamPattern>,
+}
+
+impl NestedDisplay for ParamBindPattern {
+    fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
+        write!(f, "{} @ {}", self.name, self.pat)
+    }
+}
+
+impl_display_from_nested!(ParamBindPattern);
+impl_locational!(ParamBindPattern, name, pat);
+
+impl ParamBindPattern {
+    pub fn new(name: VarName, pat: ParamPattern) -> Self {
+        Self {
+            name,
+            pat: Box::new(pat),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ParamRecordAttr {
     pub lhs: Identifier,
@@ -3703,6 +4036,14 @@ impl ParamRecordPattern {
             r_brace,
         }
     }
+
+    pub const fn elems(&self) -> &ParamRecordAttrs {
+        &self.elems
+    }
+
+    pub fn elems_mut(&mut self) -> &mut ParamRecordAttrs {
+        &mut self.elems
+    }
 }
 
 /// 関数定義や無名関数で使えるパターン
@@ -3718,6 +4059,7 @@ pub enum ParamPattern {
     // DataPack(ParamDataPackPattern),
     Ref(VarName),
     RefMut(VarName),
+    Bind(ParamBindPattern),
 }
 
 impl NestedDisplay for ParamPattern {
@@ -3731,12 +4073,13 @@ impl NestedDisplay for ParamPattern {
             Self::Record(record) => write!(f, "{record}"),
             Self::Ref(var_name) => write!(f, "ref {var_name}"),
             Self::RefMut(var_name) => write!(f, "ref! {var_name}"),
+            Self::Bind(bind) => write!(f, "{bind}"),
         }
     }
 }
 
 impl_display_from_nested!(ParamPattern);
-impl_locational_for_enum!(ParamPattern; Discard, VarName, Lit, Array, Tuple, Record, Ref, RefMut);
+impl_locational_for_enum!(ParamPattern; Discard, VarName, Lit, Array, Tuple, Record, Ref, RefMut, Bind);
 
 impl ParamPattern {
     pub const fn inspect(&self) -> Option<&Str> {
@@ -3849,6 +4192,7 @@ pub struct Params {
     pub non_defaults: Vec<NonDefaultParamSignature>,
     pub var_params: Option<Box<NonDefaultParamSignature>>,
     pub defaults: Vec<DefaultParamSignature>,
+    pub kw_var_params: Option<Box<NonDefaultParamSignature>>,
     pub parens: Option<(Token, Token)>,
 }
 
@@ -3861,6 +4205,9 @@ impl fmt::Display for Params {
         if !self.defaults.is_empty() {
             write!(f, ", {}", fmt_vec(&self.defaults))?;
         }
+        if let Some(kw_var_params) = &self.kw_var_params {
+            write!(f, ", **{kw_var_params}")?;
+        }
         write!(f, ")")
     }
 }
@@ -3873,17 +4220,22 @@ impl Locational for Params {
                 return loc;
             }
         }
-        match (
-            self.non_defaults.first(),
-            self.var_params.as_ref(),
-            self.defaults.last(),
-        ) {
-            (Some(l), _, Some(r)) => Location::concat(l, r),
-            (Some(l), Some(r), None) => Location::concat(l, r.as_ref()),
-            (None, Some(l), Some(r)) => Location::concat(l.as_ref(), r),
-            (Some(l), None, None) => Location::concat(l, self.non_defaults.last().unwrap()),
-            (None, Some(var), None) => var.loc(),
-            (None, None, Some(r)) => Location::concat(self.defaults.first().unwrap(), r),
+        let first = self
+            .non_defaults
+            .first()
+            .map(|p| p.loc())
+            .or_else(|| self.var_params.as_ref().map(|p| p.loc()))
+            .or_else(|| self.defaults.first().map(|p| p.loc()))
+            .or_else(|| self.kw_var_params.as_ref().map(|p| p.loc()));
+        let last = self
+            .kw_var_params
+            .as_ref()
+            .map(|p| p.loc())
+            .or_else(|| self.defaults.last().map(|p| p.loc()))
+            .or_else(|| self.var_params.as_ref().map(|p| p.loc()))
+            .or_else(|| self.non_defaults.last().map(|p| p.loc()));
+        match (first, last) {
+            (Some(l), Some(r)) => Location::concat(&l, &r),
             _ => Location::Unknown,
         }
     }
@@ -3893,6 +4245,7 @@ type RawParams = (
     Vec<NonDefaultParamSignature>,
     Option<Box<NonDefaultParamSignature>>,
     Vec<DefaultParamSignature>,
+    Option<Box<NonDefaultParamSignature>>,
     Option<(Token, Token)>,
 );
 
@@ -3901,18 +4254,20 @@ impl Params {
         non_defaults: Vec<NonDefaultParamSignature>,
         var_params: Option<NonDefaultParamSignature>,
         defaults: Vec<DefaultParamSignature>,
+        kw_var_params: Option<NonDefaultParamSignature>,
         parens: Option<(Token, Token)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params: var_params.map(Box::new),
             defaults,
+            kw_var_params: kw_var_params.map(Box::new),
             parens,
         }
     }
 
     pub fn single(non_default: NonDefaultParamSignature) -> Self {
-        Self::new(vec![non_default], None, vec![], None)
+        Self::new(vec![non_default], None, vec![], None, None)
     }
 
     pub fn deconstruct(self) -> RawParams {
@@ -3920,6 +4275,7 @@ impl Params {
             self.non_defaults,
             self.var_params,
             self.defaults,
+            self.kw_var_params,
             self.parens,
         )
     }
@@ -4006,6 +4362,13 @@ impl SubrSignature {
     pub fn vis(&self) -> &VisModifierSpec {
         &self.ident.vis
     }
+
+    /// The decorators in the order they are written (the last one is applied first)
+    pub fn ordered_decorators(&self) -> Vec<&Decorator> {
+        let mut decos = self.decorators.iter().collect::<Vec<_>>();
+        decos.sort_by_key(|deco| deco.0.ln_begin());
+        decos
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -4013,11 +4376,15 @@ pub struct LambdaSignature {
     pub bounds: TypeBoundSpecs,
     pub params: Params,
     pub return_t_spec: Option<TypeSpecWithOp>,
+    /// `(x | x > 0) -> ...` (only allowed in match arms)
+    pub guard: Option<Block>,
 }
 
 impl fmt::Display for LambdaSignature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.bounds.is_empty() {
+        if let Some(guard) = &self.guard {
+            write!(f, "({} | {guard})", self.params)
+        } else if self.bounds.is_empty() {
             write!(
                 f,
                 "{}{}",
@@ -4060,13 +4427,19 @@ impl LambdaSignature {
             params,
             return_t_spec,
             bounds,
+            guard: None,
         }
     }
 
+    pub fn with_guard(mut self, guard: Block) -> Self {
+        self.guard = Some(guard);
+        self
+    }
+
     pub fn do_sig(do_symbol: &Token) -> Self {
         let parens = Some((do_symbol.clone(), do_symbol.clone()));
         Self::new(
-            Params::new(vec![], None, vec![], parens),
+            Params::new(vec![], None, vec![], None, parens),
             None,
             TypeBoundSpecs::empty(),
         )
@@ -4237,6 +4610,8 @@ impl AscriptionKind {
 ///                   | expr ':>' type
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TypeAscription {
+    /// compile-time decorators of a declaration (e.g. `@Available python:="3.10"`)
+    pub decorators: HashSet<Decorator>,
     pub expr: Box<Expr>,
     pub t_spec: TypeSpecWithOp,
 }
@@ -4253,11 +4628,17 @@ impl_locational!(TypeAscription, expr, t_spec);
 impl TypeAscription {
     pub fn new(expr: Expr, t_spec: TypeSpecWithOp) -> Self {
         Self {
+            decorators: HashSet::new(),
             expr: Box::new(expr),
             t_spec,
         }
     }
 
+    pub fn with_decorators(mut self, decorators: HashSet<Decorator>) -> Self {
+        self.decorators = decorators;
+        self
+    }
+
     pub fn kind(&self) -> AscriptionKind {
         self.t_spec.ascription_kind()
     }
@@ -4357,6 +4738,7 @@ impl DefBody {
 pub struct Def {
     pub sig: Signature,
     pub body: DefBody,
+    pub doc: Option<DocComment>,
 }
 
 impl NestedDisplay for Def {
@@ -4372,7 +4754,16 @@ impl_locational!(Def, sig, body);
 
 impl Def {
     pub const fn new(sig: Signature, body: DefBody) -> Self {
-        Self { sig, body }
+        Self {
+            sig,
+            body,
+            doc: None,
+        }
+    }
+
+    pub fn with_doc(mut self, doc: Option<DocComment>) -> Self {
+        self.doc = doc;
+        self
     }
 
     pub fn is_const(&self) -> bool {
@@ -4520,6 +4911,7 @@ impl PatchDef {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Expr {
     Literal(Literal),
+    StrInterp(StrInterp),
     Accessor(Accessor),
     Array(Array),
     Tuple(Tuple),
@@ -4537,14 +4929,15 @@ pub enum Expr {
     ClassDef(ClassDef),
     PatchDef(PatchDef),
     ReDef(ReDef),
-    /// for mapping to Python AST
+    /// for mapping to Python AST,
+    /// and also the recovery node of a statement that failed to parse (with no `exprs`)
     Dummy(Dummy),
 }
 
-impl_nested_display_for_chunk_enum!(Expr; Literal, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
-impl_from_trait_for_enum!(Expr; Literal, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
+impl_nested_display_for_chunk_enum!(Expr; Literal, StrInterp, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
+impl_from_trait_for_enum!(Expr; Literal, StrInterp, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
 impl_display_from_nested!(Expr);
-impl_locational_for_enum!(Expr; Literal, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
+impl_locational_for_enum!(Expr; Literal, StrInterp, Accessor, Array, Tuple, Dict, Set, Record, BinOp, UnaryOp, Call, DataPack, Lambda, TypeAscription, Def, Methods, ClassDef, PatchDef, ReDef, Dummy);
 
 impl Expr {
     pub fn is_match_call(&self) -> bool {
@@ -4565,6 +4958,7 @@ impl Expr {
     pub const fn name(&self) -> &'static str {
         match self {
             Self::Literal(_) => "literal",
+            Self::StrInterp(_) => "string interpolation",
             Self::Accessor(_) => "accessor",
             Self::Array(_) => "array",
             Self::Tuple(_) => "tuple",
@@ -1,4 +1,5 @@
 use erg_common::config::ErgConfig;
+use erg_common::timing::{Phase, TIMINGS};
 use erg_common::traits::{ExitStatus, Runnable};
 use erg_common::Str;
 
@@ -65,14 +66,17 @@ impl ASTBuilder {
         IncompleteArtifact<AST, ParserRunnerErrors>,
     > {
         let name = Str::from(self.runner.cfg().input.filename());
+        let path = self.runner.cfg().input.path().to_path_buf();
         let mut desugarer = Desugarer::new();
-        let artifact = self.runner.parse(src).map_err(|iart| {
-            iart.map_mod(|module| {
-                let module = desugarer.desugar(module);
-                AST::new(name.clone(), module)
-            })
-        })?;
-        let module = desugarer.desugar(artifact.ast);
+        let artifact = TIMINGS
+            .measure(Phase::Parse, &path, || self.runner.parse(src))
+            .map_err(|iart| {
+                iart.map_mod(|module| {
+                    let module = desugarer.desugar(module);
+                    AST::new(name.clone(), module)
+                })
+            })?;
+        let module = TIMINGS.measure(Phase::Desugar, &path, || desugarer.desugar(artifact.ast));
         let ast = AST::new(name, module);
         Ok(CompleteArtifact::new(
             ast,
@@ -88,9 +92,9 @@ impl ASTBuilder {
         IncompleteArtifact<AST, ParserRunnerErrors>,
     > {
         let name = Str::from(self.runner.cfg().input.filename());
-        let artifact = self
-            .runner
-            .parse(src)
+        let path = self.runner.cfg().input.path().to_path_buf();
+        let artifact = TIMINGS
+            .measure(Phase::Parse, &path, || self.runner.parse(src))
             .map_err(|iart| iart.map_mod(|module| AST::new(name.clone(), module)))?;
         let ast = AST::new(name, artifact.ast);
         Ok(CompleteArtifact::new(
@@ -211,7 +211,7 @@ impl Parser {
         let mut vars = Vars::empty();
         match tuple {
             Tuple::Normal(tup) => {
-                let (pos_args, _var_args, _kw_args, paren) = tup.elems.deconstruct();
+                let (pos_args, _var_args, _kw_args, _kw_var, paren) = tup.elems.deconstruct();
                 for arg in pos_args {
                     let sig = self
                         .convert_rhs_to_sig(arg.expr)
@@ -325,7 +325,7 @@ impl Parser {
             return Ok(TypeBoundSpecs::empty());
         };
         let mut bounds = vec![];
-        let (pos_args, _var_args, _kw_args, _paren) = args.deconstruct();
+        let (pos_args, _var_args, _kw_args, _kw_var, _paren) = args.deconstruct();
         for arg in pos_args.into_iter() {
             let bound = self
                 .convert_type_arg_to_bound(arg)
@@ -365,8 +365,8 @@ impl Parser {
 
     pub(crate) fn convert_args_to_params(&mut self, args: Args) -> ParseResult<Params> {
         debug_call_info!(self);
-        let (pos_args, var_args, kw_args, parens) = args.deconstruct();
-        let mut params = Params::new(vec![], None, vec![], parens);
+        let (pos_args, var_args, kw_args, kw_var_args, parens) = args.deconstruct();
+        let mut params = Params::new(vec![], None, vec![], None, parens);
         for (i, arg) in pos_args.into_iter().enumerate() {
             let nd_param = self
                 .convert_pos_arg_to_non_default_param(arg, i == 0)
@@ -379,13 +379,18 @@ impl Parser {
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             params.var_params = Some(Box::new(var_args));
         }
-        // TODO: varargs
         for arg in kw_args.into_iter() {
             let d_param = self
                 .convert_kw_arg_to_default_param(arg)
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             params.defaults.push(d_param);
         }
+        if let Some(kw_var_args) = kw_var_args {
+            let kw_var_args = self
+                .convert_pos_arg_to_non_default_param(kw_var_args, false)
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            params.kw_var_params = Some(Box::new(kw_var_args));
+        }
         debug_exit_info!(self);
         Ok(params)
     }
@@ -403,7 +408,7 @@ impl Parser {
         Ok(param)
     }
 
-    fn convert_rhs_to_param(
+    pub(crate) fn convert_rhs_to_param(
         &mut self,
         expr: Expr,
         allow_self: bool,
@@ -498,6 +503,28 @@ impl Parser {
                     Err(())
                 }
             },
+            Expr::BinOp(bin) => match bin.op.kind {
+                TokenKind::AtSign => {
+                    let param = self
+                        .convert_bind_to_param(bin)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    debug_exit_info!(self);
+                    Ok(param)
+                }
+                TokenKind::OrOp => {
+                    let param = self
+                        .convert_or_to_param(bin)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    debug_exit_info!(self);
+                    Ok(param)
+                }
+                _ => {
+                    let err = ParseError::simple_syntax_error(line!() as usize, bin.loc());
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    Err(())
+                }
+            },
             other => {
                 let err = ParseError::simple_syntax_error(line!() as usize, other.loc());
                 self.errs.push(err);
@@ -507,6 +534,107 @@ impl Parser {
         }
     }
 
+    /// `x @ [y, z]` -> `Bind(x, [y, z])`
+    /// `x @ (1 or 2)` -> `x: {1, 2}`
+    fn convert_bind_to_param(&mut self, bin: BinOp) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        let [lhs, rhs] = bin.args;
+        let Expr::Accessor(Accessor::Ident(ident)) = *lhs else {
+            let err = ParseError::simple_syntax_error(line!() as usize, lhs.loc());
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        };
+        let inner = self
+            .convert_rhs_to_param(*rhs, false)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        let pat = match inner.pat {
+            ParamPattern::Discard(_) => ParamPattern::VarName(ident.name),
+            ParamPattern::Lit(_)
+            | ParamPattern::Array(_)
+            | ParamPattern::Tuple(_)
+            | ParamPattern::Record(_) => {
+                ParamPattern::Bind(ParamBindPattern::new(ident.name, inner.pat))
+            }
+            // `x @ y`, `x @ ref y`, `x @ y @ z`
+            other => {
+                let err = ParseError::simple_syntax_error(line!() as usize, other.loc());
+                self.errs.push(err);
+                debug_exit_info!(self);
+                return Err(());
+            }
+        };
+        debug_exit_info!(self);
+        Ok(NonDefaultParamSignature::new(pat, inner.t_spec))
+    }
+
+    /// `1 or 2 or 3` -> `_: {1, 2, 3}`
+    /// `Int or Str` -> `_: Int or Str`
+    fn convert_or_to_param(&mut self, bin: BinOp) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        let expr = Expr::BinOp(bin);
+        let mut lits = vec![];
+        let (t_spec, t_spec_as_expr) = if Self::collect_lit_alternatives(&expr, &mut lits) {
+            let l_brace = Token {
+                content: "{".into(),
+                kind: TokenKind::LBrace,
+                ..lits[0].token.clone()
+            };
+            let r_brace = Token {
+                content: "}".into(),
+                kind: TokenKind::RBrace,
+                ..lits[lits.len() - 1].token.clone()
+            };
+            let args = Args::pos_only(
+                lits.iter()
+                    .map(|lit| PosArg::new(Expr::Literal(lit.clone())))
+                    .collect(),
+                None,
+            );
+            let set = Expr::from(NormalSet::new(l_brace, r_brace, args));
+            (TypeSpec::enum_t_spec(lits), set)
+        } else {
+            let t_spec = Self::expr_to_type_spec(expr.clone()).map_err(|e| {
+                self.errs.push(e);
+                self.stack_dec(fn_name!())
+            })?;
+            (t_spec, expr)
+        };
+        let pat = ParamPattern::Discard(Token::new(
+            TokenKind::UBar,
+            "_",
+            t_spec_as_expr.ln_begin().unwrap_or(1),
+            t_spec_as_expr.col_begin().unwrap_or(0),
+        ));
+        let t_spec = TypeSpecWithOp::new(Token::DUMMY, t_spec, t_spec_as_expr);
+        debug_exit_info!(self);
+        Ok(NonDefaultParamSignature::new(pat, Some(t_spec)))
+    }
+
+    fn is_lit_alternatives(expr: &Expr) -> bool {
+        match expr {
+            Expr::Literal(_) => true,
+            Expr::BinOp(bin) if bin.op.is(TokenKind::OrOp) => {
+                Self::is_lit_alternatives(&bin.args[0]) && Self::is_lit_alternatives(&bin.args[1])
+            }
+            _ => false,
+        }
+    }
+
+    fn collect_lit_alternatives(expr: &Expr, lits: &mut Vec<Literal>) -> bool {
+        match expr {
+            Expr::Literal(lit) => {
+                lits.push(lit.clone());
+                true
+            }
+            Expr::BinOp(bin) if bin.op.is(TokenKind::OrOp) => {
+                Self::collect_lit_alternatives(&bin.args[0], lits)
+                    && Self::collect_lit_alternatives(&bin.args[1], lits)
+            }
+            _ => false,
+        }
+    }
+
     fn convert_kw_arg_to_default_param(
         &mut self,
         arg: KwArg,
@@ -527,7 +655,7 @@ impl Parser {
                 for arg in arr.elems.into_iters().0 {
                     params.push(self.convert_pos_arg_to_non_default_param(arg, false)?);
                 }
-                let params = Params::new(params, None, vec![], None);
+                let params = Params::new(params, None, vec![], None, None);
                 debug_exit_info!(self);
                 Ok(ParamArrayPattern::new(arr.l_sqbr, params, arr.r_sqbr))
             }
@@ -548,14 +676,11 @@ impl Parser {
             return Err(());
         };
         assert_eq!(attr.body.block.len(), 1);
-        let first = attr.body.block.remove(0);
-        let Expr::Accessor(rhs) = first else {
-            let err = ParseError::simple_syntax_error(line!() as usize, first.loc());
-            self.errs.push(err);
-            debug_exit_info!(self);
-            return Err(());
+        let rhs = match attr.body.block.remove(0) {
+            Expr::Accessor(acc) => self.convert_accessor_to_param_sig(acc)?,
+            // {x = (a, b)}, {x = [a, b]}, {x = {y; z}}
+            other => self.convert_rhs_to_param(other, false)?,
         };
-        let rhs = self.convert_accessor_to_param_sig(rhs)?;
         Ok(ParamRecordAttr::new(lhs, rhs))
     }
 
@@ -606,7 +731,7 @@ impl Parser {
         match tuple {
             Tuple::Normal(tup) => {
                 let mut params = vec![];
-                let (elems, var_args, _, parens) = tup.elems.deconstruct();
+                let (elems, var_args, _, _, parens) = tup.elems.deconstruct();
                 for arg in elems.into_iter() {
                     params.push(self.convert_pos_arg_to_non_default_param(arg, false)?);
                 }
@@ -616,7 +741,7 @@ impl Parser {
                 } else {
                     None
                 };
-                let params = Params::new(params, var_params, vec![], parens);
+                let params = Params::new(params, var_params, vec![], None, parens);
                 debug_exit_info!(self);
                 Ok(ParamTuplePattern::new(params))
             }
@@ -694,6 +819,22 @@ impl Parser {
                 Ok(sig)
             }
             Expr::BinOp(bin) => match bin.op.kind {
+                TokenKind::AtSign => {
+                    let param = self
+                        .convert_bind_to_param(bin)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    let params = Params::single(param);
+                    debug_exit_info!(self);
+                    Ok(LambdaSignature::new(params, None, TypeBoundSpecs::empty()))
+                }
+                TokenKind::OrOp if Self::is_lit_alternatives(&bin.args[0]) => {
+                    let param = self
+                        .convert_or_to_param(bin)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    let params = Params::single(param);
+                    debug_exit_info!(self);
+                    Ok(LambdaSignature::new(params, None, TypeBoundSpecs::empty()))
+                }
                 TokenKind::OrOp | TokenKind::AndOp => {
                     let pat = ParamPattern::Discard(bin.op.clone());
                     let expr = Expr::BinOp(bin);
@@ -758,8 +899,8 @@ impl Parser {
         debug_call_info!(self);
         match tuple {
             Tuple::Normal(tup) => {
-                let (pos_args, var_args, kw_args, paren) = tup.elems.deconstruct();
-                let mut params = Params::new(vec![], None, vec![], paren);
+                let (pos_args, var_args, kw_args, kw_var_args, paren) = tup.elems.deconstruct();
+                let mut params = Params::new(vec![], None, vec![], None, paren);
                 for (i, arg) in pos_args.into_iter().enumerate() {
                     let param = self
                         .convert_pos_arg_to_non_default_param(arg, i == 0)
@@ -778,6 +919,12 @@ impl Parser {
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     params.defaults.push(param);
                 }
+                if let Some(kw_var_args) = kw_var_args {
+                    let param = self
+                        .convert_pos_arg_to_non_default_param(kw_var_args, false)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    params.kw_var_params = Some(Box::new(param));
+                }
                 debug_exit_info!(self);
                 Ok(params)
             }
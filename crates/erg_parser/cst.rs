@@ -0,0 +1,615 @@
+//! defines `Cst` (Concrete Syntax Tree).
+//!
+//! Unlike `AST`, `Cst` retains everything in the source code (whitespace, comments, the raw text of literals),
+//! so the source code can be reproduced exactly from it (`cst.to_string() == src`).
+//! Every token and trivia has its `Span` (the byte range in the source code),
+//! and the `AST` can be derived from it (`Cst::to_module`).
+//! This is used by the formatter, and can be used to attach comments to the `AST` nodes
+//! (`Cst::leading_comments`) or to edit the source code precisely (`Cst::span_of`).
+use std::fmt;
+
+use erg_common::error::Location;
+use erg_common::normalize_newline;
+use erg_common::traits::Locational;
+use erg_common::{fn_name, Str};
+
+use crate::ast::Module;
+use crate::desugar::Desugarer;
+use crate::error::{CompleteArtifact, IncompleteArtifact, LexError, LexErrors};
+use crate::lex::Lexer;
+use crate::parse::Parser;
+use crate::token::{Token, TokenKind, TokenStream};
+
+use TokenKind::*;
+
+/// A byte range in the source code (newlines are normalized to `\n`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl Span {
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub const fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    pub fn concat(&self, other: &Self) -> Self {
+        Self::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// e.g. `    `
+    Whitespace,
+    /// `\n`
+    Newline,
+    /// e.g. `# comment`
+    Comment,
+    /// e.g. `#[ comment ]#`
+    MultiLineComment,
+    /// `\` + `\n`
+    LineContinuation,
+}
+
+/// Source code elements that are discarded by the lexer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub content: String,
+    pub span: Span,
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.content)
+    }
+}
+
+impl Trivia {
+    pub fn new<S: Into<String>>(kind: TriviaKind, content: S, span: Span) -> Self {
+        Self {
+            kind,
+            content: content.into(),
+            span,
+        }
+    }
+
+    pub fn is(&self, kind: TriviaKind) -> bool {
+        self.kind == kind
+    }
+
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self.kind,
+            TriviaKind::Comment | TriviaKind::MultiLineComment
+        )
+    }
+}
+
+/// A token and the trivia preceding it.
+#[derive(Debug, Clone)]
+pub struct CstToken {
+    pub leading: Vec<Trivia>,
+    pub token: Token,
+    /// The text of the token in the source code.
+    /// This differs from `token.content` if the token has escape sequences (e.g. `"\n"`).
+    pub raw: Str,
+    /// the span of `raw` (the leading trivia is not included)
+    pub span: Span,
+}
+
+impl fmt::Display for CstToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading.iter() {
+            write!(f, "{trivia}")?;
+        }
+        write!(f, "{}", self.raw)
+    }
+}
+
+impl CstToken {
+    /// The span including the leading trivia
+    pub fn full_span(&self) -> Span {
+        self.leading
+            .first()
+            .map_or(self.span, |trivia| trivia.span.concat(&self.span))
+    }
+
+    /// The position of the token (line number, column)
+    fn pos(&self) -> (u32, u32) {
+        (self.token.lineno, self.token.col_begin)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CstNodeKind {
+    /// the whole file
+    #[default]
+    Module,
+    /// a logical line (a statement, or a line of a multi-line expression)
+    /// the indented block after the line (e.g. the body of a definition) is a child of it
+    Line,
+    /// an indented block
+    Block,
+    /// the tokens enclosed with `()`, `[]` or `{}` (including the brackets)
+    Group,
+}
+
+#[derive(Debug, Clone)]
+pub enum CstElement {
+    /// the index of the token (`Cst::tokens`)
+    Token(usize),
+    /// `Newline`, `Indent`, `Dedent` or `EOF`. They have no text (the text is in the trivia).
+    Layout(Token),
+    Node(CstNode),
+}
+
+/// A node of the tree structure of `Cst`.
+#[derive(Debug, Clone, Default)]
+pub struct CstNode {
+    pub kind: CstNodeKind,
+    pub children: Vec<CstElement>,
+    /// from the first token to the last token of the node (the leading trivia is not included)
+    pub span: Span,
+}
+
+impl CstNode {
+    pub const fn new(kind: CstNodeKind) -> Self {
+        Self {
+            kind,
+            children: vec![],
+            span: Span::new(0, 0),
+        }
+    }
+
+    pub fn is(&self, kind: CstNodeKind) -> bool {
+        self.kind == kind
+    }
+
+    /// The child nodes (not the tokens)
+    pub fn nodes(&self) -> impl Iterator<Item = &CstNode> {
+        self.children.iter().filter_map(|elem| match elem {
+            CstElement::Node(node) => Some(node),
+            _ => None,
+        })
+    }
+
+    /// The indices of all the tokens in the node (including the tokens of the descendants)
+    pub fn token_indices(&self) -> Vec<usize> {
+        let mut indices = vec![];
+        for elem in self.children.iter() {
+            match elem {
+                CstElement::Token(idx) => indices.push(*idx),
+                CstElement::Node(node) => indices.extend(node.token_indices()),
+                CstElement::Layout(_) => {}
+            }
+        }
+        indices
+    }
+
+    fn push_tokens(&self, cst: &Cst, stream: &mut Vec<Token>) {
+        for elem in self.children.iter() {
+            match elem {
+                CstElement::Token(idx) => stream.push(cst.tokens[*idx].token.clone()),
+                CstElement::Layout(token) => stream.push(token.clone()),
+                CstElement::Node(node) => node.push_tokens(cst, stream),
+            }
+        }
+    }
+
+    fn set_span(&mut self, tokens: &[CstToken]) {
+        let indices = self.token_indices();
+        if let (Some(first), Some(last)) = (indices.first(), indices.last()) {
+            self.span = tokens[*first].span.concat(&tokens[*last].span);
+        }
+        for elem in self.children.iter_mut() {
+            if let CstElement::Node(node) = elem {
+                node.set_span(tokens);
+            }
+        }
+    }
+}
+
+/// Builds the tree of `Cst` from the tokens.
+#[derive(Debug)]
+struct TreeBuilder {
+    stack: Vec<CstNode>,
+    /// the number of the indents in groups that have not been closed
+    /// (the lexer does not match the indents with the brackets, e.g. `f(1,\n    2)\n` + `Dedent`)
+    group_indents: usize,
+}
+
+impl TreeBuilder {
+    fn new() -> Self {
+        Self {
+            stack: vec![CstNode::new(CstNodeKind::Module)],
+            group_indents: 0,
+        }
+    }
+
+    fn top(&mut self) -> &mut CstNode {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn top_is(&self, kind: CstNodeKind) -> bool {
+        self.stack.last().is_some_and(|node| node.is(kind))
+    }
+
+    fn push(&mut self, elem: CstElement) {
+        self.top().children.push(elem);
+    }
+
+    fn open(&mut self, kind: CstNodeKind) {
+        self.stack.push(CstNode::new(kind));
+    }
+
+    fn close(&mut self) {
+        if self.stack.len() > 1 {
+            let node = self.stack.pop().unwrap();
+            self.push(CstElement::Node(node));
+        }
+    }
+
+    fn ensure_line(&mut self) {
+        if self.top_is(CstNodeKind::Module) || self.top_is(CstNodeKind::Block) {
+            self.open(CstNodeKind::Line);
+        }
+    }
+
+    /// `next`: the kind of the next token
+    fn token(&mut self, token: Token, idx: Option<usize>, next: Option<TokenKind>) {
+        let in_group = self.top_is(CstNodeKind::Group);
+        match token.kind {
+            LParen | LSqBr | LBrace => {
+                self.ensure_line();
+                self.open(CstNodeKind::Group);
+                self.push(CstElement::Token(idx.unwrap()));
+            }
+            RParen | RSqBr | RBrace if in_group => {
+                self.push(CstElement::Token(idx.unwrap()));
+                self.close();
+            }
+            Indent if in_group => {
+                self.group_indents += 1;
+                self.push(CstElement::Layout(token));
+            }
+            Dedent if self.group_indents > 0 => {
+                self.group_indents -= 1;
+                self.push(CstElement::Layout(token));
+            }
+            Newline if !in_group => {
+                self.push(CstElement::Layout(token));
+                // the line continues to the block (e.g. `f x =` + newline + indented block)
+                if self.top_is(CstNodeKind::Line) && next != Some(Indent) {
+                    self.close();
+                }
+            }
+            Indent if !in_group => {
+                self.ensure_line();
+                self.open(CstNodeKind::Block);
+                self.push(CstElement::Layout(token));
+            }
+            Dedent if !in_group => {
+                if self.top_is(CstNodeKind::Line) {
+                    self.close();
+                }
+                self.push(CstElement::Layout(token));
+                if self.top_is(CstNodeKind::Block) {
+                    self.close();
+                }
+                // the line that owns the block ends with the block
+                if self.top_is(CstNodeKind::Line) {
+                    self.close();
+                }
+            }
+            EOF => {
+                while self.stack.len() > 1 {
+                    self.close();
+                }
+                self.push(CstElement::Layout(token));
+            }
+            Newline | Indent | Dedent => self.push(CstElement::Layout(token)),
+            _ => {
+                self.ensure_line();
+                self.push(CstElement::Token(idx.unwrap()));
+            }
+        }
+    }
+
+    fn finish(mut self) -> CstNode {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+        self.stack.pop().unwrap()
+    }
+}
+
+/// Lossless concrete syntax tree.
+/// `tokens` is the flat sequence of the tokens with trivia (in order of appearance),
+/// and `root` is the tree structure (lines, blocks and groups) of them.
+/// `Newline`, `Indent` and `Dedent` tokens are not included in `tokens`; their text is represented as trivia.
+#[derive(Debug, Clone, Default)]
+pub struct Cst {
+    pub tokens: Vec<CstToken>,
+    /// trivia at the end of the file
+    pub trailing: Vec<Trivia>,
+    pub root: CstNode,
+}
+
+impl fmt::Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.tokens.iter() {
+            write!(f, "{token}")?;
+        }
+        for trivia in self.trailing.iter() {
+            write!(f, "{trivia}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Cst {
+    /// Newlines are normalized to `\n`.
+    pub fn parse(src: &str) -> Result<Self, LexErrors> {
+        let src = normalize_newline(src);
+        let tokens = Lexer::from_str(src.clone()).lex()?;
+        let mut scanner = Scanner::new(&src);
+        let mut cst = Self::default();
+        let mut builder = TreeBuilder::new();
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            let next = tokens.peek().map(|t| t.kind);
+            if matches!(token.kind, Newline | Indent | Dedent | BOF | EOF) {
+                builder.token(token, None, next);
+                continue;
+            }
+            let leading = scanner.trivia();
+            let start = scanner.offset;
+            let Some(raw) = scanner.token(&token) else {
+                return Err(LexErrors::new(vec![LexError::compiler_bug(
+                    line!() as usize,
+                    token.loc(),
+                    fn_name!(),
+                    line!(),
+                )]));
+            };
+            builder.token(token.clone(), Some(cst.tokens.len()), next);
+            cst.tokens.push(CstToken {
+                leading,
+                token,
+                raw: Str::from(raw),
+                span: Span::new(start, scanner.offset),
+            });
+        }
+        cst.trailing = scanner.trivia();
+        if !scanner.is_eof() {
+            return Err(LexErrors::new(vec![LexError::compiler_bug(
+                line!() as usize,
+                Location::Unknown,
+                fn_name!(),
+                line!(),
+            )]));
+        }
+        cst.root = builder.finish();
+        cst.root.set_span(&cst.tokens);
+        Ok(cst)
+    }
+
+    /// The tokens for the parser (the same as the tokens of the lexer)
+    pub fn token_stream(&self) -> TokenStream {
+        let mut stream = vec![];
+        self.root.push_tokens(self, &mut stream);
+        TokenStream::new(stream.into())
+    }
+
+    /// Derives the (desugared) `AST` module from the CST.
+    pub fn to_module(&self) -> Result<CompleteArtifact<Module>, IncompleteArtifact<Module>> {
+        let mut desugarer = Desugarer::new();
+        let artifact = Parser::new(self.token_stream())
+            .parse()
+            .map_err(|iart| iart.map_mod(|module| desugarer.desugar(module)))?;
+        Ok(artifact.map(|module| desugarer.desugar(module)))
+    }
+
+    /// The span of the code at `loc` (e.g. the location of an AST node).
+    pub fn span_of<L: Locational>(&self, loc: &L) -> Option<Span> {
+        let loc = loc.loc();
+        let begin = (loc.ln_begin()?, loc.col_begin().unwrap_or(0));
+        let end = (loc.ln_end()?, loc.col_end().unwrap_or(u32::MAX));
+        let first = self.tokens.partition_point(|token| token.pos() < begin);
+        let last = self.tokens.partition_point(|token| token.pos() < end);
+        if first >= last {
+            return None;
+        }
+        Some(self.tokens[first].span.concat(&self.tokens[last - 1].span))
+    }
+
+    /// The comments just before the code at `loc` (e.g. the documentation comments of a definition).
+    pub fn leading_comments<L: Locational>(&self, loc: &L) -> Vec<&Trivia> {
+        let loc = loc.loc();
+        let (Some(ln), Some(col)) = (loc.ln_begin(), loc.col_begin()) else {
+            return vec![];
+        };
+        self.tokens
+            .iter()
+            .find(|token| token.pos() == (ln, col))
+            .map_or(vec![], |token| {
+                token
+                    .leading
+                    .iter()
+                    .filter(|trivia| trivia.is_comment())
+                    .collect()
+            })
+    }
+}
+
+/// Finds the trivia and the raw text of tokens in the source code.
+struct Scanner {
+    chars: Vec<char>,
+    cursor: usize,
+    /// the byte offset of `cursor`
+    offset: usize,
+    /// the closing quotes of the interpolated strings being scanned
+    quotes: Vec<&'static str>,
+}
+
+impl Scanner {
+    fn new(src: &str) -> Self {
+        Self {
+            chars: src.chars().collect(),
+            cursor: 0,
+            offset: 0,
+            quotes: vec![],
+        }
+    }
+
+    fn is_eof(&self) -> bool {
+        self.cursor >= self.chars.len()
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.cursor + offset).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.cursor + i) == Some(&c))
+    }
+
+    fn take(&mut self, len: usize) -> String {
+        let end = (self.cursor + len).min(self.chars.len());
+        let s: String = self.chars[self.cursor..end].iter().collect();
+        self.cursor = end;
+        self.offset += s.len();
+        s
+    }
+
+    fn trivia(&mut self) -> Vec<Trivia> {
+        let mut trivia = vec![];
+        while let Some(c) = self.peek(0) {
+            let (kind, len) = match (c, self.peek(1)) {
+                (' ', _) => {
+                    let len = self.chars[self.cursor..]
+                        .iter()
+                        .take_while(|c| **c == ' ')
+                        .count();
+                    (TriviaKind::Whitespace, len)
+                }
+                ('\n', _) => (TriviaKind::Newline, 1),
+                ('\\', Some('\n')) => (TriviaKind::LineContinuation, 2),
+                ('#', Some('[')) => (TriviaKind::MultiLineComment, self.multi_line_comment_len()),
+                // doc comment (token)
+                ('#', Some('|')) => break,
+                ('#', _) => {
+                    let len = self.chars[self.cursor..]
+                        .iter()
+                        .take_while(|c| **c != '\n')
+                        .count();
+                    (TriviaKind::Comment, len)
+                }
+                _ => break,
+            };
+            let start = self.offset;
+            let content = self.take(len);
+            trivia.push(Trivia::new(kind, content, Span::new(start, self.offset)));
+        }
+        trivia
+    }
+
+    fn multi_line_comment_len(&self) -> usize {
+        let mut nest_level = 0;
+        let mut i = self.cursor;
+        while i < self.chars.len() {
+            match (self.chars[i], self.chars.get(i + 1)) {
+                ('#', Some('[')) => {
+                    nest_level += 1;
+                    i += 2;
+                }
+                (']', Some('#')) => {
+                    nest_level -= 1;
+                    i += 2;
+                    if nest_level == 0 {
+                        break;
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+        i.min(self.chars.len()) - self.cursor
+    }
+
+    /// Returns the length of a string body (with the closing quote or `\{`) starting at `start`.
+    /// `raw`: `\{` is not an interpolation (e.g. `r"\{"`)
+    fn str_body_len(&mut self, start: usize, quote: &'static str, raw: bool) -> Option<usize> {
+        let mut i = start;
+        while i < self.chars.len() {
+            let rest = &self.chars[i..];
+            if rest.iter().zip(quote.chars()).all(|(a, b)| *a == b) && rest.len() >= quote.len() {
+                self.quotes.pop();
+                return Some(i + quote.len() - self.cursor);
+            }
+            match (rest[0], rest.get(1)) {
+                ('\\', Some('{')) if !raw => return Some(i + 2 - self.cursor),
+                ('\\', Some(_)) => i += 2,
+                _ => i += 1,
+            }
+        }
+        None
+    }
+
+    fn token(&mut self, token: &Token) -> Option<String> {
+        let len = match token.kind {
+            // e.g. `#| ...`
+            DocComment if self.starts_with(&token.content) => token.content.chars().count(),
+            StrLit | StrInterpLeft | DocComment => {
+                // e.g. `r"..."`, `d"""..."""`
+                let prefix = ["rd", "dr", "r", "d"]
+                    .into_iter()
+                    .find(|p| self.starts_with(p) && self.peek(p.len()) == Some('"'))
+                    .unwrap_or("");
+                let quote = ["\"\"\"", "'''", "\"", "'"]
+                    .into_iter()
+                    .find(|q| self.starts_with(&format!("{prefix}{q}")))?;
+                self.quotes.push(quote);
+                let start = self.cursor + prefix.len() + quote.len();
+                self.str_body_len(start, quote, prefix.contains('r'))?
+            }
+            StrInterpMid | StrInterpRight => {
+                let quote = *self.quotes.last()?;
+                self.str_body_len(self.cursor + 1, quote, false)?
+            }
+            // e.g. `+`
+            Symbol if self.starts_with(&format!("`{}`", token.content)) => {
+                token.content.chars().count() + 2
+            }
+            _ => {
+                if !self.starts_with(&token.content) {
+                    return None;
+                }
+                token.content.chars().count()
+            }
+        };
+        Some(self.take(len))
+    }
+}
@@ -5,6 +5,7 @@
 //! 型チェックなどによる検証は行わない
 
 use erg_common::fresh::FreshNameGenerator;
+use erg_common::spawn::is_stack_exhausted;
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{enum_unwrap, get_hash, log, set};
@@ -12,13 +13,13 @@ use erg_common::{enum_unwrap, get_hash, log, set};
 use crate::ast::{
     Accessor, Args, Array, ArrayComprehension, ArrayTypeSpec, ArrayWithLength, BinOp, Block, Call,
     ClassAttr, ClassAttrs, ClassDef, ConstExpr, DataPack, Def, DefBody, DefId,
-    DefaultParamSignature, Dict, Dummy, Expr, Identifier, KeyValue, KwArg, Lambda, LambdaSignature,
-    Literal, Methods, MixedRecord, Module, NonDefaultParamSignature, NormalArray, NormalDict,
-    NormalRecord, NormalSet, NormalTuple, ParamPattern, ParamRecordAttr, ParamTuplePattern, Params,
-    PatchDef, PosArg, ReDef, Record, RecordAttrOrIdent, RecordAttrs, Set as astSet,
-    SetComprehension, SetWithLength, Signature, SubrSignature, Tuple, TupleTypeSpec, TypeAppArgs,
-    TypeAppArgsKind, TypeBoundSpecs, TypeSpec, TypeSpecWithOp, UnaryOp, VarName, VarPattern,
-    VarRecordAttr, VarSignature, VisModifierSpec,
+    DefaultParamSignature, Dict, DictComprehension, Dummy, Expr, Identifier, KeyValue, KwArg,
+    Lambda, LambdaSignature, Literal, Methods, MixedRecord, Module, NonDefaultParamSignature,
+    NormalArray, NormalDict, NormalRecord, NormalSet, NormalTuple, ParamPattern, ParamRecordAttr,
+    ParamTuplePattern, Params, PatchDef, PosArg, ReDef, Record, RecordAttrOrIdent, RecordAttrs,
+    Set as astSet, SetComprehension, SetWithLength, Signature, StrInterp, SubrSignature, Tuple,
+    TupleTypeSpec, TypeAppArgs, TypeAppArgsKind, TypeBoundSpecs, TypeSpec, TypeSpecWithOp, UnaryOp,
+    VarName, VarPattern, VarRecordAttr, VarSignature, VisModifierSpec,
 };
 use crate::token::{Token, TokenKind, COLON, DOT};
 
@@ -45,6 +46,7 @@ impl Desugarer {
 
     pub fn desugar(&mut self, module: Module) -> Module {
         log!(info "the desugaring process has started.");
+        let module = self.desugar_placeholder(module);
         let module = self.desugar_multiple_pattern_def(module);
         let module = self.desugar_pattern_in_module(module);
         let module = Self::desugar_shortened_record(module);
@@ -61,12 +63,57 @@ impl Desugarer {
         Self::rec_desugar_acc(expr)
     }
 
+    /// `x[a..b; s]` => `x[slice(a, b + 1, s)]`
+    /// `x[a<..<b]` => `x[slice(a + 1, b)]`
+    /// `x[..b]` => `x[slice(None, b + 1)]`
+    /// `x[a..]` => `x[slice(a, None)]`
+    pub fn desugar_slice(
+        start: Option<Expr>,
+        op: Token,
+        stop: Option<Expr>,
+        step: Option<Expr>,
+    ) -> Expr {
+        let (line, col) = (op.lineno, op.col_begin);
+        let none = || {
+            Expr::Literal(Literal::new(Token::new(
+                TokenKind::NoneLit,
+                "None",
+                line,
+                col,
+            )))
+        };
+        let succ = |expr: Expr| {
+            let plus = Token::new(TokenKind::Plus, "+", line, col);
+            let one = Expr::Literal(Literal::new(Token::new(TokenKind::NatLit, "1", line, col)));
+            Expr::BinOp(BinOp::new(plus, expr, one))
+        };
+        let start = match (start, op.kind) {
+            (Some(start), TokenKind::LeftOpen | TokenKind::Open) => succ(start),
+            (Some(start), _) => start,
+            (None, _) => none(),
+        };
+        let stop = match (stop, op.kind) {
+            (Some(stop), TokenKind::Closed | TokenKind::LeftOpen) => succ(stop),
+            (Some(stop), _) => stop,
+            (None, _) => none(),
+        };
+        let mut args = vec![PosArg::new(start), PosArg::new(stop)];
+        if let Some(step) = step {
+            args.push(PosArg::new(step));
+        }
+        let slice = Expr::Accessor(Accessor::Ident(Identifier::private_with_line(
+            Str::ever("slice"),
+            line,
+        )));
+        slice.call_expr(Args::pos_only(args, None))
+    }
+
     fn desugar_all_chunks(module: Module, desugar: impl Fn(Expr) -> Expr) -> Module {
         module.into_iter().map(desugar).collect()
     }
 
     fn desugar_args(mut desugar: impl FnMut(Expr) -> Expr, args: Args) -> Args {
-        let (pos_args, var_args, kw_args, paren) = args.deconstruct();
+        let (pos_args, var_args, kw_args, kw_var_args, paren) = args.deconstruct();
         let pos_args = pos_args
             .into_iter()
             .map(|arg| PosArg::new(desugar(arg.expr)))
@@ -78,7 +125,8 @@ impl Desugarer {
                 KwArg::new(arg.keyword, arg.t_spec, desugar(arg.expr)) // TODO: t_spec
             })
             .collect();
-        Args::new(pos_args, var_args, kw_args, paren)
+        let kw_var_args = kw_var_args.map(|arg| PosArg::new(desugar(arg.expr)));
+        Args::new(pos_args, var_args, kw_args, kw_var_args, paren)
     }
 
     fn perform_desugar_acc(mut desugar: impl FnMut(Expr) -> Expr, acc: Accessor) -> Accessor {
@@ -110,8 +158,16 @@ impl Desugarer {
     }
 
     fn perform_desugar(mut desugar: impl FnMut(Expr) -> Expr, expr: Expr) -> Expr {
+        // too deeply nested code is left as is (the lowerer reports it as an error)
+        if is_stack_exhausted() {
+            return expr;
+        }
         match expr {
             Expr::Literal(_) => expr,
+            Expr::StrInterp(interp) => {
+                let exprs = interp.exprs.into_iter().map(desugar).collect();
+                Expr::StrInterp(StrInterp::new(interp.lits, exprs))
+            }
             Expr::Record(record) => match record {
                 Record::Normal(rec) => {
                     let mut new_attrs = vec![];
@@ -184,7 +240,7 @@ impl Desugarer {
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
-                    let (elems, _, _, paren) = tup.elems.deconstruct();
+                    let (elems, _, _, _, paren) = tup.elems.deconstruct();
                     let elems = elems
                         .into_iter()
                         .map(|elem| PosArg::new(desugar(elem.expr)))
@@ -212,16 +268,15 @@ impl Desugarer {
                     Expr::Set(astSet::WithLength(set))
                 }
                 astSet::Comprehension(set) => {
-                    let iter = desugar(*set.iter);
-                    let pred = desugar(*set.pred);
-                    let set = SetComprehension::new(
-                        set.l_brace,
-                        set.r_brace,
-                        set.var,
-                        set.op,
-                        iter,
-                        pred,
-                    );
+                    let layout = set.layout.map(|layout| desugar(*layout));
+                    let generators = set
+                        .generators
+                        .into_iter()
+                        .map(|(ident, gen)| (ident, desugar(gen)))
+                        .collect();
+                    let guards = set.guards.into_iter().map(desugar).collect();
+                    let set =
+                        SetComprehension::new(set.l_brace, set.r_brace, layout, generators, guards);
                     Expr::Set(astSet::Comprehension(set))
                 }
             },
@@ -239,7 +294,25 @@ impl Desugarer {
                     let tup = NormalDict::new(dic.l_brace, dic.r_brace, new_kvs);
                     Expr::Dict(Dict::Normal(tup))
                 }
-                _ => todo!("dict comprehension"),
+                Dict::Comprehension(dic) => {
+                    let key = desugar(*dic.key);
+                    let value = desugar(*dic.value);
+                    let generators = dic
+                        .generators
+                        .into_iter()
+                        .map(|(ident, gen)| (ident, desugar(gen)))
+                        .collect();
+                    let guards = dic.guards.into_iter().map(desugar).collect();
+                    let dic = DictComprehension::new(
+                        dic.l_brace,
+                        dic.r_brace,
+                        key,
+                        value,
+                        generators,
+                        guards,
+                    );
+                    Expr::Dict(Dict::Comprehension(dic))
+                }
             },
             Expr::BinOp(binop) => {
                 let mut args = binop.args.into_iter();
@@ -270,7 +343,7 @@ impl Desugarer {
                     def.sig = Signature::Subr(subr);
                 }
                 let body = DefBody::new(def.body.op, Block::new(chunks), def.body.id);
-                Expr::Def(Def::new(def.sig, body))
+                Expr::Def(Def::new(def.sig, body).with_doc(def.doc))
             }
             Expr::ClassDef(class_def) => {
                 let Expr::Def(def) = desugar(Expr::Def(class_def.def)) else { unreachable!() };
@@ -303,6 +376,10 @@ impl Desugarer {
                 if let Some(t_op) = &mut lambda.sig.return_t_spec {
                     *t_op.t_spec_as_expr = desugar(*t_op.t_spec_as_expr.clone());
                 }
+                if let Some(guard) = lambda.sig.guard.take() {
+                    let guard = guard.into_iter().map(&mut desugar).collect();
+                    lambda.sig.guard = Some(Block::new(guard));
+                }
                 lambda.sig.params = Self::perform_desugar_params(desugar, lambda.sig.params);
                 let body = Block::new(chunks);
                 Expr::Lambda(Lambda::new(lambda.sig, lambda.op, body, lambda.id))
@@ -312,7 +389,7 @@ impl Desugarer {
                 let t_spec_as_expr = desugar(*tasc.t_spec.t_spec_as_expr);
                 let t_spec =
                     TypeSpecWithOp::new(tasc.t_spec.op, tasc.t_spec.t_spec, t_spec_as_expr);
-                expr.type_asc_expr(t_spec)
+                Expr::TypeAscription(expr.type_asc(t_spec).with_decorators(tasc.decorators))
             }
             Expr::Methods(method_defs) => {
                 let mut new_attrs = vec![];
@@ -324,7 +401,8 @@ impl Desugarer {
                                 chunks.push(desugar(chunk));
                             }
                             let body = DefBody::new(def.body.op, Block::new(chunks), def.body.id);
-                            new_attrs.push(ClassAttr::Def(Def::new(def.sig, body)));
+                            new_attrs
+                                .push(ClassAttr::Def(Def::new(def.sig, body).with_doc(def.doc)));
                         }
                         ClassAttr::Decl(decl) => {
                             let expr = desugar(*decl.expr);
@@ -334,7 +412,9 @@ impl Desugarer {
                                 decl.t_spec.t_spec,
                                 t_spec_as_expr,
                             );
-                            new_attrs.push(ClassAttr::Decl(expr.type_asc(t_spec)));
+                            new_attrs.push(ClassAttr::Decl(
+                                expr.type_asc(t_spec).with_decorators(decl.decorators),
+                            ));
                         }
                         ClassAttr::Doc(doc) => {
                             new_attrs.push(ClassAttr::Doc(doc));
@@ -375,6 +455,12 @@ impl Desugarer {
             });
             var_params
         });
+        params.kw_var_params = params.kw_var_params.map(|mut kw_var_params| {
+            kw_var_params.t_spec = kw_var_params.t_spec.map(|t_spec| {
+                TypeSpecWithOp::new(t_spec.op, t_spec.t_spec, desugar(*t_spec.t_spec_as_expr))
+            });
+            kw_var_params
+        });
         let mut defaults = vec![];
         for mut default in params.defaults.into_iter() {
             let default_val = desugar(default.default_val);
@@ -401,7 +487,13 @@ impl Desugarer {
                     if let Some(Expr::Def(previous)) = new.last() {
                         if previous.is_subr() && previous.sig.name_as_str() == def.sig.name_as_str()
                         {
-                            let Some(Expr::Def(previous)) = new.pop() else { unreachable!() };
+                            let Some(Expr::Def(mut previous)) = new.pop() else { unreachable!() };
+                            let doc = previous.doc.take();
+                            // the decorators are written on the first definition
+                            let decorators = match &previous.sig {
+                                Signature::Subr(subr) => subr.decorators.clone(),
+                                Signature::Var(_) => set! {},
+                            };
                             let name = def.sig.ident().unwrap().clone();
                             let id = def.body.id;
                             let op = def.body.op.clone();
@@ -434,7 +526,7 @@ impl Desugarer {
                                             _ => unreachable!(),
                                         }
                                     }
-                                    Params::new(params, None, vec![], None)
+                                    Params::new(params, None, vec![], None, None)
                                 }
                                 Expr::Accessor(Accessor::Ident(ident)) => {
                                     let param_name = ident.inspect();
@@ -453,14 +545,14 @@ impl Desugarer {
                                 _ => unreachable!(),
                             };
                             let sig = Signature::Subr(SubrSignature::new(
-                                set! {},
+                                decorators,
                                 name,
                                 TypeBoundSpecs::empty(),
                                 params,
                                 return_t_spec,
                             ));
                             let body = DefBody::new(op, Block::new(vec![Expr::Call(call)]), id);
-                            let def = Def::new(sig, body);
+                            let def = Def::new(sig, body).with_doc(doc);
                             new.push(Expr::Def(def));
                         } else {
                             new.push(Expr::Def(def));
@@ -570,16 +662,36 @@ impl Desugarer {
         (buf_name, buf_sig)
     }
 
-    fn gen_buf_nd_param(&mut self, line: u32) -> (Str, ParamPattern) {
+    /// If the pattern is bound to a name (`name @ pattern`), the name is used as the buffer
+    fn gen_buf_nd_param(&mut self, bind: Option<VarName>, line: u32) -> (Str, ParamPattern) {
+        if let Some(name) = bind {
+            return (name.inspect().clone(), ParamPattern::VarName(name));
+        }
         let buf_name = self.var_gen.fresh_varname();
         let pat = ParamPattern::VarName(VarName::from_str_and_line(Str::rc(&buf_name), line));
         (buf_name, pat)
     }
 
+    /// `x @ [y, z]` -> `[y, z]` (returns `x`)
+    fn take_bind_name(pat: &mut ParamPattern) -> Option<VarName> {
+        let ParamPattern::Bind(bind) = pat else {
+            return None;
+        };
+        let name = bind.name.clone();
+        let inner = std::mem::replace(bind.pat.as_mut(), ParamPattern::Discard(Token::DUMMY));
+        *pat = inner;
+        Some(name)
+    }
+
     fn rec_desugar_lambda_pattern(&mut self, expr: Expr) -> Expr {
         match expr {
             Expr::Lambda(mut lambda) => {
-                self.desugar_params_patterns(&mut lambda.sig.params, &mut lambda.body);
+                // the guard is evaluated before the body, so the destructured variables must be bound in it
+                let bind_to = lambda.sig.guard.as_mut().unwrap_or(&mut lambda.body);
+                self.desugar_params_patterns(&mut lambda.sig.params, bind_to);
+                if let Some(guard) = lambda.sig.guard.take() {
+                    lambda.sig.guard = Some(self.desugar_pattern_in_block(guard));
+                }
                 lambda.body = self.desugar_pattern_in_block(lambda.body);
                 Expr::Lambda(lambda)
             }
@@ -611,6 +723,7 @@ impl Desugarer {
                 Expr::Def(Def {
                     sig: Signature::Var(v),
                     body,
+                    doc,
                 }) => match &v.pat {
                     VarPattern::Tuple(tup) => {
                         let (buf_name, buf_sig) =
@@ -707,13 +820,14 @@ impl Desugarer {
                             .collect();
                         let block = self.desugar_pattern_in_block(block);
                         let body = DefBody::new(body.op, block, body.id);
-                        let def = Def::new(Signature::Var(v), body);
+                        let def = Def::new(Signature::Var(v), body).with_doc(doc);
                         new.push(Expr::Def(def));
                     }
                 },
                 Expr::Def(Def {
                     sig: Signature::Subr(mut subr),
                     mut body,
+                    doc,
                 }) => {
                     self.desugar_params_patterns(&mut subr.params, &mut body.block);
                     let block = body
@@ -723,7 +837,7 @@ impl Desugarer {
                         .collect();
                     let block = self.desugar_pattern_in_block(block);
                     let body = DefBody::new(body.op, block, body.id);
-                    let def = Def::new(Signature::Subr(subr), body);
+                    let def = Def::new(Signature::Subr(subr), body).with_doc(doc);
                     new.push(Expr::Def(def));
                 }
                 Expr::Dummy(dummy) => {
@@ -749,6 +863,9 @@ impl Desugarer {
         for param in params.defaults.iter_mut() {
             self.desugar_nd_param(&mut param.sig, body);
         }
+        if let Some(kw_var_params) = params.kw_var_params.as_mut() {
+            self.desugar_nd_param(kw_var_params, body);
+        }
     }
 
     fn desugar_nested_var_pattern(
@@ -903,25 +1020,56 @@ impl Desugarer {
         NormalRecord::new(record.l_brace, record.r_brace, attrs)
     }
 
-    fn dummy_array_expr(len: Literal) -> Expr {
-        let l_sqbr = Token {
-            content: "[".into(),
-            kind: TokenKind::LSqBr,
-            ..len.token
-        };
-        let r_sqbr = Token {
-            content: "]".into(),
-            kind: TokenKind::RSqBr,
-            ..len.token
-        };
-        let elem = Expr::local("Obj", l_sqbr.lineno, l_sqbr.col_begin);
-        let array = Array::WithLength(ArrayWithLength::new(
+    /// `[x, 1, (y, z)]` -> `[?; 3]` (for type checking) and `[Obj, {1}, (Obj, Obj)]` (for runtime pattern matching)
+    ///
+    /// `[x: Int, 1]` -> `[Int or {1}; 2]` and `[Int, {1}]`
+    ///
+    /// `[(x, y), (z, w)]` -> `[(?, ?); 2]` and `[(Obj, Obj), (Obj, Obj)]`
+    fn array_pattern_t_spec(elems: &Params, line: u32) -> TypeSpecWithOp {
+        let len = elems.non_defaults.len();
+        let len = Literal::new(Token::new(TokenKind::NatLit, len.to_string(), line, 0));
+        let infer = Token::new(TokenKind::Try, "?", line, 0);
+        let elem_t_specs = elems
+            .non_defaults
+            .iter()
+            .map(|elem| elem.t_spec.as_ref().map(|ts| ts.t_spec.clone()))
+            .collect::<Option<Vec<_>>>();
+        let elem_t_spec = match elem_t_specs {
+            Some(t_specs) if t_specs.iter().all(|t_spec| !t_spec.has_infer()) => {
+                t_specs.into_iter().reduce(TypeSpec::or)
+            }
+            // elements with the same shape: the element type can be inferred from the shape
+            Some(t_specs)
+                if t_specs
+                    .iter()
+                    .all(|t_spec| t_spec.to_string() == t_specs[0].to_string()) =>
+            {
+                t_specs.into_iter().next()
+            }
+            _ => None,
+        }
+        .unwrap_or(TypeSpec::Infer(infer));
+        let t_spec = ArrayTypeSpec::new(elem_t_spec, ConstExpr::Lit(len));
+        let elem_exprs = elems
+            .non_defaults
+            .iter()
+            .map(|elem| {
+                let expr = elem
+                    .t_spec
+                    .as_ref()
+                    .map(|ts| *ts.t_spec_as_expr.clone())
+                    .unwrap_or(Expr::local("Obj", line, 0));
+                PosArg::new(expr)
+            })
+            .collect();
+        let l_sqbr = Token::new(TokenKind::LSqBr, "[", line, 0);
+        let r_sqbr = Token::new(TokenKind::RSqBr, "]", line, 0);
+        let t_spec_as_expr = Expr::Array(Array::Normal(NormalArray::new(
             l_sqbr,
             r_sqbr,
-            PosArg::new(elem),
-            Expr::Literal(len),
-        ));
-        Expr::Array(array)
+            Args::pos_only(elem_exprs, None),
+        )));
+        TypeSpecWithOp::new(COLON, TypeSpec::Array(t_spec), t_spec_as_expr)
     }
 
     fn dummy_set_expr(lit: Literal) -> Expr {
@@ -974,16 +1122,21 @@ impl Desugarer {
     fn desugar_nd_param(&mut self, param: &mut NonDefaultParamSignature, body: &mut Block) {
         let mut insertion_idx = 0;
         let line = param.ln_begin().unwrap_or(1);
+        let bind = Self::take_bind_name(&mut param.pat);
         match &mut param.pat {
             ParamPattern::VarName(_v) => {}
             ParamPattern::Lit(l) => {
                 let lit = l.clone();
-                param.pat = ParamPattern::Discard(Token::new(
-                    TokenKind::UBar,
-                    "_",
-                    l.ln_begin().unwrap_or(1),
-                    l.col_begin().unwrap_or(0),
-                ));
+                param.pat = if let Some(name) = bind {
+                    ParamPattern::VarName(name)
+                } else {
+                    ParamPattern::Discard(Token::new(
+                        TokenKind::UBar,
+                        "_",
+                        l.ln_begin().unwrap_or(1),
+                        l.col_begin().unwrap_or(0),
+                    ))
+                };
                 let l_brace = Token {
                     content: "{".into(),
                     kind: TokenKind::LBrace,
@@ -1001,7 +1154,7 @@ impl Desugarer {
                 param.t_spec = Some(t_spec);
             }
             ParamPattern::Tuple(tup) => {
-                let (buf_name, buf_param) = self.gen_buf_nd_param(line);
+                let (buf_name, buf_param) = self.gen_buf_nd_param(bind.clone(), line);
                 let mut ty_specs = vec![];
                 let mut ty_exprs = vec![];
                 for (n, elem) in tup.elems.non_defaults.iter_mut().enumerate() {
@@ -1039,7 +1192,7 @@ impl Desugarer {
                 param.pat = buf_param;
             }
             ParamPattern::Array(arr) => {
-                let (buf_name, buf_param) = self.gen_buf_nd_param(line);
+                let (buf_name, buf_param) = self.gen_buf_nd_param(bind.clone(), line);
                 for (n, elem) in arr.elems.non_defaults.iter_mut().enumerate() {
                     insertion_idx = self.desugar_nested_param_pattern(
                         body,
@@ -1050,22 +1203,12 @@ impl Desugarer {
                     );
                 }
                 if param.t_spec.is_none() {
-                    let len = arr.elems.non_defaults.len();
-                    let len = Literal::new(Token::new(TokenKind::NatLit, len.to_string(), line, 0));
-                    let infer = Token::new(TokenKind::Try, "?", line, 0);
-                    let t_spec =
-                        ArrayTypeSpec::new(TypeSpec::Infer(infer), ConstExpr::Lit(len.clone()));
-                    let t_spec_as_expr = Self::dummy_array_expr(len);
-                    param.t_spec = Some(TypeSpecWithOp::new(
-                        Token::dummy(TokenKind::Colon, ":"),
-                        TypeSpec::Array(t_spec),
-                        t_spec_as_expr,
-                    ));
+                    param.t_spec = Some(Self::array_pattern_t_spec(&arr.elems, line));
                 }
                 param.pat = buf_param;
             }
             ParamPattern::Record(rec) => {
-                let (buf_name, buf_param) = self.gen_buf_nd_param(line);
+                let (buf_name, buf_param) = self.gen_buf_nd_param(bind.clone(), line);
                 for ParamRecordAttr { lhs, rhs } in rec.elems.iter_mut() {
                     insertion_idx = self.desugar_nested_param_pattern(
                         body,
@@ -1131,6 +1274,19 @@ impl Desugarer {
         }
     }
 
+    /// `%buf: t_spec = body` (the type is specified so that the nested patterns can be type-checked)
+    fn insert_buf_def(
+        new_body: &mut Block,
+        idx: usize,
+        buf_name: &str,
+        t_spec: Option<TypeSpecWithOp>,
+        body: DefBody,
+    ) {
+        let ident = Identifier::private(Str::rc(buf_name));
+        let sig = Signature::Var(VarSignature::new(VarPattern::Ident(ident), t_spec));
+        new_body.insert(idx, Expr::Def(Def::new(sig, body)));
+    }
+
     fn desugar_nested_param_pattern(
         &mut self,
         new_body: &mut Block,
@@ -1168,21 +1324,12 @@ impl Desugarer {
         let op = Token::from_str(TokenKind::Assign, "=");
         let body = DefBody::new(op, block, id);
         let line = sig.ln_begin().unwrap_or(1);
+        let bind = Self::take_bind_name(&mut sig.pat);
         match &mut sig.pat {
             ParamPattern::Tuple(tup) => {
-                let (buf_name, buf_sig) = self.gen_buf_nd_param(line);
-                let ident = Identifier::private(Str::from(&buf_name));
-                new_body.insert(
-                    insertion_idx,
-                    Expr::Def(Def::new(
-                        Signature::Var(VarSignature::new(
-                            VarPattern::Ident(ident),
-                            sig.t_spec.clone(),
-                        )),
-                        body,
-                    )),
-                );
-                insertion_idx += 1;
+                let (buf_name, buf_sig) = self.gen_buf_nd_param(bind.clone(), line);
+                // the buffer definition is inserted here after its type is determined
+                let buf_idx = insertion_idx;
                 let mut ty_exprs = vec![];
                 let mut tys = vec![];
                 for (n, elem) in tup.elems.non_defaults.iter_mut().enumerate() {
@@ -1216,22 +1363,13 @@ impl Desugarer {
                     )));
                     sig.t_spec = Some(TypeSpecWithOp::new(COLON, t_spec, t_spec_as_expr));
                 }
+                Self::insert_buf_def(new_body, buf_idx, &buf_name, sig.t_spec.clone(), body);
                 sig.pat = buf_sig;
-                insertion_idx
+                insertion_idx + 1
             }
             ParamPattern::Array(arr) => {
-                let (buf_name, buf_sig) = self.gen_buf_nd_param(line);
-                new_body.insert(
-                    insertion_idx,
-                    Expr::Def(Def::new(
-                        Signature::Var(VarSignature::new(
-                            VarPattern::Ident(Identifier::private(Str::from(&buf_name))),
-                            sig.t_spec.clone(),
-                        )),
-                        body,
-                    )),
-                );
-                insertion_idx += 1;
+                let (buf_name, buf_sig) = self.gen_buf_nd_param(bind.clone(), line);
+                let buf_idx = insertion_idx;
                 for (n, elem) in arr.elems.non_defaults.iter_mut().enumerate() {
                     insertion_idx = self.desugar_nested_param_pattern(
                         new_body,
@@ -1242,34 +1380,15 @@ impl Desugarer {
                     );
                 }
                 if sig.t_spec.is_none() {
-                    let len = arr.elems.non_defaults.len();
-                    let len = Literal::new(Token::new(TokenKind::NatLit, len.to_string(), line, 0));
-                    let infer = Token::new(TokenKind::Try, "?", line, 0);
-                    let t_spec =
-                        ArrayTypeSpec::new(TypeSpec::Infer(infer), ConstExpr::Lit(len.clone()));
-                    let t_spec_as_expr = Self::dummy_array_expr(len);
-                    sig.t_spec = Some(TypeSpecWithOp::new(
-                        COLON,
-                        TypeSpec::Array(t_spec),
-                        t_spec_as_expr,
-                    ));
+                    sig.t_spec = Some(Self::array_pattern_t_spec(&arr.elems, line));
                 }
+                Self::insert_buf_def(new_body, buf_idx, &buf_name, sig.t_spec.clone(), body);
                 sig.pat = buf_sig;
-                insertion_idx
+                insertion_idx + 1
             }
             ParamPattern::Record(rec) => {
-                let (buf_name, buf_sig) = self.gen_buf_nd_param(line);
-                new_body.insert(
-                    insertion_idx,
-                    Expr::Def(Def::new(
-                        Signature::Var(VarSignature::new(
-                            VarPattern::Ident(Identifier::private(Str::from(&buf_name))),
-                            sig.t_spec.clone(),
-                        )),
-                        body,
-                    )),
-                );
-                insertion_idx += 1;
+                let (buf_name, buf_sig) = self.gen_buf_nd_param(bind.clone(), line);
+                let buf_idx = insertion_idx;
                 let mut attrs = RecordAttrs::new(vec![]);
                 let mut tys = vec![];
                 for ParamRecordAttr { lhs, rhs } in rec.elems.iter_mut() {
@@ -1310,8 +1429,9 @@ impl Desugarer {
                     ));
                     sig.t_spec = Some(TypeSpecWithOp::new(COLON, t_spec, t_spec_as_expr));
                 }
+                Self::insert_buf_def(new_body, buf_idx, &buf_name, sig.t_spec.clone(), body);
                 sig.pat = buf_sig;
-                insertion_idx
+                insertion_idx + 1
             }
             /*
             VarPattern::DataPack(pack) => {
@@ -1348,6 +1468,13 @@ impl Desugarer {
                 let t_spec = TypeSpec::enum_t_spec(vec![lit.clone()]);
                 let t_spec_as_expr = Self::dummy_set_expr(lit);
                 sig.t_spec = Some(TypeSpecWithOp::new(COLON, t_spec, t_spec_as_expr));
+                if let Some(name) = bind {
+                    let ident = Identifier::new(VisModifierSpec::Private, name);
+                    let v = VarSignature::new(VarPattern::Ident(ident), sig.t_spec.clone());
+                    let def = Def::new(Signature::Var(v), body);
+                    new_body.insert(insertion_idx, Expr::Def(def));
+                    insertion_idx += 1;
+                }
                 insertion_idx
             }
             _ => insertion_idx,
@@ -1426,6 +1553,207 @@ impl Desugarer {
     }
 
     // TODO: pipeline desugaring (move from `Parser`)
+    /// `(_ + 1)` -> `%1 -> %1 + 1`
+    /// `(_ + _ * 2)` -> `(%1, %2) -> %1 + %2 * 2`
+    /// `f(_, 1)` -> `%1 -> f(%1, 1)`
+    /// `_.method(1)` -> `%1 -> %1.method(1)`
+    /// `_.attr` -> `%1 -> %1.attr`
+    fn desugar_placeholder(&mut self, module: Module) -> Module {
+        module
+            .into_iter()
+            .map(|chunk| self.rec_desugar_placeholder(chunk))
+            .collect()
+    }
+
+    fn is_placeholder(expr: &Expr) -> bool {
+        matches!(expr, Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == "_")
+    }
+
+    /// Whether the operator chain (e.g. `_ + x * _`) contains placeholders as its operands
+    fn has_operand_placeholder(expr: &Expr) -> bool {
+        match expr {
+            Expr::BinOp(bin) => bin
+                .args
+                .iter()
+                .any(|arg| Self::is_placeholder(arg) || Self::has_operand_placeholder(arg)),
+            Expr::UnaryOp(unary) => unary
+                .args
+                .iter()
+                .any(|arg| Self::is_placeholder(arg) || Self::has_operand_placeholder(arg)),
+            _ => false,
+        }
+    }
+
+    fn has_arg_placeholder(call: &Call) -> bool {
+        (call.attr_name.is_some() && Self::is_placeholder(&call.obj))
+            || call
+                .args
+                .pos_args()
+                .iter()
+                .any(|arg| Self::is_placeholder(&arg.expr))
+            || call
+                .args
+                .kw_args()
+                .iter()
+                .any(|arg| Self::is_placeholder(&arg.expr))
+    }
+
+    fn rec_desugar_placeholder(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::BinOp(_) | Expr::UnaryOp(_) if Self::has_operand_placeholder(&expr) => {
+                let mut params = vec![];
+                let body = self.replace_operand_placeholders(expr, &mut params);
+                Self::gen_placeholder_lambda(params, body, false)
+            }
+            Expr::Call(call) if Self::has_arg_placeholder(&call) => {
+                let mut params = vec![];
+                let is_procedural = match (&call.attr_name, call.obj.as_ref()) {
+                    (Some(attr), _) => attr.is_procedural(),
+                    (None, Expr::Accessor(Accessor::Ident(ident))) => ident.is_procedural(),
+                    _ => false,
+                };
+                let obj = self.replace_placeholder(*call.obj, &mut params);
+                let (pos_args, var_args, kw_args, kw_var_args, paren) = call.args.deconstruct();
+                let pos_args = pos_args
+                    .into_iter()
+                    .map(|arg| PosArg::new(self.replace_placeholder(arg.expr, &mut params)))
+                    .collect();
+                let var_args =
+                    var_args.map(|arg| PosArg::new(self.rec_desugar_placeholder(arg.expr)));
+                let kw_args = kw_args
+                    .into_iter()
+                    .map(|arg| {
+                        let expr = self.replace_placeholder(arg.expr, &mut params);
+                        KwArg::new(arg.keyword, arg.t_spec, expr)
+                    })
+                    .collect();
+                let kw_var_args =
+                    kw_var_args.map(|arg| PosArg::new(self.rec_desugar_placeholder(arg.expr)));
+                let args = Args::new(pos_args, var_args, kw_args, kw_var_args, paren);
+                let body = Expr::Call(Call::new(obj, call.attr_name, args));
+                Self::gen_placeholder_lambda(params, body, is_procedural)
+            }
+            Expr::Accessor(Accessor::Attr(attr)) if Self::is_placeholder(&attr.obj) => {
+                let mut params = vec![];
+                let obj = self.replace_placeholder(*attr.obj, &mut params);
+                let body = obj.attr_expr(attr.ident);
+                Self::gen_placeholder_lambda(params, body, false)
+            }
+            // type specifications can contain `_` (e.g. `Array(Int, _)`), so they are left as they are
+            // (`C(T, _) <: Trait` is also a type-level expression)
+            Expr::TypeAscription(tasc) if tasc.t_spec.op.is(TokenKind::Colon) => {
+                let expr = self.rec_desugar_placeholder(*tasc.expr);
+                Expr::TypeAscription(expr.type_asc(tasc.t_spec).with_decorators(tasc.decorators))
+            }
+            Expr::TypeAscription(tasc) => Expr::TypeAscription(tasc),
+            Expr::Def(def) => {
+                let block = def
+                    .body
+                    .block
+                    .into_iter()
+                    .map(|chunk| self.rec_desugar_placeholder(chunk))
+                    .collect();
+                let body = DefBody::new(def.body.op, block, def.body.id);
+                Expr::Def(Def::new(def.sig, body).with_doc(def.doc))
+            }
+            Expr::Lambda(mut lambda) => {
+                if let Some(guard) = lambda.sig.guard.take() {
+                    let guard = guard
+                        .into_iter()
+                        .map(|chunk| self.rec_desugar_placeholder(chunk))
+                        .collect();
+                    lambda.sig.guard = Some(guard);
+                }
+                let body = lambda
+                    .body
+                    .into_iter()
+                    .map(|chunk| self.rec_desugar_placeholder(chunk))
+                    .collect();
+                Expr::Lambda(Lambda::new(lambda.sig, lambda.op, body, lambda.id))
+            }
+            Expr::Methods(methods) => {
+                let attrs = methods
+                    .attrs
+                    .into_iter()
+                    .map(|attr| match attr {
+                        ClassAttr::Def(def) => {
+                            let Expr::Def(def) = self.rec_desugar_placeholder(Expr::Def(def))
+                            else {
+                                unreachable!()
+                            };
+                            ClassAttr::Def(def)
+                        }
+                        other => other,
+                    })
+                    .collect::<Vec<_>>();
+                Expr::Methods(Methods::new(
+                    methods.class,
+                    *methods.class_as_expr,
+                    methods.vis,
+                    ClassAttrs::from(attrs),
+                ))
+            }
+            expr => Self::perform_desugar(|ex| self.rec_desugar_placeholder(ex), expr),
+        }
+    }
+
+    fn replace_placeholder(
+        &mut self,
+        expr: Expr,
+        params: &mut Vec<NonDefaultParamSignature>,
+    ) -> Expr {
+        if Self::is_placeholder(&expr) {
+            let line = expr.ln_begin().unwrap_or(1);
+            let col = expr.col_begin().unwrap_or(0);
+            let name = self.var_gen.fresh_varname();
+            let var = VarName::from_str_and_line(Str::rc(&name), line);
+            params.push(NonDefaultParamSignature::new(
+                ParamPattern::VarName(var),
+                None,
+            ));
+            Expr::local(&name, line, col)
+        } else {
+            self.rec_desugar_placeholder(expr)
+        }
+    }
+
+    fn replace_operand_placeholders(
+        &mut self,
+        expr: Expr,
+        params: &mut Vec<NonDefaultParamSignature>,
+    ) -> Expr {
+        match expr {
+            Expr::BinOp(bin) => {
+                let (op, lhs, rhs) = bin.deconstruct();
+                let lhs = self.replace_operand_placeholders(lhs, params);
+                let rhs = self.replace_operand_placeholders(rhs, params);
+                Expr::BinOp(BinOp::new(op, lhs, rhs))
+            }
+            Expr::UnaryOp(unary) => {
+                let (op, expr) = unary.deconstruct();
+                let expr = self.replace_operand_placeholders(expr, params);
+                Expr::UnaryOp(UnaryOp::new(op, expr))
+            }
+            other => self.replace_placeholder(other, params),
+        }
+    }
+
+    fn gen_placeholder_lambda(
+        params: Vec<NonDefaultParamSignature>,
+        body: Expr,
+        is_procedural: bool,
+    ) -> Expr {
+        let op = if is_procedural {
+            Token::from_str(TokenKind::ProcArrow, "=>")
+        } else {
+            Token::from_str(TokenKind::FuncArrow, "->")
+        };
+        let id = DefId(get_hash(&body));
+        let params = Params::new(params, None, vec![], None, None);
+        let sig = LambdaSignature::new(params, None, TypeBoundSpecs::empty());
+        Expr::Lambda(Lambda::new(sig, op, Block::new(vec![body]), id))
+    }
+
     fn desugar_operator(module: Module) -> Module {
         Self::desugar_all_chunks(module, Self::rec_desugar_operator)
     }
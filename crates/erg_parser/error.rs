@@ -63,6 +63,10 @@ impl LexError {
         Self(Box::new(core))
     }
 
+    pub fn core(&self) -> &ErrorCore {
+        &self.0
+    }
+
     pub fn set_hint<S: Into<String>>(&mut self, hint: S) {
         if let Some(sub_msg) = self.0.sub_messages.get_mut(0) {
             sub_msg.set_hint(hint)
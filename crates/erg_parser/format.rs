@@ -0,0 +1,415 @@
+//! implements `Formatter` (`erg fmt`).
+//!
+//! The formatter works on `Cst`, so comments and blank lines are preserved.
+//! Only whitespace is changed (indentation, spaces between tokens, blank lines),
+//! and the result is verified to have the same tokens and `AST` as the original code.
+use std::fs;
+use std::path::Path;
+
+use erg_common::config::{ErgConfig, FormatStyle};
+use erg_common::diff::unified_diff;
+use erg_common::error::{ErrorDisplay, Location, MultiErrorDisplay};
+use erg_common::exit_code;
+use erg_common::io::Input;
+use erg_common::pathutil::expand_targets;
+use erg_common::traits::{DequeStream, ExitStatus, Stream};
+use erg_common::{fn_name, switch_lang};
+
+use crate::cst::{Cst, TriviaKind};
+use crate::error::{ParseError, ParseErrors, ParserRunnerErrors};
+use crate::lex::Lexer;
+use crate::token::{Token, TokenCategory, TokenKind};
+
+use TokenKind::*;
+
+#[derive(Debug, Clone)]
+enum Elem {
+    Token(Token, String),
+    /// `# ...` or `#[ ... ]#`
+    Comment(String),
+    /// `\` + `\n`
+    LineContinuation,
+}
+
+impl Elem {
+    fn text(&self) -> &str {
+        match self {
+            Self::Token(_, raw) => raw,
+            Self::Comment(comment) => comment,
+            Self::LineContinuation => "\\\n",
+        }
+    }
+}
+
+/// A logical line. Multi-line tokens (e.g. strings) and line continuations are included in one line.
+#[derive(Debug, Clone, Default)]
+struct Line {
+    /// the width of the original indentation
+    indent: usize,
+    /// elements and the number of spaces before them
+    elems: Vec<(usize, Elem)>,
+}
+
+impl Line {
+    fn is_blank(&self) -> bool {
+        self.elems.is_empty()
+    }
+
+    fn has_code(&self) -> bool {
+        self.elems
+            .iter()
+            .any(|(_, elem)| matches!(elem, Elem::Token(..)))
+    }
+}
+
+fn lines(cst: Cst) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut line = Line::default();
+    let mut gap = 0;
+    let trivia = cst
+        .tokens
+        .into_iter()
+        .flat_map(|token| {
+            let elem = Elem::Token(token.token, token.raw.to_string());
+            token.leading.into_iter().map(Err).chain([Ok(elem)])
+        })
+        .chain(cst.trailing.into_iter().map(Err));
+    for elem_or_trivia in trivia {
+        let elem = match elem_or_trivia {
+            Ok(elem) => elem,
+            Err(trivia) => match trivia.kind {
+                TriviaKind::Newline => {
+                    lines.push(std::mem::take(&mut line));
+                    gap = 0;
+                    continue;
+                }
+                TriviaKind::Whitespace if line.elems.is_empty() => {
+                    line.indent = trivia.content.len();
+                    continue;
+                }
+                TriviaKind::Whitespace => {
+                    gap = trivia.content.len();
+                    continue;
+                }
+                TriviaKind::Comment | TriviaKind::MultiLineComment => Elem::Comment(trivia.content),
+                TriviaKind::LineContinuation => Elem::LineContinuation,
+            },
+        };
+        line.elems.push((gap, elem));
+        gap = 0;
+    }
+    if !line.is_blank() {
+        lines.push(line);
+    }
+    lines
+}
+
+const fn is_closing(kind: TokenKind) -> bool {
+    matches!(kind, RParen | RSqBr | RBrace)
+}
+
+const fn is_opening(kind: TokenKind) -> bool {
+    matches!(kind, LParen | LSqBr | LBrace)
+}
+
+const fn is_spaced_op(kind: TokenKind) -> bool {
+    matches!(kind, Assign | FuncArrow | ProcArrow | Inclusion)
+}
+
+#[derive(Debug)]
+pub struct Formatter {
+    style: FormatStyle,
+}
+
+impl Default for Formatter {
+    fn default() -> Self {
+        Self::new(FormatStyle::default())
+    }
+}
+
+impl Formatter {
+    pub const fn new(style: FormatStyle) -> Self {
+        Self { style }
+    }
+
+    /// `**` is not spaced (e.g. `x**2 + 1`).
+    /// `spaced_ops`: `false` if the line has range operators (`M+1..N+1` is more readable than `M + 1..N + 1`)
+    fn is_binop(&self, kind: TokenKind, spaced_ops: bool) -> bool {
+        self.style.spaces_around_ops
+            && spaced_ops
+            && kind.category() == TokenCategory::BinOp
+            && !kind.is_range_op()
+            && kind != Pow
+    }
+
+    /// The number of spaces between `prev` and `next`. `gap`: the original number of spaces.
+    fn spacing(&self, prev: &Elem, next: &Elem, gap: usize, spaced_ops: bool) -> usize {
+        let (Elem::Token(prev, _), Elem::Token(next, _)) = (prev, next) else {
+            return match (prev, next) {
+                // keep the indentation of the continued line
+                (Elem::LineContinuation, _) => gap,
+                (_, Elem::Comment(_)) => 1,
+                _ => gap.min(1),
+            };
+        };
+        match (prev.kind, next.kind) {
+            (_, Comma | Semi) => 0,
+            (Comma | Semi, kind) if is_closing(kind) => 0,
+            (Comma | Semi, _) => 1,
+            // both `{ .a = 1 }` and `{.a = 1}` are allowed
+            (LBrace, _) | (_, RBrace) => gap.min(1),
+            (kind, _) if is_opening(kind) => 0,
+            (_, kind) if is_closing(kind) => 0,
+            (_, Colon) => 0,
+            (Colon, _) => 1,
+            (kind, _) | (_, kind) if is_spaced_op(kind) || self.is_binop(kind, spaced_ops) => 1,
+            _ => gap.min(1),
+        }
+    }
+
+    /// The new indentation of each line.
+    fn indents(&self, lines: &[Line]) -> Vec<usize> {
+        let width = self.style.indent_width;
+        let mut indents = vec![0; lines.len()];
+        // the original indentation widths of the enclosing blocks
+        let mut stack = vec![0];
+        // (the index of the line, the stack at the line) of the comment lines before the next code line
+        let mut comments = vec![];
+        for (i, line) in lines.iter().enumerate() {
+            if !line.has_code() {
+                if !line.is_blank() {
+                    comments.push((i, stack.clone()));
+                }
+                continue;
+            }
+            if line.indent > *stack.last().unwrap() {
+                stack.push(line.indent);
+            } else {
+                while stack.len() > 1 && line.indent < *stack.last().unwrap() {
+                    stack.pop();
+                }
+            }
+            indents[i] = (stack.len() - 1) * width;
+            for (j, comment_stack) in comments.drain(..) {
+                indents[j] =
+                    Self::comment_indent(lines[j].indent, &comment_stack, line, indents[i], width);
+            }
+        }
+        for (j, comment_stack) in comments {
+            let level = comment_stack
+                .iter()
+                .rposition(|w| *w <= lines[j].indent)
+                .unwrap_or(0);
+            indents[j] = level * width;
+        }
+        indents
+    }
+
+    /// Comments are aligned with the next code line or the enclosing blocks.
+    fn comment_indent(
+        indent: usize,
+        stack: &[usize],
+        next: &Line,
+        next_indent: usize,
+        width: usize,
+    ) -> usize {
+        let last = *stack.last().unwrap();
+        if indent == next.indent {
+            next_indent
+        } else if let Some(level) = stack.iter().position(|w| *w == indent) {
+            level * width
+        } else if indent > last {
+            if next.indent > last {
+                next_indent
+            } else {
+                stack.len() * width
+            }
+        } else {
+            stack.iter().rposition(|w| *w < indent).unwrap_or(0) * width
+        }
+    }
+
+    fn format_cst(&self, cst: Cst) -> String {
+        let lines = lines(cst);
+        let indents = self.indents(&lines);
+        let mut result = String::new();
+        let mut blanks = 0;
+        for (line, indent) in lines.iter().zip(indents) {
+            if line.is_blank() {
+                blanks += 1;
+                continue;
+            }
+            if !result.is_empty() {
+                for _ in 0..blanks.min(self.style.max_blank_lines) {
+                    result.push('\n');
+                }
+            }
+            blanks = 0;
+            result.push_str(&" ".repeat(indent));
+            let spaced_ops = !line
+                .elems
+                .iter()
+                .any(|(_, elem)| matches!(elem, Elem::Token(token, _) if token.kind.is_range_op()));
+            let mut prev: Option<&Elem> = None;
+            for (gap, elem) in line.elems.iter() {
+                if let Some(prev) = prev {
+                    result.push_str(&" ".repeat(self.spacing(prev, elem, *gap, spaced_ops)));
+                }
+                if let Elem::Comment(comment) = elem {
+                    result.push_str(comment.trim_end());
+                } else {
+                    result.push_str(elem.text());
+                }
+                prev = Some(elem);
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Tokens for comparison; the number of newlines and the width of indentation may be changed by formatting.
+    fn tokens(src: &str) -> Vec<(TokenKind, String)> {
+        let Ok(tokens) = Lexer::from_str(src.to_string()).lex() else {
+            return vec![];
+        };
+        let mut result: Vec<(TokenKind, String)> = vec![];
+        for token in tokens.iter() {
+            match token.kind {
+                Newline if result.last().is_some_and(|(kind, _)| *kind == Newline) => {}
+                Indent => result.push((Indent, String::new())),
+                EOF => {}
+                _ => result.push((token.kind, token.content.to_string())),
+            }
+        }
+        // all blocks are closed at the end of the file
+        while result
+            .last()
+            .is_some_and(|(kind, _)| matches!(kind, Newline | Dedent))
+        {
+            result.pop();
+        }
+        result
+    }
+
+    /// Formats the code. Syntax errors are returned if the code cannot be parsed.
+    pub fn format(&self, src: &str) -> Result<String, ParseErrors> {
+        let cst = Cst::parse(src)?;
+        let original = cst.to_module().map_err(|iart| iart.errors)?;
+        let formatted = self.format_cst(cst);
+        let is_same = match Cst::parse(&formatted).map(|cst| cst.to_module()) {
+            Ok(Ok(artifact)) => {
+                artifact.ast.to_string() == original.ast.to_string()
+                    && Self::tokens(&formatted) == Self::tokens(src)
+            }
+            _ => false,
+        };
+        if !is_same {
+            return Err(ParseErrors::new(vec![ParseError::compiler_bug(
+                line!() as usize,
+                Location::Unknown,
+                fn_name!(),
+                line!(),
+            )]));
+        }
+        Ok(formatted)
+    }
+
+    /// Returns whether the file is changed (or is to be changed if `check` is `true`).
+    fn format_file(&self, path: &Path, check: bool) -> Result<bool, ParserRunnerErrors> {
+        let input = Input::file(path.to_path_buf());
+        let src = fs::read_to_string(path).map_err(|err| {
+            let err = ParseError::syntax_error(
+                line!() as usize,
+                Location::Unknown,
+                switch_lang!(
+                    "japanese" => format!("{}を読み込めません: {err}", path.display()),
+                    "simplified_chinese" => format!("无法读取{}: {err}", path.display()),
+                    "traditional_chinese" => format!("無法讀取{}: {err}", path.display()),
+                    "english" => format!("cannot read {}: {err}", path.display()),
+                ),
+                None,
+            );
+            ParserRunnerErrors::convert(&input, ParseErrors::new(vec![err]))
+        })?;
+        let formatted = self
+            .format(&src)
+            .map_err(|errs| ParserRunnerErrors::convert(&input, errs))?;
+        if formatted == src {
+            return Ok(false);
+        }
+        let name = path.display().to_string();
+        if check {
+            print!(
+                "{}",
+                unified_diff(&src, &formatted, &name, &format!("{name} (formatted)"), 2)
+            );
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("cannot write {name}: {err}");
+        }
+        Ok(true)
+    }
+
+    /// Formats the files in `cfg.targets` (or the package) in place.
+    /// If `cfg.fmt_check` is `true`, the files are not changed, and the diffs are printed instead.
+    pub fn run(mut cfg: ErgConfig) -> ExitStatus {
+        let formatter = Self::new(cfg.fmt_style);
+        if !cfg.input.is_repl() {
+            let src = cfg.input.read();
+            return match formatter.format(&src) {
+                Ok(formatted) if cfg.fmt_check && formatted != src => {
+                    print!(
+                        "{}",
+                        unified_diff(&src, &formatted, "<stdin>", "<stdin> (formatted)", 2)
+                    );
+                    ExitStatus::ERR1
+                }
+                Ok(_) if cfg.fmt_check => ExitStatus::OK,
+                Ok(formatted) => {
+                    print!("{formatted}");
+                    ExitStatus::OK
+                }
+                Err(errs) => {
+                    let errs = ParserRunnerErrors::convert(&cfg.input, errs);
+                    errs.write_all_stderr();
+                    let code = exit_code::of_errors(errs.iter().map(|err| err.core()));
+                    ExitStatus::new(code, 0, errs.len())
+                }
+            };
+        }
+        let targets = expand_targets(&cfg.targets);
+        let (mut changed, mut failed, mut num_errors) = (0, 0, 0);
+        let mut code = exit_code::OK;
+        for path in targets.iter() {
+            match formatter.format_file(path, cfg.fmt_check) {
+                Ok(true) => {
+                    changed += 1;
+                    if !cfg.fmt_check && cfg.verbose > 0 && !cfg.quiet {
+                        println!("formatted: {}", path.display());
+                    }
+                }
+                Ok(false) => {}
+                Err(errs) => {
+                    errs.write_all_stderr();
+                    failed += 1;
+                    num_errors += errs.len();
+                    code = code.max(exit_code::of_errors(errs.iter().map(|err| err.core())));
+                }
+            }
+        }
+        if cfg.verbose > 0 && !cfg.quiet {
+            let unchanged = targets.len() - changed - failed;
+            if cfg.fmt_check {
+                println!(
+                    "{changed} file(s) would be formatted, {unchanged} file(s) already formatted"
+                );
+            } else {
+                println!("{changed} file(s) formatted, {unchanged} file(s) unchanged");
+            }
+        }
+        // the unformatted files are reported like the compile errors
+        if cfg.fmt_check && changed > 0 {
+            code = code.max(exit_code::COMPILE_ERROR);
+        }
+        ExitStatus::new(code, 0, num_errors)
+    }
+}
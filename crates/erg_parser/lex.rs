@@ -6,15 +6,31 @@ use unicode_xid::UnicodeXID;
 
 use erg_common::cache::CacheSet;
 use erg_common::config::ErgConfig;
+use erg_common::error::Location;
+use erg_common::grapheme::{col_to_byte, grapheme_count};
 use erg_common::io::Input;
 use erg_common::traits::DequeStream;
 use erg_common::traits::{Locational, Runnable, Stream};
-use erg_common::{debug_power_assert, fn_name_full, normalize_newline, switch_lang};
+use erg_common::{fn_name_full, normalize_newline, switch_lang, Str};
 
 use crate::error::{LexError, LexErrors, LexResult, LexerRunnerError, LexerRunnerErrors};
 use crate::token::{Token, TokenCategory, TokenKind, TokenStream};
 use TokenKind::*;
 
+/// The byte offsets of the beginning of lines (`\n`, `\r\n` and `\r` are line breaks).
+fn line_starts(src: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    let bytes = src.as_bytes();
+    for (i, b) in bytes.iter().enumerate() {
+        match b {
+            b'\n' => starts.push(i + 1),
+            b'\r' if bytes.get(i + 1) != Some(&b'\n') => starts.push(i + 1),
+            _ => {}
+        }
+    }
+    starts
+}
+
 pub trait Lexable {
     fn lex(code: String) -> Result<TokenStream, LexErrors>;
 }
@@ -110,23 +126,33 @@ impl Runnable for LexerRunner {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interpolation {
     SingleLine,
-    MultiLine(Quote),
+    /// `Some(indent)` if the string is dedented (`d"""..."""`)
+    MultiLine(Quote, Option<usize>),
     Not,
 }
 
 impl Interpolation {
     pub const fn is_in(&self) -> bool {
-        matches!(self, Self::SingleLine | Self::MultiLine(_))
+        matches!(self, Self::SingleLine | Self::MultiLine(..))
     }
 
     pub const fn quote(&self) -> Option<Quote> {
         match self {
-            Self::MultiLine(q) => Some(*q),
+            Self::MultiLine(q, _) => Some(*q),
             _ => None,
         }
     }
 }
 
+/// The prefixes of a string literal
+/// * `r`: raw string (escape sequences and interpolations are not processed)
+/// * `d`: dedented multi-line string (the common indentation of the lines is removed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StrPrefix {
+    pub raw: bool,
+    pub dedent: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Quote {
     Single,
@@ -173,14 +199,19 @@ pub struct Lexer /*<'a>*/ {
     prev_token: Token,
     /// 0-origin, but Token.lineno will 1-origin
     lineno_token_starts: u32,
-    /// 0-origin, indicates the column number in which the token appears
+    /// 0-origin, indicates the column number (in grapheme clusters) in which the token appears
     col_token_starts: u32,
     interpol_stack: Vec<Interpolation>,
+    /// the normalized source (to calculate the byte offsets of tokens)
+    src: String,
+    /// the byte offsets of the beginning of lines
+    line_starts: Vec<usize>,
 }
 
 impl Lexer /*<'a>*/ {
     pub fn new(mut input: Input) -> Self {
-        let normed = normalize_newline(&input.read());
+        let src = input.read();
+        let normed = normalize_newline(&src);
         Lexer {
             str_cache: CacheSet::new(),
             chars: normed.chars().collect::<Vec<char>>(),
@@ -190,6 +221,8 @@ impl Lexer /*<'a>*/ {
             lineno_token_starts: 0,
             col_token_starts: 0,
             interpol_stack: vec![Interpolation::Not],
+            line_starts: line_starts(&src),
+            src,
         }
     }
 
@@ -205,6 +238,8 @@ impl Lexer /*<'a>*/ {
             lineno_token_starts: 0,
             col_token_starts: 0,
             interpol_stack: vec![Interpolation::Not],
+            line_starts: line_starts(&src),
+            src,
         }
     }
 
@@ -226,23 +261,48 @@ impl Lexer /*<'a>*/ {
         }
     }
 
-    fn emit_multiline_token(&mut self, kind: TokenKind, col_begin: u32, cont: &str) -> Token {
+    /// The byte offset of (`lineno`, `col`) in the source.
+    fn byte_offset(&self, lineno: u32, col: u32) -> u32 {
+        let Some(&start) = (lineno as usize)
+            .checked_sub(1)
+            .and_then(|i| self.line_starts.get(i))
+        else {
+            return Location::UNKNOWN_BYTE;
+        };
+        let line = self.src[start..].split(['\r', '\n']).next().unwrap_or("");
+        (start + col_to_byte(line, col as usize)) as u32
+    }
+
+    fn emit_multiline_token(
+        &mut self,
+        kind: TokenKind,
+        lineno: u32,
+        col_begin: u32,
+        cont: &str,
+    ) -> Token {
         let cont = self.str_cache.get(cont);
-        let lineno = (self.lineno_token_starts + 2).saturating_sub(cont.lines().count() as u32);
         // cannot use String::len() for multi-byte characters
-        let cont_len = cont.chars().count();
-        let token = Token::new(kind, cont, lineno, col_begin);
+        let cont_len = grapheme_count(&cont);
+        let byte_begin = self.byte_offset(lineno, col_begin);
+        let token = Token::new(kind, cont, lineno, col_begin).with_byte_begin(byte_begin);
         self.prev_token = token.clone();
         self.col_token_starts += cont_len as u32;
         token
     }
 
     fn emit_token(&mut self, kind: TokenKind, cont: &str) -> Token {
-        let cont = self.str_cache.get(cont);
+        // identifiers are shared among modules
+        let cont = if kind == TokenKind::Symbol {
+            Str::intern(cont)
+        } else {
+            self.str_cache.get(cont)
+        };
         let lineno = (self.lineno_token_starts + 2).saturating_sub(cont.lines().count() as u32);
         // cannot use String::len() for multi-byte characters
-        let cont_len = cont.chars().count();
-        let token = Token::new(kind, cont, lineno, self.col_token_starts);
+        let cont_len = grapheme_count(&cont);
+        let byte_begin = self.byte_offset(lineno, self.col_token_starts);
+        let token =
+            Token::new(kind, cont, lineno, self.col_token_starts).with_byte_begin(byte_begin);
         self.prev_token = token.clone();
         self.col_token_starts += cont_len as u32;
         token
@@ -317,10 +377,14 @@ impl Lexer /*<'a>*/ {
             | TokenCategory::StrInterpLeft
             | TokenCategory::StrInterpMid
             | TokenCategory::BOF => Some(OpFix::Prefix),
+            // `_` can be an operand of an operator section (e.g. `(_ + 1)`)
+            // `x? + 1`: `?` is a postfix operator
             TokenCategory::REnclosure
+            | TokenCategory::PostfixOp
             | TokenCategory::Literal
             | TokenCategory::StrInterpRight
-            | TokenCategory::Symbol => match (self.peek_prev_prev_ch(), self.peek_cur_ch()) {
+            | TokenCategory::Symbol
+            | TokenCategory::UBar => match (self.peek_prev_prev_ch(), self.peek_cur_ch()) {
                 (Some(' '), Some(' ')) => Some(OpFix::Infix), // x + 1: bin
                 (Some(' '), Some(_)) => Some(OpFix::Prefix),  // x +1: unary
                 (Some(_), Some(' ')) => Some(OpFix::Infix),   // x+ 1 : bin
@@ -390,6 +454,52 @@ impl Lexer /*<'a>*/ {
         Ok(())
     }
 
+    /// `#| ...` lines (the consecutive lines starting at the same column are a doc comment)
+    fn lex_line_doc_comment(&mut self) -> LexResult<Token> {
+        let lineno = self.lineno_token_starts + 1;
+        let col_begin = self.col_token_starts;
+        let mut s = "#".to_string();
+        loop {
+            while let Some(c) = self.peek_cur_ch() {
+                if c == '\n' {
+                    break;
+                } else if Self::is_bidi(c) {
+                    let comment = self.emit_multiline_token(Illegal, lineno, col_begin, &s);
+                    return Err(LexError::syntax_error(
+                        line!() as usize,
+                        comment.loc(),
+                        switch_lang!(
+                            "japanese" => "不正なユニコード文字(双方向オーバーライド)がコメント中に使用されています",
+                            "simplified_chinese" => "注释中使用了非法的unicode字符（双向覆盖）",
+                            "traditional_chinese" => "註釋中使用了非法的unicode字符（雙向覆蓋）",
+                            "english" => "invalid unicode character (bi-directional override) in comments",
+                        ),
+                        None,
+                    ));
+                }
+                s.push(self.consume().unwrap());
+            }
+            // continue if the next line is `#| ...` with the same indentation
+            let indent = col_begin as usize;
+            let next_line = self.cursor + 1;
+            let continued = self
+                .chars
+                .get(next_line..next_line + indent)
+                .is_some_and(|spaces| spaces.iter().all(|c| *c == ' '))
+                && self.chars.get(next_line + indent) == Some(&'#')
+                && self.chars.get(next_line + indent + 1) == Some(&'|');
+            if !continued {
+                break;
+            }
+            for _ in 0..=indent {
+                s.push(self.consume().unwrap()); // '\n' and spaces
+            }
+            self.lineno_token_starts += 1;
+            self.col_token_starts = 0;
+        }
+        Ok(self.emit_multiline_token(DocComment, lineno, col_begin, &s))
+    }
+
     fn lex_multi_line_comment(&mut self) -> LexResult<()> {
         let mut s = "".to_string();
         let mut nest_level = 0;
@@ -522,8 +632,8 @@ impl Lexer /*<'a>*/ {
                 ),
             )));
         }
-        // ignore indents if the current line is a comment
-        if let Some('#') = self.peek_cur_ch() {
+        // ignore indents if the current line is a comment (not a doc comment)
+        if self.peek_cur_ch() == Some('#') && self.peek_next_ch() != Some('|') {
             if let Some('[') = self.peek_next_ch() {
                 if let Err(e) = self.lex_multi_line_comment() {
                     return Some(Err(e));
@@ -579,19 +689,66 @@ impl Lexer /*<'a>*/ {
         }
     }
 
+    fn invalid_num_literal_error(&self, token: &Token) -> LexError {
+        LexError::syntax_error(
+            line!() as usize,
+            token.loc(),
+            switch_lang!(
+                "japanese" => format!("`{}`は無効な数値リテラルです", &token.content),
+                "simplified_chinese" => format!("`{}`是无效的数字字面量", &token.content),
+                "traditional_chinese" => format!("`{}`是無效的數字字面量", &token.content),
+                "english" => format!("`{}` is invalid numeric literal", &token.content),
+            ),
+            None,
+        )
+    }
+
+    /// `f` (Float), `i` (Int), `n` (Nat), not followed by other symbol characters (e.g. `1f`, but not `1foo`)
+    fn is_num_suffix(&self, c: char) -> bool {
+        matches!(c, 'f' | 'i' | 'n')
+            && !self
+                .peek_next_ch()
+                .map(Self::is_valid_continue_symbol_ch)
+                .unwrap_or(false)
+    }
+
+    /// `1n`: Nat, `1i`: Int, `1f`/`1.5f`/`1e3f`: Float
+    fn lex_num_suffix(&mut self, mut num: String, is_ratio: bool) -> LexResult<Token> {
+        let suffix = self.consume().unwrap();
+        num.push(suffix);
+        let kind = match suffix {
+            'f' => FloatLit,
+            'i' if !is_ratio => IntLit,
+            'n' if !is_ratio && (!num.starts_with('-') || Self::is_zero(&num[..num.len() - 1])) => {
+                NatLit
+            }
+            _ => {
+                let token = self.emit_token(Illegal, &num);
+                return Err(self.invalid_num_literal_error(&token));
+            }
+        };
+        Ok(self.emit_token(kind, &num))
+    }
+
     fn lex_exponent(&mut self, mantissa: String) -> LexResult<Token> {
         let mut num = mantissa;
-        debug_power_assert!(self.peek_cur_ch(), ==, Some('e'));
-        num.push(self.consume().unwrap()); // e
-        if self.peek_cur_ch().is_some() {
-            num.push(self.consume().unwrap()); // + | -
-            while let Some(cur) = self.peek_cur_ch() {
-                if cur.is_ascii_digit() || cur == '_' {
-                    num.push(self.consume().unwrap());
-                } else {
-                    break;
-                }
+        debug_assert!(matches!(self.peek_cur_ch(), Some('e' | 'E')));
+        num.push(self.consume().unwrap()); // e | E
+        if let Some('+' | '-') = self.peek_cur_ch() {
+            num.push(self.consume().unwrap());
+        }
+        let mut has_digit = false;
+        while let Some(cur) = self.peek_cur_ch() {
+            if cur.is_ascii_digit() || cur == '_' {
+                has_digit |= cur != '_';
+                num.push(self.consume().unwrap());
+            } else if self.is_num_suffix(cur) && has_digit {
+                return self.lex_num_suffix(num, true);
+            } else {
+                break;
             }
+        }
+        if has_digit {
             Ok(self.emit_token(RatioLit, &num))
         } else {
             let token = self.emit_token(RatioLit, &num);
@@ -621,33 +778,33 @@ impl Lexer /*<'a>*/ {
                 n if n.is_ascii_digit() || n == '_' => {
                     num.push(self.consume().unwrap());
                 }
-                'b' | 'B' => {
+                'b' | 'B' if Self::is_radix_prefix(&num) => {
                     num.push(self.consume().unwrap());
-                    return self.lex_bin(num);
+                    return self.lex_radix(num, BinLit, |c| matches!(c, '0' | '1'));
                 }
-                'o' | 'O' => {
+                'o' | 'O' if Self::is_radix_prefix(&num) => {
                     num.push(self.consume().unwrap());
-                    return self.lex_oct(num);
+                    return self.lex_radix(num, OctLit, |c| matches!(c, '0'..='7'));
                 }
-                'x' | 'X' => {
+                'x' | 'X' if Self::is_radix_prefix(&num) => {
                     num.push(self.consume().unwrap());
-                    return self.lex_hex(num);
+                    return self.lex_radix(num, HexLit, |c| c.is_ascii_hexdigit());
+                }
+                // exponent (e.g. 10e+3, 1E6)
+                'e' | 'E' => {
+                    return self.lex_exponent(num);
+                }
+                c if self.is_num_suffix(c) => {
+                    return self.lex_num_suffix(num, false);
                 }
                 c if Self::is_valid_continue_symbol_ch(c) => {
-                    // exponent (e.g. 10e+3)
-                    if c == 'e'
-                        && (self.peek_next_ch() == Some('+') || self.peek_next_ch() == Some('-'))
-                    {
-                        return self.lex_exponent(num);
-                    } else {
-                        // IntLit * Symbol(e.g. 3x + 1)
-                        let token = self.emit_token(Illegal, &(num + &c.to_string()));
-                        return Err(LexError::feature_error(
-                            line!() as usize,
-                            token.loc(),
-                            "*-less multiply",
-                        ));
-                    }
+                    // IntLit * Symbol(e.g. 3x + 1)
+                    let token = self.emit_token(Illegal, &(num + &c.to_string()));
+                    return Err(LexError::feature_error(
+                        line!() as usize,
+                        token.loc(),
+                        "*-less multiply",
+                    ));
                 }
                 _ => {
                     break;
@@ -694,37 +851,50 @@ impl Lexer /*<'a>*/ {
         }
     }
 
-    fn lex_bin(&mut self, mut num: String) -> LexResult<Token> {
-        while let Some(cur) = self.peek_cur_ch() {
-            if cur == '0' || cur == '1' || cur == '_' {
-                num.push(self.consume().unwrap());
-            } else {
-                break;
-            }
-        }
-        Ok(self.emit_token(BinLit, &num))
+    /// `0` or `-0` (e.g. `0` of `0b101`)
+    fn is_radix_prefix(num: &str) -> bool {
+        num == "0" || num == "-0"
     }
 
-    fn lex_oct(&mut self, mut num: String) -> LexResult<Token> {
+    /// Bin/Oct/HexLit (e.g. `0b101`, `0o777`, `0xdead_beef`)
+    /// negative literals (e.g. `-0xff`) are IntLit
+    fn lex_radix(
+        &mut self,
+        mut num: String,
+        kind: TokenKind,
+        is_digit: fn(char) -> bool,
+    ) -> LexResult<Token> {
+        let mut has_digit = false;
         while let Some(cur) = self.peek_cur_ch() {
-            if matches!(cur, '0'..='7') || cur == '_' {
+            if is_digit(cur) || cur == '_' {
+                has_digit |= cur != '_';
                 num.push(self.consume().unwrap());
             } else {
                 break;
             }
         }
-        Ok(self.emit_token(OctLit, &num))
-    }
-
-    fn lex_hex(&mut self, mut num: String) -> LexResult<Token> {
-        while let Some(cur) = self.peek_cur_ch() {
-            if cur.is_ascii_hexdigit() || cur == '_' {
-                num.push(self.consume().unwrap());
-            } else {
-                break;
+        let followed_by_symbol = self
+            .peek_cur_ch()
+            .map(|c| Self::is_valid_continue_symbol_ch(c) && c != '_')
+            .unwrap_or(false);
+        if !has_digit || followed_by_symbol {
+            // e.g. `0b`, `0b102`, `0xfg`
+            while let Some(cur) = self.peek_cur_ch() {
+                if Self::is_valid_continue_symbol_ch(cur) {
+                    num.push(self.consume().unwrap());
+                } else {
+                    break;
+                }
             }
+            let token = self.emit_token(Illegal, &num);
+            return Err(self.invalid_num_literal_error(&token));
         }
-        Ok(self.emit_token(HexLit, &num))
+        let kind = if num.starts_with('-') && !Self::is_zero(&num[3..]) {
+            IntLit
+        } else {
+            kind
+        };
+        Ok(self.emit_token(kind, &num))
     }
 
     /// int_part_and_point must be like `12.`
@@ -733,8 +903,10 @@ impl Lexer /*<'a>*/ {
         while let Some(cur) = self.peek_cur_ch() {
             if cur.is_ascii_digit() || cur == '_' {
                 num.push(self.consume().unwrap());
-            } else if cur == 'e' {
+            } else if cur == 'e' || cur == 'E' {
                 return self.lex_exponent(num);
+            } else if self.is_num_suffix(cur) {
+                return self.lex_num_suffix(num, true);
             } else {
                 break;
             }
@@ -901,7 +1073,10 @@ impl Lexer /*<'a>*/ {
                 _ => {
                     let c = self.consume().unwrap();
                     if c == '\\' {
-                        let next_c = self.consume().unwrap();
+                        // the input ends with a backslash (the string is unclosed)
+                        let Some(next_c) = self.consume() else {
+                            break;
+                        };
                         match next_c {
                             '{' => {
                                 s.push_str("\\{");
@@ -934,16 +1109,89 @@ impl Lexer /*<'a>*/ {
         Err(Self::unclosed_string_error(token, "\"", line!() as usize))
     }
 
-    fn lex_multi_line_str(&mut self, quote: Quote) -> LexResult<Token> {
+    /// Skips at most `indent` spaces at the beginning of a line.
+    fn skip_indent(&mut self, indent: usize) {
+        for _ in 0..indent {
+            if self.peek_cur_ch() != Some(' ') {
+                break;
+            }
+            self.consume();
+        }
+    }
+
+    /// Removes the spaces before the closing quotes if they are on a line by themselves.
+    /// The cursor must point to the first closing quote.
+    fn trim_closing_line(&self, s: &mut String) {
+        let before = self.chars[..self.cursor]
+            .iter()
+            .rev()
+            .find(|c| **c != ' ')
+            .copied();
+        if before == Some('\n') {
+            s.truncate(s.trim_end_matches(' ').len());
+        }
+    }
+
+    /// Calculates the indentation to be removed from the lines of a dedented string.
+    /// The cursor must point to the character right after the opening quotes.
+    ///
+    /// The first line (right after the opening quotes) is not counted.
+    /// Blank lines are not counted, except the line of the closing quotes.
+    fn dedent_width(&self, raw: bool) -> usize {
+        let mut lines = vec![];
+        let mut line = String::new();
+        let mut i = self.cursor;
+        while let Some(&c) = self.chars.get(i) {
+            match c {
+                '\\' if !raw => {
+                    line.push(c);
+                    i += 1;
+                }
+                '"' if self.chars.get(i + 1) == Some(&'"')
+                    && self.chars.get(i + 2) == Some(&'"') =>
+                {
+                    break;
+                }
+                '\n' => {
+                    lines.push(std::mem::take(&mut line));
+                }
+                _ => line.push(c),
+            }
+            i += 1;
+        }
+        let closing = (line.chars().all(|c| c == ' ')).then_some(line.len());
+        lines
+            .iter()
+            .skip(1)
+            .chain((!lines.is_empty()).then_some(&line))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .chain(closing)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// `dedent`: the indentation to be removed from each line (`d"""..."""`)
+    fn lex_multi_line_str(&mut self, quote: Quote, dedent: Option<usize>) -> LexResult<Token> {
+        let lineno = self.lineno_token_starts + 1;
         let col_begin = self.col_token_starts;
         let mut s = quote.quotes().to_string();
+        if let Some(indent) = dedent {
+            // the line break right after the opening quotes is ignored
+            if self.peek_cur_ch() == Some('\n') {
+                self.consume();
+                self.lineno_token_starts += 1;
+                self.col_token_starts = 0;
+                self.skip_indent(indent);
+            }
+        }
         while let Some(c) = self.peek_cur_ch() {
             if c == quote.char() {
                 let c = self.consume().unwrap();
                 let next_c = self.peek_cur_ch();
                 let aft_next_c = self.peek_next_ch();
                 if next_c.is_none() {
-                    let token = self.emit_multiline_token(Illegal, col_begin, &s);
+                    let token = self.emit_multiline_token(Illegal, lineno, col_begin, &s);
                     return Err(Self::unclosed_string_error(
                         token,
                         quote.quotes(),
@@ -952,7 +1200,7 @@ impl Lexer /*<'a>*/ {
                 }
                 if aft_next_c.is_none() {
                     s.push(self.consume().unwrap());
-                    let token = self.emit_multiline_token(Illegal, col_begin, &s);
+                    let token = self.emit_multiline_token(Illegal, lineno, col_begin, &s);
                     return Err(Self::unclosed_string_error(
                         token,
                         quote.quotes(),
@@ -960,10 +1208,14 @@ impl Lexer /*<'a>*/ {
                     ));
                 }
                 if next_c.unwrap() == quote.char() && aft_next_c.unwrap() == quote.char() {
+                    if dedent.is_some() {
+                        self.trim_closing_line(&mut s);
+                    }
                     self.consume().unwrap();
                     self.consume().unwrap();
                     s.push_str(quote.quotes());
-                    let token = self.emit_multiline_token(quote.token_kind(), col_begin, &s);
+                    let token =
+                        self.emit_multiline_token(quote.token_kind(), lineno, col_begin, &s);
                     return Ok(token);
                 }
                 // else unclosed_string_error
@@ -972,12 +1224,16 @@ impl Lexer /*<'a>*/ {
                 let c = self.consume().unwrap();
                 match c {
                     '\\' => {
-                        let next_c = self.consume().unwrap();
+                        let Some(next_c) = self.consume() else {
+                            break;
+                        };
                         match next_c {
                             '{' => {
                                 s.push_str("\\{");
-                                self.interpol_stack.push(Interpolation::MultiLine(quote));
-                                let token = self.emit_multiline_token(StrInterpLeft, col_begin, &s);
+                                self.interpol_stack
+                                    .push(Interpolation::MultiLine(quote, dedent));
+                                let token =
+                                    self.emit_multiline_token(StrInterpLeft, lineno, col_begin, &s);
                                 return Ok(token);
                             }
                             '0' => s.push('\0'),
@@ -995,6 +1251,7 @@ impl Lexer /*<'a>*/ {
                             _ => {
                                 let token = self.emit_multiline_token(
                                     Illegal,
+                                    lineno,
                                     col_begin,
                                     &format!("\\{next_c}"),
                                 );
@@ -1006,6 +1263,9 @@ impl Lexer /*<'a>*/ {
                         self.lineno_token_starts += 1;
                         self.col_token_starts = 0;
                         s.push('\n');
+                        if let Some(indent) = dedent {
+                            self.skip_indent(indent);
+                        }
                     }
                     _ => {
                         s.push(c);
@@ -1034,11 +1294,14 @@ impl Lexer /*<'a>*/ {
         while let Some(c) = self.peek_cur_ch() {
             match c {
                 '\n' => match self.interpol_stack.last().unwrap() {
-                    Interpolation::MultiLine(_) => {
+                    &Interpolation::MultiLine(_, dedent) => {
                         self.lineno_token_starts += 1;
                         self.col_token_starts = 0;
                         self.consume().unwrap();
                         s.push('\n');
+                        if let Some(indent) = dedent {
+                            self.skip_indent(indent);
+                        }
                     }
                     Interpolation::SingleLine => {
                         if self.peek_next_ch().is_some() {
@@ -1055,9 +1318,16 @@ impl Lexer /*<'a>*/ {
                     }
                 },
                 '"' => {
+                    if let Some(Interpolation::MultiLine(_, Some(_))) = self.interpol_stack.last() {
+                        if self.peek_next_ch() == Some('"')
+                            && self.chars.get(self.cursor + 2) == Some(&'"')
+                        {
+                            self.trim_closing_line(&mut s);
+                        }
+                    }
                     let c = self.consume().unwrap();
                     match self.interpol_stack.last().copied().unwrap() {
-                        Interpolation::MultiLine(quote) => {
+                        Interpolation::MultiLine(quote, _) => {
                             let next_c = self.peek_cur_ch();
                             let aft_next_c = self.peek_next_ch();
                             if next_c.is_none() {
@@ -1104,7 +1374,10 @@ impl Lexer /*<'a>*/ {
                 _ => {
                     let c = self.consume().unwrap();
                     if c == '\\' {
-                        let next_c = self.consume().unwrap();
+                        // the input ends with a backslash (the string is unclosed)
+                        let Some(next_c) = self.consume() else {
+                            break;
+                        };
                         match next_c {
                             '{' => {
                                 s.push_str("\\{");
@@ -1136,6 +1409,113 @@ impl Lexer /*<'a>*/ {
         Err(Self::unclosed_string_error(token, "", line!() as usize))
     }
 
+    /// Returns the prefix if `first_ch` and the following characters are a string prefix (e.g. `r"`, `d"""`).
+    fn str_prefix(&self, first_ch: char) -> Option<StrPrefix> {
+        let is_quote = |i: usize| self.chars.get(self.cursor + i) == Some(&'"');
+        let is_triple_quote = |i: usize| is_quote(i) && is_quote(i + 1) && is_quote(i + 2);
+        let prefix = |c: char| StrPrefix {
+            raw: c == 'r',
+            dedent: c == 'd',
+        };
+        match (first_ch, self.peek_cur_ch()) {
+            ('r', Some('"')) => Some(prefix('r')),
+            ('d', Some('"')) if is_triple_quote(0) => Some(prefix('d')),
+            ('r', Some('d')) | ('d', Some('r')) if is_triple_quote(1) => Some(StrPrefix {
+                raw: true,
+                dedent: true,
+            }),
+            _ => None,
+        }
+    }
+
+    /// e.g. `r"\d+"`, `d"""..."""`, `rd"""..."""`
+    fn lex_prefixed_str(&mut self, prefix: StrPrefix) -> LexResult<Token> {
+        if self.peek_cur_ch() != Some('"') {
+            self.consume(); // consume the second prefix
+        }
+        self.consume(); // consume '"'
+        let multi_line = self.peek_cur_ch() == Some('"') && self.peek_next_ch() == Some('"');
+        if multi_line {
+            self.consume();
+            self.consume();
+        } else if self.peek_cur_ch() == Some('"') {
+            // r""
+            self.consume();
+            return Ok(self.emit_token(StrLit, "\"\""));
+        }
+        let dedent = prefix.dedent.then(|| self.dedent_width(prefix.raw));
+        if prefix.raw {
+            self.lex_raw_str(multi_line, dedent)
+        } else {
+            self.lex_multi_line_str(Quote::Double, dedent)
+        }
+    }
+
+    /// Escape sequences are not processed, but `\"` does not close the string (the backslash remains).
+    fn lex_raw_str(&mut self, multi_line: bool, dedent: Option<usize>) -> LexResult<Token> {
+        let lineno = self.lineno_token_starts + 1;
+        let col_begin = self.col_token_starts;
+        let quotes = if multi_line { "\"\"\"" } else { "\"" };
+        let mut s = quotes.to_string();
+        if let Some(indent) = dedent {
+            if self.peek_cur_ch() == Some('\n') {
+                self.consume();
+                self.lineno_token_starts += 1;
+                self.col_token_starts = 0;
+                self.skip_indent(indent);
+            }
+        }
+        while let Some(c) = self.peek_cur_ch() {
+            let closed = if multi_line {
+                c == '"'
+                    && self.peek_next_ch() == Some('"')
+                    && self.chars.get(self.cursor + 2) == Some(&'"')
+            } else {
+                c == '"'
+            };
+            if closed {
+                if dedent.is_some() {
+                    self.trim_closing_line(&mut s);
+                }
+                for _ in 0..quotes.len() {
+                    self.consume();
+                }
+                s.push_str(quotes);
+                return Ok(self.emit_multiline_token(StrLit, lineno, col_begin, &s));
+            }
+            let c = self.consume().unwrap();
+            match c {
+                '\\' => {
+                    s.push(c);
+                    if let Some(next_c @ ('"' | '\\')) = self.peek_cur_ch() {
+                        s.push(next_c);
+                        self.consume();
+                    }
+                }
+                '\n' if !multi_line => {
+                    let token = self.emit_token(Illegal, &s);
+                    return Err(Self::str_line_break_error(token, line!() as usize));
+                }
+                '\n' => {
+                    self.lineno_token_starts += 1;
+                    self.col_token_starts = 0;
+                    s.push('\n');
+                    if let Some(indent) = dedent {
+                        self.skip_indent(indent);
+                    }
+                }
+                _ => {
+                    s.push(c);
+                    if Self::is_bidi(c) {
+                        return Err(self.invalid_unicode_character(&s));
+                    }
+                }
+            }
+        }
+        let token = self.emit_multiline_token(Illegal, lineno, col_begin, &s);
+        Err(Self::unclosed_string_error(token, quotes, line!() as usize))
+    }
+
     fn lex_raw_ident(&mut self) -> LexResult<Token> {
         let mut s = "\'".to_string();
         while let Some(c) = self.peek_cur_ch() {
@@ -1187,7 +1567,7 @@ impl Iterator for Lexer /*<'a>*/ {
         if indent_dedent.is_some() {
             return indent_dedent;
         }
-        if let Some('#') = self.peek_cur_ch() {
+        if self.peek_cur_ch() == Some('#') && self.peek_next_ch() != Some('|') {
             if let Some('[') = self.peek_next_ch() {
                 if let Err(e) = self.lex_multi_line_comment() {
                     return Some(Err(e));
@@ -1197,6 +1577,7 @@ impl Iterator for Lexer /*<'a>*/ {
             }
         }
         match self.consume() {
+            Some('#') if self.peek_cur_ch() == Some('|') => Some(self.lex_line_doc_comment()),
             Some('(') => self.accept(LParen, "("),
             Some(')') => self.accept(RParen, ")"),
             Some('[') => self.accept(LSqBr, "["),
@@ -1398,7 +1779,21 @@ impl Iterator for Lexer /*<'a>*/ {
                 // TODO: infix/prefix
                 Some('*') => {
                     self.consume();
-                    self.accept(Pow, "**")
+                    // the first `*` has already been consumed, so look back one more character
+                    let spaced_before = self
+                        .cursor
+                        .checked_sub(3)
+                        .and_then(|i| self.chars.get(i))
+                        .is_some_and(|c| *c == ' ');
+                    let kind = match self.op_fix() {
+                        Some(OpFix::Prefix) => PreDblStar,
+                        // `f **kwargs`
+                        Some(OpFix::Infix) if spaced_before && self.peek_cur_ch() != Some(' ') => {
+                            PreDblStar
+                        }
+                        _ => Pow,
+                    };
+                    self.accept(kind, "**")
                 }
                 _ => {
                     let kind = match self.op_fix() {
@@ -1502,7 +1897,7 @@ impl Iterator for Lexer /*<'a>*/ {
                     (Some('"'), Some('"')) => {
                         self.consume(); // consume second '"'
                         self.consume(); // consume third '"'
-                        Some(self.lex_multi_line_str(Quote::Double))
+                        Some(self.lex_multi_line_str(Quote::Double, None))
                     }
                     (Some('"'), None) => {
                         self.consume(); // consume second '"'
@@ -1533,7 +1928,7 @@ impl Iterator for Lexer /*<'a>*/ {
                     (Some('\''), Some('\'')) => {
                         self.consume(); // consume second '
                         self.consume(); // consume third '
-                        Some(self.lex_multi_line_str(Quote::Single))
+                        Some(self.lex_multi_line_str(Quote::Single, None))
                     }
                     (Some('\''), _) => {
                         self.consume(); // consume second '\''
@@ -1595,6 +1990,11 @@ impl Iterator for Lexer /*<'a>*/ {
             }
             // IntLit (or Bin/Oct/Hex) or RatioLit
             Some(n) if n.is_ascii_digit() => Some(self.lex_num(n)),
+            // Raw StrLit or dedented multi-line StrLit (e.g. `r"\d+"`, `d"""..."""`)
+            Some(c @ ('r' | 'd')) if self.str_prefix(c).is_some() => {
+                let prefix = self.str_prefix(c).unwrap();
+                Some(self.lex_prefixed_str(prefix))
+            }
             // Symbol (includes '_')
             Some(c) if Self::is_valid_start_symbol_ch(c) => Some(self.lex_symbol(c)),
             // Invalid character (e.g. space-like character)
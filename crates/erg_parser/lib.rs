@@ -7,11 +7,14 @@ extern crate erg_common;
 pub mod ast;
 pub mod build_ast;
 pub mod convert;
+pub mod cst;
 pub mod desugar;
 pub mod error;
+pub mod format;
 pub mod lex;
 pub mod parse;
 pub mod token;
 pub mod typespec;
+pub mod visitor;
 
 pub use parse::{Parser, ParserRunner};
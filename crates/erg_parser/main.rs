@@ -8,6 +8,7 @@ use erg_common::spawn::exec_new_thread;
 use erg_common::traits::{ExitStatus, Runnable};
 
 use erg_parser::build_ast::ASTBuilder;
+use erg_parser::format::Formatter;
 use erg_parser::lex::LexerRunner;
 use erg_parser::ParserRunner;
 
@@ -17,6 +18,7 @@ fn run() {
         Lex => LexerRunner::run(cfg),
         Parse => ParserRunner::run(cfg),
         Desugar | Execute => ASTBuilder::run(cfg),
+        Format => Formatter::run(cfg),
         other => {
             eprintln!("invalid mode: {other}");
             ExitStatus::ERR1
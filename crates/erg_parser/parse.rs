@@ -5,9 +5,10 @@
 use std::mem;
 
 use erg_common::config::ErgConfig;
-use erg_common::error::Location;
+use erg_common::error::{ErrorCore, ErrorKind, Location};
 use erg_common::io::{Input, InputKind};
 use erg_common::set::Set as HashSet;
+use erg_common::spawn::is_stack_exhausted;
 use erg_common::str::Str;
 use erg_common::traits::{DequeStream, ExitStatus, Locational, Runnable, Stream};
 use erg_common::{
@@ -23,14 +24,21 @@ use crate::error::{
 };
 use crate::lex::Lexer;
 use crate::token::{Token, TokenCategory, TokenKind, TokenStream};
+use crate::visitor::{walk_expr, Visitor};
 
 use TokenCategory as TC;
 use TokenKind::*;
 
 #[macro_export]
 /// Display the name of the called function for debugging the parser
+/// (and fail if the stack is nearly exhausted, see `Parser::check_stack`)
 macro_rules! debug_call_info {
     ($self: ident) => {
+        $self.check_stack()?;
+        $crate::debug_call_info!(unchecked $self);
+    };
+    // for the methods not returning `ParseResult`
+    (unchecked $self: ident) => {
         $self.level += 1;
         log!(
             c DEBUG_MAIN,
@@ -125,6 +133,7 @@ enum ArgKind {
     Pos(PosArg),
     Var(PosArg),
     Kw(KwArg),
+    KwVar(PosArg),
 }
 
 pub enum ArrayInner {
@@ -190,6 +199,8 @@ pub struct Parser {
     counter: DefId,
     pub(super) level: usize, // nest level (for debugging)
     tokens: TokenStream,
+    /// the kind and the location of the last consumed token (for error recovery)
+    prev: Option<(TokenKind, Location)>,
     warns: ParseErrors,
     pub(crate) errs: ParseErrors,
 }
@@ -200,6 +211,7 @@ impl Parser {
             counter: DefId(0),
             level: 0,
             tokens: ts,
+            prev: None,
             warns: ParseErrors::empty(),
             errs: ParseErrors::empty(),
         }
@@ -221,12 +233,16 @@ impl Parser {
 
     #[inline]
     fn skip(&mut self) {
-        self.tokens.pop_front();
+        if let Some(t) = self.tokens.pop_front() {
+            self.prev = Some((t.kind, t.loc()));
+        }
     }
 
     #[inline]
     fn lpop(&mut self) -> Token {
-        self.tokens.pop_front().unwrap()
+        let t = self.tokens.pop_front().unwrap();
+        self.prev = Some((t.kind, t.loc()));
+        t
     }
 
     fn cur_category_is(&self, category: TokenCategory) -> bool {
@@ -300,6 +316,48 @@ impl Parser {
         }
     }
 
+    /// 解析に失敗した文を読み飛ばし、同じブロックの次の文の先頭に移動する
+    /// skip the statement that failed to parse and move to the beginning of the next statement of the same block
+    /// (nested blocks are skipped, and the dedent of the current block is not consumed)
+    fn next_stmt(&mut self) {
+        let mut nest_cnt = 0;
+        while let Some(t) = self.peek() {
+            match t.kind {
+                Newline | Semi if nest_cnt == 0 => {
+                    self.skip();
+                    return;
+                }
+                Indent => {
+                    self.skip();
+                    nest_cnt += 1;
+                }
+                Dedent if nest_cnt == 0 => return,
+                Dedent => {
+                    self.skip();
+                    nest_cnt -= 1;
+                }
+                EOF => return,
+                _ => {
+                    self.skip();
+                }
+            }
+        }
+    }
+
+    /// Resynchronizes at the next statement boundary after `try_reduce_chunk` failed,
+    /// and returns the recovery node that covers the skipped statement.
+    /// `start` is the first token of the statement, and `len` is the number of the remaining tokens at that time.
+    fn recover_chunk(&mut self, start: Location, len: usize) -> Expr {
+        if self.tokens.len() == len || !matches!(self.prev, Some((Newline | Semi | Dedent, _))) {
+            self.next_stmt();
+        }
+        let loc = match self.prev {
+            Some((_, end)) if self.tokens.len() < len => Location::concat(&start, &end),
+            _ => start,
+        };
+        Expr::Dummy(Dummy::new(Some(loc), vec![]))
+    }
+
     fn unexpected_none(&self, errno: u32, caused_by: &str) -> ParseError {
         log!(err "error caused by: {caused_by}");
         ParseError::invalid_none_match(0, Location::Unknown, file!(), errno)
@@ -375,6 +433,26 @@ impl Parser {
         self.tokens.push_front(token);
     }
 
+    /// Deeply nested code is reported as an error instead of overflowing the stack.
+    pub(crate) fn check_stack(&mut self) -> ParseResult<()> {
+        if !is_stack_exhausted() {
+            return Ok(());
+        }
+        // report only once
+        if !self
+            .errs
+            .iter()
+            .any(|err| err.core().kind == ErrorKind::RecursionError)
+        {
+            let loc = self.peek().map_or(Location::Unknown, |tok| tok.loc());
+            self.errs.push(ParseError::new(ErrorCore::too_deeply_nested(
+                line!() as usize,
+                loc,
+            )));
+        }
+        Err(())
+    }
+
     pub(crate) fn stack_dec(&mut self, fn_name: &str) {
         self.level -= 1;
         log!(
@@ -451,12 +529,54 @@ impl ParserRunner {
         let ts = Lexer::new(Input::new(InputKind::Str(src), self.cfg.input.id()))
             .lex()
             .map_err(|errs| ParserRunnerErrors::convert(self.input(), errs))?;
+        if self.cfg.cancel.is_cancelled() {
+            let err = ParserRunnerError::new(
+                ErrorCore::cancelled(line!() as usize),
+                self.input().clone(),
+            );
+            return Err(ParserRunnerErrors::new(vec![err]).into());
+        }
         Parser::new(ts)
             .parse()
             .map_err(|iart| iart.map_errs(|errs| ParserRunnerErrors::convert(self.input(), errs)))
     }
 }
 
+/// The expressions nested deeper than this are rejected.
+/// The parser itself checks the stack, but long chains of operators or method calls (`1 + 1 + ...`) are parsed iteratively,
+/// and the later passes (desugaring, lowering, etc.) recurse more deeply than the parser.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+/// Finds the first expression nested deeper than `MAX_NESTING_DEPTH`.
+#[derive(Debug, Default)]
+struct NestingChecker {
+    depth: usize,
+    too_deep: Option<Location>,
+}
+
+impl NestingChecker {
+    fn check(module: &Module) -> Option<Location> {
+        let mut checker = Self::default();
+        checker.visit_module(module);
+        checker.too_deep
+    }
+}
+
+impl<'a> Visitor<'a> for NestingChecker {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if self.too_deep.is_some() {
+            return;
+        }
+        if self.depth >= MAX_NESTING_DEPTH {
+            self.too_deep = Some(expr.loc());
+            return;
+        }
+        self.depth += 1;
+        walk_expr(self, expr);
+        self.depth -= 1;
+    }
+}
+
 impl Parser {
     pub fn parse(&mut self) -> Result<CompleteArtifact, IncompleteArtifact> {
         if self.tokens.is_empty() {
@@ -484,6 +604,18 @@ impl Parser {
                 mem::take(&mut self.errs),
             ));
         }
+        // the later passes cannot handle the module, so it is discarded
+        if let Some(loc) = NestingChecker::check(&module) {
+            self.errs.push(ParseError::new(ErrorCore::too_deeply_nested(
+                line!() as usize,
+                loc,
+            )));
+            return Err(IncompleteArtifact::new(
+                None,
+                mem::take(&mut self.warns),
+                mem::take(&mut self.errs),
+            ));
+        }
         log!(info "the parsing process has completed (errs: {}).", self.errs.len());
         log!(info "AST:\n{module}");
         if self.errs.is_empty() {
@@ -511,16 +643,28 @@ impl Parser {
                     break;
                 }
                 Some(_) => {
-                    if let Ok(expr) = self.try_reduce_chunk(true, false) {
-                        if !self.cur_is(EOF) && !self.cur_category_is(TC::Separator) {
-                            let err = self.skip_and_throw_invalid_chunk_err(
-                                caused_by!(),
-                                line!(),
-                                expr.loc(),
-                            );
-                            self.errs.push(err);
+                    let start = self.peek().map(|t| t.loc()).unwrap_or_default();
+                    let len = self.tokens.len();
+                    match self.try_reduce_chunk(true, false) {
+                        Ok(expr) => {
+                            if !self.cur_is(EOF) && !self.cur_category_is(TC::Separator) {
+                                let err = self.skip_and_throw_invalid_chunk_err(
+                                    caused_by!(),
+                                    line!(),
+                                    expr.loc(),
+                                );
+                                self.errs.push(err);
+                            }
+                            chunks.push(expr);
+                        }
+                        Err(_) => {
+                            let err_node = self.recover_chunk(start, len);
+                            // the dedents of the nested blocks that failed to parse
+                            while self.cur_is(Dedent) {
+                                self.skip();
+                            }
+                            chunks.push(err_node);
                         }
-                        chunks.push(expr);
                     }
                 }
                 None => {
@@ -535,11 +679,53 @@ impl Parser {
             }
         }
         debug_exit_info!(self);
-        Ok(chunks)
+        Ok(Module::new(Self::attach_doc_comments(chunks.payload())))
+    }
+
+    /// Attaches the doc comments to the definition right below them.
+    /// The doc comments separated from the definition by blank lines remain as they are.
+    fn attach_doc_comments(chunks: Vec<Expr>) -> Vec<Expr> {
+        let mut attached = Vec::with_capacity(chunks.len());
+        let mut docs: Vec<Literal> = vec![];
+        for chunk in chunks.into_iter() {
+            let next_line = docs.last().map(|doc| doc.ln_last() + 1);
+            match chunk {
+                Expr::Literal(lit) if lit.is_doc_comment() => {
+                    if next_line.is_some_and(|ln| lit.ln_begin() != Some(ln)) {
+                        attached.extend(docs.drain(..).map(Expr::Literal));
+                    }
+                    docs.push(lit);
+                }
+                Expr::Def(def) if next_line.is_some() && Self::def_ln_begin(&def) == next_line => {
+                    let doc = crate::ast::DocComment::new(mem::take(&mut docs));
+                    attached.push(Expr::Def(def.with_doc(Some(doc))));
+                }
+                other => {
+                    attached.extend(docs.drain(..).map(Expr::Literal));
+                    attached.push(other);
+                }
+            }
+        }
+        attached.extend(docs.into_iter().map(Expr::Literal));
+        attached
+    }
+
+    /// The first line of the definition including the decorators
+    fn def_ln_begin(def: &Def) -> Option<u32> {
+        def.sig
+            .decorators()
+            .into_iter()
+            .flat_map(|decos| decos.iter())
+            .filter_map(|deco| deco.0.ln_begin())
+            .chain(def.ln_begin())
+            .min()
     }
 
     // expect the block`= ; . -> =>`
-    fn try_reduce_block(&mut self) -> ParseResult<Block> {
+    /// If `is_lambda_body` is true, the block may end with a definition,
+    /// which may initialize a variable declared outside the lambda (e.g. a branch of `if!`).
+    /// Whether the definition is valid is checked by the compiler.
+    fn try_reduce_block(&mut self, is_lambda_body: bool) -> ParseResult<Block> {
         debug_call_info!(self);
         let mut block = Block::with_capacity(2);
         // single line block
@@ -560,7 +746,7 @@ impl Parser {
                 debug_exit_info!(self);
                 self.errs.push(err);
             }
-            if block.last().unwrap().is_definition() {
+            if !is_lambda_body && block.last().unwrap().is_definition() {
                 let err = ParseError::invalid_definition_of_last_block(
                     line!() as usize,
                     block.last().unwrap().loc(),
@@ -598,17 +784,25 @@ impl Parser {
                     break;
                 }
                 Some(_) => {
-                    if let Ok(expr) = self.try_reduce_chunk(true, false) {
-                        if !self.cur_is(Dedent) && !self.cur_category_is(TC::Separator) {
-                            let err = self.skip_and_throw_invalid_chunk_err(
-                                caused_by!(),
-                                line!(),
-                                expr.loc(),
-                            );
-                            debug_exit_info!(self);
-                            self.errs.push(err);
+                    let start = self.peek().map(|t| t.loc()).unwrap_or_default();
+                    let len = self.tokens.len();
+                    match self.try_reduce_chunk(true, false) {
+                        Ok(expr) => {
+                            if !self.cur_is(Dedent) && !self.cur_category_is(TC::Separator) {
+                                let err = self.skip_and_throw_invalid_chunk_err(
+                                    caused_by!(),
+                                    line!(),
+                                    expr.loc(),
+                                );
+                                debug_exit_info!(self);
+                                self.errs.push(err);
+                            }
+                            block.push(expr);
+                        }
+                        Err(_) => {
+                            let err_node = self.recover_chunk(start, len);
+                            block.push(err_node);
                         }
-                        block.push(expr);
                     }
                 }
                 None => {
@@ -619,6 +813,7 @@ impl Parser {
                 }
             }
         }
+        let block = Block::new(Self::attach_doc_comments(block.payload()));
         if block.is_empty() {
             let loc = if let Some(u) = self.peek() {
                 u.loc()
@@ -629,7 +824,7 @@ impl Parser {
             self.errs.push(err);
             debug_exit_info!(self);
             Err(())
-        } else if block.last().unwrap().is_definition() {
+        } else if !is_lambda_body && block.last().unwrap().is_definition() {
             let err =
                 ParseError::invalid_chunk_error(line!() as usize, block.last().unwrap().loc());
             self.errs.push(err);
@@ -828,14 +1023,16 @@ impl Parser {
                 return Ok(ArrayInner::WithLength(elems.remove_pos(0), len));
             }
             Some(VBar) => {
-                let caused_by = caused_by!();
-                log!(err "error caused by: {caused_by}");
-                let err =
-                    ParseError::feature_error(line!() as usize, self.lpop().loc(), "comprehension");
-                self.lpop();
-                self.errs.push(err);
+                self.skip();
+                let (generators, guards) = self
+                    .try_reduce_comprehension_clauses("]")
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
                 debug_exit_info!(self);
-                return Err(());
+                return Ok(ArrayInner::Comprehension {
+                    elem: elems.remove_pos(0),
+                    generators,
+                    guards,
+                });
             }
             Some(RParen | RSqBr | RBrace | Dedent | Comma) => {}
             Some(_) => {
@@ -914,7 +1111,7 @@ impl Parser {
     }
 
     fn opt_reduce_args(&mut self, in_type_args: bool) -> Option<ParseResult<Args>> {
-        debug_call_info!(self);
+        debug_call_info!(unchecked self);
         match self.peek() {
             Some(t)
                 if t.category_is(TC::Literal)
@@ -984,8 +1181,9 @@ impl Parser {
             .map_err(|_| self.stack_dec(fn_name!()))?
         {
             ArgKind::Pos(arg) => Args::single(arg),
-            ArgKind::Var(arg) => Args::new(vec![], Some(arg), vec![], None),
-            ArgKind::Kw(arg) => Args::new(vec![], None, vec![arg], None),
+            ArgKind::Var(arg) => Args::new(vec![], Some(arg), vec![], None, None),
+            ArgKind::Kw(arg) => Args::new(vec![], None, vec![arg], None, None),
+            ArgKind::KwVar(arg) => Args::new(vec![], None, vec![], Some(arg), None),
         };
         loop {
             match self.peek_kind() {
@@ -1029,7 +1227,7 @@ impl Parser {
                         args.set_parens((lp.unwrap(), rp));
                         break;
                     }
-                    if !args.kw_is_empty() {
+                    if !args.kw_is_empty() && !self.cur_is(PreDblStar) {
                         args.push_kw(
                             self.try_reduce_kw_arg(in_type_args)
                                 .map_err(|_| self.stack_dec(fn_name!()))?,
@@ -1048,6 +1246,9 @@ impl Parser {
                             ArgKind::Kw(arg) => {
                                 args.push_kw(arg);
                             }
+                            ArgKind::KwVar(arg) => {
+                                args.set_kw_var_args(arg);
+                            }
                         }
                     }
                 }
@@ -1057,8 +1258,8 @@ impl Parser {
                         args.set_parens((lp, rp));
                     } else {
                         // e.g. f(g 1)
-                        let (pos_args, var_args, kw_args, _) = args.deconstruct();
-                        args = Args::new(pos_args, var_args, kw_args, None);
+                        let (pos_args, var_args, kw_args, kw_var_args, _) = args.deconstruct();
+                        args = Args::new(pos_args, var_args, kw_args, kw_var_args, None);
                     }
                     break;
                 }
@@ -1097,7 +1298,7 @@ impl Parser {
                     break;
                 }
                 Some(_) if style.is_colon() => {
-                    if !args.kw_is_empty() {
+                    if !args.kw_is_empty() && !self.cur_is(PreDblStar) {
                         args.push_kw(
                             self.try_reduce_kw_arg(in_type_args)
                                 .map_err(|_| self.stack_dec(fn_name!()))?,
@@ -1116,6 +1317,9 @@ impl Parser {
                             ArgKind::Kw(arg) => {
                                 args.push_kw(arg);
                             }
+                            ArgKind::KwVar(arg) => {
+                                args.set_kw_var_args(arg);
+                            }
                         }
                     }
                 }
@@ -1249,6 +1453,24 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(ArgKind::Var(PosArg::new(expr)))
             }
+            Some(PreDblStar) => {
+                self.skip();
+                let expr = self
+                    .try_reduce_expr(false, in_type_args, false, false)
+                    .map_err(|_| {
+                        if let Some(err) = self.errs.last_mut() {
+                            err.set_hint(switch_lang!(
+                                "japanese" => "期待: 可変長キーワード引数",
+                                "simplified_chinese" => "期望: 可变长度关键字参数",
+                                "traditional_chinese" => "期望: 可變長度關鍵字參數",
+                                "english" => "expect: variable-length keyword arguments",
+                            ))
+                        }
+                        self.stack_dec(fn_name!())
+                    })?;
+                debug_exit_info!(self);
+                Ok(ArgKind::KwVar(PosArg::new(expr)))
+            }
             Some(_) => {
                 let expr = self
                     .try_reduce_expr(false, in_type_args, false, false)
@@ -1516,6 +1738,20 @@ impl Parser {
                 }
             }
         }
+        let attrs = attrs.into_iter().map(|attr| match attr {
+            ClassAttr::Def(def) => Expr::Def(def),
+            ClassAttr::Decl(decl) => Expr::TypeAscription(decl),
+            ClassAttr::Doc(doc) => Expr::Literal(doc),
+        });
+        let attrs = Self::attach_doc_comments(attrs.collect())
+            .into_iter()
+            .map(|attr| match attr {
+                Expr::Def(def) => ClassAttr::Def(def),
+                Expr::TypeAscription(decl) => ClassAttr::Decl(decl),
+                Expr::Literal(doc) => ClassAttr::Doc(doc),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
         let attrs = ClassAttrs::from(attrs);
         let t_spec = Self::expr_to_type_spec(class.clone()).map_err(|e| {
             self.errs.push(e);
@@ -1537,7 +1773,7 @@ impl Parser {
         if self.cur_is(Colon) {
             self.lpop();
             let body = self
-                .try_reduce_block()
+                .try_reduce_block(true)
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             self.counter.inc();
             debug_exit_info!(self);
@@ -1588,7 +1824,7 @@ impl Parser {
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     self.counter.inc();
                     let block = if is_multiline_block {
-                        self.try_reduce_block()
+                        self.try_reduce_block(false)
                             .map_err(|_| self.stack_dec(fn_name!()))?
                     } else {
                         // precedence: `=` < `,`
@@ -1614,13 +1850,17 @@ impl Parser {
                 Some(op) if op.category_is(TC::LambdaOp) => {
                     let op = self.lpop();
                     let is_multiline_block = self.cur_is(Newline);
+                    // `1 or 2 -> ...` == `(1 or 2) -> ...`
+                    while stack.len() >= 3 {
+                        collect_last_binop_on_stack(&mut stack);
+                    }
                     let lhs = enum_unwrap!(stack.pop(), Some:(ExprOrOp::Expr:(_)));
                     let sig = self
                         .convert_rhs_to_lambda_sig(lhs)
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     self.counter.inc();
                     let block = if is_multiline_block {
-                        self.try_reduce_block()
+                        self.try_reduce_block(true)
                             .map_err(|_| self.stack_dec(fn_name!()))?
                     } else {
                         // precedence: `->` > `,`
@@ -1703,6 +1943,14 @@ impl Parser {
                         })?,
                     ));
                 }
+                // binding pattern (`whole @ {x; y}`)
+                Some(op) if op.is(AtSign) => {
+                    let lhs = enum_unwrap!(stack.pop(), Some:(ExprOrOp::Expr:(_)));
+                    let bind = self
+                        .try_reduce_bind_pattern(lhs, in_brace)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    stack.push(ExprOrOp::Expr(bind));
+                }
                 Some(t) if t.is(DblColon) => {
                     let vis = VisModifierSpec::ExplicitPrivate(self.lpop());
                     match self.lpop() {
@@ -1820,7 +2068,7 @@ impl Parser {
                     };
                     self.skip();
                     let index = self
-                        .try_reduce_expr(false, false, in_brace, false)
+                        .try_reduce_subscript_index(false, in_brace)
                         .map_err(|_| {
                             if let Some(err) = self.errs.last_mut() {
                                 err.set_hint(switch_lang!(
@@ -1920,13 +2168,17 @@ impl Parser {
                 Some(op) if op.category_is(TC::LambdaOp) => {
                     let op = self.lpop();
                     let is_multiline_block = self.cur_is(Newline);
+                    // `1 or 2 -> ...` == `(1 or 2) -> ...`
+                    while stack.len() >= 3 {
+                        collect_last_binop_on_stack(&mut stack);
+                    }
                     let lhs = enum_unwrap!(stack.pop(), Some:(ExprOrOp::Expr:(_)));
                     let sig = self
                         .convert_rhs_to_lambda_sig(lhs)
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     self.counter.inc();
                     let block = if is_multiline_block {
-                        self.try_reduce_block()
+                        self.try_reduce_block(true)
                             .map_err(|_| self.stack_dec(fn_name!()))?
                     } else {
                         let expr = self
@@ -1972,6 +2224,12 @@ impl Parser {
                     let expr = lhs.type_asc_expr(t_spec_op);
                     stack.push(ExprOrOp::Expr(expr));
                 }
+                // `x[start..]`: the end of the slice is omitted
+                Some(op)
+                    if op.kind.is_range_op() && (self.nth_is(1, RSqBr) || self.nth_is(1, Semi)) =>
+                {
+                    break;
+                }
                 Some(op) if op.category_is(TC::BinOp) => {
                     let op_prec = op.kind.precedence();
                     if stack.len() >= 2 {
@@ -2008,6 +2266,14 @@ impl Parser {
                             })?,
                     ));
                 }
+                // binding pattern (`whole @ {x; y}`)
+                Some(op) if op.is(AtSign) => {
+                    let lhs = enum_unwrap!(stack.pop(), Some:(ExprOrOp::Expr:(_)));
+                    let bind = self
+                        .try_reduce_bind_pattern(lhs, in_brace)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    stack.push(ExprOrOp::Expr(bind));
+                }
                 Some(t) if t.is(Dot) => {
                     let vis = self.lpop();
                     match self.lpop() {
@@ -2055,7 +2321,7 @@ impl Parser {
                     };
                     self.skip(); // l_sqbr
                     let index = self
-                        .try_reduce_expr(false, false, in_brace, false)
+                        .try_reduce_subscript_index(false, in_brace)
                         .map_err(|_| {
                             if let Some(err) = self.errs.last_mut() {
                                 err.set_hint(switch_lang!(
@@ -2200,6 +2466,55 @@ impl Parser {
 
     /// "LHS" is the smallest unit that can be the left-hand side of an BinOp.
     /// e.g. Call, Name, UnaryOp, Lambda
+    /// `x[i]`, `x[start..stop]`, `x[..stop]`, `x[start..]`, `x[start..stop; step]`
+    ///
+    /// Slices are desugared into `x[slice(start, stop, step)]`.
+    fn try_reduce_subscript_index(&mut self, winding: bool, in_brace: bool) -> ParseResult<Expr> {
+        debug_call_info!(self);
+        // `x[..stop]`, `x[..]`
+        let start = if self.peek().is_some_and(|t| t.kind.is_range_op()) {
+            None
+        } else {
+            let index = self
+                .try_reduce_expr(winding, false, in_brace, false)
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            Some(index)
+        };
+        let (start, op, stop) = match start {
+            Some(Expr::BinOp(bin)) if bin.op.kind.is_range_op() => {
+                let (op, start, stop) = bin.deconstruct();
+                (Some(start), op, Some(stop))
+            }
+            Some(index) if !self.peek().is_some_and(|t| t.kind.is_range_op()) => {
+                debug_exit_info!(self);
+                return Ok(index);
+            }
+            start => {
+                let op = self.lpop();
+                let stop = if self.cur_is(RSqBr) || self.cur_is(Semi) {
+                    None
+                } else {
+                    let stop = self
+                        .try_reduce_expr(false, false, in_brace, false)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    Some(stop)
+                };
+                (start, op, stop)
+            }
+        };
+        let step = if self.cur_is(Semi) {
+            self.skip();
+            let step = self
+                .try_reduce_expr(false, false, in_brace, false)
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            Some(step)
+        } else {
+            None
+        };
+        debug_exit_info!(self);
+        Ok(Desugarer::desugar_slice(start, op, stop, step))
+    }
+
     fn try_reduce_bin_lhs(&mut self, in_type_args: bool, in_brace: bool) -> ParseResult<Expr> {
         debug_call_info!(self);
         match self.peek() {
@@ -2245,7 +2560,7 @@ impl Parser {
                     .try_reduce_string_interpolation()
                     .map_err(|_| self.stack_dec(fn_name!()))?;
                 debug_exit_info!(self);
-                Ok(str_interp)
+                Ok(Expr::StrInterp(str_interp))
             }
             Some(t) if t.is(AtSign) => {
                 let decos = self.opt_reduce_decorators()?;
@@ -2260,6 +2575,12 @@ impl Parser {
                     }
                     self.stack_dec(fn_name!())
                 })?;
+                // `@Available python:="3.10"` + `.f: Int -> Int`
+                if let Expr::TypeAscription(tasc) = expr {
+                    let expr = Expr::TypeAscription(tasc.with_decorators(decos));
+                    debug_exit_info!(self);
+                    return Ok(expr);
+                }
                 let Expr::Def(mut def) = expr else {
                     // self.restore(other);
                     let err = self.skip_and_throw_syntax_err(line!(), caused_by!());
@@ -2276,7 +2597,10 @@ impl Parser {
                     }
                     Signature::Var(var) => {
                         let mut last = def.body.block.pop().unwrap();
-                        for deco in decos.into_iter() {
+                        let mut decos = decos.into_iter().collect::<Vec<_>>();
+                        decos.sort_by_key(|deco| deco.0.ln_begin());
+                        // the nearest decorator is applied first
+                        for deco in decos.into_iter().rev() {
                             last = deco.into_expr().call_expr(Args::single(PosArg::new(last)));
                         }
                         def.body.block.push(last);
@@ -2314,6 +2638,27 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(Expr::Tuple(tuple))
             }
+            Some(t) if t.is(PreDblStar) => {
+                let _ = self.lpop();
+                let expr = self
+                    .try_reduce_expr(false, in_type_args, in_brace, false)
+                    .map_err(|_| {
+                        if let Some(err) = self.errs.last_mut() {
+                            err.set_hint(switch_lang!(
+                                "japanese" => "期待: 可変長キーワード引数",
+                                "simplified_chinese" => "期望: 可变长度关键字参数",
+                                "traditional_chinese" => "期望: 可變長度關鍵字參數",
+                                "english" => "expect: variable-length keyword arguments",
+                            ))
+                        }
+                        self.stack_dec(fn_name!())
+                    })?;
+                let tuple = self
+                    .try_reduce_nonempty_tuple(ArgKind::KwVar(PosArg::new(expr)), false)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                Ok(Expr::Tuple(tuple))
+            }
             Some(t) if t.category_is(TC::UnaryOp) => {
                 let unaryop = self
                     .try_reduce_unary()
@@ -2360,6 +2705,13 @@ impl Parser {
                         self.skip();
                     }
                 }
+                if self.cur_is(VBar) {
+                    let lambda = self
+                        .try_reduce_guarded_lambda(expr)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    debug_exit_info!(self);
+                    return Ok(Expr::Lambda(lambda));
+                }
                 let rparen = match self.peek_kind() {
                     Some(RParen) => self.lpop(),
                     Some(_) => {
@@ -2378,8 +2730,21 @@ impl Parser {
                         return Err(());
                     }
                 };
-                if let Expr::Tuple(Tuple::Normal(tup)) = &mut expr {
-                    tup.elems.paren = Some((lparen, rparen));
+                match &mut expr {
+                    // `((k, v)) -> ...`: a single tuple-pattern parameter
+                    Expr::Tuple(Tuple::Normal(tup))
+                        if tup.elems.paren.is_some()
+                            && matches!(self.peek_kind(), Some(FuncArrow | ProcArrow)) =>
+                    {
+                        let args = Args::pos_only(vec![PosArg::new(expr)], Some((lparen, rparen)));
+                        let tup = Tuple::Normal(NormalTuple::new(args));
+                        debug_exit_info!(self);
+                        return Ok(Expr::Tuple(tup));
+                    }
+                    Expr::Tuple(Tuple::Normal(tup)) => {
+                        tup.elems.paren = Some((lparen, rparen));
+                    }
+                    _ => {}
                 }
                 debug_exit_info!(self);
                 Ok(expr)
@@ -2426,7 +2791,7 @@ impl Parser {
                 let sig = LambdaSignature::new(params, None, bounds);
                 let op = expect_pop!(self, category TC::LambdaOp);
                 let block = self
-                    .try_reduce_block()
+                    .try_reduce_block(true)
                     .map_err(|_| self.stack_dec(fn_name!()))?;
                 self.counter.inc();
                 let lambda = Lambda::new(sig, op, block, self.counter);
@@ -2486,19 +2851,17 @@ impl Parser {
             match self.peek() {
                 Some(t) if t.is(LSqBr) && obj.col_end() == t.col_begin() => {
                     let _l_sqbr = self.lpop();
-                    let index = self
-                        .try_reduce_expr(true, false, false, false)
-                        .map_err(|_| {
-                            if let Some(err) = self.errs.last_mut() {
-                                err.set_hint(switch_lang!(
-                                    "japanese" => "期待: Nat型",
-                                    "simplified_chinese" => "期望: Nat类型",
-                                    "traditional_chinese" => "期望: Nat類型",
-                                    "english" => "expect: Nat type",
-                                ))
-                            }
-                            self.stack_dec(fn_name!())
-                        })?;
+                    let index = self.try_reduce_subscript_index(true, false).map_err(|_| {
+                        if let Some(err) = self.errs.last_mut() {
+                            err.set_hint(switch_lang!(
+                                "japanese" => "期待: Nat型",
+                                "simplified_chinese" => "期望: Nat类型",
+                                "traditional_chinese" => "期望: Nat類型",
+                                "english" => "expect: Nat type",
+                            ))
+                        }
+                        self.stack_dec(fn_name!())
+                    })?;
                     let r_sqbr = expect_pop!(self, fail_next RSqBr);
                     obj = Expr::Accessor(Accessor::subscr(obj, index, r_sqbr));
                 }
@@ -2599,12 +2962,17 @@ impl Parser {
                     let call = Call::new(receiver, attr_name, args);
                     obj = Expr::Call(call);
                 }
-                Some(t) if t.is(VBar) && !in_type_args => {
+                Some(t) if t.is(VBar) && !in_type_args && obj.col_end() == t.col_begin() => {
                     let type_args = self
                         .try_reduce_type_app_args()
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     obj = Expr::Accessor(Accessor::TypeApp(TypeApp::new(obj, type_args)));
                 }
+                // early return: `x?`
+                Some(t) if t.is(Try) && obj.col_end() == t.col_begin() => {
+                    let op = self.lpop();
+                    obj = Expr::UnaryOp(UnaryOp::new(op, obj));
+                }
                 _ => {
                     break;
                 }
@@ -2614,6 +2982,67 @@ impl Parser {
         Ok(obj)
     }
 
+    /// `(pattern | guard) -> body`
+    /// The opening parenthesis and the pattern have already been consumed.
+    /// `name @ pattern` is only valid as a parameter pattern,
+    /// so it is left as a binary operation here and converted to a pattern later.
+    /// `@` binds more tightly than any other binary operator.
+    fn try_reduce_bind_pattern(&mut self, lhs: Expr, in_brace: bool) -> ParseResult<Expr> {
+        debug_call_info!(self);
+        let op = self.lpop();
+        let rhs = self.try_reduce_bin_lhs(false, in_brace).map_err(|_| {
+            if let Some(err) = self.errs.last_mut() {
+                err.set_hint(switch_lang!(
+                    "japanese" => "予期: パターン",
+                    "simplified_chinese" => "期望: 模式",
+                    "traditional_chinese" => "期望: 模式",
+                    "english" => "expect: pattern",
+                ))
+            }
+            self.stack_dec(fn_name!())
+        })?;
+        debug_exit_info!(self);
+        Ok(Expr::BinOp(BinOp::new(op, lhs, rhs)))
+    }
+
+    fn try_reduce_guarded_lambda(&mut self, pattern: Expr) -> ParseResult<Lambda> {
+        debug_call_info!(self);
+        let _vbar = expect_pop!(self, VBar);
+        let guard = self
+            .try_reduce_expr(false, false, false, false)
+            .map_err(|_| {
+                if let Some(err) = self.errs.last_mut() {
+                    err.set_hint(switch_lang!(
+                        "japanese" => "予期: ガード条件",
+                        "simplified_chinese" => "期望: 守卫条件",
+                        "traditional_chinese" => "期望: 守衛條件",
+                        "english" => "expect: guard condition",
+                    ))
+                }
+                self.stack_dec(fn_name!())
+            })?;
+        let _rparen = expect_pop!(self, fail_next RParen);
+        let param = self
+            .convert_rhs_to_param(pattern, false)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        let sig = LambdaSignature::new(Params::single(param), None, TypeBoundSpecs::empty())
+            .with_guard(Block::new(vec![guard]));
+        let op = expect_pop!(self, category TC::LambdaOp);
+        let is_multiline_block = self.cur_is(Newline);
+        self.counter.inc();
+        let block = if is_multiline_block {
+            self.try_reduce_block(true)
+                .map_err(|_| self.stack_dec(fn_name!()))?
+        } else {
+            let expr = self
+                .try_reduce_expr(false, false, false, false)
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            Block::new(vec![expr])
+        };
+        debug_exit_info!(self);
+        Ok(Lambda::new(sig, op, block, self.counter))
+    }
+
     #[inline]
     fn try_reduce_unary(&mut self) -> ParseResult<UnaryOp> {
         debug_call_info!(self);
@@ -2663,22 +3092,106 @@ impl Parser {
             ArrayInner::WithLength(elem, len) => {
                 Array::WithLength(ArrayWithLength::new(l_sqbr, r_sqbr, elem, len))
             }
-            ArrayInner::Comprehension { .. } => {
-                let caused_by = caused_by!();
-                log!(err "error caused by: {caused_by}");
-                self.errs.push(ParseError::feature_error(
-                    line!() as usize,
-                    Location::concat(&l_sqbr, &r_sqbr),
-                    "array comprehension",
-                ));
-                debug_exit_info!(self);
-                return Err(());
-            }
+            ArrayInner::Comprehension {
+                elem,
+                generators,
+                guards,
+            } => Array::Comprehension(ArrayComprehension::new(
+                l_sqbr, r_sqbr, elem.expr, generators, guards,
+            )),
         };
         debug_exit_info!(self);
         Ok(arr)
     }
 
+    /// Parses the clauses after `|` of a comprehension (`x <- xs, y <- ys, x < y`).
+    /// Clauses are separated by `,` or `;`, and guards cannot precede generators.
+    #[allow(clippy::type_complexity)]
+    fn try_reduce_comprehension_clauses(
+        &mut self,
+        closer: &str,
+    ) -> ParseResult<(Vec<(Identifier, Expr)>, Vec<Expr>)> {
+        debug_call_info!(self);
+        let mut generators = vec![];
+        let mut guards: Vec<Expr> = vec![];
+        loop {
+            if self.cur_is(Symbol) && self.nth_is(1, Inclusion) {
+                let var = Identifier::private_from_token(self.lpop());
+                if let Some(guard) = guards.first() {
+                    let err = ParseError::syntax_error(
+                        line!() as usize,
+                        guard.loc(),
+                        switch_lang!(
+                            "japanese" => "ガード節はバインド節の後に置く必要があります",
+                            "simplified_chinese" => "守卫子句必须位于绑定子句之后",
+                            "traditional_chinese" => "守衛子句必須位於綁定子句之後",
+                            "english" => "guard clauses must follow bind clauses",
+                        ),
+                        None,
+                    );
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+                let _arrow = self.lpop();
+                let iter = self
+                    .try_reduce_expr(false, false, false, false)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                generators.push((var, iter));
+            } else {
+                let guard = self
+                    .try_reduce_expr(false, false, false, false)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                guards.push(guard);
+            }
+            match self.peek_kind() {
+                Some(Comma | Semi) => {
+                    self.skip();
+                }
+                Some(RSqBr | RBrace) => {
+                    break;
+                }
+                Some(_) => {
+                    let err = self.skip_and_throw_invalid_unclosed_err(
+                        caused_by!(),
+                        line!(),
+                        closer,
+                        "comprehension",
+                    );
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+                None => {
+                    self.errs.push(self.unexpected_none(line!(), caused_by!()));
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+            }
+        }
+        if generators.is_empty() {
+            let loc = guards
+                .first()
+                .map_or(Location::Unknown, |guard| guard.loc());
+            let err = ParseError::syntax_error(
+                line!() as usize,
+                loc,
+                switch_lang!(
+                    "japanese" => "内包表記にはバインド節(`x <- xs`)が必要です",
+                    "simplified_chinese" => "推导式需要绑定子句(`x <- xs`)",
+                    "traditional_chinese" => "推導式需要綁定子句(`x <- xs`)",
+                    "english" => "a comprehension requires a bind clause (`x <- xs`)",
+                ),
+                None,
+            );
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        }
+        debug_exit_info!(self);
+        Ok((generators, guards))
+    }
+
     /// Set, Dict, Record
     fn try_reduce_brace_container(&mut self) -> ParseResult<BraceContainer> {
         debug_call_info!(self);
@@ -2828,10 +3341,21 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(res)
             }
+            layout if self.cur_is(VBar) => {
+                self.skip();
+                let (generators, guards) = self
+                    .try_reduce_comprehension_clauses("}")
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                let r_brace = expect_pop!(self, fail_next RBrace);
+                let set_comp =
+                    SetComprehension::new(l_brace, r_brace, Some(layout), generators, guards);
+                debug_exit_info!(self);
+                Ok(BraceContainer::Set(Set::Comprehension(set_comp)))
+            }
             other => {
                 match self.peek() {
                     Some(r_brace) if r_brace.is(RBrace) => {
-                        let arg = Args::new(vec![PosArg::new(other)], None, vec![], None);
+                        let arg = Args::pos_only(vec![PosArg::new(other)], None);
                         let r_brace = self.lpop();
                         return Ok(BraceContainer::Set(Set::Normal(NormalSet::new(
                             l_brace, r_brace, arg,
@@ -2965,11 +3489,21 @@ impl Parser {
         lhs: Expr,
     ) -> ParseResult<BraceContainer> {
         debug_call_info!(self);
-        let colon = expect_pop!(self, fail_next Colon);
+        let _colon = expect_pop!(self, fail_next Colon);
         let rhs = self
             .try_reduce_expr(false, true, false, false)
             .map_err(|_| self.stack_dec(fn_name!()))?;
-        if self.cur_is(VBar) {
+        // {k: v | k <- ks; v <- vs}
+        if self.cur_is(VBar) && self.nth_is(1, Symbol) && self.nth_is(2, Inclusion) {
+            self.skip();
+            let (generators, guards) = self
+                .try_reduce_comprehension_clauses("}")
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            let r_brace = expect_pop!(self, fail_next RBrace);
+            let dict_comp = DictComprehension::new(l_brace, r_brace, lhs, rhs, generators, guards);
+            debug_exit_info!(self);
+            Ok(BraceContainer::Dict(Dict::Comprehension(dict_comp)))
+        } else if self.cur_is(VBar) {
             self.skip();
             let Expr::Accessor(Accessor::Ident(var)) = lhs else {
                 let err = ParseError::simple_syntax_error(line!() as usize, lhs.loc());
@@ -2982,7 +3516,7 @@ impl Parser {
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             let r_brace = expect_pop!(self, fail_next RBrace);
             let set_comp =
-                SetComprehension::new(l_brace, r_brace, var.name.into_token(), colon, rhs, pred);
+                SetComprehension::new(l_brace, r_brace, None, vec![(var, rhs)], vec![pred]);
             debug_exit_info!(self);
             Ok(BraceContainer::Set(Set::Comprehension(set_comp)))
         } else {
@@ -3162,7 +3696,7 @@ impl Parser {
                                 }
                             }
                         },
-                        ArgKind::Var(var) => {
+                        ArgKind::Var(var) | ArgKind::KwVar(var) => {
                             let err = ParseError::simple_syntax_error(line!() as usize, var.loc());
                             self.errs.push(err);
                             debug_exit_info!(self);
@@ -3212,8 +3746,9 @@ impl Parser {
         debug_call_info!(self);
         let mut args = match first_elem {
             ArgKind::Pos(pos) => Args::single(pos),
-            ArgKind::Var(var) => Args::new(vec![], Some(var), vec![], None),
-            ArgKind::Kw(kw) => Args::new(vec![], None, vec![kw], None),
+            ArgKind::Var(var) => Args::new(vec![], Some(var), vec![], None, None),
+            ArgKind::Kw(kw) => Args::new(vec![], None, vec![kw], None, None),
+            ArgKind::KwVar(kw_var) => Args::new(vec![], None, vec![], Some(kw_var), None),
         };
         #[allow(clippy::while_let_loop)]
         loop {
@@ -3275,6 +3810,10 @@ impl Parser {
                         ArgKind::Kw(arg) => {
                             args.push_kw(arg);
                         }
+                        // e.g. (x, **kwargs) -> ...
+                        ArgKind::KwVar(arg) => {
+                            args.set_kw_var_args(arg);
+                        }
                     }
                 }
                 Some(_other) => {
@@ -3317,38 +3856,29 @@ impl Parser {
         }
     }
 
-    /// "...\{, expr, }..." ==> "..." + str(expr) + "..."
-    /// "...\{, expr, }..." ==> "..." + str(expr) + "..."
-    fn try_reduce_string_interpolation(&mut self) -> ParseResult<Expr> {
+    /// "...\{, expr, }...\{, expr, }..." ==> StrInterp
+    fn try_reduce_string_interpolation(&mut self) -> ParseResult<StrInterp> {
         debug_call_info!(self);
-        let mut left = self.lpop();
-        left.content = Str::from(left.content.trim_end_matches("\\{").to_string() + "\"");
-        left.kind = StrLit;
-        let mut expr = Expr::Literal(Literal::from(left));
+        let mut lits = vec![self.lpop()];
+        let mut exprs = vec![];
         loop {
             match self.peek() {
                 Some(l) if l.is(StrInterpRight) => {
-                    let mut right = self.lpop();
-                    right.content =
-                        Str::from(format!("\"{}", right.content.trim_start_matches('}')));
-                    right.kind = StrLit;
-                    let right = Expr::Literal(Literal::from(right));
-                    let op = Token::new(
-                        Plus,
-                        "+",
-                        right.ln_begin().unwrap(),
-                        right.col_begin().unwrap(),
-                    );
-                    expr = Expr::BinOp(BinOp::new(op, expr, right));
+                    lits.push(self.lpop());
                     debug_exit_info!(self);
-                    return Ok(expr);
+                    return Ok(StrInterp::new(lits, exprs));
                 }
                 Some(t) if t.is(EOF) => {
                     let caused_by = caused_by!();
                     log!(err "error caused by: {caused_by}");
+                    let loc = if let Some(expr) = exprs.last() {
+                        Location::concat(&lits[0], expr)
+                    } else {
+                        lits[0].loc()
+                    };
                     let err = ParseError::syntax_error(
                         line!() as usize,
-                        expr.loc(),
+                        loc,
                         switch_lang!(
                             "japanese" => "文字列補間の終わりが見つかりませんでした",
                             "simplified_chinese" => "未找到字符串的结束插值",
@@ -3361,40 +3891,20 @@ impl Parser {
                     debug_exit_info!(self);
                     return Err(());
                 }
-                Some(_) => {
-                    let mid_expr = self
+                Some(_) if exprs.len() < lits.len() => {
+                    let expr = self
                         .try_reduce_expr(true, false, false, false)
                         .map_err(|_| self.stack_dec(fn_name!()))?;
-                    let str_func = Expr::local(
-                        "str",
-                        mid_expr.ln_begin().unwrap(),
-                        mid_expr.col_begin().unwrap(),
-                    );
-                    let call = Call::new(str_func, None, Args::single(PosArg::new(mid_expr)));
-                    let op = Token::new(
-                        Plus,
-                        "+",
-                        call.ln_begin().unwrap(),
-                        call.col_begin().unwrap(),
-                    );
-                    let bin = BinOp::new(op, expr, Expr::Call(call));
-                    expr = Expr::BinOp(bin);
-                    if self.cur_is(StrInterpMid) {
-                        let mut mid = self.lpop();
-                        mid.content = Str::from(format!(
-                            "\"{}\"",
-                            mid.content.trim_start_matches('}').trim_end_matches("\\{")
-                        ));
-                        mid.kind = StrLit;
-                        let mid = Expr::Literal(Literal::from(mid));
-                        let op = Token::new(
-                            Plus,
-                            "+",
-                            mid.ln_begin().unwrap(),
-                            mid.col_begin().unwrap(),
-                        );
-                        expr = Expr::BinOp(BinOp::new(op, expr, mid));
-                    }
+                    exprs.push(expr);
+                }
+                Some(mid) if mid.is(StrInterpMid) => {
+                    lits.push(self.lpop());
+                }
+                Some(_) => {
+                    let err = self.skip_and_throw_syntax_err(line!(), caused_by!());
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
                 }
                 None => {
                     self.errs.push(self.unexpected_none(line!(), caused_by!()));
@@ -3469,23 +3979,50 @@ impl Parser {
             let expect_call = self
                 .try_reduce_call_or_acc(false)
                 .map_err(|_| self.stack_dec(fn_name!()))?;
-            let Expr::Call(mut call) = expect_call else {
-                    let caused_by = caused_by!();
-                    log!(err "error caused by: {caused_by}");
-                let err = self.get_stream_op_syntax_error(line!() as usize, expect_call.loc(), caused_by!());
-                self.errs.push(err);
+            let ExprOrOp::Expr(first_arg) = stack.pop().unwrap() else {
+                let caused_by = caused_by!();
+                log!(err "error caused by: {caused_by}");
+                self.errs.push(ParseError::compiler_bug(
+                    line!() as usize,
+                    expect_call.loc(),
+                    fn_name!(),
+                    line!(),
+                ));
                 debug_exit_info!(self);
                 return Err(());
             };
-            let ExprOrOp::Expr(first_arg) = stack.pop().unwrap() else {
+            let mut call = match expect_call {
+                Expr::Call(call) => call,
+                // `x |> f` => `f(x)`
+                Expr::Accessor(acc) => {
+                    let call = Expr::Accessor(acc).call(Args::single(PosArg::new(first_arg)));
+                    stack.push(ExprOrOp::Expr(Expr::Call(call)));
+                    debug_exit_info!(self);
+                    return Ok(());
+                }
+                other => {
                     let caused_by = caused_by!();
                     log!(err "error caused by: {caused_by}");
-                self.errs
-                    .push(ParseError::compiler_bug(line!() as usize, call.loc(), fn_name!(), line!()));
-                debug_exit_info!(self);
-                return Err(());
+                    let err = self.get_stream_op_syntax_error(
+                        line!() as usize,
+                        other.loc(),
+                        caused_by!(),
+                    );
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
             };
-            call.args.insert_pos(0, PosArg::new(first_arg));
+            // `x |> f(y, _)` => `f(y, x)`
+            let placeholder = call.args.pos_args().iter().position(|arg| {
+                matches!(&arg.expr, Expr::Accessor(Accessor::Ident(ident)) if ident.inspect() == "_")
+            });
+            if let Some(placeholder) = placeholder {
+                call.args.remove_pos(placeholder);
+                call.args.insert_pos(placeholder, PosArg::new(first_arg));
+            } else {
+                call.args.insert_pos(0, PosArg::new(first_arg));
+            }
             stack.push(ExprOrOp::Expr(Expr::Call(call)));
         }
         debug_exit_info!(self);
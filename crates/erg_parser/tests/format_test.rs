@@ -0,0 +1,99 @@
+use std::fs;
+
+use erg_common::normalize_newline;
+use erg_common::traits::Stream;
+
+use erg_parser::cst::{Cst, CstNodeKind};
+use erg_parser::format::Formatter;
+use erg_parser::lex::Lexer;
+
+const UNFORMATTED: &str = "tests/unformatted.er";
+const FORMATTED: &str = "tests/formatted.er";
+
+fn read(path: &str) -> String {
+    normalize_newline(&fs::read_to_string(path).unwrap())
+}
+
+#[test]
+fn test_cst_is_lossless() {
+    for path in [
+        "tests/comment.er",
+        "tests/multi_line_str_literal.er",
+        "tests/test1_basic_syntax.er",
+        "tests/test2_advanced_syntax.er",
+        UNFORMATTED,
+    ] {
+        let src = read(path);
+        let cst = Cst::parse(&src).unwrap();
+        assert_eq!(cst.to_string(), src, "{path}");
+    }
+}
+
+#[test]
+fn test_cst_spans_and_tokens() {
+    for path in [
+        "tests/args.er",
+        "tests/comment.er",
+        "tests/multi_line_str_literal.er",
+        "tests/test1_basic_syntax.er",
+        "tests/test2_advanced_syntax.er",
+        UNFORMATTED,
+    ] {
+        let src = read(path);
+        let cst = Cst::parse(&src).unwrap();
+        for token in cst.tokens.iter() {
+            assert_eq!(&src[token.span.start..token.span.end], &token.raw[..]);
+            for trivia in token.leading.iter() {
+                assert_eq!(&src[trivia.span.start..trivia.span.end], trivia.content);
+            }
+        }
+        // the AST is derived from the CST
+        let tokens = Lexer::from_str(src.clone()).lex().unwrap();
+        assert_eq!(cst.token_stream().to_string(), tokens.to_string(), "{path}");
+        assert_eq!(cst.root.token_indices().len(), cst.tokens.len(), "{path}");
+    }
+}
+
+#[test]
+fn test_cst_tree() {
+    let src = "# f\nf x =\n    y = g(\n        1,\n        2\n    )\n    x\nprint! f 1\n";
+    let cst = Cst::parse(src).unwrap();
+    let lines = cst.root.nodes().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.is(CstNodeKind::Line)));
+    assert_eq!(&src[lines[1].span.start..lines[1].span.end], "print! f 1");
+    let block = lines[0].nodes().next().unwrap();
+    assert!(block.is(CstNodeKind::Block));
+    assert_eq!(block.nodes().count(), 2);
+    let group = block.nodes().next().unwrap().nodes().next().unwrap();
+    assert!(group.is(CstNodeKind::Group));
+    assert_eq!(
+        &src[group.span.start..group.span.end],
+        "(\n        1,\n        2\n    )"
+    );
+
+    let module = cst.to_module().unwrap().ast;
+    let def = module.get(0).unwrap();
+    let span = cst.span_of(def).unwrap();
+    assert_eq!(
+        &src[span.start..span.end],
+        "f x =\n    y = g(\n        1,\n        2\n    )\n    x"
+    );
+    let comments = cst.leading_comments(def);
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].content, "# f");
+}
+
+#[test]
+fn test_format() {
+    let formatter = Formatter::default();
+    let formatted = formatter.format(&read(UNFORMATTED)).unwrap();
+    assert_eq!(formatted, read(FORMATTED));
+    // idempotent
+    assert_eq!(formatter.format(&formatted).unwrap(), formatted);
+}
+
+#[test]
+fn test_format_invalid_code() {
+    assert!(Formatter::default().format("x = \n").is_err());
+}
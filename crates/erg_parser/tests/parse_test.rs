@@ -3,11 +3,13 @@ use erg_common::consts::DEBUG_MODE;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::io::Input;
 use erg_common::spawn::exec_new_thread;
-use erg_common::traits::{Runnable, Stream};
+use erg_common::traits::{Locational, Runnable, Stream};
 
+use erg_parser::ast::{Expr, Identifier};
 use erg_parser::error::{ErrorArtifact, ParseWarnings, ParserRunnerErrors};
 use erg_parser::lex::Lexer;
-use erg_parser::ParserRunner;
+use erg_parser::visitor::{Visitor, VisitorMut};
+use erg_parser::{Parser, ParserRunner};
 
 #[test]
 fn parse_args() -> Result<(), ()> {
@@ -64,6 +66,11 @@ fn parse_str_literal() -> Result<(), ()> {
     expect_failure("tests/failed_str_lit.er", 0, 2)
 }
 
+#[test]
+fn parse_unclosed_escape() -> Result<(), ()> {
+    expect_failure("tests/unclosed_escape.er", 0, 1)
+}
+
 #[test]
 fn parse_invalid_chunk() -> Result<(), ()> {
     expect_failure("tests/invalid_chunk.er", 0, 62)
@@ -71,7 +78,7 @@ fn parse_invalid_chunk() -> Result<(), ()> {
 
 #[test]
 fn parse_invalid_collections() -> Result<(), ()> {
-    expect_failure("tests/invalid_collections.er", 0, 29)
+    expect_failure("tests/invalid_collections.er", 0, 25)
 }
 
 #[test]
@@ -79,6 +86,128 @@ fn parse_invalid_class_definition() -> Result<(), ()> {
     expect_failure("tests/invalid_class_definition.er", 0, 7)
 }
 
+#[test]
+fn parse_recover() -> Result<(), ()> {
+    expect_failure("tests/recover.er", 0, 5)
+}
+
+#[test]
+fn parse_recover_partial_module() -> Result<(), ()> {
+    let src = std::fs::read_to_string("tests/recover.er").map_err(|_| ())?;
+    let mut parser = ParserRunner::new(ErgConfig::default());
+    let Err(artifact) = parser.parse(src) else {
+        return Err(());
+    };
+    let module = artifact.ast.ok_or(())?;
+    let defs = module
+        .iter()
+        .filter_map(|chunk| match chunk {
+            Expr::Def(def) => def.sig.ident().map(|ident| ident.inspect().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if defs != ["a", "b", "f", "c", "g", "d", "e"] {
+        println!("err: the definitions are {defs:?}");
+        return Err(());
+    }
+    // the statements of `f` that failed to parse are replaced with `Dummy`s
+    let Some(Expr::Def(f)) = module.get(2) else {
+        return Err(());
+    };
+    let dummies = f
+        .body
+        .block
+        .iter()
+        .filter(|chunk| matches!(chunk, Expr::Dummy(_)))
+        .count();
+    if dummies != 2 {
+        println!("err: number of recovery nodes is not 2 but {dummies}");
+        return Err(());
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_str_interp() -> Result<(), ()> {
+    let src = "s = \"a\\{x}b\\{f y}c\"";
+    let tokens = Lexer::from_str(src.into()).lex().map_err(|_| ())?;
+    let module = Parser::new(tokens).parse().map_err(|_| ())?.ast;
+    let Some(Expr::Def(def)) = module.get(0) else {
+        return Err(());
+    };
+    let Some(Expr::StrInterp(interp)) = def.body.block.get(0) else {
+        return Err(());
+    };
+    if interp.lits.len() != 3 || interp.exprs.len() != 2 {
+        println!("err: the interpolation is parsed as {interp:?}");
+        return Err(());
+    }
+    // the embedded expressions have the locations in the string
+    let cols = interp
+        .exprs
+        .iter()
+        .map(|expr| (expr.col_begin(), expr.col_end()))
+        .collect::<Vec<_>>();
+    if cols != [(Some(8), Some(9)), (Some(13), Some(16))] {
+        println!("err: the columns are {cols:?}");
+        return Err(());
+    }
+    Ok(())
+}
+
+#[test]
+fn parse_doc_comment() -> Result<(), ()> {
+    let src = "#| Identity\n#| function.\nid x = x\n\n'''\nThe answer\n'''\nanswer = 42\n";
+    let tokens = Lexer::from_str(src.into()).lex().map_err(|_| ())?;
+    let module = Parser::new(tokens).parse().map_err(|_| ())?.ast;
+    let docs = module
+        .iter()
+        .map(|chunk| match chunk {
+            Expr::Def(def) => def.doc.as_ref().map(|doc| doc.text()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let expected = [
+        Some("Identity\nfunction.".to_string()),
+        Some("The answer".to_string()),
+    ];
+    if docs != expected {
+        println!("err: the doc comments are attached as {docs:?}");
+        return Err(());
+    }
+    Ok(())
+}
+
+#[test]
+fn visit_ast() -> Result<(), ()> {
+    struct NameCollector<'a>(Vec<&'a str>);
+    impl<'a> Visitor<'a> for NameCollector<'a> {
+        fn visit_identifier(&mut self, ident: &'a Identifier) {
+            self.0.push(ident.inspect());
+        }
+    }
+    struct Renamer;
+    impl VisitorMut for Renamer {
+        fn visit_identifier_mut(&mut self, ident: &mut Identifier) {
+            if &ident.inspect()[..] == "x" {
+                *ident = Identifier::private("y".into());
+            }
+        }
+    }
+    let src = "f x = x + 1\nprint! f(x), [x, {\"a\": x}], (x.real, )";
+    let tokens = Lexer::from_str(src.into()).lex().map_err(|_| ())?;
+    let mut module = Parser::new(tokens).parse().map_err(|_| ())?.ast;
+    Renamer.visit_module_mut(&mut module);
+    let mut collector = NameCollector(vec![]);
+    collector.visit_module(&module);
+    // the names of the definitions (`f`) and the parameters (`x`) are not visited
+    if collector.0 != ["y", "print!", "f", "y", "y", "y", "y", "real"] {
+        println!("err: the names are {:?}", collector.0);
+        return Err(());
+    }
+    Ok(())
+}
+
 #[test]
 fn exec_invalid_chunk_prs_err() -> Result<(), ()> {
     expect_failure("tests/invalid_chunk.er", 0, 62)
@@ -1,6 +1,8 @@
 use std::iter::Iterator;
 
+use erg_common::error::Location;
 use erg_common::io::Input;
+use erg_common::traits::{DequeStream, Locational};
 
 // use erg_compiler::parser;
 
@@ -14,6 +16,9 @@ const FILE2: &str = "tests/test2_advanced_syntax.er";
 const FILE3: &str = "tests/test3_literal_syntax.er";
 const FILE4: &str = "tests/multi_line_str_literal.er";
 const FILE5: &str = "tests/for.er";
+const FILE6: &str = "tests/raw_str_literal.er";
+const FILE7: &str = "tests/num_literal.er";
+const FILE8: &str = "tests/doc_comment.er";
 
 #[test]
 fn test_lexer_for_basic() -> ParseResult<()> {
@@ -403,6 +408,135 @@ line break\"\"\"",
     Ok(())
 }
 
+#[test]
+fn test_lexer_for_raw_and_dedented_str_literal() -> ParseResult<()> {
+    let mut lexer = Lexer::new(Input::file(FILE6.into()));
+    let newline = "\n";
+    let token_array = [
+        (Symbol, "raw"),
+        (Assign, "="),
+        (StrLit, "\"\\d+\\{x}\""),
+        (Newline, newline),
+        (Symbol, "raw_quote"),
+        (Assign, "="),
+        (StrLit, "\"a\\\"b\""),
+        (Newline, newline),
+        (Symbol, "sql"),
+        (Assign, "="),
+        (StrLit, "\"\"\"SELECT *\n  FROM t\n\"\"\""),
+        (Newline, newline),
+        (Symbol, "html"),
+        (Assign, "="),
+        (StrLit, "\"\"\"<p>\\{x}</p>\n\"\"\""),
+        (Newline, newline),
+        (Symbol, "interp"),
+        (Assign, "="),
+        (StrInterpLeft, "\"\"\"x = \\{"),
+        (Symbol, "x"),
+        (StrInterpRight, "}\ndone\n\"\"\""),
+        (Newline, newline),
+        (EOF, "\0"),
+    ];
+    let mut tok: Token;
+    for (id, i) in token_array.into_iter().enumerate() {
+        print!("{id:>03}: ");
+        tok = lexer.next().unwrap().unwrap();
+        assert_eq!(tok, Token::from_str(i.0, i.1));
+        println!("{tok}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lexer_for_num_literal() -> ParseResult<()> {
+    let mut lexer = Lexer::new(Input::file(FILE7.into()));
+    let newline = "\n";
+    let token_array = [
+        (NatLit, "1_000_000"),
+        (Comma, ","),
+        (BinLit, "0b1010"),
+        (Comma, ","),
+        (OctLit, "0o777"),
+        (Comma, ","),
+        (HexLit, "0xdead_BEEF"),
+        (Newline, newline),
+        (IntLit, "-0b11"),
+        (Comma, ","),
+        (IntLit, "-0xff"),
+        (Newline, newline),
+        (RatioLit, "1e3"),
+        (Comma, ","),
+        (RatioLit, "1E-3"),
+        (Comma, ","),
+        (RatioLit, "2.5e+1_0"),
+        (Newline, newline),
+        (FloatLit, "1f"),
+        (Comma, ","),
+        (FloatLit, "2.5f"),
+        (Comma, ","),
+        (FloatLit, "1e3f"),
+        (Comma, ","),
+        (IntLit, "1i"),
+        (Comma, ","),
+        (IntLit, "-1i"),
+        (Comma, ","),
+        (NatLit, "5n"),
+        (Newline, newline),
+        (EOF, "\0"),
+    ];
+    let mut tok: Token;
+    for (id, i) in token_array.into_iter().enumerate() {
+        print!("{id:>03}: ");
+        tok = lexer.next().unwrap().unwrap();
+        assert_eq!(tok, Token::from_str(i.0, i.1));
+        println!("{tok}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lexer_for_doc_comment() -> ParseResult<()> {
+    let mut lexer = Lexer::new(Input::file(FILE8.into()));
+    let newline = "\n";
+    let token_array = [
+        (DocComment, "#| Identity\n#| function."),
+        (Newline, newline),
+        (Symbol, "id"),
+        (Symbol, "x"),
+        (Assign, "="),
+        (Symbol, "x"),
+        (Newline, newline),
+        (Newline, newline),
+        (Symbol, "C"),
+        (Assign, "="),
+        (Symbol, "Class"),
+        (LParen, "("),
+        (RParen, ")"),
+        (Newline, newline),
+        (Symbol, "C"),
+        (Dot, "."),
+        (Newline, newline),
+        (Indent, "    "),
+        (DocComment, "#| Get x."),
+        (Newline, newline),
+        (Symbol, "get"),
+        (Symbol, "self"),
+        (Assign, "="),
+        (NatLit, "1"),
+        (Newline, newline),
+        (Dedent, ""),
+        (EOF, "\0"),
+    ];
+    let mut tok: Token;
+    for (id, i) in token_array.into_iter().enumerate() {
+        print!("{id:>03}: ");
+        tok = lexer.next().unwrap().unwrap();
+        assert_eq!(tok, Token::from_str(i.0, i.1));
+        println!("{tok}");
+    }
+    Ok(())
+}
+
 #[test]
 fn for_loop() -> ParseResult<()> {
     let mut lexer = Lexer::new(Input::file(FILE5.into()));
@@ -454,6 +588,24 @@ fn for_loop() -> ParseResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_lexer_for_unicode_location() -> ParseResult<()> {
+    let src = "s = \"あ👨‍👩‍👧e\u{301}\"; x = 1\r\ny = s\n";
+    let tokens = Lexer::from_str(src.to_string()).lex().unwrap();
+    let find = |cont: &str| tokens.iter().find(|tok| &tok.content[..] == cont).unwrap();
+    // the columns count grapheme clusters
+    let str_lit = find("\"あ👨‍👩‍👧e\u{301}\"");
+    assert_eq!((str_lit.col_begin, str_lit.col_end()), (4, Some(9)));
+    let x = find("x");
+    assert_eq!(x.loc(), Location::range(1, 11, 1, 12));
+    // the byte offsets are those in the original source (with `\r\n`)
+    for cont in ["x", "y", "1", str_lit.content.as_ref()] {
+        let range = find(cont).loc().byte_range().unwrap();
+        assert_eq!(&src[range], cont);
+    }
+    Ok(())
+}
+
 #[test]
 fn tesop_te_prec() {
     assert_eq!(Mod.precedence(), Some(170));
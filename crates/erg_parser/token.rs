@@ -6,6 +6,7 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 
 use erg_common::error::Location;
+use erg_common::grapheme::grapheme_count;
 use erg_common::impl_displayable_deque_stream_for_wrapper;
 use erg_common::opcode311::BinOpCode;
 use erg_common::str::Str;
@@ -30,7 +31,10 @@ pub enum TokenKind {
     OctLit,
     /// e.g. 0xdeadbeef
     HexLit,
+    /// e.g. 1.5, 1e+3
     RatioLit,
+    /// e.g. 1f, 2.5f
+    FloatLit,
     BoolLit,
     StrLit,
     /// e.g. "abc\{
@@ -43,6 +47,7 @@ pub enum TokenKind {
     /// ... (== Ellipsis)
     EllipsisLit,
     InfLit,
+    /// e.g. '''doc''', #| doc
     DocComment,
     /// `+` (unary)
     PrePlus,
@@ -240,8 +245,8 @@ impl TokenKind {
     pub const fn category(&self) -> TokenCategory {
         match self {
             Symbol => TokenCategory::Symbol,
-            NatLit | BinLit | OctLit | HexLit | IntLit | RatioLit | StrLit | BoolLit | NoneLit
-            | EllipsisLit | InfLit | DocComment => TokenCategory::Literal,
+            NatLit | BinLit | OctLit | HexLit | IntLit | RatioLit | FloatLit | StrLit | BoolLit
+            | NoneLit | EllipsisLit | InfLit | DocComment => TokenCategory::Literal,
             StrInterpLeft => TokenCategory::StrInterpLeft,
             StrInterpMid => TokenCategory::StrInterpMid,
             StrInterpRight => TokenCategory::StrInterpRight,
@@ -340,8 +345,10 @@ pub struct Token {
     /// 1 origin
     // TODO: 複数行文字列リテラルもあるのでタプルにするのが妥当?
     pub lineno: u32,
-    /// a pointer from which the token starts (0 origin)
+    /// a pointer from which the token starts (0 origin, in grapheme clusters)
     pub col_begin: u32,
+    /// the byte offset in the source (0 origin), `Location::UNKNOWN_BYTE` if unknown
+    pub byte_begin: u32,
 }
 
 pub const COLON: Token = Token::dummy(TokenKind::Colon, ":");
@@ -387,18 +394,23 @@ impl Locational for Token {
         if self.lineno == 0 {
             Location::Unknown
         } else {
-            Location::range(
+            let loc = Location::range(
                 self.lineno,
                 self.col_begin,
                 self.lineno,
-                self.col_begin + self.content.len() as u32,
-            )
+                self.col_end().unwrap_or(self.col_begin),
+            );
+            if self.byte_begin == Location::UNKNOWN_BYTE {
+                loc
+            } else {
+                loc.with_byte_range(self.byte_begin, self.byte_begin + self.content.len() as u32)
+            }
         }
     }
 
     #[inline]
     fn col_end(&self) -> Option<u32> {
-        Some(self.col_begin + self.content.len() as u32)
+        Some(self.col_begin + grapheme_count(&self.content) as u32)
     }
 }
 
@@ -408,6 +420,7 @@ impl Token {
         content: Str::ever("DUMMY"),
         lineno: 1,
         col_begin: 0,
+        byte_begin: Location::UNKNOWN_BYTE,
     };
 
     pub const fn dummy(kind: TokenKind, content: &'static str) -> Self {
@@ -416,6 +429,7 @@ impl Token {
             content: Str::ever(content),
             lineno: 1,
             col_begin: 0,
+            byte_begin: Location::UNKNOWN_BYTE,
         }
     }
 
@@ -426,6 +440,7 @@ impl Token {
             content: cont.into(),
             lineno,
             col_begin,
+            byte_begin: Location::UNKNOWN_BYTE,
         }
     }
 
@@ -436,6 +451,7 @@ impl Token {
             content: Str::rc(cont),
             lineno: 0,
             col_begin: 0,
+            byte_begin: Location::UNKNOWN_BYTE,
         }
     }
 
@@ -451,6 +467,7 @@ impl Token {
             content: Str::rc(cont),
             lineno,
             col_begin: 0,
+            byte_begin: Location::UNKNOWN_BYTE,
         }
     }
 
@@ -460,6 +477,7 @@ impl Token {
             content: cont.into(),
             lineno: loc.ln_begin().unwrap_or(0),
             col_begin: loc.col_begin().unwrap_or(0),
+            byte_begin: loc.byte_begin().unwrap_or(Location::UNKNOWN_BYTE),
         }
     }
 
@@ -469,9 +487,15 @@ impl Token {
             content: Str::ever(s),
             lineno: 0,
             col_begin: 0,
+            byte_begin: Location::UNKNOWN_BYTE,
         }
     }
 
+    pub const fn with_byte_begin(mut self, byte_begin: u32) -> Self {
+        self.byte_begin = byte_begin;
+        self
+    }
+
     pub fn deep_eq(&self, other: &Self) -> bool {
         self.kind == other.kind
             && self.content == other.content
@@ -4,7 +4,7 @@ use erg_common::traits::{Locational, Stream};
 use crate::ast::*;
 use crate::desugar::Desugarer;
 use crate::error::ParseError;
-use crate::token::TokenKind;
+use crate::token::{Token, TokenKind};
 use crate::Parser;
 
 // The APIs defined below are also used by `ASTLowerer` to interpret expressions as types.
@@ -62,14 +62,23 @@ impl Parser {
                     let const_set = ConstNormalSet::new(set.l_brace, set.r_brace, elems);
                     Ok(ConstExpr::Set(ConstSet::Normal(const_set)))
                 }
-                Set::Comprehension(set) => {
-                    let iter = Self::validate_const_expr(*set.iter)?;
-                    let pred = Self::validate_const_expr(*set.pred)?;
+                Set::Comprehension(set) if set.is_refinement() => {
+                    let (var, iter) = set.generators.into_iter().next().unwrap();
+                    let pred = set.guards.into_iter().next().unwrap();
+                    let iter = Self::validate_const_expr(iter)?;
+                    let pred = Self::validate_const_expr(pred)?;
+                    let var = var.name.into_token();
+                    let colon = Token::new(
+                        TokenKind::Colon,
+                        ":",
+                        var.lineno,
+                        var.col_end().unwrap_or(0),
+                    );
                     let const_set_comp = ConstSetComprehension::new(
                         set.l_brace,
                         set.r_brace,
-                        set.var,
-                        set.op,
+                        var,
+                        colon,
                         iter,
                         pred,
                     );
@@ -100,7 +109,7 @@ impl Parser {
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
-                    let (elems, _, _, paren) = tup.elems.deconstruct();
+                    let (elems, _, _, _, paren) = tup.elems.deconstruct();
                     let mut const_elems = vec![];
                     for elem in elems.into_iter() {
                         let const_expr = Self::validate_const_expr(elem.expr)?;
@@ -132,7 +141,7 @@ impl Parser {
                     ));
                 };*/
                 let attr_name = call.attr_name;
-                let (pos_args, _, _, paren) = call.args.deconstruct();
+                let (pos_args, _, _, _, paren) = call.args.deconstruct();
                 let mut const_pos_args = vec![];
                 for elem in pos_args.into_iter() {
                     let const_expr = Self::validate_const_expr(elem.expr)?;
@@ -218,7 +227,7 @@ impl Parser {
     pub(crate) fn call_to_predecl_type_spec(call: Call) -> Result<PreDeclTypeSpec, ParseError> {
         match *call.obj {
             Expr::Accessor(Accessor::Ident(ident)) => {
-                let (_pos_args, _var_args, _kw_args, paren) = call.args.deconstruct();
+                let (_pos_args, _var_args, _kw_args, _kw_var, paren) = call.args.deconstruct();
                 let mut pos_args = vec![];
                 for arg in _pos_args.into_iter() {
                     let const_expr = Self::validate_const_expr(arg.expr)?;
@@ -364,10 +373,12 @@ impl Parser {
                 Ok(TypeSpec::SetWithLen(SetWithLenTypeSpec::new(t_spec, len)))
             }
             Set::Comprehension(set) => {
-                if set.op.is(TokenKind::Colon) {
-                    let typ = Self::expr_to_type_spec(*set.iter)?;
-                    let pred = Self::validate_const_expr(*set.pred)?;
-                    let refine = RefinementTypeSpec::new(set.var, typ, pred);
+                if set.is_refinement() {
+                    let (var, iter) = set.generators.into_iter().next().unwrap();
+                    let pred = set.guards.into_iter().next().unwrap();
+                    let typ = Self::expr_to_type_spec(iter)?;
+                    let pred = Self::validate_const_expr(pred)?;
+                    let refine = RefinementTypeSpec::new(var.name.into_token(), typ, pred);
                     Ok(TypeSpec::Refinement(refine))
                 } else {
                     Err(ParseError::simple_syntax_error(line!() as usize, set.loc()))
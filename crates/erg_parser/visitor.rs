@@ -0,0 +1,733 @@
+//! defines `Visitor` and `VisitorMut` (the traversal of `AST`).
+//!
+//! The default implementation of each `visit_*` method calls the corresponding `walk_*` function,
+//! which visits the children of the node. Override only the methods you need,
+//! and call `walk_*` in them to keep traversing the children.
+//!
+//! Type specifications (e.g. `Int` of `x: Int`) and the patterns of the signatures are not traversed.
+//! ```
+//! use erg_parser::ast::{Identifier, AST};
+//! use erg_parser::visitor::{walk_identifier, Visitor};
+//!
+//! #[derive(Default)]
+//! struct NameCollector<'a> {
+//!     names: Vec<&'a str>,
+//! }
+//!
+//! impl<'a> Visitor<'a> for NameCollector<'a> {
+//!     fn visit_identifier(&mut self, ident: &'a Identifier) {
+//!         self.names.push(ident.inspect());
+//!         walk_identifier(self, ident);
+//!     }
+//! }
+//!
+//! fn names(ast: &AST) -> Vec<&str> {
+//!     let mut collector = NameCollector::default();
+//!     collector.visit_module(&ast.module);
+//!     collector.names
+//! }
+//! ```
+use erg_common::traits::Stream;
+
+use crate::ast::*;
+
+/// Traverses the `AST` by reference.
+/// `'a` is the lifetime of the `AST`, so the visitor can keep the references to the nodes.
+pub trait Visitor<'a> {
+    fn visit_module(&mut self, module: &'a Module) {
+        walk_module(self, module);
+    }
+    fn visit_block(&mut self, block: &'a Block) {
+        walk_block(self, block);
+    }
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        walk_expr(self, expr);
+    }
+    fn visit_literal(&mut self, _lit: &'a Literal) {}
+    fn visit_str_interp(&mut self, interp: &'a StrInterp) {
+        walk_str_interp(self, interp);
+    }
+    /// Called for the identifiers in expressions (not for the names of definitions, see `visit_signature`).
+    fn visit_identifier(&mut self, ident: &'a Identifier) {
+        walk_identifier(self, ident);
+    }
+    fn visit_accessor(&mut self, acc: &'a Accessor) {
+        walk_accessor(self, acc);
+    }
+    fn visit_array(&mut self, arr: &'a Array) {
+        walk_array(self, arr);
+    }
+    fn visit_tuple(&mut self, tup: &'a Tuple) {
+        walk_tuple(self, tup);
+    }
+    fn visit_dict(&mut self, dict: &'a Dict) {
+        walk_dict(self, dict);
+    }
+    fn visit_set(&mut self, set: &'a Set) {
+        walk_set(self, set);
+    }
+    fn visit_record(&mut self, record: &'a Record) {
+        walk_record(self, record);
+    }
+    fn visit_bin_op(&mut self, bin: &'a BinOp) {
+        walk_bin_op(self, bin);
+    }
+    fn visit_unary_op(&mut self, unary: &'a UnaryOp) {
+        walk_unary_op(self, unary);
+    }
+    fn visit_call(&mut self, call: &'a Call) {
+        walk_call(self, call);
+    }
+    fn visit_args(&mut self, args: &'a Args) {
+        walk_args(self, args);
+    }
+    fn visit_data_pack(&mut self, pack: &'a DataPack) {
+        walk_data_pack(self, pack);
+    }
+    fn visit_lambda(&mut self, lambda: &'a Lambda) {
+        walk_lambda(self, lambda);
+    }
+    fn visit_params(&mut self, params: &'a Params) {
+        walk_params(self, params);
+    }
+    fn visit_type_ascription(&mut self, tasc: &'a TypeAscription) {
+        walk_type_ascription(self, tasc);
+    }
+    fn visit_def(&mut self, def: &'a Def) {
+        walk_def(self, def);
+    }
+    fn visit_signature(&mut self, sig: &'a Signature) {
+        walk_signature(self, sig);
+    }
+    fn visit_methods(&mut self, methods: &'a Methods) {
+        walk_methods(self, methods);
+    }
+    fn visit_class_def(&mut self, class_def: &'a ClassDef) {
+        walk_class_def(self, class_def);
+    }
+    fn visit_patch_def(&mut self, patch_def: &'a PatchDef) {
+        walk_patch_def(self, patch_def);
+    }
+    fn visit_redef(&mut self, redef: &'a ReDef) {
+        walk_redef(self, redef);
+    }
+    fn visit_dummy(&mut self, dummy: &'a Dummy) {
+        walk_dummy(self, dummy);
+    }
+}
+
+pub fn walk_module<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, module: &'a Module) {
+    for chunk in module.iter() {
+        visitor.visit_expr(chunk);
+    }
+}
+
+pub fn walk_block<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, block: &'a Block) {
+    for chunk in block.iter() {
+        visitor.visit_expr(chunk);
+    }
+}
+
+pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
+    match expr {
+        Expr::Literal(lit) => visitor.visit_literal(lit),
+        Expr::StrInterp(interp) => visitor.visit_str_interp(interp),
+        Expr::Accessor(acc) => visitor.visit_accessor(acc),
+        Expr::Array(arr) => visitor.visit_array(arr),
+        Expr::Tuple(tup) => visitor.visit_tuple(tup),
+        Expr::Dict(dict) => visitor.visit_dict(dict),
+        Expr::Set(set) => visitor.visit_set(set),
+        Expr::Record(record) => visitor.visit_record(record),
+        Expr::BinOp(bin) => visitor.visit_bin_op(bin),
+        Expr::UnaryOp(unary) => visitor.visit_unary_op(unary),
+        Expr::Call(call) => visitor.visit_call(call),
+        Expr::DataPack(pack) => visitor.visit_data_pack(pack),
+        Expr::Lambda(lambda) => visitor.visit_lambda(lambda),
+        Expr::TypeAscription(tasc) => visitor.visit_type_ascription(tasc),
+        Expr::Def(def) => visitor.visit_def(def),
+        Expr::Methods(methods) => visitor.visit_methods(methods),
+        Expr::ClassDef(class_def) => visitor.visit_class_def(class_def),
+        Expr::PatchDef(patch_def) => visitor.visit_patch_def(patch_def),
+        Expr::ReDef(redef) => visitor.visit_redef(redef),
+        Expr::Dummy(dummy) => visitor.visit_dummy(dummy),
+    }
+}
+
+pub fn walk_identifier<'a, V: Visitor<'a> + ?Sized>(_visitor: &mut V, _ident: &'a Identifier) {}
+
+pub fn walk_accessor<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, acc: &'a Accessor) {
+    match acc {
+        Accessor::Ident(ident) => visitor.visit_identifier(ident),
+        Accessor::Attr(attr) => {
+            visitor.visit_expr(&attr.obj);
+            visitor.visit_identifier(&attr.ident);
+        }
+        Accessor::TupleAttr(attr) => {
+            visitor.visit_expr(&attr.obj);
+            visitor.visit_literal(&attr.index);
+        }
+        Accessor::Subscr(subscr) => {
+            visitor.visit_expr(&subscr.obj);
+            visitor.visit_expr(&subscr.index);
+        }
+        Accessor::TypeApp(app) => {
+            visitor.visit_expr(&app.obj);
+            if let TypeAppArgsKind::Args(args) = &app.type_args.args {
+                visitor.visit_args(args);
+            }
+        }
+    }
+}
+
+pub fn walk_array<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, arr: &'a Array) {
+    match arr {
+        Array::Normal(arr) => visitor.visit_args(&arr.elems),
+        Array::WithLength(arr) => {
+            visitor.visit_expr(&arr.elem.expr);
+            visitor.visit_expr(&arr.len);
+        }
+        Array::Comprehension(arr) => {
+            for (_, iter) in arr.generators.iter() {
+                visitor.visit_expr(iter);
+            }
+            for guard in arr.guards.iter() {
+                visitor.visit_expr(guard);
+            }
+            visitor.visit_expr(&arr.elem);
+        }
+    }
+}
+
+pub fn walk_tuple<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, tup: &'a Tuple) {
+    match tup {
+        Tuple::Normal(tup) => visitor.visit_args(&tup.elems),
+    }
+}
+
+pub fn walk_dict<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, dict: &'a Dict) {
+    match dict {
+        Dict::Normal(dict) => {
+            for kv in dict.kvs.iter() {
+                visitor.visit_expr(&kv.key);
+                visitor.visit_expr(&kv.value);
+            }
+        }
+        Dict::Comprehension(dict) => {
+            for (_, iter) in dict.generators.iter() {
+                visitor.visit_expr(iter);
+            }
+            for guard in dict.guards.iter() {
+                visitor.visit_expr(guard);
+            }
+            visitor.visit_expr(&dict.key);
+            visitor.visit_expr(&dict.value);
+        }
+    }
+}
+
+pub fn walk_set<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, set: &'a Set) {
+    match set {
+        Set::Normal(set) => visitor.visit_args(&set.elems),
+        Set::WithLength(set) => {
+            visitor.visit_expr(&set.elem.expr);
+            visitor.visit_expr(&set.len);
+        }
+        Set::Comprehension(set) => {
+            for (_, iter) in set.generators.iter() {
+                visitor.visit_expr(iter);
+            }
+            for guard in set.guards.iter() {
+                visitor.visit_expr(guard);
+            }
+            if let Some(layout) = &set.layout {
+                visitor.visit_expr(layout);
+            }
+        }
+    }
+}
+
+pub fn walk_record<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, record: &'a Record) {
+    match record {
+        Record::Normal(record) => {
+            for attr in record.attrs.iter() {
+                visitor.visit_def(attr);
+            }
+        }
+        Record::Mixed(record) => {
+            for attr in record.attrs.iter() {
+                match attr {
+                    RecordAttrOrIdent::Attr(def) => visitor.visit_def(def),
+                    RecordAttrOrIdent::Ident(ident) => visitor.visit_identifier(ident),
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_str_interp<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, interp: &'a StrInterp) {
+    for expr in interp.exprs.iter() {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn walk_bin_op<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, bin: &'a BinOp) {
+    for arg in bin.args.iter() {
+        visitor.visit_expr(arg);
+    }
+}
+
+pub fn walk_unary_op<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, unary: &'a UnaryOp) {
+    for arg in unary.args.iter() {
+        visitor.visit_expr(arg);
+    }
+}
+
+pub fn walk_call<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, call: &'a Call) {
+    visitor.visit_expr(&call.obj);
+    if let Some(attr_name) = &call.attr_name {
+        visitor.visit_identifier(attr_name);
+    }
+    visitor.visit_args(&call.args);
+}
+
+pub fn walk_args<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, args: &'a Args) {
+    for arg in args.pos_args() {
+        visitor.visit_expr(&arg.expr);
+    }
+    if let Some(var_args) = &args.var_args {
+        visitor.visit_expr(&var_args.expr);
+    }
+    for arg in args.kw_args() {
+        visitor.visit_expr(&arg.expr);
+    }
+    if let Some(kw_var_args) = &args.kw_var_args {
+        visitor.visit_expr(&kw_var_args.expr);
+    }
+}
+
+pub fn walk_data_pack<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, pack: &'a DataPack) {
+    visitor.visit_expr(&pack.class);
+    visitor.visit_record(&pack.args);
+}
+
+pub fn walk_lambda<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, lambda: &'a Lambda) {
+    visitor.visit_params(&lambda.sig.params);
+    if let Some(guard) = &lambda.sig.guard {
+        visitor.visit_block(guard);
+    }
+    visitor.visit_block(&lambda.body);
+}
+
+/// Visits the default values of the parameters.
+pub fn walk_params<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, params: &'a Params) {
+    for default in params.defaults.iter() {
+        visitor.visit_expr(&default.default_val);
+    }
+}
+
+pub fn walk_type_ascription<'a, V: Visitor<'a> + ?Sized>(
+    visitor: &mut V,
+    tasc: &'a TypeAscription,
+) {
+    visitor.visit_expr(&tasc.expr);
+}
+
+pub fn walk_def<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, def: &'a Def) {
+    visitor.visit_signature(&def.sig);
+    visitor.visit_block(&def.body.block);
+}
+
+/// Visits the decorators and the default values of the parameters.
+pub fn walk_signature<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, sig: &'a Signature) {
+    if let Signature::Subr(subr) = sig {
+        for deco in subr.decorators.iter() {
+            visitor.visit_expr(deco.expr());
+        }
+        visitor.visit_params(&subr.params);
+    }
+}
+
+pub fn walk_methods<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, methods: &'a Methods) {
+    visitor.visit_expr(&methods.class_as_expr);
+    for attr in methods.attrs.iter() {
+        match attr {
+            ClassAttr::Def(def) => visitor.visit_def(def),
+            ClassAttr::Decl(decl) => visitor.visit_type_ascription(decl),
+            ClassAttr::Doc(doc) => visitor.visit_literal(doc),
+        }
+    }
+}
+
+pub fn walk_class_def<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, class_def: &'a ClassDef) {
+    visitor.visit_def(&class_def.def);
+    for methods in class_def.methods_list.iter() {
+        visitor.visit_methods(methods);
+    }
+}
+
+pub fn walk_patch_def<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, patch_def: &'a PatchDef) {
+    visitor.visit_def(&patch_def.def);
+    for methods in patch_def.methods_list.iter() {
+        visitor.visit_methods(methods);
+    }
+}
+
+pub fn walk_redef<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, redef: &'a ReDef) {
+    visitor.visit_accessor(&redef.attr);
+    visitor.visit_expr(&redef.expr);
+}
+
+pub fn walk_dummy<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, dummy: &'a Dummy) {
+    for chunk in dummy.iter() {
+        visitor.visit_expr(chunk);
+    }
+}
+
+/// Traverses the `AST` by mutable reference (e.g. for rewriting the nodes in place).
+/// The decorators are not visited (they cannot be mutated in place).
+pub trait VisitorMut {
+    fn visit_module_mut(&mut self, module: &mut Module) {
+        walk_module_mut(self, module);
+    }
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+    fn visit_literal_mut(&mut self, _lit: &mut Literal) {}
+    fn visit_str_interp_mut(&mut self, interp: &mut StrInterp) {
+        walk_str_interp_mut(self, interp);
+    }
+    /// Called for the identifiers in expressions (not for the names of definitions, see `visit_signature_mut`).
+    fn visit_identifier_mut(&mut self, ident: &mut Identifier) {
+        walk_identifier_mut(self, ident);
+    }
+    fn visit_accessor_mut(&mut self, acc: &mut Accessor) {
+        walk_accessor_mut(self, acc);
+    }
+    fn visit_array_mut(&mut self, arr: &mut Array) {
+        walk_array_mut(self, arr);
+    }
+    fn visit_tuple_mut(&mut self, tup: &mut Tuple) {
+        walk_tuple_mut(self, tup);
+    }
+    fn visit_dict_mut(&mut self, dict: &mut Dict) {
+        walk_dict_mut(self, dict);
+    }
+    fn visit_set_mut(&mut self, set: &mut Set) {
+        walk_set_mut(self, set);
+    }
+    fn visit_record_mut(&mut self, record: &mut Record) {
+        walk_record_mut(self, record);
+    }
+    fn visit_bin_op_mut(&mut self, bin: &mut BinOp) {
+        walk_bin_op_mut(self, bin);
+    }
+    fn visit_unary_op_mut(&mut self, unary: &mut UnaryOp) {
+        walk_unary_op_mut(self, unary);
+    }
+    fn visit_call_mut(&mut self, call: &mut Call) {
+        walk_call_mut(self, call);
+    }
+    fn visit_args_mut(&mut self, args: &mut Args) {
+        walk_args_mut(self, args);
+    }
+    fn visit_data_pack_mut(&mut self, pack: &mut DataPack) {
+        walk_data_pack_mut(self, pack);
+    }
+    fn visit_lambda_mut(&mut self, lambda: &mut Lambda) {
+        walk_lambda_mut(self, lambda);
+    }
+    fn visit_params_mut(&mut self, params: &mut Params) {
+        walk_params_mut(self, params);
+    }
+    fn visit_type_ascription_mut(&mut self, tasc: &mut TypeAscription) {
+        walk_type_ascription_mut(self, tasc);
+    }
+    fn visit_def_mut(&mut self, def: &mut Def) {
+        walk_def_mut(self, def);
+    }
+    fn visit_signature_mut(&mut self, sig: &mut Signature) {
+        walk_signature_mut(self, sig);
+    }
+    fn visit_methods_mut(&mut self, methods: &mut Methods) {
+        walk_methods_mut(self, methods);
+    }
+    fn visit_class_def_mut(&mut self, class_def: &mut ClassDef) {
+        walk_class_def_mut(self, class_def);
+    }
+    fn visit_patch_def_mut(&mut self, patch_def: &mut PatchDef) {
+        walk_patch_def_mut(self, patch_def);
+    }
+    fn visit_redef_mut(&mut self, redef: &mut ReDef) {
+        walk_redef_mut(self, redef);
+    }
+    fn visit_dummy_mut(&mut self, dummy: &mut Dummy) {
+        walk_dummy_mut(self, dummy);
+    }
+}
+
+pub fn walk_module_mut<V: VisitorMut + ?Sized>(visitor: &mut V, module: &mut Module) {
+    for chunk in module.iter_mut() {
+        visitor.visit_expr_mut(chunk);
+    }
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for chunk in block.iter_mut() {
+        visitor.visit_expr_mut(chunk);
+    }
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Literal(lit) => visitor.visit_literal_mut(lit),
+        Expr::StrInterp(interp) => visitor.visit_str_interp_mut(interp),
+        Expr::Accessor(acc) => visitor.visit_accessor_mut(acc),
+        Expr::Array(arr) => visitor.visit_array_mut(arr),
+        Expr::Tuple(tup) => visitor.visit_tuple_mut(tup),
+        Expr::Dict(dict) => visitor.visit_dict_mut(dict),
+        Expr::Set(set) => visitor.visit_set_mut(set),
+        Expr::Record(record) => visitor.visit_record_mut(record),
+        Expr::BinOp(bin) => visitor.visit_bin_op_mut(bin),
+        Expr::UnaryOp(unary) => visitor.visit_unary_op_mut(unary),
+        Expr::Call(call) => visitor.visit_call_mut(call),
+        Expr::DataPack(pack) => visitor.visit_data_pack_mut(pack),
+        Expr::Lambda(lambda) => visitor.visit_lambda_mut(lambda),
+        Expr::TypeAscription(tasc) => visitor.visit_type_ascription_mut(tasc),
+        Expr::Def(def) => visitor.visit_def_mut(def),
+        Expr::Methods(methods) => visitor.visit_methods_mut(methods),
+        Expr::ClassDef(class_def) => visitor.visit_class_def_mut(class_def),
+        Expr::PatchDef(patch_def) => visitor.visit_patch_def_mut(patch_def),
+        Expr::ReDef(redef) => visitor.visit_redef_mut(redef),
+        Expr::Dummy(dummy) => visitor.visit_dummy_mut(dummy),
+    }
+}
+
+pub fn walk_identifier_mut<V: VisitorMut + ?Sized>(_visitor: &mut V, _ident: &mut Identifier) {}
+
+pub fn walk_accessor_mut<V: VisitorMut + ?Sized>(visitor: &mut V, acc: &mut Accessor) {
+    match acc {
+        Accessor::Ident(ident) => visitor.visit_identifier_mut(ident),
+        Accessor::Attr(attr) => {
+            visitor.visit_expr_mut(&mut attr.obj);
+            visitor.visit_identifier_mut(&mut attr.ident);
+        }
+        Accessor::TupleAttr(attr) => {
+            visitor.visit_expr_mut(&mut attr.obj);
+            visitor.visit_literal_mut(&mut attr.index);
+        }
+        Accessor::Subscr(subscr) => {
+            visitor.visit_expr_mut(&mut subscr.obj);
+            visitor.visit_expr_mut(&mut subscr.index);
+        }
+        Accessor::TypeApp(app) => {
+            visitor.visit_expr_mut(&mut app.obj);
+            if let TypeAppArgsKind::Args(args) = &mut app.type_args.args {
+                visitor.visit_args_mut(args);
+            }
+        }
+    }
+}
+
+pub fn walk_array_mut<V: VisitorMut + ?Sized>(visitor: &mut V, arr: &mut Array) {
+    match arr {
+        Array::Normal(arr) => visitor.visit_args_mut(&mut arr.elems),
+        Array::WithLength(arr) => {
+            visitor.visit_expr_mut(&mut arr.elem.expr);
+            visitor.visit_expr_mut(&mut arr.len);
+        }
+        Array::Comprehension(arr) => {
+            for (_, iter) in arr.generators.iter_mut() {
+                visitor.visit_expr_mut(iter);
+            }
+            for guard in arr.guards.iter_mut() {
+                visitor.visit_expr_mut(guard);
+            }
+            visitor.visit_expr_mut(&mut arr.elem);
+        }
+    }
+}
+
+pub fn walk_tuple_mut<V: VisitorMut + ?Sized>(visitor: &mut V, tup: &mut Tuple) {
+    match tup {
+        Tuple::Normal(tup) => visitor.visit_args_mut(&mut tup.elems),
+    }
+}
+
+pub fn walk_dict_mut<V: VisitorMut + ?Sized>(visitor: &mut V, dict: &mut Dict) {
+    match dict {
+        Dict::Normal(dict) => {
+            for kv in dict.kvs.iter_mut() {
+                visitor.visit_expr_mut(&mut kv.key);
+                visitor.visit_expr_mut(&mut kv.value);
+            }
+        }
+        Dict::Comprehension(dict) => {
+            for (_, iter) in dict.generators.iter_mut() {
+                visitor.visit_expr_mut(iter);
+            }
+            for guard in dict.guards.iter_mut() {
+                visitor.visit_expr_mut(guard);
+            }
+            visitor.visit_expr_mut(&mut dict.key);
+            visitor.visit_expr_mut(&mut dict.value);
+        }
+    }
+}
+
+pub fn walk_set_mut<V: VisitorMut + ?Sized>(visitor: &mut V, set: &mut Set) {
+    match set {
+        Set::Normal(set) => visitor.visit_args_mut(&mut set.elems),
+        Set::WithLength(set) => {
+            visitor.visit_expr_mut(&mut set.elem.expr);
+            visitor.visit_expr_mut(&mut set.len);
+        }
+        Set::Comprehension(set) => {
+            for (_, iter) in set.generators.iter_mut() {
+                visitor.visit_expr_mut(iter);
+            }
+            for guard in set.guards.iter_mut() {
+                visitor.visit_expr_mut(guard);
+            }
+            if let Some(layout) = &mut set.layout {
+                visitor.visit_expr_mut(layout);
+            }
+        }
+    }
+}
+
+pub fn walk_record_mut<V: VisitorMut + ?Sized>(visitor: &mut V, record: &mut Record) {
+    match record {
+        Record::Normal(record) => {
+            for attr in record.attrs.iter_mut() {
+                visitor.visit_def_mut(attr);
+            }
+        }
+        Record::Mixed(record) => {
+            for attr in record.attrs.iter_mut() {
+                match attr {
+                    RecordAttrOrIdent::Attr(def) => visitor.visit_def_mut(def),
+                    RecordAttrOrIdent::Ident(ident) => visitor.visit_identifier_mut(ident),
+                }
+            }
+        }
+    }
+}
+
+pub fn walk_str_interp_mut<V: VisitorMut + ?Sized>(visitor: &mut V, interp: &mut StrInterp) {
+    for expr in interp.exprs.iter_mut() {
+        visitor.visit_expr_mut(expr);
+    }
+}
+
+pub fn walk_bin_op_mut<V: VisitorMut + ?Sized>(visitor: &mut V, bin: &mut BinOp) {
+    for arg in bin.args.iter_mut() {
+        visitor.visit_expr_mut(arg);
+    }
+}
+
+pub fn walk_unary_op_mut<V: VisitorMut + ?Sized>(visitor: &mut V, unary: &mut UnaryOp) {
+    for arg in unary.args.iter_mut() {
+        visitor.visit_expr_mut(arg);
+    }
+}
+
+pub fn walk_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut Call) {
+    visitor.visit_expr_mut(&mut call.obj);
+    if let Some(attr_name) = &mut call.attr_name {
+        visitor.visit_identifier_mut(attr_name);
+    }
+    visitor.visit_args_mut(&mut call.args);
+}
+
+pub fn walk_args_mut<V: VisitorMut + ?Sized>(visitor: &mut V, args: &mut Args) {
+    for arg in args.pos_args_mut() {
+        visitor.visit_expr_mut(&mut arg.expr);
+    }
+    if let Some(var_args) = &mut args.var_args {
+        visitor.visit_expr_mut(&mut var_args.expr);
+    }
+    for arg in args.kw_args_mut() {
+        visitor.visit_expr_mut(&mut arg.expr);
+    }
+    if let Some(kw_var_args) = &mut args.kw_var_args {
+        visitor.visit_expr_mut(&mut kw_var_args.expr);
+    }
+}
+
+pub fn walk_data_pack_mut<V: VisitorMut + ?Sized>(visitor: &mut V, pack: &mut DataPack) {
+    visitor.visit_expr_mut(&mut pack.class);
+    visitor.visit_record_mut(&mut pack.args);
+}
+
+pub fn walk_lambda_mut<V: VisitorMut + ?Sized>(visitor: &mut V, lambda: &mut Lambda) {
+    visitor.visit_params_mut(&mut lambda.sig.params);
+    if let Some(guard) = &mut lambda.sig.guard {
+        visitor.visit_block_mut(guard);
+    }
+    visitor.visit_block_mut(&mut lambda.body);
+}
+
+/// Visits the default values of the parameters.
+pub fn walk_params_mut<V: VisitorMut + ?Sized>(visitor: &mut V, params: &mut Params) {
+    for default in params.defaults.iter_mut() {
+        visitor.visit_expr_mut(&mut default.default_val);
+    }
+}
+
+pub fn walk_type_ascription_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    tasc: &mut TypeAscription,
+) {
+    visitor.visit_expr_mut(&mut tasc.expr);
+}
+
+pub fn walk_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, def: &mut Def) {
+    visitor.visit_signature_mut(&mut def.sig);
+    visitor.visit_block_mut(&mut def.body.block);
+}
+
+/// Visits the default values of the parameters.
+pub fn walk_signature_mut<V: VisitorMut + ?Sized>(visitor: &mut V, sig: &mut Signature) {
+    if let Signature::Subr(subr) = sig {
+        visitor.visit_params_mut(&mut subr.params);
+    }
+}
+
+pub fn walk_methods_mut<V: VisitorMut + ?Sized>(visitor: &mut V, methods: &mut Methods) {
+    visitor.visit_expr_mut(&mut methods.class_as_expr);
+    for attr in methods.attrs.iter_mut() {
+        match attr {
+            ClassAttr::Def(def) => visitor.visit_def_mut(def),
+            ClassAttr::Decl(decl) => visitor.visit_type_ascription_mut(decl),
+            ClassAttr::Doc(doc) => visitor.visit_literal_mut(doc),
+        }
+    }
+}
+
+pub fn walk_class_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, class_def: &mut ClassDef) {
+    visitor.visit_def_mut(&mut class_def.def);
+    for methods in class_def.methods_list.iter_mut() {
+        visitor.visit_methods_mut(methods);
+    }
+}
+
+pub fn walk_patch_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, patch_def: &mut PatchDef) {
+    visitor.visit_def_mut(&mut patch_def.def);
+    for methods in patch_def.methods_list.iter_mut() {
+        visitor.visit_methods_mut(methods);
+    }
+}
+
+pub fn walk_redef_mut<V: VisitorMut + ?Sized>(visitor: &mut V, redef: &mut ReDef) {
+    visitor.visit_accessor_mut(&mut redef.attr);
+    visitor.visit_expr_mut(&mut redef.expr);
+}
+
+pub fn walk_dummy_mut<V: VisitorMut + ?Sized>(visitor: &mut V, dummy: &mut Dummy) {
+    for chunk in dummy.iter_mut() {
+        visitor.visit_expr_mut(chunk);
+    }
+}
@@ -0,0 +1,87 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use erg_common::dict;
+use erg_compiler::ty::constructors::{
+    and, array_t, dict_t, free_var, func, int_interval, not, or, pos, tuple_t, unknown_len_array_t,
+};
+use erg_compiler::ty::free::Constraint;
+use erg_compiler::ty::{IntervalOp, Type};
+
+/// The number of the distinct type variables (shared by both sides)
+const NUM_VARS: usize = 4;
+
+#[derive(Debug, Arbitrary)]
+enum FuzzType {
+    Obj,
+    Never,
+    Int,
+    Nat,
+    Float,
+    Bool,
+    Str,
+    NoneType,
+    Type,
+    Interval(i8, i8),
+    Array(Box<FuzzType>, Option<u8>),
+    Tuple(Vec<FuzzType>),
+    Dict(Box<FuzzType>, Box<FuzzType>),
+    Func(Vec<FuzzType>, Box<FuzzType>),
+    Or(Box<FuzzType>, Box<FuzzType>),
+    And(Box<FuzzType>, Box<FuzzType>),
+    Not(Box<FuzzType>),
+    /// A type variable `?T(<: sup)`.
+    /// The upper bound is used only at the first appearance of the variable.
+    Var(u8, Option<Box<FuzzType>>),
+}
+
+impl FuzzType {
+    fn into_type(self, vars: &mut [Option<Type>; NUM_VARS]) -> Type {
+        match self {
+            Self::Obj => Type::Obj,
+            Self::Never => Type::Never,
+            Self::Int => Type::Int,
+            Self::Nat => Type::Nat,
+            Self::Float => Type::Float,
+            Self::Bool => Type::Bool,
+            Self::Str => Type::Str,
+            Self::NoneType => Type::NoneType,
+            Self::Type => Type::Type,
+            Self::Interval(l, r) => int_interval(IntervalOp::Closed, l as i32, r as i32),
+            Self::Array(elem, Some(len)) => array_t(elem.into_type(vars), (len as usize).into()),
+            Self::Array(elem, None) => unknown_len_array_t(elem.into_type(vars)),
+            Self::Tuple(elems) => tuple_t(elems.into_iter().map(|t| t.into_type(vars)).collect()),
+            Self::Dict(k, v) => dict_t(dict! { k.into_type(vars) => v.into_type(vars) }.into()),
+            Self::Func(params, ret) => {
+                let params = params.into_iter().map(|t| pos(t.into_type(vars))).collect();
+                func(params, None, vec![], ret.into_type(vars))
+            }
+            Self::Or(l, r) => or(l.into_type(vars), r.into_type(vars)),
+            Self::And(l, r) => and(l.into_type(vars), r.into_type(vars)),
+            Self::Not(t) => not(t.into_type(vars)),
+            Self::Var(idx, sup) => {
+                let idx = idx as usize % NUM_VARS;
+                if let Some(var) = &vars[idx] {
+                    return var.clone();
+                }
+                let sup = sup.map_or(Type::Obj, |sup| sup.into_type(vars));
+                // the variable may appear in the upper bound
+                if let Some(var) = &vars[idx] {
+                    return var.clone();
+                }
+                let var = free_var(1, Constraint::new_sandwiched(Type::Never, sup));
+                vars[idx] = Some(var.clone());
+                var
+            }
+        }
+    }
+}
+
+fuzz_target!(|types: (FuzzType, FuzzType)| {
+    let mut vars = Default::default();
+    let lhs = types.0.into_type(&mut vars);
+    let rhs = types.1.into_type(&mut vars);
+    erg_compiler::fuzz::unify(lhs, rhs);
+});
@@ -10,6 +10,7 @@ use erg_common::error::MultiErrorDisplay;
 use erg_common::python_util::spawn_py;
 use erg_common::traits::{ExitStatus, Runnable, Stream};
 
+use erg_compiler::context::ContextProvider;
 use erg_compiler::hir::Expr;
 use erg_compiler::ty::HasType;
 
@@ -289,9 +290,14 @@ impl Runnable for DummyVM {
             eart.errors
         })?;
         art.warns.write_all_to(&mut self.cfg_mut().output);
+        let script = self.cfg().input.path().to_string_lossy().to_string();
+        let argv = [&script[..]]
+            .into_iter()
+            .chain(self.cfg().runtime_args.iter().copied())
+            .collect::<Vec<_>>();
         let stat = art
             .object
-            .exec(self.cfg().py_magic_num, self.cfg().output.clone())
+            .exec(self.cfg().py_magic_num, &argv, self.cfg().output.clone())
             .expect("failed to execute");
         let stat = ExitStatus::new(stat.code().unwrap_or(0), art.warns.len(), 0);
         Ok(stat)
@@ -380,6 +386,22 @@ impl Runnable for DummyVM {
         }
         Ok(res)
     }
+
+    fn complete(&self, code: &str) -> Vec<String> {
+        self.compiler.completion_candidates(code)
+    }
+
+    fn exec_command(&mut self, command: &str) -> Option<Result<String, Self::Errs>> {
+        self.compiler.exec_command(command)
+    }
+
+    fn module_names(&self) -> Vec<String> {
+        self.compiler.module_names()
+    }
+
+    fn remove_defs(&mut self, names: &[String]) {
+        self.compiler.remove_defs(names)
+    }
 }
 
 impl DummyVM {
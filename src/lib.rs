@@ -1,4 +1,6 @@
 extern crate erg_common;
 extern crate erg_compiler;
 mod dummy;
+pub mod scaffold;
 pub use dummy::DummyVM;
+pub use scaffold::Scaffold;
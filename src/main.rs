@@ -1,35 +1,84 @@
 extern crate erg;
 extern crate erg_compiler;
+extern crate erg_linter;
 extern crate erg_parser;
 
+use erg_common::cancel;
+use erg_common::completion;
 use erg_common::config::{ErgConfig, ErgMode::*};
+use erg_common::deterministic;
+use erg_common::exit_code;
+use erg_common::profile::PROFILER;
 use erg_common::spawn::exec_new_thread;
+use erg_common::timing::TIMINGS;
 use erg_common::traits::{ExitStatus, Runnable};
 
 use erg_parser::build_ast::ASTBuilder;
+use erg_parser::format::Formatter;
 use erg_parser::lex::LexerRunner;
 use erg_parser::ParserRunner;
 
-use erg_compiler::build_hir::HIRBuilder;
+use erg_compiler::batch::BatchCompiler;
+use erg_compiler::bench::BenchRunner;
+use erg_compiler::check::PackageChecker;
+use erg_compiler::coverage::CoverageReporter;
+use erg_compiler::doc::DocGenerator;
+use erg_compiler::emit::Emitter;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::minimize::Minimizer;
+use erg_compiler::test_runner::TestRunner;
 use erg_compiler::transpile::Transpiler;
 use erg_compiler::ty::deserialize::Deserializer;
 use erg_compiler::Compiler;
 
-use erg::DummyVM;
+use erg_linter::{FixRunner, Lint, LintRunner};
+
+use erg::{DummyVM, Scaffold};
 
 fn run() {
     let cfg = ErgConfig::parse();
+    let timings = cfg.timings;
+    let profile_self = cfg.profile_self;
+    let mode = cfg.mode;
+    if cfg.deterministic {
+        deterministic::enable();
+    }
+    // in the REPL, the watch mode and the language server, Ctrl-C is handled as usual
+    let is_repl = cfg.input.is_repl() && cfg.targets.is_empty();
+    if !is_repl && !cfg.watch && mode != LanguageServer {
+        cancel::cancel_on_ctrl_c(&cfg.cancel);
+    }
+    if cfg.memory_stats {
+        TIMINGS.enable_memory_stats();
+    } else if timings {
+        TIMINGS.enable();
+    }
+    if profile_self {
+        PROFILER.enable();
+    }
     let stat = match cfg.mode {
+        _ if !cfg.emit.is_empty() => Emitter::run(cfg),
         Lex => LexerRunner::run(cfg),
         Parse => ParserRunner::run(cfg),
         Desugar => ASTBuilder::run(cfg),
         TypeCheck => ASTLowerer::run(cfg),
-        FullCheck => HIRBuilder::run(cfg),
+        FullCheck => PackageChecker::run(cfg),
+        Format => Formatter::run(cfg),
+        Lint => LintRunner::run(cfg),
+        Fix => FixRunner::run(cfg),
+        Doc => DocGenerator::run(cfg),
+        Test => TestRunner::run(cfg),
+        Bench => BenchRunner::run(cfg),
+        Minimize => Minimizer::run(cfg),
+        Coverage => CoverageReporter::run(cfg),
+        Compile if !cfg.targets.is_empty() => BatchCompiler::run(cfg),
         Compile => Compiler::run(cfg),
         Transpile => Transpiler::run(cfg),
+        Execute if cfg.watch => DummyVM::watch(cfg),
         Execute => DummyVM::run(cfg),
         Read => Deserializer::run(cfg),
+        Completions => completion::run(&cfg, &Lint::ALL.map(|lint| lint.name())),
+        New | Init => Scaffold::run(cfg),
         LanguageServer => {
             #[cfg(feature = "els")]
             {
@@ -45,7 +94,13 @@ fn run() {
             }
         }
     };
-    std::process::exit(stat.code);
+    if timings {
+        TIMINGS.report();
+    }
+    if profile_self {
+        PROFILER.report();
+    }
+    std::process::exit(exit_code::of_status(&stat, mode));
 }
 
 fn main() {
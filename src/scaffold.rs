@@ -0,0 +1,229 @@
+//! Creates a new package (`erg new`, `erg init`).
+//!
+//! The layout is the one that `erg check`, `erg test`, etc. expect (see `doc/EN/tools/new.md`).
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use erg_common::config::{ErgConfig, ErgMode, PackageTemplate};
+use erg_common::switch_lang;
+use erg_common::traits::ExitStatus;
+
+const GITIGNORE: &str = "\
+/build/
+__pycache__/
+*.pyc
+";
+
+const HELLO: &str = "\
+# Returns the greeting for `name`.
+.greet(name: Str): Str = \"Hello, \" + name + \"!\"
+";
+
+const APP_MAIN: &str = "\
+hello = import \"hello\"
+
+print! hello.greet \"world\"
+";
+
+const APP_TEST: &str = "\
+hello = import \"hello\"
+
+test_greet() =
+    assert_eq hello.greet(\"world\"), \"Hello, world!\"
+";
+
+const LIB_TEST: &str = "\
+lib = import \"lib\"
+
+test_greet() =
+    assert_eq lib.greet(\"world\"), \"Hello, world!\"
+";
+
+const PY_MODULE: &str = "\
+def greet(name):
+    return f\"Hello, {name}!\"
+";
+
+const PY_DECL: &str = "\
+# The types of `hello.py`
+.greet: (name: Str) -> Str
+";
+
+const PY_LIB: &str = "\
+hello = pyimport \"hello\"
+
+# Returns the greeting for `name` (implemented in `hello.py`).
+.greet(name: Str): Str = hello.greet name
+";
+
+/// Package names are used as the module names.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[derive(Debug)]
+pub struct Scaffold {
+    pub root: PathBuf,
+    pub name: String,
+    pub template: PackageTemplate,
+}
+
+impl Scaffold {
+    pub fn new(root: PathBuf, template: PackageTemplate) -> Self {
+        let name = root
+            .canonicalize()
+            .unwrap_or_else(|_| root.clone())
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().to_string());
+        Self {
+            root,
+            name,
+            template,
+        }
+    }
+
+    fn manifest(&self) -> String {
+        let ty = match self.template {
+            PackageTemplate::App => "app",
+            PackageTemplate::Lib | PackageTemplate::Py => "lib",
+        };
+        format!(
+            "\
+name = \"{}\"
+version = \"0.1.0\"
+description = \"\"
+type = \"{ty}\" # \"app\" or \"lib\"
+license = \"\" # e.g. \"MIT\", \"Apache-2.0\"
+dependencies = {{:}}
+",
+            self.name
+        )
+    }
+
+    /// The files of the package (relative to the root) and their contents.
+    pub fn files(&self) -> Vec<(PathBuf, String)> {
+        let mut files = vec![
+            (PathBuf::from("package.er"), self.manifest()),
+            (PathBuf::from(".gitignore"), GITIGNORE.to_string()),
+        ];
+        let src = match self.template {
+            PackageTemplate::App => vec![
+                ("main.er", APP_MAIN),
+                ("hello.er", HELLO),
+                ("hello.test.er", APP_TEST),
+            ],
+            PackageTemplate::Lib => vec![("lib.er", HELLO), ("lib.test.er", LIB_TEST)],
+            PackageTemplate::Py => vec![
+                ("hello.py", PY_MODULE),
+                ("hello.d.er", PY_DECL),
+                ("lib.er", PY_LIB),
+                ("lib.test.er", LIB_TEST),
+            ],
+        };
+        files.extend(
+            src.into_iter()
+                .map(|(path, content)| (Path::new("src").join(path), content.to_string())),
+        );
+        files
+    }
+
+    /// Writes the files of the package. Existing files (e.g. `.gitignore` of `erg init`) are not overwritten.
+    /// Returns the created files.
+    pub fn create(&self) -> io::Result<Vec<PathBuf>> {
+        let mut created = vec![];
+        for (path, content) in self.files() {
+            let path = self.root.join(path);
+            if path.exists() {
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, content)?;
+            created.push(path);
+        }
+        Ok(created)
+    }
+
+    /// `erg new <path>` creates the package in a new directory,
+    /// and `erg init [path]` creates the package in an existing directory (the current directory by default).
+    pub fn run(cfg: ErgConfig) -> ExitStatus {
+        let root = match (cfg.mode, cfg.targets.first()) {
+            (_, Some(path)) => path.clone(),
+            (ErgMode::Init, None) => PathBuf::from("."),
+            _ => {
+                eprintln!(
+                    "{}",
+                    switch_lang!(
+                        "japanese" => "パッケージのパスを指定してください(`erg new <path>`)",
+                        "simplified_chinese" => "请指定包的路径(`erg new <path>`)",
+                        "traditional_chinese" => "請指定套件的路徑(`erg new <path>`)",
+                        "english" => "please give the path of the package (`erg new <path>`)",
+                    )
+                );
+                return ExitStatus::ERR1;
+            }
+        };
+        if cfg.mode == ErgMode::New
+            && root
+                .read_dir()
+                .is_ok_and(|mut entries| entries.next().is_some())
+        {
+            eprintln!(
+                "{}",
+                switch_lang!(
+                    "japanese" => format!("{}は空ではありません(既存のディレクトリには`erg init`を使用してください)", root.display()),
+                    "simplified_chinese" => format!("{}不是空的(对于已有的目录请使用`erg init`)", root.display()),
+                    "traditional_chinese" => format!("{}不是空的(對於已有的目錄請使用`erg init`)", root.display()),
+                    "english" => format!("{} is not empty (use `erg init` for an existing directory)", root.display()),
+                )
+            );
+            return ExitStatus::ERR1;
+        }
+        if root.join("package.er").exists() {
+            eprintln!(
+                "{}",
+                switch_lang!(
+                    "japanese" => format!("{}は既にパッケージです", root.display()),
+                    "simplified_chinese" => format!("{}已经是一个包", root.display()),
+                    "traditional_chinese" => format!("{}已經是一個套件", root.display()),
+                    "english" => format!("{} is already a package", root.display()),
+                )
+            );
+            return ExitStatus::ERR1;
+        }
+        let scaffold = Self::new(root, cfg.package_template);
+        if !is_valid_name(&scaffold.name) {
+            eprintln!(
+                "{}",
+                switch_lang!(
+                    "japanese" => format!("`{}`はパッケージ名として使用できません(英数字, `_`, `-`のみ使用可能で、数字から始めることはできません)", scaffold.name),
+                    "simplified_chinese" => format!("`{}`不能用作包名(只能使用字母、数字、`_`和`-`，且不能以数字开头)", scaffold.name),
+                    "traditional_chinese" => format!("`{}`不能用作套件名稱(只能使用字母、數字、`_`和`-`，且不能以數字開頭)", scaffold.name),
+                    "english" => format!("`{}` cannot be used as a package name (only alphanumerics, `_` and `-` are allowed, and it cannot start with a digit)", scaffold.name),
+                )
+            );
+            return ExitStatus::ERR1;
+        }
+        match scaffold.create() {
+            Ok(created) => {
+                if !cfg.quiet {
+                    for path in created.iter() {
+                        println!("created: {}", path.display());
+                    }
+                    println!("created {} package `{}`", scaffold.template, scaffold.name);
+                }
+                ExitStatus::OK
+            }
+            Err(err) => {
+                eprintln!("{}: {err}", scaffold.root.display());
+                ExitStatus::ERR1
+            }
+        }
+    }
+}
@@ -5,13 +5,14 @@ use erg_common::config::ErgConfig;
 use erg_common::consts::DEBUG_MODE;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::io::{DummyStdin, Input, Output};
-use erg_common::python_util::PythonVersion;
+use erg_common::python_util::{exec_py, PythonVersion};
 use erg_common::spawn::exec_new_thread;
 use erg_common::style::{colors::DEBUG_MAIN, RESET};
 use erg_common::traits::{ExitStatus, Runnable, Stream};
 
 use erg_compiler::error::CompileErrors;
-use erg_compiler::Compiler;
+use erg_compiler::golden::compare_file;
+use erg_compiler::{Compiler, Transpiler};
 
 use erg::DummyVM;
 
@@ -192,6 +193,56 @@ pub(crate) fn expect_failure(
     }
 }
 
+/// Transpiles the file into a Python script and executes it.
+pub(crate) fn expect_transpile_success(file_path: &'static str) -> Result<(), ()> {
+    match exec_new_thread(move || _exec_transpiled(file_path), file_path) {
+        Ok(stat) if stat.succeed() => Ok(()),
+        Ok(stat) => {
+            println!(
+                "err[{file_path}]: the transpiled script should succeed, but end with {}",
+                stat.code
+            );
+            Err(())
+        }
+        Err(errs) => {
+            if DEBUG_MODE {
+                errs.write_all_stderr();
+            }
+            println!("err[{file_path}]: should succeed, but got transpile errors");
+            Err(())
+        }
+    }
+}
+
+/// Compares the code objects compiled by Erg with the ones CPython compiles from the transpiled script.
+/// The runtime conversions (e.g. `Nat(1)`) are only inserted by the transpiler, so they are ignored.
+pub(crate) fn expect_golden(file_path: &'static str) -> Result<(), ()> {
+    let res = exec_new_thread(
+        move || compare_file(&set_cfg(ErgConfig::default()), &PathBuf::from(file_path)),
+        file_path,
+    );
+    match res {
+        Ok(divergences) => {
+            let divergences = divergences
+                .into_iter()
+                .filter(|div| div.field != "conversions")
+                .collect::<Vec<_>>();
+            if divergences.is_empty() {
+                Ok(())
+            } else {
+                for div in divergences {
+                    println!("err[{file_path}]: {div}");
+                }
+                Err(())
+            }
+        }
+        Err(err) => {
+            println!("err[{file_path}]: cannot compare the code objects: {err}");
+            Err(())
+        }
+    }
+}
+
 fn set_cfg(mut cfg: ErgConfig) -> ErgConfig {
     cfg.py_command = if cfg!(windows) {
         Some("python")
@@ -243,6 +294,25 @@ pub fn _exec_compiler(file_path: &'static str) -> Result<ExitStatus, CompileErro
     compiler.exec()
 }
 
+fn _exec_transpiled(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
+    println!("{DEBUG_MAIN}[test] exec transpiled: {file_path}{RESET}");
+    let cfg = ErgConfig::with_main_path(PathBuf::from(file_path));
+    let mut transpiler = Transpiler::new(set_cfg(cfg));
+    let src = transpiler.cfg.input.read();
+    let script = transpiler
+        .transpile(src, "exec")
+        .map_err(|eart| eart.errors)?;
+    let py = std::env::temp_dir().join(format!(
+        "erg_transpiled_{}_{}.py",
+        std::process::id(),
+        transpiler.cfg.input.filename()
+    ));
+    std::fs::write(&py, script.object.code).unwrap();
+    let code = exec_py(&py.to_string_lossy());
+    let _ = std::fs::remove_file(&py);
+    Ok(ExitStatus::new(code.unwrap_or(1), 0, 0))
+}
+
 pub(crate) fn exec_file(file_path: &'static str) -> Result<ExitStatus, CompileErrors> {
     exec_new_thread(move || _exec_file(file_path), file_path)
 }
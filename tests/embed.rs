@@ -3,6 +3,7 @@ use erg_common::config::ErgConfig;
 use erg_common::error::MultiErrorDisplay;
 use erg_compiler::artifact::Buildable;
 use erg_compiler::module::SharedCompilerResource;
+use erg_compiler::sandbox::{Isolation, Sandbox, SandboxLimits, SandboxOutcome};
 use erg_compiler::HIRBuilder;
 use erg_compiler::Transpiler;
 
@@ -41,3 +42,18 @@ fn test_builder() -> Result<(), ()> {
     let _res = checker.build(src, "exec");
     Ok(())
 }
+
+#[test]
+fn test_sandbox_process() {
+    let isolation = Isolation::Process(env!("CARGO_BIN_EXE_erg").into());
+    let sandbox = Sandbox::new(ErgConfig::default(), SandboxLimits::default(), isolation);
+    let SandboxOutcome::Checked { errors, warns } = sandbox.check("x: Int = \"a\"\nprint! x")
+    else {
+        panic!()
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("\"code\":\"TypeError\""));
+    assert!(warns.is_empty());
+    let src = format!("print! {}1{}", "(".repeat(5000), ")".repeat(5000));
+    assert!(!sandbox.check(&src).is_crashed());
+}
@@ -0,0 +1,11 @@
+def old(x):
+    return x
+
+
+def new(x):
+    return x + 1
+
+
+class C:
+    def f(self):
+        return 1
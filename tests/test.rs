@@ -1,5 +1,10 @@
 mod common;
-use common::{expect_compile_success, expect_end_with, expect_failure, expect_success};
+use common::{
+    expect_compile_success, expect_end_with, expect_failure, expect_golden, expect_success,
+    expect_transpile_success,
+};
+use erg::Scaffold;
+use erg_common::config::PackageTemplate;
 use erg_common::python_util::{module_exists, opt_which_python};
 
 #[test]
@@ -12,6 +17,11 @@ fn exec_advanced_type_spec() -> Result<(), ()> {
     expect_success("tests/should_ok/advanced_type_spec.er", 5)
 }
 
+#[test]
+fn exec_argv() -> Result<(), ()> {
+    expect_success("tests/should_ok/argv.er", 0)
+}
+
 #[test]
 fn exec_array() -> Result<(), ()> {
     expect_success("tests/should_ok/array.er", 0)
@@ -22,6 +32,16 @@ fn exec_array_member() -> Result<(), ()> {
     expect_success("tests/should_ok/array_member.er", 0)
 }
 
+#[test]
+fn exec_available() -> Result<(), ()> {
+    expect_success("tests/should_ok/available.er", 0)
+}
+
+#[test]
+fn exec_cache() -> Result<(), ()> {
+    expect_success("tests/should_ok/cache.er", 0)
+}
+
 #[test]
 fn exec_class() -> Result<(), ()> {
     expect_success("examples/class.er", 0)
@@ -34,7 +54,7 @@ fn exec_class_test() -> Result<(), ()> {
 
 #[test]
 fn exec_class_attr() -> Result<(), ()> {
-    expect_success("tests/should_ok/class_attr.er", 2)
+    expect_success("tests/should_ok/class_attr.er", 1)
 }
 
 #[test]
@@ -47,6 +67,16 @@ fn exec_comment() -> Result<(), ()> {
     expect_success("tests/should_ok/comment.er", 0)
 }
 
+#[test]
+fn exec_comprehension() -> Result<(), ()> {
+    expect_success("tests/should_ok/comprehension.er", 0)
+}
+
+#[test]
+fn exec_comptime_eval() -> Result<(), ()> {
+    expect_success("tests/should_ok/comptime_eval.er", 0)
+}
+
 #[test]
 fn exec_control() -> Result<(), ()> {
     expect_success("examples/control.er", 2)
@@ -57,16 +87,31 @@ fn exec_control_expr() -> Result<(), ()> {
     expect_success("tests/should_ok/control_expr.er", 3)
 }
 
+#[test]
+fn exec_dead_def() -> Result<(), ()> {
+    expect_success("tests/should_ok/dead_def.er", 5)
+}
+
 #[test]
 fn exec_decimal() -> Result<(), ()> {
     expect_success("tests/should_ok/decimal.er", 0)
 }
 
+#[test]
+fn exec_decorator() -> Result<(), ()> {
+    expect_success("tests/should_ok/decorator.er", 0)
+}
+
 #[test]
 fn exec_default_param() -> Result<(), ()> {
     expect_success("tests/should_ok/default_param.er", 0)
 }
 
+#[test]
+fn exec_definite_assign() -> Result<(), ()> {
+    expect_success("tests/should_ok/definite_assign.er", 0)
+}
+
 #[test]
 fn exec_dependent() -> Result<(), ()> {
     expect_success("tests/should_ok/dependent.er", 0)
@@ -77,6 +122,21 @@ fn exec_dict() -> Result<(), ()> {
     expect_success("examples/dict.er", 0)
 }
 
+#[test]
+fn exec_division() -> Result<(), ()> {
+    expect_success("tests/should_ok/division.er", 2)
+}
+
+#[test]
+fn exec_early_return() -> Result<(), ()> {
+    expect_success("tests/should_ok/early_return.er", 0)
+}
+
+#[test]
+fn exec_early_return_error() -> Result<(), ()> {
+    expect_success("tests/should_ok/early_return_error.er", 0)
+}
+
 #[test]
 fn exec_external() -> Result<(), ()> {
     let py_command = opt_which_python().unwrap();
@@ -107,6 +167,11 @@ fn exec_if() -> Result<(), ()> {
     expect_success("tests/should_ok/if.er", 0)
 }
 
+#[test]
+fn exec_if_chain() -> Result<(), ()> {
+    expect_success("tests/should_ok/if_chain.er", 1)
+}
+
 #[test]
 fn exec_impl() -> Result<(), ()> {
     expect_success("examples/impl.er", 0)
@@ -128,6 +193,11 @@ fn exec_index() -> Result<(), ()> {
     expect_success("tests/should_ok/index.er", 0)
 }
 
+#[test]
+fn exec_index_bounds() -> Result<(), ()> {
+    expect_success("tests/should_ok/index_bounds.er", 1)
+}
+
 #[test]
 fn exec_inherit() -> Result<(), ()> {
     expect_success("tests/should_ok/inherit.er", 0)
@@ -155,7 +225,12 @@ fn exec_interpolation() -> Result<(), ()> {
 
 #[test]
 fn exec_long() -> Result<(), ()> {
-    expect_success("tests/should_ok/long.er", 257)
+    expect_success("tests/should_ok/long.er", 256)
+}
+
+#[test]
+fn exec_macro() -> Result<(), ()> {
+    expect_success("tests/should_ok/macro.er", 0)
 }
 
 #[test]
@@ -173,6 +248,11 @@ fn exec_map() -> Result<(), ()> {
     expect_success("tests/should_ok/map.er", 0)
 }
 
+#[test]
+fn exec_match_guard() -> Result<(), ()> {
+    expect_success("tests/should_ok/match_guard.er", 0)
+}
+
 #[test]
 fn exec_mut() -> Result<(), ()> {
     expect_success("examples/mut.er", 0)
@@ -190,7 +270,7 @@ fn exec_mut_dict() -> Result<(), ()> {
 
 #[test]
 fn exec_nested() -> Result<(), ()> {
-    expect_success("tests/should_ok/nested.er", 3)
+    expect_success("tests/should_ok/nested.er", 0)
 }
 
 #[test]
@@ -198,11 +278,21 @@ fn exec_never() -> Result<(), ()> {
     expect_success("tests/should_ok/never.er", 0)
 }
 
+#[test]
+fn exec_num_literal() -> Result<(), ()> {
+    expect_success("tests/should_ok/num_literal.er", 0)
+}
+
 #[test]
 fn exec_operators() -> Result<(), ()> {
     expect_success("tests/should_ok/operators.er", 0)
 }
 
+#[test]
+fn exec_option() -> Result<(), ()> {
+    expect_success("tests/should_ok/option.er", 0)
+}
+
 #[test]
 fn exec_patch() -> Result<(), ()> {
     expect_success("examples/patch.er", 0)
@@ -213,6 +303,16 @@ fn exec_pattern() -> Result<(), ()> {
     expect_success("tests/should_ok/pattern.er", 0)
 }
 
+#[test]
+fn exec_placeholder() -> Result<(), ()> {
+    expect_success("tests/should_ok/placeholder.er", 0)
+}
+
+#[test]
+fn exec_pipeline() -> Result<(), ()> {
+    expect_success("tests/should_ok/pipeline.er", 0)
+}
+
 #[test]
 fn exec_pyimport_test() -> Result<(), ()> {
     expect_success("tests/should_ok/pyimport.er", 2)
@@ -245,7 +345,36 @@ fn exec_refinement() -> Result<(), ()> {
 
 #[test]
 fn exec_return() -> Result<(), ()> {
-    expect_success("tests/should_ok/return.er", 0)
+    expect_success("tests/should_ok/return.er", 1)
+}
+
+#[test]
+fn exec_scaffold() {
+    /// Removes the scaffold directory even if an assertion fails
+    struct TempDir(std::path::PathBuf);
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+    let tmp = TempDir(std::env::temp_dir().join(format!("erg_scaffold_{}", std::process::id())));
+    for template in [
+        PackageTemplate::App,
+        PackageTemplate::Lib,
+        PackageTemplate::Py,
+    ] {
+        let root = tmp.0.join(format!("hello_{template}"));
+        let scaffold = Scaffold::new(root.clone(), template);
+        assert_eq!(scaffold.name, format!("hello_{template}"));
+        let created = scaffold.create().unwrap();
+        let again = scaffold.create().unwrap();
+        let manifest = std::fs::read_to_string(root.join("package.er")).unwrap();
+        assert_eq!(created.len(), scaffold.files().len());
+        assert!(created.contains(&root.join(".gitignore")));
+        assert!(manifest.contains(&format!("name = \"hello_{template}\"")));
+        // existing files are not overwritten
+        assert!(again.is_empty());
+    }
 }
 
 #[test]
@@ -253,6 +382,16 @@ fn exec_self_type() -> Result<(), ()> {
     expect_success("tests/should_ok/self_type.er", 0)
 }
 
+#[test]
+fn exec_slice() -> Result<(), ()> {
+    expect_success("tests/should_ok/slice.er", 0)
+}
+
+#[test]
+fn exec_str_literal() -> Result<(), ()> {
+    expect_success("tests/should_ok/str_literal.er", 0)
+}
+
 #[test]
 fn exec_structural_example() -> Result<(), ()> {
     expect_success("examples/structural.er", 0)
@@ -293,6 +432,11 @@ fn exec_unused_import() -> Result<(), ()> {
     expect_success("tests/should_ok/many_import/unused_import.er", 2)
 }
 
+#[test]
+fn exec_unused_param() -> Result<(), ()> {
+    expect_success("tests/should_ok/unused_param.er", 4)
+}
+
 #[test]
 fn exec_use_py() -> Result<(), ()> {
     expect_success("examples/use_py.er", 0)
@@ -313,9 +457,14 @@ fn exec_addition_err() -> Result<(), ()> {
     expect_failure("tests/should_err/addition.er", 3, 9)
 }
 
+#[test]
+fn exec_aliasing() -> Result<(), ()> {
+    expect_failure("tests/should_err/aliasing.er", 0, 2)
+}
+
 #[test]
 fn exec_args() -> Result<(), ()> {
-    expect_failure("tests/should_err/args.er", 0, 16)
+    expect_failure("tests/should_err/args.er", 0, 17)
 }
 
 #[test]
@@ -338,6 +487,11 @@ fn exec_assert_cast() -> Result<(), ()> {
     expect_failure("examples/assert_cast.er", 0, 3)
 }
 
+#[test]
+fn exec_available_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/available.er", 0, 3)
+}
+
 #[test]
 fn exec_class_attr_err() -> Result<(), ()> {
     expect_failure("tests/should_err/class_attr.er", 1, 1)
@@ -348,11 +502,46 @@ fn exec_collection_err() -> Result<(), ()> {
     expect_failure("tests/should_err/collection.er", 0, 4)
 }
 
+#[test]
+fn exec_comprehension_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/comprehension.er", 0, 3)
+}
+
+#[test]
+fn exec_comptime_eval_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/comptime_eval.er", 0, 4)
+}
+
+#[test]
+fn exec_decorator_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/decorator.er", 0, 3)
+}
+
+#[test]
+fn exec_definite_assign_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/definite_assign.er", 0, 5)
+}
+
 #[test]
 fn exec_dependent_err() -> Result<(), ()> {
     expect_failure("tests/should_err/dependent.er", 0, 5)
 }
 
+#[test]
+fn exec_early_return_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/early_return.er", 0, 3)
+}
+
+#[test]
+fn exec_early_return_error_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/early_return_error.er", 0, 2)
+}
+
+#[test]
+fn exec_effect() -> Result<(), ()> {
+    expect_failure("tests/should_err/effect.er", 0, 7)
+}
+
 #[test]
 fn exec_err_import() -> Result<(), ()> {
     expect_failure("tests/should_err/err_import.er", 0, 9)
@@ -379,11 +568,21 @@ fn exec_import_cyclic_err() -> Result<(), ()> {
     expect_failure("tests/should_err/cyclic/import.er", 0, 1)
 }
 
+#[test]
+fn exec_index_bounds_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/index_bounds.er", 0, 4)
+}
+
 #[test]
 fn exec_infer_union_array() -> Result<(), ()> {
     expect_failure("tests/should_err/infer_union_array.er", 2, 1)
 }
 
+#[test]
+fn exec_interpolation_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/interpolation.er", 0, 4)
+}
+
 #[test]
 fn exec_invalid_interpol() -> Result<(), ()> {
     expect_failure("tests/should_err/invalid_interpol.er", 0, 2)
@@ -394,6 +593,16 @@ fn exec_invalid_param() -> Result<(), ()> {
     expect_failure("tests/should_err/invalid_param.er", 0, 3)
 }
 
+#[test]
+fn exec_macro_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/macro.er", 3, 8)
+}
+
+#[test]
+fn exec_match_guard_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/match_guard.er", 1, 3)
+}
+
 #[test]
 fn exec_move_check() -> Result<(), ()> {
     expect_failure("examples/move_check.er", 1, 1)
@@ -418,6 +627,11 @@ fn exec_side_effect() -> Result<(), ()> {
     expect_failure("examples/side_effect.er", 5, 4)
 }
 
+#[test]
+fn exec_slice_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/slice.er", 0, 3)
+}
+
 #[test]
 fn exec_structural_err() -> Result<(), ()> {
     expect_failure("tests/should_err/structural.er", 1, 9)
@@ -463,11 +677,31 @@ fn exec_mut_dict_err() -> Result<(), ()> {
     expect_failure("tests/should_err/mut_dict.er", 0, 3)
 }
 
+#[test]
+fn exec_overflow_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/overflow.er", 0, 6)
+}
+
+#[test]
+fn exec_possibly_none_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/possibly_none.er", 0, 4)
+}
+
 #[test]
 fn exec_quantified_err() -> Result<(), ()> {
     expect_failure("tests/should_err/quantified.er", 0, 3)
 }
 
+#[test]
+fn exec_recover_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/recover.er", 0, 4)
+}
+
+#[test]
+fn exec_recursive_return_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/recursive_return.er", 1, 1)
+}
+
 #[test]
 fn exec_refinement_err() -> Result<(), ()> {
     expect_failure("tests/should_err/refinement.er", 0, 8)
@@ -475,10 +709,65 @@ fn exec_refinement_err() -> Result<(), ()> {
 
 #[test]
 fn exec_var_args_err() -> Result<(), ()> {
-    expect_failure("tests/should_err/var_args.er", 0, 3)
+    expect_failure("tests/should_err/var_args.er", 0, 7)
 }
 
 #[test]
 fn exec_visibility() -> Result<(), ()> {
     expect_failure("tests/should_err/visibility.er", 2, 7)
 }
+
+#[test]
+fn golden_addition() -> Result<(), ()> {
+    expect_golden("tests/should_ok/addition.er")
+}
+
+#[test]
+fn golden_decimal() -> Result<(), ()> {
+    expect_golden("tests/should_ok/decimal.er")
+}
+
+#[test]
+fn golden_if() -> Result<(), ()> {
+    expect_golden("tests/should_ok/if.er")
+}
+
+#[test]
+fn golden_int() -> Result<(), ()> {
+    expect_golden("tests/should_ok/int.er")
+}
+
+#[test]
+fn golden_map() -> Result<(), ()> {
+    expect_golden("tests/should_ok/map.er")
+}
+
+#[test]
+fn golden_operators() -> Result<(), ()> {
+    expect_golden("tests/should_ok/operators.er")
+}
+
+#[test]
+fn golden_pipeline() -> Result<(), ()> {
+    expect_golden("tests/should_ok/pipeline.er")
+}
+
+#[test]
+fn golden_structural() -> Result<(), ()> {
+    expect_golden("tests/should_ok/structural.er")
+}
+
+#[test]
+fn transpile_early_return() -> Result<(), ()> {
+    expect_transpile_success("tests/should_ok/early_return.er")
+}
+
+#[test]
+fn transpile_early_return_error() -> Result<(), ()> {
+    expect_transpile_success("tests/should_ok/early_return_error.er")
+}
+
+#[test]
+fn transpile_match_guard() -> Result<(), ()> {
+    expect_transpile_success("tests/should_ok/match_guard.er")
+}